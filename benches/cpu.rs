@@ -0,0 +1,146 @@
+/*
+ * Filename: /benches/cpu.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 18:31:27
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! interpreter benchmarks, run with 'cargo bench'.
+//!
+//! the 'debugger' variants keep an (unreachable) exec breakpoint installed, which forces the checked interpreter loop,
+//! to compare against the fast path taken when nothing is attached.
+//! the 'generic_bus' variants run a Cpu<DefaultBus> instead of the default Cpu<Box<dyn Bus>>.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rv6502emu::bus::{Bus, DefaultBus};
+use rv6502emu::cpu::debugger::{BreakpointType, Debugger};
+use rv6502emu::cpu::{Cpu, ResetKind};
+use rv6502emu::memory;
+use std::path::Path;
+
+/// Klaus functional test binary (from the 6502_65C02_functional_tests submodule).
+const KLAUS_BIN: &str = "./tests/6502_65C02_functional_tests/bin_files/6502_functional_test.bin";
+
+/// Klaus functional test success trap.
+const KLAUS_SUCCESS_PC: u16 = 0x3469;
+
+/**
+ * a debugger with an exec breakpoint never reached, to force the checked loop.
+ */
+fn watching_debugger<B: Bus>(c: &mut Cpu<B>) -> Debugger {
+    let mut d = Debugger::new(false);
+    d.add_breakpoint(c, 0xfff0, BreakpointType::EXEC, None)
+        .unwrap();
+    d
+}
+
+/**
+ * loads a loop doing sed, clc, then adc/sbc in decimal mode on 256 values, forever.
+ */
+fn load_bcd_loop<B: Bus>(cpu: &mut Cpu<B>) {
+    // $0400: sed, clc
+    // $0402: ldx #$00
+    // $0404: txa, adc #$19, sbc #$07, sta $10, dex, bne $0404
+    // $040e: jmp $0402
+    let prg = [
+        0xf8, 0x18, 0xa2, 0x00, 0x8a, 0x69, 0x19, 0xe9, 0x07, 0x85, 0x10, 0xca, 0xd0, 0xf6, 0x4c,
+        0x02, 0x04,
+    ];
+    for (i, b) in prg.iter().enumerate() {
+        cpu.bus.get_memory().write_byte(0x400 + i, *b).unwrap();
+    }
+}
+
+/**
+ * runs 1M instructions of the bcd loop.
+ */
+fn bcd_loop(c: &mut Criterion) {
+    let mut g = c.benchmark_group("bcd_loop");
+    for watched in [false, true] {
+        let mut cpu = Cpu::new_default(None);
+        load_bcd_loop(&mut cpu);
+        let mut d = if watched {
+            Some(watching_debugger(&mut cpu))
+        } else {
+            None
+        };
+        g.bench_function(if watched { "debugger" } else { "fast" }, |b| {
+            b.iter(|| {
+                cpu.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+                black_box(cpu.run_until(d.as_mut(), 0, 1_000_000).unwrap())
+            })
+        });
+    }
+
+    // same, monomorphized on the bus
+    let mut cpu = Cpu::new(DefaultBus::new(memory::new_default()), None, None);
+    load_bcd_loop(&mut cpu);
+    g.bench_function("generic_bus", |b| {
+        b.iter(|| {
+            cpu.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+            black_box(cpu.run_until(None, 0, 1_000_000).unwrap())
+        })
+    });
+    g.finish();
+}
+
+/**
+ * the whole Klaus functional test, from start to the success trap.
+ */
+fn klaus_functional(c: &mut Criterion) {
+    if !Path::new(KLAUS_BIN).exists() {
+        println!(
+            "{} not found (submodule not checked out ?), skipped.",
+            KLAUS_BIN
+        );
+        return;
+    }
+    let mut g = c.benchmark_group("klaus_functional");
+    g.sample_size(10);
+    for watched in [false, true] {
+        let mut cpu = Cpu::new_default(None);
+        cpu.bus.get_memory().load(KLAUS_BIN, 0).unwrap();
+        let mut d = if watched {
+            Some(watching_debugger(&mut cpu))
+        } else {
+            None
+        };
+        g.bench_function(if watched { "debugger" } else { "fast" }, |b| {
+            b.iter(|| {
+                // the test traps in a jmp to itself on success, run in slices until there
+                cpu.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+                while cpu.regs.pc != KLAUS_SUCCESS_PC {
+                    cpu.run_until(d.as_mut(), 0, 100_000).unwrap();
+                }
+                black_box(cpu.cycles)
+            })
+        });
+    }
+    g.finish();
+}
+
+criterion_group!(benches, bcd_loop, klaus_functional);
+criterion_main!(benches);
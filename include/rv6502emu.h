@@ -0,0 +1,153 @@
+#ifndef RV6502EMU_H
+#define RV6502EMU_H
+
+/* generated by cbindgen from src/capi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ * error codes returned by the rv6502_* functions, mapped from CpuErrorType.
+ */
+typedef enum rv6502_error {
+  RV6502_OK = 0,
+  RV6502_ERR_MEMORY_READ,
+  RV6502_ERR_MEMORY_WRITE,
+  RV6502_ERR_MEMORY_LOAD,
+  RV6502_ERR_INVALID_OPCODE,
+  RV6502_ERR_RW_BREAKPOINT,
+  RV6502_ERR_DEADLOCK,
+  RV6502_ERR_HALTED,
+  RV6502_ERR_READ_ONLY_MEMORY,
+  RV6502_ERR_GENERIC,
+  /*
+   an NMOS JAM (KIL) opcode has been executed, only rv6502_reset() recovers.
+   */
+  RV6502_ERR_JAMMED,
+  /*
+   a NULL pointer has been passed in.
+   */
+  RV6502_ERR_INVALID_ARGUMENT,
+} rv6502_error;
+
+/*
+ * opaque cpu handle, created by rv6502_new() and freed by rv6502_destroy().
+ */
+typedef struct rv6502 rv6502;
+
+/*
+ * the registers, for rv6502_get_regs() and rv6502_set_regs().
+ */
+typedef struct rv6502_regs {
+  uint8_t a;
+  uint8_t x;
+  uint8_t y;
+  uint8_t s;
+  uint8_t p;
+  uint16_t pc;
+  /*
+   total elapsed cycles, ignored by rv6502_set_regs().
+   */
+  uint64_t cycles;
+} rv6502_regs;
+
+/*
+ * called by the cpu on every memory access (with value pointing to the byte being written, or to be filled on reads).
+ *
+ * returns true if the access has been handled by the host (i.e. memory-mapped i/o), false to let it through to ram.
+ */
+typedef bool (*rv6502_rw_callback)(void *user_data, uint16_t address, uint8_t *value, bool write);
+
+/*
+ * creates a MOS6502 with memory_size bytes of zero filled ram, to be freed with rv6502_destroy().
+ */
+struct rv6502 *rv6502_new(uintptr_t memory_size);
+
+/*
+ * frees a handle returned by rv6502_new(), NULL is ignored.
+ *
+ * # Safety
+ *
+ * h must be NULL or a handle from rv6502_new() not already destroyed.
+ */
+void rv6502_destroy(struct rv6502 *h);
+
+/*
+ * power-on reset, starting at address or at the reset vector if address is negative.
+ *
+ * # Safety
+ *
+ * h must be a valid handle.
+ */
+enum rv6502_error rv6502_reset(struct rv6502 *h, int32_t address);
+
+/*
+ * executes one instruction (or services a pending interrupt).
+ *
+ * # Safety
+ *
+ * h must be a valid handle.
+ */
+enum rv6502_error rv6502_step(struct rv6502 *h);
+
+/*
+ * runs for (at least) the given cycles, the cycles actually consumed are stored in executed if not NULL.
+ *
+ * # Safety
+ *
+ * h must be a valid handle, executed NULL or valid.
+ */
+enum rv6502_error rv6502_run_cycles(struct rv6502 *h,
+                                    uintptr_t cycles,
+                                    uintptr_t *executed);
+
+/*
+ * copies the registers to regs.
+ *
+ * # Safety
+ *
+ * h and regs must be valid.
+ */
+enum rv6502_error rv6502_get_regs(struct rv6502 *h, struct rv6502_regs *regs);
+
+/*
+ * sets the registers from regs (cycles is ignored).
+ *
+ * # Safety
+ *
+ * h and regs must be valid.
+ */
+enum rv6502_error rv6502_set_regs(struct rv6502 *h, const struct rv6502_regs *regs);
+
+/*
+ * reads the ram byte at address into value, bypassing the rw callback.
+ *
+ * # Safety
+ *
+ * h and value must be valid.
+ */
+enum rv6502_error rv6502_read(struct rv6502 *h, uint16_t address, uint8_t *value);
+
+/*
+ * writes value to the ram at address, bypassing the rw callback and the rom protection.
+ *
+ * # Safety
+ *
+ * h must be a valid handle.
+ */
+enum rv6502_error rv6502_write(struct rv6502 *h, uint16_t address, uint8_t value);
+
+/*
+ * installs (or removes, with NULL) the callback which is offered every cpu memory access, user_data is passed back to it.
+ *
+ * # Safety
+ *
+ * h must be a valid handle, user_data must stay valid as long as the callback is installed.
+ */
+enum rv6502_error rv6502_set_rw_callback(struct rv6502 *h,
+                                         rv6502_rw_callback cb,
+                                         void *user_data);
+
+#endif  /* RV6502EMU_H */
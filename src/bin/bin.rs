@@ -30,10 +30,12 @@
 
 use rv6502emu::cpu::debugger::Debugger;
 use rv6502emu::cpu::CpuCallbackContext;
-use rv6502emu::cpu::{Cpu, CpuOperation, CpuType};
+use rv6502emu::cpu::{Cpu, CpuOperation, CpuType, ResetKind};
 
 static mut TEST: i8 = 0;
 
+// the interrupt test pulses the legacy must_trigger_* fields
+#[allow(deprecated)]
 fn test_callback(c: &mut Cpu, cb: CpuCallbackContext) {
     // check final PC for klaus functional test
     unsafe {
@@ -108,7 +110,7 @@ fn decimal_test(c: &mut Cpu, d: Option<&mut Debugger>) {
         .unwrap();
 
     // resets to $200
-    c.reset(Some(0x200)).unwrap();
+    c.reset(Some(0x200), ResetKind::PowerOn).unwrap();
 
     // and run again
     c.run(d, 0).unwrap();
@@ -129,7 +131,7 @@ fn interrupt_test(c: &mut Cpu, d: Option<&mut Debugger>) {
         .unwrap();
 
     // resets to $400
-    c.reset(Some(0x400)).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
     let mut empty_dbg = Debugger::new(false);
     let dbg = d.unwrap_or(&mut empty_dbg);
 
@@ -155,7 +157,7 @@ fn klaus_functional_test(c: &mut Cpu, d: Option<&mut Debugger>) {
         .unwrap();
 
     // resets the cpu (use 0x400 as custom address for the Klaus test) and start execution
-    c.reset(Some(0x400)).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
 
     // run
     c.run(d, 0).unwrap();
@@ -182,7 +184,7 @@ fn klaus_65c02_test(c: &mut Cpu, d: Option<&mut Debugger>) {
         .unwrap();
 
     // resets the cpu
-    c.reset(Some(0x400)).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
 
     // run
     c.run(d, 0).unwrap();
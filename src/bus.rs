@@ -28,25 +28,91 @@
  * SOFTWARE.
  */
 
-use crate::memory::Memory;
+use crate::cpu::cpu_error::{self, CpuError, CpuErrorType};
+use crate::memory::{Memory, MemoryInitPolicy};
+use alloc::{boxed::Box, vec::Vec};
+use core::ops::RangeInclusive;
 
 /**
  * a Bus is connected to the Cpu, and must expose at least a Memory interface.
+ *
+ * buses are Send, so a Cpu can be moved to another thread (i.e. a GUI front-end running the emulation on a worker).
  */
-pub trait Bus {
+pub trait Bus: Send {
     /**
      * gets the emulated memory.
      */
     fn get_memory(&mut self) -> &mut Box<dyn Memory>;
+
+    /**
+     * copies data to memory at address, as Memory::load_from_slice().
+     */
+    fn load_from_slice(&mut self, data: &[u8], address: usize) -> Result<(), CpuError> {
+        self.get_memory().load_from_slice(data, address)
+    }
+
+    /**
+     * returns len bytes starting at address, as Memory::dump_range().
+     */
+    fn dump_range(&mut self, address: usize, len: usize) -> Result<Vec<u8>, CpuError> {
+        self.get_memory().dump_range(address, len)
+    }
+
+    /**
+     * saves len bytes starting at address to the file at path, as Memory::save_to_file().
+     */
+    #[cfg(feature = "std")]
+    fn save_to_file(&mut self, path: &str, address: usize, len: usize) -> Result<(), CpuError> {
+        self.get_memory().save_to_file(path, address, len)
+    }
+
+    /**
+     * reads a byte without side effects, for the debugger: no cpu hooks, watchpoints or device reads are triggered.
+     *
+     * mapped devices are reflected if they implement Device::peek(), the ram below them is returned otherwise.
+     */
+    fn peek(&mut self, address: u16) -> Result<u8, CpuError> {
+        let mut b = [0u8; 1];
+        self.get_memory().read_range(address as usize, &mut b)?;
+        Ok(b[0])
+    }
+
+    /**
+     * writes a byte bypassing the cpu hooks, watchpoints and the rom protection, for the debugger.
+     */
+    fn poke(&mut self, address: u16, v: u8) -> Result<(), CpuError> {
+        self.get_memory().write_byte_privileged(address as usize, v)
+    }
+}
+
+/**
+ * boxed buses (i.e. Box<dyn Bus>, the default for Cpu) are buses too.
+ */
+impl<T: Bus + ?Sized> Bus for Box<T> {
+    #[inline]
+    fn get_memory(&mut self) -> &mut Box<dyn Memory> {
+        (**self).get_memory()
+    }
 }
 
 /**
  * implements the default Bus exposing Memory only.
+ *
+ * use it directly as Cpu<DefaultBus> to avoid the dynamic dispatch on the bus, or through new_default().
  */
-struct DefaultBus {
+pub struct DefaultBus {
     m: Box<dyn Memory>,
 }
 
+impl DefaultBus {
+    /**
+     * creates a new default bus with the given Memory attached.
+     */
+    pub fn new(mem: Box<dyn Memory>) -> DefaultBus {
+        DefaultBus { m: mem }
+    }
+}
+
 impl Bus for DefaultBus {
     #[inline]
     fn get_memory(&mut self) -> &mut Box<dyn Memory> {
@@ -59,6 +125,208 @@ impl Bus for DefaultBus {
  * creates a new default bus with the given Memory attached.
  */
 pub fn new_default(mem: Box<dyn Memory>) -> Box<dyn Bus> {
-    let b = DefaultBus { m: mem };
-    Box::new(b)
+    Box::new(DefaultBus::new(mem))
+}
+
+/**
+ * a device mapped on the bus (i.e. a VIA, a ROM, ...).
+ *
+ * addresses are passed as absolute cpu addresses. must be Send, as the Bus holding it.
+ */
+pub trait Device: Send {
+    /**
+     * reads a byte from the device.
+     */
+    fn read(&mut self, address: u16) -> Result<u8, CpuError>;
+
+    /**
+     * writes a byte to the device.
+     */
+    fn write(&mut self, address: u16, v: u8);
+
+    /**
+     * reads a byte without side effects (i.e. without acknowledging an interrupt or popping a fifo), for Bus::peek().
+     *
+     * the default returns None, the ram below the device is shown instead.
+     */
+    fn peek(&self, _address: u16) -> Option<u8> {
+        None
+    }
+}
+
+/**
+ * a Memory which dispatches accesses to the mapped devices, falling through to ram.
+ */
+struct MappedMemory {
+    ram: Box<dyn Memory>,
+    devices: Vec<(RangeInclusive<u16>, Box<dyn Device>)>,
+}
+
+impl MappedMemory {
+    /**
+     * returns the index of the first device mapping the given address, if any.
+     */
+    fn find_device(&self, address: usize) -> Option<usize> {
+        if address > 0xffff {
+            return None;
+        }
+        self.devices
+            .iter()
+            .position(|(r, _)| r.contains(&(address as u16)))
+    }
+}
+
+impl Memory for MappedMemory {
+    fn read_byte(&mut self, address: usize) -> Result<u8, CpuError> {
+        match self.find_device(address) {
+            Some(idx) => self.devices[idx].1.read(address as u16),
+            None => self.ram.read_byte(address),
+        }
+    }
+
+    fn read_word_le(&mut self, address: usize) -> Result<u16, CpuError> {
+        cpu_error::check_address_boundaries(
+            self.ram.get_size(),
+            address,
+            2,
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+
+        // compose from two byte reads, the word may straddle a mapping boundary
+        let lsb = self.read_byte(address)?;
+        let msb = self.read_byte(address + 1)?;
+        Ok(((msb as u16) << 8) | (lsb as u16))
+    }
+
+    fn write_word_le(&mut self, address: usize, w: u16) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            self.ram.get_size(),
+            address,
+            2,
+            CpuErrorType::MemoryWrite,
+            None,
+        )?;
+        self.write_byte(address, (w & 0xff) as u8)?;
+        self.write_byte(address + 1, (w >> 8) as u8)
+    }
+
+    fn write_byte(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        match self.find_device(address) {
+            Some(idx) => {
+                self.devices[idx].1.write(address as u16, b);
+                Ok(())
+            }
+            None => self.ram.write_byte(address, b),
+        }
+    }
+
+    fn get_size(&self) -> usize {
+        self.ram.get_size()
+    }
+
+    fn load_from_slice(&mut self, data: &[u8], address: usize) -> Result<(), CpuError> {
+        // loads to ram only
+        self.ram.load_from_slice(data, address)
+    }
+
+    fn clear(&mut self) {
+        self.ram.clear()
+    }
+
+    fn set_init_policy(&mut self, policy: MemoryInitPolicy) {
+        self.ram.set_init_policy(policy)
+    }
+
+    fn init_policy(&self) -> MemoryInitPolicy {
+        self.ram.init_policy()
+    }
+
+    fn read_range(&self, start: usize, buf: &mut [u8]) -> Result<(), CpuError> {
+        // ram, with the devices which can be peeked
+        self.ram.read_range(start, buf)?;
+        for (i, b) in buf.iter_mut().enumerate() {
+            if let Some(idx) = self.find_device(start + i) {
+                if let Some(v) = self.devices[idx].1.peek((start + i) as u16) {
+                    *b = v;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn select_bank(&mut self, slot: usize, bank: usize) -> Result<(), CpuError> {
+        self.ram.select_bank(slot, bank)
+    }
+
+    fn set_rom_region(&mut self, address: usize, len: usize, report_error: bool) {
+        self.ram.set_rom_region(address, len, report_error)
+    }
+
+    fn write_byte_privileged(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        match self.find_device(address) {
+            Some(idx) => {
+                self.devices[idx].1.write(address as u16, b);
+                Ok(())
+            }
+            None => self.ram.write_byte_privileged(address, b),
+        }
+    }
+}
+
+/**
+ * a Bus dispatching accesses to an ordered list of mapped devices, with fall-through to ram.
+ *
+ * use BusBuilder to create one.
+ */
+pub struct MappedBus {
+    m: Box<dyn Memory>,
+}
+
+impl Bus for MappedBus {
+    #[inline]
+    fn get_memory(&mut self) -> &mut Box<dyn Memory> {
+        &mut self.m
+    }
+}
+
+/**
+ * builds a MappedBus.
+ *
+ * mappings are checked in the order they're added, the first one matching the address wins.
+ */
+pub struct BusBuilder {
+    ram: Box<dyn Memory>,
+    devices: Vec<(RangeInclusive<u16>, Box<dyn Device>)>,
+}
+
+impl BusBuilder {
+    /**
+     * creates a new builder, with the given Memory used as ram for the unmapped addresses.
+     */
+    pub fn new(ram: Box<dyn Memory>) -> BusBuilder {
+        BusBuilder {
+            ram,
+            devices: Vec::new(),
+        }
+    }
+
+    /**
+     * maps a device at the given address range.
+     */
+    pub fn map(mut self, range: RangeInclusive<u16>, dev: Box<dyn Device>) -> BusBuilder {
+        self.devices.push((range, dev));
+        self
+    }
+
+    /**
+     * builds the bus.
+     */
+    pub fn build(self) -> Box<dyn Bus> {
+        let m = MappedMemory {
+            ram: self.ram,
+            devices: self.devices,
+        };
+        Box::new(MappedBus { m: Box::new(m) })
+    }
 }
@@ -0,0 +1,430 @@
+/*
+ * Filename: /src/capi.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 11:05:12
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::cpu::{Cpu, CpuFlags, ResetKind, RunResult, StopReason};
+use crate::memory::{self, Memory, MemoryInitPolicy};
+use std::ffi::c_void;
+use std::sync::{Arc, Mutex};
+
+/**
+ * error codes returned by the rv6502_* functions, mapped from CpuErrorType.
+ */
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum rv6502_error {
+    RV6502_OK = 0,
+    RV6502_ERR_MEMORY_READ,
+    RV6502_ERR_MEMORY_WRITE,
+    RV6502_ERR_MEMORY_LOAD,
+    RV6502_ERR_INVALID_OPCODE,
+    RV6502_ERR_RW_BREAKPOINT,
+    RV6502_ERR_DEADLOCK,
+    RV6502_ERR_HALTED,
+    RV6502_ERR_READ_ONLY_MEMORY,
+    RV6502_ERR_GENERIC,
+    /// an NMOS JAM (KIL) opcode has been executed, only rv6502_reset() recovers.
+    RV6502_ERR_JAMMED,
+    /// a NULL pointer has been passed in.
+    RV6502_ERR_INVALID_ARGUMENT,
+}
+
+impl From<CpuError> for rv6502_error {
+    fn from(e: CpuError) -> Self {
+        match e.t {
+            CpuErrorType::MemoryRead => rv6502_error::RV6502_ERR_MEMORY_READ,
+            CpuErrorType::MemoryWrite => rv6502_error::RV6502_ERR_MEMORY_WRITE,
+            CpuErrorType::MemoryLoad => rv6502_error::RV6502_ERR_MEMORY_LOAD,
+            CpuErrorType::InvalidOpcode => rv6502_error::RV6502_ERR_INVALID_OPCODE,
+            CpuErrorType::RwBreakpoint => rv6502_error::RV6502_ERR_RW_BREAKPOINT,
+            CpuErrorType::Deadlock => rv6502_error::RV6502_ERR_DEADLOCK,
+            CpuErrorType::Halted => rv6502_error::RV6502_ERR_HALTED,
+            CpuErrorType::ReadOnlyMemory => rv6502_error::RV6502_ERR_READ_ONLY_MEMORY,
+            CpuErrorType::Generic => rv6502_error::RV6502_ERR_GENERIC,
+        }
+    }
+}
+
+/**
+ * the registers, for rv6502_get_regs() and rv6502_set_regs().
+ */
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct rv6502_regs {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub s: u8,
+    pub p: u8,
+    pub pc: u16,
+    /// total elapsed cycles, ignored by rv6502_set_regs().
+    pub cycles: u64,
+}
+
+/**
+ * called by the cpu on every memory access (with value pointing to the byte being written, or to be filled on reads).
+ *
+ * returns true if the access has been handled by the host (i.e. memory-mapped i/o), false to let it through to ram.
+ */
+#[allow(non_camel_case_types)]
+pub type rv6502_rw_callback = Option<
+    unsafe extern "C" fn(user_data: *mut c_void, address: u16, value: *mut u8, write: bool) -> bool,
+>;
+
+/**
+ * the installed rv6502_rw_callback.
+ */
+#[derive(Clone, Copy)]
+struct RwCallback {
+    cb: rv6502_rw_callback,
+    user_data: *mut c_void,
+}
+
+// user_data is owned by the host, which is responsible to use the handle from one thread at a time.
+unsafe impl Send for RwCallback {}
+
+/**
+ * ram which offers every access to the host callback first.
+ */
+struct CallbackMemory {
+    ram: Box<dyn Memory>,
+    rw: Arc<Mutex<RwCallback>>,
+}
+
+impl CallbackMemory {
+    /**
+     * offers the access to the callback, returns true if handled.
+     */
+    fn call(&self, address: usize, value: &mut u8, write: bool) -> bool {
+        let rw = *self.rw.lock().unwrap();
+        match rw.cb {
+            Some(f) => unsafe { f(rw.user_data, address as u16, value, write) },
+            None => false,
+        }
+    }
+}
+
+impl Memory for CallbackMemory {
+    fn read_byte(&mut self, address: usize) -> Result<u8, CpuError> {
+        let mut b = 0;
+        if self.call(address, &mut b, false) {
+            return Ok(b);
+        }
+        self.ram.read_byte(address)
+    }
+
+    fn read_word_le(&mut self, address: usize) -> Result<u16, CpuError> {
+        let lo = self.read_byte(address)?;
+        let hi = self.read_byte(address + 1)?;
+        Ok(u16::from_le_bytes([lo, hi]))
+    }
+
+    fn write_word_le(&mut self, address: usize, w: u16) -> Result<(), CpuError> {
+        let b = w.to_le_bytes();
+        self.write_byte(address, b[0])?;
+        self.write_byte(address + 1, b[1])
+    }
+
+    fn write_byte(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        let mut v = b;
+        if self.call(address, &mut v, true) {
+            return Ok(());
+        }
+        self.ram.write_byte(address, b)
+    }
+
+    fn get_size(&self) -> usize {
+        self.ram.get_size()
+    }
+
+    fn clear(&mut self) {
+        self.ram.clear();
+    }
+
+    fn set_init_policy(&mut self, policy: MemoryInitPolicy) {
+        self.ram.set_init_policy(policy);
+    }
+
+    fn init_policy(&self) -> MemoryInitPolicy {
+        self.ram.init_policy()
+    }
+
+    fn read_range(&self, start: usize, buf: &mut [u8]) -> Result<(), CpuError> {
+        self.ram.read_range(start, buf)
+    }
+
+    fn set_rom_region(&mut self, address: usize, len: usize, report_error: bool) {
+        self.ram.set_rom_region(address, len, report_error);
+    }
+
+    fn write_byte_privileged(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        self.ram.write_byte_privileged(address, b)
+    }
+}
+
+/**
+ * opaque cpu handle, created by rv6502_new() and freed by rv6502_destroy().
+ */
+#[allow(non_camel_case_types)]
+pub struct rv6502 {
+    c: Cpu,
+    rw: Arc<Mutex<RwCallback>>,
+}
+
+/**
+ * maps a run_until() result to an error code.
+ */
+fn run_result(r: Result<RunResult, CpuError>) -> rv6502_error {
+    match r {
+        Ok(res) => match res.reason {
+            StopReason::Halted => rv6502_error::RV6502_ERR_HALTED,
+            StopReason::Jammed { .. } => rv6502_error::RV6502_ERR_JAMMED,
+            _ => rv6502_error::RV6502_OK,
+        },
+        Err(e) => e.into(),
+    }
+}
+
+/**
+ * creates a MOS6502 with memory_size bytes of zero filled ram, to be freed with rv6502_destroy().
+ */
+#[no_mangle]
+pub extern "C" fn rv6502_new(memory_size: usize) -> *mut rv6502 {
+    let rw = Arc::new(Mutex::new(RwCallback {
+        cb: None,
+        user_data: std::ptr::null_mut(),
+    }));
+    let m = CallbackMemory {
+        ram: memory::new_sized(memory_size),
+        rw: rw.clone(),
+    };
+    let h = rv6502 {
+        c: Cpu::new(bus::new_default(Box::new(m)), None, None),
+        rw,
+    };
+    Box::into_raw(Box::new(h))
+}
+
+/**
+ * frees a handle returned by rv6502_new(), NULL is ignored.
+ *
+ * # Safety
+ *
+ * h must be NULL or a handle from rv6502_new() not already destroyed.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_destroy(h: *mut rv6502) {
+    if !h.is_null() {
+        drop(Box::from_raw(h));
+    }
+}
+
+/**
+ * power-on reset, starting at address or at the reset vector if address is negative.
+ *
+ * # Safety
+ *
+ * h must be a valid handle.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_reset(h: *mut rv6502, address: i32) -> rv6502_error {
+    let h = match h.as_mut() {
+        Some(h) => h,
+        None => return rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    };
+    let start = if address < 0 {
+        None
+    } else {
+        Some(address as u16)
+    };
+    match h.c.reset(start, ResetKind::PowerOn) {
+        Ok(()) => rv6502_error::RV6502_OK,
+        Err(e) => e.into(),
+    }
+}
+
+/**
+ * executes one instruction (or services a pending interrupt).
+ *
+ * # Safety
+ *
+ * h must be a valid handle.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_step(h: *mut rv6502) -> rv6502_error {
+    match h.as_mut() {
+        Some(h) => run_result(h.c.run_until(None, 0, 1)),
+        None => rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    }
+}
+
+/**
+ * runs for (at least) the given cycles, the cycles actually consumed are stored in executed if not NULL.
+ *
+ * # Safety
+ *
+ * h must be a valid handle, executed NULL or valid.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_run_cycles(
+    h: *mut rv6502,
+    cycles: usize,
+    executed: *mut usize,
+) -> rv6502_error {
+    let h = match h.as_mut() {
+        Some(h) => h,
+        None => return rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    };
+    let before = h.c.cycles();
+    let res = run_result(h.c.run_until(None, cycles, 0));
+    if let Some(e) = executed.as_mut() {
+        *e = h.c.cycles() - before;
+    }
+    res
+}
+
+/**
+ * copies the registers to regs.
+ *
+ * # Safety
+ *
+ * h and regs must be valid.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_get_regs(h: *mut rv6502, regs: *mut rv6502_regs) -> rv6502_error {
+    let (h, regs) = match (h.as_mut(), regs.as_mut()) {
+        (Some(h), Some(regs)) => (h, regs),
+        _ => return rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    };
+    *regs = rv6502_regs {
+        a: h.c.regs.a,
+        x: h.c.regs.x,
+        y: h.c.regs.y,
+        s: h.c.regs.s,
+        p: h.c.regs.p.bits(),
+        pc: h.c.regs.pc,
+        cycles: h.c.cycles() as u64,
+    };
+    rv6502_error::RV6502_OK
+}
+
+/**
+ * sets the registers from regs (cycles is ignored).
+ *
+ * # Safety
+ *
+ * h and regs must be valid.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_set_regs(h: *mut rv6502, regs: *const rv6502_regs) -> rv6502_error {
+    let (h, regs) = match (h.as_mut(), regs.as_ref()) {
+        (Some(h), Some(regs)) => (h, regs),
+        _ => return rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    };
+    h.c.regs.a = regs.a;
+    h.c.regs.x = regs.x;
+    h.c.regs.y = regs.y;
+    h.c.regs.s = regs.s;
+    h.c.regs.p = CpuFlags::from_bits_truncate(regs.p);
+    h.c.regs.pc = regs.pc;
+    rv6502_error::RV6502_OK
+}
+
+/**
+ * reads the ram byte at address into value, bypassing the rw callback.
+ *
+ * # Safety
+ *
+ * h and value must be valid.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_read(h: *mut rv6502, address: u16, value: *mut u8) -> rv6502_error {
+    let (h, value) = match (h.as_mut(), value.as_mut()) {
+        (Some(h), Some(value)) => (h, value),
+        _ => return rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    };
+    let mut b = [0u8; 1];
+    match h.c.bus.get_memory().read_range(address as usize, &mut b) {
+        Ok(()) => {
+            *value = b[0];
+            rv6502_error::RV6502_OK
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/**
+ * writes value to the ram at address, bypassing the rw callback and the rom protection.
+ *
+ * # Safety
+ *
+ * h must be a valid handle.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_write(h: *mut rv6502, address: u16, value: u8) -> rv6502_error {
+    let h = match h.as_mut() {
+        Some(h) => h,
+        None => return rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    };
+    match h
+        .c
+        .bus
+        .get_memory()
+        .write_byte_privileged(address as usize, value)
+    {
+        Ok(()) => rv6502_error::RV6502_OK,
+        Err(e) => e.into(),
+    }
+}
+
+/**
+ * installs (or removes, with NULL) the callback which is offered every cpu memory access, user_data is passed back to it.
+ *
+ * # Safety
+ *
+ * h must be a valid handle, user_data must stay valid as long as the callback is installed.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn rv6502_set_rw_callback(
+    h: *mut rv6502,
+    cb: rv6502_rw_callback,
+    user_data: *mut c_void,
+) -> rv6502_error {
+    match h.as_mut() {
+        Some(h) => {
+            *h.rw.lock().unwrap() = RwCallback { cb, user_data };
+            rv6502_error::RV6502_OK
+        }
+        None => rv6502_error::RV6502_ERR_INVALID_ARGUMENT,
+    }
+}
@@ -29,23 +29,59 @@
  */
 
 use crate::bus::Bus;
-use debugger::breakpoints::BreakpointType;
-use debugger::Debugger;
+use debugger::breakpoints::{BpId, BreakpointType, InterruptContext};
+use debugger::{dbg_println, Debugger};
 pub(crate) mod opcodes;
-use std::fmt::{Display, Error, Formatter};
+use core::fmt::{Display, Error, Formatter};
+use core::str::FromStr;
 
 use bitflags::bitflags;
+use log::info;
 pub(crate) mod addressing_modes;
+pub use addressing_modes::AddressingModeId;
+use opcodes::OpcodeEntry;
+pub use opcodes::{opcode_info, OpcodeInfo};
 
+mod access_counters;
+use access_counters::AccessCounters;
+mod builder;
+pub use builder::CpuBuilder;
+mod coverage;
+pub use coverage::Coverage;
+mod interrupt_stats;
+pub use interrupt_stats::InterruptStats;
+use interrupt_stats::{InterruptKind, InterruptTracker};
+pub mod asm;
 pub mod cpu_error;
+#[cfg(feature = "std")]
 pub mod debugger;
+#[cfg(not(feature = "std"))]
+#[path = "cpu/no_debugger.rs"]
+pub mod debugger;
+pub mod disasm;
+mod profiler;
 use crate::utils::*;
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
 use cpu_error::{CpuError, CpuErrorType};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/**
+ * run_realtime() spins instead of sleeping for the last part of a wait, as sleep() may oversleep.
+ */
+#[cfg(feature = "std")]
+const REALTIME_SPIN: Duration = Duration::from_millis(1);
+
+/**
+ * run_realtime() slice size with an unthrottled (0 Hz) clock.
+ */
+#[cfg(feature = "std")]
+const UNTHROTTLED_SLICE_CYCLES: usize = 100_000;
 
 /**
  * the cpu registers.
  */
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Registers {
     pub a: u8,
     pub x: u8,
@@ -55,10 +91,95 @@ pub struct Registers {
     pub pc: u16,
 }
 
+/**
+ * plain copy of the registers with P split into single flags, returned by Registers::snapshot() to assert on in tests.
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct RegistersSnapshot {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub s: u8,
+    pub pc: u16,
+    pub n: bool,
+    pub v: bool,
+    pub u: bool,
+    pub b: bool,
+    pub d: bool,
+    pub i: bool,
+    pub z: bool,
+    pub c: bool,
+}
+
+/**
+ * a register (or flag) changed between two Registers, returned by Registers::diff().
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RegChange {
+    A {
+        from: u8,
+        to: u8,
+    },
+    X {
+        from: u8,
+        to: u8,
+    },
+    Y {
+        from: u8,
+        to: u8,
+    },
+    S {
+        from: u8,
+        to: u8,
+    },
+    PC {
+        from: u16,
+        to: u16,
+    },
+    /// the flag (one of CpuFlags) has been set (to=true) or cleared.
+    Flag {
+        flag: CpuFlags,
+        to: bool,
+    },
+}
+
+impl Display for RegChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            RegChange::A { from, to } => write!(f, "A: ${:02x} -> ${:02x}", from, to),
+            RegChange::X { from, to } => write!(f, "X: ${:02x} -> ${:02x}", from, to),
+            RegChange::Y { from, to } => write!(f, "Y: ${:02x} -> ${:02x}", from, to),
+            RegChange::S { from, to } => write!(f, "S: ${:02x} -> ${:02x}", from, to),
+            RegChange::PC { from, to } => write!(f, "PC: ${:04x} -> ${:04x}", from, to),
+            RegChange::Flag { flag, to } => {
+                let name = FLAG_NAMES
+                    .iter()
+                    .find(|(fl, _)| fl == flag)
+                    .map_or('?', |(_, n)| *n);
+                write!(f, "{}: {}", name, if *to { "set" } else { "cleared" })
+            }
+        }
+    }
+}
+
+/**
+ * the flags from bit 7 to bit 0, with the letter used by Registers::flags_to_string().
+ */
+const FLAG_NAMES: [(CpuFlags, char); 8] = [
+    (CpuFlags::N, 'N'),
+    (CpuFlags::V, 'V'),
+    (CpuFlags::U, 'U'),
+    (CpuFlags::B, 'B'),
+    (CpuFlags::D, 'D'),
+    (CpuFlags::I, 'I'),
+    (CpuFlags::Z, 'Z'),
+    (CpuFlags::C, 'C'),
+];
+
 /**
  * indicates the operation CpuCallbackContext refers to.
  */
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum CpuOperation {
     Exec,
     Read,
@@ -66,17 +187,52 @@ pub enum CpuOperation {
     Irq,
     Nmi,
     Brk,
+    /// a byte of the reset/irq/nmi vector is read.
+    VectorFetch,
+    /// the SO pin has set the V flag.
+    So,
 }
 
 /**
  * type of emulated cpu
  */
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum CpuType {
     /// default, MOS6502
     MOS6502,
-    /// WDC 6502C
+    /// WDC 65C02, the full CMOS set: the Rockwell bit instructions (BBR/BBS/RMB/SMB) and WAI/STP.
     WDC65C02,
+    /// MOS6510 (MOS6502 with the on-chip i/o port at $0000/$0001, i.e. C64)
+    MOS6510,
+    /// Ricoh 2A03 (NES, MOS6502 without decimal mode: ADC/SBC ignore the D flag)
+    Ricoh2A03,
+    /// Rockwell R65C02, the 65C02 with the bit instructions (BBR/BBS/RMB/SMB) but without WAI/STP ($cb/$db are NOPs).
+    R65C02,
+    /// the base 65C02 set (i.e. GTE/Synertek 65SC02), without the bit instructions and WAI/STP (their opcodes are NOPs).
+    G65SC02,
+}
+
+impl CpuType {
+    /**
+     * true for the CMOS cpus (WDC65C02, R65C02, G65SC02), which share the 65C02 instruction set and behaviour.
+     */
+    pub fn is_65c02(&self) -> bool {
+        matches!(self, CpuType::WDC65C02 | CpuType::R65C02 | CpuType::G65SC02)
+    }
+
+    /**
+     * true if the cpu has the Rockwell bit instructions (BBR/BBS/RMB/SMB).
+     */
+    pub fn has_bit_instructions(&self) -> bool {
+        matches!(self, CpuType::WDC65C02 | CpuType::R65C02)
+    }
+
+    /**
+     * true if the cpu has the WDC WAI/STP instructions.
+     */
+    pub fn has_wai_stp(&self) -> bool {
+        *self == CpuType::WDC65C02
+    }
 }
 
 impl Display for CpuType {
@@ -88,11 +244,34 @@ impl Display for CpuType {
             CpuType::WDC65C02 => {
                 write!(f, "WDC65C02")?;
             }
+            CpuType::MOS6510 => {
+                write!(f, "MOS6510")?;
+            }
+            CpuType::Ricoh2A03 => {
+                write!(f, "Ricoh2A03")?;
+            }
+            CpuType::R65C02 => {
+                write!(f, "R65C02")?;
+            }
+            CpuType::G65SC02 => {
+                write!(f, "G65SC02")?;
+            }
         };
         Ok(())
     }
 }
 
+/**
+ * kind of reset performed by Cpu::reset().
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ResetKind {
+    /// power-on: A/X/Y zeroed, S=$FF, D cleared and the cycle counter restarted.
+    PowerOn,
+    /// reset line pulled on a running cpu, as the hardware does: A/X/Y preserved, the current S decremented by 3 (the fake pushes, $FF gives $FC), I set, D cleared only on the 65C02, cycles added to the counter, irq/nmi lines kept.
+    Warm,
+}
+
 bitflags! {
     /**
      * flags (values for the P register).
@@ -139,9 +318,269 @@ bitflags! {
     }
 }
 
+impl FromStr for CpuFlags {
+    type Err = CpuError;
+
+    /**
+     * parses the 8 characters flags string emitted by Registers::flags_to_string(), from N to C: the uppercase letter
+     * sets the flag, the lowercase letter or '-' clears it (i.e. "NV-BdIzc" or "Nv-bdizc").
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() != FLAG_NAMES.len() {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(format!(
+                    "invalid flags string '{}', must be 8 characters",
+                    s
+                )),
+            ));
+        }
+        let mut p = CpuFlags::empty();
+        for (ch, (flag, name)) in chars.iter().zip(FLAG_NAMES.iter()) {
+            if ch == name {
+                p.insert(*flag);
+            } else if *ch != '-' && *ch != name.to_ascii_lowercase() {
+                return Err(CpuError::new_default(
+                    CpuErrorType::Generic,
+                    0,
+                    Some(format!(
+                        "invalid flags string '{}', expected {}, {} or - for the {} flag",
+                        s,
+                        name,
+                        name.to_ascii_lowercase(),
+                        name
+                    )),
+                ));
+            }
+        }
+        Ok(p)
+    }
+}
+
+/**
+ * implemented by the embedder to handle the MOS6510 on-chip i/o port ($0000 = data direction, $0001 = data).
+ *
+ * must be Send, as the Cpu holding it.
+ */
+pub trait PortHandler: Send {
+    /**
+     * called after a write to $0000 or $0001, with the current direction and data registers.
+     */
+    fn write(&mut self, ddr: u8, data: u8);
+
+    /**
+     * called on read from $0001, returns the external input bits (only bits set as input in ddr are used).
+     */
+    fn read(&mut self, ddr: u8) -> u8;
+}
+
+/**
+ * implemented by the embedder to be notified when reads/writes/irq/nmi occurs, see Cpu::set_hook().
+ *
+ * closures taking (&CpuCallbackContext, &mut CpuView) implement it too. must be Send, as the Cpu holding it (i.e. share
+ * state with the embedder through an Arc<Mutex<...>> rather than an Rc<RefCell<...>>).
+ */
+pub trait CpuHook<B: Bus = Box<dyn Bus>>: Send {
+    /**
+     * called with the event and a restricted view of the cpu.
+     */
+    fn on_event(&mut self, ctx: &CpuCallbackContext, cpu: &mut CpuView<B>);
+}
+
+impl<B: Bus, F: FnMut(&CpuCallbackContext, &mut CpuView<B>) + Send> CpuHook<B> for F {
+    fn on_event(&mut self, ctx: &CpuCallbackContext, cpu: &mut CpuView<B>) {
+        self(ctx, cpu)
+    }
+}
+
+/**
+ * identifies a hook installed with Cpu::add_hook(), to remove it.
+ */
+pub type HookId = usize;
+
+/**
+ * selects the events notified to a hook installed with Cpu::add_hook(), the default notifies everything.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookFilter {
+    /// the operations notified, all if empty.
+    pub operations: Vec<CpuOperation>,
+    /// the addresses notified (start, end inclusive), all if None. events without an address (irq, nmi, so) are
+    /// notified regardless.
+    pub range: Option<(u16, u16)>,
+}
+
+impl HookFilter {
+    /**
+     * check if the hook is interested in the event.
+     */
+    fn matches(&self, ctx: &CpuCallbackContext) -> bool {
+        if !self.operations.is_empty() && !self.operations.contains(&ctx.operation) {
+            return false;
+        }
+        match (self.range, ctx.operation) {
+            (None, _) | (_, CpuOperation::Irq | CpuOperation::Nmi | CpuOperation::So) => true,
+            (Some((start, end)), _) => ctx.address >= start && ctx.address <= end,
+        }
+    }
+}
+
+/**
+ * wraps the plain fn callbacks passed to Cpu::new().
+ */
+struct FnHook<B: Bus>(fn(c: &mut Cpu<B>, cb: CpuCallbackContext));
+
+impl<B: Bus> CpuHook<B> for FnHook<B> {
+    fn on_event(&mut self, ctx: &CpuCallbackContext, cpu: &mut CpuView<B>) {
+        (self.0)(cpu.c, ctx.clone())
+    }
+}
+
+/**
+ * returned by a CpuTrap, tells the cpu how to go on at the trap address.
+ */
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TrapAction {
+    /// execute the instruction at the trap address, as if there was no trap.
+    Continue,
+    /// the routine has been serviced: return to the caller as if an RTS was executed at the trap address, accounting
+    /// the cycles set with Cpu::set_trap_cycles().
+    Return,
+}
+
+/**
+ * implemented by the embedder to service a routine natively (i.e. the C64 CHROUT at $ffd2), see Cpu::install_trap().
+ *
+ * closures taking (&mut CpuView) and returning a TrapAction implement it too. must be Send, as the Cpu holding it.
+ */
+pub trait CpuTrap<B: Bus = Box<dyn Bus>>: Send {
+    /**
+     * called before executing the instruction at the trap address, with a restricted view of the cpu.
+     */
+    fn on_trap(&mut self, cpu: &mut CpuView<B>) -> TrapAction;
+}
+
+impl<B: Bus, F: FnMut(&mut CpuView<B>) -> TrapAction + Send> CpuTrap<B> for F {
+    fn on_trap(&mut self, cpu: &mut CpuView<B>) -> TrapAction {
+        self(cpu)
+    }
+}
+
+/**
+ * restricted view of the cpu (registers, memory, interrupt lines) passed to hooks and traps.
+ */
+pub struct CpuView<'a, B: Bus = Box<dyn Bus>> {
+    c: &'a mut Cpu<B>,
+}
+
+impl<'a, B: Bus> CpuView<'a, B> {
+    /**
+     * the cpu registers.
+     */
+    pub fn regs(&self) -> &Registers {
+        &self.c.regs
+    }
+
+    /**
+     * the cpu registers, mutable.
+     */
+    pub fn regs_mut(&mut self) -> &mut Registers {
+        &mut self.c.regs
+    }
+
+    /**
+     * current cpu cycles.
+     */
+    pub fn cycles(&self) -> usize {
+        self.c.cycles
+    }
+
+    /**
+     * the emulated cpu type.
+     */
+    pub fn cpu_type(&self) -> CpuType {
+        self.c.cpu_type
+    }
+
+    /**
+     * reads a byte from memory, without notifying the hook.
+     */
+    pub fn read_byte(&mut self, address: u16) -> Result<u8, CpuError> {
+        self.c.bus.get_memory().read_byte(address as usize)
+    }
+
+    /**
+     * writes a byte to memory, without notifying the hook.
+     */
+    pub fn write_byte(&mut self, address: u16, b: u8) -> Result<(), CpuError> {
+        self.c.bus.get_memory().write_byte(address as usize, b)
+    }
+
+    /**
+     * replaces the byte returned to the cpu by the read being notified (CpuOperation::Read or CpuOperation::VectorFetch).
+     *
+     * with more hooks installed, the first override wins.
+     */
+    pub fn override_read(&mut self, b: u8) {
+        self.c.read_override.get_or_insert(b);
+    }
+
+    /**
+     * sets the irq line state, as Cpu::set_irq_line().
+     */
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.c.set_irq_line(asserted);
+    }
+
+    /**
+     * sets the nmi line state, as Cpu::set_nmi_line().
+     */
+    pub fn set_nmi_line(&mut self, asserted: bool) {
+        self.c.set_nmi_line(asserted);
+    }
+
+    /**
+     * queues an nmi, as Cpu::trigger_nmi().
+     */
+    pub fn trigger_nmi(&mut self) {
+        self.c.trigger_nmi();
+    }
+
+    /**
+     * asserts the SO pin, as Cpu::assert_so().
+     */
+    pub fn assert_so(&mut self) {
+        self.c.assert_so();
+    }
+
+    /**
+     * sets the RDY line, as Cpu::set_rdy().
+     */
+    pub fn set_rdy(&mut self, ready: bool) {
+        self.c.set_rdy(ready);
+    }
+
+    /**
+     * forces run() to exit, as setting Cpu::done.
+     */
+    pub fn stop(&mut self) {
+        self.c.done = true;
+    }
+
+    /**
+     * changes the run_realtime() clock, as Cpu::set_realtime_frequency().
+     */
+    pub fn set_realtime_frequency(&mut self, freq_hz: u32) {
+        self.c.set_realtime_frequency(freq_hz);
+    }
+}
+
 /**
  * this is called by the cpu to provide the user with notification when reads/writes/irq/nmi occurs.
  */
+#[derive(Debug, Clone)]
 pub struct CpuCallbackContext {
     /// address acessed.
     pub address: u16,
@@ -151,28 +590,48 @@ pub struct CpuCallbackContext {
     pub value: u8,
     /// one of the CpuOperation enums.
     pub operation: CpuOperation,
+    /// for Exec, the opcode byte.
+    pub opcode: Option<u8>,
+    /// for Exec, the lowercase mnemonic (i.e. "sta").
+    pub mnemonic: Option<&'static str>,
+    /// for Exec, the effective address the instruction will access (or jump/branch to), computed before executing it.
+    /// None for the accumulator, implied and immediate modes.
+    pub target: Option<u16>,
 }
 
 impl Display for CpuCallbackContext {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         match self.operation {
-            CpuOperation::Irq | CpuOperation::Nmi => {
+            CpuOperation::Irq | CpuOperation::Nmi | CpuOperation::So => {
                 write!(f, "CALLBACK! type={:?}", self.operation)?;
             }
-            CpuOperation::Read | CpuOperation::Write => {
+            CpuOperation::Read | CpuOperation::Write | CpuOperation::VectorFetch => {
                 write!(
                     f,
                     "CALLBACK! type={:?}, address=${:04x}, value=${:02x}, access_size={}",
                     self.operation, self.address, self.value, self.access_size
                 )?;
             }
-            CpuOperation::Brk | CpuOperation::Exec => {
+            CpuOperation::Brk => {
                 write!(
                     f,
                     "CALLBACK! type={:?}, address=${:04x}",
                     self.operation, self.address
                 )?;
             }
+            CpuOperation::Exec => {
+                write!(
+                    f,
+                    "CALLBACK! type={:?}, address=${:04x}, opcode=${:02x} ({})",
+                    self.operation,
+                    self.address,
+                    self.opcode.unwrap_or(0),
+                    self.mnemonic.unwrap_or("???")
+                )?;
+                if let Some(t) = self.target {
+                    write!(f, ", target=${:04x}", t)?;
+                }
+            }
         }
         Ok(())
     }
@@ -202,7 +661,7 @@ impl Registers {
             a: 0,
             x: 0,
             y: 0,
-            p: CpuFlags::from_bits(0).unwrap(),
+            p: CpuFlags::empty(),
             s: 0,
             pc: 0,
         };
@@ -210,146 +669,534 @@ impl Registers {
     }
 
     /**
-     * convert P (flags) register to a meaningful string
+     * C flag.
      */
-    fn flags_to_string(&self) -> String {
-        let s = format!(
-            "{}{}{}{}{}{}{}{}",
-            if self.p.contains(CpuFlags::N) {
-                "N"
-            } else {
-                "-"
-            },
-            if self.p.contains(CpuFlags::V) {
-                "V"
-            } else {
-                "-"
-            },
-            if self.p.contains(CpuFlags::U) {
-                "U"
-            } else {
-                "-"
-            },
-            if self.p.contains(CpuFlags::B) {
-                "B"
-            } else {
-                "-"
-            },
-            if self.p.contains(CpuFlags::D) {
-                "D"
-            } else {
-                "-"
-            },
-            if self.p.contains(CpuFlags::I) {
-                "I"
-            } else {
-                "-"
-            },
-            if self.p.contains(CpuFlags::Z) {
-                "Z"
-            } else {
-                "-"
-            },
-            if self.p.contains(CpuFlags::C) {
-                "C"
-            } else {
-                "-"
-            },
-        );
-        s
+    pub fn carry(&self) -> bool {
+        self.p.contains(CpuFlags::C)
     }
-}
 
-/**
- * 6502 has 3 vectors (= addresses at which the cpu is directed to perform certain tasks)
- */
-#[derive(Debug, PartialEq)]
-enum Vectors {
-    NMI = 0xfffa,
-    RESET = 0xfffc,
-    IRQ = 0xfffe,
-}
+    /**
+     * Z flag.
+     */
+    pub fn zero(&self) -> bool {
+        self.p.contains(CpuFlags::Z)
+    }
 
-/**
- * implements the cpu.
- */
-pub struct Cpu {
-    /// cpu registers.
-    pub regs: Registers,
+    /**
+     * I flag.
+     */
+    pub fn interrupt_disable(&self) -> bool {
+        self.p.contains(CpuFlags::I)
+    }
 
-    /// current cpu cycles.
-    pub cycles: usize,
+    /**
+     * D flag.
+     */
+    pub fn decimal(&self) -> bool {
+        self.p.contains(CpuFlags::D)
+    }
 
-    /// running under debugger ?
-    debug: bool,
+    /**
+     * V flag.
+     */
+    pub fn overflow(&self) -> bool {
+        self.p.contains(CpuFlags::V)
+    }
 
-    /// forces run() to exit
-    pub done: bool,
+    /**
+     * N flag.
+     */
+    pub fn negative(&self) -> bool {
+        self.p.contains(CpuFlags::N)
+    }
 
-    /// the bus.
-    pub bus: Box<dyn Bus>,
+    /**
+     * sets/clears the C flag.
+     */
+    pub fn set_carry(&mut self, enable: bool) {
+        self.p.set(CpuFlags::C, enable);
+    }
 
-    /// callback for the user (optional).
-    cb: Option<fn(c: &mut Cpu, cb: CpuCallbackContext)>,
-    /// set if irq() must be called within the run loop.
-    pub must_trigger_irq: bool,
-    /// set if nmi() must be called within the run loop.
-    pub must_trigger_nmi: bool,
-    /// is there an intewrrupt pending ?
-    irq_pending: bool,
-    /// to handle interrupt return after RTI in certain situations.
-    fix_pc_rti: i8,
-    /// the emulated cpu type, default MOS6502.
-    cpu_type: CpuType,
-}
+    /**
+     * sets/clears the Z flag.
+     */
+    pub fn set_zero(&mut self, enable: bool) {
+        self.p.set(CpuFlags::Z, enable);
+    }
 
-impl Cpu {
     /**
-     * activate logging on stdout through env_logger (max level).
+     * sets/clears the I flag.
      */
-    pub fn enable_logging(&self, enable: bool) {
-        enable_logging_internal(enable)
+    pub fn set_interrupt_disable(&mut self, enable: bool) {
+        self.p.set(CpuFlags::I, enable);
     }
 
     /**
-     * call installed cpu callback if any.
+     * sets/clears the D flag.
      */
-    pub(crate) fn call_callback(
-        &mut self,
-        address: u16,
-        value: u8,
-        access_size: i8,
-        op: CpuOperation,
-    ) {
-        if self.cb.is_some() {
-            // call callback
-            let ctx = CpuCallbackContext {
-                address: address,
-                access_size: access_size,
-                value: value,
-                operation: op,
-            };
-            self.cb.unwrap()(self, ctx);
+    pub fn set_decimal(&mut self, enable: bool) {
+        self.p.set(CpuFlags::D, enable);
+    }
+
+    /**
+     * sets/clears the V flag.
+     */
+    pub fn set_overflow(&mut self, enable: bool) {
+        self.p.set(CpuFlags::V, enable);
+    }
+
+    /**
+     * sets/clears the N flag.
+     */
+    pub fn set_negative(&mut self, enable: bool) {
+        self.p.set(CpuFlags::N, enable);
+    }
+
+    /**
+     * P as pushed on the stack: U is always set, B is set by PHP/BRK (brk=true) and clear on irq/nmi.
+     *
+     * https://wiki.nesdev.com/w/index.php/Status_flags#The_B_flag
+     */
+    pub fn status_byte_for_push(&self, brk: bool) -> u8 {
+        let mut flags = self.p;
+        flags.set(CpuFlags::U, true);
+        flags.set(CpuFlags::B, brk);
+        flags.bits()
+    }
+
+    /**
+     * sets P from a byte pulled from the stack (PLP/RTI): B does not exist in the register, so it's cleared, and U is
+     * always set.
+     */
+    pub fn set_status_from_pull(&mut self, b: u8) {
+        self.p = CpuFlags::from_bits_truncate(b);
+        self.p.set(CpuFlags::B, false);
+        self.p.set(CpuFlags::U, true);
+    }
+
+    /**
+     * registers with the given values and P parsed from a flags string as emitted by flags_to_string(), i.e.
+     * Registers::with(0x01, 0, 0, 0xff, 0x400, "Nv-bdizc"), see CpuFlags::from_str().
+     */
+    pub fn with(a: u8, x: u8, y: u8, s: u8, pc: u16, flags: &str) -> Result<Registers, CpuError> {
+        Ok(Registers {
+            a,
+            x,
+            y,
+            p: flags.parse()?,
+            s,
+            pc,
+        })
+    }
+
+    /**
+     * returns a plain copy of the registers with P split into single flags.
+     */
+    pub fn snapshot(&self) -> RegistersSnapshot {
+        RegistersSnapshot {
+            a: self.a,
+            x: self.x,
+            y: self.y,
+            s: self.s,
+            pc: self.pc,
+            n: self.p.contains(CpuFlags::N),
+            v: self.p.contains(CpuFlags::V),
+            u: self.p.contains(CpuFlags::U),
+            b: self.p.contains(CpuFlags::B),
+            d: self.p.contains(CpuFlags::D),
+            i: self.p.contains(CpuFlags::I),
+            z: self.p.contains(CpuFlags::Z),
+            c: self.p.contains(CpuFlags::C),
         }
     }
 
     /**
-     * check if cpu flag is set
+     * returns what changed from self to other, registers first (A, X, Y, S, PC) then flags from N to C.
      */
-    pub(crate) fn is_cpu_flag_set(&self, f: CpuFlags) -> bool {
-        if self.regs.p.contains(f) {
-            return true;
+    pub fn diff(&self, other: &Registers) -> Vec<RegChange> {
+        let mut v = Vec::new();
+        if self.a != other.a {
+            v.push(RegChange::A {
+                from: self.a,
+                to: other.a,
+            });
         }
-        false
+        if self.x != other.x {
+            v.push(RegChange::X {
+                from: self.x,
+                to: other.x,
+            });
+        }
+        if self.y != other.y {
+            v.push(RegChange::Y {
+                from: self.y,
+                to: other.y,
+            });
+        }
+        if self.s != other.s {
+            v.push(RegChange::S {
+                from: self.s,
+                to: other.s,
+            });
+        }
+        if self.pc != other.pc {
+            v.push(RegChange::PC {
+                from: self.pc,
+                to: other.pc,
+            });
+        }
+        for (flag, _) in FLAG_NAMES.iter() {
+            if self.p.contains(*flag) != other.p.contains(*flag) {
+                v.push(RegChange::Flag {
+                    flag: *flag,
+                    to: other.p.contains(*flag),
+                });
+            }
+        }
+        v
     }
 
     /**
-     * set/unset cpu flag
+     * convert P (flags) register to a meaningful string, one character per flag from N to C ('-' if clear), which
+     * CpuFlags::from_str() parses back.
      */
-    pub(crate) fn set_cpu_flags(&mut self, f: CpuFlags, enable: bool) {
-        self.regs.p.set(f, enable);
+    pub fn flags_to_string(&self) -> String {
+        let s = format!(
+            "{}{}{}{}{}{}{}{}",
+            if self.p.contains(CpuFlags::N) {
+                "N"
+            } else {
+                "-"
+            },
+            if self.p.contains(CpuFlags::V) {
+                "V"
+            } else {
+                "-"
+            },
+            if self.p.contains(CpuFlags::U) {
+                "U"
+            } else {
+                "-"
+            },
+            if self.p.contains(CpuFlags::B) {
+                "B"
+            } else {
+                "-"
+            },
+            if self.p.contains(CpuFlags::D) {
+                "D"
+            } else {
+                "-"
+            },
+            if self.p.contains(CpuFlags::I) {
+                "I"
+            } else {
+                "-"
+            },
+            if self.p.contains(CpuFlags::Z) {
+                "Z"
+            } else {
+                "-"
+            },
+            if self.p.contains(CpuFlags::C) {
+                "C"
+            } else {
+                "-"
+            },
+        );
+        s
+    }
+}
+
+/**
+ * why run_until() returned.
+ */
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StopReason {
+    /// the cycles budget has been reached, overshoot is how many cycles the last instruction went past it.
+    CyclesReached { overshoot: usize },
+    /// the instructions budget has been reached.
+    InstructionsReached,
+    /// a breakpoint triggered with the debugger disabled, the instruction at pc has not been executed.
+    Breakpoint(BpId),
+    /// 'q' from the debugger, done set by a callback or a one-shot breakpoint (step/run to), errors are returned as Err.
+    Stopped,
+    /// STP has been executed, only reset() recovers.
+    Halted,
+    /// an NMOS JAM (KIL) opcode has been executed at pc, only reset() recovers.
+    Jammed { opcode: u8, pc: u16 },
+    /// RDY is low, no instruction is executed until it is raised again. cycles is what was left of the cycles
+    /// budget (0 with no budget), for the caller to account as stalled.
+    Stalled { cycles: usize },
+}
+
+/**
+ * returned by run_until().
+ */
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RunResult {
+    /// why the cpu stopped.
+    pub reason: StopReason,
+    /// cycles consumed by the executed instructions.
+    pub cycles: usize,
+    /// executed instructions.
+    pub instructions: usize,
+}
+
+/**
+ * returned by run_cycles().
+ */
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CyclesRun {
+    /// cycles consumed by the executed instructions, the last one may exceed the budget.
+    pub cycles: u64,
+    /// how many cycles past the budget the last instruction went, to be subtracted from the next budget.
+    pub overshoot: u64,
+    /// StopReason::CyclesReached, or why the cpu stopped before the budget has been consumed.
+    pub reason: StopReason,
+}
+
+/**
+ * why run_until_trap() returned.
+ */
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TrapReason {
+    /// an instruction jumped or branched to itself (i.e. 'bne *' or 'jmp *'), as test roms do to signal the result.
+    SelfJump,
+    /// WAI is waiting for an interrupt, with no interrupt source it would never resume.
+    Waiting,
+    /// STP has been executed, only reset() recovers.
+    Halted,
+    /// an NMOS JAM (KIL) opcode has been executed, only reset() recovers.
+    Jammed { opcode: u8 },
+    /// the cycles limit has been reached before trapping.
+    CyclesReached,
+    /// done set by a callback or RDY low.
+    Stopped,
+}
+
+/**
+ * returned by run_until_trap().
+ */
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TrapResult {
+    /// why the cpu stopped.
+    pub reason: TrapReason,
+    /// the address of the trapping instruction (for SelfJump and Waiting, else the pc when stopped).
+    pub pc: u16,
+    /// the registers when stopped.
+    pub regs: Registers,
+    /// cycles consumed by the executed instructions.
+    pub cycles: usize,
+}
+
+/**
+ * snapshot of the interrupt related state, returned by Cpu::interrupt_state().
+ */
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct InterruptState {
+    /// irq line asserted.
+    pub irq_line: bool,
+    /// nmi line asserted.
+    pub nmi_line: bool,
+    /// an irq is requested and not serviced yet (it may be masked by the I flag).
+    pub irq_pending: bool,
+    /// an nmi is latched (or requested) and will be serviced before the next instruction.
+    pub nmi_pending: bool,
+    /// the I flag is set, irqs are masked.
+    pub irq_masked: bool,
+    /// the cpu is halted by STP, only reset() recovers.
+    pub halted: bool,
+    /// the cpu is jammed by a JAM (KIL) opcode, only reset() recovers.
+    pub jammed: bool,
+}
+
+impl Display for InterruptState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let mut v: Vec<&str> = Vec::new();
+        if self.irq_pending {
+            v.push(if self.irq_masked {
+                "IRQ pending (masked)"
+            } else {
+                "IRQ pending"
+            });
+        }
+        if self.nmi_pending {
+            v.push("NMI pending");
+        }
+        if self.halted {
+            v.push("halted");
+        }
+        if self.jammed {
+            v.push("jammed");
+        }
+        if v.is_empty() {
+            v.push("no interrupts pending");
+        }
+        write!(f, "{}", v.join(", "))
+    }
+}
+
+/**
+ * 6502 has 3 vectors (= addresses at which the cpu is directed to perform certain tasks), see Cpu::get_vector() and
+ * Cpu::set_vector().
+ */
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Vectors {
+    /// non maskable interrupt handler, at $fffa.
+    NMI = 0xfffa,
+    /// start address after reset, at $fffc.
+    RESET = 0xfffc,
+    /// irq and BRK handler, at $fffe.
+    IRQ = 0xfffe,
+}
+
+impl Vectors {
+    /**
+     * the address of the vector lsb.
+     */
+    pub fn address(self) -> u16 {
+        self as u16
     }
+}
+
+/**
+ * implements the cpu, generic over the Bus to let memory accesses be monomorphized.
+ *
+ * plain Cpu (or DynCpu) is a Cpu<Box<dyn Bus>>, as returned by new_default().
+ *
+ * a Cpu is Send (buses, memories, devices, hooks, traps and the port handler are required to be Send), so it can be
+ * moved to a worker thread, i.e. by a GUI front-end. it is not Sync, and the Debugger (whose output may be any writer)
+ * is not Send, so it's meant to be created on the thread running the cpu.
+ *
+ * ```
+ * use rv6502emu::bus::DefaultBus;
+ * use rv6502emu::cpu::{Cpu, ResetKind};
+ * use std::thread;
+ *
+ * fn is_send<T: Send>(_: &T) {}
+ *
+ * let mut c = Cpu::new_default(None);
+ * is_send(&c);
+ * is_send(&Cpu::<DefaultBus>::new(DefaultBus::new(rv6502emu::memory::new_default()), None, None));
+ * c.bus.get_memory().load_from_slice(&[0xea; 10], 0x400).unwrap();
+ * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+ * let worker = thread::spawn(move || c.run_until(None, 0, 10).unwrap().instructions);
+ * assert_eq!(worker.join().unwrap(), 10);
+ * ```
+ */
+pub struct Cpu<B: Bus = Box<dyn Bus>> {
+    /// cpu registers.
+    pub regs: Registers,
+
+    /// current cpu cycles.
+    pub cycles: usize,
+
+    /// running under debugger ?
+    debug: bool,
+
+    /// forces run() to exit
+    pub done: bool,
+
+    /// run_realtime() clock in Hz, 0 = unthrottled.
+    realtime_hz: u32,
+
+    /// the bus.
+    pub bus: B,
+
+    /// hooks for the user, in registration order.
+    hooks: Vec<(HookId, HookFilter, Box<dyn CpuHook<B>>)>,
+    /// the id for the next hook installed.
+    next_hook_id: HookId,
+    /// traps, checked before executing the instruction at their address.
+    traps: Vec<(u16, Box<dyn CpuTrap<B>>)>,
+    /// cycles accounted when a trap returns to the caller.
+    trap_cycles: usize,
+    /// set if irq() must be called within the run loop.
+    ///
+    /// > deprecated, use set_irq_line() instead.
+    #[deprecated(note = "use set_irq_line/set_nmi_line")]
+    pub must_trigger_irq: bool,
+    /// set if nmi() must be called within the run loop.
+    ///
+    /// > deprecated, use set_nmi_line() instead.
+    #[deprecated(note = "use set_irq_line/set_nmi_line")]
+    pub must_trigger_nmi: bool,
+    /// irq line state (true = asserted/low), level triggered.
+    irq_line: bool,
+    /// nmi line state (true = asserted/low).
+    nmi_line: bool,
+    /// set on nmi line high-to-low transition, cleared when the nmi is serviced.
+    nmi_latched: bool,
+    /// I flag value to be used on the next interrupt poll (set by CLI/SEI/PLP).
+    poll_i_flag: Option<bool>,
+    /// is there an intewrrupt pending ?
+    irq_pending: bool,
+    /// to handle interrupt return after RTI in certain situations.
+    fix_pc_rti: i8,
+    /// the emulated cpu type, default MOS6502.
+    cpu_type: CpuType,
+    /// set by STP (65C02), the cpu is stopped until reset().
+    halted: bool,
+    /// set by JAM (KIL) opcodes (NMOS), the cpu is stuck until reset().
+    jammed: bool,
+    /// the JAM opcode which jammed the cpu.
+    jam_opcode: u8,
+    /// if set, JAM opcodes are reported as InvalidOpcode errors instead of jamming the cpu.
+    strict_jam: bool,
+    /// if set, branching/jumping to self is reported as a Deadlock error (default).
+    deadlock_detection: bool,
+    /// if set, addresses wrap into the memory size instead of erroring past its end.
+    address_wrapping: bool,
+    /// 'magic' constant for the unstable ANE (XAA) opcode.
+    ane_magic: u8,
+    /// 'magic' constant for the unstable LXA opcode.
+    lxa_magic: u8,
+    /// if set, SHX/SHY/AHX/TAS drop the unstable 'AND (H+1)'.
+    sh_drop_and: bool,
+    /// MOS6510 i/o port data direction register ($0000).
+    port_ddr: u8,
+    /// MOS6510 i/o port data register ($0001).
+    port_data: u8,
+    /// MOS6510 i/o port handler (optional).
+    port_handler: Option<Box<dyn PortHandler>>,
+    /// per-address (executions, cycles) counters, if profiling is enabled.
+    profile: Option<Box<[(u32, u64)]>>,
+    /// per-address read/write counters, if enabled.
+    access_counters: Option<Box<AccessCounters>>,
+    /// executed/read/written bitmaps.
+    coverage: Box<Coverage>,
+    /// coverage tracking enabled/disabled.
+    coverage_enabled: bool,
+    /// (address, old value) of the bytes written by the current step, if the debugger history is enabled.
+    #[cfg(feature = "std")]
+    journal: Option<Vec<(u16, u8)>>,
+    /// set by opcodes which load pc themselves (jumps, taken branches, returns, ...), so it's not advanced after.
+    pc_set: bool,
+    /// byte set by the hook with CpuView::override_read(), replaces the value of the notified read.
+    read_override: Option<u8>,
+    /// read-modify-write instructions perform the dummy write (NMOS) or read (65C02).
+    rmw_dummy_access: bool,
+    /// indexed modes perform the dummy reads.
+    accurate_bus: bool,
+    /// the dummy read of the indexed modes is still to be done for the current instruction.
+    dummy_read_pending: bool,
+    /// the SO pin has been asserted, V is set at the next instruction boundary.
+    so_latched: bool,
+    /// the RDY line, the cpu is stalled while low.
+    rdy: bool,
+    /// ADC/SBC honour the D flag.
+    bcd_enabled: bool,
+    /// serviced interrupts statistics.
+    istats: InterruptTracker,
+}
+
+/**
+ * a cpu with a dynamically dispatched bus, as created by Cpu::new_default().
+ */
+pub type DynCpu = Cpu<Box<dyn Bus>>;
 
+impl<B: Bus + 'static> Cpu<B> {
     /**
      * creates a new cpu instance, with the given Bus attached.
      *
@@ -357,31 +1204,81 @@ impl Cpu {
      *
      * - memory read
      * - memory write
+     * - reset/irq/nmi vector fetch
      * - irq
      * - nmi
      * - brk
+     *
+     * > plain fn callbacks are kept for compatibility, use set_hook() to install a closure or a CpuHook with state.
      */
+    #[allow(deprecated)]
     pub fn new(
-        b: Box<dyn Bus>,
-        cb: Option<fn(c: &mut Cpu, cb: CpuCallbackContext)>,
+        b: B,
+        cb: Option<fn(c: &mut Cpu<B>, cb: CpuCallbackContext)>,
         t: Option<CpuType>,
-    ) -> Cpu {
-        let c = Cpu {
+    ) -> Cpu<B> {
+        let mut c = Cpu {
             regs: Registers::new(),
             cycles: 0,
             bus: b,
-            cb: cb,
+            hooks: Vec::new(),
+            next_hook_id: 0,
+            traps: Vec::new(),
+            trap_cycles: 6,
             done: false,
+            realtime_hz: 0,
             debug: false,
             must_trigger_irq: false,
             must_trigger_nmi: false,
+            irq_line: false,
+            nmi_line: false,
+            nmi_latched: false,
+            poll_i_flag: None,
             irq_pending: false,
             fix_pc_rti: 0,
             cpu_type: t.unwrap_or(CpuType::MOS6502),
+            halted: false,
+            jammed: false,
+            jam_opcode: 0,
+            strict_jam: false,
+            deadlock_detection: true,
+            address_wrapping: false,
+            ane_magic: 0xef,
+            lxa_magic: 0xee,
+            sh_drop_and: false,
+            port_ddr: 0,
+            port_data: 0,
+            port_handler: None,
+            profile: None,
+            access_counters: None,
+            coverage: Box::new(Coverage::new()),
+            coverage_enabled: false,
+            #[cfg(feature = "std")]
+            journal: None,
+            pc_set: false,
+            read_override: None,
+            rmw_dummy_access: true,
+            accurate_bus: false,
+            dummy_read_pending: false,
+            so_latched: false,
+            rdy: true,
+            bcd_enabled: true,
+            istats: InterruptTracker::default(),
         };
-        println!("created new cpu, type={}", c.cpu_type);
+        if let Some(f) = cb {
+            // sugar for an unfiltered hook
+            c.add_hook(HookFilter::default(), FnHook(f));
+        }
+        info!("created new cpu, type={}", c.cpu_type);
         c
     }
+}
+
+impl Cpu {
+    /**
+     * cycles taken by the irq/nmi sequence, accounted in cycles() (BRK takes them from the opcode table).
+     */
+    pub const INTERRUPT_CYCLES: usize = 7;
 
     /**
      * creates a new cpu instance (MOS6502), with the given Bus attached, exposing a Memory.
@@ -391,118 +1288,1044 @@ impl Cpu {
         let b = super::bus::new_default(m);
         Cpu::new(b, cb, Some(CpuType::MOS6502))
     }
+}
 
+impl<B: Bus> Cpu<B> {
     /**
-     * resets the cpu setting all registers to the initial values.
-     *
-     * http://forum.6502.org/viewtopic.php?p=2959
+     * activate logging on stdout through env_logger (max level).
      */
-    pub fn reset(&mut self, start_address: Option<u16>) -> Result<(), CpuError> {
-        let addr: u16;
-        if let Some(a) = start_address {
-            // use the provided address
-            addr = a;
-        } else {
-            // get the start address from reset vector
-            // from https://www.pagetable.com/?p=410
-            addr = self
-                .bus
-                .get_memory()
-                .read_word_le(Vectors::RESET as usize)?;
-        }
+    #[cfg(feature = "std")]
+    pub fn enable_logging(&self, enable: bool) {
+        enable_logging_internal(enable)
+    }
 
-        self.regs = Registers {
-            a: 0,
-            x: 0,
-            y: 0,
-            // I (enable interrupts), and the U flag is always set.
-            p: CpuFlags::U | CpuFlags::I,
-            s: 0xff,
+    /**
+     * call the installed cpu hooks whose filter matches, in registration order.
+     */
+    pub(crate) fn call_callback(
+        &mut self,
+        address: u16,
+        value: u8,
+        access_size: i8,
+        op: CpuOperation,
+    ) {
+        if self.hooks.is_empty() {
+            return;
+        }
 
-            // at reset, we read PC from RESET vector
-            pc: addr,
-        };
-        self.cycles = 7;
-        self.done = false;
-        self.irq_pending = false;
-        self.must_trigger_irq = false;
-        self.must_trigger_nmi = false;
-        self.fix_pc_rti = 0;
-        Ok(())
+        self.dispatch_hooks(CpuCallbackContext {
+            address,
+            access_size,
+            value,
+            operation: op,
+            opcode: None,
+            mnemonic: None,
+            target: None,
+        });
     }
 
     /**
-     * fetch opcode at PC
+     * call installed cpu hooks with Exec for the (decoded) instruction at pc, with its opcode, mnemonic and target address.
      */
-    pub(crate) fn fetch(&mut self) -> Result<u8, CpuError> {
-        let mem = self.bus.get_memory();
-        let b = mem.read_byte(self.regs.pc as usize)?;
-        Ok(b)
+    fn call_exec_callback(&mut self, opcode: u8) {
+        let info = opcodes::opcode_info(opcode, self.cpu_type);
+        let mut ctx = CpuCallbackContext {
+            address: self.regs.pc,
+            access_size: 0,
+            value: 0,
+            operation: CpuOperation::Exec,
+            opcode: Some(opcode),
+            mnemonic: Some(info.mnemonic),
+            target: None,
+        };
+
+        // decoding isn't free, skip it if no hook is interested
+        if !self.hooks.iter().any(|(_, filter, _)| filter.matches(&ctx)) {
+            return;
+        }
+        ctx.target = self
+            .decode_at(self.regs.pc)
+            .ok()
+            .and_then(|i| i.effective_address);
+        self.dispatch_hooks(ctx);
     }
 
     /**
-     * increment pc and the elapsed cycles
+     * run the hooks whose filter matches ctx.
+     */
+    fn dispatch_hooks(&mut self, ctx: CpuCallbackContext) {
+        // the hooks are moved out while running, so they can get a view on the cpu
+        let mut hooks = core::mem::take(&mut self.hooks);
+        for (_, filter, h) in hooks.iter_mut() {
+            if filter.matches(&ctx) {
+                h.on_event(&ctx, &mut CpuView { c: self });
+            }
+        }
+
+        // keep the hooks installed meanwhile (i.e. by a plain fn callback), after the others
+        hooks.append(&mut self.hooks);
+        self.hooks = hooks;
+    }
+
+    /**
+     * call installed cpu hook if any for a read, returns the byte read or the one set by the hook with CpuView::override_read().
+     */
+    pub(crate) fn call_read_callback(&mut self, address: u16, value: u8, op: CpuOperation) -> u8 {
+        self.read_override = None;
+        self.call_callback(address, value, 1, op);
+        self.read_override.take().unwrap_or(value)
+    }
+
+    /**
+     * fetch the address at vector v, a byte at a time, as a read (breakpoints, coverage and hook with CpuOperation::VectorFetch).
+     *
+     * a read breakpoint triggered by the fetch is stored in bp, to be reported once the sequence is complete.
+     */
+    pub(crate) fn fetch_vector(
+        &mut self,
+        d: Option<&Debugger>,
+        v: Vectors,
+        bp: &mut Option<CpuError>,
+    ) -> Result<u16, CpuError> {
+        let mut w: u16 = 0;
+        for i in 0..2 {
+            let address = v.address().wrapping_add(i);
+            let b = self.bus.get_memory().read_byte(address as usize)?;
+            if let Some(dbg) = d {
+                defer_breakpoint(
+                    dbg.handle_rw_breakpoint(self, address, BreakpointType::READ),
+                    bp,
+                )?;
+            }
+            self.cover_access(address, false);
+            let b = self.call_read_callback(address, b, CpuOperation::VectorFetch);
+            w |= (b as u16) << (8 * i);
+        }
+        Ok(w)
+    }
+
+    /**
+     * reads the address at vector v through the bus, as the cpu does on reset, BRK, irq and nmi (hooks are notified with
+     * CpuOperation::VectorFetch).
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, Vectors};
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.set_vector(Vectors::IRQ, 0xc000).unwrap();
+     * assert_eq!(c.get_vector(Vectors::IRQ).unwrap(), 0xc000);
+     * ```
+     */
+    pub fn get_vector(&mut self, v: Vectors) -> Result<u16, CpuError> {
+        self.fetch_vector(None, v, &mut None)
+    }
+
+    /**
+     * writes address to vector v through the bus: rom regions are respected and hooks are notified with CpuOperation::Write.
+     */
+    pub fn set_vector(&mut self, v: Vectors, address: u16) -> Result<(), CpuError> {
+        for (i, b) in address.to_le_bytes().iter().enumerate() {
+            let a = v.address().wrapping_add(i as u16);
+            self.bus.get_memory().write_byte(a as usize, *b)?;
+            self.call_callback(a, *b, 1, CpuOperation::Write);
+        }
+        Ok(())
+    }
+
+    /**
+     * install the hook called *after* memory reads/writes, irq, nmi, brk and before executing each instruction.
+     *
+     * replaces the previous hooks or callback, if any.
+     */
+    pub fn set_hook<H: CpuHook<B> + 'static>(&mut self, hook: H) {
+        self.clear_hook();
+        self.add_hook(HookFilter::default(), hook);
+    }
+
+    /**
+     * install a hook notified of the events matching filter, after the hooks already installed. returns its id, to
+     * remove it with remove_hook().
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, CpuCallbackContext, CpuOperation, CpuView, HookFilter};
+     *
+     * let mut c = Cpu::new_default(None);
+     * let filter = HookFilter {
+     *     operations: vec![CpuOperation::Write],
+     *     range: Some((0xd000, 0xdfff)),
+     * };
+     * let id = c.add_hook(filter, |ctx: &CpuCallbackContext, _: &mut CpuView| {
+     *     println!("i/o write {}", ctx)
+     * });
+     * c.remove_hook(id).unwrap();
+     * assert!(c.remove_hook(id).is_err());
+     * ```
+     */
+    pub fn add_hook<H: CpuHook<B> + 'static>(&mut self, filter: HookFilter, hook: H) -> HookId {
+        let id = self.next_hook_id;
+        self.next_hook_id = self.next_hook_id.wrapping_add(1);
+        self.hooks.push((id, filter, Box::new(hook)));
+        id
+    }
+
+    /**
+     * remove the hook with the given id.
+     */
+    pub fn remove_hook(&mut self, id: HookId) -> Result<(), CpuError> {
+        match self.hooks.iter().position(|(i, _, _)| *i == id) {
+            Some(idx) => {
+                self.hooks.remove(idx);
+                Ok(())
+            }
+            None => Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(format!("hook {} not found!", id)),
+            )),
+        }
+    }
+
+    /**
+     * remove all the installed hooks and callback, if any.
+     */
+    pub fn clear_hook(&mut self) {
+        self.hooks.clear();
+    }
+
+    /**
+     * install a trap at address, called before executing the instruction there (replaces the trap already installed at
+     * address, if any).
+     *
+     * the trap may service the routine natively and return TrapAction::Return, to pop the return address and resume
+     * the caller as RTS does, or TrapAction::Continue to execute the instruction at address.
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, CpuView, ResetKind, TrapAction};
+     *
+     * let mut c = Cpu::new_default(None);
+     * // jsr $ffd2, with the (unexecuted) routine at $ffd2 being a jam
+     * c.bus.get_memory().load_from_slice(&[0x20, 0xd2, 0xff], 0x400).unwrap();
+     * c.bus.get_memory().write_byte(0xffd2, 0x02).unwrap();
+     * c.install_trap(0xffd2, |cpu: &mut CpuView| {
+     *     println!("CHROUT {}", cpu.regs().a as char);
+     *     TrapAction::Return
+     * });
+     * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+     * c.run_until(None, 0, 2).unwrap();
+     * assert_eq!(c.regs.pc, 0x403);
+     * ```
+     */
+    pub fn install_trap<T: CpuTrap<B> + 'static>(&mut self, address: u16, trap: T) {
+        match self.traps.iter_mut().find(|(a, _)| *a == address) {
+            Some(t) => t.1 = Box::new(trap),
+            None => self.traps.push((address, Box::new(trap))),
+        }
+    }
+
+    /**
+     * remove the trap installed at address.
+     */
+    pub fn remove_trap(&mut self, address: u16) -> Result<(), CpuError> {
+        match self.traps.iter().position(|(a, _)| *a == address) {
+            Some(idx) => {
+                self.traps.remove(idx);
+                Ok(())
+            }
+            None => Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                address,
+                Some(format!("no trap at ${:04x}!", address)),
+            )),
+        }
+    }
+
+    /**
+     * sets the cycles accounted when a trap returns to the caller, default is 6 (as RTS).
+     */
+    pub fn set_trap_cycles(&mut self, cycles: usize) {
+        self.trap_cycles = cycles;
+    }
+
+    /**
+     * call the trap installed at pc, if any. returns the cycles to account if it has returned to the caller (pc is
+     * then set to the popped return address), None if the instruction at pc must be executed.
+     */
+    fn call_trap(&mut self, d: Option<&Debugger>) -> Result<Option<usize>, CpuError> {
+        if self.traps.is_empty() {
+            return Ok(None);
+        }
+        let address = self.regs.pc;
+        let idx = match self.traps.iter().position(|(a, _)| *a == address) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+
+        // the trap is moved out while running, so it can get a view on the cpu
+        let (_, mut t) = self.traps.swap_remove(idx);
+        let action = t.on_trap(&mut CpuView { c: self });
+        if !self.traps.iter().any(|(a, _)| *a == address) {
+            // unless it has been replaced meanwhile
+            self.traps.push((address, t));
+        }
+        match action {
+            TrapAction::Continue => Ok(None),
+            TrapAction::Return => {
+                // pop the return address, as RTS (breakpoints, hooks and counters included)
+                let ret = opcodes::pop_word_le(self, d)?;
+                self.set_pc(ret.wrapping_add(1));
+                Ok(Some(self.trap_cycles))
+            }
+        }
+    }
+
+    /**
+     * check if cpu flag is set
+     */
+    pub(crate) fn is_cpu_flag_set(&self, f: CpuFlags) -> bool {
+        if self.regs.p.contains(f) {
+            return true;
+        }
+        false
+    }
+
+    /**
+     * check if arithmetic has to be done in decimal mode (D set, bcd enabled and not a 2A03).
+     */
+    pub(crate) fn is_decimal_mode(&self) -> bool {
+        self.bcd_enabled && self.cpu_type != CpuType::Ricoh2A03 && self.is_cpu_flag_set(CpuFlags::D)
+    }
+
+    /**
+     * set/unset cpu flag
+     */
+    pub(crate) fn set_cpu_flags(&mut self, f: CpuFlags, enable: bool) {
+        self.regs.p.set(f, enable);
+    }
+
+    /**
+     * resets the cpu, starting at start_address or at the RESET vector if None.
+     *
+     * ResetKind::PowerOn sets all registers to the initial values (S=$FF) and restarts the cycle counter at 7.
+     *
+     * ResetKind::Warm behaves as the hardware reset line: A/X/Y are preserved, the three fake pushes
+     * decrement the current S by 3 (so $FC right after a PowerOn reset, the $FD seen on hardware comes from the S=$00 it
+     * usually powers up with, set regs.s to reproduce it), I is set, D is cleared on the 65C02 only
+     * (NMOS leaves it undefined, so it's left untouched), and the 7 reset cycles are added to the counter.
+     * the irq/nmi lines are driven by the devices and keep their state, only the latched nmi edge and the pending
+     * interrupts are dropped: an irq still held is serviced once I is cleared.
+     *
+     * http://forum.6502.org/viewtopic.php?p=2959
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, CpuFlags, ResetKind};
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+     * assert_eq!((c.regs.s, c.cycles()), (0xff, 7));
+     * c.regs.a = 0x42;
+     * c.regs.p.insert(CpuFlags::D);
+     * c.reset(Some(0x400), ResetKind::Warm).unwrap();
+     * assert_eq!((c.regs.a, c.regs.s, c.cycles()), (0x42, 0xfc, 14));
+     * assert!(c.regs.p.contains(CpuFlags::I | CpuFlags::D));
+     * ```
+     */
+    #[allow(deprecated)]
+    pub fn reset(&mut self, start_address: Option<u16>, kind: ResetKind) -> Result<(), CpuError> {
+        let addr: u16;
+        if let Some(a) = start_address {
+            // use the provided address
+            addr = a;
+        } else {
+            // get the start address from reset vector
+            // from https://www.pagetable.com/?p=410
+            addr = self.get_vector(Vectors::RESET)?;
+        }
+
+        match kind {
+            ResetKind::PowerOn => {
+                self.regs = Registers {
+                    a: 0,
+                    x: 0,
+                    y: 0,
+                    // I (enable interrupts), and the U flag is always set.
+                    p: CpuFlags::U | CpuFlags::I,
+                    s: 0xff,
+
+                    // at reset, we read PC from RESET vector
+                    pc: addr,
+                };
+                self.cycles = 7;
+
+                // nothing drives the interrupt lines yet
+                self.irq_line = false;
+                self.nmi_line = false;
+            }
+            ResetKind::Warm => {
+                // the reset sequence performs 3 pushes with the write line held high
+                self.regs.s = self.regs.s.wrapping_sub(3);
+                self.regs.p.insert(CpuFlags::U | CpuFlags::I);
+                if self.cpu_type.is_65c02() {
+                    self.regs.p.remove(CpuFlags::D);
+                }
+                self.regs.pc = addr;
+                self.cycles = self.cycles.wrapping_add(7);
+            }
+        }
+        self.done = false;
+        self.irq_pending = false;
+        self.must_trigger_irq = false;
+        self.must_trigger_nmi = false;
+        self.nmi_latched = false;
+        self.poll_i_flag = None;
+        self.fix_pc_rti = 0;
+        self.halted = false;
+        self.jammed = false;
+        self.pc_set = false;
+        self.dummy_read_pending = self.accurate_bus;
+        self.so_latched = false;
+        self.reset_interrupt_tracking();
+
+        // on reset, the 6510 port pins are all set as inputs
+        self.port_ddr = 0;
+        self.port_data = 0;
+        Ok(())
+    }
+
+    /**
+     * returns the emulated cpu type.
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, CpuType};
+     *
+     * let mut c = Cpu::new_default(None);
+     * assert_eq!(c.cpu_type(), CpuType::MOS6502);
+     * c.set_cpu_type(CpuType::WDC65C02);
+     * assert_eq!(c.cpu_type(), CpuType::WDC65C02);
+     * ```
+     */
+    pub fn cpu_type(&self) -> CpuType {
+        self.cpu_type
+    }
+
+    /**
+     * returns the cycles elapsed since reset() (or reset_cycle_counter()).
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, ResetKind};
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+     * assert_eq!(c.cycles(), 7);
+     * c.reset_cycle_counter();
+     * assert_eq!(c.cycles(), 0);
+     * ```
+     */
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /**
+     * zeroes the cycle counter, i.e. to measure a routine or at the start of a frame.
+     */
+    pub fn reset_cycle_counter(&mut self) {
+        self.cycles = 0;
+    }
+
+    /**
+     * returns a snapshot of the interrupt lines and pending interrupts.
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, ResetKind};
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+     * c.set_irq_line(true);
+     * let s = c.interrupt_state();
+     * assert!(s.irq_line && s.irq_pending && s.irq_masked);
+     * assert!(!s.nmi_pending);
+     * assert_eq!(s.to_string(), "IRQ pending (masked)");
+     * ```
+     */
+    #[allow(deprecated)]
+    pub fn interrupt_state(&self) -> InterruptState {
+        InterruptState {
+            irq_line: self.irq_line,
+            nmi_line: self.nmi_line,
+            irq_pending: self.irq_line || self.irq_pending || self.must_trigger_irq,
+            nmi_pending: self.nmi_latched || self.must_trigger_nmi,
+            irq_masked: self.is_cpu_flag_set(CpuFlags::I),
+            halted: self.halted,
+            jammed: self.jammed,
+        }
+    }
+
+    /**
+     * returns the live stack bytes, from $0100+S+1 to $01ff (the most recently pushed first).
+     *
+     * memory is read directly, no callbacks or breakpoints are triggered. empty if the stack is empty, or if memory
+     * doesn't cover the stack page.
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, ResetKind};
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+     * assert!(c.stack_slice().is_empty());
+     * c.bus.get_memory().write_byte(0x1fe, 0x12).unwrap();
+     * c.bus.get_memory().write_byte(0x1ff, 0x34).unwrap();
+     * c.regs.s = 0xfd;
+     * assert_eq!(c.stack_slice(), vec![0x12, 0x34]);
+     * ```
+     */
+    pub fn stack_slice(&mut self) -> Vec<u8> {
+        let start = 0x100 + self.regs.s as usize + 1;
+        let mut v = vec![0u8; 0x200 - start];
+        match self.bus.get_memory().read_range(start, &mut v) {
+            Ok(()) => v,
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /**
+     * decode the instruction at address as the run loop does, with its effective address for the current registers
+     * (where it would read, write or jump if executed now).
+     *
+     * memory is peeked: no cpu or memory state is changed, no callbacks, breakpoints or device side effects are
+     * triggered.
+     *
+     * ```
+     * use rv6502emu::cpu::Cpu;
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.bus.get_memory().load_from_slice(&[0xb1, 0x20], 0x400).unwrap();
+     * c.bus.get_memory().write_word_le(0x20, 0x1234).unwrap();
+     * c.regs.y = 2;
+     * let i = c.decode_at(0x400).unwrap();
+     * assert_eq!((i.mnemonic, i.size, i.base_cycles), ("lda", 2, 5));
+     * assert_eq!(i.operand_bytes, vec![0x20]);
+     * assert_eq!(i.effective_address, Some(0x1236));
+     * ```
+     */
+    pub fn decode_at(&mut self, address: u16) -> Result<disasm::DecodedInstruction, CpuError> {
+        let i = self.decode_instruction_at(address)?;
+        let info = opcode_info(i.bytes[0], self.cpu_type);
+        let effective_address =
+            addressing_modes::effective_address(self, address, i.mode).unwrap_or(None);
+        Ok(disasm::DecodedInstruction {
+            address,
+            opcode: info.opcode,
+            mnemonic: info.mnemonic,
+            mode: info.addressing_mode,
+            size: info.bytes,
+            base_cycles: info.base_cycles,
+            operand_bytes: i.bytes[1..].to_vec(),
+            effective_address,
+            is_undocumented: info.undocumented,
+        })
+    }
+
+    /**
+     * disassemble the instruction at address, returns the instruction text (i.e. "LDA ($20), Y") and its size.
+     *
+     * memory is read directly: pc is not changed, no callbacks or breakpoints are triggered.
+     *
+     * ```
+     * use rv6502emu::cpu::Cpu;
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.bus.get_memory().write_byte(0x400, 0xb1).unwrap();
+     * c.bus.get_memory().write_byte(0x401, 0x20).unwrap();
+     * assert_eq!(c.disassemble_at(0x400).unwrap(), (String::from("LDA ($20), Y"), 2));
+     *
+     * // operand bytes past the end of memory
+     * c.bus.get_memory().write_byte(0xffff, 0xad).unwrap();
+     * assert!(c.disassemble_at(0xffff).is_err());
+     * ```
+     */
+    pub fn disassemble_at(&mut self, address: u16) -> Result<(String, u8), CpuError> {
+        let i = self.decode_instruction_at(address)?;
+        let s = format!("{} {}", i.mnemonic.to_uppercase(), i.operand_text());
+        Ok((String::from(s.trim_end()), i.bytes.len() as u8))
+    }
+
+    /**
+     * peek the whole instruction at address and decode it, errors if it doesn't fit in memory.
+     */
+    fn decode_instruction_at(&mut self, address: u16) -> Result<disasm::Instruction, CpuError> {
+        let cpu_type = self.cpu_type;
+        let mem = self.bus.get_memory();
+        let mut m = [0u8; 1];
+        mem.read_range(address as usize, &mut m)?;
+
+        // the operand bytes must fit too
+        let info = opcode_info(m[0], cpu_type);
+        cpu_error::check_opcode_boundaries(
+            mem.get_size(),
+            address as usize,
+            info.opcode,
+            info.addressing_mode,
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+        let mut m = vec![0; info.bytes as usize];
+        mem.read_range(address as usize, &mut m)?;
+        disasm::decode(&m, address, cpu_type).ok_or_else(|| {
+            CpuError::new_default(CpuErrorType::MemoryRead, address, None)
+                .with_opcode(info.opcode, info.addressing_mode)
+        })
+    }
+
+    /**
+     * check if the emulated cpu is a 65C02 (either, it's an NMOS 6502 variant).
+     */
+    pub(crate) fn is_65c02(&self) -> bool {
+        self.cpu_type.is_65c02()
+    }
+
+    /**
+     * sets the handler for the MOS6510 i/o port.
+     */
+    pub fn set_port_handler(&mut self, h: Box<dyn PortHandler>) {
+        self.port_handler = Some(h);
+    }
+
+    /**
+     * on MOS6510, handles reads from the i/o port at $0000/$0001. returns None if the access must go to memory.
+     */
+    pub(crate) fn port_read(&mut self, address: u16) -> Option<u8> {
+        if self.cpu_type != CpuType::MOS6510 {
+            return None;
+        }
+        match address {
+            0 => Some(self.port_ddr),
+            1 => {
+                // output bits come from the data register, input bits from the handler
+                let input = match self.port_handler.as_mut() {
+                    Some(h) => h.read(self.port_ddr),
+                    None => self.port_data,
+                };
+                Some((self.port_data & self.port_ddr) | (input & !self.port_ddr))
+            }
+            _ => None,
+        }
+    }
+
+    /**
+     * on MOS6510, handles writes to the i/o port at $0000/$0001. returns false if the access must go to memory.
+     */
+    pub(crate) fn port_write(&mut self, address: u16, b: u8) -> bool {
+        if self.cpu_type != CpuType::MOS6510 {
+            return false;
+        }
+        match address {
+            0 => self.port_ddr = b,
+            1 => self.port_data = b,
+            _ => return false,
+        }
+        if let Some(h) = self.port_handler.as_mut() {
+            h.write(self.port_ddr, self.port_data);
+        }
+        true
+    }
+
+    /**
+     * fetch opcode at PC
+     */
+    pub(crate) fn fetch(&mut self) -> Result<u8, CpuError> {
+        let a = self.mem_address(self.regs.pc);
+        let mem = self.bus.get_memory();
+        let b = mem.read_byte(a)?;
+        Ok(b)
+    }
+
+    /**
+     * the memory offset for address, wrapped into the memory size with set_address_wrapping().
+     */
+    pub(crate) fn mem_address(&mut self, address: u16) -> usize {
+        if !self.address_wrapping {
+            return address as usize;
+        }
+        let size = self.bus.get_memory().get_size();
+        if size == 0 {
+            return address as usize;
+        }
+        address as usize % size
+    }
+
+    /**
+     * increment pc and the elapsed cycles
      */
     fn inc_pc(&mut self, instr_size: u16, opcode_cycles: usize) {
-        // advance pc and increment the elapsed cycles
-        self.regs.pc = self.regs.pc.wrapping_add(instr_size);
+        // advance pc (unless the opcode has set it) and increment the elapsed cycles
+        if self.pc_set {
+            self.pc_set = false;
+        } else {
+            self.regs.pc = self.regs.pc.wrapping_add(instr_size);
+        }
         self.cycles = self.cycles.wrapping_add(opcode_cycles);
+        self.dummy_read_pending = self.accurate_bus;
+    }
+
+    /**
+     * returns true (once per instruction) if the indexed modes must do their dummy read.
+     */
+    pub(crate) fn take_dummy_read(&mut self) -> bool {
+        let r = self.dummy_read_pending;
+        self.dummy_read_pending = false;
+        r
+    }
+
+    /**
+     * set pc from an opcode, the run loop won't advance it past the instruction.
+     */
+    pub(crate) fn set_pc(&mut self, address: u16) {
+        self.regs.pc = address;
+        self.pc_set = true;
+    }
+
+    /**
+     * run the cpu for the given cycles, optionally with a debugger attached.
+     *
+     * pass 0 to run indefinitely. returns a Halted error after STP, while a JAM (KIL) opcode just stops
+     * (check is_jammed(), or use run_until() to get the StopReason).
+     *
+     * > note that reset() must be called first to set the start address !
+     */
+    pub fn run(&mut self, debugger: Option<&mut Debugger>, cycles: usize) -> Result<(), CpuError> {
+        match self.run_until(debugger, cycles, 0)?.reason {
+            StopReason::Halted => Err(CpuError::new_default(
+                CpuErrorType::Halted,
+                self.regs.pc,
+                None,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * run whole instructions until the cycles budget is consumed, i.e. to sync with a frame loop, without a debugger.
+     *
+     * the last instruction may exceed the budget: the returned cycles are exactly what has been added to cycles(), and the
+     * overshoot is meant to be subtracted from the next budget. no state is kept between calls, a budget of 0 runs nothing.
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, ResetKind};
+     *
+     * // inx, jmp $0400
+     * let mut c = Cpu::new_default(None);
+     * c.bus.get_memory().load_from_slice(&[0xe8, 0x4c, 0x00, 0x04], 0x400).unwrap();
+     * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+     * let mut carry = 0;
+     * for _ in 0..10 {
+     *     // 1 MHz at 50 Hz
+     *     let r = c.run_cycles(20000 - carry).unwrap();
+     *     carry = r.overshoot;
+     * }
+     * ```
+     */
+    pub fn run_cycles(&mut self, budget: u64) -> Result<CyclesRun, CpuError> {
+        if budget == 0 {
+            return Ok(CyclesRun {
+                cycles: 0,
+                overshoot: 0,
+                reason: StopReason::CyclesReached { overshoot: 0 },
+            });
+        }
+        let r = self.run_until(None, budget as usize, 0)?;
+        let overshoot = match r.reason {
+            StopReason::CyclesReached { overshoot } => overshoot as u64,
+            _ => 0,
+        };
+        Ok(CyclesRun {
+            cycles: r.cycles as u64,
+            overshoot,
+            reason: r.reason,
+        })
+    }
+
+    /**
+     * run the cpu paced to the wall clock at freq_hz (i.e. 1_000_000 for an authentic 1 MHz), optionally with a debugger
+     * attached, in cycle slices sized to slice: after each one it sleeps (then spins) until the time the executed cycles
+     * should have taken.
+     *
+     * pacing is measured from the start of the run, so rounding and sleep jitter don't accumulate. lagging more than
+     * 10 slices behind (i.e. stopped in the debugger) restarts it from the current time instead of running flat out
+     * to catch up. set_realtime_frequency() changes the clock on the fly (i.e. from a hook, for a turbo toggle).
+     *
+     * returns when a slice stops for anything else than its budget (done set, a breakpoint, STP, JAM), with the
+     * totals of the whole run.
+     *
+     * > note that reset() must be called first to set the start address !
+     */
+    #[cfg(feature = "std")]
+    pub fn run_realtime(
+        &mut self,
+        freq_hz: u32,
+        slice: Duration,
+        mut debugger: Option<&mut Debugger>,
+    ) -> Result<RunResult, CpuError> {
+        self.realtime_hz = freq_hz;
+        let mut total = RunResult {
+            reason: StopReason::Stopped,
+            cycles: 0,
+            instructions: 0,
+        };
+        let mut hz = freq_hz;
+        let mut start = Instant::now();
+        let mut paced_cycles: u64 = 0;
+        loop {
+            if self.realtime_hz != hz {
+                // clock changed, restart pacing
+                hz = self.realtime_hz;
+                start = Instant::now();
+                paced_cycles = 0;
+            }
+            let budget = if hz == 0 {
+                UNTHROTTLED_SLICE_CYCLES
+            } else {
+                core::cmp::max(1, (hz as f64 * slice.as_secs_f64()) as usize)
+            };
+            let r = self.run_until(debugger.as_deref_mut(), budget, 0)?;
+            total.cycles += r.cycles;
+            total.instructions += r.instructions;
+            let elapsed = match r.reason {
+                StopReason::CyclesReached { .. } => r.cycles,
+                StopReason::Stalled { cycles } => r.cycles + cycles,
+                reason => {
+                    total.reason = reason;
+                    return Ok(total);
+                }
+            };
+            if hz == 0 {
+                continue;
+            }
+
+            paced_cycles += elapsed as u64;
+            let target = start + Duration::from_secs_f64(paced_cycles as f64 / hz as f64);
+            let now = Instant::now();
+            if now < target {
+                let left = target - now;
+                if left > REALTIME_SPIN {
+                    std::thread::sleep(left - REALTIME_SPIN);
+                }
+                while Instant::now() < target {
+                    core::hint::spin_loop();
+                }
+            } else if now - target > slice * 10 {
+                start = now;
+                paced_cycles = 0;
+            }
+        }
+    }
+
+    /**
+     * changes the run_realtime() clock, effective from the next slice. 0 runs unthrottled.
+     */
+    pub fn set_realtime_frequency(&mut self, freq_hz: u32) {
+        self.realtime_hz = freq_hz;
+    }
+
+    /**
+     * returns the run_realtime() clock in Hz.
+     */
+    pub fn realtime_frequency(&self) -> u32 {
+        self.realtime_hz
+    }
+
+    /**
+     * run the cpu without debugger until an instruction leaves pc unchanged (a jump or branch to itself, as the Klaus
+     * test roms do to signal success or failure) or max_cycles (0 = no limit) is reached.
+     *
+     * deadlock detection is disabled meanwhile (and restored after), WAI waiting for an interrupt, STP and JAM are
+     * reported as such rather than as self jumps.
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, ResetKind, TrapReason};
+     *
+     * let mut c = Cpu::new_default(None);
+     * // ldx #$03, dex, bne *-1, jmp *
+     * c.bus
+     *     .get_memory()
+     *     .load_from_slice(&[0xa2, 0x03, 0xca, 0xd0, 0xfd, 0x4c, 0x05, 0x04], 0x400)
+     *     .unwrap();
+     * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+     * let r = c.run_until_trap(0).unwrap();
+     * assert_eq!((r.reason, r.pc), (TrapReason::SelfJump, 0x405));
+     * ```
+     *
+     * > note that reset() must be called first to set the start address !
+     */
+    pub fn run_until_trap(&mut self, max_cycles: usize) -> Result<TrapResult, CpuError> {
+        let deadlock_detection = self.deadlock_detection;
+        self.deadlock_detection = false;
+        let res = self.run_until_trap_internal(max_cycles);
+        self.deadlock_detection = deadlock_detection;
+        res
+    }
+
+    /**
+     * internal, run_until_trap() with deadlock detection disabled.
+     */
+    fn run_until_trap_internal(&mut self, max_cycles: usize) -> Result<TrapResult, CpuError> {
+        // a passive debugger, so to step through the fast loop
+        let mut dbg = Debugger::new_detached();
+        let mut run_cycles: usize = 0;
+        let reason = loop {
+            let pc = self.regs.pc;
+            let r = self.run_until(Some(&mut dbg), 0, 1)?;
+            run_cycles = run_cycles.wrapping_add(r.cycles);
+            match r.reason {
+                StopReason::InstructionsReached => (),
+                StopReason::Halted => break TrapReason::Halted,
+                StopReason::Jammed { opcode, pc: _ } => break TrapReason::Jammed { opcode },
+                _ => break TrapReason::Stopped,
+            }
+            if self.regs.pc == pc {
+                // WAI stays on itself until an interrupt comes
+                let mut b = [0u8; 1];
+                let is_wai = self.cpu_type.has_wai_stp()
+                    && self
+                        .bus
+                        .get_memory()
+                        .read_range(pc as usize, &mut b)
+                        .is_ok()
+                    && b[0] == 0xcb;
+                break if is_wai {
+                    TrapReason::Waiting
+                } else {
+                    TrapReason::SelfJump
+                };
+            }
+            if max_cycles != 0 && run_cycles >= max_cycles {
+                break TrapReason::CyclesReached;
+            }
+        };
+        Ok(TrapResult {
+            reason,
+            pc: self.regs.pc,
+            regs: self.regs,
+            cycles: run_cycles,
+        })
     }
 
     /**
-     * run the cpu for the given cycles, optionally with a debugger attached.
+     * run the cpu until the cycles or instructions budget is reached (0 = no limit), optionally with a debugger attached.
      *
-     * pass 0 to run indefinitely.
+     * the last instruction may overshoot the cycles budget, the overshoot is reported to be carried into the next slice.
+     * with the debugger disabled, a triggered breakpoint returns StopReason::Breakpoint and the next call resumes from
+     * there (the breakpoint does not trigger again on the first instruction). unrecoverable errors (i.e. a read past the end
+     * of memory, a deadlock or an invalid opcode) are returned as Err, unless the debugger is enabled (it stops at the prompt).
      *
      * > note that reset() must be called first to set the start address !
      */
-    pub fn run(&mut self, debugger: Option<&mut Debugger>, cycles: usize) -> Result<(), CpuError> {
+    #[allow(deprecated)]
+    pub fn run_until(
+        &mut self,
+        debugger: Option<&mut Debugger>,
+        cycles: usize,
+        instructions: usize,
+    ) -> Result<RunResult, CpuError> {
         let mut bp_rw_triggered = false;
         let mut instr_size: i8 = 0;
-        // construct an empty, disabled, debugger to use when None is passed in
-        let mut empty_dbg = Debugger::new(false);
+        // construct an empty, disabled, debugger to use when None is passed in (its output goes to the log)
+        let mut empty_dbg = Debugger::new_detached();
         let dbg = debugger.unwrap_or(&mut empty_dbg);
         if dbg.enabled {
             self.debug = true;
         }
 
+        // nothing to check per instruction, use the fast loop
+        if !self.debug && self.hooks.is_empty() && !log_enabled() && dbg.is_passive() {
+            dbg.resume_pc = None;
+            return self.run_fast(cycles, instructions);
+        }
+
         let mut silence_output = false;
         let mut is_error = false;
         let mut opcode_cycles: usize = 0;
         let mut run_cycles: usize = 0;
+        let mut run_instructions: usize = 0;
+
+        // resuming from a breakpoint, do not trigger it again
+        let mut resuming = dbg.resume_pc.take() == Some(self.regs.pc);
+
         // loop
-        'interpreter: loop {
+        let reason = 'interpreter: loop {
+            dbg.bp_suspended = resuming;
+            if self.halted || self.jammed {
+                // STP or JAM has been executed, only reset() recovers
+                if !self.debug {
+                    break 'interpreter self.stuck_reason();
+                }
+
+                // let the debugger in (i.e. to reset), but do not step
+                dbg.going = false;
+                match dbg.parse_cmd_stdin(self) {
+                    Err(_) => {
+                        break 'interpreter StopReason::Stopped;
+                    }
+                    Ok((a, _)) => {
+                        if a.eq("q") {
+                            break 'interpreter StopReason::Stopped;
+                        }
+                        if a.eq("p") && self.halted {
+                            dbg_println!(dbg, "cpu is halted (STP), reset to resume.");
+                        }
+                        if a.eq("p") && self.jammed {
+                            dbg_println!(dbg, "cpu is jammed (KIL), reset to resume.");
+                        }
+                    }
+                };
+                continue 'interpreter;
+            }
+            if !self.rdy {
+                // stalled before executing the instruction at pc, resume from here
+                if resuming {
+                    dbg.resume_pc = Some(self.regs.pc);
+                }
+                break 'interpreter StopReason::Stalled {
+                    cycles: cycles.saturating_sub(run_cycles),
+                };
+            }
+
             // fetch
             let b = self.fetch()?;
-            let (opcode_f, in_cycles, add_extra_cycle_on_page_crossing, mrk) =
-                if self.cpu_type == CpuType::MOS6502 {
-                    opcodes::OPCODE_MATRIX[b as usize]
-                } else {
-                    opcodes::OPCODE_MATRIX_65C02[b as usize]
-                };
+            let &OpcodeEntry {
+                f: opcode_f,
+                cycles: in_cycles,
+                extra_cycle_on_page_crossing: add_extra_cycle_on_page_crossing,
+                mrk,
+            } = opcodes::opcode_entry(b, self.cpu_type);
             if !is_error {
                 if !silence_output && dbg.show_registers_before_opcode {
                     if log_enabled() {
                         // show registers
-                        debug_out_registers(self);
+                        debug_out_registers(dbg, self);
                     }
                 }
 
-                // check boundaries
-                match cpu_error::check_opcode_boundaries(
-                    self.bus.get_memory().get_size(),
-                    self.regs.pc as usize,
-                    mrk.id,
-                    CpuErrorType::MemoryRead,
-                    None,
-                ) {
+                // check boundaries (nothing to check if the addresses wrap)
+                let bounds = if self.address_wrapping {
+                    Ok(())
+                } else {
+                    cpu_error::check_opcode_boundaries(
+                        self.bus.get_memory().get_size(),
+                        self.regs.pc as usize,
+                        b,
+                        mrk.id,
+                        CpuErrorType::MemoryRead,
+                        None,
+                    )
+                };
+                match bounds {
                     Err(e) => {
-                        println!("{}", e);
                         if !self.debug {
-                            // unrecoverable
-                            break 'interpreter;
+                            // unrecoverable, returned to the caller
+                            dbg.bp_suspended = false;
+                            return Err(e);
                         } else {
                             // either, this will stop in the debugger
+                            dbg_println!(dbg, "{}", e);
                             dbg.going = false;
                             is_error = true;
                             continue 'interpreter;
@@ -522,12 +2345,13 @@ impl Cpu {
                     silence_output, // quiet
                 ) {
                     Err(e) => {
-                        println!("{}", e);
                         if !self.debug {
-                            // unrecoverable
-                            break 'interpreter;
+                            // unrecoverable, returned to the caller
+                            dbg.bp_suspended = false;
+                            return Err(e.with_opcode(b, mrk.id));
                         } else {
                             // either, this will stop in the debugger
+                            dbg_println!(dbg, "{}", e.with_opcode(b, mrk.id));
                             dbg.going = false;
                             is_error = true;
                             continue 'interpreter;
@@ -539,11 +2363,11 @@ impl Cpu {
                 };
 
                 // call callback if any
-                self.call_callback(self.regs.pc, 0, 0, CpuOperation::Exec);
+                self.call_exec_callback(b);
                 // check if done has been set
                 if self.done {
                     // exiting
-                    break 'interpreter;
+                    break 'interpreter StopReason::Stopped;
                 }
 
                 // check if irq or nmi has to be triggered
@@ -551,7 +2375,9 @@ impl Cpu {
                     // trigger irq or nmi
                     if self.must_trigger_nmi {
                         self.fix_pc_rti = instr_size;
+                        let c0 = self.cycles;
                         self.nmi(Some(dbg))?;
+                        run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
                         self.must_trigger_nmi = false;
                         if self.must_trigger_irq {
                             // there's an irq pending, CLI opcode will detect it
@@ -562,29 +2388,46 @@ impl Cpu {
                     }
                     if self.must_trigger_irq {
                         self.fix_pc_rti = instr_size;
+                        let c0 = self.cycles;
                         self.irq(Some(dbg))?;
+                        run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
                         self.must_trigger_irq = false;
                         self.must_trigger_nmi = false;
                         continue 'interpreter;
                     }
                 }
 
-                // check if we have an exec breakpoint at pc
-                if self.debug {
-                    match dbg.has_enabled_breakpoint(
-                        self,
-                        self.regs.pc,
-                        BreakpointType::EXEC | BreakpointType::NMI | BreakpointType::IRQ,
-                    ) {
-                        None => (),
-                        Some(idx) => {
-                            dbg.going = false;
-                            if !silence_output {
-                                println!("breakpoint {} triggered!", idx);
+                // poll the irq/nmi lines
+                let c0 = self.cycles;
+                if self.poll_interrupt_lines(Some(dbg))? {
+                    run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
+                    continue 'interpreter;
+                }
+
+                // check if we have an exec breakpoint at pc, or an opcode breakpoint
+                let interrupt_bp = dbg.take_interrupt_breakpoint();
+                match interrupt_bp
+                    .or_else(|| {
+                        dbg.has_enabled_breakpoint(self, self.regs.pc, BreakpointType::EXEC)
+                    })
+                    .or_else(|| dbg.has_enabled_opcode_breakpoint(self, b))
+                {
+                    None => (),
+                    Some(idx) => {
+                        let user_bp = dbg.breakpoint_triggered(idx as usize);
+                        if !self.debug {
+                            // return to the caller
+                            break 'interpreter dbg.stop_reason(self, user_bp, idx);
+                        }
+                        if user_bp && !silence_output {
+                            dbg_println!(dbg, "breakpoint {} triggered!", idx);
+                            if interrupt_bp.is_some() {
+                                dbg_println!(dbg, "{}", dbg.interrupt_context().unwrap());
                             }
+                            dbg.queue_breakpoint_commands(idx as usize);
                         }
-                    };
-                }
+                    }
+                };
             } else {
                 // we had an error, will break in the debugger below
                 is_error = false;
@@ -598,7 +2441,7 @@ impl Cpu {
                     match dbg.parse_cmd_stdin(self) {
                         Err(_) => {
                             // io error, something's broken really bad .... break
-                            break 'interpreter;
+                            break 'interpreter StopReason::Stopped;
                         }
                         Ok((a, b)) => {
                             cmd = a;
@@ -610,39 +2453,78 @@ impl Cpu {
             match cmd.as_ref() {
                 "p" => {
                     silence_output = false;
+
+                    // check if we're stepping out and this is the RTS/RTI at the original stack depth
+                    let stop_after_instr = dbg.is_step_out_done(self, b);
+                    let instr_pc = self.regs.pc;
+                    let p_before = self.regs.p;
                     if !bp_rw_triggered {
+                        // trace, if active
+                        if let Err(e) = dbg.trace_instruction(self) {
+                            dbg_println!(dbg, "{}, trace stopped.", e);
+                            let _ = dbg.stop_trace();
+                        }
+
                         // execute decoded instruction
-                        let _ = match opcode_f(
-                            self,
-                            Some(dbg),
-                            b, // the opcode byte
-                            in_cycles,
-                            add_extra_cycle_on_page_crossing,
-                            false, // decode only
-                            true,  // quiet, do not print instruction again
-                        ) {
+                        dbg.history_begin(self);
+                        let res = match self.call_trap(Some(dbg)) {
+                            // serviced by the trap, returned to the caller
+                            Ok(Some(trap_cycles)) => Ok((0, trap_cycles)),
+                            Ok(None) => opcode_f(
+                                self,
+                                Some(dbg),
+                                b, // the opcode byte
+                                in_cycles,
+                                add_extra_cycle_on_page_crossing,
+                                false, // decode only
+                                true,  // quiet, do not print instruction again
+                            ),
+                            Err(e) => Err(e),
+                        };
+                        dbg.history_end(self);
+                        let _ = match res {
                             Ok((_instr_size, _out_cycles)) => {
                                 instr_size = _instr_size;
                                 opcode_cycles = _out_cycles;
+                                self.profile_instruction(instr_pc, opcode_cycles);
+                                self.cover_exec(instr_pc, mrk.id.instr_size());
                             }
                             Err(e) => {
                                 if e.t == CpuErrorType::RwBreakpoint {
                                     // an r/w breakpoint has triggered, opcode has not executed.
+                                    if self.pc_set {
+                                        // unless it completed loading pc (BRK reports its breakpoints at the end), do not advance it then
+                                        self.pc_set = false;
+                                        instr_size = 0;
+                                        opcode_cycles = in_cycles;
+                                    }
+                                    let user_bp = dbg.breakpoint_triggered(e.bp_idx as usize);
+                                    if !self.debug {
+                                        // return to the caller
+                                        break 'interpreter dbg
+                                            .stop_reason(self, user_bp, e.bp_idx);
+                                    }
                                     if !silence_output {
-                                        println!("R/W breakpoint {} triggered!", e.bp_idx);
+                                        dbg_println!(dbg, "R/W breakpoint {} triggered!", e.bp_idx);
+                                        if let Some(m) = &e.msg {
+                                            dbg_println!(dbg, "{}", m);
+                                        }
+                                        if user_bp {
+                                            dbg.queue_breakpoint_commands(e.bp_idx as usize);
+                                        }
                                     }
-                                    dbg.going = false;
                                     bp_rw_triggered = true;
                                     is_error = true;
                                     continue 'interpreter;
                                 } else {
                                     // report error and break
-                                    println!("{}", e);
                                     if !self.debug {
-                                        // unrecoverable
-                                        break;
+                                        // unrecoverable, returned to the caller
+                                        dbg.bp_suspended = false;
+                                        return Err(e.with_opcode(b, mrk.id));
                                     } else {
                                         // either, this will stop in the debugger
+                                        dbg_println!(dbg, "{}", e.with_opcode(b, mrk.id));
                                         dbg.going = false;
                                         is_error = true;
                                         continue 'interpreter;
@@ -656,24 +2538,54 @@ impl Cpu {
                         is_error = false;
                     }
 
+                    if stop_after_instr {
+                        // step out done, stop in the debugger
+                        dbg.going = false;
+                    }
+
                     // step, advance pc and increment the elapsed cycles
                     self.inc_pc(instr_size as u16, opcode_cycles);
                     run_cycles = run_cycles.wrapping_add(opcode_cycles);
+                    run_instructions += 1;
+                    resuming = false;
+
+                    // check if the instruction changed a flag watched by a flag breakpoint
+                    if let Some(idx) = dbg.has_enabled_flag_breakpoint(self, instr_pc, p_before) {
+                        let user_bp = dbg.breakpoint_triggered(idx as usize);
+                        if !self.debug {
+                            // return to the caller, the instruction already executed so there's nothing to skip when resuming
+                            let reason = dbg.stop_reason(self, user_bp, idx);
+                            dbg.resume_pc = None;
+                            break 'interpreter reason;
+                        }
+                        if !silence_output {
+                            dbg_println!(dbg, "flag breakpoint {} triggered!", idx);
+                            dbg_println!(dbg, "{}", dbg.flag_change().unwrap());
+                            dbg.queue_breakpoint_commands(idx as usize);
+                        }
+                    }
                     if cycles != 0 && run_cycles >= cycles {
                         // we're done
-                        break 'interpreter;
+                        break 'interpreter StopReason::CyclesReached {
+                            overshoot: run_cycles - cycles,
+                        };
+                    }
+                    if instructions != 0 && run_instructions >= instructions {
+                        break 'interpreter StopReason::InstructionsReached;
                     }
 
                     // finally recheck if there was a pending irq re-enabled by CLI
                     if self.must_trigger_irq {
+                        let c0 = self.cycles;
                         self.irq(Some(dbg))?;
+                        run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
                         self.must_trigger_irq = false;
                         self.must_trigger_nmi = false;
                     }
                 }
                 "q" => {
                     // gracefully exit
-                    break 'interpreter;
+                    break 'interpreter StopReason::Stopped;
                 }
                 "*" => {
                     silence_output = true;
@@ -681,80 +2593,575 @@ impl Cpu {
                 }
                 _ => {}
             }
+        };
+        dbg.bp_suspended = false;
+        Ok(RunResult {
+            reason,
+            cycles: run_cycles,
+            instructions: run_instructions,
+        })
+    }
+
+    /**
+     * run_until() without debugger, hook and logging: no breakpoints checks, no callbacks and no decode pass, errors are
+     * returned to the caller.
+     */
+    #[allow(deprecated)]
+    fn run_fast(&mut self, cycles: usize, instructions: usize) -> Result<RunResult, CpuError> {
+        let mut run_cycles: usize = 0;
+        let mut run_instructions: usize = 0;
+
+        // with less than 64k, opcodes may cross the end of memory (unless the addresses wrap)
+        let mem_size = self.bus.get_memory().get_size();
+        let check_boundaries = mem_size < 0x10000 && !self.address_wrapping;
+
+        let reason = 'interpreter: loop {
+            if self.halted || self.jammed {
+                break 'interpreter self.stuck_reason();
+            }
+            if self.done {
+                break 'interpreter StopReason::Stopped;
+            }
+            if !self.rdy {
+                break 'interpreter StopReason::Stalled {
+                    cycles: cycles.saturating_sub(run_cycles),
+                };
+            }
+
+            // fetch
+            let b = self.fetch()?;
+            let &OpcodeEntry {
+                f: opcode_f,
+                cycles: in_cycles,
+                extra_cycle_on_page_crossing: add_extra_cycle_on_page_crossing,
+                mrk,
+            } = opcodes::opcode_entry(b, self.cpu_type);
+            if check_boundaries {
+                if let Err(e) = cpu_error::check_opcode_boundaries(
+                    mem_size,
+                    self.regs.pc as usize,
+                    b,
+                    mrk.id,
+                    CpuErrorType::MemoryRead,
+                    None,
+                ) {
+                    // unrecoverable
+                    return Err(e);
+                }
+            }
+
+            // check if irq or nmi has to be triggered
+            if self.must_trigger_nmi {
+                self.fix_pc_rti = mrk.id.instr_size() as i8;
+                let c0 = self.cycles;
+                self.nmi(None)?;
+                run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
+                self.must_trigger_nmi = false;
+                if self.must_trigger_irq {
+                    // there's an irq pending, CLI opcode will detect it
+                    self.irq_pending = true;
+                }
+                self.must_trigger_irq = false;
+                continue 'interpreter;
+            }
+            if self.must_trigger_irq {
+                self.fix_pc_rti = mrk.id.instr_size() as i8;
+                let c0 = self.cycles;
+                self.irq(None)?;
+                run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
+                self.must_trigger_irq = false;
+                continue 'interpreter;
+            }
+
+            // poll the irq/nmi lines
+            let c0 = self.cycles;
+            if self.poll_interrupt_lines(None)? {
+                run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
+                continue 'interpreter;
+            }
+
+            // execute
+            let instr_pc = self.regs.pc;
+            let res = match self.call_trap(None) {
+                // serviced by the trap, returned to the caller
+                Ok(Some(trap_cycles)) => Ok((0, trap_cycles)),
+                Ok(None) => opcode_f(
+                    self,
+                    None,
+                    b, // the opcode byte
+                    in_cycles,
+                    add_extra_cycle_on_page_crossing,
+                    false, // decode only
+                    true,  // quiet
+                ),
+                Err(e) => Err(e),
+            };
+            let (instr_size, opcode_cycles) = match res {
+                Ok(r) => r,
+                Err(e) => {
+                    // unrecoverable
+                    return Err(e.with_opcode(b, mrk.id));
+                }
+            };
+            self.profile_instruction(instr_pc, opcode_cycles);
+            self.cover_exec(instr_pc, mrk.id.instr_size());
+
+            // step, advance pc and increment the elapsed cycles
+            self.inc_pc(instr_size as u16, opcode_cycles);
+            run_cycles = run_cycles.wrapping_add(opcode_cycles);
+            run_instructions += 1;
+            if cycles != 0 && run_cycles >= cycles {
+                break 'interpreter StopReason::CyclesReached {
+                    overshoot: run_cycles - cycles,
+                };
+            }
+            if instructions != 0 && run_instructions >= instructions {
+                break 'interpreter StopReason::InstructionsReached;
+            }
+
+            // finally recheck if there was a pending irq re-enabled by CLI
+            if self.must_trigger_irq {
+                let c0 = self.cycles;
+                self.irq(None)?;
+                run_cycles = run_cycles.wrapping_add(self.cycles.wrapping_sub(c0));
+                self.must_trigger_irq = false;
+                self.must_trigger_nmi = false;
+            }
+        };
+        Ok(RunResult {
+            reason,
+            cycles: run_cycles,
+            instructions: run_instructions,
+        })
+    }
+
+    /**
+     * sets the irq line state (true = asserted).
+     *
+     * irq is level triggered: it's serviced as long as the line is asserted and the I flag is clear.
+     */
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        if asserted && !self.irq_line {
+            self.interrupt_asserted(InterruptKind::Irq);
         }
-        Ok(())
+        self.irq_line = asserted;
+    }
+
+    /**
+     * sets the nmi line state (true = asserted).
+     *
+     * nmi is edge triggered: it's latched on the transition to asserted, and do not retrigger until the line is released.
+     */
+    pub fn set_nmi_line(&mut self, asserted: bool) {
+        if asserted && !self.nmi_line {
+            // high-to-low transition, latch
+            self.nmi_latched = true;
+            self.interrupt_asserted(InterruptKind::Nmi);
+        }
+        self.nmi_line = asserted;
+    }
+
+    /**
+     * queues an nmi (a pulse on the nmi line), serviced by the run loop before the next instruction.
+     */
+    pub fn trigger_nmi(&mut self) {
+        self.nmi_latched = true;
+        self.interrupt_asserted(InterruptKind::Nmi);
+    }
+
+    /**
+     * asserts the SO (set overflow) pin, i.e. the 1541 byte-ready: the V flag is set at the next instruction boundary,
+     * regardless of the instruction, and the hook is called with CpuOperation::So.
+     */
+    pub fn assert_so(&mut self) {
+        self.so_latched = true;
+    }
+
+    /**
+     * sets the RDY line (high by default), i.e. pulled low by the VIC-II on badlines.
+     *
+     * while low, run()/run_until() do not execute instructions and return StopReason::Stalled with the cycles left in the
+     * budget, which the caller accounts as stalled. the instruction at pc is executed once RDY is high again.
+     * the stall has instruction granularity: lowering RDY from a callback takes effect at the next instruction boundary.
+     */
+    pub fn set_rdy(&mut self, ready: bool) {
+        self.rdy = ready;
+    }
+
+    /**
+     * returns the state of the RDY line.
+     */
+    pub fn is_rdy(&self) -> bool {
+        self.rdy
+    }
+
+    /**
+     * poll the irq/nmi lines (nmi first) and service the interrupt, returns true if an interrupt has been serviced.
+     *
+     * a pending SO is applied here too.
+     */
+    fn poll_interrupt_lines(&mut self, debugger: Option<&mut Debugger>) -> Result<bool, CpuError> {
+        if self.so_latched {
+            self.so_latched = false;
+            self.set_cpu_flags(CpuFlags::V, true);
+            self.call_callback(0, 0, 0, CpuOperation::So);
+        }
+
+        // after CLI/SEI/PLP, polling happens with the I flag as it was before the instruction
+        // https://wiki.nesdev.com/w/index.php/CPU_interrupts#Delayed_IRQ_response_after_CLI.2C_SEI.2C_and_PLP
+        let i = self
+            .poll_i_flag
+            .take()
+            .unwrap_or(self.is_cpu_flag_set(CpuFlags::I));
+        if self.nmi_latched {
+            self.nmi_latched = false;
+            self.nmi(debugger)?;
+            return Ok(true);
+        }
+        if self.irq_line && !i {
+            self.irq(debugger)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /**
+     * called by CLI/SEI/PLP, the next interrupt poll uses the I flag value before the change.
+     */
+    pub(crate) fn delay_i_flag_change(&mut self, prev_i: bool) {
+        self.poll_i_flag = Some(prev_i);
     }
 
     /**
      * internal, triggers irq or nmi
      */
-    fn irq_nmi(&mut self, debugger: Option<&mut Debugger>, v: u16) -> Result<(), CpuError> {
-        let mut empty_dbg = Debugger::new(false);
-        let dbg = debugger.unwrap_or(&mut empty_dbg);
+    fn irq_nmi(&mut self, debugger: Option<&mut Debugger>, v: Vectors) -> Result<(), CpuError> {
+        if self.halted || self.jammed {
+            // irq/nmi do not wake the cpu from STP or JAM
+            return Ok(());
+        }
+        match debugger {
+            Some(dbg) => {
+                // the interrupt entry is a step in the history too
+                dbg.history_begin(self);
+                let res = self.irq_nmi_sequence(Some(&mut *dbg), v);
+                dbg.history_end(self);
+                res
+            }
+            None => self.irq_nmi_sequence(None, v),
+        }
+    }
+
+    /**
+     * internal, pushes pc and p and jumps to the irq or nmi handler.
+     */
+    fn irq_nmi_sequence(
+        &mut self,
+        mut debugger: Option<&mut Debugger>,
+        v: Vectors,
+    ) -> Result<(), CpuError> {
+        // a breakpoint triggered by the pushes is reported once the sequence is complete
+        let mut bp: Option<CpuError> = None;
+        let interrupted_pc = self.regs.pc;
+        let cycles = self.cycles;
+
         // push pc and p on stack
-        opcodes::push_word_le(self, Some(dbg), self.regs.pc)?;
+        defer_breakpoint(
+            opcodes::push_word_le(self, debugger.as_deref(), self.regs.pc),
+            &mut bp,
+        )?;
 
-        // always push P with U(ndefined) set
-        // https://wiki.nesdev.com/w/index.php/Status_flags#The_B_flag
-        let mut flags = self.regs.p.clone();
-        flags.set(CpuFlags::U, true);
-        flags.set(CpuFlags::B, false);
-        opcodes::push_byte(self, Some(dbg), flags.bits())?;
+        // push P with U set and B clear
+        let flags = self.regs.status_byte_for_push(false);
+        defer_breakpoint(
+            opcodes::push_byte(self, debugger.as_deref(), flags),
+            &mut bp,
+        )?;
 
         // set I
         self.set_cpu_flags(CpuFlags::I, true);
 
-        if self.cpu_type == CpuType::WDC65C02 {
+        if self.is_65c02() {
             // clear the D flag
             // http://6502.org/tutorials/65c02opcodes.html
             self.regs.p.set(CpuFlags::D, false);
         }
 
         // set pc to address contained at vector
-        let addr = self.bus.get_memory().read_word_le(v as usize)?;
+        let addr = self.fetch_vector(debugger.as_deref(), v, &mut bp)?;
 
         // check for deadlock
-        if addr == self.regs.pc {
+        if self.deadlock_detection && addr == self.regs.pc {
             return Err(CpuError::new_default(
                 CpuErrorType::Deadlock,
                 self.regs.pc,
                 None,
             ));
         }
+        if let Some(dbg) = debugger.as_mut() {
+            // check the irq/nmi breakpoints, the run loop triggers them on the handler
+            let ctx = InterruptContext {
+                kind: if v == Vectors::NMI {
+                    BreakpointType::NMI
+                } else {
+                    BreakpointType::IRQ
+                },
+                pc: interrupted_pc,
+                vector: v.address(),
+                handler: addr,
+                p: flags,
+                cycles,
+            };
+            dbg.check_interrupt_breakpoint(self, ctx);
+        }
         self.regs.pc = addr;
+
+        // the sequence takes 7 cycles, the handler starts after them as for BRK
+        self.cycles = self.cycles.wrapping_add(Cpu::INTERRUPT_CYCLES);
+        self.interrupt_entered(
+            if v == Vectors::NMI {
+                InterruptKind::Nmi
+            } else {
+                InterruptKind::Irq
+            },
+            self.cycles,
+        );
+        if let (Some(e), Some(dbg)) = (bp, debugger.as_mut()) {
+            // stop in the debugger
+            dbg.breakpoint_triggered(e.bp_idx as usize);
+            dbg_println!(dbg, "R/W breakpoint {} triggered!", e.bp_idx);
+            if let Some(m) = e.msg {
+                dbg_println!(dbg, "{}", m);
+            }
+        }
         Ok(())
     }
 
     /**
-     * triggers an irq.
+     * services an irq right away (regardless of the I flag), the debugger is used to check the r/w breakpoints on the stack and vector.
+     *
+     * the sequence adds Cpu::INTERRUPT_CYCLES to cycles(), and to the budget when serviced by run_until()/run_cycles().
+     *
+     * > to inject interrupts from the embedder or a hook, use set_irq_line() or trigger_nmi() instead: they're serviced by the run
+     * > loop before the next instruction, which also checks the IRQ/NMI breakpoints.
      */
     pub fn irq(&mut self, debugger: Option<&mut Debugger>) -> Result<(), CpuError> {
-        println!("triggering irq !");
-        let res = self.irq_nmi(debugger, Vectors::IRQ as u16);
+        debug_out_text(debugger.as_deref(), &"triggering irq !");
+        let res = self.irq_nmi(debugger, Vectors::IRQ);
         // call callback if any
         self.call_callback(0, 0, 0, CpuOperation::Irq);
         res
     }
 
     /**
-     * triggers an nmi.
+     * services an nmi right away, as irq().
      */
     pub fn nmi(&mut self, debugger: Option<&mut Debugger>) -> Result<(), CpuError> {
-        println!("triggering nmi !");
-        let res = self.irq_nmi(debugger, Vectors::NMI as u16);
+        debug_out_text(debugger.as_deref(), &"triggering nmi !");
+        let res = self.irq_nmi(debugger, Vectors::NMI);
 
         // call callback if any
         self.call_callback(0, 0, 0, CpuOperation::Nmi);
         res
     }
 
+    /**
+     * returns true if the cpu has been halted by STP (cleared only by reset()).
+     */
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /**
+     * returns true if the cpu has been jammed by a JAM (KIL) opcode (cleared only by reset()).
+     */
+    pub fn is_jammed(&self) -> bool {
+        self.jammed
+    }
+
+    /**
+     * internal, sets the cpu jammed by the given JAM opcode, pc is left on the opcode.
+     */
+    pub(crate) fn jam(&mut self, opcode: u8) {
+        self.jammed = true;
+        self.jam_opcode = opcode;
+    }
+
+    /**
+     * internal, the stop reason for an halted or jammed cpu.
+     */
+    fn stuck_reason(&self) -> StopReason {
+        if self.jammed {
+            return StopReason::Jammed {
+                opcode: self.jam_opcode,
+                pc: self.regs.pc,
+            };
+        }
+        StopReason::Halted
+    }
+
+    /**
+     * if set, JAM (KIL) opcodes are reported as InvalidOpcode errors, stopping the run loop as unrecoverable, instead of jamming the cpu (default false).
+     */
+    pub fn set_strict_jam(&mut self, enable: bool) {
+        self.strict_jam = enable;
+    }
+
+    /**
+     * returns true if JAM (KIL) opcodes are reported as InvalidOpcode errors.
+     */
+    pub fn is_strict_jam(&self) -> bool {
+        self.strict_jam
+    }
+
+    /**
+     * enable/disable decimal mode (enabled by default): when disabled, the D flag can still be set and cleared but ADC/SBC
+     * (and ARR) always operate in binary, as on CpuType::Ricoh2A03 regardless of this setting.
+     */
+    pub fn set_bcd_enabled(&mut self, enable: bool) {
+        self.bcd_enabled = enable;
+    }
+
+    /**
+     * returns true if ADC/SBC honour the D flag.
+     */
+    pub fn is_bcd_enabled(&self) -> bool {
+        self.bcd_enabled
+    }
+
+    /**
+     * enable/disable the dummy access of read-modify-write instructions (enabled by default).
+     *
+     * i.e. INC $D019 writes the unmodified value first then the incremented one on NMOS, which some i/o relies on for acknowledging,
+     * while the 65C02 reads the operand twice instead. disabling it saves a memory access per instruction.
+     */
+    pub fn set_rmw_dummy_access(&mut self, enable: bool) {
+        self.rmw_dummy_access = enable;
+    }
+
+    /**
+     * enable/disable the dummy reads of the indexed modes (disabled by default).
+     *
+     * on NMOS, abs,X, abs,Y and (zp),Y loads crossing a page read first from the address with the high byte not yet fixed
+     * (i.e. LDA $12F0,X with X=$20 reads $1210, then $1310), and stores always do (at the right address, if not crossing).
+     * the reads go through the bus and the hook as any other, for hardware with read-sensitive registers.
+     */
+    pub fn set_accurate_bus(&mut self, enable: bool) {
+        self.accurate_bus = enable;
+        self.dummy_read_pending = enable;
+    }
+
+    /**
+     * enable/disable deadlock detection (enabled by default).
+     *
+     * when disabled, branching/jumping to self (i.e. 'jmp *') executes normally and the run loop keeps going,
+     * so a pending irq/nmi may break out of it.
+     */
+    pub fn set_deadlock_detection(&mut self, enable: bool) {
+        self.deadlock_detection = enable;
+    }
+
+    /**
+     * enable/disable the address wrapping (disabled by default).
+     *
+     * when enabled the address space wraps as on the real cpu, i.e. the operand of an instruction at $ffff is read from
+     * $0000, and addresses past the end of a smaller memory wrap into it. when disabled, accesses past the end of memory
+     * are reported as errors (to detect out of bounds accesses with small memories).
+     */
+    pub fn set_address_wrapping(&mut self, enable: bool) {
+        self.address_wrapping = enable;
+    }
+
+    /**
+     * returns true if the address wrapping is enabled.
+     */
+    pub fn is_address_wrapping(&self) -> bool {
+        self.address_wrapping
+    }
+
+    /**
+     * sets the 'magic' constants used by the unstable ANE (XAA) and LXA opcodes (default $ef and $ee).
+     *
+     * https://csdb.dk/release/?id=198357
+     */
+    pub fn set_unstable_magic(&mut self, ane: u8, lxa: u8) {
+        self.ane_magic = ane;
+        self.lxa_magic = lxa;
+    }
+
+    /**
+     * returns the 'magic' constants used by the unstable ANE (XAA) and LXA opcodes, as (ane, lxa).
+     */
+    pub fn get_unstable_magic(&self) -> (u8, u8) {
+        (self.ane_magic, self.lxa_magic)
+    }
+
+    /**
+     * if set, the unstable SHX/SHY/AHX/TAS opcodes drop the 'AND (H+1)' and store the unmasked value (default false).
+     */
+    pub fn set_unstable_sh_drop_and(&mut self, drop: bool) {
+        self.sh_drop_and = drop;
+    }
+
+    /**
+     * returns true if the unstable SHX/SHY/AHX/TAS opcodes drop the 'AND (H+1)'.
+     */
+    pub fn get_unstable_sh_drop_and(&self) -> bool {
+        self.sh_drop_and
+    }
+
     /**
      * sets the cpu mode.
      *
+     * the 65C02 comes as WDC65C02 (all the CMOS opcodes), R65C02 (no WAI/STP) and G65SC02 (no bit instructions and
+     * no WAI/STP), the missing opcodes decode as 1 byte, 1 cycle NOPs.
+     *
      * > this should be called before run()!     
      */
     pub fn set_cpu_type(&mut self, t: CpuType) {
         self.cpu_type = t;
-        println!("setting cpu type to {}.", self.cpu_type);
+        info!("setting cpu type to {}.", self.cpu_type);
+    }
+}
+
+/**
+ * prints registers, cpu type, cycles and interrupt state on one line.
+ *
+ * ```
+ * use rv6502emu::cpu::{Cpu, ResetKind};
+ *
+ * let mut c = Cpu::new_default(None);
+ * c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+ * assert_eq!(
+ *     c.to_string(),
+ *     "PC: $0400, A: $00, X: $00, Y: $00, S: $ff, P: $24(--U--I--), cpu=MOS6502, cycles=7, no interrupts pending"
+ * );
+ * ```
+ */
+impl<B: Bus> Display for Cpu<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "{}, cpu={}, cycles={}, {}",
+            self.regs,
+            self.cpu_type,
+            self.cycles,
+            self.interrupt_state()
+        )
+    }
+}
+
+/**
+ * keeps the first breakpoint error in bp, to be reported later, and returns any other error.
+ */
+fn defer_breakpoint(res: Result<(), CpuError>, bp: &mut Option<CpuError>) -> Result<(), CpuError> {
+    match res {
+        Err(e) if e.t == CpuErrorType::RwBreakpoint => {
+            if bp.is_none() {
+                *bp = Some(e);
+            }
+            Ok(())
+        }
+        _ => res,
     }
 }
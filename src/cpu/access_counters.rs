@@ -0,0 +1,125 @@
+/*
+ * Filename: /src/cpu/access_counters.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:41:09
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::Cpu;
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::convert::TryInto;
+
+/**
+ * per-address read and write counters.
+ */
+pub(crate) struct AccessCounters {
+    reads: Box<[u32; 0x10000]>,
+    writes: Box<[u32; 0x10000]>,
+}
+
+/**
+ * a zeroed counters array, allocated on the heap (a Box::new([0; 0x10000]) would build it on the stack first).
+ */
+fn new_counters() -> Box<[u32; 0x10000]> {
+    vec![0u32; 0x10000].into_boxed_slice().try_into().unwrap()
+}
+
+impl<B: Bus> Cpu<B> {
+    /**
+     * enable/disable the per-address read/write counters, enabling clears them.
+     *
+     * data accesses (loads, stores and the stack) are counted, not opcode fetches, immediate operands and vector fetches.
+     */
+    pub fn enable_access_counters(&mut self, enable: bool) {
+        self.access_counters = if enable {
+            Some(Box::new(AccessCounters {
+                reads: new_counters(),
+                writes: new_counters(),
+            }))
+        } else {
+            None
+        };
+    }
+
+    /**
+     * returns true if the access counters are enabled.
+     */
+    pub fn is_access_counters_enabled(&self) -> bool {
+        self.access_counters.is_some()
+    }
+
+    /**
+     * returns the (reads, writes) at address, (0, 0) if the counters are disabled.
+     */
+    pub fn access_counts(&self, address: u16) -> (u32, u32) {
+        match self.access_counters.as_ref() {
+            Some(a) => (a.reads[address as usize], a.writes[address as usize]),
+            None => (0, 0),
+        }
+    }
+
+    /**
+     * returns the top_n (all if 0) accessed addresses as (address, reads, writes), by reads + writes descending.
+     */
+    pub fn top_accessed(&self, top_n: usize) -> Vec<(u16, u32, u32)> {
+        let a = match self.access_counters.as_ref() {
+            Some(a) => a,
+            None => return Vec::new(),
+        };
+        let mut v: Vec<(u16, u32, u32)> = a
+            .reads
+            .iter()
+            .zip(a.writes.iter())
+            .enumerate()
+            .filter(|(_, (r, w))| **r != 0 || **w != 0)
+            .map(|(addr, (r, w))| (addr as u16, *r, *w))
+            .collect();
+        v.sort_by(|a, b| {
+            (b.1 as u64 + b.2 as u64)
+                .cmp(&(a.1 as u64 + a.2 as u64))
+                .then(a.0.cmp(&b.0))
+        });
+        if top_n != 0 {
+            v.truncate(top_n);
+        }
+        v
+    }
+
+    /**
+     * count a read or write at address, if enabled.
+     */
+    pub(crate) fn count_access(&mut self, address: u16, write: bool) {
+        if let Some(a) = self.access_counters.as_mut() {
+            let e = if write {
+                &mut a.writes[address as usize]
+            } else {
+                &mut a.reads[address as usize]
+            };
+            *e = e.wrapping_add(1);
+        }
+    }
+}
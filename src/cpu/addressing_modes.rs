@@ -28,38 +28,74 @@
  * SOFTWARE.
  */
 
+use crate::bus::Bus;
 use crate::cpu::cpu_error::CpuError;
 use crate::cpu::debugger::breakpoints::BreakpointType;
 use crate::cpu::debugger::Debugger;
-use crate::cpu::{Cpu, CpuOperation, CpuType};
+use crate::cpu::{Cpu, CpuOperation};
 use crate::utils;
-use std::fmt::Display;
-use std::fmt::Error;
-use std::fmt::Formatter;
+use alloc::{format, string::String};
+use core::fmt::Display;
+use core::fmt::Error;
+use core::fmt::Formatter;
 
 /**
- * this is used by the assembler part to tag elements in the opcode matrix
+ * the addressing modes, used to tag elements in the opcode matrix.
  */
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub(crate) enum AddressingModeId {
+pub enum AddressingModeId {
+    /// accumulator (OPC A).
     Acc,
+    /// absolute (OPC $addr).
     Abs,
+    /// absolute, X-indexed (OPC $addr,X).
     Abx,
+    /// absolute, Y-indexed (OPC $addr,Y).
     Aby,
+    /// absolute indexed indirect (OPC ($addr,X), 65C02).
     Aix,
+    /// immediate (OPC #$BB).
     Imm,
+    /// implied (OPC).
     Imp,
+    /// indirect (OPC ($addr)).
     Ind,
+    /// zeropage indirect (OPC ($LL), 65C02).
     Izp,
+    /// X-indexed, indirect (OPC ($LL,X)).
     Xin,
+    /// indirect, Y-indexed (OPC ($LL),Y).
     Iny,
+    /// relative (OPC $BB).
     Rel,
+    /// zeropage (OPC $LL).
     Zpg,
+    /// zeropage, X-indexed (OPC $LL,X).
     Zpx,
+    /// zeropage, Y-indexed (OPC $LL,Y).
     Zpy,
+    /// zeropage relative (OPC $LL,$BB, 65C02).
     Zpr,
 }
 
+impl AddressingModeId {
+    /**
+     * the instruction size (opcode + operands) for this addressing mode.
+     */
+    pub fn instr_size(&self) -> u8 {
+        match self {
+            AddressingModeId::Imp | AddressingModeId::Acc => 1,
+            AddressingModeId::Abs
+            | AddressingModeId::Abx
+            | AddressingModeId::Aby
+            | AddressingModeId::Aix
+            | AddressingModeId::Zpr
+            | AddressingModeId::Ind => 3,
+            _ => 2,
+        }
+    }
+}
+
 impl Display for AddressingModeId {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         match self {
@@ -138,28 +174,50 @@ pub(crate) trait AddressingMode {
     /**
      * string representation
      */
-    fn repr(_c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
+    fn repr<B: Bus>(_c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
         Ok(String::from(opcode_name.to_uppercase()))
     }
 
     /**
      * fetch the opcode target address depending on the addressing mode, returns a tuple with (address, extra_cycle_if_page_crossed))
      */
-    fn target_address(
-        _c: &mut Cpu,
+    fn target_address<B: Bus>(
+        _c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         Ok((0, false))
     }
 
+    /**
+     * the addresses of the pointer lsb and msb, for the indirect jumps (None for the other modes).
+     */
+    fn pointer<B: Bus>(_c: &mut Cpu<B>) -> Result<Option<(u16, u16)>, CpuError> {
+        Ok(None)
+    }
+
+    /**
+     * the dummy read issued before accessing address, with Cpu::set_accurate_bus() (indexed modes only).
+     */
+    fn dummy_read<B: Bus>(
+        _c: &mut Cpu<B>,
+        _d: Option<&Debugger>,
+        _address: u16,
+        _store: bool,
+    ) -> Result<(), CpuError> {
+        Ok(())
+    }
+
     /**
      * load byte from address
      */
-    fn load(c: &mut Cpu, d: Option<&Debugger>, address: u16) -> Result<u8, CpuError> {
-        let m = c.bus.get_memory();
+    fn load<B: Bus>(c: &mut Cpu<B>, d: Option<&Debugger>, address: u16) -> Result<u8, CpuError> {
+        Self::dummy_read(c, d, address, false)?;
 
-        // read
-        let b = m.read_byte(address as usize)?;
+        // read (6510 i/o port is handled by the cpu itself)
+        let b = match c.port_read(address) {
+            Some(p) => p,
+            None => read_mem_byte(c, address)?,
+        };
 
         // check if a breakpoint has to be triggered
         if d.is_some() {
@@ -167,19 +225,33 @@ pub(crate) trait AddressingMode {
                 .handle_rw_breakpoint(c, address, BreakpointType::READ)?
         }
 
-        // call callback if any
-        c.call_callback(address, b, 1, CpuOperation::Read);
-        Ok(b)
+        // immediate operands and branch offsets are part of the instruction, not data
+        if Self::id() != AddressingModeId::Imm && Self::id() != AddressingModeId::Rel {
+            c.cover_access(address, false);
+            c.count_access(address, false);
+        }
+
+        // call callback if any, it may override the byte read
+        Ok(c.call_read_callback(address, b, CpuOperation::Read))
     }
 
     /**
      * store byte to address
      */
-    fn store(c: &mut Cpu, d: Option<&Debugger>, address: u16, b: u8) -> Result<(), CpuError> {
-        let m = c.bus.get_memory();
-
-        // write
-        m.write_byte(address as usize, b)?;
+    fn store<B: Bus>(
+        c: &mut Cpu<B>,
+        d: Option<&Debugger>,
+        address: u16,
+        b: u8,
+    ) -> Result<(), CpuError> {
+        Self::dummy_read(c, d, address, true)?;
+
+        // write (6510 i/o port is handled by the cpu itself)
+        if !c.port_write(address, b) {
+            c.journal_write(address);
+            let a = c.mem_address(address);
+            c.bus.get_memory().write_byte(a, b)?;
+        }
 
         // check if a breakpoint has to be triggered
         if d.is_some() {
@@ -188,6 +260,8 @@ pub(crate) trait AddressingMode {
         }
 
         // call callback if any
+        c.cover_access(address, true);
+        c.count_access(address, true);
         c.call_callback(address, b, 1, CpuOperation::Write);
         Ok(())
     }
@@ -197,25 +271,164 @@ pub(crate) trait AddressingMode {
  * check hi-byte of source and destination addresses, to determine if there's a page cross.
  */
 fn is_page_cross(src_addr: u16, dst_addr: u16) -> bool {
-    if src_addr & 0xff00 == dst_addr & 0xff00 {
-        return true;
+    src_addr & 0xff00 != dst_addr & 0xff00
+}
+
+/**
+ * the NMOS dummy read of the indexed modes, at the target address before the page is fixed (same low byte, un-incremented
+ * high byte). it's done by loads crossing a page and by stores (at the right address if not crossing), once per instruction.
+ */
+fn indexed_dummy_read<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    address: u16,
+    index: u8,
+    store: bool,
+) -> Result<(), CpuError> {
+    if !c.take_dummy_read() || c.is_65c02() {
+        return Ok(());
+    }
+    let base = address.wrapping_sub(index as u16);
+    if store || is_page_cross(base, address) {
+        A::load(c, d, (base & 0xff00) | (address & 0x00ff))?;
+    }
+    Ok(())
+}
+
+/**
+ * read a byte, wrapping the address into the memory size with Cpu::set_address_wrapping().
+ */
+pub(crate) fn read_mem_byte<B: Bus>(c: &mut Cpu<B>, address: u16) -> Result<u8, CpuError> {
+    let a = c.mem_address(address);
+    c.bus.get_memory().read_byte(a)
+}
+
+/**
+ * read a little endian word, with Cpu::set_address_wrapping() the msb at $ffff is read from $0000 (or wrapped into the
+ * memory size) instead of erroring.
+ */
+pub(crate) fn read_mem_word<B: Bus>(c: &mut Cpu<B>, address: u16) -> Result<u16, CpuError> {
+    if !c.is_address_wrapping() {
+        return c.bus.get_memory().read_word_le(address as usize);
     }
-    false
+    let lsb = read_mem_byte(c, address)?;
+    let msb = read_mem_byte(c, address.wrapping_add(1))?;
+    Ok(((msb as u16) << 8) | (lsb as u16))
+}
+
+/**
+ * read the pointer of the indirect jumps, without side effects (JMP reads it again through load() when executing).
+ */
+fn read_pointer<A: AddressingMode, B: Bus>(c: &mut Cpu<B>) -> Result<(u16, bool), CpuError> {
+    let (lo, hi) = A::pointer(c)?.unwrap_or_default();
+    let lsb = read_mem_byte(c, lo)?;
+    let msb = read_mem_byte(c, hi)?;
+    Ok((((msb as u16) << 8) | (lsb as u16), false))
+}
+
+/**
+ * read a pointer in the zeropage, the msb is read at lo + 1 wrapping within page zero (a pointer at $ff takes the msb from $00).
+ */
+pub(crate) fn read_zp_word<B: Bus>(c: &mut Cpu<B>, lo: u8) -> Result<u16, CpuError> {
+    let lsb = read_mem_byte(c, lo as u16)?;
+    let msb = read_mem_byte(c, lo.wrapping_add(1) as u16)?;
+    Ok(((msb as u16) << 8) | (lsb as u16))
+}
+
+/**
+ * read a byte as read_mem_byte(), but without side effects (the bus devices are peeked, see Bus::peek()).
+ */
+fn peek_mem_byte<B: Bus>(c: &mut Cpu<B>, address: u16) -> Result<u8, CpuError> {
+    let a = c.mem_address(address);
+    let mut b = [0u8; 1];
+    c.bus.get_memory().read_range(a, &mut b)?;
+    Ok(b[0])
+}
+
+/**
+ * read a little endian word without side effects, the msb of a zeropage pointer wraps within page zero if zp is set.
+ */
+fn peek_mem_word<B: Bus>(c: &mut Cpu<B>, address: u16, zp: bool) -> Result<u16, CpuError> {
+    let msb_address = if zp {
+        (address as u8).wrapping_add(1) as u16
+    } else {
+        address.wrapping_add(1)
+    };
+    let lsb = peek_mem_byte(c, address)?;
+    let msb = peek_mem_byte(c, msb_address)?;
+    Ok(((msb as u16) << 8) | (lsb as u16))
+}
+
+/**
+ * the effective address of the instruction at pc, as computed when executing it with the current registers: the memory
+ * operand, the destination of the jumps and of the relative branches (the zeropage byte tested for BBR/BBS).
+ *
+ * memory is peeked, so no hooks or device side effects are triggered. None for the accumulator, implied and immediate
+ * modes.
+ */
+pub(crate) fn effective_address<B: Bus>(
+    c: &mut Cpu<B>,
+    pc: u16,
+    id: AddressingModeId,
+) -> Result<Option<u16>, CpuError> {
+    let operand = pc.wrapping_add(1);
+    let tgt = match id {
+        AddressingModeId::Acc | AddressingModeId::Imp | AddressingModeId::Imm => return Ok(None),
+        AddressingModeId::Rel => {
+            let offset = peek_mem_byte(c, operand)?;
+            get_relative_branch_target(pc.wrapping_add(2), offset).0
+        }
+        AddressingModeId::Zpr | AddressingModeId::Zpg => peek_mem_byte(c, operand)? as u16,
+        AddressingModeId::Zpx => peek_mem_byte(c, operand)?.wrapping_add(c.regs.x) as u16,
+        AddressingModeId::Zpy => peek_mem_byte(c, operand)?.wrapping_add(c.regs.y) as u16,
+        AddressingModeId::Abs => peek_mem_word(c, operand, false)?,
+        AddressingModeId::Abx => peek_mem_word(c, operand, false)?.wrapping_add(c.regs.x as u16),
+        AddressingModeId::Aby => peek_mem_word(c, operand, false)?.wrapping_add(c.regs.y as u16),
+        AddressingModeId::Aix => {
+            let w = peek_mem_word(c, operand, false)?.wrapping_add(c.regs.x as u16);
+            peek_mem_word(c, w, false)?
+        }
+        AddressingModeId::Ind => {
+            let w = peek_mem_word(c, operand, false)?;
+            if w & 0xff == 0xff && !c.is_65c02() {
+                // the 6502 JMP bug, the msb is taken from the start of the page (see IndirectAddressing::pointer())
+                let lsb = peek_mem_byte(c, w)?;
+                let msb = peek_mem_byte(c, w & 0xff00)?;
+                ((msb as u16) << 8) | (lsb as u16)
+            } else {
+                peek_mem_word(c, w, false)?
+            }
+        }
+        AddressingModeId::Izp => {
+            let zp = peek_mem_byte(c, operand)?;
+            peek_mem_word(c, zp as u16, true)?
+        }
+        AddressingModeId::Xin => {
+            let zp = peek_mem_byte(c, operand)?.wrapping_add(c.regs.x);
+            peek_mem_word(c, zp as u16, true)?
+        }
+        AddressingModeId::Iny => {
+            let zp = peek_mem_byte(c, operand)?;
+            peek_mem_word(c, zp as u16, true)?.wrapping_add(c.regs.y as u16)
+        }
+    };
+    Ok(Some(tgt))
 }
 
 /**
  * get branch target for relative addressing, returns tuple with (new_pc_address, add_extra_cycle)
+ *
+ * next_pc is the address of the instruction following the branch (pc + 2 for Rel, pc + 3 for Zpr), the offset is
+ * relative to it.
  */
-pub(crate) fn get_relative_branch_target(src_pc: u16, branch_offset: u8) -> (u16, bool) {
+pub(crate) fn get_relative_branch_target(next_pc: u16, branch_offset: u8) -> (u16, bool) {
     let mut two_compl: u16 = branch_offset as u16;
     if utils::is_signed(branch_offset) {
         // sign extend
         two_compl |= 0xff00;
     }
-
-    // new offset is pc + 2 complement signed offset + sizeof the opcode (which, for relative addressing, is 2)
-    let new_pc = src_pc.wrapping_add(two_compl).wrapping_add(2);
-    if is_page_cross(src_pc, new_pc) {
+    let new_pc = next_pc.wrapping_add(two_compl);
+    if is_page_cross(next_pc, new_pc) {
         return (new_pc, true);
     }
     (new_pc, false)
@@ -229,8 +442,8 @@ impl AddressingMode for AccumulatorAddressing {
     fn id() -> AddressingModeId {
         AddressingModeId::Acc
     }
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let b = c.bus.get_memory().read_byte(c.regs.pc as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b = read_mem_byte(c, c.regs.pc)?;
         Ok(format!(
             "${:04x}:\t{:02x}\t\t-->\t{} A\t[{}])",
             c.regs.pc,
@@ -240,18 +453,23 @@ impl AddressingMode for AccumulatorAddressing {
         ))
     }
 
-    fn target_address(
-        _c: &mut Cpu,
+    fn target_address<B: Bus>(
+        _c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // implied A
         Ok((0, false))
     }
 
-    fn load(c: &mut Cpu, _d: Option<&Debugger>, _address: u16) -> Result<u8, CpuError> {
+    fn load<B: Bus>(c: &mut Cpu<B>, _d: Option<&Debugger>, _address: u16) -> Result<u8, CpuError> {
         Ok(c.regs.a)
     }
-    fn store(c: &mut Cpu, _d: Option<&Debugger>, _address: u16, b: u8) -> Result<(), CpuError> {
+    fn store<B: Bus>(
+        c: &mut Cpu<B>,
+        _d: Option<&Debugger>,
+        _address: u16,
+        b: u8,
+    ) -> Result<(), CpuError> {
         c.regs.a = b;
         Ok(())
     }
@@ -270,11 +488,10 @@ impl AddressingMode for AbsoluteAddressing {
         3
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let b3 = m.read_byte((c.regs.pc.wrapping_add(2)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let b3 = read_mem_byte(c, c.regs.pc.wrapping_add(2))?;
         let tgt = Self::target_address(c, false)?;
         Ok(format!(
             "${:04x}:\t{:02x} {:02x} {:02x}\t-->\t{} ${:04x}\t[{}, tgt=${:04x}]",
@@ -289,11 +506,11 @@ impl AddressingMode for AbsoluteAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
-        let w = c.bus.get_memory().read_word_le((c.regs.pc + 1) as usize)?;
+        let w = read_mem_word(c, c.regs.pc.wrapping_add(1))?;
 
         Ok((w, false))
     }
@@ -311,11 +528,10 @@ impl AddressingMode for AbsoluteXAddressing {
         3
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let b3 = m.read_byte((c.regs.pc.wrapping_add(2)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let b3 = read_mem_byte(c, c.regs.pc.wrapping_add(2))?;
         let tgt = Self::target_address(c, false)?;
         Ok(format!(
             "${:04x}:\t{:02x} {:02x} {:02x}\t-->\t{} ${:04x}, X\t[{}, tgt=${:04x}]",
@@ -330,14 +546,21 @@ impl AddressingMode for AbsoluteXAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn dummy_read<B: Bus>(
+        c: &mut Cpu<B>,
+        d: Option<&Debugger>,
+        address: u16,
+        store: bool,
+    ) -> Result<(), CpuError> {
+        let index = c.regs.x;
+        indexed_dummy_read::<Self, B>(c, d, address, index, store)
+    }
+
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
-        let w = c
-            .bus
-            .get_memory()
-            .read_word_le((c.regs.pc.wrapping_add(1)) as usize)?;
+        let w = read_mem_word(c, c.regs.pc.wrapping_add(1))?;
         let ww = w.wrapping_add(c.regs.x as u16);
 
         // check for page crossing, in case we need to add a cycle
@@ -362,11 +585,10 @@ impl AddressingMode for AbsoluteYAddressing {
         3
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let b3 = m.read_byte((c.regs.pc.wrapping_add(2)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let b3 = read_mem_byte(c, c.regs.pc.wrapping_add(2))?;
         let tgt = Self::target_address(c, false)?;
         Ok(format!(
             "${:04x}:\t{:02x} {:02x} {:02x}\t-->\t{} ${:04x}, Y\t[{}, tgt=${:04x}]",
@@ -381,14 +603,21 @@ impl AddressingMode for AbsoluteYAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn dummy_read<B: Bus>(
+        c: &mut Cpu<B>,
+        d: Option<&Debugger>,
+        address: u16,
+        store: bool,
+    ) -> Result<(), CpuError> {
+        let index = c.regs.y;
+        indexed_dummy_read::<Self, B>(c, d, address, index, store)
+    }
+
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
-        let w = c
-            .bus
-            .get_memory()
-            .read_word_le((c.regs.pc.wrapping_add(1)) as usize)?;
+        let w = read_mem_word(c, c.regs.pc.wrapping_add(1))?;
         let ww = w.wrapping_add(c.regs.y as u16);
 
         // check for page crossing, in case we need to add a cycle
@@ -413,10 +642,9 @@ impl AddressingMode for ImmediateAddressing {
         2
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
         let tgt = Self::target_address(c, false)?;
         Ok(format!(
             "${:04x}:\t{:02x} {:02x}\t\t-->\t{} #${:02x}\t[{}, tgt=${:04x}]",
@@ -430,8 +658,8 @@ impl AddressingMode for ImmediateAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         let w = c.regs.pc.wrapping_add(1);
@@ -447,8 +675,8 @@ impl AddressingMode for ImpliedAddressing {
     fn id() -> AddressingModeId {
         AddressingModeId::Imp
     }
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let b = c.bus.get_memory().read_byte(c.regs.pc as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b = read_mem_byte(c, c.regs.pc)?;
         Ok(format!(
             "${:04x}:\t{:02x}\t\t-->\t{}\t\t[{}]",
             c.regs.pc,
@@ -472,11 +700,10 @@ impl AddressingMode for IndirectAddressing {
         3
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let b3 = m.read_byte((c.regs.pc.wrapping_add(2)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let b3 = read_mem_byte(c, c.regs.pc.wrapping_add(2))?;
         let tgt = Self::target_address(c, false)?;
         Ok(format!(
             "${:04x}:\t{:02x} {:02x} {:02x}\t-->\t{} (${:04x})\t[{}, tgt=${:04x}]",
@@ -491,27 +718,23 @@ impl AddressingMode for IndirectAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
-        _add_extra_cycle_on_page_crossing: bool,
-    ) -> Result<(u16, bool), CpuError> {
+    fn pointer<B: Bus>(c: &mut Cpu<B>) -> Result<Option<(u16, u16)>, CpuError> {
         // read address
-        let w = c.bus.get_memory().read_word_le((c.regs.pc + 1) as usize)?;
-
-        let ww: u16;
-        if w & 0xff == 0xff && c.cpu_type == CpuType::MOS6502 {
+        let w = read_mem_word(c, c.regs.pc.wrapping_add(1))?;
+        if w & 0xff == 0xff && !c.is_65c02() {
             // emulate 6502 JMP bug on access across page boundary (this addressing mode is used by JMP only):
             // An original 6502 has does not correctly fetch the target address if the indirect vector falls on a page boundary (e.g. $xxFF where xx is any value from $00 to $FF).
             // In this case fetches the LSB from $xxFF as expected but takes the MSB from $xx00.
-            let lsb = c.bus.get_memory().read_byte(w as usize)?;
-            let msb = c.bus.get_memory().read_byte((w & 0xff00) as usize)?;
-            ww = ((msb as u16) << 8) | (lsb as u16);
-        } else {
-            // read word at address
-            ww = c.bus.get_memory().read_word_le(w as usize)?;
+            return Ok(Some((w, w & 0xff00)));
         }
+        Ok(Some((w, w.wrapping_add(1))))
+    }
 
-        Ok((ww, false))
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
+        _add_extra_cycle_on_page_crossing: bool,
+    ) -> Result<(u16, bool), CpuError> {
+        read_pointer::<Self, B>(c)
     }
 }
 
@@ -532,10 +755,9 @@ impl AddressingMode for XIndirectAddressing {
         2
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
         let tgt = Self::target_address(c, false)?;
 
         Ok(format!(
@@ -550,19 +772,16 @@ impl AddressingMode for XIndirectAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // read address in zeropage
-        let mut w = c
-            .bus
-            .get_memory()
-            .read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+        let mut w = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
 
         // add x (wrapping), and read word
         w = w.wrapping_add(c.regs.x);
-        let ww = c.bus.get_memory().read_word_le(w as usize)?;
+        let ww = read_zp_word(c, w)?;
 
         Ok((ww, false))
     }
@@ -587,10 +806,9 @@ impl AddressingMode for IndirectYAddressing {
     fn len() -> i8 {
         2
     }
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
         let tgt = Self::target_address(c, false)?;
 
         Ok(format!(
@@ -605,16 +823,23 @@ impl AddressingMode for IndirectYAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn dummy_read<B: Bus>(
+        c: &mut Cpu<B>,
+        d: Option<&Debugger>,
+        address: u16,
+        store: bool,
+    ) -> Result<(), CpuError> {
+        let index = c.regs.y;
+        indexed_dummy_read::<Self, B>(c, d, address, index, store)
+    }
+
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // read address contained at address in the zeropage
-        let w = c
-            .bus
-            .get_memory()
-            .read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let ww = c.bus.get_memory().read_word_le(w as usize)?;
+        let w = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let ww = read_zp_word(c, w)?;
 
         // add y
         let addr_plus_y = ww.wrapping_add(c.regs.y as u16);
@@ -642,11 +867,10 @@ impl AddressingMode for RelativeAddressing {
         2
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let tgt = Self::target_address(c, false)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let (tgt, _) = get_relative_branch_target(c.regs.pc.wrapping_add(Self::len() as u16), b2);
 
         Ok(format!(
             "${:04x}:\t{:02x} {:02x}\t\t-->\t{} ${:02x}\t\t[{}, tgt=${:04x}]",
@@ -656,20 +880,17 @@ impl AddressingMode for RelativeAddressing {
             opcode_name.to_uppercase(),
             b2,
             AddressingModeId::Rel,
-            tgt.0.wrapping_sub(1)
+            tgt
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
+        // pc+1=offset to branch to, the page cross penalty is applied by the branch opcode only when taken
         let w = c.regs.pc.wrapping_add(1);
-
-        // this will check for page crossing too (check mandatory in relative addressing)
-        let (_, cross) =
-            get_relative_branch_target(c.regs.pc, c.bus.get_memory().read_byte(w as usize)?);
-        Ok((w as u16, cross))
+        Ok((w, false))
     }
 }
 
@@ -688,10 +909,9 @@ impl AddressingMode for ZeroPageAddressing {
         2
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
         let tgt = Self::target_address(c, false)?;
 
         Ok(format!(
@@ -706,15 +926,12 @@ impl AddressingMode for ZeroPageAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // read address in the zeropage
-        let w = c
-            .bus
-            .get_memory()
-            .read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+        let w = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
 
         Ok((w as u16, false))
     }
@@ -734,10 +951,9 @@ impl AddressingMode for ZeroPageXAddressing {
         2
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
         let tgt = Self::target_address(c, false)?;
 
         Ok(format!(
@@ -752,15 +968,12 @@ impl AddressingMode for ZeroPageXAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // read address in the zeropage
-        let w = c
-            .bus
-            .get_memory()
-            .read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+        let w = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
 
         // and add x, wrapping
         let w = w.wrapping_add(c.regs.x);
@@ -782,10 +995,9 @@ impl AddressingMode for ZeroPageYAddressing {
         2
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
         let tgt = Self::target_address(c, false)?;
 
         Ok(format!(
@@ -800,15 +1012,12 @@ impl AddressingMode for ZeroPageYAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // read address in the zeropage
-        let w = c
-            .bus
-            .get_memory()
-            .read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+        let w = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
 
         // and add y, wrapping
         let w = w.wrapping_add(c.regs.y);
@@ -830,10 +1039,9 @@ impl AddressingMode for IndirectZeroPageAddressing {
         2
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
         let tgt = Self::target_address(c, false)?;
 
         Ok(format!(
@@ -848,19 +1056,16 @@ impl AddressingMode for IndirectZeroPageAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // read address in the zeropage
-        let w = c
-            .bus
-            .get_memory()
-            .read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
+        let w = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
 
         // read address indirect
-        let ww = c.bus.get_memory().read_word_le(w as usize)?;
-        Ok((ww as u16, false))
+        let ww = read_zp_word(c, w)?;
+        Ok((ww, false))
     }
 }
 
@@ -879,11 +1084,10 @@ impl AddressingMode for AbsoluteIndirectXAddressing {
         3
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let b3 = m.read_byte((c.regs.pc.wrapping_add(2)) as usize)?;
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let b3 = read_mem_byte(c, c.regs.pc.wrapping_add(2))?;
         let tgt = Self::target_address(c, false)?;
         Ok(format!(
             "${:04x}:\t{:02x} {:02x} {:02x}\t-->\t{} (${:04x}, X)\t[{}, tgt=${:04x}]",
@@ -898,24 +1102,25 @@ impl AddressingMode for AbsoluteIndirectXAddressing {
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn pointer<B: Bus>(c: &mut Cpu<B>) -> Result<Option<(u16, u16)>, CpuError> {
+        // x is added to the whole address, with carry
+        let w = read_mem_word(c, c.regs.pc.wrapping_add(1))?;
+        let ww = w.wrapping_add(c.regs.x as u16);
+        Ok(Some((ww, ww.wrapping_add(1))))
+    }
+
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
-        let w = c
-            .bus
-            .get_memory()
-            .read_word_le((c.regs.pc.wrapping_add(1)) as usize)?;
-        let ww = w.wrapping_add(c.regs.x as u16);
-        let www = c.bus.get_memory().read_word_le(ww as usize)?;
-        Ok((www, false))
+        read_pointer::<Self, B>(c)
     }
 }
 
 /**
  * 65C02 only!
- * This is mostly the same as Relative addressing.
- * addr = rd_mem16(pc+1) + pc + 2;  pc += 0; // Leave PC at ZP operand
+ * This is mostly the same as Relative addressing, pc+1 is the zeropage address to test and pc+2 the branch offset,
+ * relative to the next instruction (pc+3).
  */
 pub(crate) struct ZeroPageRelativeAddressing;
 impl AddressingMode for ZeroPageRelativeAddressing {
@@ -926,12 +1131,11 @@ impl AddressingMode for ZeroPageRelativeAddressing {
         3
     }
 
-    fn repr(c: &mut Cpu, opcode_name: &str) -> Result<String, CpuError> {
-        let m = c.bus.get_memory();
-        let b1 = m.read_byte(c.regs.pc as usize)?;
-        let b2 = m.read_byte((c.regs.pc.wrapping_add(1)) as usize)?;
-        let b3 = m.read_byte((c.regs.pc.wrapping_add(2)) as usize)?;
-        let tgt = get_relative_branch_target(c.regs.pc, b2);
+    fn repr<B: Bus>(c: &mut Cpu<B>, opcode_name: &str) -> Result<String, CpuError> {
+        let b1 = read_mem_byte(c, c.regs.pc)?;
+        let b2 = read_mem_byte(c, c.regs.pc.wrapping_add(1))?;
+        let b3 = read_mem_byte(c, c.regs.pc.wrapping_add(2))?;
+        let (tgt, _) = get_relative_branch_target(c.regs.pc.wrapping_add(Self::len() as u16), b3);
         Ok(format!(
             "${:04x}:\t{:02x} {:02x} {:02x}\t-->\t{} ${:02x}, ${:02x}\t[{}, tgt=${:04x}]",
             c.regs.pc,
@@ -942,12 +1146,12 @@ impl AddressingMode for ZeroPageRelativeAddressing {
             b2,
             b3,
             AddressingModeId::Zpr,
-            tgt.0.wrapping_sub(Self::len() as u16).wrapping_sub(2)
+            tgt
         ))
     }
 
-    fn target_address(
-        c: &mut Cpu,
+    fn target_address<B: Bus>(
+        c: &mut Cpu<B>,
         _add_extra_cycle_on_page_crossing: bool,
     ) -> Result<(u16, bool), CpuError> {
         // pc+1=byte to test
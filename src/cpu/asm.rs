@@ -0,0 +1,305 @@
+/*
+ * Filename: /src/cpu/asm.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 23:41:27
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! one-shot assembler, assembles source text to bytes without touching the cpu.
+
+use crate::cpu::addressing_modes::AddressingModeId;
+use crate::cpu::opcodes::opcode_info;
+use crate::cpu::CpuType;
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt::{Display, Error, Formatter};
+
+/**
+ * an assembler error, at the given source line.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsmError {
+    /// the source line, 1 based.
+    pub line: usize,
+    /// what went wrong.
+    pub msg: String,
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AsmError {}
+
+/**
+ * resolves a symbol used in place of an address.
+ */
+pub(crate) type Resolver<'a> = dyn Fn(&str) -> Result<u16, String> + 'a;
+
+/**
+ * assemble source (one instruction per line) as if it was located at origin, for the given cpu type.
+ *
+ * empty lines and comments (from ';' to the end of the line) are skipped. the syntax is taken from
+ * https://www.masswerk.at/6502/6502_instruction_set.html, values are hex with the $ prefix:
+ *
+ * A       Accumulator         OPC A           operand is AC (implied single byte instruction)
+ * abs     absolute            OPC $addr       operand is address $HHLL
+ * abs,X   absolute, X-indexed OPC $addr,X     operand is address; effective address is address incremented by X with carry
+ * abs,Y   absolute, Y-indexed OPC $addr,Y     operand is address; effective address is address incremented by Y with carry
+ * #       immediate           OPC #$BB        operand is byte BB
+ * impl    implied             OPC             operand implied
+ * ind     indirect            OPC ($addr)     operand is address; effective address is contents of word at address: C.w($HHLL)
+ * X,ind   X-indexed, indirect OPC ($ad,X)     operand is zeropage address; effective address is word in (LL + X, LL + X + 1), inc. without carry: C.w($00LL + X)
+ * ind,Y   indirect, Y-indexed OPC ($ad),Y     operand is zeropage address; effective address is word in (LL, LL + 1) incremented by Y with carry: C.w($00LL) + Y
+ * rel     relative            OPC $BB         branch target is PC + signed offset BB (or OPC $HHLL, the branch target)
+ * zpg     zeropage            OPC $LL         operand is zeropage address (hi-byte is zero, address = $00LL)
+ * zpg,X   zeropage, X-indexed OPC $LL,X       operand is zeropage address; effective address is address incremented by X without carry
+ * zpg,Y   zeropage, Y-indexed OPC $LL,Y       operand is zeropage address; effective address is address incremented by Y without carry
+ *
+ * for 65c02:
+ * izp (Indirect ZeroPage)      OPC ($LL)       operand is zeropage address
+ * zpr (ZeroPage relative)      OPC $ad,$BB     operand is zeropage address, then offset BB (or OPC $ad,$HHLL, the branch target)
+ * iax (Indirect Absolute X)    OPC ($addr,X)
+ *
+ * ```
+ * use rv6502emu::cpu::asm;
+ * use rv6502emu::cpu::CpuType;
+ *
+ * let v = asm::assemble("ldx #$00\n; clear\nstz $10,x\nbne $0400", 0x400, CpuType::WDC65C02).unwrap();
+ * assert_eq!(v, [0xa2, 0x00, 0x74, 0x10, 0xd0, 0xfa]);
+ *
+ * let e = asm::assemble("nop\nstz $10", 0x400, CpuType::MOS6502).unwrap_err();
+ * assert_eq!(e.line, 2);
+ * ```
+ */
+pub fn assemble(source: &str, origin: u16, cpu_type: CpuType) -> Result<Vec<u8>, AsmError> {
+    let no_symbols = |s: &str| -> Result<u16, String> { Err(format!("unknown symbol '{}'!", s)) };
+    let mut v: Vec<u8> = Vec::new();
+    for (i, l) in source.lines().enumerate() {
+        let l = l.split(';').next().unwrap_or_default();
+        if l.trim().is_empty() {
+            continue;
+        }
+        let address = origin.wrapping_add(v.len() as u16);
+        let b = assemble_instruction(cpu_type, address, l, &no_symbols)
+            .map_err(|msg| AsmError { line: i + 1, msg })?;
+        v.extend(b);
+    }
+    Ok(v)
+}
+
+/**
+ * find instruction in the opcode matrix, documented opcodes are preferred (i.e. nop is $ea, not $1a).
+ */
+fn find_instruction(t: CpuType, s: &str, id: AddressingModeId) -> Option<u8> {
+    let mut found: Option<u8> = None;
+    for i in 0..=0xff_u8 {
+        let info = opcode_info(i, t);
+        if info.mnemonic.eq(s) && info.addressing_mode == id {
+            if !info.undocumented {
+                return Some(i);
+            }
+            found = found.or(Some(i));
+        }
+    }
+    found
+}
+
+/**
+ * resolve an operand value, either $hex (returns the number of digits too) or a label name (digits=0).
+ */
+fn asm_value(s: &str, resolve: &Resolver) -> Result<(u16, usize), String> {
+    if let Some(h) = s.strip_prefix('$') {
+        if h.len() > 0 && h.len() <= 4 {
+            if let Ok(v) = u16::from_str_radix(h, 16) {
+                return Ok((v, h.len()));
+            }
+        }
+        return Err(format!("invalid operand '{}'!", s));
+    }
+    Ok((resolve(s)?, 0))
+}
+
+/**
+ * assemble a single instruction at address, for the given cpu type.
+ *
+ * branches take either the raw offset ($BB) or the target ($HHLL or label), zpr takes $ad,$BB|$HHLL|label.
+ * rmb/smb/bbr/bbs take the bit number embedded (rmb3 $12) or as first operand (rmb 3,$12).
+ */
+pub(crate) fn assemble_instruction(
+    t: CpuType,
+    address: u16,
+    s: &str,
+    resolve: &Resolver,
+) -> Result<Vec<u8>, String> {
+    // split opcode and operand/s, also ensure there's no whitespaces in the operands part
+    let s = s.trim().to_ascii_lowercase();
+    let (opcode, tmp) = s.split_once(char::is_whitespace).unwrap_or((&s, ""));
+    let mut opcode = String::from(opcode);
+    let mut operand_s = tmp.replace(" ", "").replace("\t", "");
+
+    // bit number as first operand
+    if matches!(opcode.as_str(), "rmb" | "smb" | "bbr" | "bbs") {
+        match operand_s.split_once(',') {
+            Some((n, rest)) if matches!(n, "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7") => {
+                opcode.push_str(n);
+                operand_s = String::from(rest);
+            }
+            _ => return Err(String::from("missing bit number!")),
+        }
+    }
+    let is_branch = matches!(
+        opcode.as_str(),
+        "bpl" | "bmi" | "bvc" | "bvs" | "bcc" | "bcs" | "bne" | "beq" | "bra"
+    );
+
+    // find addressing mode and operand
+    let mode_id: AddressingModeId;
+    let mut operand: u16 = 0;
+    let mut branch_target: Option<u16> = None;
+    if operand_s.is_empty() {
+        // implied
+        mode_id = AddressingModeId::Imp;
+    } else if operand_s.eq("a") {
+        // accumulator
+        mode_id = AddressingModeId::Acc;
+    } else if let Some(v) = operand_s.strip_prefix('#') {
+        // immediate
+        let (o, digits) = asm_value(v, resolve)?;
+        if digits == 0 || digits > 2 {
+            return Err(format!("invalid operand '{}'!", operand_s));
+        }
+        mode_id = AddressingModeId::Imm;
+        operand = o;
+    } else if let Some(v) = operand_s
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix(",x)"))
+    {
+        // X indirect, or absolute indirect x (65c02)
+        let (o, digits) = asm_value(v, resolve)?;
+        mode_id = if digits == 1 || digits == 2 || (digits == 0 && o <= 0xff) {
+            AddressingModeId::Xin
+        } else {
+            AddressingModeId::Aix
+        };
+        operand = o;
+    } else if let Some(v) = operand_s
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix("),y"))
+    {
+        // indirect Y
+        mode_id = AddressingModeId::Iny;
+        operand = asm_value(v, resolve)?.0;
+    } else if let Some(v) = operand_s
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix(')'))
+    {
+        // indirect, or indirect zeropage (65c02)
+        let (o, digits) = asm_value(v, resolve)?;
+        let zp = digits == 1
+            || digits == 2
+            || (digits == 0
+                && o <= 0xff
+                && find_instruction(t, &opcode, AddressingModeId::Izp).is_some());
+        mode_id = if zp {
+            AddressingModeId::Izp
+        } else {
+            AddressingModeId::Ind
+        };
+        operand = o;
+    } else if let Some((zp, rel)) = operand_s
+        .split_once(',')
+        .filter(|(_, r)| *r != "x" && *r != "y")
+    {
+        // zeropage relative (65c02)
+        mode_id = AddressingModeId::Zpr;
+        operand = asm_value(zp, resolve)?.0;
+        let (r, digits) = asm_value(rel, resolve)?;
+        if digits == 1 || digits == 2 {
+            // raw offset
+            operand |= r << 8;
+        } else {
+            branch_target = Some(r);
+        }
+    } else {
+        // zeropage/absolute, optionally indexed, or relative
+        let (v, index) = match operand_s.split_once(',') {
+            Some((v, i)) => (v, Some(i)),
+            None => (operand_s.as_str(), None),
+        };
+        let (o, digits) = asm_value(v, resolve)?;
+        let zp = digits == 1 || digits == 2 || (digits == 0 && o <= 0xff);
+        operand = o;
+        mode_id = match (index, zp) {
+            (None, _) if is_branch => AddressingModeId::Rel,
+            (None, true) if find_instruction(t, &opcode, AddressingModeId::Zpg).is_some() => {
+                AddressingModeId::Zpg
+            }
+            (None, _) => AddressingModeId::Abs,
+            (Some("x"), true) if find_instruction(t, &opcode, AddressingModeId::Zpx).is_some() => {
+                AddressingModeId::Zpx
+            }
+            (Some("x"), _) => AddressingModeId::Abx,
+            (Some("y"), true) if find_instruction(t, &opcode, AddressingModeId::Zpy).is_some() => {
+                AddressingModeId::Zpy
+            }
+            (Some("y"), _) => AddressingModeId::Aby,
+            _ => return Err(format!("invalid operand '{}'!", operand_s)),
+        };
+        if mode_id == AddressingModeId::Rel && digits != 1 && digits != 2 {
+            // not a raw offset
+            branch_target = Some(o);
+        }
+    }
+
+    // find a match in the opcode matrix
+    let op_byte = match find_instruction(t, &opcode, mode_id) {
+        None => return Err(String::from("invalid opcode!")),
+        Some(b) => b,
+    };
+
+    // branch targets are relative to the next instruction
+    let size = mode_id.instr_size() as u16;
+    if let Some(tgt) = branch_target {
+        let offset = tgt.wrapping_sub(address.wrapping_add(size)) as i16;
+        if !(-128..=127).contains(&offset) {
+            return Err(format!("branch target ${:04x} out of range!", tgt));
+        }
+        if mode_id == AddressingModeId::Zpr {
+            operand = (operand & 0xff) | ((offset as u8 as u16) << 8);
+        } else {
+            operand = offset as u8 as u16;
+        }
+    }
+    match size {
+        1 => Ok(vec![op_byte]),
+        2 if operand > 0xff => Err(format!("invalid operand '{}'!", operand_s)),
+        2 => Ok(vec![op_byte, operand as u8]),
+        _ => Ok(vec![op_byte, (operand & 0xff) as u8, (operand >> 8) as u8]),
+    }
+}
@@ -0,0 +1,200 @@
+/*
+ * Filename: /src/cpu/builder.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 21:02:37
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::{self, Bus};
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::cpu::{Cpu, CpuHook, CpuType, HookFilter};
+use crate::memory::{self, MemoryInitPolicy};
+use alloc::{boxed::Box, format, string::String};
+
+/**
+ * builds a Cpu with its memory (or bus) and options in one go, as an alternative to Cpu::new() plus the setters.
+ *
+ * ```
+ * use rv6502emu::cpu::{CpuBuilder, CpuCallbackContext, CpuType, CpuView};
+ *
+ * let mut c = CpuBuilder::new()
+ *     .cpu_type(CpuType::WDC65C02)
+ *     .memory_size(0x10000)
+ *     .hook(|ctx: &CpuCallbackContext, _: &mut CpuView| println!("{}", ctx))
+ *     .build()
+ *     .unwrap();
+ * assert_eq!(c.cpu_type(), CpuType::WDC65C02);
+ * assert_eq!(c.bus.get_memory().get_size(), 0x10000);
+ * ```
+ */
+pub struct CpuBuilder {
+    cpu_type: CpuType,
+    memory_size: Option<usize>,
+    bus: Option<Box<dyn Bus>>,
+    hook: Option<Box<dyn CpuHook<Box<dyn Bus>>>>,
+    deadlock_detection: bool,
+    address_wrapping: bool,
+    bcd_enabled: bool,
+    memory_init: Option<MemoryInitPolicy>,
+}
+
+impl Default for CpuBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuBuilder {
+    /**
+     * creates a new builder: MOS6502 with 64k of memory on the default bus, no hook, the options as in Cpu::new().
+     */
+    pub fn new() -> CpuBuilder {
+        CpuBuilder {
+            cpu_type: CpuType::MOS6502,
+            memory_size: None,
+            bus: None,
+            hook: None,
+            deadlock_detection: true,
+            address_wrapping: false,
+            bcd_enabled: true,
+            memory_init: None,
+        }
+    }
+
+    /**
+     * sets the emulated cpu type.
+     */
+    pub fn cpu_type(mut self, t: CpuType) -> CpuBuilder {
+        self.cpu_type = t;
+        self
+    }
+
+    /**
+     * sets the size of the memory on the default bus (64k if not set), it must cover the vectors.
+     */
+    pub fn memory_size(mut self, size: usize) -> CpuBuilder {
+        self.memory_size = Some(size);
+        self
+    }
+
+    /**
+     * attaches a custom bus (i.e. built with BusBuilder), instead of the default one. can't be used with memory_size().
+     */
+    pub fn bus(mut self, b: Box<dyn Bus>) -> CpuBuilder {
+        self.bus = Some(b);
+        self
+    }
+
+    /**
+     * installs the hook, as Cpu::set_hook().
+     */
+    pub fn hook<H: CpuHook<Box<dyn Bus>> + 'static>(mut self, hook: H) -> CpuBuilder {
+        self.hook = Some(Box::new(hook));
+        self
+    }
+
+    /**
+     * as Cpu::set_deadlock_detection().
+     */
+    pub fn deadlock_detection(mut self, enable: bool) -> CpuBuilder {
+        self.deadlock_detection = enable;
+        self
+    }
+
+    /**
+     * as Cpu::set_address_wrapping().
+     */
+    pub fn address_wrapping(mut self, enable: bool) -> CpuBuilder {
+        self.address_wrapping = enable;
+        self
+    }
+
+    /**
+     * as Cpu::set_bcd_enabled().
+     */
+    pub fn decimal_mode_enabled(mut self, enable: bool) -> CpuBuilder {
+        self.bcd_enabled = enable;
+        self
+    }
+
+    /**
+     * fills the memory according to policy and keeps it for Memory::clear(). with a custom bus, its memory is cleared.
+     */
+    pub fn memory_init(mut self, policy: MemoryInitPolicy) -> CpuBuilder {
+        self.memory_init = Some(policy);
+        self
+    }
+
+    /**
+     * builds the cpu, errors on invalid combinations (memory not covering the vectors, both bus and memory size set).
+     */
+    pub fn build(self) -> Result<Cpu, CpuError> {
+        let b = match (self.bus, self.memory_size) {
+            (Some(_), Some(_)) => {
+                return Err(CpuError::new_default(
+                    CpuErrorType::Generic,
+                    0,
+                    Some(String::from(
+                        "memory size can't be set along with a custom bus",
+                    )),
+                ));
+            }
+            (Some(mut b), None) => {
+                if let Some(p) = self.memory_init {
+                    b.get_memory().set_init_policy(p);
+                    b.get_memory().clear();
+                }
+                b
+            }
+            (None, size) => {
+                let size = size.unwrap_or(0x10000);
+                if size != 0x10000 {
+                    return Err(CpuError::new_default(
+                        CpuErrorType::Generic,
+                        0,
+                        Some(format!(
+                            "memory size ${:x} must be $10000 to cover the vectors",
+                            size
+                        )),
+                    ));
+                }
+                bus::new_default(memory::new_with_policy(
+                    size,
+                    self.memory_init.unwrap_or_default(),
+                ))
+            }
+        };
+        let mut c = Cpu::new(b, None, Some(self.cpu_type));
+        if let Some(h) = self.hook {
+            c.hooks.push((0, HookFilter::default(), h));
+            c.next_hook_id = 1;
+        }
+        c.set_deadlock_detection(self.deadlock_detection);
+        c.set_address_wrapping(self.address_wrapping);
+        c.set_bcd_enabled(self.bcd_enabled);
+        Ok(c)
+    }
+}
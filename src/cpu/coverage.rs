@@ -0,0 +1,230 @@
+/*
+ * Filename: /src/cpu/coverage.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 17:52:09
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::Cpu;
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/**
+ * executed/read/written bitmaps over the 64K address space.
+ */
+pub struct Coverage {
+    /// bytes belonging to an executed instruction.
+    executed: Box<[u64]>,
+    /// bytes read by instructions (opcode fetches excluded).
+    read: Box<[u64]>,
+    /// bytes written by instructions.
+    written: Box<[u64]>,
+}
+
+/**
+ * set the bit for address.
+ */
+fn set(bitmap: &mut [u64], address: u16) {
+    bitmap[(address >> 6) as usize] |= 1 << (address & 0x3f);
+}
+
+/**
+ * check the bit for address.
+ */
+fn get(bitmap: &[u64], address: u16) -> bool {
+    bitmap[(address >> 6) as usize] & (1 << (address & 0x3f)) != 0
+}
+
+/**
+ * turn a bitmap into (start, end) inclusive ranges.
+ */
+fn ranges(bitmap: &[u64]) -> Vec<(u16, u16)> {
+    let mut v: Vec<(u16, u16)> = Vec::new();
+    let mut start: Option<u16> = None;
+    for a in 0..=0xffff_u16 {
+        match (get(bitmap, a), start) {
+            (true, None) => start = Some(a),
+            (false, Some(s)) => {
+                v.push((s, a - 1));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(s) = start {
+        v.push((s, 0xffff));
+    }
+    v
+}
+
+/**
+ * count the set bits.
+ */
+fn count(bitmap: &[u64]) -> usize {
+    bitmap.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+impl Coverage {
+    pub(crate) fn new() -> Coverage {
+        Coverage {
+            executed: vec![0; 1024].into_boxed_slice(),
+            read: vec![0; 1024].into_boxed_slice(),
+            written: vec![0; 1024].into_boxed_slice(),
+        }
+    }
+
+    /**
+     * clear all the bitmaps.
+     */
+    pub fn clear(&mut self) {
+        self.executed.fill(0);
+        self.read.fill(0);
+        self.written.fill(0);
+    }
+
+    /**
+     * returns true if the byte at address belongs to an executed instruction.
+     */
+    pub fn is_executed(&self, address: u16) -> bool {
+        get(&self.executed, address)
+    }
+
+    /**
+     * returns true if the byte at address has been read.
+     */
+    pub fn is_read(&self, address: u16) -> bool {
+        get(&self.read, address)
+    }
+
+    /**
+     * returns true if the byte at address has been written.
+     */
+    pub fn is_written(&self, address: u16) -> bool {
+        get(&self.written, address)
+    }
+
+    /**
+     * executed bytes, as (start, end) inclusive ranges.
+     */
+    pub fn executed_ranges(&self) -> Vec<(u16, u16)> {
+        ranges(&self.executed)
+    }
+
+    /**
+     * read bytes, as (start, end) inclusive ranges.
+     */
+    pub fn read_ranges(&self) -> Vec<(u16, u16)> {
+        ranges(&self.read)
+    }
+
+    /**
+     * written bytes, as (start, end) inclusive ranges.
+     */
+    pub fn written_ranges(&self) -> Vec<(u16, u16)> {
+        ranges(&self.written)
+    }
+
+    /**
+     * returns the number of (executed, read, written) bytes.
+     */
+    pub fn counts(&self) -> (usize, usize, usize) {
+        (
+            count(&self.executed),
+            count(&self.read),
+            count(&self.written),
+        )
+    }
+
+    /**
+     * one byte per address, bit 0 = executed, bit 1 = read, bit 2 = written.
+     */
+    pub fn to_map(&self) -> Vec<u8> {
+        (0..=0xffff_u16)
+            .map(|a| {
+                (self.is_executed(a) as u8)
+                    | ((self.is_read(a) as u8) << 1)
+                    | ((self.is_written(a) as u8) << 2)
+            })
+            .collect()
+    }
+}
+
+impl<B: Bus> Cpu<B> {
+    /**
+     * enable/disable coverage tracking, enabling clears the bitmaps.
+     */
+    pub fn enable_coverage(&mut self, enable: bool) {
+        if enable {
+            self.coverage.clear();
+        }
+        self.coverage_enabled = enable;
+    }
+
+    /**
+     * returns true if coverage tracking is enabled.
+     */
+    pub fn is_coverage_enabled(&self) -> bool {
+        self.coverage_enabled
+    }
+
+    /**
+     * the coverage bitmaps.
+     */
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+
+    /**
+     * clear the coverage bitmaps.
+     */
+    pub fn clear_coverage(&mut self) {
+        self.coverage.clear();
+    }
+
+    /**
+     * mark size bytes starting at address as executed, if tracking.
+     */
+    pub(crate) fn cover_exec(&mut self, address: u16, size: u8) {
+        if self.coverage_enabled {
+            for i in 0..size as u16 {
+                set(&mut self.coverage.executed, address.wrapping_add(i));
+            }
+        }
+    }
+
+    /**
+     * mark the byte at address as read or written, if tracking.
+     */
+    pub(crate) fn cover_access(&mut self, address: u16, write: bool) {
+        if self.coverage_enabled {
+            if write {
+                set(&mut self.coverage.written, address);
+            } else {
+                set(&mut self.coverage.read, address);
+            }
+        }
+    }
+}
@@ -29,7 +29,8 @@
  */
 
 use crate::cpu::addressing_modes::AddressingModeId;
-use std::fmt;
+use alloc::string::String;
+use core::fmt;
 
 /**
  * type of cpu error.
@@ -48,12 +49,16 @@ pub enum CpuErrorType {
     RwBreakpoint,
     /// deadlock.
     Deadlock,
+    /// cpu halted by STP, only reset() recovers.
+    Halted,
+    /// write to a read-only (rom) region.
+    ReadOnlyMemory,
     /// generic error
     Generic,
 }
 
-impl std::fmt::Display for CpuErrorType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+impl core::fmt::Display for CpuErrorType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> fmt::Result {
         match self {
             CpuErrorType::MemoryRead => write!(f, "MemRead"),
             CpuErrorType::MemoryWrite => write!(f, "MemWrite"),
@@ -61,6 +66,8 @@ impl std::fmt::Display for CpuErrorType {
             CpuErrorType::InvalidOpcode => write!(f, "InvalidOpcode"),
             CpuErrorType::RwBreakpoint => write!(f, "RwBreakpoint"),
             CpuErrorType::Deadlock => write!(f, "Deadlock"),
+            CpuErrorType::Halted => write!(f, "Halted"),
+            CpuErrorType::ReadOnlyMemory => write!(f, "ReadOnlyMemory"),
             CpuErrorType::Generic => write!(f, "Generic"),
         }
     }
@@ -83,17 +90,41 @@ pub struct CpuError {
     pub bp_idx: i8,
     /// an optional message.
     pub msg: Option<String>,
+    /// the opcode byte being executed or decoded, if any.
+    pub opcode: Option<u8>,
+    /// the addressing mode of the opcode, if any.
+    pub mode: Option<AddressingModeId>,
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for CpuError {}
 
-impl std::fmt::Display for CpuError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+impl core::fmt::Display for CpuError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_error(f)?;
+        if let Some(op) = self.opcode {
+            write!(f, ", opcode=${:02x}", op)?;
+        }
+        if let Some(mode) = self.mode {
+            write!(f, ", mode={:?}", mode)?;
+        }
+        Ok(())
+    }
+}
+
+impl CpuError {
+    /**
+     * display the type specific part of the error.
+     */
+    fn fmt_error(&self, f: &mut core::fmt::Formatter<'_>) -> fmt::Result {
         match self.t {
-            CpuErrorType::MemoryLoad => {
+            CpuErrorType::MemoryLoad if self.msg.is_some() => {
                 write!(f, "Error ({}), msg={}", self.t, self.msg.as_ref().unwrap(),)
             }
-            CpuErrorType::Generic | CpuErrorType::InvalidOpcode | CpuErrorType::Deadlock => {
+            CpuErrorType::Generic
+            | CpuErrorType::InvalidOpcode
+            | CpuErrorType::Deadlock
+            | CpuErrorType::Halted => {
                 write!(
                     f,
                     "Error ({}) PC=${:04x} {}",
@@ -116,6 +147,7 @@ impl std::fmt::Display for CpuError {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for CpuError {
     fn from(err: std::io::Error) -> Self {
         let e = CpuError {
@@ -125,6 +157,8 @@ impl From<std::io::Error> for CpuError {
             access_size: 0,
             bp_idx: 0,
             msg: Some(err.to_string()),
+            opcode: None,
+            mode: None,
         };
         e
     }
@@ -141,9 +175,22 @@ impl CpuError {
             access_size: 0,
             bp_idx: 0,
             msg: m,
+            opcode: None,
+            mode: None,
         };
         e
     }
+
+    /**
+     * sets the opcode and its addressing mode, unless already set (i.e. by an inner call).
+     */
+    pub(crate) fn with_opcode(mut self, opcode: u8, mode: AddressingModeId) -> Self {
+        if self.opcode.is_none() {
+            self.opcode = Some(opcode);
+            self.mode = Some(mode);
+        }
+        self
+    }
 }
 
 /**
@@ -158,7 +205,8 @@ pub(crate) fn check_address_boundaries(
     msg: Option<String>,
 ) -> Result<(), CpuError> {
     // check if memory access overflows
-    if (address + access_size - 1 > mem_size) || (address + access_size - 1) > 0xffff {
+    let end = address.saturating_add(access_size);
+    if end > mem_size || end > 0x10000 {
         // report read or write error
         let e = CpuError {
             t: op,
@@ -167,6 +215,8 @@ pub(crate) fn check_address_boundaries(
             access_size: access_size,
             bp_idx: 0,
             msg: msg,
+            opcode: None,
+            mode: None,
         };
         return Err(e);
     }
@@ -177,6 +227,21 @@ pub(crate) fn check_address_boundaries(
  * check memory boundaries during opcode access
  */
 pub(crate) fn check_opcode_boundaries(
+    mem_size: usize,
+    address: usize,
+    opcode: u8,
+    addr_mode: AddressingModeId,
+    op: CpuErrorType,
+    msg: Option<String>,
+) -> Result<(), CpuError> {
+    check_instruction_boundaries(mem_size, address, addr_mode, op, msg)
+        .map_err(|e| e.with_opcode(opcode, addr_mode))
+}
+
+/**
+ * check memory boundaries for the whole instruction at address
+ */
+fn check_instruction_boundaries(
     mem_size: usize,
     address: usize,
     addr_mode: AddressingModeId,
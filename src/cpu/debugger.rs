@@ -28,19 +28,58 @@
  * SOFTWARE.
  */
 
+use crate::bus::Bus;
 use crate::cpu::cpu_error;
-use crate::cpu::cpu_error::CpuErrorType;
-use crate::cpu::{Cpu, CpuFlags, CpuType};
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::cpu::{Cpu, CpuFlags, CpuType, ResetKind};
+use crate::memory::MemoryInitPolicy;
 use crate::utils::*;
-use breakpoints::Bp;
-use hexplay::HexViewBuilder;
+use log::info;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
 use std::fs::File;
 use std::io;
 use std::io::{BufRead, Write};
 use std::str::SplitWhitespace;
 
+/**
+ * writes to the debugger output, as print!().
+ */
+macro_rules! dbg_print {
+    ($d:expr, $($arg:tt)*) => {
+        $d.write_out(format_args!($($arg)*))
+    };
+}
+
+/**
+ * writes a line to the debugger output, as println!().
+ */
+macro_rules! dbg_println {
+    ($d:expr) => {
+        $d.write_out(format_args!("\n"))
+    };
+    ($d:expr, $($arg:tt)*) => {
+        $d.write_out(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+pub(crate) use dbg_println;
+
 mod asm_disasm;
+mod backtrace;
 pub(crate) mod breakpoints;
+#[cfg(feature = "gdbstub")]
+pub mod gdbstub;
+mod hexdump;
+mod history;
+mod json;
+mod labels;
+mod script;
+mod trace;
+pub use backtrace::Frame;
+pub use breakpoints::{Bp, BpCondition, BpId, BreakpointType, FlagTrigger, InterruptContext};
+pub use hexdump::{HexdumpOptions, HexdumpText};
+pub use trace::{TraceDivergence, TraceFormat, TRACE_CONTEXT};
 
 /**
  * exposes the debugger.
@@ -57,6 +96,81 @@ pub struct Debugger {
 
     /// to display registers before the opcode.
     pub(crate) show_registers_before_opcode: bool,
+
+    /// the last triggered breakpoint.
+    pub(crate) triggered_bp: Option<BpId>,
+
+    /// set by the 'fin' command to the S value to step out from.
+    pub(crate) step_out_s: Option<u8>,
+
+    /// commands to execute before reading stdin.
+    cmd_queue: VecDeque<String>,
+
+    /// instruction trace, if active.
+    trace: Option<trace::Trace>,
+
+    /// symbols loaded from label files.
+    labels: labels::Labels,
+
+    /// set when run_until() returns on a breakpoint, to not trigger it again when resuming.
+    pub(crate) resume_pc: Option<u16>,
+
+    /// breakpoints are ignored while set (the first instruction when resuming).
+    pub(crate) bp_suspended: bool,
+
+    /// executed steps history, if enabled.
+    history: Option<history::History>,
+
+    /// irq/nmi breakpoint matched by the interrupt sequence, triggered before the handler executes.
+    pub(crate) pending_interrupt_bp: Option<(i8, InterruptContext)>,
+
+    /// the context of the last triggered irq/nmi breakpoint.
+    interrupt_ctx: Option<InterruptContext>,
+
+    /// the flag change which triggered the last flag breakpoint.
+    flag_change: Option<String>,
+
+    /// where the debugger output goes, stdout by default.
+    out: RefCell<Box<dyn Write>>,
+
+    /// 'r', 'bl', 'd', 'x' and the errors are output as single line json.
+    json: bool,
+}
+
+/**
+ * a differing byte, as returned by Debugger::compare_memory().
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// offset from the start of both regions.
+    pub offset: usize,
+
+    /// the byte in the first region.
+    pub a: u8,
+
+    /// the byte in the second region.
+    pub b: u8,
+}
+
+/**
+ * the output of a detached debugger, complete lines are logged (info level).
+ */
+#[derive(Default)]
+struct LogWriter(Vec<u8>);
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        while let Some(i) = self.0.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.0.drain(..=i).collect();
+            info!("{}", String::from_utf8_lossy(&line[..i]));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 impl Debugger {
@@ -69,28 +183,391 @@ impl Debugger {
             enabled: enabled,
             going: false,
             show_registers_before_opcode: false,
+            triggered_bp: None,
+            step_out_s: None,
+            cmd_queue: VecDeque::new(),
+            trace: None,
+            labels: labels::Labels::default(),
+            resume_pc: None,
+            bp_suspended: false,
+            history: None,
+            pending_interrupt_bp: None,
+            interrupt_ctx: None,
+            flag_change: None,
+            out: RefCell::new(Box::new(io::stdout())),
+            json: false,
         }
     }
 
+    /**
+     * the passive debugger used when no debugger is attached (i.e. run_until(None, ..)), its output goes to the log.
+     */
+    pub(crate) fn new_detached() -> Debugger {
+        let mut d = Debugger::new(false);
+        d.set_output(LogWriter::default());
+        d
+    }
+
+    /**
+     * redirect the debugger output (commands output, prompts, breakpoint messages, registers and, with logging enabled,
+     * the executed instructions) to w, i.e. a Vec<u8> to capture it or a TUI widget.
+     *
+     * ```
+     * use rv6502emu::cpu::Cpu;
+     * use rv6502emu::cpu::debugger::Debugger;
+     * use std::cell::RefCell;
+     * use std::io::Write;
+     * use std::rc::Rc;
+     *
+     * // a writer shared with the caller
+     * #[derive(Clone, Default)]
+     * struct Sink(Rc<RefCell<Vec<u8>>>);
+     * impl Write for Sink {
+     *     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+     *         self.0.borrow_mut().write(buf)
+     *     }
+     *     fn flush(&mut self) -> std::io::Result<()> {
+     *         Ok(())
+     *     }
+     * }
+     *
+     * let mut c = Cpu::new_default(None);
+     * let mut d = Debugger::new(true);
+     * let sink = Sink::default();
+     * d.set_output(sink.clone());
+     * d.parse_cmd(&mut c, "bl");
+     * assert_eq!(String::from_utf8(sink.0.borrow().clone()).unwrap(), "no breakpoints set.\n");
+     * ```
+     */
+    pub fn set_output<W: Write + 'static>(&mut self, w: W) {
+        self.out = RefCell::new(Box::new(w));
+    }
+
+    /**
+     * switch the output of the 'r', 'bl', 'd' and 'x' commands to single line json, for front-ends parsing it (as the
+     * 'set output json' command does).
+     *
+     * registers are an object with numeric fields, breakpoints an array of {id, address, end, type, enabled, hits, text},
+     * disassembly an array of {addr, bytes, text} and memory an object {address, bytes}. errors are reported as
+     * {"error": "..."} and 'bc' does not ask for confirmation. the other commands output text as usual.
+     */
+    pub fn set_json_output(&mut self, enable: bool) {
+        self.json = enable;
+    }
+
+    /**
+     * whether the json output is enabled.
+     */
+    pub fn is_json_output(&self) -> bool {
+        self.json
+    }
+
+    /**
+     * write to the debugger output, errors are ignored as with print!().
+     */
+    pub(crate) fn write_out(&self, args: fmt::Arguments<'_>) {
+        let mut out = self.out.borrow_mut();
+        let _ = out.write_fmt(args);
+        let _ = out.flush();
+    }
+
+    /**
+     * queue a command to be executed at the next debugger prompt, instead of reading from stdin.
+     */
+    pub fn queue_cmd(&mut self, cmd: &str) {
+        self.cmd_queue.push_back(String::from(cmd));
+    }
+
+    /**
+     * step over: if the instruction at pc is JSR, runs until it returns at the current stack depth.
+     *
+     * returns false if the instruction is not a JSR, then it's just a single step.
+     */
+    pub fn step_over<B: Bus>(&mut self, c: &mut Cpu<B>) -> Result<bool, CpuError> {
+        if c.fetch()? != 0x20 {
+            return Ok(false);
+        }
+        let cond = BpCondition {
+            s: Some(c.regs.s),
+            ..Default::default()
+        };
+        self.add_temporary_breakpoint(c.regs.pc.wrapping_add(3), Some(cond));
+        self.going = true;
+        Ok(true)
+    }
+
+    /**
+     * step out: runs until an RTS/RTI executes at the current stack depth.
+     */
+    pub fn step_out<B: Bus>(&mut self, c: &mut Cpu<B>) {
+        self.step_out_s = Some(c.regs.s);
+        self.going = true;
+    }
+
+    /**
+     * run to address, using a one-shot exec breakpoint.
+     */
+    pub fn run_to<B: Bus>(&mut self, c: &mut Cpu<B>, address: u16) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            c.bus.get_memory().get_size(),
+            address as usize,
+            1,
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+        self.add_temporary_breakpoint(address, None);
+        self.going = true;
+        Ok(())
+    }
+
+    /**
+     * check if opcode (about to be executed) is the RTS/RTI which ends a step out.
+     */
+    pub(crate) fn is_step_out_done<B: Bus>(&mut self, c: &Cpu<B>, opcode: u8) -> bool {
+        match self.step_out_s {
+            // pushes not yet pulled in the current routine leave S above the original depth
+            Some(s) if (opcode == 0x60 || opcode == 0x40) && c.regs.s >= s => {
+                self.step_out_s = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /**
+     * returns true if the debugger has nothing to check while running (disabled, no enabled breakpoints, no trace, no
+     * history and no pending step out), so the cpu can skip it entirely.
+     */
+    pub(crate) fn is_passive(&self) -> bool {
+        !self.enabled
+            && self.trace.is_none()
+            && self.history.is_none()
+            && self.step_out_s.is_none()
+            && !self.breakpoints.iter().any(|bp| bp.is_enabled())
+    }
+
+    /**
+     * returns the index of the last triggered breakpoint, if any.
+     */
+    pub fn triggered_breakpoint(&self) -> Option<BpId> {
+        self.triggered_bp
+    }
+
     /**
      * report invalid command
      */
     fn cmd_invalid(&self) {
-        println!("invalid command, try 'h' for help !");
+        if self.json {
+            dbg_println!(self, "{{\"error\":\"invalid command\"}}");
+            return;
+        }
+        dbg_println!(self, "invalid command, try 'h' for help !");
+    }
+
+    /**
+     * report an error from the debugger api, generic errors just carry a message for the user.
+     */
+    fn cmd_error(&self, e: &CpuError) {
+        let msg = match (&e.t, &e.msg) {
+            (CpuErrorType::Generic, Some(m)) => m.clone(),
+            _ => e.to_string(),
+        };
+        if self.json {
+            dbg_println!(self, "{{\"error\":{}}}", json::string(&msg));
+        } else {
+            dbg_println!(self, "{}", msg);
+        }
+    }
+
+    /**
+     * set a debugger option: 'set output <json|text>'.
+     */
+    fn cmd_set(&mut self, mut it: SplitWhitespace<'_>) -> bool {
+        match (it.next(), it.next()) {
+            (Some("output"), Some("json")) => self.json = true,
+            (Some("output"), Some("text")) => self.json = false,
+            _ => {
+                self.cmd_invalid();
+                return false;
+            }
+        }
+        if self.json {
+            dbg_println!(self, "{{\"output\":\"json\"}}");
+        } else {
+            dbg_println!(self, "output is text.");
+        }
+        return true;
+    }
+
+    /**
+     * read len bytes at address, without side effects on mapped devices.
+     */
+    pub fn read_memory<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        len: usize,
+    ) -> Result<Vec<u8>, CpuError> {
+        c.bus.dump_range(address as usize, len)
+    }
+
+    /**
+     * write bytes starting at address, bypassing rom protection.
+     */
+    pub fn write_memory<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        data: &[u8],
+    ) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            c.bus.get_memory().get_size(),
+            address as usize,
+            data.len(),
+            CpuErrorType::MemoryWrite,
+            None,
+        )?;
+        for (i, b) in data.iter().enumerate() {
+            c.bus.poke(address.wrapping_add(i as u16), *b)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * fill len bytes starting at address repeating pattern, bypassing rom protection.
+     *
+     * like write_memory(), this doesn't go through the bus so write breakpoints are not triggered.
+     */
+    pub fn fill_memory<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        len: usize,
+        pattern: &[u8],
+    ) -> Result<(), CpuError> {
+        if pattern.is_empty() {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                address,
+                Some(String::from("empty fill pattern!")),
+            ));
+        }
+        let data: Vec<u8> = pattern.iter().cycle().take(len).copied().collect();
+        self.write_memory(c, address, &data)
+    }
+
+    /**
+     * copy len bytes from src to dst, bypassing rom protection.
+     *
+     * overlapping ranges are handled (like memmove), write breakpoints are not triggered.
+     */
+    pub fn copy_memory<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        src: u16,
+        dst: u16,
+        len: usize,
+    ) -> Result<(), CpuError> {
+        // read it all first, so overlapping works in both directions
+        let data = self.read_memory(c, src, len)?;
+        self.write_memory(c, dst, &data)
+    }
+
+    /**
+     * search memory between start and end (inclusive) for pattern, None matches any byte.
+     *
+     * returns the addresses of all the matches (overlapping too), at most max (0 = no limit).
+     */
+    pub fn find_memory<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        start: u16,
+        end: u16,
+        pattern: &[Option<u8>],
+        max: usize,
+    ) -> Result<Vec<u16>, CpuError> {
+        if pattern.is_empty() || end < start {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                start,
+                Some(String::from("invalid pattern or range!")),
+            ));
+        }
+        let m = self.read_memory(c, start, (end - start) as usize + 1)?;
+        let mut v: Vec<u16> = Vec::new();
+        for (i, w) in m.windows(pattern.len()).enumerate() {
+            let found = w
+                .iter()
+                .zip(pattern.iter())
+                .all(|(b, p)| p.is_none_or(|p| p == *b));
+            if found {
+                v.push(start + i as u16);
+                if v.len() == max {
+                    break;
+                }
+            }
+        }
+        Ok(v)
+    }
+
+    /**
+     * compare len bytes at address1 with len bytes at address2.
+     *
+     * returns the differing bytes, empty if the regions are identical.
+     */
+    pub fn compare_memory<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address1: u16,
+        address2: u16,
+        len: usize,
+    ) -> Result<Vec<Mismatch>, CpuError> {
+        let m1 = self.read_memory(c, address1, len)?;
+        let m2 = self.read_memory(c, address2, len)?;
+        Ok(m1
+            .iter()
+            .zip(m2.iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, (a, b))| Mismatch {
+                offset: i,
+                a: *a,
+                b: *b,
+            })
+            .collect())
+    }
+
+    /**
+     * compute the CRC32 (IEEE, same as zip/png) of len bytes at address.
+     */
+    pub fn checksum<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        len: usize,
+    ) -> Result<u32, CpuError> {
+        let m = self.read_memory(c, address, len)?;
+        Ok(crc32(&m))
     }
 
     /**
      * perform cpu reset
      */
-    fn cmd_reset(&self, c: &mut Cpu, mut it: SplitWhitespace<'_>) -> bool {
+    fn cmd_reset<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
         let s = it.next().unwrap_or_default();
         if s.len() > 0 {
             // use provided address
-            let addr = u16::from_str_radix(&s[is_dollar_hex(&s)..], 16).unwrap_or_default();
-            println!("cpu reset, restarting at PC=${:04x}.", addr);
-            let _ = match c.reset(Some(addr)) {
+            let addr = match self.parse_address(s) {
+                Err(e) => {
+                    self.cmd_error(&e);
+                    return false;
+                }
+                Ok(a) => a,
+            };
+            dbg_println!(self, "cpu reset, restarting at PC=${:04x}.", addr);
+            let _ = match c.reset(Some(addr), ResetKind::PowerOn) {
                 Err(e) => {
-                    println!("{}", e);
+                    dbg_println!(self, "{}", e);
                     return false;
                 }
                 Ok(()) => (),
@@ -99,216 +576,953 @@ impl Debugger {
         }
 
         // use the reset vector as default
-        println!("cpu reset, restarting at RESET vector.");
-        let _ = match c.reset(None) {
+        dbg_println!(self, "cpu reset, restarting at RESET vector.");
+        let _ = match c.reset(None, ResetKind::PowerOn) {
+            Err(e) => {
+                dbg_println!(self, "{}", e);
+                return false;
+            }
+            Ok(()) => (),
+        };
+        return true;
+    }
+
+    /**
+     * write byte value/s at the given address.
+     */
+    fn cmd_edit_memory<B: Bus>(&self, c: &mut Cpu<B>, it: SplitWhitespace<'_>) -> bool {
+        // turn to collection
+        let col: Vec<&str> = it.collect();
+        let l = col.len();
+        if l < 2 {
+            // invalid command
+            self.cmd_invalid();
+            return false;
+        }
+
+        // last item is the address
+        let addr_s = col[l - 1];
+        let mut addr: u16;
+        let _ = match self.parse_address(addr_s) {
+            Err(e) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(a) => addr = a,
+        };
+
+        // parse values
+        let mut data: Vec<u8> = Vec::new();
+        for item in col[..l - 1].iter() {
+            let _ = match u8::from_str_radix(&item[is_dollar_hex(&item)..], 16) {
+                Err(_) => {
+                    // invalid command, value invalid
+                    self.cmd_invalid();
+                    return false;
+                }
+                Ok(a) => data.push(a),
+            };
+        }
+
+        // write all items starting at address
+        match self.write_memory(c, addr, &data) {
+            Err(e) => {
+                dbg_println!(self, "{}", e);
+                return false;
+            }
+            Ok(()) => {
+                dbg_println!(self, "writing {} bytes starting at {}.\n", l - 1, addr_s);
+                for item in col[..l - 1].iter() {
+                    dbg_println!(self, "written {} at ${:04x}.", item, addr);
+                    addr = addr.wrapping_add(1);
+                }
+            }
+        }
+        return true;
+    }
+
+    /**
+     * save/hexdump memory
+     */
+    fn cmd_dump_save_memory<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        cmd: &str,
+        mut it: SplitWhitespace<'_>,
+    ) -> bool {
+        // check input
+        let len_s = it.next().unwrap_or_default();
+        let mem = c.bus.get_memory();
+        let mut num_bytes = usize::from_str_radix(&len_s, 10).unwrap_or_default();
+        if num_bytes == 0 {
+            // set to full memory size
+            num_bytes = mem.get_size();
+        }
+        let addr_s = it.next().unwrap_or_default();
+        let addr: u16;
+
+        // get the start address
+        let _ = match self.parse_address(addr_s) {
+            Err(e) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(a) => addr = a,
+        };
+
+        let mut is_save: bool = false;
+        let mut file_path: &str = "";
+        if cmd.eq("s") {
+            is_save = true;
+            // get path
+            file_path = it.next().unwrap_or_default();
+            if file_path.len() == 0 {
+                // invalid command, path invalid
+                self.cmd_invalid();
+                return false;
+            }
+        }
+
+        if is_save {
+            // save to file
+            match c.bus.save_to_file(file_path, addr as usize, num_bytes) {
+                Err(e) => {
+                    dbg_println!(self, "{}", e);
+                    return false;
+                }
+                Ok(()) => dbg_println!(self, "file {} correctly saved!", file_path),
+            };
+        } else {
+            // options: width, u(ppercase), petscii/notext, >path
+            let mut opts = HexdumpOptions::default();
+            let mut out_path: Option<&str> = None;
+            for o in it {
+                match o.to_ascii_lowercase().as_str() {
+                    "u" => opts.uppercase = true,
+                    "petscii" => opts.text = HexdumpText::Petscii,
+                    "notext" => opts.text = HexdumpText::None,
+                    _ => {
+                        if let Some(p) = o.strip_prefix('>') {
+                            out_path = Some(p);
+                        } else if let Ok(w) = o.parse::<usize>() {
+                            opts.width = w;
+                        } else {
+                            self.cmd_invalid();
+                            return false;
+                        }
+                    }
+                }
+            }
+
+            if self.json && out_path.is_none() {
+                match self.read_memory(c, addr, num_bytes) {
+                    Err(e) => {
+                        self.cmd_error(&e);
+                        return false;
+                    }
+                    Ok(data) => dbg_println!(self, "{}", self.json_memory(addr, &data)),
+                }
+                return true;
+            }
+
+            // dump hex
+            let dump = match self.hexdump_with_options(c, addr, num_bytes, &opts) {
+                Err(e) => {
+                    self.cmd_error(&e);
+                    return false;
+                }
+                Ok(d) => d,
+            };
+            match out_path {
+                Some(p) => match std::fs::write(p, &dump) {
+                    Err(e) => {
+                        dbg_println!(self, "{}", e);
+                        return false;
+                    }
+                    Ok(()) => dbg_println!(
+                        self,
+                        "hexdump of {} bytes at ${:04x} saved to {}.",
+                        num_bytes,
+                        addr,
+                        p
+                    ),
+                },
+                None => {
+                    dbg_println!(self, "dumping {} bytes at ${:04x}\n", num_bytes, addr);
+                    dbg_print!(self, "{}", dump);
+                }
+            }
+        }
+        return true;
+    }
+
+    /**
+     * fill memory with a repeated pattern
+     */
+    fn cmd_fill_memory<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        // check input
+        let addr = match self.parse_address(it.next().unwrap_or_default()) {
+            Err(e) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(a) => a,
+        };
+        let len = match it.next().unwrap_or_default().parse::<usize>() {
+            Err(_) => {
+                // invalid command, len invalid
+                self.cmd_invalid();
+                return false;
+            }
+            Ok(l) => l,
+        };
+        let mut pattern: Vec<u8> = Vec::new();
+        for item in it.next().unwrap_or_default().split(',') {
+            match u8::from_str_radix(&item[is_dollar_hex(item)..], 16) {
+                Err(_) => {
+                    // invalid command, value invalid
+                    self.cmd_invalid();
+                    return false;
+                }
+                Ok(b) => pattern.push(b),
+            };
+        }
+
+        // fill
+        match self.fill_memory(c, addr, len, &pattern) {
+            Err(e) => {
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(_) => dbg_println!(self, "filled {} bytes at ${:04x}.", len, addr),
+        };
+        return true;
+    }
+
+    /**
+     * set the memory init policy and clear the memory, or show the current policy
+     */
+    fn cmd_init_memory<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        let policy = match it.next() {
+            None => {
+                dbg_println!(
+                    self,
+                    "memory init policy: {}.",
+                    c.bus.get_memory().init_policy()
+                );
+                return true;
+            }
+            Some("zero") => MemoryInitPolicy::Zero,
+            Some("ones") => MemoryInitPolicy::Ones,
+            Some("pattern") => {
+                let a = it.next().unwrap_or("$00");
+                let b = it.next().unwrap_or("$ff");
+                match (
+                    u8::from_str_radix(&a[is_dollar_hex(a)..], 16),
+                    u8::from_str_radix(&b[is_dollar_hex(b)..], 16),
+                ) {
+                    (Ok(a), Ok(b)) => MemoryInitPolicy::Pattern(a, b),
+                    _ => {
+                        // invalid command, value invalid
+                        self.cmd_invalid();
+                        return false;
+                    }
+                }
+            }
+            Some("random") => {
+                let seed = it.next().unwrap_or("$0");
+                match u64::from_str_radix(&seed[is_dollar_hex(seed)..], 16) {
+                    Ok(seed) => MemoryInitPolicy::Random { seed },
+                    Err(_) => {
+                        // invalid command, seed invalid
+                        self.cmd_invalid();
+                        return false;
+                    }
+                }
+            }
+            Some(_) => {
+                self.cmd_invalid();
+                return false;
+            }
+        };
+
+        // set and clear
+        let m = c.bus.get_memory();
+        m.set_init_policy(policy);
+        m.clear();
+        dbg_println!(self, "memory cleared, init policy: {}.", policy);
+        return true;
+    }
+
+    /**
+     * copy a memory region, overlapping allowed
+     */
+    fn cmd_copy_memory<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        // check input
+        let (src, dst) = match (
+            self.parse_address(it.next().unwrap_or_default()),
+            self.parse_address(it.next().unwrap_or_default()),
+        ) {
+            (Ok(s), Ok(d)) => (s, d),
+            (Err(e), _) | (_, Err(e)) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+        };
+        let len = match it.next().unwrap_or_default().parse::<usize>() {
+            Err(_) => {
+                // invalid command, len invalid
+                self.cmd_invalid();
+                return false;
+            }
+            Ok(l) => l,
+        };
+
+        // copy
+        match self.copy_memory(c, src, dst, len) {
+            Err(e) => {
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(_) => dbg_println!(
+                self,
+                "copied {} bytes from ${:04x} to ${:04x}.",
+                len,
+                src,
+                dst
+            ),
+        };
+        return true;
+    }
+
+    /**
+     * compare two memory regions
+     */
+    fn cmd_compare_memory<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        // check input
+        let (addr1, addr2) = match (
+            self.parse_address(it.next().unwrap_or_default()),
+            self.parse_address(it.next().unwrap_or_default()),
+        ) {
+            (Ok(a1), Ok(a2)) => (a1, a2),
+            (Err(e), _) | (_, Err(e)) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+        };
+        let len = it
+            .next()
+            .unwrap_or_default()
+            .parse::<usize>()
+            .unwrap_or_default();
+        if len == 0 {
+            // invalid command, len invalid
+            self.cmd_invalid();
+            return false;
+        }
+
+        // compare
+        let v = match self.compare_memory(c, addr1, addr2, len) {
+            Err(e) => {
+                dbg_println!(self, "{}", e);
+                return false;
+            }
+            Ok(v) => v,
+        };
+        if v.is_empty() {
+            dbg_println!(
+                self,
+                "{} bytes at ${:04x} and ${:04x} are identical.",
+                len,
+                addr1,
+                addr2
+            );
+            return true;
+        }
+        dbg_println!(
+            self,
+            "{} bytes at ${:04x} and ${:04x} differ in {} bytes.\n",
+            len,
+            addr1,
+            addr2,
+            v.len()
+        );
+        for m in v.iter() {
+            dbg_println!(
+                self,
+                "+${:04x}:\t${:04x}=${:02x}\t${:04x}=${:02x}",
+                m.offset,
+                addr1 as usize + m.offset,
+                m.a,
+                addr2 as usize + m.offset,
+                m.b
+            );
+        }
+        return true;
+    }
+
+    /**
+     * show CRC32 and 8 bit sum of a memory region
+     */
+    fn cmd_checksum<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        // check input
+        let addr = match self.parse_address(it.next().unwrap_or_default()) {
+            Err(e) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(a) => a,
+        };
+        let mut len = it
+            .next()
+            .unwrap_or_default()
+            .parse::<usize>()
+            .unwrap_or_default();
+        if len == 0 {
+            // up to the end of memory
+            len = c.bus.get_memory().get_size().saturating_sub(addr as usize);
+        }
+
+        // compute
+        let crc = match self.checksum(c, addr, len) {
             Err(e) => {
-                println!("{}", e);
+                dbg_println!(self, "{}", e);
                 return false;
             }
-            Ok(()) => (),
+            Ok(crc) => crc,
+        };
+        let sum = self
+            .read_memory(c, addr, len)
+            .unwrap_or_default()
+            .iter()
+            .fold(0u8, |s, b| s.wrapping_add(*b));
+        dbg_println!(
+            self,
+            "{} bytes at ${:04x}: crc32=${:08x}, sum=${:02x}",
+            len,
+            addr,
+            crc,
+            sum
+        );
+        return true;
+    }
+
+    /**
+     * search memory for a byte pattern (?? = any byte) or a "string" (ascii, or petscii)
+     */
+    fn cmd_find<B: Bus>(&self, c: &mut Cpu<B>, it: SplitWhitespace<'_>) -> bool {
+        let col: Vec<&str> = it.collect();
+        if col.len() < 3 {
+            // invalid command
+            self.cmd_invalid();
+            return false;
+        }
+
+        // get the range
+        let (start, end) = match (self.parse_address(col[0]), self.parse_address(col[1])) {
+            (Ok(s), Ok(e)) => (s, e),
+            (Err(e), _) | (_, Err(e)) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+        };
+
+        // options
+        let mut max: usize = 0;
+        let mut petscii = false;
+        let mut items: Vec<&str> = Vec::new();
+        for item in col[2..].iter() {
+            if item.eq_ignore_ascii_case("petscii") {
+                petscii = true;
+            } else if let Some(n) = item.strip_prefix("max=") {
+                max = match n.parse::<usize>() {
+                    Err(_) => {
+                        self.cmd_invalid();
+                        return false;
+                    }
+                    Ok(n) => n,
+                };
+            } else {
+                items.push(item);
+            }
+        }
+
+        // build the pattern
+        let mut pattern: Vec<Option<u8>> = Vec::new();
+        let joined = items.join(" ");
+        if joined.len() >= 2 && joined.starts_with('"') && joined.ends_with('"') {
+            // string
+            for ch in joined[1..joined.len() - 1].bytes() {
+                pattern.push(Some(if petscii { ascii_to_petscii(ch) } else { ch }));
+            }
+        } else {
+            for item in items.iter() {
+                if item.eq(&"??") {
+                    pattern.push(None);
+                    continue;
+                }
+                match u8::from_str_radix(&item[is_dollar_hex(item)..], 16) {
+                    Err(_) => {
+                        // invalid command, value invalid
+                        self.cmd_invalid();
+                        return false;
+                    }
+                    Ok(b) => pattern.push(Some(b)),
+                };
+            }
+        }
+
+        // search
+        let v = match self.find_memory(c, start, end, &pattern, max) {
+            Err(e) => {
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(v) => v,
+        };
+        dbg_println!(
+            self,
+            "found {} matches between ${:04x} and ${:04x}.\n",
+            v.len(),
+            start,
+            end
+        );
+        let size = c.bus.get_memory().get_size();
+        for a in v.iter() {
+            let len = std::cmp::min(16, size - *a as usize);
+            let m = self.read_memory(c, *a, len).unwrap_or_default();
+            let hex: Vec<String> = m.iter().map(|b| format!("{:02x}", b)).collect();
+            let text: String = m
+                .iter()
+                .map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            dbg_println!(self, "${:04x}:\t{}\t{}", a, hex.join(" "), text);
+        }
+        return true;
+    }
+
+    /**
+     * load file in memory
+     */
+    fn cmd_load_memory<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        // check input
+        let addr_s = it.next().unwrap_or_default();
+        let addr: u16;
+
+        let _ = match self.parse_address(addr_s) {
+            Err(e) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(a) => addr = a,
+        };
+
+        // get path
+        let file_path = it.next().unwrap_or_default();
+        if file_path.len() == 0 {
+            // invalid command, path invalid
+            self.cmd_invalid();
+            return false;
+        }
+        // get format, defaults to raw binary
+        let format = it.next().unwrap_or("bin");
+        if !matches!(format, "bin" | "ihex" | "srec" | "auto") {
+            // invalid command, format invalid
+            self.cmd_invalid();
+            return false;
+        }
+
+        // clear memory first
+        let mem = c.bus.get_memory();
+        mem.clear();
+
+        // and load
+        let res = match format {
+            "ihex" => mem.load_ihex(file_path),
+            "srec" => mem.load_srec(file_path),
+            "auto" => mem.load_image(file_path, addr as usize),
+            _ => mem.load(file_path, addr as usize).map(|_| (0, None)),
+        };
+        match res {
+            Err(e) => {
+                dbg_println!(self, "{}", e);
+                return false;
+            }
+            Ok((n, entry)) => {
+                if format != "bin" {
+                    dbg_print!(self, "{} bytes loaded from {}", n, file_path);
+                    match entry {
+                        Some(e) => dbg_println!(self, ", entry point ${:04x}.", e),
+                        None => dbg_println!(self, "."),
+                    }
+                }
+            }
+        };
+        return true;
+    }
+
+    /**
+     * run to address
+     */
+    fn cmd_run_to<B: Bus>(
+        &mut self,
+        c: &mut Cpu<B>,
+        mut it: SplitWhitespace<'_>,
+    ) -> (String, bool) {
+        let addr_s = it.next().unwrap_or_default();
+        let addr = match self.parse_address(addr_s) {
+            Err(e) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return (String::from("*"), false);
+            }
+            Ok(a) => a,
         };
-        return true;
+        match self.run_to(c, addr) {
+            Err(e) => {
+                dbg_println!(self, "{}", e);
+                (String::from("*"), false)
+            }
+            Ok(()) => (String::from("p"), true),
+        }
     }
 
     /**
-     * write byte value/s at the given address.
+     * enable (depth > 0)/disable (depth = 0) the history, or show its status.
      */
-    fn cmd_edit_memory(&self, c: &mut Cpu, it: SplitWhitespace<'_>) -> bool {
-        // turn to collection
-        let col: Vec<&str> = it.collect();
-        let l = col.len();
-        if l < 2 {
-            // invalid command
-            self.cmd_invalid();
-            return false;
+    fn cmd_history(&mut self, mut it: SplitWhitespace<'_>) -> bool {
+        let arg = it.next().unwrap_or_default();
+        if arg.is_empty() {
+            match &self.history {
+                None => dbg_println!(self, "history is disabled."),
+                Some(_) => dbg_println!(
+                    self,
+                    "history is enabled, {} steps recorded.",
+                    self.history_len()
+                ),
+            }
+            return true;
         }
-
-        // last item is the address
-        let addr_s = col[l - 1];
-        let mut addr: u16;
-        let _ = match u16::from_str_radix(&addr_s[is_dollar_hex(&addr_s)..], 16) {
+        let depth = match arg.parse::<usize>() {
             Err(_) => {
-                // invalid command, address invalid
+                // invalid command, depth invalid
                 self.cmd_invalid();
                 return false;
             }
-            Ok(a) => addr = a,
+            Ok(n) => n,
         };
+        self.enable_history(depth);
+        if depth == 0 {
+            dbg_println!(self, "history is disabled!");
+        } else {
+            dbg_println!(
+                self,
+                "history is enabled, recording the last {} steps!",
+                depth
+            );
+        }
+        return true;
+    }
 
-        // check access
-        let mem = c.bus.get_memory();
-        let _ = match cpu_error::check_address_boundaries(
-            mem.get_size(),
-            addr as usize,
-            col.len() - 1,
-            CpuErrorType::MemoryWrite,
-            None,
-        ) {
+    /**
+     * step back [n] (default 1) instructions.
+     */
+    fn cmd_step_back<B: Bus>(&mut self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        let arg = it.next().unwrap_or_default();
+        let n = if arg.is_empty() {
+            1
+        } else {
+            match arg.parse::<usize>() {
+                Err(_) => {
+                    // invalid command, count invalid
+                    self.cmd_invalid();
+                    return false;
+                }
+                Ok(n) => n,
+            }
+        };
+        if self.history.is_none() {
+            dbg_println!(self, "history is disabled, use 'hist <depth>' first.");
+            return false;
+        }
+        match self.step_back(c, n) {
             Err(e) => {
-                println!("{}", e);
+                dbg_println!(self, "{}", e);
                 return false;
             }
-            Ok(()) => (),
+            Ok(done) => {
+                dbg_println!(self, "stepped back {} steps.", done);
+                dbg_println!(self, "{}", c.regs);
+            }
         };
+        return true;
+    }
 
-        // write all items starting at address (may overlap)
-        println!("writing {} bytes starting at {}.\n", l - 1, addr_s);
-        for (i, item) in col.iter().enumerate() {
-            if i == (l - 1) {
-                break;
+    /**
+     * enable/disable the profiler, or show the hot addresses (default top 10) with their disassembly.
+     */
+    fn cmd_profile<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        let arg = it.next().unwrap_or_default();
+        match arg {
+            "on" => {
+                c.enable_profiling(true);
+                dbg_println!(self, "profiling is enabled!");
+                return true;
             }
-
-            let b: u8;
-            let _ = match u8::from_str_radix(&item[is_dollar_hex(&item)..], 16) {
+            "off" => {
+                c.enable_profiling(false);
+                dbg_println!(self, "profiling is disabled!");
+                return true;
+            }
+            _ => (),
+        }
+        let top_n = if arg.is_empty() {
+            10
+        } else {
+            match usize::from_str_radix(arg, 10) {
                 Err(_) => {
-                    // invalid command, value invalid
+                    // invalid command, count invalid
                     self.cmd_invalid();
                     return false;
                 }
-                Ok(a) => b = a,
-            };
-            let _ = match c.bus.get_memory().write_byte(addr as usize, b) {
-                Err(e) => {
-                    println!("{}", e);
-                    return false;
-                }
-                Ok(_) => {
-                    println!("written {} at ${:04x}.", item, addr);
-                }
-            };
+                Ok(n) => n,
+            }
+        };
+        if !c.is_profiling() {
+            dbg_println!(self, "profiling is disabled, use 'prof on' first.");
+            return false;
+        }
 
-            // next address
-            addr = addr.wrapping_add(1);
+        // walk
+        let total: usize = c
+            .profile_report(0)
+            .iter()
+            .map(|(_, _, cycles)| cycles)
+            .sum();
+        let v = c.profile_report(top_n);
+        dbg_println!(
+            self,
+            "top {} addresses by cycles, {} cycles total
+",
+            v.len(),
+            total
+        );
+        for (address, n, cycles) in v.iter() {
+            let text = match self.disassemble(c, *address, 1) {
+                Ok(i) => self.format_instruction(&i[0]),
+                Err(_) => format!("${:04x}", address),
+            };
+            dbg_println!(
+                self,
+                "{:>10} execs, {:>10} cycles ({:>5.1}%)\t{}",
+                n,
+                cycles,
+                (*cycles as f64) * 100.0 / (total.max(1) as f64),
+                text
+            );
         }
         return true;
     }
 
     /**
-     * save/hexdump memory
+     * enable/disable the access counters, or show them between start and end, one 16 bytes row per line (rows with no
+     * accesses are skipped): a digit per byte with the magnitude of its reads + writes (n = up to 2^n-1, . = none).
      */
-    fn cmd_dump_save_memory(&self, c: &mut Cpu, cmd: &str, mut it: SplitWhitespace<'_>) -> bool {
-        // check input
-        let len_s = it.next().unwrap_or_default();
-        let mem = c.bus.get_memory();
-        let mut num_bytes = usize::from_str_radix(&len_s, 10).unwrap_or_default();
-        if num_bytes == 0 {
-            // set to full memory size
-            num_bytes = mem.get_size();
-        }
-        let addr_s = it.next().unwrap_or_default();
-        let addr: usize;
-
-        // get the start address
-        let _ = match usize::from_str_radix(&addr_s[is_dollar_hex(&addr_s)..], 16) {
-            Err(_) => {
-                // invalid command, address invalid
-                self.cmd_invalid();
-                return false;
+    fn cmd_heat<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        let arg = it.next().unwrap_or_default();
+        match arg {
+            "on" => {
+                c.enable_access_counters(true);
+                dbg_println!(self, "access counters are enabled!");
+                return true;
             }
-            Ok(a) => addr = a,
-        };
-
-        let mut is_save: bool = false;
-        let mut file_path: &str = "";
-        if cmd.eq("s") {
-            is_save = true;
-            // get path
-            file_path = it.next().unwrap_or_default();
-            if file_path.len() == 0 {
-                // invalid command, path invalid
+            "off" => {
+                c.enable_access_counters(false);
+                dbg_println!(self, "access counters are disabled!");
+                return true;
+            }
+            "" => {
                 self.cmd_invalid();
                 return false;
             }
+            _ => (),
         }
-
-        // check access
-        let _ = match cpu_error::check_address_boundaries(
-            mem.get_size(),
-            addr as usize,
-            num_bytes as usize,
-            CpuErrorType::MemoryRead,
-            None,
+        let (start, end) = match (
+            self.parse_address(arg),
+            it.next().map(|a| self.parse_address(a)),
         ) {
-            Err(e) => {
-                println!("{}", e);
+            (Ok(s), Some(Ok(e))) if s <= e => (s, e),
+            (Err(e), _) | (_, Some(Err(e))) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
+                return false;
+            }
+            _ => {
+                self.cmd_invalid();
                 return false;
             }
-            Ok(()) => (),
         };
+        if !c.is_access_counters_enabled() {
+            dbg_println!(self, "access counters are disabled, use 'heat on' first.");
+            return false;
+        }
 
-        // get the end address
-        let addr_end = addr.wrapping_add(num_bytes).wrapping_sub(1);
-        let m_slice = &mem.as_vec()[addr as usize..=addr_end as usize];
-
-        if is_save {
-            // save to file
-            let _ = match File::create(file_path) {
-                Err(e) => {
-                    // error
-                    println!("{}", e);
-                    return false;
+        // walk the rows
+        let mut shown = false;
+        let mut row = start & 0xfff0;
+        loop {
+            let mut heat = String::new();
+            let (mut reads, mut writes) = (0u64, 0u64);
+            for addr in row..=row | 0xf {
+                if addr < start || addr > end {
+                    heat.push(' ');
+                    continue;
                 }
-                Ok(mut f) => {
-                    let _ = match f.write_all(m_slice) {
-                        Err(e) => {
-                            // error
-                            println!("{}", e);
-                            return false;
-                        }
-                        Ok(_) => println!("file {} correctly saved!", file_path),
-                    };
-                }
-            };
-        } else {
-            // dump hex
-            let mut sl = vec![0; m_slice.len()];
-            sl.copy_from_slice(&m_slice);
-            println!("dumping {} bytes at ${:04x}\n", num_bytes, addr);
-            let dump = HexViewBuilder::new(&sl)
-                .address_offset(addr as usize)
-                .row_width(16)
-                .finish();
-            println!("{}", dump);
+                let (r, w) = c.access_counts(addr);
+                reads += r as u64;
+                writes += w as u64;
+                let n = r as u64 + w as u64;
+                heat.push(match n {
+                    0 => '.',
+                    _ => core::char::from_digit((64 - n.leading_zeros()).min(9), 10).unwrap(),
+                });
+            }
+            if reads != 0 || writes != 0 {
+                dbg_println!(self, "${:04x}: {} r={} w={}", row, heat, reads, writes);
+                shown = true;
+            }
+            if row >= end & 0xfff0 {
+                break;
+            }
+            row += 0x10;
+        }
+        if !shown {
+            dbg_println!(self, "no accesses between ${:04x} and ${:04x}.", start, end);
         }
         return true;
     }
 
     /**
-     * load file in memory
+     * show the interrupt statistics, or zero them.
      */
-    fn cmd_load_memory(&self, c: &mut Cpu, mut it: SplitWhitespace<'_>) -> bool {
-        // check input
-        let addr_s = it.next().unwrap_or_default();
-        let addr: u16;
-
-        let _ = match u16::from_str_radix(&addr_s[is_dollar_hex(&addr_s)..], 16) {
-            Err(_) => {
-                // invalid command, address invalid
+    fn cmd_interrupt_stats<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        match it.next().unwrap_or_default() {
+            "" => {
+                dbg_println!(self, "{}", c.interrupt_stats());
+                true
+            }
+            "clear" => {
+                c.reset_interrupt_stats();
+                dbg_println!(self, "interrupt statistics cleared.");
+                true
+            }
+            _ => {
                 self.cmd_invalid();
-                return false;
+                false
             }
-            Ok(a) => addr = a,
-        };
-
-        // get path
-        let file_path = it.next().unwrap_or_default();
-        if file_path.len() == 0 {
-            // invalid command, path invalid
-            self.cmd_invalid();
-            return false;
         }
-        // clear memory first
-        let mem = c.bus.get_memory();
-        mem.clear();
+    }
 
-        // and load
-        match mem.load(file_path, addr as usize) {
-            Err(e) => {
-                println!("{}", e);
+    /**
+     * enable/disable/clear coverage tracking, save the coverage map to file, or show a summary.
+     */
+    fn cmd_coverage<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
+        match it.next().unwrap_or_default() {
+            "on" => {
+                c.enable_coverage(true);
+                dbg_println!(self, "coverage is enabled!");
+                return true;
+            }
+            "off" => {
+                c.enable_coverage(false);
+                dbg_println!(self, "coverage is disabled!");
+                return true;
+            }
+            "clear" => {
+                c.clear_coverage();
+                dbg_println!(self, "coverage cleared!");
+                return true;
+            }
+            "save" => {
+                let file_path = match it.next() {
+                    None => {
+                        self.cmd_invalid();
+                        return false;
+                    }
+                    Some(p) => p,
+                };
+                let _ = match File::create(file_path) {
+                    Err(e) => {
+                        // error
+                        dbg_println!(self, "{}", e);
+                        return false;
+                    }
+                    Ok(mut f) => {
+                        let _ = match f.write_all(&c.coverage().to_map()) {
+                            Err(e) => {
+                                // error
+                                dbg_println!(self, "{}", e);
+                                return false;
+                            }
+                            Ok(_) => dbg_println!(self, "file {} correctly saved!", file_path),
+                        };
+                    }
+                };
+                return true;
+            }
+            "" => (),
+            _ => {
+                self.cmd_invalid();
                 return false;
             }
-            Ok(()) => {}
-        };
+        }
+        if !c.is_coverage_enabled() {
+            dbg_println!(self, "coverage is disabled, use 'cov on' first.");
+        }
+
+        // summary, up to 16 ranges each
+        let cov = c.coverage();
+        let (executed, read, written) = cov.counts();
+        for (name, n, ranges) in [
+            ("executed", executed, cov.executed_ranges()),
+            ("read", read, cov.read_ranges()),
+            ("written", written, cov.written_ranges()),
+        ] {
+            let v: Vec<String> = ranges
+                .iter()
+                .take(16)
+                .map(|(start, end)| {
+                    if start == end {
+                        format!("${:04x}", start)
+                    } else {
+                        format!("${:04x}-${:04x}", start, end)
+                    }
+                })
+                .collect();
+            dbg_println!(
+                self,
+                "{:>8}: {} bytes in {} ranges\t{}{}",
+                name,
+                n,
+                ranges.len(),
+                v.join(", "),
+                if ranges.len() > 16 { ", ..." } else { "" }
+            );
+        }
         return true;
     }
 
@@ -316,101 +1530,176 @@ impl Debugger {
      * print help banner
      */
     fn cmd_show_help(&self) -> bool {
-        println!("debugger supported commands:");
-        println!("\ta <$address> .......................... assemble instructions (one per line) at <$address>, <enter> to finish.");
-        println!("\tbx|br|bw|brw|bn|bq [$address] [c,...] . add exec/read/write/readwrite/execute/nmi/irq breakpoint, [c]onditions can be <a|x|y|s|p>|<cycles>=n|$n.\n\tnote: for anything except bn and bq, [$address] is mandatory !",
+        dbg_println!(self, "debugger supported commands:");
+        dbg_println!(self, "\ta <$address> .......................... assemble instructions (one per line) at <$address>, <enter> to finish.");
+        dbg_println!(self, "\tback [n] .............................. step back [n] (default 1) instructions, needs the history enabled with 'hist'.");
+        dbg_println!(self, "\tbx|br|bw|brw|bn|bq [$address] [c,...] . add exec/read/write/readwrite/execute/nmi/irq breakpoint, [c]onditions can be <a|x|y|s|p>|<cycles>=n|$n, plus handler=$n for bn and bq.\n\tnote: for anything except bn and bq, [$address] is mandatory and may be a range ($start-$end) !",
+        );
+        dbg_println!(self, "\tbx|br|bw|brw|bn|bq|bs|bo|bf ... do \"cmd; ...\" attach commands to the breakpoint, executed when it triggers (execution continues if the list ends with g).");
+        dbg_println!(self, "\tbs [$floor] ........................... add stack breakpoint, triggers when S wraps on push/pull or a push takes S below [$floor].");
+        dbg_println!(self, "\tbo <mnemonic|$opcode> [c,...] ......... add opcode breakpoint, triggers before executing <mnemonic> (any addressing mode) or <$opcode> anywhere, [c]onditions as above.");
+        dbg_println!(self, "\tbf <n|v|b|d|i|z|c><=0|=1| changed> [c,...] add flag breakpoint, triggers after an instruction clears, sets or changes the flag, i.e. 'bf d=1'.");
+        dbg_println!(
+            self,
+            "\tbl .................................... show breakpoints."
+        );
+        dbg_println!(
+            self,
+            "\tbe <n> ................................ enable breakpoint <n>."
         );
-        println!("\tbl .................................... show breakpoints.");
-        println!("\tbe <n> ................................ enable breakpoint <n>.");
-        println!("\tbd <n> ................................ disable breakpoint<n>.");
-        println!("\tbdel <n> .............................. delete breakpoint <n>.");
-        println!("\tbc .................................... clear all breakpoints.");
-        println!("\tc <6502|65C02>......................... switch cpu type (warning: done after reset() may cause unpredictable results !).");
-        println!("\td <# instr> [$address] ................ disassemble <# instructions> at [$address], address defaults to pc.",
+        dbg_println!(
+            self,
+            "\tbd <n> ................................ disable breakpoint<n>."
         );
-        println!("\te <$value> [$value...] <$address> ..... write one or more <$value> bytes in memory starting at <$address>.");
-        println!(
+        dbg_println!(
+            self,
+            "\tbdel <n> .............................. delete breakpoint <n>."
+        );
+        dbg_println!(
+            self,
+            "\tbc .................................... clear all breakpoints."
+        );
+        dbg_println!(self, "\tbt .................................... show the call chain, newest first, scanning the stack for JSR return addresses (? marks a possible false positive).");
+        dbg_println!(self, "\tc <6502|6510|65C02|R65C02|65SC02|2A03> switch cpu type (warning: done after reset() may cause unpredictable results !).");
+        dbg_println!(self, "\tcmp <$address1> <$address2> <len> .. compare <len> bytes at <$address1> and <$address2>, show the differing bytes.");
+        dbg_println!(self, "\tcopy <$src> <$dst> <len> .............. copy <len> bytes from <$src> to <$dst>, overlapping is allowed.");
+        dbg_println!(self, "\tcov [on|off|clear|save <path>] ........ enable/disable/clear code coverage, save the coverage map (1 byte per address, bit0=exec, bit1=read, bit2=write) to <path>, or show a summary.");
+        dbg_println!(self, "\tcrc <$address> [len] .................. show CRC32 and 8 bit sum of [len|0=up to memory size] bytes at <$address>.");
+        dbg_println!(self, "\td <# instr> [$address] [stop|bytes] .. disassemble <# instructions> at [$address], address defaults to pc. undocumented opcodes are marked with *, stop before them or show them as .byte.",
+        );
+        dbg_println!(self, "\te <$value> [$value...] <$address> ..... write one or more <$value> bytes in memory starting at <$address>.");
+        dbg_println!(self, "\tf <$start> <$end> <xx ..|\"text\"> [petscii] [max=n] search memory between <$start> and <$end> for a byte pattern (?? matches any byte) or ascii/petscii text, show at most [max] matches.");
+        dbg_println!(self, "\tfill <$address> <len> <$value[,$value...]> fill <len> bytes at <$address> repeating the given <$value> bytes.");
+        dbg_println!(self, "\tfin ................................... step out, continue until RTS/RTI at the current stack depth.");
+        dbg_println!(self,
         "\tg ..................................... continue execution until breakpoint or trap.",
     );
-        println!("\th ..................................... this help.");
-        println!("\tl <$address> <path> ................... load <path> at <$address>.",);
-        println!("\tlg .................................... enable/disable cpu log to console (warning, slows down a lot!).",);
-        println!("\tq ..................................... exit emulator.");
-        println!("\tr ..................................... show registers.");
-        println!("\trst [$address] ........................ reset (restart from given [$address], or from address contained at reset vector if empty).");
-        println!("\tp ..................................... step next instruction.");
-        println!(
+        dbg_println!(self, "\th ..................................... this help.");
+        dbg_println!(self, "\theat [on|off|<$start> <$end>] ......... enable/disable the read/write counters, or show them per 16 bytes row between <$start> and <$end> (a digit per byte, n=up to 2^n-1 accesses).");
+        dbg_println!(self, "\thist [depth] .......................... record the last [depth] instructions to step back (0 disables), or show the history status.\n\tnote: best-effort, side effects on memory mapped devices are not undone.");
+        dbg_println!(self, "\tinit [zero|ones|pattern [$a $b]|random [$seed]] set how memory is initialized and clear it (pattern=64 bytes stripes of $a and $b, default $00 $ff as the C64), or show the current policy.");
+        dbg_println!(self, "\tistat [clear] ......................... show the serviced IRQ/NMI/BRK counters, handler cycles and worst case latency, or zero them.");
+        dbg_println!(self, "\tl <$address> <path> [bin|ihex|srec|auto] load <path> at <$address> (raw binary, default), or at the addresses in the ihex/srec records (auto=detect).",);
+        dbg_println!(self, "\tlg .................................... enable/disable cpu log to console (warning, slows down a lot!).",);
+        dbg_println!(self, "\tn ..................................... step over, like p but runs a JSR until it returns.");
+        dbg_println!(self, "\tprof [on|off|n] ....................... enable/disable the profiler, or show the [n] (default 10) addresses taking most cycles.");
+        dbg_println!(
+            self,
+            "\tq ..................................... exit emulator."
+        );
+        dbg_println!(
+            self,
+            "\tr ..................................... show registers."
+        );
+        dbg_println!(self, "\trst [$address] ........................ reset (restart from given [$address], or from address contained at reset vector if empty).");
+        dbg_println!(
+            self,
+            "\tp ..................................... step next instruction."
+        );
+        dbg_println!(self,
             "\to ..................................... enable/disable show registers before the opcode, default is off (needs logging enabled)."
         );
-        println!("\ts <len> <$address> <path> ............. save <len|0=up to memory size> memory bytes starting from <$address> to file at <path>.",
+        dbg_println!(self, "\tscript <path> ......................... execute the commands in <path>, one per line ('#' for comments, q to quit), a command resuming execution ends it and queues the rest.");
+        dbg_println!(self, "\tset output <json|text> ................ output 'r', 'bl', 'd', 'x' and the errors as single line json (no 'bc' confirmation), or as text.");
+        dbg_println!(self, "\ts <len> <$address> <path> ............. save <len|0=up to memory size> memory bytes starting from <$address> to file at <path>.",
+        );
+        dbg_println!(
+            self,
+            "\tss .................................... show 16 stack bytes ($1f0-$1ff)."
+        );
+        dbg_println!(
+            self,
+            "\ttn .................................... trigger NMI and set PC=NMI handler."
         );
-        println!("\tss .................................... show 16 stack bytes ($1f0-$1ff).");
-        println!("\ttn .................................... trigger NMI and set PC=NMI handler.");
-        println!("\ttq .................................... trigger IRQ and set PC=IRQ handler.");
-        println!("\tv <a|x|y|s|p|pc> <$value>.............. set register value, according to bitness (pc=16bit, others=8bit).");
-        println!("\tx <len> <$address> .................... hexdump <len> bytes at <$address>.");
-        println!("NOTE: all addresses/values must be hex where specified, the $ prefix is optional and just for clarity ($0400 = 400). 
-        This is valid everywhere but in the handwritten assembler inside the 'a' command.");
+        dbg_println!(
+            self,
+            "\ttq .................................... trigger IRQ and set PC=IRQ handler."
+        );
+        dbg_println!(
+            self,
+            "\tu <$address> .......................... run to <$address>."
+        );
+        dbg_println!(self, "\tv <a|x|y|s|sp|p|pc> <$value>........... set register value, according to bitness (pc=16bit, others=8bit), and show the registers.");
+        dbg_println!(self, "\tv <n|v|b|d|i|z|c> <0|1>................ set or clear a single flag, and show the registers.");
+        dbg_println!(self, "\tx <len> <$address> [opts] ............. hexdump <len> bytes at <$address>, [opts] can be any of <width> (default 16), u (uppercase), petscii|notext (text column), ><path> (to file).");
+        dbg_println!(self, "NOTE: e, fill and copy write memory directly, bypassing rom protection and write breakpoints.");
+        dbg_println!(self, "NOTE: all addresses/values must be hex where specified, the $ prefix is optional and just for clarity ($0400 = 400). 
+        This is valid everywhere but in the handwritten assembler inside the 'a' command.
+        once labels are loaded, <$address> may also be a label name (a label wins over unprefixed hex, i.e. 'add', use '$add' for hex).");
         return true;
     }
 
     /**
-     * edit cpu registers
+     * edit cpu registers (a, x, y, s|sp, p, pc) or a single flag (n, v, b, d, i, z, c), then show the registers.
      */
-    fn cmd_edit_registers(&self, c: &mut Cpu, mut it: SplitWhitespace<'_>) -> bool {
+    fn cmd_edit_registers<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
         // check input
-        let reg = it.next().unwrap_or_default();
+        let reg = it.next().unwrap_or_default().to_ascii_lowercase();
         let val = it.next().unwrap_or_default();
         if reg.len() == 0 || val.len() == 0 {
             // invalid command, missing value
             self.cmd_invalid();
             return false;
         }
+        let v = match u16::from_str_radix(&val[is_dollar_hex(&val)..], 16) {
+            Ok(v) => v,
+            Err(_) => {
+                // invalid value
+                self.cmd_invalid();
+                return false;
+            }
+        };
 
-        // match registers and assign value
-        let r = reg.chars().next().unwrap_or_default();
-        let res_u16 = u16::from_str_radix(&val[is_dollar_hex(&val)..], 16);
-        match r {
-            'a' | 'x' | 'y' | 's' | 'p' => match res_u16 {
-                Err(_) => {
-                    // invalid value
-                    self.cmd_invalid();
-                    return false;
-                }
-                Ok(a) => {
-                    if reg.eq("pc") {
-                        c.regs.pc = a;
-                    } else {
-                        if a > 0xff {
-                            // invalid value
-                            self.cmd_invalid();
-                            return false;
-                        }
-                        match r {
-                            'a' => c.regs.a = a as u8,
-                            'x' => c.regs.x = a as u8,
-                            'y' => c.regs.y = a as u8,
-                            's' => c.regs.s = a as u8,
-                            'p' => c.regs.p = CpuFlags::from_bits(a as u8).unwrap(),
-                            _ => (),
-                        }
-                    }
-                }
-            },
+        // a single flag, 0 or 1
+        let flag = match reg.as_str() {
+            "n" => Some(CpuFlags::N),
+            "v" => Some(CpuFlags::V),
+            "b" => Some(CpuFlags::B),
+            "d" => Some(CpuFlags::D),
+            "i" => Some(CpuFlags::I),
+            "z" => Some(CpuFlags::Z),
+            "c" => Some(CpuFlags::C),
+            _ => None,
+        };
+        if let Some(f) = flag {
+            if v > 1 {
+                // invalid value
+                self.cmd_invalid();
+                return false;
+            }
+            c.regs.p.set(f, v == 1);
+            debug_out_registers(self, c);
+            return true;
+        }
+
+        // a register, according to bitness
+        if reg.ne("pc") && v > 0xff {
+            // invalid value
+            self.cmd_invalid();
+            return false;
+        }
+        match reg.as_str() {
+            "a" => c.regs.a = v as u8,
+            "x" => c.regs.x = v as u8,
+            "y" => c.regs.y = v as u8,
+            "s" | "sp" => c.regs.s = v as u8,
+            // U is always set
+            "p" => c.regs.p = CpuFlags::from_bits_truncate(v as u8) | CpuFlags::U,
+            "pc" => c.regs.pc = v,
             _ => {
                 // invalid command, register name invalid
                 self.cmd_invalid();
                 return false;
             }
         }
-        println!("register '{}' set to {}.", reg, val);
+        debug_out_registers(self, c);
         return true;
     }
 
     /**
      * change cpu type
      */
-    fn cmd_switch_cpu_type(&self, c: &mut Cpu, mut it: SplitWhitespace<'_>) -> bool {
+    fn cmd_switch_cpu_type<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
         // check input
         let t = it.next().unwrap_or_default();
         match t.to_ascii_lowercase().as_str() {
@@ -422,6 +1711,22 @@ impl Debugger {
                 c.set_cpu_type(CpuType::WDC65C02);
                 return true;
             }
+            "r65c02" => {
+                c.set_cpu_type(CpuType::R65C02);
+                return true;
+            }
+            "65sc02" => {
+                c.set_cpu_type(CpuType::G65SC02);
+                return true;
+            }
+            "6510" => {
+                c.set_cpu_type(CpuType::MOS6510);
+                return true;
+            }
+            "2a03" => {
+                c.set_cpu_type(CpuType::Ricoh2A03);
+                return true;
+            }
             _ => {
                 self.cmd_invalid();
             }
@@ -434,7 +1739,10 @@ impl Debugger {
      *
      * returns a tuple with the debugger command ("q" on exit, "*"" for no-op, ...) and a boolean to indicate an error
      */
-    pub fn parse_cmd_stdin(&mut self, c: &mut Cpu) -> Result<(String, bool), std::io::Error> {
+    pub fn parse_cmd_stdin<B: Bus>(
+        &mut self,
+        c: &mut Cpu<B>,
+    ) -> Result<(String, bool), std::io::Error> {
         if self.enabled {
             if self.going {
                 // let it go!
@@ -442,11 +1750,18 @@ impl Debugger {
             }
         }
 
-        // read from stdin
+        // read from the queue first, then from stdin
         let mut cmd_string = String::new();
-        print!("?:> ");
-        io::stdout().flush().unwrap();
-        io::stdin().lock().read_line(&mut cmd_string)?;
+        dbg_print!(self, "?:> ");
+        match self.cmd_queue.pop_front() {
+            Some(cmd) => {
+                dbg_println!(self, "{}", cmd);
+                cmd_string = cmd;
+            }
+            None => {
+                io::stdin().lock().read_line(&mut cmd_string)?;
+            }
+        }
         Ok(self.parse_cmd(c, &cmd_string))
     }
 
@@ -455,7 +1770,7 @@ impl Debugger {
      *
      * returns the debugger command ('q' on exit, '*' for no-op)
      */
-    pub fn parse_cmd(&mut self, c: &mut Cpu, cmd_string: &str) -> (String, bool) {
+    pub fn parse_cmd<B: Bus>(&mut self, c: &mut Cpu<B>, cmd_string: &str) -> (String, bool) {
         if self.enabled {
             if self.going {
                 // let it go!
@@ -475,6 +1790,10 @@ impl Debugger {
             "c" => {
                 return (String::from("*"), self.cmd_switch_cpu_type(c, it));
             }
+            // step back
+            "back" => {
+                return (String::from("*"), self.cmd_step_back(c, it));
+            }
             "bc" => {
                 return (String::from("*"), self.cmd_clear_breakpoints());
             }
@@ -484,12 +1803,31 @@ impl Debugger {
                     self.cmd_enable_disable_delete_breakpoint(cmd, it),
                 );
             }
-            "bx" | "br" | "bw" | "brw" | "bq" | "bn" => {
-                return (String::from("*"), self.cmd_add_breakpoint(c, cmd, it));
+            "bx" | "br" | "bw" | "brw" | "bq" | "bn" | "bs" | "bo" | "bf" => {
+                return (
+                    String::from("*"),
+                    self.cmd_add_breakpoint(c, cmd, cmd_string),
+                );
             }
             "bl" => {
                 return (String::from("*"), self.cmd_show_breakpoints());
             }
+            // backtrace
+            "bt" => {
+                return (String::from("*"), self.cmd_backtrace(c));
+            }
+            // compare memory
+            "cmp" => {
+                return (String::from("*"), self.cmd_compare_memory(c, it));
+            }
+            // copy memory
+            "copy" => {
+                return (String::from("*"), self.cmd_copy_memory(c, it));
+            }
+            // checksum memory
+            "crc" => {
+                return (String::from("*"), self.cmd_checksum(c, it));
+            }
             // help
             "d" => {
                 return (String::from("*"), self.cmd_disassemble(c, it));
@@ -503,10 +1841,39 @@ impl Debugger {
                 self.going = true;
                 return (String::from("p"), true);
             }
+            // find in memory
+            "f" => {
+                return (String::from("*"), self.cmd_find(c, it));
+            }
+            // fill memory
+            "fill" => {
+                return (String::from("*"), self.cmd_fill_memory(c, it));
+            }
+            // step out
+            "fin" => {
+                self.step_out(c);
+                return (String::from("p"), true);
+            }
             // help
             "h" => {
                 return (String::from("*"), self.cmd_show_help());
             }
+            // access counters
+            "heat" => {
+                return (String::from("*"), self.cmd_heat(c, it));
+            }
+            // history
+            "hist" => {
+                return (String::from("*"), self.cmd_history(it));
+            }
+            // memory init policy
+            "init" => {
+                return (String::from("*"), self.cmd_init_memory(c, it));
+            }
+            // interrupt statistics
+            "istat" => {
+                return (String::from("*"), self.cmd_interrupt_stats(c, it));
+            }
             // load memory
             "l" => {
                 return (String::from("*"), self.cmd_load_memory(c, it));
@@ -515,21 +1882,33 @@ impl Debugger {
             "lg" => {
                 if log_enabled() {
                     c.enable_logging(false);
-                    println!("logging is disabled!");
+                    dbg_println!(self, "logging is disabled!");
                 } else {
                     c.enable_logging(true);
-                    println!("logging is enabled!");
+                    dbg_println!(self, "logging is enabled!");
                 }
                 return (String::from("*"), true);
             }
+            // coverage
+            "cov" => {
+                return (String::from("*"), self.cmd_coverage(c, it));
+            }
+            // profiler
+            "prof" => {
+                return (String::from("*"), self.cmd_profile(c, it));
+            }
             // quit
             "q" => {
-                println!("quit!");
+                dbg_println!(self, "quit!");
                 return (String::from("q"), true);
             }
             // show registers
             "r" => {
-                debug_out_registers(c);
+                if self.json {
+                    dbg_println!(self, "{}", self.json_registers(c));
+                } else {
+                    debug_out_registers(self, c);
+                }
                 return (String::from("*"), true);
             }
             // reset
@@ -540,10 +1919,23 @@ impl Debugger {
             "p" => {
                 return (String::from("p"), true);
             }
+            // step over
+            "n" => {
+                if let Err(e) = self.step_over(c) {
+                    dbg_println!(self, "{}", e);
+                    return (String::from("*"), false);
+                }
+                return (String::from("p"), true);
+            }
+            // run to address
+            "u" => {
+                return self.cmd_run_to(c, it);
+            }
             // show/hide registers before showing the opcode
             "o" => {
                 self.show_registers_before_opcode = !self.show_registers_before_opcode;
-                println!(
+                dbg_println!(
+                    self,
                     "{}showing registers before the opcode.",
                     if self.show_registers_before_opcode {
                         ""
@@ -553,6 +1945,14 @@ impl Debugger {
                 );
                 return (String::from("*"), true);
             }
+            // run script
+            "script" => {
+                return self.cmd_script(c, it);
+            }
+            // set options
+            "set" => {
+                return (String::from("*"), self.cmd_set(it));
+            }
             // save memory
             "s" => {
                 return (String::from("*"), self.cmd_dump_save_memory(c, cmd, it));
@@ -564,14 +1964,14 @@ impl Debugger {
             // trigger nmi
             "tn" => {
                 c.nmi(Some(self)).unwrap();
-                println!("NMI triggered!");
+                dbg_println!(self, "NMI triggered!");
                 self.going = false;
                 return (String::from("p"), true);
             }
             // trigger irq
             "tq" => {
                 c.irq(Some(self)).unwrap();
-                println!("IRQ triggered!");
+                dbg_println!(self, "IRQ triggered!");
                 self.going = false;
                 return (String::from("p"), true);
             }
@@ -591,3 +1991,29 @@ impl Debugger {
         };
     }
 }
+
+/**
+ * convert an ascii character to petscii (lowercase to unshifted $41-$5a, uppercase to shifted $c1-$da).
+ */
+fn ascii_to_petscii(ch: u8) -> u8 {
+    match ch {
+        b'a'..=b'z' => ch - 0x20,
+        b'A'..=b'Z' => ch + 0x80,
+        _ => ch,
+    }
+}
+
+/**
+ * CRC32 (IEEE 802.3, reflected, polynomial $edb88320).
+ */
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffffffff;
+    for b in data {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+    !crc
+}
@@ -28,168 +28,164 @@
  * SOFTWARE.
  */
 
-use crate::cpu::addressing_modes::AddressingModeId;
+use crate::bus::Bus;
+use crate::cpu::asm;
 use crate::cpu::cpu_error;
-use crate::cpu::cpu_error::CpuErrorType;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
 use crate::cpu::debugger::Debugger;
-use crate::cpu::opcodes;
-use crate::cpu::opcodes::OpcodeMarker;
-use crate::cpu::{Cpu, CpuType};
-use crate::utils::*;
+use crate::cpu::disasm;
+use crate::cpu::disasm::{DisasmOptions, Instruction};
+use crate::cpu::Cpu;
 use std::io;
-use std::io::{BufRead, Write};
+use std::io::BufRead;
 
-/**
- * disassemble n instructions at the given address
- */
 use std::str::SplitWhitespace;
 
 impl Debugger {
-    pub(super) fn cmd_disassemble(&self, c: &mut Cpu, mut it: SplitWhitespace<'_>) -> bool {
+    /**
+     * disassemble count instructions at address, through the structured disassembler.
+     */
+    pub fn disassemble<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        count: usize,
+    ) -> Result<Vec<Instruction>, CpuError> {
+        self.disassemble_with_options(c, address, count, &DisasmOptions::default())
+    }
+
+    /**
+     * disassemble as disassemble(), handling the undocumented opcodes as set in opts.
+     *
+     * with DisasmOptions::stop_on_undocumented, less than count instructions are returned if an undocumented opcode is
+     * found.
+     */
+    pub fn disassemble_with_options<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        count: usize,
+        opts: &DisasmOptions,
+    ) -> Result<Vec<Instruction>, CpuError> {
+        let size = c.bus.get_memory().get_size();
+        cpu_error::check_address_boundaries(
+            size,
+            address as usize,
+            1,
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+
+        // at most 3 bytes per instruction, peeked without side effects
+        let start = address as usize;
+        let m = c
+            .bus
+            .dump_range(start, std::cmp::min(count * 3, size - start))?;
+        let mut v = disasm::disassemble_with_options(&m, address, c.cpu_type, opts);
+        if v.len() < count && !opts.stop_on_undocumented {
+            // overlap
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                address,
+                Some(String::from("ERROR, overlapping detected!")),
+            ));
+        }
+        v.truncate(count);
+        Ok(v)
+    }
+
+    /**
+     * disassemble n instructions at the given address, 'stop' stops before an undocumented opcode and 'bytes' shows them
+     * as '.byte $xx'.
+     */
+    pub(super) fn cmd_disassemble<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        mut it: SplitWhitespace<'_>,
+    ) -> bool {
         // check input
         let n_s = it.next().unwrap_or_default();
         let n = u16::from_str_radix(&n_s, 10).unwrap_or_default();
-        let addr_s = it.next().unwrap_or_default();
         if n == 0 {
             // invalid command, missing number of instructions to decode
             self.cmd_invalid();
             return false;
         }
-        let mut res = true;
-
-        // save current pc
-        let prev_pc = c.regs.pc;
-        let addr: u16;
 
-        // get the start address
-        if addr_s.len() > 0 {
-            match u16::from_str_radix(&addr_s[is_dollar_hex(&addr_s)..], 16) {
-                Err(_) => {
-                    // invalid command, address invalid
-                    self.cmd_invalid();
-                    return false;
-                }
-                Ok(a) => addr = a,
+        // get the options and the start address, which defaults to pc
+        let mut opts = DisasmOptions::default();
+        let mut addr = c.regs.pc;
+        for s in it {
+            match s {
+                "stop" => opts.stop_on_undocumented = true,
+                "bytes" => opts.bytes_for_undocumented = true,
+                _ => match self.parse_address(s) {
+                    Err(e) => {
+                        // invalid address or unknown symbol
+                        self.cmd_error(&e);
+                        return false;
+                    }
+                    Ok(a) => addr = a,
+                },
             }
-        } else {
-            // defaults to pc
-            addr = c.regs.pc;
         }
 
         // disassemble
-        c.regs.pc = addr;
-        let mut instr_count: u16 = 0;
-        println!("disassembling {} instructions at ${:04x}\n", n, addr);
-        loop {
-            // fetch an instruction
-            let b: u8;
-            match c.fetch() {
-                Err(e) => {
-                    res = false;
-                    println!("{}", e);
-                    break;
-                }
-                Ok(ok) => b = ok,
-            }
-            // get opcode and check access
-            let (opcode_f, _, _, mrk) = if c.cpu_type == CpuType::MOS6502 {
-                opcodes::OPCODE_MATRIX[b as usize]
-            } else {
-                opcodes::OPCODE_MATRIX_65C02[b as usize]
-            };
-
-            let instr_size: i8;
-            match cpu_error::check_opcode_boundaries(
-                c.bus.get_memory().get_size(),
-                c.regs.pc as usize,
-                mrk.id,
-                CpuErrorType::MemoryRead,
-                None,
-            ) {
-                Err(e) => {
-                    println!("{}", e);
-                    res = false;
-                    break;
-                }
-                Ok(()) => (),
-            };
-            // decode
-            match opcode_f(c, None, b, 0, false, true, false) {
-                Err(e) => {
-                    println!("{}", e);
-                    res = false;
-                    break;
-                }
-                Ok((a, _)) => instr_size = a,
+        if !self.json {
+            dbg_println!(self, "disassembling {} instructions at ${:04x}\n", n, addr);
+        }
+        match self.disassemble_with_options(c, addr, n as usize, &opts) {
+            Err(e) => {
+                self.cmd_error(&e);
+                false
             }
-
-            // next
-            instr_count = instr_count.wrapping_add(1);
-            if instr_count == n {
-                break;
+            Ok(v) if self.json => {
+                dbg_println!(self, "{}", self.json_disassembly(&v));
+                true
             }
-
-            // next instruction
-            let (next_pc, o) = c.regs.pc.overflowing_add(instr_size as u16);
-            if o {
-                // overlap
-                println!("ERROR, overlapping detected!");
-                res = false;
-                break;
+            Ok(v) => {
+                for i in v.iter() {
+                    if let Some(l) = self.label_at(i.address) {
+                        dbg_println!(self, "{}:", l);
+                    }
+                    dbg_println!(self, "\t{}", self.format_instruction(i));
+                }
+                true
             }
-            c.regs.pc = next_pc;
         }
-
-        // restore pc in the end
-        c.regs.pc = prev_pc;
-        return res;
     }
 
     /**
-     * find instruction in the opcode matrix
+     * assemble lines (one instruction each) at address, for the current cpu type.
+     *
+     * returns the address following the last assembled instruction.
      */
-    fn find_instruction(
+    pub fn assemble<B: Bus>(
         &self,
-        t: &CpuType,
-        s: &str,
-        id: AddressingModeId,
-    ) -> Option<(&OpcodeMarker, u8)> {
-        for (i, (_, _, _, op)) in if *t == CpuType::MOS6502 {
-            opcodes::OPCODE_MATRIX.iter().enumerate()
-        } else {
-            opcodes::OPCODE_MATRIX_65C02.iter().enumerate()
-        } {
-            if op.name.eq(s) && op.id == id {
-                return Some((&op, i as u8));
-            }
+        c: &mut Cpu<B>,
+        address: u16,
+        lines: &[&str],
+    ) -> Result<u16, CpuError> {
+        let resolve = |name: &str| -> Result<u16, String> {
+            self.resolve_label(name)
+                .map_err(|e| e.msg.unwrap_or_default())
+        };
+        let mut addr = address;
+        for l in lines.iter() {
+            let v = asm::assemble_instruction(c.cpu_type, addr, l, &resolve)
+                .map_err(|m| asm_error(&m))?;
+            self.write_memory(c, addr, &v)?;
+            addr = addr.wrapping_add(v.len() as u16);
         }
-        None
+        Ok(addr)
     }
 
     /**
-     * assemble instruction/s
-     *
-     * syntax for the assembler is taken from https://www.masswerk.at/6502/6502_instruction_set.html
-     *
-     * A	    Accumulator	        OPC A           operand is AC (implied single byte instruction)
-     * abs	    absolute	        OPC $addr	    operand is address $HHLL
-     * abs,X	absolute, X-indexed	OPC $addr,X	    operand is address; effective address is address incremented by X with carry
-     * abs,Y	absolute, Y-indexed	OPC $addr,Y	    operand is address; effective address is address incremented by Y with carry
-     * #	    immediate	        OPC #$BB	    operand is byte BB
-     * impl	    implied	            OPC	            operand implied
-     * ind	    indirect	        OPC ($addr)	    operand is address; effective address is contents of word at address: C.w($HHLL)
-     * X,ind	X-indexed, indirect	OPC ($ad,X)	    operand is zeropage address; effective address is word in (LL + X, LL + X + 1), inc. without carry: C.w($00LL + X)
-     * ind,Y	indirect, Y-indexed	OPC ($ad),Y	    operand is zeropage address; effective address is word in (LL, LL + 1) incremented by Y with carry: C.w($00LL) + Y
-     * rel	    relative	        OPC $BB         branch target is PC + signed offset BB
-     * zpg	    zeropage	        OPC $LL	        operand is zeropage address (hi-byte is zero, address = $00LL)
-     * zpg,X	zeropage, X-indexed	OPC $LL,X	    operand is zeropage address; effective address is address incremented by X without carry
-     * zpg,Y	zeropage, Y-indexed	OPC $LL,Y	    operand is zeropage address; effective address is address incremented by Y without carry
+     * assemble instruction/s read from stdin, one per line, with the syntax of asm::assemble().
      *
-     * for 65c02:
-     * zpr (ZeroPage relative)      OPC $ad,$BB     operand is zeropage address
-     * iax (Indirect Absolute X)    OPC ($addr,X)
+     * loaded labels are accepted in place of addresses.
      */
-    pub(super) fn cmd_assemble(&self, c: &mut Cpu, mut it: SplitWhitespace<'_>) -> bool {
+    pub(super) fn cmd_assemble<B: Bus>(&self, c: &mut Cpu<B>, mut it: SplitWhitespace<'_>) -> bool {
         // check input
         let addr_s = it.next().unwrap_or_default();
         let mut addr: u16;
@@ -199,300 +195,39 @@ impl Debugger {
             return false;
         }
 
-        let _ = match u16::from_str_radix(&addr_s[is_dollar_hex(&addr_s)..], 16) {
-            Err(_) => {
-                // invalid command, address invalid
-                self.cmd_invalid();
+        let _ = match self.parse_address(addr_s) {
+            Err(e) => {
+                // invalid address or unknown symbol
+                self.cmd_error(&e);
                 return false;
             }
             Ok(a) => addr = a,
         };
 
         // read from stdin
-        println!("assembling at ${:04x}, <enter> to stop.", addr);
-
-        // loop
-        let mut prev_addr = addr;
-
-        // silence this warning, i really can't understand why it happens....
-        #[allow(unused_assignments)]
-        let mut res: bool = true;
-        'assembler: loop {
+        dbg_println!(self, "assembling at ${:04x}, <enter> to stop.", addr);
+        loop {
             // read asm
-            print!("?a> ${:04x}: ", addr);
-            io::stdout().flush().unwrap();
+            dbg_print!(self, "?a> ${:04x}: ", addr);
             let mut full_string = String::new();
-            let _ = match io::stdin().lock().read_line(&mut full_string) {
-                Err(_) => {
-                    res = false;
-                    break 'assembler;
-                }
-                Ok(_) => (),
-            };
-            // split opcode and operand/s
-            full_string = full_string.trim().to_ascii_lowercase();
-            if full_string.len() == 0 {
+            if io::stdin().lock().read_line(&mut full_string).is_err() {
+                return false;
+            }
+            if full_string.trim().len() == 0 {
                 // done
-                res = false;
-                break 'assembler;
+                return true;
             }
-            let (mut opcode, tmp) = full_string.split_once(' ').unwrap_or_default();
-            opcode = &opcode.trim();
-
-            // also ensure there's no whitestpaces in the operands part
-            let mut operand_s = tmp.trim().replace(" ", "").replace("\t", "");
-
-            // find addressing mode and instruction length
-            let mode_id: AddressingModeId;
-            if operand_s.eq("a") {
-                // accumulator
-                mode_id = AddressingModeId::Acc;
-            } else if operand_s.starts_with("$") && operand_s.len() == 5 && !operand_s.contains(",")
-            {
-                // absolute
-                mode_id = AddressingModeId::Abs;
-            } else if operand_s.starts_with("$") && operand_s.ends_with(",x") && operand_s.len() > 6
-            {
-                // absolute x
-                mode_id = AddressingModeId::Abx;
-                operand_s.truncate(operand_s.len() - 2);
-            } else if operand_s.starts_with("$") && operand_s.ends_with(",y") && operand_s.len() > 6
-            {
-                // absolute y
-                mode_id = AddressingModeId::Aby;
-                operand_s.truncate(operand_s.len() - 2);
-            } else if operand_s.starts_with("#$") {
-                // immediate
-                mode_id = AddressingModeId::Imm;
-                operand_s.remove(0);
-            } else if opcode.len() == 0 && operand_s.len() == 0 {
-                // implied
-                mode_id = AddressingModeId::Imp;
-                opcode = &full_string;
-            } else if operand_s.starts_with("($") && operand_s.ends_with(",x)") {
-                // absolute indirect x (65c02)
-                mode_id = AddressingModeId::Aix;
-                operand_s.truncate(operand_s.len() - 3);
-                operand_s.remove(0);
-                operand_s.remove(0);
-            } else if operand_s.starts_with("(") && operand_s.ends_with(")") {
-                // indirect
-                mode_id = AddressingModeId::Ind;
-                operand_s.truncate(operand_s.len() - 1);
-                operand_s.remove(0);
-            } else if operand_s.ends_with(",x)") {
-                // X indirect
-                mode_id = AddressingModeId::Xin;
-                operand_s.truncate(operand_s.len() - 3);
-                operand_s.remove(0);
-            } else if operand_s.ends_with("),y") {
-                // indirect Y
-                mode_id = AddressingModeId::Iny;
-                operand_s.truncate(operand_s.len() - 3);
-                operand_s.remove(0);
-            } else if operand_s.starts_with("$(") && operand_s.len() <= 5 {
-                // indirect ZP (65c02)
-                mode_id = AddressingModeId::Izp;
-                operand_s.truncate(operand_s.len() - 1);
-                operand_s.remove(0);
-                operand_s.remove(0);
-            } else if operand_s.contains(",$") {
-                // zeropage relative (65c02)
-                mode_id = AddressingModeId::Zpr;
-            } else if operand_s.starts_with("$") && operand_s.len() <= 3 {
-                if opcode.eq("bpl")
-                    || opcode.eq("bmi")
-                    || opcode.eq("bvc")
-                    || opcode.eq("bvs")
-                    || opcode.eq("bcc")
-                    || opcode.eq("bcs")
-                    || opcode.eq("bne")
-                    || opcode.eq("beq")
-                {
-                    // relative
-                    mode_id = AddressingModeId::Rel;
-                } else {
-                    // zeropage
-                    mode_id = AddressingModeId::Zpg;
-                }
-            } else if operand_s.starts_with("$")
-                && operand_s.ends_with(",x")
-                && operand_s.len() <= 5
-            {
-                // zeropage X
-                mode_id = AddressingModeId::Zpx;
-                operand_s.truncate(operand_s.len() - 2);
-            } else if operand_s.starts_with("$")
-                && operand_s.ends_with(",y")
-                && operand_s.len() <= 5
-            {
-                // zeropage Y
-                mode_id = AddressingModeId::Zpy;
-                operand_s.truncate(operand_s.len() - 2);
-            } else {
-                println!("invalid opcode!");
-                continue 'assembler;
+            match self.assemble(c, addr, &[&full_string]) {
+                Err(e) => self.cmd_error(&e),
+                Ok(next) => addr = next,
             }
-
-            // check access
-            match cpu_error::check_opcode_boundaries(
-                c.bus.get_memory().get_size(),
-                addr as usize,
-                mode_id,
-                CpuErrorType::MemoryWrite,
-                None,
-            ) {
-                Err(e) => {
-                    println!("{}", e);
-                    continue 'assembler;
-                }
-                Ok(()) => (),
-            };
-
-            // find a match in the opcode matrix
-            let op_byte: u8;
-            let _ = match self.find_instruction(&c.cpu_type, &opcode, mode_id) {
-                None => {
-                    println!("invalid opcode!");
-                    continue 'assembler;
-                }
-                Some((_, idx)) => op_byte = idx,
-            };
-
-            /*println!(
-                "opcode: {} (${:02x}) - operand: {} - modeid={:?}",
-                opcode, op_byte, operand_s, mode_id
-            );*/
-
-            // write
-            match mode_id {
-                AddressingModeId::Imp | AddressingModeId::Acc => {
-                    if c.bus
-                        .get_memory()
-                        .write_byte(addr as usize, op_byte)
-                        .is_err()
-                    {
-                        res = false;
-                        break 'assembler;
-                    }
-                    addr = addr.wrapping_add(1);
-                }
-                AddressingModeId::Abs
-                | AddressingModeId::Abx
-                | AddressingModeId::Zpr
-                | AddressingModeId::Aix
-                | AddressingModeId::Aby
-                | AddressingModeId::Ind => {
-                    if mode_id == AddressingModeId::Zpr {
-                        // first split $xx,$yy
-                        let v: Vec<&str> = operand_s.split(',').collect();
-                        let b1: u8;
-                        let b2: u8;
-                        // get bytes
-                        let _ = match u8::from_str_radix(&v[0][1..], 16) {
-                            Err(_) => {
-                                println!("invalid opcode!");
-                                continue 'assembler;
-                            }
-                            Ok(a) => b1 = a,
-                        };
-                        let _ = match u8::from_str_radix(&v[1][1..], 16) {
-                            Err(_) => {
-                                println!("invalid opcode!");
-                                continue 'assembler;
-                            }
-                            Ok(a) => b2 = a,
-                        };
-
-                        // write opcode
-                        if c.bus
-                            .get_memory()
-                            .write_byte(addr as usize, op_byte)
-                            .is_err()
-                        {
-                            res = false;
-                            break 'assembler;
-                        }
-                        addr = addr.wrapping_add(1);
-
-                        // write zeropage address
-                        if c.bus.get_memory().write_byte(addr as usize, b1).is_err() {
-                            res = false;
-                            break 'assembler;
-                        }
-                        addr = addr.wrapping_add(1);
-
-                        // write offset
-                        if c.bus.get_memory().write_byte(addr as usize, b2).is_err() {
-                            res = false;
-                            break 'assembler;
-                        }
-                        addr = addr.wrapping_add(1);
-                    } else {
-                        let _ = match u16::from_str_radix(&operand_s[1..], 16) {
-                            Err(_) => {
-                                println!("invalid opcode!");
-                                continue 'assembler;
-                            }
-                            Ok(a) => {
-                                if c.bus
-                                    .get_memory()
-                                    .write_byte(addr as usize, op_byte)
-                                    .is_err()
-                                {
-                                    res = false;
-                                    break 'assembler;
-                                }
-                                addr = addr.wrapping_add(1);
-                                if c.bus.get_memory().write_word_le(addr as usize, a).is_err() {
-                                    res = false;
-                                    break 'assembler;
-                                }
-                                addr = addr.wrapping_add(2);
-                            }
-                        };
-                    }
-                }
-                AddressingModeId::Rel
-                | AddressingModeId::Imm
-                | AddressingModeId::Zpg
-                | AddressingModeId::Zpx
-                | AddressingModeId::Zpy
-                | AddressingModeId::Izp
-                | AddressingModeId::Iny
-                | AddressingModeId::Xin => {
-                    let _ = match u8::from_str_radix(&operand_s[1..], 16) {
-                        Err(_) => {
-                            println!("invalid opcode!");
-                            continue 'assembler;
-                        }
-                        Ok(a) => {
-                            if c.bus
-                                .get_memory()
-                                .write_byte(addr as usize, op_byte)
-                                .is_err()
-                            {
-                                res = false;
-                                break 'assembler;
-                            }
-                            addr = addr.wrapping_add(1);
-                            if c.bus.get_memory().write_byte(addr as usize, a).is_err() {
-                                res = false;
-                                break 'assembler;
-                            }
-                            addr = addr.wrapping_add(1);
-                        }
-                    };
-                }
-            };
-            if addr < prev_addr {
-                // overlap detected
-                println!("ERROR, overlapping detected!");
-                res = false;
-                break 'assembler;
-            }
-            prev_addr = addr;
         }
-        res
     }
 }
+
+/**
+ * builds an assembler error.
+ */
+fn asm_error(msg: &str) -> CpuError {
+    CpuError::new_default(CpuErrorType::Generic, 0, Some(String::from(msg)))
+}
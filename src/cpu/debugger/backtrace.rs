@@ -0,0 +1,135 @@
+/*
+ * Filename: /src/debugger/backtrace.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:14:03
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::debugger::Debugger;
+use crate::cpu::Cpu;
+use std::fmt::Display;
+use std::fmt::{Error, Formatter};
+
+/**
+ * a call frame found on the stack, as returned by Debugger::backtrace().
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// address of the JSR instruction.
+    pub call_site: u16,
+    /// the JSR target, the routine called.
+    pub target: u16,
+    /// where RTS returns to (call_site + 3).
+    pub return_address: u16,
+    /// stack address of the pushed return address low byte.
+    pub stack_address: u16,
+    /// the label of the called routine, if any.
+    pub label: Option<String>,
+    /// false if this may be a false positive: bytes not belonging to a frame precede it on the stack, or the called
+    /// routine starts after the code it should contain.
+    pub certain: bool,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "${:04x}: jsr ${:04x}{}, returns to ${:04x} (stack ${:04x}){}",
+            self.call_site,
+            self.target,
+            match &self.label {
+                Some(l) => format!(" ({})", l),
+                None => String::new(),
+            },
+            self.return_address,
+            self.stack_address,
+            if self.certain { "" } else { " ?" }
+        )
+    }
+}
+
+impl Debugger {
+    /**
+     * heuristic backtrace, newest first: scans the stack for return addresses pushed by JSR, i.e. words W where the
+     * byte at W-2 is a JSR opcode.
+     *
+     * pushed data may look like a return address too, such frames are marked as not certain (see Frame).
+     *
+     * memory is read directly, no callbacks or breakpoints are triggered.
+     */
+    pub fn backtrace<B: Bus>(&self, c: &mut Cpu<B>) -> Vec<Frame> {
+        let stack = c.stack_slice();
+        let base = 0x200 - stack.len();
+        let mut frames: Vec<Frame> = Vec::new();
+
+        // the innermost routine contains pc, the outer ones contain the call site of the previous certain frame
+        let mut inner = c.regs.pc;
+        let mut expected = 0;
+        let mut i = 0;
+        while i + 1 < stack.len() {
+            let w = u16::from_le_bytes([stack[i], stack[i + 1]]);
+            let call_site = w.wrapping_sub(2);
+            let jsr = c.bus.dump_range(call_site as usize, 3).unwrap_or_default();
+            let is_jsr = jsr.first() == Some(&0x20);
+            if !is_jsr {
+                i += 1;
+                continue;
+            }
+            let target = u16::from_le_bytes([jsr[1], jsr[2]]);
+            let certain = i == expected && target <= inner;
+            if certain {
+                inner = call_site;
+            }
+            frames.push(Frame {
+                call_site,
+                target,
+                return_address: w.wrapping_add(1),
+                stack_address: (base + i) as u16,
+                label: self.label_at(target).map(String::from),
+                certain,
+            });
+            i += 2;
+            expected = i;
+        }
+        frames
+    }
+
+    /**
+     * show the backtrace from the 'bt' command.
+     */
+    pub(super) fn cmd_backtrace<B: Bus>(&self, c: &mut Cpu<B>) -> bool {
+        let frames = self.backtrace(c);
+        if frames.is_empty() {
+            dbg_println!(self, "no frames found on the stack.");
+            return true;
+        }
+        for (i, f) in frames.iter().enumerate() {
+            dbg_println!(self, "#{} {}", i, f);
+        }
+        true
+    }
+}
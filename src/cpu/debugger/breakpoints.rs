@@ -28,18 +28,19 @@
  * SOFTWARE.
  */
 
+use crate::bus::Bus;
 use crate::cpu::cpu_error;
 use crate::cpu::cpu_error::CpuErrorType;
-use crate::cpu::debugger::Debugger;
+use crate::cpu::debugger::{script, Debugger};
 use crate::cpu::CpuError;
 use crate::cpu::CpuFlags;
-use crate::cpu::{Cpu, Registers, Vectors};
+use crate::cpu::{opcode_info, Cpu, Registers, StopReason, Vectors};
 use crate::utils::*;
 use bitflags::bitflags;
 use std::fmt::Display;
 use std::fmt::{Error, Formatter};
 use std::io;
-use std::io::{BufRead, Write};
+use std::io::BufRead;
 use std::str::Split;
 use std::str::SplitWhitespace;
 
@@ -47,7 +48,7 @@ bitflags! {
     /**
      * flags for breakpoint types
      */
-    pub(crate) struct BreakpointType : u8 {
+    pub struct BreakpointType : u8 {
         /// triggers on execute.
         const EXEC = 0b00000001;
 
@@ -62,6 +63,38 @@ bitflags! {
 
         /// triggers on nmi.
         const NMI =   0b00010000;
+
+        /// triggers when S wraps on push/pull, or when a push takes S below the floor (the breakpoint address).
+        const STACK = 0b00100000;
+
+        /// triggers before executing the given opcode(s), anywhere in memory.
+        const OPCODE = 0b01000000;
+
+        /// triggers after an instruction sets, clears or changes the given status flag.
+        const FLAG = 0b10000000;
+    }
+}
+
+/**
+ * when a flag breakpoint triggers.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagTrigger {
+    /// the flag goes from 0 to 1.
+    Set,
+    /// the flag goes from 1 to 0.
+    Clear,
+    /// the flag goes either way.
+    Changed,
+}
+
+impl Display for FlagTrigger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            FlagTrigger::Set => write!(f, "=1"),
+            FlagTrigger::Clear => write!(f, "=0"),
+            FlagTrigger::Changed => write!(f, " changed"),
+        }
     }
 }
 
@@ -94,24 +127,266 @@ bitflags! {
     }
 }
 
+/**
+ * index of a breakpoint in the debugger breakpoints list.
+ *
+ * > indexes shift down when a breakpoint before is removed, as with the 'bdel' command.
+ */
+pub type BpId = usize;
+
+/**
+ * breakpoint conditions, all the given registers must match.
+ *
+ * if cycles is set, the breakpoint triggers when the elapsed cycles match, regardless of the address.
+ *
+ * handler applies to irq and nmi breakpoints only, which then trigger only when the vector points there.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BpCondition {
+    pub a: Option<u8>,
+    pub x: Option<u8>,
+    pub y: Option<u8>,
+    pub s: Option<u8>,
+    pub p: Option<u8>,
+    pub cycles: Option<usize>,
+    pub handler: Option<u16>,
+}
+
+/**
+ * where an interrupt has been taken, reported when an irq or nmi breakpoint triggers (see Debugger::interrupt_context()).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterruptContext {
+    /// BreakpointType::IRQ or BreakpointType::NMI.
+    pub kind: BreakpointType,
+    /// the interrupted pc, where RTI returns to.
+    pub pc: u16,
+    /// the vector address, $fffe or $fffa.
+    pub vector: u16,
+    /// the handler address, fetched from the vector.
+    pub handler: u16,
+    /// the status byte pushed on the stack.
+    pub p: u8,
+    /// the elapsed cycles when the interrupt has been taken.
+    pub cycles: usize,
+}
+
+impl Display for InterruptContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "{} taken at PC=${:04x}, vector ${:04x} -> ${:04x}, stacked P=${:02x}, cycles={}",
+            if self.kind == BreakpointType::NMI {
+                "NMI"
+            } else {
+                "IRQ"
+            },
+            self.pc,
+            self.vector,
+            self.handler,
+            self.p,
+            self.cycles
+        )
+    }
+}
+
 /**
  * represents a breakpoint
  */
 #[derive(PartialEq, Debug)]
-pub(crate) struct Bp {
+pub struct Bp {
     pub(super) address: u16,
+    /// last address of the watched range, same as address for single address breakpoints.
+    pub(super) end: u16,
     pub(super) t: u8,
     pub(super) enabled: bool,
     pub(super) regs: Option<Registers>,
     pub(super) cycles: usize,
     mask: u8,
+    /// for irq and nmi breakpoints, trigger only if the vector points to this handler.
+    handler: Option<u16>,
+    /// one-shot breakpoint set by the 'n' and 'u' commands, deleted on the next stop.
+    temporary: bool,
+    /// for opcode breakpoints, the opcode bytes to break on.
+    opcodes: Vec<u8>,
+    /// for opcode breakpoints, the mnemonic or opcode byte as given.
+    opcode_name: String,
+    /// debugger commands executed when the breakpoint triggers.
+    commands: Vec<String>,
+    /// for flag breakpoints, the watched flag and the transition to break on.
+    flag: Option<(CpuFlags, FlagTrigger)>,
+    /// how many times the breakpoint triggered.
+    hits: usize,
 }
 
 impl Bp {
+    /**
+     * creates a new enabled breakpoint.
+     */
+    fn new(address: u16, end: u16, t: BreakpointType, cond: Option<BpCondition>) -> Bp {
+        let mut bp = Bp {
+            address,
+            end,
+            t: t.bits(),
+            enabled: true,
+            regs: None,
+            cycles: 0,
+            mask: 0,
+            handler: None,
+            temporary: false,
+            opcodes: Vec::new(),
+            opcode_name: String::new(),
+            commands: Vec::new(),
+            flag: None,
+            hits: 0,
+        };
+        if let Some(cond) = cond {
+            let mut mask = BpMask::empty();
+            let mut get = |v: Option<u8>, m: BpMask| match v {
+                Some(b) => {
+                    mask |= m;
+                    b
+                }
+                None => 0,
+            };
+            bp.regs = Some(Registers {
+                a: get(cond.a, BpMask::A),
+                x: get(cond.x, BpMask::X),
+                y: get(cond.y, BpMask::Y),
+                s: get(cond.s, BpMask::S),
+                p: CpuFlags::from_bits(get(cond.p, BpMask::P)).unwrap(),
+                pc: 0,
+            });
+            bp.mask = mask.bits();
+            bp.cycles = cond.cycles.unwrap_or_default();
+            bp.handler = cond.handler;
+        }
+        bp
+    }
+
+    /**
+     * the breakpoint address (the handler address for irq/nmi breakpoints).
+     */
+    pub fn address(&self) -> u16 {
+        self.address
+    }
+
+    /**
+     * the last address of the breakpoint range (same as address() for single address breakpoints).
+     */
+    pub fn end_address(&self) -> u16 {
+        self.end
+    }
+
+    /**
+     * check if address is within the breakpoint range.
+     */
+    pub fn contains(&self, address: u16) -> bool {
+        address >= self.address && address <= self.end
+    }
+
+    /**
+     * the breakpoint address or range as string.
+     */
+    fn address_to_string(&self) -> String {
+        if self.end != self.address {
+            format!("${:04x}-${:04x}", self.address, self.end)
+        } else {
+            format!("${:04x}", self.address)
+        }
+    }
+
+    /**
+     * the breakpoint type.
+     */
+    pub fn bp_type(&self) -> BreakpointType {
+        BreakpointType::from_bits_truncate(self.t)
+    }
+
+    /**
+     * for opcode breakpoints, the opcode bytes the breakpoint triggers on.
+     */
+    pub fn opcodes(&self) -> &[u8] {
+        &self.opcodes
+    }
+
+    /**
+     * for flag breakpoints, the watched flag and the transition the breakpoint triggers on.
+     */
+    pub fn flag(&self) -> Option<(CpuFlags, FlagTrigger)> {
+        self.flag
+    }
+
+    /**
+     * how many times the breakpoint triggered.
+     */
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /**
+     * the debugger commands executed when the breakpoint triggers.
+     */
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /**
+     * whether the breakpoint is a one-shot breakpoint set by step over/run to address.
+     */
+    pub fn is_temporary(&self) -> bool {
+        self.temporary
+    }
+
+    /**
+     * whether the breakpoint is enabled.
+     */
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /**
+     * the breakpoint conditions, if any.
+     */
+    pub fn condition(&self) -> Option<BpCondition> {
+        let regs = self.regs.as_ref()?;
+        let mask = BpMask::from_bits_truncate(self.mask);
+        let get = |v: u8, m: BpMask| if mask.contains(m) { Some(v) } else { None };
+        Some(BpCondition {
+            a: get(regs.a, BpMask::A),
+            x: get(regs.x, BpMask::X),
+            y: get(regs.y, BpMask::Y),
+            s: get(regs.s, BpMask::S),
+            p: get(regs.p.bits(), BpMask::P),
+            cycles: if self.cycles != 0 {
+                Some(self.cycles)
+            } else {
+                None
+            },
+            handler: self.handler,
+        })
+    }
+
+    /**
+     * check the registers conditions, if any.
+     */
+    fn regs_match<B: Bus>(&self, c: &Cpu<B>) -> bool {
+        if self.mask == 0 {
+            return true;
+        }
+        let mask = BpMask::from_bits(self.mask).unwrap();
+        let checks = self.regs.as_ref().unwrap();
+        !((mask.contains(BpMask::A) && checks.a != c.regs.a)
+            || (mask.contains(BpMask::X) && checks.x != c.regs.x)
+            || (mask.contains(BpMask::Y) && checks.y != c.regs.y)
+            || (mask.contains(BpMask::S) && checks.s != c.regs.s)
+            || (mask.contains(BpMask::P) && checks.p != c.regs.p))
+    }
+
     /**
      * convert BreakpointType flags to a meaningful string
      */
-    fn flags_to_string(&self) -> String {
+    pub(super) fn flags_to_string(&self) -> String {
         let p = BreakpointType::from_bits(self.t).unwrap();
         // nmi and irq are single
         if p.contains(BreakpointType::NMI) {
@@ -120,6 +395,15 @@ impl Bp {
         if p.contains(BreakpointType::IRQ) {
             return String::from("IRQ");
         }
+        if p.contains(BreakpointType::STACK) {
+            return String::from("STACK");
+        }
+        if p.contains(BreakpointType::OPCODE) {
+            return String::from("OPCODE");
+        }
+        if p.contains(BreakpointType::FLAG) {
+            return String::from("FLAG");
+        }
 
         let s = format!(
             "{}{}{}",
@@ -145,11 +429,32 @@ impl Bp {
 
 impl Display for Bp {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        if self.t == BreakpointType::NMI.bits() || self.t == BreakpointType::IRQ.bits() {
+        if self.t == BreakpointType::STACK.bits() {
+            return write!(
+                f,
+                "[{},{}], floor=${:02x}",
+                self.flags_to_string(),
+                if self.enabled { "enabled" } else { "disabled" },
+                self.address
+            );
+        }
+        if self.t == BreakpointType::NMI.bits()
+            || self.t == BreakpointType::IRQ.bits()
+            || self.t == BreakpointType::OPCODE.bits()
+            || self.t == BreakpointType::FLAG.bits()
+        {
+            let name = if self.t == BreakpointType::OPCODE.bits() {
+                format!("{} ", self.opcode_name)
+            } else if let Some((flag, trigger)) = self.flag {
+                format!("{}{} ", flag_name(flag), trigger)
+            } else {
+                String::new()
+            };
             if self.regs.is_some() {
                 write!(
                     f,
-                    "[{},{}], conditions: {}{}",
+                    "{}[{},{}], conditions: {}{}{}",
+                    name,
                     self.flags_to_string(),
                     if self.enabled { "enabled" } else { "disabled" },
                     self.regs.as_ref().unwrap(),
@@ -158,11 +463,16 @@ impl Display for Bp {
                     } else {
                         "".to_string()
                     },
+                    match self.handler {
+                        Some(h) => format!(", handler=${:04x}", h),
+                        None => "".to_string(),
+                    },
                 )?;
             } else {
                 write!(
                     f,
-                    "[{},{}]",
+                    "{}[{},{}]",
+                    name,
                     self.flags_to_string(),
                     if self.enabled { "enabled" } else { "disabled" }
                 )?;
@@ -171,8 +481,8 @@ impl Display for Bp {
             if self.regs.is_some() {
                 write!(
                     f,
-                    "${:04x} [{},{}], conditions: {}{}",
-                    self.address,
+                    "{} [{},{}], conditions: {}{}",
+                    self.address_to_string(),
                     self.flags_to_string(),
                     if self.enabled { "enabled" } else { "disabled" },
                     self.regs.as_ref().unwrap(),
@@ -185,25 +495,78 @@ impl Display for Bp {
             } else {
                 write!(
                     f,
-                    "${:04x} [{},{}]",
-                    self.address,
+                    "{} [{},{}]",
+                    self.address_to_string(),
                     self.flags_to_string(),
                     if self.enabled { "enabled" } else { "disabled" }
                 )?;
             }
         }
+        if !self.commands.is_empty() {
+            write!(f, " do \"{}\"", self.commands.join("; "))?;
+        }
 
         Ok(())
     }
 }
 
 impl Debugger {
+    /**
+     * check if a stack breakpoint triggers when pushing (push=true) or pulling size bytes with S=old_s (returns a CpuError::RwBreakpoint in case)
+     */
+    pub(crate) fn handle_stack_breakpoint<B: Bus>(
+        &self,
+        c: &Cpu<B>,
+        old_s: u8,
+        size: u8,
+        push: bool,
+    ) -> Result<(), CpuError> {
+        if self.bp_suspended {
+            return Ok(());
+        }
+        let (new_s, wrap) = if push {
+            old_s.overflowing_sub(size)
+        } else {
+            old_s.overflowing_add(size)
+        };
+        for (i, bp) in self.breakpoints.iter().enumerate() {
+            if !bp.enabled || bp.t != BreakpointType::STACK.bits() {
+                continue;
+            }
+            let reason = if wrap {
+                if push {
+                    "stack overflow"
+                } else {
+                    "stack underflow"
+                }
+            } else if push && (new_s as u16) < bp.address {
+                "stack below floor"
+            } else {
+                continue;
+            };
+            return Err(CpuError {
+                t: CpuErrorType::RwBreakpoint,
+                address: 0x100 + old_s as usize,
+                mem_size: 0,
+                access_size: size as usize,
+                bp_idx: i as i8,
+                msg: Some(format!(
+                    "{} at PC=${:04x}, S=${:02x}->${:02x}",
+                    reason, c.regs.pc, old_s, new_s
+                )),
+                opcode: None,
+                mode: None,
+            });
+        }
+        Ok(())
+    }
+
     /**
      * check if an rw breakpoint triggers at address (returns a CpuError::RwBreakpoint in case)
      */
-    pub(crate) fn handle_rw_breakpoint(
+    pub(crate) fn handle_rw_breakpoint<B: Bus>(
         &self,
-        c: &Cpu,
+        c: &Cpu<B>,
         address: u16,
         t: BreakpointType,
     ) -> Result<(), CpuError> {
@@ -218,6 +581,8 @@ impl Debugger {
                     access_size: 1,
                     bp_idx: idx,
                     msg: None,
+                    opcode: None,
+                    mode: None,
                 };
                 return Err(e);
             }
@@ -228,20 +593,11 @@ impl Debugger {
     }
 
     /**
-     * split a string like "a=$10,x=$20,cycles=1234,..." and build a breakpoint with conditions
+     * split a string like "a=$10,x=$20,cycles=1234,..." and build the breakpoint conditions
      */
-    fn bp_from_conditions<'a>(&mut self, itt: &mut Split<'a, char>, bp: &mut Bp) -> bool {
+    fn bp_conditions_from_str(itt: &mut Split<'_, char>) -> Option<BpCondition> {
         let mut count = 0;
-        let mut target_regs = Registers {
-            a: 0,
-            x: 0,
-            y: 0,
-            s: 0,
-            p: CpuFlags::from_bits(0).unwrap(),
-            pc: 0,
-        };
-        let mut target_cycles: usize = 0;
-        let mut target_mask = BpMask::from_bits(0).unwrap();
+        let mut cond = BpCondition::default();
         loop {
             // get entry
             let item = itt.next().unwrap_or_default().to_ascii_lowercase();
@@ -253,65 +609,26 @@ impl Debugger {
             let arr: Vec<&str> = item.split('=').collect();
             if arr.len() != 2 {
                 // wrong condition
-                return false;
+                return None;
             }
-            match arr[0] {
-                "a" => {
-                    let _ = match u8::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 16) {
-                        Err(_) => return false,
-                        Ok(a) => {
-                            target_regs.a = a;
-                            target_mask |= BpMask::A;
-                        }
-                    };
-                }
-                "x" => {
-                    let _ = match u8::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 16) {
-                        Err(_) => return false,
-                        Ok(x) => {
-                            target_regs.x = x;
-                            target_mask |= BpMask::X;
-                        }
-                    };
-                }
-                "y" => {
-                    let _ = match u8::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 16) {
-                        Err(_) => return false,
-                        Ok(y) => {
-                            target_regs.y = y;
-                            target_mask |= BpMask::Y;
-                        }
-                    };
-                }
-                "s" => {
-                    let _ = match u8::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 16) {
-                        Err(_) => return false,
-                        Ok(s) => {
-                            target_regs.s = s;
-                            target_mask |= BpMask::S;
-                        }
-                    };
-                }
-                "p" => {
-                    let _ = match u8::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 16) {
-                        Err(_) => return false,
-                        Ok(p) => {
-                            target_regs.p = CpuFlags::from_bits(p).unwrap();
-                            target_mask |= BpMask::P;
-                        }
-                    };
-                }
-                "cycles" => {
-                    let _ = match usize::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 10) {
-                        Err(_) => return false,
-                        Ok(cycles) => {
-                            target_cycles = cycles;
-                        }
-                    };
-                }
-                _ => {
-                    // invalid
-                    return false;
+            if arr[0] == "cycles" {
+                cond.cycles =
+                    Some(usize::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 10).ok()?);
+            } else if arr[0] == "handler" {
+                cond.handler =
+                    Some(u16::from_str_radix(&arr[1][is_dollar_hex(arr[1])..], 16).ok()?);
+            } else {
+                let v = u8::from_str_radix(&arr[1][is_dollar_hex(&arr[1])..], 16).ok()?;
+                match arr[0] {
+                    "a" => cond.a = Some(v),
+                    "x" => cond.x = Some(v),
+                    "y" => cond.y = Some(v),
+                    "s" => cond.s = Some(v),
+                    "p" => cond.p = Some(v),
+                    _ => {
+                        // invalid
+                        return None;
+                    }
                 }
             }
 
@@ -320,27 +637,313 @@ impl Debugger {
         }
         if count == 0 {
             // invalid, no items
-            return false;
+            return None;
         }
-
-        // return the filled bp struct
-        bp.regs = Some(target_regs);
-        bp.mask = target_mask.bits();
-        bp.cycles = target_cycles;
-        return true;
+        Some(cond)
     }
 
     /**
-     * add a breakpoint.
+     * add a breakpoint, returns its index.
+     *
+     * for irq and nmi breakpoints, address is ignored and the handler address is read from the vector: they trigger whenever
+     * the interrupt is taken (not on BRK), stopping on the handler with the context in interrupt_context(). to trigger only for
+     * a given handler, set it in the conditions.
+     *
+     * for stack breakpoints, address is the S floor (0 for wrap detection only).
      *
      * > exec, nmi, irq breakpoints triggers BEFORE the instruction executes. read/write breakpoints triggers AFTER the instruction executed.
      */
-    pub(super) fn cmd_add_breakpoint(
+    pub fn add_breakpoint<B: Bus>(
+        &mut self,
+        c: &mut Cpu<B>,
+        address: u16,
+        t: BreakpointType,
+        cond: Option<BpCondition>,
+    ) -> Result<BpId, CpuError> {
+        self.add_breakpoint_range(c, address, address, t, cond)
+    }
+
+    /**
+     * add a breakpoint triggering on any address between start and end (inclusive), returns its index.
+     *
+     * for irq and nmi breakpoints, the range is ignored and the handler address is read from the vector.
+     */
+    pub fn add_breakpoint_range<B: Bus>(
+        &mut self,
+        c: &mut Cpu<B>,
+        start: u16,
+        end: u16,
+        t: BreakpointType,
+        cond: Option<BpCondition>,
+    ) -> Result<BpId, CpuError> {
+        let address = start;
+        if t.is_empty()
+            || end < start
+            || t.contains(BreakpointType::OPCODE)
+            || t.contains(BreakpointType::FLAG)
+            || (t.contains(BreakpointType::STACK) && (t != BreakpointType::STACK || end > 0xff))
+        {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                address,
+                Some(String::from("invalid breakpoint type or range!")),
+            ));
+        }
+
+        // check if type is irq or nmi, so compute the address
+        let (addr, end) = if t == BreakpointType::IRQ {
+            let v = Vectors::IRQ.address();
+            let a = u16::from_le_bytes([c.bus.peek(v)?, c.bus.peek(v + 1)?]);
+            (a, a)
+        } else if t == BreakpointType::NMI {
+            let v = Vectors::NMI.address();
+            let a = u16::from_le_bytes([c.bus.peek(v)?, c.bus.peek(v + 1)?]);
+            (a, a)
+        } else {
+            cpu_error::check_address_boundaries(
+                c.bus.get_memory().get_size(),
+                address as usize,
+                (end - address) as usize + 1,
+                CpuErrorType::MemoryRead,
+                None,
+            )?;
+            (address, end)
+        };
+
+        // add breakpoint if not already present
+        let handler = cond.and_then(|c| c.handler);
+        for bp in self.breakpoints.iter() {
+            if bp.address == addr
+                && bp.end == end
+                && ((bp.t & t.bits()) != 0)
+                && bp.handler == handler
+            {
+                return Err(CpuError::new_default(
+                    CpuErrorType::Generic,
+                    addr,
+                    Some(String::from("breakpoint already set!")),
+                ));
+            }
+        }
+        self.breakpoints.push(Bp::new(addr, end, t, cond));
+        Ok(self.breakpoints.len() - 1)
+    }
+
+    /**
+     * add a breakpoint triggering before any instruction with the given opcode executes, anywhere in memory.
+     *
+     * opcode is either a mnemonic (i.e. "sta", matching all the addressing modes for the current cpu type) or an
+     * opcode byte (i.e. "$af").
+     */
+    pub fn add_opcode_breakpoint<B: Bus>(
         &mut self,
-        c: &mut Cpu,
+        c: &Cpu<B>,
+        opcode: &str,
+        cond: Option<BpCondition>,
+    ) -> Result<BpId, CpuError> {
+        let name = opcode.to_ascii_lowercase();
+        let opcodes: Vec<u8> = if let Some(hex) = name.strip_prefix('$') {
+            match u8::from_str_radix(hex, 16) {
+                Ok(b) => vec![b],
+                Err(_) => Vec::new(),
+            }
+        } else {
+            (0..=0xffu8)
+                .filter(|b| opcode_info(*b, c.cpu_type()).mnemonic == name)
+                .collect()
+        };
+        if opcodes.is_empty() {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(format!("invalid opcode or mnemonic '{}'!", opcode)),
+            ));
+        }
+
+        // add breakpoint if not already present
+        for bp in self.breakpoints.iter() {
+            if bp.t == BreakpointType::OPCODE.bits() && bp.opcodes == opcodes {
+                return Err(CpuError::new_default(
+                    CpuErrorType::Generic,
+                    0,
+                    Some(String::from("breakpoint already set!")),
+                ));
+            }
+        }
+        let mut bp = Bp::new(0, 0, BreakpointType::OPCODE, cond);
+        bp.opcodes = opcodes;
+        bp.opcode_name = name;
+        self.breakpoints.push(bp);
+        Ok(self.breakpoints.len() - 1)
+    }
+
+    /**
+     * add a breakpoint triggering after an instruction sets (FlagTrigger::Set), clears (FlagTrigger::Clear) or
+     * changes (FlagTrigger::Changed) the given status flag, i.e. CpuFlags::D with FlagTrigger::Set to catch a stray SED.
+     *
+     * the conditions, if any, are checked against the registers after the instruction.
+     */
+    pub fn add_flag_breakpoint(
+        &mut self,
+        flag: CpuFlags,
+        trigger: FlagTrigger,
+        cond: Option<BpCondition>,
+    ) -> Result<BpId, CpuError> {
+        if flag.bits().count_ones() != 1 {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(String::from("a flag breakpoint watches a single flag!")),
+            ));
+        }
+
+        // add breakpoint if not already present
+        for bp in self.breakpoints.iter() {
+            if bp.flag == Some((flag, trigger)) {
+                return Err(CpuError::new_default(
+                    CpuErrorType::Generic,
+                    0,
+                    Some(String::from("breakpoint already set!")),
+                ));
+            }
+        }
+        let mut bp = Bp::new(0, 0, BreakpointType::FLAG, cond);
+        bp.flag = Some((flag, trigger));
+        self.breakpoints.push(bp);
+        Ok(self.breakpoints.len() - 1)
+    }
+
+    /**
+     * add a one-shot exec breakpoint, deleted (with any other temporary breakpoint) on the next stop.
+     */
+    pub(crate) fn add_temporary_breakpoint(&mut self, address: u16, cond: Option<BpCondition>) {
+        let mut bp = Bp::new(address, address, BreakpointType::EXEC, cond);
+        bp.temporary = true;
+        self.breakpoints.push(bp);
+    }
+
+    /**
+     * called when breakpoint idx triggers: stops execution and deletes temporary breakpoints.
+     *
+     * returns false if the triggered breakpoint was a temporary one.
+     */
+    pub(crate) fn breakpoint_triggered(&mut self, idx: usize) -> bool {
+        self.going = false;
+        self.step_out_s = None;
+        let temporary = match self.breakpoints.get_mut(idx) {
+            Some(bp) => {
+                bp.hits += 1;
+                bp.temporary
+            }
+            None => false,
+        };
+        self.breakpoints.retain(|bp| !bp.temporary);
+        self.triggered_bp = if temporary { None } else { Some(idx) };
+        !temporary
+    }
+
+    /**
+     * queue the commands attached to breakpoint idx, to run at the prompt before anything else.
+     */
+    pub(crate) fn queue_breakpoint_commands(&mut self, idx: usize) {
+        let cmds = self
+            .breakpoints
+            .get(idx)
+            .map_or(&[][..], |bp| &bp.commands[..]);
+        for cmd in cmds.iter().rev() {
+            self.cmd_queue.push_front(cmd.clone());
+        }
+    }
+
+    /**
+     * called when a breakpoint stops run_until() with the debugger disabled, the next run resumes from pc.
+     */
+    pub(crate) fn stop_reason<B: Bus>(&mut self, c: &Cpu<B>, user_bp: bool, idx: i8) -> StopReason {
+        self.resume_pc = Some(c.regs.pc);
+        if user_bp {
+            StopReason::Breakpoint(idx as BpId)
+        } else {
+            StopReason::Stopped
+        }
+    }
+
+    /**
+     * attach debugger commands to the breakpoint at the given index, replacing the previous ones.
+     *
+     * when the breakpoint triggers with the debugger enabled, the commands are executed at the prompt as with
+     * queue_cmd(): ending the list with 'g' continues execution, otherwise it stops waiting for input.
+     */
+    pub fn set_breakpoint_commands(&mut self, id: BpId, cmds: &[&str]) -> Result<(), CpuError> {
+        self.check_breakpoint_id(id)?;
+        self.breakpoints[id].commands = cmds.iter().map(|c| String::from(*c)).collect();
+        Ok(())
+    }
+
+    /**
+     * remove the breakpoint at the given index.
+     */
+    pub fn remove_breakpoint(&mut self, id: BpId) -> Result<(), CpuError> {
+        self.check_breakpoint_id(id)?;
+        self.breakpoints.remove(id);
+        Ok(())
+    }
+
+    /**
+     * enable or disable the breakpoint at the given index.
+     */
+    pub fn enable_breakpoint(&mut self, id: BpId, enable: bool) -> Result<(), CpuError> {
+        self.check_breakpoint_id(id)?;
+        self.breakpoints[id].enabled = enable;
+        Ok(())
+    }
+
+    /**
+     * remove all breakpoints.
+     */
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /**
+     * get the breakpoints list.
+     */
+    pub fn list_breakpoints(&self) -> &[Bp] {
+        &self.breakpoints
+    }
+
+    /**
+     * check the breakpoint index is valid.
+     */
+    fn check_breakpoint_id(&self, id: BpId) -> Result<(), CpuError> {
+        if id >= self.breakpoints.len() {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(format!("invalid breakpoint index {}!", id)),
+            ));
+        }
+        Ok(())
+    }
+
+    /**
+     * add a breakpoint from the 'bx|br|bw|brw|bn|bq|bs|bo|bf' commands, with the commands attached by 'do "..."' if any.
+     */
+    pub(super) fn cmd_add_breakpoint<B: Bus>(
+        &mut self,
+        c: &mut Cpu<B>,
         cmd: &str,
-        mut it: SplitWhitespace<'_>,
+        cmd_string: &str,
     ) -> bool {
+        let (bp_s, commands) = match script::split_attached_cmds(cmd_string) {
+            None => {
+                self.cmd_invalid();
+                return false;
+            }
+            Some(r) => r,
+        };
+        let mut it = bp_s.split_whitespace();
+        it.next();
+
         // check breakpoint type
         let t: BreakpointType;
         match cmd {
@@ -350,103 +953,131 @@ impl Debugger {
             "br" => t = BreakpointType::READ,
             "bw" => t = BreakpointType::WRITE,
             "brw" => t = BreakpointType::READ | BreakpointType::WRITE,
+            "bs" => t = BreakpointType::STACK,
+            "bo" => t = BreakpointType::OPCODE,
+            "bf" => t = BreakpointType::FLAG,
             _ => {
                 self.cmd_invalid();
                 return false;
             }
         }
 
-        // check if type is irq or nmi, so compute the address
-        let addr: u16;
-        if t == BreakpointType::IRQ {
-            match c.bus.get_memory().read_word_le(Vectors::IRQ as usize) {
-                Ok(a) => addr = a,
-                Err(_) => {
-                    self.cmd_invalid();
-                    return false;
-                }
+        // get address (or $start-$end range) from iterator, irq and nmi take it from the vector
+        let mut addr: u16 = 0;
+        let mut end: u16 = 0;
+        let mut opcode_s = "";
+        let mut flag: Option<(CpuFlags, FlagTrigger)> = None;
+        if t == BreakpointType::FLAG {
+            // <flag>=<0|1> or <flag> changed
+            let flag_s = it.next().unwrap_or_default().to_ascii_lowercase();
+            let (name, trigger) = match flag_s.split_once('=') {
+                Some((name, "1")) => (name.to_string(), Some(FlagTrigger::Set)),
+                Some((name, "0")) => (name.to_string(), Some(FlagTrigger::Clear)),
+                Some(_) => (String::new(), None),
+                None => match it.next() {
+                    Some(w) if w.eq_ignore_ascii_case("changed") => {
+                        (flag_s.clone(), Some(FlagTrigger::Changed))
+                    }
+                    _ => (String::new(), None),
+                },
             };
-        } else if t == BreakpointType::NMI {
-            match c.bus.get_memory().read_word_le(Vectors::NMI as usize) {
-                Ok(a) => addr = a,
-                Err(_) => {
-                    self.cmd_invalid();
-                    return false;
-                }
+            flag = match (flag_from_name(&name), trigger) {
+                (Some(f), Some(t)) => Some((f, t)),
+                _ => None,
             };
-        } else {
-            // get address from iterator
-            let addr_s = it.next().unwrap_or_default();
-            if addr_s.len() == 0 {
+            if flag.is_none() {
                 self.cmd_invalid();
                 return false;
             }
-            let _ = match u16::from_str_radix(&addr_s[is_dollar_hex(&addr_s)..], 16) {
-                Err(_) => {
-                    // invalid command, address invalid
-                    self.cmd_invalid();
-                    return false;
+        } else if t == BreakpointType::OPCODE {
+            // mnemonic or opcode byte
+            opcode_s = it.next().unwrap_or_default();
+            if opcode_s.is_empty() {
+                self.cmd_invalid();
+                return false;
+            }
+        } else if t == BreakpointType::STACK {
+            // optional floor
+            let floor_s = it.next().unwrap_or_default();
+            if !floor_s.is_empty() {
+                match u8::from_str_radix(&floor_s[is_dollar_hex(&floor_s)..], 16) {
+                    Err(_) => {
+                        // invalid command, floor invalid
+                        self.cmd_invalid();
+                        return false;
+                    }
+                    Ok(a) => {
+                        addr = a as u16;
+                        end = addr;
+                    }
+                };
+            }
+        } else if t != BreakpointType::IRQ && t != BreakpointType::NMI {
+            let range_s = it.next().unwrap_or_default();
+            if range_s.len() == 0 {
+                self.cmd_invalid();
+                return false;
+            }
+            let (addr_s, end_s) = range_s.split_once('-').unwrap_or((range_s, range_s));
+            match (self.parse_address(addr_s), self.parse_address(end_s)) {
+                (Ok(a), Ok(e)) => {
+                    addr = a;
+                    end = e;
                 }
-                Ok(a) => addr = a,
-            };
-            let _ = match cpu_error::check_address_boundaries(
-                c.bus.get_memory().get_size(),
-                addr as usize,
-                1,
-                CpuErrorType::MemoryRead,
-                None,
-            ) {
-                Err(e) => {
-                    println!("{}", e);
+                (Err(e), _) | (_, Err(e)) => {
+                    // invalid address or unknown symbol
+                    self.cmd_error(&e);
                     return false;
                 }
-                Ok(_) => (),
             };
         }
 
-        // add breakpoint if not already present
-        for (_, bp) in self.breakpoints.iter().enumerate() {
-            if bp.address == addr && ((bp.t & t.bits()) != 0) {
-                println!("breakpoint already set!");
-                return false;
-            }
-        }
-
-        let mut bp = Bp {
-            address: addr,
-            t: t.bits(),
-            enabled: true,
-            regs: None,
-            cycles: 0,
-            mask: 0,
-        };
-
         // check if we have conditions
+        let mut cond: Option<BpCondition> = None;
         let conditions = it.next().unwrap_or_default();
         if !conditions.is_empty() {
-            // split commas and build proper bp struct
+            // split commas and build the conditions
             let mut itt = conditions.split(',');
-            if !self.bp_from_conditions(&mut itt, &mut bp) {
+            cond = Self::bp_conditions_from_str(&mut itt);
+            if cond.is_none() {
                 // invalid command
                 self.cmd_invalid();
                 return false;
             }
         }
 
-        println!("breakpoint set! ({})", bp);
-        self.breakpoints.push(bp);
+        let res = if let Some((f, trigger)) = flag {
+            self.add_flag_breakpoint(f, trigger, cond)
+        } else if t == BreakpointType::OPCODE {
+            self.add_opcode_breakpoint(c, opcode_s, cond)
+        } else {
+            self.add_breakpoint_range(c, addr, end, t, cond)
+        };
+        match res {
+            Err(e) => {
+                self.cmd_error(&e);
+                return false;
+            }
+            Ok(idx) => {
+                self.breakpoints[idx].commands = commands;
+                dbg_println!(self, "breakpoint set! ({})", self.breakpoints[idx]);
+            }
+        }
         return true;
     }
 
     /**
      * check if there's a breakpoint at the given address and it's enabled, and return its index.
      */
-    pub(crate) fn has_enabled_breakpoint(
+    pub(crate) fn has_enabled_breakpoint<B: Bus>(
         &self,
-        c: &Cpu,
+        c: &Cpu<B>,
         addr: u16,
         t: BreakpointType,
     ) -> Option<i8> {
+        if self.bp_suspended {
+            return None;
+        }
         for (i, bp) in self.breakpoints.iter().enumerate() {
             let mut do_break: bool = false;
             if !bp.enabled || (bp.t & t.bits()) == 0 {
@@ -461,7 +1092,7 @@ impl Debugger {
                 }
             } else {
                 // either, check the address
-                if bp.address == addr {
+                if bp.contains(addr) {
                     do_break = true;
                 }
             }
@@ -517,20 +1148,148 @@ impl Debugger {
         None
     }
 
+    /**
+     * check if there's an enabled opcode breakpoint for the opcode about to be executed, and return its index.
+     */
+    pub(crate) fn has_enabled_opcode_breakpoint<B: Bus>(
+        &self,
+        c: &Cpu<B>,
+        opcode: u8,
+    ) -> Option<i8> {
+        if self.bp_suspended {
+            return None;
+        }
+        for (i, bp) in self.breakpoints.iter().enumerate() {
+            if !bp.enabled || bp.t != BreakpointType::OPCODE.bits() || !bp.opcodes.contains(&opcode)
+            {
+                continue;
+            }
+
+            // check conditions
+            if !bp.regs_match(c) {
+                continue;
+            }
+            if bp.cycles != 0 && c.cycles != bp.cycles {
+                continue;
+            }
+            return Some(i as i8);
+        }
+        None
+    }
+
+    /**
+     * check, after the instruction at pc executed, if an enabled flag breakpoint matches the change from p_before to the
+     * current P, and return its index.
+     *
+     * a flag breakpoint triggers after the instruction, so resuming does not suspend it.
+     */
+    pub(crate) fn has_enabled_flag_breakpoint<B: Bus>(
+        &mut self,
+        c: &Cpu<B>,
+        pc: u16,
+        p_before: CpuFlags,
+    ) -> Option<i8> {
+        let changed = p_before ^ c.regs.p;
+        if changed.is_empty() {
+            return None;
+        }
+        let idx = self.breakpoints.iter().position(|bp| {
+            let (flag, trigger) = match bp.flag {
+                Some(f) if bp.enabled => f,
+                _ => return false,
+            };
+            changed.contains(flag)
+                && match trigger {
+                    FlagTrigger::Set => c.regs.p.contains(flag),
+                    FlagTrigger::Clear => !c.regs.p.contains(flag),
+                    FlagTrigger::Changed => true,
+                }
+                && (bp.cycles == 0 || bp.cycles == c.cycles)
+                && bp.regs_match(c)
+        })?;
+        let (flag, _) = self.breakpoints[idx].flag.unwrap();
+        let before = Registers {
+            p: p_before,
+            ..c.regs
+        };
+        self.flag_change = Some(format!(
+            "{} {} by the instruction at ${:04x}, P={} -> {}",
+            flag_name(flag).to_ascii_uppercase(),
+            if c.regs.p.contains(flag) {
+                "set"
+            } else {
+                "cleared"
+            },
+            pc,
+            before.flags_to_string(),
+            c.regs.flags_to_string()
+        ));
+        Some(idx as i8)
+    }
+
+    /**
+     * returns a description of the flag change which triggered the last flag breakpoint, if any.
+     */
+    pub fn flag_change(&self) -> Option<&str> {
+        self.flag_change.as_deref()
+    }
+
+    /**
+     * called by the irq/nmi sequence before pc is set to the handler: if an irq/nmi breakpoint matches, it triggers before the
+     * first instruction of the handler.
+     *
+     * registers conditions are checked against the registers at the time the interrupt is taken.
+     */
+    pub(crate) fn check_interrupt_breakpoint<B: Bus>(&mut self, c: &Cpu<B>, ctx: InterruptContext) {
+        let idx = self.breakpoints.iter().position(|bp| {
+            bp.enabled
+                && bp.t == ctx.kind.bits()
+                && bp.handler.is_none_or(|h| h == ctx.handler)
+                && (bp.cycles == 0 || bp.cycles == ctx.cycles)
+                && bp.regs_match(c)
+        });
+        if let Some(idx) = idx {
+            self.pending_interrupt_bp = Some((idx as i8, ctx));
+        }
+    }
+
+    /**
+     * returns the pending irq/nmi breakpoint, if any, to be triggered by the run loop.
+     */
+    pub(crate) fn take_interrupt_breakpoint(&mut self) -> Option<i8> {
+        let (idx, ctx) = self.pending_interrupt_bp.take()?;
+        self.interrupt_ctx = Some(ctx);
+        Some(idx)
+    }
+
+    /**
+     * returns the context of the interrupt which triggered the last irq/nmi breakpoint, if any.
+     */
+    pub fn interrupt_context(&self) -> Option<&InterruptContext> {
+        self.interrupt_ctx.as_ref()
+    }
+
     /**
      * list set breakpoints
      */
     pub(super) fn cmd_show_breakpoints(&self) -> bool {
+        if self.json {
+            dbg_println!(self, "{}", self.json_breakpoints());
+            return true;
+        }
         let l = self.breakpoints.len();
         if l == 0 {
-            println!("no breakpoints set.");
+            dbg_println!(self, "no breakpoints set.");
             return false;
         }
 
         // walk
-        println!("listing {} breakpoints\n", l);
+        dbg_println!(self, "listing {} breakpoints\n", l);
         for (i, bp) in self.breakpoints.iter().enumerate() {
-            println!("{}... {}", i, bp);
+            match self.label_at(bp.address()) {
+                Some(l) => dbg_println!(self, "{}... {} ({})", i, bp, l),
+                None => dbg_println!(self, "{}... {}", i, bp),
+            }
         }
         return true;
     }
@@ -545,8 +1304,8 @@ impl Debugger {
     ) -> bool {
         // get breakpoint number
         let n_s = it.next().unwrap_or_default();
-        let n: i8;
-        let _ = match i8::from_str_radix(&n_s, 10) {
+        let n: BpId;
+        let _ = match BpId::from_str_radix(&n_s, 10) {
             Err(_) => {
                 self.cmd_invalid();
                 return false;
@@ -554,27 +1313,19 @@ impl Debugger {
             Ok(a) => n = a,
         };
 
-        let action: &str;
-        if self.breakpoints.len() >= (n as usize + 1) {
-            if mode.eq("be") {
-                // enable
-                self.breakpoints[n as usize].enabled = true;
-                action = "enabled";
-            } else if mode.eq("bd") {
-                // disable
-                self.breakpoints[n as usize].enabled = false;
-                action = "disabled";
-            } else {
-                // delete
-                self.breakpoints.remove(n as usize);
-                action = "deleted";
-            }
-            println!("breakpoint {} has been {}.", n, action);
+        let (res, action) = if mode.eq("be") {
+            (self.enable_breakpoint(n, true), "enabled")
+        } else if mode.eq("bd") {
+            (self.enable_breakpoint(n, false), "disabled")
         } else {
-            // invalid size
+            (self.remove_breakpoint(n), "deleted")
+        };
+        if res.is_err() {
+            // invalid index
             self.cmd_invalid();
             return false;
         }
+        dbg_println!(self, "breakpoint {} has been {}.", n, action);
         return true;
     }
 
@@ -582,19 +1333,57 @@ impl Debugger {
      * clear breakpoints list
      */
     pub(super) fn cmd_clear_breakpoints(&mut self) -> bool {
+        if self.json {
+            // no prompts in json mode
+            self.clear_breakpoints();
+            dbg_println!(self, "{{\"cleared\":true}}");
+            return true;
+        }
+
         // ask first
-        print!("delete all breakpoints ? (y/n) > ");
-        io::stdout().flush().unwrap();
+        dbg_print!(self, "delete all breakpoints ? (y/n) > ");
         let mut full_string = String::new();
         let _ = match io::stdin().lock().read_line(&mut full_string) {
             Err(_) => return false,
             Ok(_) => (),
         };
         if full_string.trim().eq_ignore_ascii_case("y") {
-            self.breakpoints.clear();
-            println!("breakpoints cleared.");
+            self.clear_breakpoints();
+            dbg_println!(self, "breakpoints cleared.");
             return true;
         }
         return false;
     }
 }
+
+/**
+ * the flag letter, as used by the 'bf' and 'v' commands.
+ */
+fn flag_name(flag: CpuFlags) -> &'static str {
+    match flag {
+        CpuFlags::N => "n",
+        CpuFlags::V => "v",
+        CpuFlags::U => "u",
+        CpuFlags::B => "b",
+        CpuFlags::D => "d",
+        CpuFlags::I => "i",
+        CpuFlags::Z => "z",
+        _ => "c",
+    }
+}
+
+/**
+ * the flag from its letter, as used by the 'bf' command.
+ */
+fn flag_from_name(name: &str) -> Option<CpuFlags> {
+    match name {
+        "n" => Some(CpuFlags::N),
+        "v" => Some(CpuFlags::V),
+        "b" => Some(CpuFlags::B),
+        "d" => Some(CpuFlags::D),
+        "i" => Some(CpuFlags::I),
+        "z" => Some(CpuFlags::Z),
+        "c" => Some(CpuFlags::C),
+        _ => None,
+    }
+}
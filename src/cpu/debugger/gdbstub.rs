@@ -0,0 +1,419 @@
+/*
+ * Filename: /src/debugger/gdbstub.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 21:52:08
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! a minimal GDB remote serial protocol (RSP) server, to drive the cpu from gdb/lldb front-ends over TCP.
+//!
+//! the registers are sent as a, x, y, s, p (8 bit each) and pc (16 bit, little endian), numbered 0-5 for 'p'/'P'.
+
+use crate::bus::Bus;
+use crate::cpu::cpu_error::CpuError;
+use crate::cpu::debugger::{BreakpointType, Debugger};
+use crate::cpu::{Cpu, CpuFlags, RunResult, StopReason};
+use std::convert::TryFrom;
+use std::io;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+
+/// instructions executed by 'c' between two polls for the interrupt request (ctrl-c).
+const CONTINUE_SLICE: usize = 1000;
+
+/**
+ * serves one gdb connection at a time, see serve().
+ */
+pub struct GdbStub {
+    listener: TcpListener,
+}
+
+/**
+ * what to do after a packet has been handled.
+ */
+enum Action {
+    Reply(String),
+    Continue,
+    Step,
+    Detach,
+}
+
+/**
+ * parse a hex number.
+ */
+fn hex(s: &str) -> Option<usize> {
+    usize::from_str_radix(s, 16).ok()
+}
+
+/**
+ * parse "addr,len", the range must fit the 64k address space.
+ */
+fn addr_len(s: &str) -> Option<(u16, usize)> {
+    let (a, l) = s.split_once(',')?;
+    let (a, l) = (hex(a)?, hex(l)?);
+    if a > 0xffff || l > 0x10000 - a {
+        return None;
+    }
+    Some((a as u16, l))
+}
+
+/**
+ * decode a string of hex digit pairs.
+ */
+fn hex_bytes(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/**
+ * encode bytes as hex digit pairs.
+ */
+fn to_hex(b: &[u8]) -> String {
+    b.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/**
+ * the breakpoint types, as in the Z0/Z1 (software/hardware breakpoint, the same here), Z2 (write), Z3 (read) and Z4
+ * (access) packets.
+ */
+fn z_type(z: &str) -> Option<BreakpointType> {
+    match z {
+        "0" | "1" => Some(BreakpointType::EXEC),
+        "2" => Some(BreakpointType::WRITE),
+        "3" => Some(BreakpointType::READ),
+        "4" => Some(BreakpointType::READ | BreakpointType::WRITE),
+        _ => None,
+    }
+}
+
+impl GdbStub {
+    /**
+     * listen on the given address (i.e. "127.0.0.1:3333", port 0 picks a free one).
+     */
+    pub fn listen<A: ToSocketAddrs>(addr: A) -> io::Result<GdbStub> {
+        Ok(GdbStub {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    /**
+     * the address the stub is listening on.
+     */
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /**
+     * wait for a gdb connection and serve it until it detaches ('D'), kills ('k') or disconnects.
+     *
+     * the cpu must have been reset already. the debugger is used with its breakpoints only (it's disabled while serving,
+     * so nothing is read from stdin): exec breakpoints are set with Z0/z0, watchpoints with Z2/Z3/Z4. 'c' runs in slices
+     * of instructions, polling the connection for the interrupt request (ctrl-c) between them.
+     */
+    pub fn serve<B: Bus>(&mut self, c: &mut Cpu<B>, d: &mut Debugger) -> io::Result<()> {
+        let (mut s, _) = self.listener.accept()?;
+        s.set_nodelay(true)?;
+        let enabled = d.enabled;
+        d.enabled = false;
+        let res = Self::serve_connection(&mut s, c, d);
+        d.enabled = enabled;
+        res
+    }
+
+    fn serve_connection<B: Bus>(
+        s: &mut TcpStream,
+        c: &mut Cpu<B>,
+        d: &mut Debugger,
+    ) -> io::Result<()> {
+        loop {
+            let packet = match Self::read_packet(s)? {
+                None => return Ok(()),
+                Some(p) => p,
+            };
+            let reply = match Self::handle_packet(&packet, c, d) {
+                Action::Reply(r) => r,
+                Action::Detach => {
+                    if packet == "D" {
+                        Self::write_packet(s, "OK")?;
+                    }
+                    return Ok(());
+                }
+                Action::Step => Self::stop_reply(c.run_until(Some(d), 0, 1), d),
+                Action::Continue => Self::run(s, c, d)?,
+            };
+            Self::write_packet(s, &reply)?;
+        }
+    }
+
+    /**
+     * run until something stops the cpu or gdb sends the interrupt request, returns the stop reply.
+     */
+    fn run<B: Bus>(s: &mut TcpStream, c: &mut Cpu<B>, d: &mut Debugger) -> io::Result<String> {
+        loop {
+            let res = c.run_until(Some(d), 0, CONTINUE_SLICE);
+            match res {
+                Ok(r) if r.reason == StopReason::InstructionsReached => (),
+                Ok(r) if matches!(r.reason, StopReason::Stalled { .. }) => (),
+                _ => return Ok(Self::stop_reply(res, d)),
+            }
+            if Self::interrupt_requested(s)? {
+                return Ok(String::from("S02"));
+            }
+        }
+    }
+
+    /**
+     * check, without blocking, if gdb sent the interrupt request (ctrl-c, a raw $03 byte).
+     */
+    fn interrupt_requested(s: &mut TcpStream) -> io::Result<bool> {
+        s.set_nonblocking(true)?;
+        let mut b = [0u8; 1];
+        let res = s.read(&mut b);
+        s.set_nonblocking(false)?;
+        match res {
+            Ok(1) => Ok(b[0] == 0x03),
+            // disconnected, the next read_packet() ends the session
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+     * the stop reply packet for a run result: SIGTRAP for steps and breakpoints (with the watched address for the
+     * watchpoints), SIGILL for STP/JAM, SIGSEGV for errors.
+     */
+    fn stop_reply(res: Result<RunResult, CpuError>, d: &Debugger) -> String {
+        let r = match res {
+            Err(_) => return String::from("S0b"),
+            Ok(r) => r,
+        };
+        match r.reason {
+            StopReason::Halted | StopReason::Jammed { .. } => String::from("S04"),
+            StopReason::Breakpoint(id) => {
+                let bp = &d.list_breakpoints()[id];
+                let t = bp.bp_type();
+                let kind = if t == BreakpointType::WRITE {
+                    "watch"
+                } else if t == BreakpointType::READ {
+                    "rwatch"
+                } else if t == BreakpointType::READ | BreakpointType::WRITE {
+                    "awatch"
+                } else {
+                    return String::from("S05");
+                };
+                format!("T05{}:{:04x};", kind, bp.address())
+            }
+            _ => String::from("S05"),
+        }
+    }
+
+    /**
+     * handle a packet, returning the reply or what to do.
+     */
+    fn handle_packet<B: Bus>(p: &str, c: &mut Cpu<B>, d: &mut Debugger) -> Action {
+        let (cmd, args) = p.split_at(p.chars().next().map_or(0, |ch| ch.len_utf8()));
+        let reply = match cmd {
+            "?" => Some(String::from("S05")),
+            "g" => Some(to_hex(&[
+                c.regs.a,
+                c.regs.x,
+                c.regs.y,
+                c.regs.s,
+                c.regs.p.bits(),
+                c.regs.pc as u8,
+                (c.regs.pc >> 8) as u8,
+            ])),
+            "G" => hex_bytes(args).filter(|b| b.len() == 7).map(|b| {
+                c.regs.a = b[0];
+                c.regs.x = b[1];
+                c.regs.y = b[2];
+                c.regs.s = b[3];
+                c.regs.p = CpuFlags::from_bits_truncate(b[4]);
+                c.regs.pc = u16::from_le_bytes([b[5], b[6]]);
+                String::from("OK")
+            }),
+            "p" => match hex(args) {
+                Some(0) => Some(to_hex(&[c.regs.a])),
+                Some(1) => Some(to_hex(&[c.regs.x])),
+                Some(2) => Some(to_hex(&[c.regs.y])),
+                Some(3) => Some(to_hex(&[c.regs.s])),
+                Some(4) => Some(to_hex(&[c.regs.p.bits()])),
+                Some(5) => Some(to_hex(&c.regs.pc.to_le_bytes())),
+                _ => None,
+            },
+            "P" => args.split_once('=').and_then(|(n, v)| {
+                let b = hex_bytes(v)?;
+                match (hex(n)?, b.len()) {
+                    (0, 1) => c.regs.a = b[0],
+                    (1, 1) => c.regs.x = b[0],
+                    (2, 1) => c.regs.y = b[0],
+                    (3, 1) => c.regs.s = b[0],
+                    (4, 1) => c.regs.p = CpuFlags::from_bits_truncate(b[0]),
+                    (5, 2) => c.regs.pc = u16::from_le_bytes([b[0], b[1]]),
+                    _ => return None,
+                }
+                Some(String::from("OK"))
+            }),
+            "m" => addr_len(args)
+                .and_then(|(a, l)| d.read_memory(c, a, l).ok())
+                .map(|b| to_hex(&b)),
+            "M" => args.split_once(':').and_then(|(al, data)| {
+                let (a, l) = addr_len(al)?;
+                let b = hex_bytes(data).filter(|b| b.len() == l)?;
+                d.write_memory(c, a, &b).ok()?;
+                Some(String::from("OK"))
+            }),
+            "c" | "s" => {
+                if let Some(a) = hex(args) {
+                    c.regs.pc = a as u16;
+                }
+                return if cmd == "c" {
+                    Action::Continue
+                } else {
+                    Action::Step
+                };
+            }
+            "Z" | "z" => {
+                let mut it = args.split(',');
+                let t = match it.next().and_then(z_type) {
+                    // unsupported type
+                    None => return Action::Reply(String::new()),
+                    Some(t) => t,
+                };
+                match (it.next().and_then(hex), it.next().and_then(hex)) {
+                    // the kind of exec breakpoints is the instruction size, they're set on the first byte only
+                    (Some(a), Some(_)) if t == BreakpointType::EXEC => {
+                        Self::set_breakpoint(c, d, cmd == "Z", a as u16, 1, t)
+                    }
+                    (Some(a), Some(len)) if len > 0 => {
+                        Self::set_breakpoint(c, d, cmd == "Z", a as u16, len, t)
+                    }
+                    _ => None,
+                }
+            }
+            "D" | "k" => return Action::Detach,
+            "q" if args.starts_with("Supported") => Some(String::from("PacketSize=1000")),
+            "q" if args == "Attached" => Some(String::from("1")),
+            // unsupported
+            _ => return Action::Reply(String::new()),
+        };
+        Action::Reply(reply.unwrap_or_else(|| String::from("E01")))
+    }
+
+    /**
+     * add (Z) or remove (z) a breakpoint of type t at address, len bytes.
+     */
+    fn set_breakpoint<B: Bus>(
+        c: &mut Cpu<B>,
+        d: &mut Debugger,
+        add: bool,
+        address: u16,
+        len: usize,
+        t: BreakpointType,
+    ) -> Option<String> {
+        let end = address.checked_add(u16::try_from(len - 1).ok()?)?;
+        if add {
+            d.add_breakpoint_range(c, address, end, t, None).ok()?;
+        } else {
+            let id = d.list_breakpoints().iter().position(|bp| {
+                bp.address() == address && bp.end_address() == end && bp.bp_type() == t
+            })?;
+            d.remove_breakpoint(id).ok()?;
+        }
+        Some(String::from("OK"))
+    }
+
+    /**
+     * read a packet, acknowledging it. returns None when the connection is closed.
+     */
+    fn read_packet(s: &mut TcpStream) -> io::Result<Option<String>> {
+        loop {
+            // skip acks and stray bytes up to the start of the packet
+            let mut b = [0u8; 1];
+            loop {
+                if s.read(&mut b)? == 0 {
+                    return Ok(None);
+                }
+                if b[0] == b'$' {
+                    break;
+                }
+            }
+            let mut data = Vec::new();
+            loop {
+                if s.read(&mut b)? == 0 {
+                    return Ok(None);
+                }
+                if b[0] == b'#' {
+                    break;
+                }
+                data.push(b[0]);
+            }
+            let mut cs = [0u8; 2];
+            s.read_exact(&mut cs)?;
+            let sum = data.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+            let ok = std::str::from_utf8(&cs)
+                .ok()
+                .and_then(|cs| u8::from_str_radix(cs, 16).ok())
+                == Some(sum);
+            if !ok {
+                // ask for retransmission
+                s.write_all(b"-")?;
+                continue;
+            }
+            s.write_all(b"+")?;
+            return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    /**
+     * send a packet, retransmitting until gdb acknowledges it.
+     */
+    fn write_packet(s: &mut TcpStream, data: &str) -> io::Result<()> {
+        let sum = data.bytes().fold(0u8, |acc, b| acc.wrapping_add(b));
+        let packet = format!("${}#{:02x}", data, sum);
+        loop {
+            s.write_all(packet.as_bytes())?;
+            let mut b = [0u8; 1];
+            loop {
+                if s.read(&mut b)? == 0 {
+                    return Ok(());
+                }
+                if b[0] == b'+' || b[0] == b'-' {
+                    break;
+                }
+            }
+            if b[0] == b'+' {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ * Filename: /src/cpu/debugger/hexdump.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:21:48
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::cpu::debugger::Debugger;
+use crate::cpu::Cpu;
+
+/**
+ * the text column shown after the hex bytes.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexdumpText {
+    /// no text column.
+    None,
+    /// printable ascii, '.' otherwise.
+    Ascii,
+    /// C64 petscii (unshifted charset), '.' for the graphic characters.
+    Petscii,
+}
+
+/**
+ * options for Debugger::hexdump_with_options().
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexdumpOptions {
+    /// bytes per row.
+    pub width: usize,
+    /// uppercase hex digits.
+    pub uppercase: bool,
+    /// the text column.
+    pub text: HexdumpText,
+}
+
+impl Default for HexdumpOptions {
+    fn default() -> Self {
+        HexdumpOptions {
+            width: 16,
+            uppercase: false,
+            text: HexdumpText::Ascii,
+        }
+    }
+}
+
+/**
+ * translate a byte for the text column.
+ */
+fn to_char(b: u8, text: HexdumpText) -> char {
+    match text {
+        HexdumpText::Petscii => match b {
+            0x5c => '£',
+            0x5e => '↑',
+            0x5f => '←',
+            0x20..=0x5f => b as char,
+            // shifted letters show as uppercase too in the unshifted charset
+            0xc1..=0xda => (b - 0x80) as char,
+            _ => '.',
+        },
+        _ => match b {
+            0x20..=0x7e => b as char,
+            _ => '.',
+        },
+    }
+}
+
+/**
+ * format data as a hexdump, with addresses starting at address.
+ */
+fn format(data: &[u8], address: u16, opts: &HexdumpOptions) -> String {
+    let mut s = String::new();
+    for (i, row) in data.chunks(opts.width).enumerate() {
+        let a = address as usize + i * opts.width;
+        let hex: Vec<String> = row
+            .iter()
+            .map(|b| {
+                if opts.uppercase {
+                    format!("{:02X}", b)
+                } else {
+                    format!("{:02x}", b)
+                }
+            })
+            .collect();
+        let addr = if opts.uppercase {
+            format!("${:04X}", a)
+        } else {
+            format!("${:04x}", a)
+        };
+        if opts.text == HexdumpText::None {
+            s.push_str(&format!("{}  {}\n", addr, hex.join(" ")));
+            continue;
+        }
+
+        // pad the last row, to keep the text column aligned
+        let text: String = row.iter().map(|b| to_char(*b, opts.text)).collect();
+        s.push_str(&format!(
+            "{}  {:<w$}  |{}|\n",
+            addr,
+            hex.join(" "),
+            text,
+            w = opts.width * 3 - 1
+        ));
+    }
+    s
+}
+
+impl Debugger {
+    /**
+     * returns the hexdump of len bytes at address, width bytes per row with an ascii column (as the 'x' command).
+     *
+     * memory is read without side effects on mapped devices.
+     *
+     * ```
+     * use rv6502emu::cpu::Cpu;
+     * use rv6502emu::cpu::debugger::Debugger;
+     *
+     * let mut c = Cpu::new_default(None);
+     * let d = Debugger::new(false);
+     * d.write_memory(&mut c, 0x400, b"hi!\x00").unwrap();
+     * assert_eq!(
+     *     d.hexdump(&mut c, 0x400, 4, 8).unwrap(),
+     *     "$0400  68 69 21 00              |hi!.|\n"
+     * );
+     * ```
+     */
+    pub fn hexdump<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        len: usize,
+        width: usize,
+    ) -> Result<String, CpuError> {
+        let opts = HexdumpOptions {
+            width,
+            ..Default::default()
+        };
+        self.hexdump_with_options(c, address, len, &opts)
+    }
+
+    /**
+     * returns the hexdump of len bytes at address, formatted as per opts.
+     */
+    pub fn hexdump_with_options<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        address: u16,
+        len: usize,
+        opts: &HexdumpOptions,
+    ) -> Result<String, CpuError> {
+        if opts.width == 0 {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                address,
+                Some(String::from("invalid hexdump width!")),
+            ));
+        }
+        let data = self.read_memory(c, address, len)?;
+        Ok(format(&data, address, opts))
+    }
+}
@@ -0,0 +1,158 @@
+/*
+ * Filename: /src/debugger/history.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 18:42:10
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::cpu_error::CpuError;
+use crate::cpu::debugger::Debugger;
+use crate::cpu::{Cpu, Registers};
+use std::collections::VecDeque;
+
+/**
+ * cpu state before an instruction (or an interrupt sequence) executed.
+ */
+struct HistoryEntry {
+    regs: Registers,
+    cycles: usize,
+    /// (address, old value) of the bytes written, in order.
+    writes: Vec<(u16, u8)>,
+}
+
+/**
+ * bounded history of the last executed steps, to step back.
+ */
+pub(crate) struct History {
+    depth: usize,
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl<B: Bus> Cpu<B> {
+    /**
+     * record the byte at address before it's overwritten, if the debugger history is enabled.
+     */
+    pub(crate) fn journal_write(&mut self, address: u16) {
+        if let Some(j) = self.journal.as_mut() {
+            j.push((address, self.bus.peek(address).unwrap_or_default()));
+        }
+    }
+}
+
+impl Debugger {
+    /**
+     * record the last depth instructions (and interrupt entries) executed, to step back with step_back().
+     *
+     * each step stores registers, cycle counter and the old value of the bytes written to memory (through the cpu
+     * stores and pushes).
+     *
+     * > best-effort: side effects on memory mapped devices, the 6510 port, coverage/profiler and interrupt lines are not undone.
+     *
+     * depth 0 disables the history.
+     */
+    pub fn enable_history(&mut self, depth: usize) {
+        self.history = if depth == 0 {
+            None
+        } else {
+            Some(History {
+                depth,
+                entries: VecDeque::with_capacity(depth),
+            })
+        };
+    }
+
+    /**
+     * returns the number of steps which can be undone.
+     */
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, |h| h.entries.len())
+    }
+
+    /**
+     * undo the last n steps (instructions or interrupt entries), restoring registers, cycles and memory.
+     *
+     * returns the number of steps undone, which may be less than n if the history is shorter.
+     */
+    pub fn step_back<B: Bus>(&mut self, c: &mut Cpu<B>, n: usize) -> Result<usize, CpuError> {
+        let h = match self.history.as_mut() {
+            None => return Ok(0),
+            Some(h) => h,
+        };
+        let mut done = 0;
+        while done < n {
+            let e = match h.entries.pop_back() {
+                None => break,
+                Some(e) => e,
+            };
+
+            // restore memory in reverse order, the first write to an address holds the oldest value
+            for (address, b) in e.writes.iter().rev() {
+                c.bus.poke(*address, *b)?;
+            }
+            c.regs = e.regs;
+            c.cycles = e.cycles;
+
+            // only executed steps are recorded, the cpu couldn't be stuck before
+            c.halted = false;
+            c.jammed = false;
+            done += 1;
+        }
+        Ok(done)
+    }
+
+    /**
+     * record the cpu state and start journaling memory writes, called right before a step executes.
+     */
+    pub(crate) fn history_begin<B: Bus>(&mut self, c: &mut Cpu<B>) {
+        let h = match self.history.as_mut() {
+            None => return,
+            Some(h) => h,
+        };
+        if h.entries.len() == h.depth {
+            h.entries.pop_front();
+        }
+        h.entries.push_back(HistoryEntry {
+            regs: c.regs,
+            cycles: c.cycles,
+            writes: Vec::new(),
+        });
+        c.journal = Some(Vec::new());
+    }
+
+    /**
+     * stop journaling memory writes, called right after a step executed.
+     */
+    pub(crate) fn history_end<B: Bus>(&mut self, c: &mut Cpu<B>) {
+        let j = match c.journal.take() {
+            None => return,
+            Some(j) => j,
+        };
+        if let Some(e) = self.history.as_mut().and_then(|h| h.entries.back_mut()) {
+            e.writes = j;
+        }
+    }
+}
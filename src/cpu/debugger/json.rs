@@ -0,0 +1,133 @@
+/*
+ * Filename: /src/debugger/json.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 21:14:37
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::debugger::Debugger;
+use crate::cpu::disasm::Instruction;
+use crate::cpu::Cpu;
+use std::fmt::Write;
+
+/**
+ * quote and escape s as a json string.
+ */
+pub(super) fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/**
+ * bytes as a json array of numbers.
+ */
+fn bytes(b: &[u8]) -> String {
+    let v: Vec<String> = b.iter().map(|b| b.to_string()).collect();
+    format!("[{}]", v.join(","))
+}
+
+impl Debugger {
+    /**
+     * the registers as a json object, for the 'r' command.
+     */
+    pub(super) fn json_registers<B: Bus>(&self, c: &Cpu<B>) -> String {
+        format!(
+            "{{\"pc\":{},\"a\":{},\"x\":{},\"y\":{},\"s\":{},\"p\":{},\"flags\":{},\"cycles\":{}}}",
+            c.regs.pc,
+            c.regs.a,
+            c.regs.x,
+            c.regs.y,
+            c.regs.s,
+            c.regs.p.bits(),
+            string(&c.regs.flags_to_string()),
+            c.cycles
+        )
+    }
+
+    /**
+     * the breakpoints as a json array, for the 'bl' command.
+     */
+    pub(super) fn json_breakpoints(&self) -> String {
+        let v: Vec<String> = self
+            .breakpoints
+            .iter()
+            .enumerate()
+            .map(|(i, bp)| {
+                format!(
+                    "{{\"id\":{},\"address\":{},\"end\":{},\"type\":{},\"enabled\":{},\"hits\":{},\"text\":{}}}",
+                    i,
+                    bp.address(),
+                    bp.end_address(),
+                    string(&bp.flags_to_string()),
+                    bp.is_enabled(),
+                    bp.hits(),
+                    string(&bp.to_string())
+                )
+            })
+            .collect();
+        format!("[{}]", v.join(","))
+    }
+
+    /**
+     * the disassembled instructions as a json array, for the 'd' command.
+     */
+    pub(super) fn json_disassembly(&self, v: &[Instruction]) -> String {
+        let v: Vec<String> = v
+            .iter()
+            .map(|i| {
+                format!(
+                    "{{\"addr\":{},\"bytes\":{},\"text\":{}}}",
+                    i.address,
+                    bytes(&i.bytes),
+                    string(&self.format_instruction(i))
+                )
+            })
+            .collect();
+        format!("[{}]", v.join(","))
+    }
+
+    /**
+     * a memory dump as a json object, for the 'x' command.
+     */
+    pub(super) fn json_memory(&self, address: u16, data: &[u8]) -> String {
+        format!("{{\"address\":{},\"bytes\":{}}}", address, bytes(data))
+    }
+}
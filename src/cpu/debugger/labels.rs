@@ -0,0 +1,208 @@
+/*
+ * Filename: /src/cpu/debugger/labels.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 15:02:37
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::cpu::addressing_modes::AddressingModeId;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::cpu::debugger::Debugger;
+use crate::cpu::disasm::Instruction;
+use crate::utils::*;
+use std::collections::HashMap;
+use std::fs;
+
+/**
+ * symbols loaded from label files.
+ */
+#[derive(Default)]
+pub(crate) struct Labels {
+    /// address -> name, the first name loaded for an address wins.
+    by_addr: HashMap<u16, String>,
+    /// name -> addresses, more than one means the name is ambiguous.
+    by_name: HashMap<String, Vec<u16>>,
+}
+
+impl Labels {
+    fn add(&mut self, address: u16, name: &str) {
+        self.by_addr
+            .entry(address)
+            .or_insert_with(|| String::from(name));
+        let v = self.by_name.entry(String::from(name)).or_default();
+        if !v.contains(&address) {
+            v.push(address);
+        }
+    }
+}
+
+/**
+ * builds a label file parsing error.
+ */
+fn label_error(path: &str, line: usize, msg: &str) -> CpuError {
+    CpuError::new_default(
+        CpuErrorType::Generic,
+        0,
+        Some(format!("{}:{}: {}", path, line, msg)),
+    )
+}
+
+impl Debugger {
+    /**
+     * load a VICE label file (as emitted by ld65 -Ln), i.e. 'al C:1234 .loop' lines.
+     *
+     * labels are added to the ones already loaded, lines with other VICE commands are skipped.
+     * returns the number of labels loaded.
+     */
+    pub fn load_labels(&mut self, path: &str) -> Result<usize, CpuError> {
+        let s = fs::read_to_string(path)?;
+        let mut parsed: Vec<(u16, &str)> = Vec::new();
+        for (i, l) in s.lines().enumerate() {
+            let mut it = l.split_whitespace();
+            if it.next() != Some("al") {
+                continue;
+            }
+            let (addr_s, name) = match (it.next(), it.next()) {
+                (Some(a), Some(n)) => (a, n),
+                _ => return Err(label_error(path, i + 1, "malformed label")),
+            };
+
+            // address may be prefixed by the memspace (C:), names by a dot
+            let addr_s = addr_s.strip_prefix("C:").unwrap_or(addr_s);
+            let addr = match u32::from_str_radix(addr_s, 16) {
+                Ok(a) if a <= 0xffff => a as u16,
+                _ => {
+                    return Err(label_error(
+                        path,
+                        i + 1,
+                        &format!("invalid address '{}'", addr_s),
+                    ))
+                }
+            };
+            let name = name.strip_prefix('.').unwrap_or(name);
+            if name.is_empty() {
+                return Err(label_error(path, i + 1, "empty label name"));
+            }
+            parsed.push((addr, name));
+        }
+
+        // add all only if the whole file is valid
+        for (addr, name) in parsed.iter() {
+            self.labels.add(*addr, name);
+        }
+        Ok(parsed.len())
+    }
+
+    /**
+     * remove all the loaded labels.
+     */
+    pub fn clear_labels(&mut self) {
+        self.labels = Labels::default();
+    }
+
+    /**
+     * get the label at address, if any.
+     */
+    pub fn label_at(&self, address: u16) -> Option<&str> {
+        self.labels.by_addr.get(&address).map(|s| s.as_str())
+    }
+
+    /**
+     * get the address of the given label.
+     */
+    pub fn resolve_label(&self, name: &str) -> Result<u16, CpuError> {
+        match self.labels.by_name.get(name).map(|v| v.as_slice()) {
+            Some([a]) => Ok(*a),
+            Some(v) => {
+                let addrs: Vec<String> = v.iter().map(|a| format!("${:04x}", a)).collect();
+                Err(CpuError::new_default(
+                    CpuErrorType::Generic,
+                    0,
+                    Some(format!(
+                        "ambiguous symbol '{}', matches {}!",
+                        name,
+                        addrs.join(", ")
+                    )),
+                ))
+            }
+            None => Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(format!("unknown symbol '{}'!", name)),
+            )),
+        }
+    }
+
+    /**
+     * parse an address from a debugger command, either hex ($ prefix optional) or a label name.
+     *
+     * a loaded label takes precedence over an unprefixed hex value (i.e. 'add'), use '$add' to force hex.
+     */
+    pub(crate) fn parse_address(&self, s: &str) -> Result<u16, CpuError> {
+        let d = is_dollar_hex(s);
+        if d == 0 && self.labels.by_name.contains_key(s) {
+            return self.resolve_label(s);
+        }
+        match u16::from_str_radix(&s[d..], 16) {
+            Ok(a) => Ok(a),
+            Err(_) if d == 0 && !s.is_empty() => self.resolve_label(s),
+            Err(_) => Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(format!("invalid address '{}'!", s)),
+            )),
+        }
+    }
+
+    /**
+     * format a disassembled instruction, replacing the operand with the label at the address it references, if any.
+     */
+    pub fn format_instruction(&self, i: &Instruction) -> String {
+        let text = i.to_string();
+        if self.labels.by_addr.is_empty() {
+            return text;
+        }
+        let (token, tgt) = match (i.mode, i.operand) {
+            (AddressingModeId::Imm, _) | (AddressingModeId::Zpr, _) | (_, None) => return text,
+            (AddressingModeId::Rel, Some(o)) => (format!("${:02x}", o), i.target.unwrap()),
+            (_, Some(o)) if i.bytes.len() == 3 => (format!("${:04x}", o), o),
+            (_, Some(o)) => (format!("${:02x}", o), o),
+        };
+        let label = match self.label_at(tgt) {
+            Some(l) => l,
+            None => return text,
+        };
+
+        // operands follow the '-->' marker
+        match text.find("-->\t") {
+            Some(start) => {
+                let (head, tail) = text.split_at(start);
+                format!("{}{}", head, tail.replacen(&token, label, 1))
+            }
+            None => text,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+/*
+ * Filename: /src/debugger/script.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 21:05:37
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::cpu::debugger::Debugger;
+use crate::cpu::Cpu;
+use std::fs;
+use std::str::SplitWhitespace;
+
+/**
+ * builds a script error.
+ */
+fn script_error(path: &str, line: usize, msg: &str) -> CpuError {
+    CpuError::new_default(
+        CpuErrorType::Generic,
+        0,
+        Some(format!("{}:{}: {}", path, line, msg)),
+    )
+}
+
+/**
+ * split the commands attached to a breakpoint command, i.e. 'bx $1234 do "r; x 16 $0200; g"'.
+ *
+ * returns the breakpoint command without the attached part and the commands, or None if the quoted list is malformed.
+ */
+pub(super) fn split_attached_cmds(cmd_string: &str) -> Option<(&str, Vec<String>)> {
+    let mut start = 0;
+    for (i, tok) in cmd_string.split(' ').enumerate() {
+        if tok == "do" && i > 0 {
+            let list = cmd_string[start + tok.len()..].trim();
+            let inner = list.strip_prefix('"')?.strip_suffix('"')?;
+            let cmds: Vec<String> = inner
+                .split(';')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect();
+            if cmds.is_empty() {
+                return None;
+            }
+            return Some((&cmd_string[..start], cmds));
+        }
+        start += tok.len() + 1;
+    }
+    Some((cmd_string, Vec::new()))
+}
+
+impl Debugger {
+    /**
+     * execute the debugger commands in the file at path, one per line, as with parse_cmd().
+     *
+     * empty lines and lines starting with '#' are skipped, 'q' stops the script.
+     *
+     * a command resuming execution (i.e. 'g', 'p', 'u') ends the script too: the remaining lines are queued and executed
+     * at the next stop, as with queue_cmd().
+     *
+     * returns the last command as parse_cmd() does ('q' if the script quit, 'p' if it resumed execution, '*' otherwise),
+     * or an error reporting the line of the first failing command.
+     */
+    pub fn run_script<B: Bus>(&mut self, c: &mut Cpu<B>, path: &str) -> Result<String, CpuError> {
+        let s = fs::read_to_string(path)?;
+        let lines: Vec<&str> = s.lines().collect();
+        for (i, l) in lines.iter().enumerate() {
+            let l = l.trim();
+            if l.is_empty() || l.starts_with('#') {
+                continue;
+            }
+            let (cmd, ok) = self.parse_cmd(c, l);
+            if !ok {
+                return Err(script_error(
+                    path,
+                    i + 1,
+                    &format!("command '{}' failed!", l),
+                ));
+            }
+            match cmd.as_str() {
+                "q" => return Ok(cmd),
+                "p" => {
+                    for l in lines[i + 1..].iter() {
+                        let l = l.trim();
+                        if !l.is_empty() && !l.starts_with('#') {
+                            self.queue_cmd(l);
+                        }
+                    }
+                    return Ok(cmd);
+                }
+                _ => (),
+            }
+        }
+        Ok(String::from("*"))
+    }
+
+    /**
+     * run a script from the 'script' command.
+     */
+    pub(super) fn cmd_script<B: Bus>(
+        &mut self,
+        c: &mut Cpu<B>,
+        mut it: SplitWhitespace<'_>,
+    ) -> (String, bool) {
+        let path = it.next().unwrap_or_default();
+        if path.is_empty() {
+            self.cmd_invalid();
+            return (String::from("*"), false);
+        }
+        match self.run_script(c, path) {
+            Err(e) => {
+                self.cmd_error(&e);
+                (String::from("*"), false)
+            }
+            Ok(cmd) => (cmd, true),
+        }
+    }
+}
@@ -0,0 +1,448 @@
+/*
+ * Filename: /src/debugger/trace.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 14:05:33
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::addressing_modes::AddressingModeId;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::cpu::debugger::Debugger;
+use crate::cpu::disasm::Instruction;
+use crate::cpu::Cpu;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+/**
+ * instruction trace formats.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceFormat {
+    /// nestest.log layout (without the PPU column), i.e. "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7".
+    Nestest,
+    /// the debugger disassembly followed by registers and cycles.
+    Simple,
+    /// comma separated registers in hex and decimal cycles, after a "pc,a,x,y,p,s,cycles" header, i.e. "c000,00,00,00,24,fd,7".
+    Csv,
+}
+
+/**
+ * how many matching lines before a divergence are kept by Debugger::compare_trace().
+ */
+pub const TRACE_CONTEXT: usize = 8;
+
+/**
+ * the first divergence found by Debugger::compare_trace().
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceDivergence {
+    /// the reference line number (1 based).
+    pub line: usize,
+    /// the differing fields, among "PC", "A", "X", "Y", "P", "S" and "CYC".
+    pub fields: Vec<&'static str>,
+    /// the reference line.
+    pub expected: String,
+    /// the emulator state, formatted as the reference.
+    pub actual: String,
+    /// up to TRACE_CONTEXT (reference, emulator) lines matching before the divergence, oldest first.
+    pub context: Vec<(String, String)>,
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .context
+            .iter()
+            .map(|(e, _)| e.len())
+            .chain(std::iter::once(self.expected.len()))
+            .max()
+            .unwrap_or_default();
+        writeln!(
+            f,
+            "trace diverges at line {} ({}):",
+            self.line,
+            self.fields.join(", ")
+        )?;
+        for (e, a) in self.context.iter() {
+            writeln!(f, "  {:<w$} | {}", e, a, w = width)?;
+        }
+        write!(f, "> {:<w$} | {}", self.expected, self.actual, w = width)
+    }
+}
+
+/**
+ * the registers and cycles of a reference trace line.
+ */
+struct TraceState {
+    pc: u16,
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+    s: u8,
+    cycles: Option<usize>,
+}
+
+/**
+ * parse a reference trace line, None for the csv header.
+ */
+fn parse_trace_line(line: &str, format: TraceFormat) -> Option<TraceState> {
+    let hex8 = |s: &str| u8::from_str_radix(s, 16).ok();
+    match format {
+        TraceFormat::Nestest => {
+            // pc first, then the registers after the disassembly (PPU, if any, is skipped)
+            let pc = u16::from_str_radix(line.get(0..4)?, 16).ok()?;
+            let regs = &line[line.rfind(" A:")?..];
+            let mut st = TraceState {
+                pc,
+                a: 0,
+                x: 0,
+                y: 0,
+                p: 0,
+                s: 0,
+                cycles: None,
+            };
+            for item in regs.split_whitespace() {
+                if let Some(v) = item.strip_prefix("A:") {
+                    st.a = hex8(v)?;
+                } else if let Some(v) = item.strip_prefix("X:") {
+                    st.x = hex8(v)?;
+                } else if let Some(v) = item.strip_prefix("Y:") {
+                    st.y = hex8(v)?;
+                } else if let Some(v) = item.strip_prefix("P:") {
+                    st.p = hex8(v)?;
+                } else if let Some(v) = item.strip_prefix("SP:") {
+                    st.s = hex8(v)?;
+                } else if let Some(v) = item.strip_prefix("CYC:") {
+                    st.cycles = Some(v.parse().ok()?);
+                }
+            }
+            Some(st)
+        }
+        TraceFormat::Csv | TraceFormat::Simple => {
+            let col: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+            if col.len() < 6 {
+                return None;
+            }
+            Some(TraceState {
+                pc: u16::from_str_radix(col[0], 16).ok()?,
+                a: hex8(col[1])?,
+                x: hex8(col[2])?,
+                y: hex8(col[3])?,
+                p: hex8(col[4])?,
+                s: hex8(col[5])?,
+                cycles: match col.get(6) {
+                    Some(v) => Some(v.parse().ok()?),
+                    None => None,
+                },
+            })
+        }
+    }
+}
+
+/**
+ * an active trace.
+ */
+pub(crate) struct Trace {
+    w: BufWriter<File>,
+    format: TraceFormat,
+}
+
+/**
+ * read a byte from ram, without side effects on mapped devices.
+ */
+fn peek<B: Bus>(c: &mut Cpu<B>, address: u16) -> u8 {
+    c.bus.peek(address).unwrap_or_default()
+}
+
+/**
+ * read a word from ram, with the msb read from address_msb.
+ */
+fn peek_word<B: Bus>(c: &mut Cpu<B>, address: u16, address_msb: u16) -> u16 {
+    (peek(c, address) as u16) | ((peek(c, address_msb) as u16) << 8)
+}
+
+/**
+ * instruction operand in nestest syntax, including the effective address and the value read.
+ */
+fn nestest_operand<B: Bus>(c: &mut Cpu<B>, ins: &Instruction) -> String {
+    let b1 = *ins.bytes.get(1).unwrap_or(&0);
+    let w = ((*ins.bytes.get(2).unwrap_or(&0) as u16) << 8) | b1 as u16;
+    let is_jump = ins.mnemonic == "jmp" || ins.mnemonic == "jsr";
+    match ins.mode {
+        AddressingModeId::Imp => String::new(),
+        AddressingModeId::Acc => String::from("A"),
+        AddressingModeId::Imm => format!("#${:02X}", b1),
+        AddressingModeId::Zpg => format!("${:02X} = {:02X}", b1, peek(c, b1 as u16)),
+        AddressingModeId::Zpx | AddressingModeId::Zpy => {
+            let (r, i) = if ins.mode == AddressingModeId::Zpx {
+                ("X", c.regs.x)
+            } else {
+                ("Y", c.regs.y)
+            };
+            let ea = b1.wrapping_add(i);
+            format!(
+                "${:02X},{} @ {:02X} = {:02X}",
+                b1,
+                r,
+                ea,
+                peek(c, ea as u16)
+            )
+        }
+        AddressingModeId::Abs => {
+            if is_jump {
+                format!("${:04X}", w)
+            } else {
+                format!("${:04X} = {:02X}", w, peek(c, w))
+            }
+        }
+        AddressingModeId::Abx | AddressingModeId::Aby => {
+            let (r, i) = if ins.mode == AddressingModeId::Abx {
+                ("X", c.regs.x)
+            } else {
+                ("Y", c.regs.y)
+            };
+            let ea = w.wrapping_add(i as u16);
+            format!("${:04X},{} @ {:04X} = {:02X}", w, r, ea, peek(c, ea))
+        }
+        AddressingModeId::Ind => {
+            // the nmos jmp indirect bug, msb is fetched without crossing the page
+            let msb = if c.is_65c02() {
+                w.wrapping_add(1)
+            } else {
+                (w & 0xff00) | (w.wrapping_add(1) & 0xff)
+            };
+            format!("(${:04X}) = {:04X}", w, peek_word(c, w, msb))
+        }
+        AddressingModeId::Xin => {
+            let p = b1.wrapping_add(c.regs.x);
+            let ea = peek_word(c, p as u16, p.wrapping_add(1) as u16);
+            format!(
+                "(${:02X},X) @ {:02X} = {:04X} = {:02X}",
+                b1,
+                p,
+                ea,
+                peek(c, ea)
+            )
+        }
+        AddressingModeId::Iny => {
+            let base = peek_word(c, b1 as u16, b1.wrapping_add(1) as u16);
+            let ea = base.wrapping_add(c.regs.y as u16);
+            format!(
+                "(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
+                b1,
+                base,
+                ea,
+                peek(c, ea)
+            )
+        }
+        AddressingModeId::Izp => {
+            let ea = peek_word(c, b1 as u16, b1.wrapping_add(1) as u16);
+            format!("(${:02X}) = {:04X} = {:02X}", b1, ea, peek(c, ea))
+        }
+        AddressingModeId::Aix => {
+            let p = w.wrapping_add(c.regs.x as u16);
+            format!(
+                "(${:04X},X) @ {:04X} = {:04X}",
+                w,
+                p,
+                peek_word(c, p, p.wrapping_add(1))
+            )
+        }
+        AddressingModeId::Rel => format!("${:04X}", ins.target.unwrap_or_default()),
+        AddressingModeId::Zpr => format!("${:02X},${:04X}", b1, ins.target.unwrap_or_default()),
+    }
+}
+
+impl Debugger {
+    /**
+     * start tracing each executed instruction to the file at path (overwritten), in the given format.
+     */
+    pub fn start_trace(&mut self, path: &str, format: TraceFormat) -> Result<(), CpuError> {
+        let mut w = BufWriter::new(File::create(path)?);
+        if format == TraceFormat::Csv {
+            writeln!(w, "pc,a,x,y,p,s,cycles")?;
+        }
+        self.trace = Some(Trace { w, format });
+        Ok(())
+    }
+
+    /**
+     * stop tracing, flushing the trace file.
+     */
+    pub fn stop_trace(&mut self) -> Result<(), CpuError> {
+        if let Some(mut t) = self.trace.take() {
+            t.w.flush()?;
+        }
+        Ok(())
+    }
+
+    /**
+     * write the instruction at pc to the trace, if active. called right before the instruction executes.
+     */
+    pub(crate) fn trace_instruction<B: Bus>(&mut self, c: &mut Cpu<B>) -> Result<(), CpuError> {
+        let format = match self.trace.as_ref() {
+            Some(t) => t.format,
+            None => return Ok(()),
+        };
+        let line = match self.trace_line(c, format)? {
+            Some(l) => l,
+            None => return Ok(()),
+        };
+        let t = self.trace.as_mut().unwrap();
+        writeln!(t.w, "{}", line)?;
+        Ok(())
+    }
+
+    /**
+     * the trace line for the instruction at pc, in the given format.
+     */
+    fn trace_line<B: Bus>(
+        &self,
+        c: &mut Cpu<B>,
+        format: TraceFormat,
+    ) -> Result<Option<String>, CpuError> {
+        if format == TraceFormat::Csv {
+            return Ok(Some(format!(
+                "{:04x},{:02x},{:02x},{:02x},{:02x},{:02x},{}",
+                c.regs.pc,
+                c.regs.a,
+                c.regs.x,
+                c.regs.y,
+                c.regs.p.bits(),
+                c.regs.s,
+                c.cycles
+            )));
+        }
+        let ins = match self.disassemble(c, c.regs.pc, 1)?.pop() {
+            Some(i) => i,
+            None => return Ok(None),
+        };
+        let line = match format {
+            TraceFormat::Nestest => {
+                let bytes: Vec<String> = ins.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+                let asm = format!(
+                    "{} {}",
+                    ins.mnemonic.to_uppercase(),
+                    nestest_operand(c, &ins)
+                );
+                format!(
+                    "{:04X}  {:<8} {}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+                    ins.address,
+                    bytes.join(" "),
+                    if ins.is_undocumented { '*' } else { ' ' },
+                    asm.trim_end(),
+                    c.regs.a,
+                    c.regs.x,
+                    c.regs.y,
+                    c.regs.p.bits(),
+                    c.regs.s,
+                    c.cycles
+                )
+            }
+            _ => format!("{}\t{}, cycles={}", ins, c.regs, c.cycles),
+        };
+        Ok(Some(line))
+    }
+
+    /**
+     * run the cpu from its current state one instruction at a time (without debugger), comparing pc, registers and
+     * cycles before each instruction with the reference trace at path (i.e. from VICE or another emulator), in the
+     * Nestest or Csv format (the cycles are compared only if the reference has them).
+     *
+     * returns the first divergence, with the cpu stopped right before the diverging instruction, or None if the whole
+     * trace matches.
+     */
+    pub fn compare_trace<B: Bus>(
+        &mut self,
+        c: &mut Cpu<B>,
+        path: &str,
+        format: TraceFormat,
+    ) -> Result<Option<TraceDivergence>, CpuError> {
+        if format == TraceFormat::Simple {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                c.regs.pc,
+                Some(String::from("only nestest and csv traces can be compared")),
+            ));
+        }
+        let reference = fs::read_to_string(path)?;
+        let mut context: VecDeque<(String, String)> = VecDeque::new();
+        for (i, line) in reference.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() || (format == TraceFormat::Csv && line.starts_with("pc")) {
+                continue;
+            }
+            let expected = match parse_trace_line(line, format) {
+                Some(st) => st,
+                None => {
+                    return Err(CpuError::new_default(
+                        CpuErrorType::Generic,
+                        c.regs.pc,
+                        Some(format!("{}: invalid trace line {}", path, i + 1)),
+                    ))
+                }
+            };
+
+            // compare before the instruction executes, as the trace lines are written
+            let mut fields: Vec<&'static str> = Vec::new();
+            for (name, differs) in [
+                ("PC", expected.pc != c.regs.pc),
+                ("A", expected.a != c.regs.a),
+                ("X", expected.x != c.regs.x),
+                ("Y", expected.y != c.regs.y),
+                ("P", expected.p != c.regs.p.bits()),
+                ("S", expected.s != c.regs.s),
+                ("CYC", expected.cycles.map_or(false, |n| n != c.cycles)),
+            ] {
+                if differs {
+                    fields.push(name);
+                }
+            }
+            let actual = self.trace_line(c, format)?.unwrap_or_default();
+            if !fields.is_empty() {
+                return Ok(Some(TraceDivergence {
+                    line: i + 1,
+                    fields,
+                    expected: String::from(line),
+                    actual,
+                    context: context.into_iter().collect(),
+                }));
+            }
+            if context.len() == TRACE_CONTEXT {
+                context.pop_front();
+            }
+            context.push_back((String::from(line), actual));
+
+            // step, if the cpu halts or jams the next line diverges
+            c.run_until(None, 0, 1)?;
+        }
+        Ok(None)
+    }
+}
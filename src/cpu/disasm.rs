@@ -0,0 +1,280 @@
+/*
+ * Filename: /src/cpu/disasm.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 15:40:12
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! structured disassembler, decodes a block of bytes without touching the cpu.
+
+use crate::cpu::addressing_modes::{get_relative_branch_target, AddressingModeId};
+use crate::cpu::opcodes::opcode_info;
+use crate::cpu::CpuType;
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt::{Display, Error, Formatter};
+
+/**
+ * a disassembled instruction.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    /// instruction address.
+    pub address: u16,
+    /// opcode and operand bytes.
+    pub bytes: Vec<u8>,
+    /// lowercase mnemonic.
+    pub mnemonic: &'static str,
+    /// addressing mode.
+    pub mode: AddressingModeId,
+    /// the operand as encoded (byte or little endian word), None for implied/accumulator.
+    ///
+    /// for Zpr this is the zeropage address, the branch offset is in bytes[2].
+    pub operand: Option<u16>,
+    /// branch target, for the relative modes (Rel, Zpr).
+    pub target: Option<u16>,
+    /// undocumented (illegal) opcode.
+    pub is_undocumented: bool,
+}
+
+/**
+ * an instruction decoded in place by Cpu::decode_at(), with the effective address for the current registers.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedInstruction {
+    /// instruction address.
+    pub address: u16,
+    /// the opcode byte.
+    pub opcode: u8,
+    /// lowercase mnemonic.
+    pub mnemonic: &'static str,
+    /// addressing mode.
+    pub mode: AddressingModeId,
+    /// instruction size, including the opcode byte.
+    pub size: u8,
+    /// base cycles, without the page crossing (or taken branch) penalty.
+    pub base_cycles: u8,
+    /// the operand bytes, following the opcode.
+    pub operand_bytes: Vec<u8>,
+    /// the address the instruction would access or jump to if executed now, None for implied/accumulator/immediate
+    /// (or if it can't be read, i.e. past the end of memory).
+    ///
+    /// for Zpr this is the zeropage address tested, as for Rel the branch target.
+    pub effective_address: Option<u16>,
+    /// undocumented (illegal) opcode.
+    pub is_undocumented: bool,
+}
+
+/**
+ * options for disassemble_with_options().
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DisasmOptions {
+    /// stop before the first undocumented opcode (data regions often decode to nonsense undocumented instructions).
+    pub stop_on_undocumented: bool,
+    /// emit undocumented opcodes as a '.byte $xx' pseudo instruction (1 byte), instead of decoding them.
+    pub bytes_for_undocumented: bool,
+}
+
+/**
+ * the mnemonic of the '.byte $xx' pseudo instruction.
+ */
+const DATA_BYTE: &str = ".byte";
+
+impl Instruction {
+    /**
+     * check if this is a '.byte $xx' pseudo instruction, emitted in place of an undocumented opcode with
+     * DisasmOptions::bytes_for_undocumented.
+     */
+    pub fn is_data(&self) -> bool {
+        self.mnemonic == DATA_BYTE
+    }
+
+    /**
+     * the mnemonic as displayed: uppercase, undocumented opcodes are marked with '*' (i.e. "*SLO").
+     */
+    pub fn mnemonic_text(&self) -> String {
+        if self.is_data() {
+            return String::from(DATA_BYTE);
+        }
+        format!(
+            "{}{}",
+            if self.is_undocumented { "*" } else { "" },
+            self.mnemonic.to_uppercase()
+        )
+    }
+
+    /**
+     * the operand in assembler syntax (i.e. "($10), Y").
+     */
+    pub fn operand_text(&self) -> String {
+        if self.is_data() {
+            return format!("${:02x}", self.bytes[0]);
+        }
+        let o = self.operand.unwrap_or_default();
+        match self.mode {
+            AddressingModeId::Imp => String::new(),
+            AddressingModeId::Acc => String::from("A"),
+            AddressingModeId::Imm => format!("#${:02x}", o),
+            AddressingModeId::Zpg | AddressingModeId::Rel => format!("${:02x}", o),
+            AddressingModeId::Zpx => format!("${:02x}, X", o),
+            AddressingModeId::Zpy => format!("${:02x}, Y", o),
+            AddressingModeId::Abs => format!("${:04x}", o),
+            AddressingModeId::Abx => format!("${:04x}, X", o),
+            AddressingModeId::Aby => format!("${:04x}, Y", o),
+            AddressingModeId::Ind => format!("(${:04x})", o),
+            AddressingModeId::Xin => format!("(${:02x}, X)", o),
+            AddressingModeId::Iny => format!("(${:02x}), Y", o),
+            AddressingModeId::Izp => format!("(${:02x})", o),
+            AddressingModeId::Aix => format!("(${:04x}, X)", o),
+            AddressingModeId::Zpr => format!("${:02x}, ${:02x}", o, self.bytes[2]),
+        }
+    }
+
+    /**
+     * the target address, when it doesn't depend on registers or memory contents.
+     */
+    fn static_target(&self) -> Option<u16> {
+        match self.mode {
+            AddressingModeId::Rel | AddressingModeId::Zpr => self.target,
+            AddressingModeId::Abs | AddressingModeId::Zpg => self.operand,
+            AddressingModeId::Imm => Some(self.address.wrapping_add(1)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let asm = format!("{} {}", self.mnemonic_text(), self.operand_text());
+        let asm = asm.trim_end();
+        if self.is_data() {
+            return write!(f, "${:04x}:\t{}\t\t-->\t{}", self.address, bytes[0], asm);
+        }
+        write!(
+            f,
+            "${:04x}:\t{}{}-->\t{}{}[{}",
+            self.address,
+            bytes.join(" "),
+            if self.bytes.len() < 3 { "\t\t" } else { "\t" },
+            asm,
+            if asm.len() < 8 { "\t\t" } else { "\t" },
+            self.mode
+        )?;
+        if let Some(t) = self.static_target() {
+            write!(f, ", tgt=${:04x}", t)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/**
+ * decode a single instruction at the start of mem, located at address.
+ *
+ * returns None if mem is too short to hold the whole instruction.
+ */
+pub fn decode(mem: &[u8], address: u16, cpu_type: CpuType) -> Option<Instruction> {
+    let info = opcode_info(*mem.first()?, cpu_type);
+    let size = info.bytes as usize;
+    if mem.len() < size {
+        return None;
+    }
+    let bytes = mem[..size].to_vec();
+    let operand = match size {
+        2 => Some(bytes[1] as u16),
+        3 if info.addressing_mode == AddressingModeId::Zpr => Some(bytes[1] as u16),
+        3 => Some(u16::from_le_bytes([bytes[1], bytes[2]])),
+        _ => None,
+    };
+    let target = match info.addressing_mode {
+        // the offset is relative to the next instruction
+        AddressingModeId::Rel | AddressingModeId::Zpr => {
+            Some(get_relative_branch_target(address.wrapping_add(size as u16), bytes[size - 1]).0)
+        }
+        _ => None,
+    };
+    Some(Instruction {
+        address,
+        bytes,
+        mnemonic: info.mnemonic,
+        mode: info.addressing_mode,
+        operand,
+        target,
+        is_undocumented: info.undocumented,
+    })
+}
+
+/**
+ * disassemble mem as if it was located at origin, for the given cpu type.
+ *
+ * decoding stops at the end of mem, or before an instruction which would be truncated.
+ */
+pub fn disassemble(mem: &[u8], origin: u16, cpu_type: CpuType) -> Vec<Instruction> {
+    disassemble_with_options(mem, origin, cpu_type, &DisasmOptions::default())
+}
+
+/**
+ * disassemble mem as disassemble(), handling the undocumented opcodes as set in opts.
+ */
+pub fn disassemble_with_options(
+    mem: &[u8],
+    origin: u16,
+    cpu_type: CpuType,
+    opts: &DisasmOptions,
+) -> Vec<Instruction> {
+    let mut v: Vec<Instruction> = Vec::new();
+    let mut offset: usize = 0;
+    while offset < mem.len() {
+        let address = origin.wrapping_add(offset as u16);
+        let info = opcode_info(mem[offset], cpu_type);
+        if info.undocumented {
+            if opts.stop_on_undocumented {
+                break;
+            }
+            if opts.bytes_for_undocumented {
+                v.push(Instruction {
+                    address,
+                    bytes: vec![mem[offset]],
+                    mnemonic: DATA_BYTE,
+                    mode: AddressingModeId::Imp,
+                    operand: None,
+                    target: None,
+                    is_undocumented: true,
+                });
+                offset += 1;
+                continue;
+            }
+        }
+        match decode(&mem[offset..], address, cpu_type) {
+            Some(i) => {
+                offset += i.bytes.len();
+                v.push(i);
+            }
+            None => break,
+        }
+    }
+    v
+}
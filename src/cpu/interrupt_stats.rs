@@ -0,0 +1,195 @@
+/*
+ * Filename: /src/cpu/interrupt_stats.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:51:40
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::Cpu;
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::fmt::{Error, Formatter};
+
+/**
+ * serviced interrupts counters, as returned by Cpu::interrupt_stats().
+ *
+ * handler cycles go from the handler entry (the interrupt sequence itself is not accounted) to the matching RTI
+ * included, handlers still running are not accounted yet.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterruptStats {
+    /// irqs serviced.
+    pub irqs: usize,
+    /// nmis serviced (including the ones hijacking a BRK).
+    pub nmis: usize,
+    /// BRKs executed.
+    pub brks: usize,
+    /// cycles spent in the irq handlers.
+    pub irq_cycles: usize,
+    /// cycles spent in the nmi handlers.
+    pub nmi_cycles: usize,
+    /// cycles spent in the BRK handlers.
+    pub brk_cycles: usize,
+    /// worst case cycles from the irq line assertion to the handler entry, the 7 cycles sequence included.
+    pub max_irq_latency: usize,
+    /// worst case cycles from the nmi edge (or trigger_nmi()) to the handler entry, the 7 cycles sequence included.
+    pub max_nmi_latency: usize,
+}
+
+impl Display for InterruptStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "IRQ: {} serviced, {} handler cycles, max latency {}\n\
+             NMI: {} serviced, {} handler cycles, max latency {}\n\
+             BRK: {} executed, {} handler cycles",
+            self.irqs,
+            self.irq_cycles,
+            self.max_irq_latency,
+            self.nmis,
+            self.nmi_cycles,
+            self.max_nmi_latency,
+            self.brks,
+            self.brk_cycles
+        )
+    }
+}
+
+/**
+ * kind of interrupt entered.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InterruptKind {
+    Irq,
+    Nmi,
+    Brk,
+}
+
+/**
+ * the interrupt statistics and the handlers still running.
+ */
+#[derive(Debug, Default)]
+pub(crate) struct InterruptTracker {
+    stats: InterruptStats,
+    /// (kind, S after the pushes, cycles at the handler entry) of the handlers not yet returned, innermost last.
+    running: Vec<(InterruptKind, u8, usize)>,
+    /// cycles when the irq line has been asserted, if not yet serviced.
+    irq_asserted: Option<usize>,
+    /// cycles when the nmi has been latched, if not yet serviced.
+    nmi_asserted: Option<usize>,
+}
+
+impl<B: Bus> Cpu<B> {
+    /**
+     * returns the counters of the serviced irqs, nmis and BRKs, with the cycles spent in their handlers.
+     */
+    pub fn interrupt_stats(&self) -> InterruptStats {
+        self.istats.stats
+    }
+
+    /**
+     * zeroes the interrupt counters, handlers still running are accounted when they return.
+     */
+    pub fn reset_interrupt_stats(&mut self) {
+        self.istats.stats = InterruptStats::default();
+    }
+
+    /**
+     * record the irq line assertion or the nmi latch, to compute the latency.
+     */
+    pub(crate) fn interrupt_asserted(&mut self, kind: InterruptKind) {
+        let cycles = self.cycles;
+        let t = &mut self.istats;
+        match kind {
+            InterruptKind::Irq => t.irq_asserted = t.irq_asserted.or(Some(cycles)),
+            InterruptKind::Nmi => t.nmi_asserted = t.nmi_asserted.or(Some(cycles)),
+            InterruptKind::Brk => (),
+        }
+    }
+
+    /**
+     * account an interrupt handler entered at cycles, S is after the pushes.
+     */
+    pub(crate) fn interrupt_entered(&mut self, kind: InterruptKind, cycles: usize) {
+        let s = self.regs.s;
+        let t = &mut self.istats;
+        match kind {
+            InterruptKind::Irq => {
+                t.stats.irqs = t.stats.irqs.wrapping_add(1);
+                if let Some(a) = t.irq_asserted.take() {
+                    t.stats.max_irq_latency = t.stats.max_irq_latency.max(cycles.wrapping_sub(a));
+                }
+            }
+            InterruptKind::Nmi => {
+                t.stats.nmis = t.stats.nmis.wrapping_add(1);
+                if let Some(a) = t.nmi_asserted.take() {
+                    t.stats.max_nmi_latency = t.stats.max_nmi_latency.max(cycles.wrapping_sub(a));
+                }
+            }
+            InterruptKind::Brk => t.stats.brks = t.stats.brks.wrapping_add(1),
+        }
+        // handlers at the same depth or deeper can't return anymore, their frames are overwritten
+        t.running.retain(|r| r.1 > s);
+        t.running.push((kind, s, cycles));
+    }
+
+    /**
+     * called by RTI before pulling, with the cycles when it completes: accounts the handler entered at the same stack
+     * depth, dropping the ones deeper (left without RTI).
+     */
+    pub(crate) fn interrupt_returned(&mut self, cycles: usize) {
+        let s = self.regs.s;
+        let t = &mut self.istats;
+        while let Some(&(kind, entry_s, entry_cycles)) = t.running.last() {
+            if entry_s > s {
+                // an outer handler, this RTI doesn't belong to an interrupt
+                break;
+            }
+            t.running.pop();
+            if entry_s < s {
+                continue;
+            }
+            let elapsed = cycles.wrapping_sub(entry_cycles);
+            let total = match kind {
+                InterruptKind::Irq => &mut t.stats.irq_cycles,
+                InterruptKind::Nmi => &mut t.stats.nmi_cycles,
+                InterruptKind::Brk => &mut t.stats.brk_cycles,
+            };
+            *total = total.wrapping_add(elapsed);
+            break;
+        }
+    }
+
+    /**
+     * forget the running handlers and the pending assertions, on reset.
+     */
+    pub(crate) fn reset_interrupt_tracking(&mut self) {
+        self.istats.running.clear();
+        self.istats.irq_asserted = None;
+        self.istats.nmi_asserted = None;
+    }
+}
@@ -0,0 +1,242 @@
+/*
+ * Filename: /src/cpu/no_debugger.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 23:12:05
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! the Debugger placeholder used without the std feature: it has no breakpoints, no trace and no history, so the cpu
+//! always runs through the fast loop (pass None to run()/run_until()).
+
+use crate::bus::Bus;
+use crate::cpu::cpu_error::CpuError;
+use crate::cpu::{Cpu, CpuFlags, StopReason};
+use alloc::string::String;
+use core::fmt;
+
+/**
+ * writes a line to the debugger output, which is discarded.
+ */
+macro_rules! dbg_println {
+    ($d:expr, $($arg:tt)*) => {
+        $d.write_out(format_args!($($arg)*))
+    };
+}
+pub(crate) use dbg_println;
+
+pub(crate) mod breakpoints {
+    use bitflags::bitflags;
+
+    bitflags! {
+        /**
+         * flags for breakpoint types
+         */
+        pub struct BreakpointType : u8 {
+            /// triggers on execute.
+            const EXEC = 0b00000001;
+
+            /// triggers on memory read.
+            const READ = 0b00000010;
+
+            /// triggers on memory write.
+            const WRITE = 0b00000100;
+
+            /// triggers on irq.
+            const IRQ =   0b00001000;
+
+            /// triggers on nmi.
+            const NMI =   0b00010000;
+        }
+    }
+
+    /**
+     * identifies a breakpoint.
+     */
+    pub type BpId = usize;
+
+    /**
+     * where an interrupt has been taken.
+     */
+    #[derive(Debug, Clone, Copy)]
+    pub struct InterruptContext {
+        pub kind: BreakpointType,
+        pub pc: u16,
+        pub vector: u16,
+        pub handler: u16,
+        pub p: u8,
+        pub cycles: usize,
+    }
+
+    impl core::fmt::Display for InterruptContext {
+        fn fmt(&self, _f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            Ok(())
+        }
+    }
+}
+pub use breakpoints::{BpId, BreakpointType, InterruptContext};
+
+/**
+ * the debugger, a passive placeholder without the std feature.
+ */
+pub struct Debugger {
+    pub(crate) enabled: bool,
+    pub(crate) going: bool,
+    pub(crate) show_registers_before_opcode: bool,
+    pub(crate) bp_suspended: bool,
+    pub(crate) resume_pc: Option<u16>,
+}
+
+impl Debugger {
+    /**
+     * creates the placeholder debugger, always disabled.
+     */
+    pub fn new(_enabled: bool) -> Debugger {
+        Debugger {
+            enabled: false,
+            going: false,
+            show_registers_before_opcode: false,
+            bp_suspended: false,
+            resume_pc: None,
+        }
+    }
+
+    pub(crate) fn new_detached() -> Debugger {
+        Debugger::new(false)
+    }
+
+    pub(crate) fn write_out(&self, _args: fmt::Arguments<'_>) {}
+
+    pub(crate) fn is_passive(&self) -> bool {
+        true
+    }
+
+    pub(crate) fn parse_cmd_stdin<B: Bus>(
+        &mut self,
+        _c: &mut Cpu<B>,
+    ) -> Result<(String, bool), ()> {
+        Err(())
+    }
+
+    pub(crate) fn handle_rw_breakpoint<B: Bus>(
+        &self,
+        _c: &Cpu<B>,
+        _address: u16,
+        _t: BreakpointType,
+    ) -> Result<(), CpuError> {
+        Ok(())
+    }
+
+    pub(crate) fn handle_stack_breakpoint<B: Bus>(
+        &self,
+        _c: &Cpu<B>,
+        _old_s: u8,
+        _size: u8,
+        _push: bool,
+    ) -> Result<(), CpuError> {
+        Ok(())
+    }
+
+    pub(crate) fn has_enabled_breakpoint<B: Bus>(
+        &self,
+        _c: &Cpu<B>,
+        _addr: u16,
+        _t: BreakpointType,
+    ) -> Option<i8> {
+        None
+    }
+
+    pub(crate) fn has_enabled_opcode_breakpoint<B: Bus>(
+        &self,
+        _c: &Cpu<B>,
+        _opcode: u8,
+    ) -> Option<i8> {
+        None
+    }
+
+    pub(crate) fn has_enabled_flag_breakpoint<B: Bus>(
+        &mut self,
+        _c: &Cpu<B>,
+        _pc: u16,
+        _p_before: CpuFlags,
+    ) -> Option<i8> {
+        None
+    }
+
+    pub(crate) fn flag_change(&self) -> Option<&str> {
+        None
+    }
+
+    pub(crate) fn check_interrupt_breakpoint<B: Bus>(
+        &mut self,
+        _c: &Cpu<B>,
+        _ctx: InterruptContext,
+    ) {
+    }
+
+    pub(crate) fn take_interrupt_breakpoint(&mut self) -> Option<i8> {
+        None
+    }
+
+    pub(crate) fn interrupt_context(&self) -> Option<&InterruptContext> {
+        None
+    }
+
+    pub(crate) fn breakpoint_triggered(&mut self, _idx: usize) -> bool {
+        self.going = false;
+        true
+    }
+
+    pub(crate) fn queue_breakpoint_commands(&mut self, _idx: usize) {}
+
+    pub(crate) fn stop_reason<B: Bus>(&mut self, c: &Cpu<B>, user_bp: bool, idx: i8) -> StopReason {
+        self.resume_pc = Some(c.regs.pc);
+        if user_bp {
+            StopReason::Breakpoint(idx as BpId)
+        } else {
+            StopReason::Stopped
+        }
+    }
+
+    pub(crate) fn is_step_out_done<B: Bus>(&mut self, _c: &Cpu<B>, _opcode: u8) -> bool {
+        false
+    }
+
+    pub(crate) fn history_begin<B: Bus>(&mut self, _c: &mut Cpu<B>) {}
+
+    pub(crate) fn history_end<B: Bus>(&mut self, _c: &mut Cpu<B>) {}
+
+    pub(crate) fn trace_instruction<B: Bus>(&mut self, _c: &mut Cpu<B>) -> Result<(), CpuError> {
+        Ok(())
+    }
+
+    pub(crate) fn stop_trace(&mut self) -> Result<(), CpuError> {
+        Ok(())
+    }
+}
+
+impl<B: Bus> Cpu<B> {
+    pub(crate) fn journal_write(&mut self, _address: u16) {}
+}
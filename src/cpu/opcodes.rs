@@ -28,18 +28,21 @@
  * SOFTWARE.
  */
 
+use crate::bus::Bus;
 use crate::cpu::addressing_modes;
 use crate::cpu::addressing_modes::AddressingModeId::*;
 use crate::cpu::addressing_modes::*;
 use crate::cpu::cpu_error::{CpuError, CpuErrorType};
 use crate::cpu::debugger::breakpoints::BreakpointType;
 use crate::cpu::debugger::Debugger;
+use crate::cpu::interrupt_stats::InterruptKind;
 use crate::cpu::CpuFlags;
-use crate::cpu::{Cpu, CpuOperation, CpuType, Vectors};
+use crate::cpu::{defer_breakpoint, Cpu, CpuOperation, CpuType, Vectors};
 use crate::utils;
 use crate::utils::*;
 use ::function_name::named;
-use lazy_static::*;
+use alloc::boxed::Box;
+use core::marker::PhantomData;
 
 /**
  * holds opcode information for assembler/disassembler
@@ -53,634 +56,962 @@ pub(crate) struct OpcodeMarker {
     pub(crate) id: AddressingModeId,
 }
 
-lazy_static! {
 /**
- * the 6502 256 opcodes table (includes undocumented)
+ * an opcode implementation.
  *
- * each opcode gets in input a reference to the Cpu, a reference to the Debugger, the cycles needed to execute the opcode, a boolean to indicate if, on crossing page boundaries, an extra cycles must be added,
- * a boolean to indicate decoding only (no execution, for the disassembler), a boolean to indicate if an rw breakpoint has been triggered before, a boolean to silence outputs for combined opcodes (i.e ISC).
- * returns a tuple with the instruction size and the effective elapsed cycles (may include the aferomentioned additional cycle).
+ * fn(c: &mut Cpu, d: Option<&Debugger>, opcode_byte: u8, in_cycles: usize, extra_cycle_on_page_crossing: bool, decode_only: bool, quiet: bool) -> Result<(instr_size:i8, out_cycles:usize), CpuError>
  *
- * for clarity, each Vec element is a tuple defined as this (each element is named including return values):
- *
- * (< fn(c: &mut Cpu, d: Option<&Debugger>, opcode_byte: u8, in_cycles: usize, extra_cycle_on_page_crossing: bool, decode_only: bool, quiet: bool) -> Result<(instr_size:i8, out_cycles:usize), CpuError>, d: Option<& Debugger>, in_cycles: usize, add_extra_cycle:bool, mrk: OpcodeMarker) >)
- *
- * all the opcodes info are taken from, in no particular order :
- *
- * - https://www.masswerk.at/6502/6502_instruction_set.html
- * - https://problemkaputt.de/2k6specs.htm#cpu65xxmicroprocessor
- * - http://www.oxyron.de/html/opcodes02.html
- * - http://6502.org/tutorials/65c02opcodes.html
- * - http://www.obelisk.me.uk/6502/reference.html (WARNING: ASL, LSR, ROL, ROR info is wrong! flag Z is set when RESULT=0, not when A=0. i fixed this in functions comments.)
- * - [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
+ * instr_size is always the real instruction size, opcodes which load pc (jumps, taken branches, ...) use Cpu::set_pc()
+ * so the run loop doesn't advance it.
+ */
+pub(crate) type OpcodeFn<B> = fn(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    opcode_byte: u8,
+    in_cycles: usize,
+    extra_cycle_on_page_crossing: bool,
+    decode_only: bool,
+    quiet: bool,
+) -> Result<(i8, usize), CpuError>;
+
+/**
+ * an entry in the opcode tables.
+ */
+pub(crate) struct OpcodeEntry<B: Bus> {
+    /// the opcode implementation.
+    pub(crate) f: OpcodeFn<B>,
+
+    /// base cycles.
+    pub(crate) cycles: usize,
+
+    /// an extra cycle is added on page crossing (or taken branch).
+    pub(crate) extra_cycle_on_page_crossing: bool,
+
+    /// name and addressing mode.
+    pub(crate) mrk: OpcodeMarker,
+}
+
+impl<B: Bus> OpcodeEntry<B> {
+    const fn new(
+        f: OpcodeFn<B>,
+        cycles: usize,
+        extra_cycle_on_page_crossing: bool,
+        mrk: OpcodeMarker,
+    ) -> OpcodeEntry<B> {
+        OpcodeEntry {
+            f,
+            cycles,
+            extra_cycle_on_page_crossing,
+            mrk,
+        }
+    }
+}
+
+/**
+ * public opcode information, as returned by opcode_info().
  */
-pub(crate) static ref OPCODE_MATRIX: Vec<( fn(c: &mut Cpu, d: Option<&Debugger>, opcode_byte: u8, in_cycles: usize, extra_cycle_on_page_crossing: bool, decode_only:bool, quiet: bool) -> Result<(i8, usize), CpuError>, usize, bool, OpcodeMarker)> =
-    vec![
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub struct OpcodeInfo {
+    /// the opcode byte.
+    pub opcode: u8,
+    /// lowercase mnemonic (i.e. "lda").
+    pub mnemonic: &'static str,
+    /// the addressing mode.
+    pub addressing_mode: AddressingModeId,
+    /// instruction size, including the opcode byte.
+    pub bytes: u8,
+    /// base cycles.
+    pub base_cycles: u8,
+    /// an extra cycle is added on page crossing (or taken branch).
+    pub page_cross_penalty: bool,
+    /// undocumented (illegal) opcode.
+    pub undocumented: bool,
+}
+
+/**
+ * check if an opcode is undocumented for the given cpu type.
+ */
+fn is_undocumented(opcode: u8, name: &str, t: &CpuType) -> bool {
+    if name.eq("nop") {
+        // $ea is the only official nop
+        return opcode != 0xea;
+    }
+    if t.is_65c02() {
+        // on 65C02 all the other opcodes are defined
+        return false;
+    }
+    if opcode == 0xeb {
+        // sbc #imm mirror
+        return true;
+    }
+    matches!(
+        name,
+        "ahx"
+            | "alr"
+            | "anc"
+            | "arr"
+            | "dcp"
+            | "isc"
+            | "kil"
+            | "las"
+            | "lax"
+            | "lxa"
+            | "rla"
+            | "rra"
+            | "sax"
+            | "sbx"
+            | "shx"
+            | "shy"
+            | "slo"
+            | "sre"
+            | "tas"
+            | "xaa"
+    )
+}
+
+/**
+ * returns information about the given opcode, for the given cpu type.
+ */
+pub fn opcode_info(opcode: u8, cpu_type: CpuType) -> OpcodeInfo {
+    // any bus will do, only the opcode metadata is used
+    let e = opcode_entry::<Box<dyn Bus>>(opcode, cpu_type);
+    OpcodeInfo {
+        opcode,
+        mnemonic: e.mrk.name,
+        addressing_mode: e.mrk.id,
+        bytes: e.mrk.id.instr_size(),
+        base_cycles: e.cycles as u8,
+        page_cross_penalty: e.extra_cycle_on_page_crossing,
+        undocumented: is_undocumented(opcode, e.mrk.name, &cpu_type),
+    }
+}
+
+/**
+ * holds the opcode tables, monomorphized for the Bus type so the opcodes access memory without dynamic dispatch.
+ */
+pub(crate) struct Opcodes<B: Bus>(PhantomData<B>);
+
+impl<B: Bus> Opcodes<B> {
+    /**
+     * the 6502 256 opcodes table (includes undocumented)
+     *
+     * each opcode gets in input a reference to the Cpu, a reference to the Debugger, the cycles needed to execute the opcode, a boolean to indicate if, on crossing page boundaries, an extra cycles must be added,
+     * a boolean to indicate decoding only (no execution, for the disassembler), a boolean to indicate if an rw breakpoint has been triggered before, a boolean to silence outputs for combined opcodes (i.e ISC).
+     * returns a tuple with the instruction size and the effective elapsed cycles (may include the aferomentioned additional cycle).
+     *
+     * each element is an OpcodeEntry (see OpcodeFn for the opcode function signature), indexed by the opcode byte.
+     * the tables are built at compile time (once per Bus type), and their type enforces exactly 256 entries.
+     *
+     * all the opcodes info are taken from, in no particular order :
+     *
+     * - https://www.masswerk.at/6502/6502_instruction_set.html
+     * - https://problemkaputt.de/2k6specs.htm#cpu65xxmicroprocessor
+     * - http://www.oxyron.de/html/opcodes02.html
+     * - http://6502.org/tutorials/65c02opcodes.html
+     * - http://www.obelisk.me.uk/6502/reference.html (WARNING: ASL, LSR, ROL, ROR info is wrong! flag Z is set when RESULT=0, not when A=0. i fixed this in functions comments.)
+     * - [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
+     */
+    #[rustfmt::skip]
+    pub(crate) const OPCODE_MATRIX: [OpcodeEntry<B>; 256] = [
         // 0x0 - 0xf
-        (brk::<ImpliedAddressing>, 7, false, OpcodeMarker{ name: "brk", id: Imp}),
-        (ora::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "ora", id: Xin}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (slo::<XIndirectAddressing>, 8, false, OpcodeMarker{ name: "slo", id: Xin}),
-        (nop::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
-        (ora::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "ora", id: Zpg}),
-        (asl::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "asl", id: Zpg}),
-        (slo::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "slo", id: Zpg}),
-        (php::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "php", id: Imp}),
-        (ora::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "ora", id: Imm}),
-        (asl::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "asl", id: Acc}),
-        (anc::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "anc", id: Imm}),
-        (nop::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Abs}),
-        (ora::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "ora", id: Abs}),
-        (asl::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "asl", id: Abs}),
-        (slo::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "slo", id: Abs}),
+        OpcodeEntry::new(brk::<ImpliedAddressing, B>, 7, false, OpcodeMarker{ name: "brk", id: Imp}),
+        OpcodeEntry::new(ora::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "ora", id: Xin}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(slo::<XIndirectAddressing, B>, 8, false, OpcodeMarker{ name: "slo", id: Xin}),
+        OpcodeEntry::new(nop::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
+        OpcodeEntry::new(ora::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "ora", id: Zpg}),
+        OpcodeEntry::new(asl::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "asl", id: Zpg}),
+        OpcodeEntry::new(slo::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "slo", id: Zpg}),
+        OpcodeEntry::new(php::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "php", id: Imp}),
+        OpcodeEntry::new(ora::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "ora", id: Imm}),
+        OpcodeEntry::new(asl::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "asl", id: Acc}),
+        OpcodeEntry::new(anc::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "anc", id: Imm}),
+        OpcodeEntry::new(nop::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Abs}),
+        OpcodeEntry::new(ora::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "ora", id: Abs}),
+        OpcodeEntry::new(asl::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "asl", id: Abs}),
+        OpcodeEntry::new(slo::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "slo", id: Abs}),
 
         // 0x10 - 0x1f
-        (bpl::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bpl", id: Rel}),
-        (ora::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "ora", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (slo::<IndirectYAddressing>, 8, false, OpcodeMarker{ name: "slo", id: Iny}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (ora::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "ora", id: Zpx}),
-        (asl::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "asl", id: Zpx}),
-        (slo::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "slo", id: Zpx}),
-        (clc::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "clc", id: Imp}),
-        (ora::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "ora", id: Aby}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (slo::<AbsoluteYAddressing>, 7, false, OpcodeMarker{ name: "slo", id: Aby}),
-        (nop::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
-        (ora::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "ora", id: Abx}),
-        (asl::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "asl", id: Abx}),
-        (slo::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "slo", id: Abx}),
+        OpcodeEntry::new(bpl::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bpl", id: Rel}),
+        OpcodeEntry::new(ora::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "ora", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(slo::<IndirectYAddressing, B>, 8, false, OpcodeMarker{ name: "slo", id: Iny}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(ora::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "ora", id: Zpx}),
+        OpcodeEntry::new(asl::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "asl", id: Zpx}),
+        OpcodeEntry::new(slo::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "slo", id: Zpx}),
+        OpcodeEntry::new(clc::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "clc", id: Imp}),
+        OpcodeEntry::new(ora::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "ora", id: Aby}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(slo::<AbsoluteYAddressing, B>, 7, false, OpcodeMarker{ name: "slo", id: Aby}),
+        OpcodeEntry::new(nop::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
+        OpcodeEntry::new(ora::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "ora", id: Abx}),
+        OpcodeEntry::new(asl::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "asl", id: Abx}),
+        OpcodeEntry::new(slo::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "slo", id: Abx}),
 
         // 0x20 - 0x2f
-        (jsr::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "jsr", id: Abs}),
-        (and::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "and", id: Xin}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (rla::<XIndirectAddressing>, 8, false, OpcodeMarker{ name: "rla", id: Xin}),
-        (bit::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "bit", id: Zpg}),
-        (and::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "and", id: Zpg}),
-        (rol::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rol", id: Zpg}),
-        (rla::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rla", id: Zpg}),
-        (plp::<ImpliedAddressing>, 4, false, OpcodeMarker{ name: "plp", id: Imp}),
-        (and::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "and", id: Imm}),
-        (rol::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "rol", id: Acc}),
-        (anc::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "anc", id: Imm}),
-        (bit::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "bit", id: Abs}),
-        (and::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "and", id: Abs}),
-        (rol::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "rol", id: Abs}),
-        (rla::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "rla", id: Abs}),
+        OpcodeEntry::new(jsr::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "jsr", id: Abs}),
+        OpcodeEntry::new(and::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "and", id: Xin}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(rla::<XIndirectAddressing, B>, 8, false, OpcodeMarker{ name: "rla", id: Xin}),
+        OpcodeEntry::new(bit::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "bit", id: Zpg}),
+        OpcodeEntry::new(and::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "and", id: Zpg}),
+        OpcodeEntry::new(rol::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rol", id: Zpg}),
+        OpcodeEntry::new(rla::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rla", id: Zpg}),
+        OpcodeEntry::new(plp::<ImpliedAddressing, B>, 4, false, OpcodeMarker{ name: "plp", id: Imp}),
+        OpcodeEntry::new(and::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "and", id: Imm}),
+        OpcodeEntry::new(rol::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "rol", id: Acc}),
+        OpcodeEntry::new(anc::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "anc", id: Imm}),
+        OpcodeEntry::new(bit::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "bit", id: Abs}),
+        OpcodeEntry::new(and::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "and", id: Abs}),
+        OpcodeEntry::new(rol::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "rol", id: Abs}),
+        OpcodeEntry::new(rla::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "rla", id: Abs}),
 
         // 0x30 - 0x3f
-        (bmi::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bmi", id: Rel}),
-        (and::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "and", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (rla::<IndirectYAddressing>, 8, false, OpcodeMarker{ name: "rla", id: Iny}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (and::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "and", id: Zpx}),
-        (rol::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "rol", id: Zpx}),
-        (rla::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "rla", id: Zpx}),
-        (sec::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "sec", id: Imp}),
-        (and::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "and", id: Aby}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (rla::<AbsoluteYAddressing>, 7, false, OpcodeMarker{ name: "rla", id: Aby}),
-        (nop::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
-        (and::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "and", id: Abx}),
-        (rol::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "rol", id: Abx}),
-        (rla::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "rla", id: Abx}),
+        OpcodeEntry::new(bmi::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bmi", id: Rel}),
+        OpcodeEntry::new(and::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "and", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(rla::<IndirectYAddressing, B>, 8, false, OpcodeMarker{ name: "rla", id: Iny}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(and::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "and", id: Zpx}),
+        OpcodeEntry::new(rol::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "rol", id: Zpx}),
+        OpcodeEntry::new(rla::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "rla", id: Zpx}),
+        OpcodeEntry::new(sec::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "sec", id: Imp}),
+        OpcodeEntry::new(and::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "and", id: Aby}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(rla::<AbsoluteYAddressing, B>, 7, false, OpcodeMarker{ name: "rla", id: Aby}),
+        OpcodeEntry::new(nop::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
+        OpcodeEntry::new(and::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "and", id: Abx}),
+        OpcodeEntry::new(rol::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "rol", id: Abx}),
+        OpcodeEntry::new(rla::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "rla", id: Abx}),
 
         // 0x40 - 0x4f
-        (rti::<ImpliedAddressing>, 6, false, OpcodeMarker{ name: "rti", id: Imp}),
-        (eor::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "eor", id: Xin}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (sre::<XIndirectAddressing>, 8, false, OpcodeMarker{ name: "sre", id: Xin}),
-        (nop::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
-        (eor::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "eor", id: Zpg}),
-        (lsr::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "lsr", id: Zpg}),
-        (sre::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "sre", id: Zpg}),
-        (pha::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "pha", id: Imp}),
-        (eor::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "eor", id: Imm}),
-        (lsr::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "lsr", id: Acc}),
-        (alr::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "alr", id: Imm}),
-        (jmp::<AbsoluteAddressing>, 3, false, OpcodeMarker{ name: "jmp", id: Abs}),
-        (eor::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "eor", id: Abs}),
-        (lsr::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "lsr", id: Abs}),
-        (sre::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "sre", id: Abs}),
+        OpcodeEntry::new(rti::<ImpliedAddressing, B>, 6, false, OpcodeMarker{ name: "rti", id: Imp}),
+        OpcodeEntry::new(eor::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "eor", id: Xin}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(sre::<XIndirectAddressing, B>, 8, false, OpcodeMarker{ name: "sre", id: Xin}),
+        OpcodeEntry::new(nop::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
+        OpcodeEntry::new(eor::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "eor", id: Zpg}),
+        OpcodeEntry::new(lsr::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "lsr", id: Zpg}),
+        OpcodeEntry::new(sre::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "sre", id: Zpg}),
+        OpcodeEntry::new(pha::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "pha", id: Imp}),
+        OpcodeEntry::new(eor::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "eor", id: Imm}),
+        OpcodeEntry::new(lsr::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "lsr", id: Acc}),
+        OpcodeEntry::new(alr::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "alr", id: Imm}),
+        OpcodeEntry::new(jmp::<AbsoluteAddressing, B>, 3, false, OpcodeMarker{ name: "jmp", id: Abs}),
+        OpcodeEntry::new(eor::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "eor", id: Abs}),
+        OpcodeEntry::new(lsr::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "lsr", id: Abs}),
+        OpcodeEntry::new(sre::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "sre", id: Abs}),
 
         // 0x50 - 0x5f
-        (bvc::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bvc", id: Rel}),
-        (eor::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "eor", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (sre::<IndirectYAddressing>, 8, false, OpcodeMarker{ name: "sre", id: Iny}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (eor::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "eor", id: Zpx}),
-        (lsr::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "lsr", id: Zpx}),
-        (sre::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "sre", id: Zpx}),
-        (cli::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "cli", id: Imp}),
-        (eor::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "eor", id: Aby}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (sre::<AbsoluteYAddressing>, 7, false, OpcodeMarker{ name: "sre", id: Aby}),
-        (nop::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
-        (eor::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "eor", id: Abx}),
-        (lsr::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "lsr", id: Abx}),
-        (sre::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "sre", id: Abx}),
+        OpcodeEntry::new(bvc::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bvc", id: Rel}),
+        OpcodeEntry::new(eor::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "eor", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(sre::<IndirectYAddressing, B>, 8, false, OpcodeMarker{ name: "sre", id: Iny}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(eor::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "eor", id: Zpx}),
+        OpcodeEntry::new(lsr::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "lsr", id: Zpx}),
+        OpcodeEntry::new(sre::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "sre", id: Zpx}),
+        OpcodeEntry::new(cli::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "cli", id: Imp}),
+        OpcodeEntry::new(eor::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "eor", id: Aby}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(sre::<AbsoluteYAddressing, B>, 7, false, OpcodeMarker{ name: "sre", id: Aby}),
+        OpcodeEntry::new(nop::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
+        OpcodeEntry::new(eor::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "eor", id: Abx}),
+        OpcodeEntry::new(lsr::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "lsr", id: Abx}),
+        OpcodeEntry::new(sre::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "sre", id: Abx}),
 
         // 0x60 - 0x6f
-        (rts::<ImpliedAddressing>, 6, false, OpcodeMarker{ name: "rts", id: Imp}),
-        (adc::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "adc", id: Xin}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (rra::<XIndirectAddressing>, 8, false, OpcodeMarker{ name: "rra", id: Xin}),
-        (nop::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
-        (adc::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "adc", id: Zpg}),
-        (ror::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "ror", id: Zpg}),
-        (rra::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rra", id: Zpg}),
-        (pla::<ImpliedAddressing>, 4, false, OpcodeMarker{ name: "pla", id: Imp}),
-        (adc::<ImmediateAddressing>, 2, true, OpcodeMarker{ name: "adc", id: Imm}),
-        (ror::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "ror", id: Acc}),
-        (arr::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "arr", id: Imm}),
-        (jmp::<IndirectAddressing>, 5, false, OpcodeMarker{ name: "jmp", id: Ind}),
-        (adc::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "adc", id: Abs}),
-        (ror::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "ror", id: Abs}),
-        (rra::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "rra", id: Abs}),
+        OpcodeEntry::new(rts::<ImpliedAddressing, B>, 6, false, OpcodeMarker{ name: "rts", id: Imp}),
+        OpcodeEntry::new(adc::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "adc", id: Xin}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(rra::<XIndirectAddressing, B>, 8, false, OpcodeMarker{ name: "rra", id: Xin}),
+        OpcodeEntry::new(nop::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
+        OpcodeEntry::new(adc::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "adc", id: Zpg}),
+        OpcodeEntry::new(ror::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "ror", id: Zpg}),
+        OpcodeEntry::new(rra::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rra", id: Zpg}),
+        OpcodeEntry::new(pla::<ImpliedAddressing, B>, 4, false, OpcodeMarker{ name: "pla", id: Imp}),
+        OpcodeEntry::new(adc::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "adc", id: Imm}),
+        OpcodeEntry::new(ror::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "ror", id: Acc}),
+        OpcodeEntry::new(arr::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "arr", id: Imm}),
+        OpcodeEntry::new(jmp::<IndirectAddressing, B>, 5, false, OpcodeMarker{ name: "jmp", id: Ind}),
+        OpcodeEntry::new(adc::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "adc", id: Abs}),
+        OpcodeEntry::new(ror::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "ror", id: Abs}),
+        OpcodeEntry::new(rra::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "rra", id: Abs}),
 
         // 0x70 - 0x7f
-        (bvs::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bvs", id: Rel}),
-        (adc::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "adc", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (rra::<IndirectYAddressing>, 8, false, OpcodeMarker{ name: "rra", id: Iny}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (adc::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "adc", id: Zpx}),
-        (ror::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "ror", id: Zpx}),
-        (rra::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "rra", id: Zpx}),
-        (sei::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "sei", id: Imp}),
-        (adc::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "adc", id: Aby}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (rra::<AbsoluteYAddressing>, 7, false, OpcodeMarker{ name: "rra", id: Aby}),
-        (nop::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
-        (adc::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "adc", id: Abx}),
-        (ror::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "ror", id: Abx}),
-        (rra::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "rra", id: Abx}),
+        OpcodeEntry::new(bvs::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bvs", id: Rel}),
+        OpcodeEntry::new(adc::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "adc", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(rra::<IndirectYAddressing, B>, 8, false, OpcodeMarker{ name: "rra", id: Iny}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(adc::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "adc", id: Zpx}),
+        OpcodeEntry::new(ror::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "ror", id: Zpx}),
+        OpcodeEntry::new(rra::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "rra", id: Zpx}),
+        OpcodeEntry::new(sei::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "sei", id: Imp}),
+        OpcodeEntry::new(adc::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "adc", id: Aby}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(rra::<AbsoluteYAddressing, B>, 7, false, OpcodeMarker{ name: "rra", id: Aby}),
+        OpcodeEntry::new(nop::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
+        OpcodeEntry::new(adc::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "adc", id: Abx}),
+        OpcodeEntry::new(ror::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "ror", id: Abx}),
+        OpcodeEntry::new(rra::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "rra", id: Abx}),
 
         // 0x80 - 0x8f
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (sta::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "sta", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (sax::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "sax", id: Xin}),
-        (sty::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "sty", id: Zpg}),
-        (sta::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "sta", id: Zpg}),
-        (stx::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "stx", id: Zpg}),
-        (sax::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "sax", id: Zpg}),
-        (dey::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "dey", id: Imp}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (txa::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "txa", id: Imp}),
-        (xaa::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "xaa", id: Imm}),
-        (sty::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "sty", id: Abs}),
-        (sta::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "sta", id: Abs}),
-        (stx::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "stx", id: Abs}),
-        (sax::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "sax", id: Abs}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(sta::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "sta", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(sax::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "sax", id: Xin}),
+        OpcodeEntry::new(sty::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "sty", id: Zpg}),
+        OpcodeEntry::new(sta::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "sta", id: Zpg}),
+        OpcodeEntry::new(stx::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "stx", id: Zpg}),
+        OpcodeEntry::new(sax::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "sax", id: Zpg}),
+        OpcodeEntry::new(dey::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "dey", id: Imp}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(txa::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "txa", id: Imp}),
+        OpcodeEntry::new(xaa::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "xaa", id: Imm}),
+        OpcodeEntry::new(sty::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "sty", id: Abs}),
+        OpcodeEntry::new(sta::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "sta", id: Abs}),
+        OpcodeEntry::new(stx::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "stx", id: Abs}),
+        OpcodeEntry::new(sax::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "sax", id: Abs}),
 
         // 0x90 - 0x9f
-        (bcc::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bcc", id: Rel}),
-        (sta::<IndirectYAddressing>, 6, false, OpcodeMarker{ name: "sta", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (ahx::<IndirectYAddressing>, 6, false, OpcodeMarker{ name: "ahx", id: Iny}),
-        (sty::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "sty", id: Zpx}),
-        (sta::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "sta", id: Zpx}),
-        (stx::<ZeroPageYAddressing>, 4, false, OpcodeMarker{ name: "stx", id: Zpy}),
-        (sax::<ZeroPageYAddressing>, 4, false, OpcodeMarker{ name: "sax", id: Zpy}),
-        (tya::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tya", id: Imp}),
-        (sta::<AbsoluteYAddressing>, 5, false, OpcodeMarker{ name: "sta", id: Aby}),
-        (txs::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "txs", id: Imp}),
-        (tas::<AbsoluteYAddressing>, 5, false, OpcodeMarker{ name: "tas", id: Aby}),
-        (shy::<AbsoluteXAddressing>, 5, false, OpcodeMarker{ name: "shy", id: Abx}),
-        (sta::<AbsoluteXAddressing>, 5, false, OpcodeMarker{ name: "sta", id: Abx}),
-        (shx::<AbsoluteYAddressing>, 5, false, OpcodeMarker{ name: "shx", id: Aby}),
-        (ahx::<AbsoluteYAddressing>, 5, false, OpcodeMarker{ name: "ahx", id: Aby}),
+        OpcodeEntry::new(bcc::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bcc", id: Rel}),
+        OpcodeEntry::new(sta::<IndirectYAddressing, B>, 6, false, OpcodeMarker{ name: "sta", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(ahx::<IndirectYAddressing, B>, 6, false, OpcodeMarker{ name: "ahx", id: Iny}),
+        OpcodeEntry::new(sty::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "sty", id: Zpx}),
+        OpcodeEntry::new(sta::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "sta", id: Zpx}),
+        OpcodeEntry::new(stx::<ZeroPageYAddressing, B>, 4, false, OpcodeMarker{ name: "stx", id: Zpy}),
+        OpcodeEntry::new(sax::<ZeroPageYAddressing, B>, 4, false, OpcodeMarker{ name: "sax", id: Zpy}),
+        OpcodeEntry::new(tya::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tya", id: Imp}),
+        OpcodeEntry::new(sta::<AbsoluteYAddressing, B>, 5, false, OpcodeMarker{ name: "sta", id: Aby}),
+        OpcodeEntry::new(txs::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "txs", id: Imp}),
+        OpcodeEntry::new(tas::<AbsoluteYAddressing, B>, 5, false, OpcodeMarker{ name: "tas", id: Aby}),
+        OpcodeEntry::new(shy::<AbsoluteXAddressing, B>, 5, false, OpcodeMarker{ name: "shy", id: Abx}),
+        OpcodeEntry::new(sta::<AbsoluteXAddressing, B>, 5, false, OpcodeMarker{ name: "sta", id: Abx}),
+        OpcodeEntry::new(shx::<AbsoluteYAddressing, B>, 5, false, OpcodeMarker{ name: "shx", id: Aby}),
+        OpcodeEntry::new(ahx::<AbsoluteYAddressing, B>, 5, false, OpcodeMarker{ name: "ahx", id: Aby}),
 
         // 0xa0 - 0xaf
-        (ldy::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "ldy", id: Imm}),
-        (lda::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "lda", id: Xin}),
-        (ldx::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "ldx", id: Imm}),
-        (lax::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "lax", id: Xin}),
-        (ldy::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "ldy", id: Zpg}),
-        (lda::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "lda", id: Zpg}),
-        (ldx::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "ldx", id: Zpg}),
-        (lax::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "lax", id: Zpg}),
-        (tay::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tay", id: Imp}),
-        (lda::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "lda", id: Imm}),
-        (tax::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tax", id: Imp}),
-        (lax::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "lxa", id: Imm}),
-        (ldy::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "ldy", id: Abs}),
-        (lda::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "lda", id: Abs}),
-        (ldx::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "ldx", id: Abs}),
-        (lax::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "lax", id: Abs}),
+        OpcodeEntry::new(ldy::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "ldy", id: Imm}),
+        OpcodeEntry::new(lda::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "lda", id: Xin}),
+        OpcodeEntry::new(ldx::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "ldx", id: Imm}),
+        OpcodeEntry::new(lax::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "lax", id: Xin}),
+        OpcodeEntry::new(ldy::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "ldy", id: Zpg}),
+        OpcodeEntry::new(lda::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "lda", id: Zpg}),
+        OpcodeEntry::new(ldx::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "ldx", id: Zpg}),
+        OpcodeEntry::new(lax::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "lax", id: Zpg}),
+        OpcodeEntry::new(tay::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tay", id: Imp}),
+        OpcodeEntry::new(lda::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "lda", id: Imm}),
+        OpcodeEntry::new(tax::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tax", id: Imp}),
+        OpcodeEntry::new(lxa::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "lxa", id: Imm}),
+        OpcodeEntry::new(ldy::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "ldy", id: Abs}),
+        OpcodeEntry::new(lda::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "lda", id: Abs}),
+        OpcodeEntry::new(ldx::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "ldx", id: Abs}),
+        OpcodeEntry::new(lax::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "lax", id: Abs}),
 
         // 0xb0 - 0xbf
-        (bcs::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bcs", id: Rel}),
-        (lda::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "lda", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (lax::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "lax", id: Iny}),
-        (ldy::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "ldy", id: Zpx}),
-        (lda::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "lda", id: Zpx}),
-        (ldx::<ZeroPageYAddressing>, 4, false, OpcodeMarker{ name: "ldx", id: Zpy}),
-        (lax::<ZeroPageYAddressing>, 4, false, OpcodeMarker{ name: "lax", id: Zpy}),
-        (clv::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "clv", id: Imp}),
-        (lda::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "lda", id: Aby}),
-        (tsx::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tsx", id: Imp}),
-        (las::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "las", id: Aby}),
-        (ldy::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "ldy", id: Abx}),
-        (lda::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "lda", id: Abx}),
-        (ldx::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "ldx", id: Aby}),
-        (lax::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "lax", id: Aby}),
+        OpcodeEntry::new(bcs::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bcs", id: Rel}),
+        OpcodeEntry::new(lda::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "lda", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(lax::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "lax", id: Iny}),
+        OpcodeEntry::new(ldy::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "ldy", id: Zpx}),
+        OpcodeEntry::new(lda::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "lda", id: Zpx}),
+        OpcodeEntry::new(ldx::<ZeroPageYAddressing, B>, 4, false, OpcodeMarker{ name: "ldx", id: Zpy}),
+        OpcodeEntry::new(lax::<ZeroPageYAddressing, B>, 4, false, OpcodeMarker{ name: "lax", id: Zpy}),
+        OpcodeEntry::new(clv::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "clv", id: Imp}),
+        OpcodeEntry::new(lda::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "lda", id: Aby}),
+        OpcodeEntry::new(tsx::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tsx", id: Imp}),
+        OpcodeEntry::new(las::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "las", id: Aby}),
+        OpcodeEntry::new(ldy::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "ldy", id: Abx}),
+        OpcodeEntry::new(lda::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "lda", id: Abx}),
+        OpcodeEntry::new(ldx::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "ldx", id: Aby}),
+        OpcodeEntry::new(lax::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "lax", id: Aby}),
 
         // 0xc0 - 0xcf
-        (cpy::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "cpy", id: Imm}),
-        (cmp::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "cmp", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (dcp::<XIndirectAddressing>, 8, false, OpcodeMarker{ name: "dcp", id: Xin}),
-        (cpy::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "cpy", id: Zpg}),
-        (cmp::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "cmp", id: Zpg}),
-        (dec::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "dec", id: Zpg}),
-        (dcp::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "dcp", id: Zpg}),
-        (iny::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "iny", id: Imp}),
-        (cmp::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "cmp", id: Imm}),
-        (dex::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "dex", id: Imp}),
-        (sbx::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "sbx", id: Imm}),
-        (cpy::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "cpy", id: Abs}),
-        (cmp::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "cmp", id: Abs}),
-        (dec::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "dec", id: Abs}),
-        (dcp::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "dcp", id: Abs}),
+        OpcodeEntry::new(cpy::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "cpy", id: Imm}),
+        OpcodeEntry::new(cmp::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "cmp", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(dcp::<XIndirectAddressing, B>, 8, false, OpcodeMarker{ name: "dcp", id: Xin}),
+        OpcodeEntry::new(cpy::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "cpy", id: Zpg}),
+        OpcodeEntry::new(cmp::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "cmp", id: Zpg}),
+        OpcodeEntry::new(dec::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "dec", id: Zpg}),
+        OpcodeEntry::new(dcp::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "dcp", id: Zpg}),
+        OpcodeEntry::new(iny::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "iny", id: Imp}),
+        OpcodeEntry::new(cmp::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "cmp", id: Imm}),
+        OpcodeEntry::new(dex::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "dex", id: Imp}),
+        OpcodeEntry::new(sbx::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "sbx", id: Imm}),
+        OpcodeEntry::new(cpy::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "cpy", id: Abs}),
+        OpcodeEntry::new(cmp::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "cmp", id: Abs}),
+        OpcodeEntry::new(dec::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "dec", id: Abs}),
+        OpcodeEntry::new(dcp::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "dcp", id: Abs}),
 
         // 0xd0 - 0xdf
-        (bne::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bne", id: Rel}),
-        (cmp::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "cmp", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (dcp::<IndirectYAddressing>, 8, false, OpcodeMarker{ name: "dcp", id: Iny}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (cmp::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "cmp", id: Zpx}),
-        (dec::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "dec", id: Zpx}),
-        (dcp::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "dcp", id: Zpx}),
-        (cld::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "cld", id: Imp}),
-        (cmp::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "cmp", id: Aby}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (dcp::<AbsoluteYAddressing>, 7, false, OpcodeMarker{ name: "dcp", id: Aby}),
-        (nop::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
-        (cmp::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "cmp", id: Abx}),
-        (dec::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "dec", id: Abx}),
-        (dcp::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "dcp", id: Abx}),
+        OpcodeEntry::new(bne::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bne", id: Rel}),
+        OpcodeEntry::new(cmp::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "cmp", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(dcp::<IndirectYAddressing, B>, 8, false, OpcodeMarker{ name: "dcp", id: Iny}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(cmp::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "cmp", id: Zpx}),
+        OpcodeEntry::new(dec::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "dec", id: Zpx}),
+        OpcodeEntry::new(dcp::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "dcp", id: Zpx}),
+        OpcodeEntry::new(cld::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "cld", id: Imp}),
+        OpcodeEntry::new(cmp::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "cmp", id: Aby}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(dcp::<AbsoluteYAddressing, B>, 7, false, OpcodeMarker{ name: "dcp", id: Aby}),
+        OpcodeEntry::new(nop::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
+        OpcodeEntry::new(cmp::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "cmp", id: Abx}),
+        OpcodeEntry::new(dec::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "dec", id: Abx}),
+        OpcodeEntry::new(dcp::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "dcp", id: Abx}),
 
         // 0xe0 - 0xef
-        (cpx::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "cpx", id: Imm}),
-        (sbc::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "sbc", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (isc::<XIndirectAddressing>, 8, false, OpcodeMarker{ name: "isc", id: Xin}),
-        (cpx::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "cpx", id: Zpg}),
-        (sbc::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "sbc", id: Zpg}),
-        (inc::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "inc", id: Zpg}),
-        (isc::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "isc", id: Zpg}),
-        (inx::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "inx", id: Imp}),
-        (sbc::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "sbc", id: Imm}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (sbc::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "sbc", id: Imm}),
-        (cpx::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "cpx", id: Abs}),
-        (sbc::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "sbc", id: Abs}),
-        (inc::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "inc", id: Abs}),
-        (isc::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "isc", id: Abs}),
+        OpcodeEntry::new(cpx::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "cpx", id: Imm}),
+        OpcodeEntry::new(sbc::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "sbc", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(isc::<XIndirectAddressing, B>, 8, false, OpcodeMarker{ name: "isc", id: Xin}),
+        OpcodeEntry::new(cpx::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "cpx", id: Zpg}),
+        OpcodeEntry::new(sbc::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "sbc", id: Zpg}),
+        OpcodeEntry::new(inc::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "inc", id: Zpg}),
+        OpcodeEntry::new(isc::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "isc", id: Zpg}),
+        OpcodeEntry::new(inx::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "inx", id: Imp}),
+        OpcodeEntry::new(sbc::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "sbc", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(sbc::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "sbc", id: Imm}),
+        OpcodeEntry::new(cpx::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "cpx", id: Abs}),
+        OpcodeEntry::new(sbc::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "sbc", id: Abs}),
+        OpcodeEntry::new(inc::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "inc", id: Abs}),
+        OpcodeEntry::new(isc::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "isc", id: Abs}),
 
         // 0xf0 - 0xff
-        (beq::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "beq", id: Rel}),
-        (sbc::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "sbc", id: Iny}),
-        (kil::<ImpliedAddressing>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
-        (isc::<IndirectYAddressing>, 8, false, OpcodeMarker{ name: "isc", id: Iny}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (sbc::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "sbc", id: Zpx}),
-        (inc::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "inc", id: Zpx}),
-        (isc::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "isc", id: Zpx}),
-        (sed::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "sed", id: Imp}),
-        (sbc::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "sbc", id: Aby}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (isc::<AbsoluteYAddressing>, 7, false, OpcodeMarker{ name: "isc", id: Aby}),
-        (nop::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
-        (sbc::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "sbc", id: Abx}),
-        (inc::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "inc", id: Abx}),
-        (isc::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "isc", id: Abx}),
-    ];
-
-/// 65C02 opcode table, same as above with the 65C02 differences.
-pub(crate) static ref OPCODE_MATRIX_65C02: Vec<( fn(c: &mut Cpu, d: Option<&Debugger>, opcode_byte: u8, in_cycles: usize, extra_cycle_on_page_crossing: bool, decode_only:bool, quiet: bool) -> Result<(i8, usize), CpuError>, usize, bool, OpcodeMarker)> =
-    vec![
+        OpcodeEntry::new(beq::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "beq", id: Rel}),
+        OpcodeEntry::new(sbc::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "sbc", id: Iny}),
+        OpcodeEntry::new(kil::<ImpliedAddressing, B>, 0, false, OpcodeMarker{ name: "kil", id: Imp}),
+        OpcodeEntry::new(isc::<IndirectYAddressing, B>, 8, false, OpcodeMarker{ name: "isc", id: Iny}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(sbc::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "sbc", id: Zpx}),
+        OpcodeEntry::new(inc::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "inc", id: Zpx}),
+        OpcodeEntry::new(isc::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "isc", id: Zpx}),
+        OpcodeEntry::new(sed::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "sed", id: Imp}),
+        OpcodeEntry::new(sbc::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "sbc", id: Aby}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(isc::<AbsoluteYAddressing, B>, 7, false, OpcodeMarker{ name: "isc", id: Aby}),
+        OpcodeEntry::new(nop::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "nop", id: Abx}),
+        OpcodeEntry::new(sbc::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "sbc", id: Abx}),
+        OpcodeEntry::new(inc::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "inc", id: Abx}),
+        OpcodeEntry::new(isc::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "isc", id: Abx}),
+        ];
+
+    /// 65C02 opcode table, same as above with the 65C02 differences.
+    #[rustfmt::skip]
+    pub(crate) const OPCODE_MATRIX_65C02: [OpcodeEntry<B>; 256] = [
         // 0x0 - 0xf
-        (brk::<ImpliedAddressing>, 7, false, OpcodeMarker{ name: "brk", id: Imp}),
-        (ora::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "ora", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (tsb::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "tsb", id: Zpg}),
-        (ora::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "ora", id: Zpg}),
-        (asl::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "asl", id: Zpg}),
-        (rmb0::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb0", id: Zpg}),
-        (php::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "php", id: Imp}),
-        (ora::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "ora", id: Imm}),
-        (asl::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "asl", id: Acc}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (tsb::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "tsb", id: Abs}),
-        (ora::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "ora", id: Abs}),
-        (asl::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "asl", id: Abs}),
-        (bbr0::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr0", id: Zpr}),
+        OpcodeEntry::new(brk::<ImpliedAddressing, B>, 7, false, OpcodeMarker{ name: "brk", id: Imp}),
+        OpcodeEntry::new(ora::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "ora", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(tsb::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "tsb", id: Zpg}),
+        OpcodeEntry::new(ora::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "ora", id: Zpg}),
+        OpcodeEntry::new(asl::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "asl", id: Zpg}),
+        OpcodeEntry::new(rmb0::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb0", id: Zpg}),
+        OpcodeEntry::new(php::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "php", id: Imp}),
+        OpcodeEntry::new(ora::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "ora", id: Imm}),
+        OpcodeEntry::new(asl::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "asl", id: Acc}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(tsb::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "tsb", id: Abs}),
+        OpcodeEntry::new(ora::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "ora", id: Abs}),
+        OpcodeEntry::new(asl::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "asl", id: Abs}),
+        OpcodeEntry::new(bbr0::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr0", id: Zpr}),
 
         // 0x10 - 0x1f
-        (bpl::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bpl", id: Rel}),
-        (ora::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "ora", id: Iny}),
-        (ora::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "ora", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (trb::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "trb", id: Zpg}),
-        (ora::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "ora", id: Zpx}),
-        (asl::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "asl", id: Zpx}),
-        (rmb1::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb1", id: Zpg}),
-        (clc::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "clc", id: Imp}),
-        (ora::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "ora", id: Aby}),
-        (inc::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "inc", id: Acc}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (trb::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "trb", id: Abs}),
-        (ora::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "ora", id: Abx}),
-        (asl::<AbsoluteXAddressing>, 6, true, OpcodeMarker{ name: "asl", id: Abx}),
-        (bbr1::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr1", id: Zpr}),
+        OpcodeEntry::new(bpl::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bpl", id: Rel}),
+        OpcodeEntry::new(ora::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "ora", id: Iny}),
+        OpcodeEntry::new(ora::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "ora", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(trb::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "trb", id: Zpg}),
+        OpcodeEntry::new(ora::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "ora", id: Zpx}),
+        OpcodeEntry::new(asl::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "asl", id: Zpx}),
+        OpcodeEntry::new(rmb1::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb1", id: Zpg}),
+        OpcodeEntry::new(clc::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "clc", id: Imp}),
+        OpcodeEntry::new(ora::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "ora", id: Aby}),
+        OpcodeEntry::new(inc::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "inc", id: Acc}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(trb::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "trb", id: Abs}),
+        OpcodeEntry::new(ora::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "ora", id: Abx}),
+        OpcodeEntry::new(asl::<AbsoluteXAddressing, B>, 6, true, OpcodeMarker{ name: "asl", id: Abx}),
+        OpcodeEntry::new(bbr1::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr1", id: Zpr}),
 
         // 0x20 - 0x2f
-        (jsr::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "jsr", id: Abs}),
-        (and::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "and", id: Abx}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (bit::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "bit", id: Zpg}),
-        (and::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "and", id: Zpg}),
-        (rol::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rol", id: Zpg}),
-        (rmb2::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb2", id: Zpg}),
-        (plp::<ImpliedAddressing>, 4, false, OpcodeMarker{ name: "plp", id: Imp}),
-        (and::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "and", id: Imm}),
-        (rol::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "rol", id: Acc}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (bit::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "bit", id: Abs}),
-        (and::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "and", id: Abs}),
-        (rol::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "rol", id: Abs}),
-        (bbr2::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr2", id: Zpr}),
+        OpcodeEntry::new(jsr::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "jsr", id: Abs}),
+        OpcodeEntry::new(and::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "and", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(bit::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "bit", id: Zpg}),
+        OpcodeEntry::new(and::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "and", id: Zpg}),
+        OpcodeEntry::new(rol::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rol", id: Zpg}),
+        OpcodeEntry::new(rmb2::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb2", id: Zpg}),
+        OpcodeEntry::new(plp::<ImpliedAddressing, B>, 4, false, OpcodeMarker{ name: "plp", id: Imp}),
+        OpcodeEntry::new(and::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "and", id: Imm}),
+        OpcodeEntry::new(rol::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "rol", id: Acc}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(bit::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "bit", id: Abs}),
+        OpcodeEntry::new(and::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "and", id: Abs}),
+        OpcodeEntry::new(rol::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "rol", id: Abs}),
+        OpcodeEntry::new(bbr2::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr2", id: Zpr}),
 
         // 0x30 - 0x3f
-        (bmi::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bmi", id: Rel}),
-        (and::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "and", id: Iny}),
-        (and::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "and", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (bit::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "bit", id: Zpx}),
-        (and::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "and", id: Zpx}),
-        (rol::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "rol", id: Zpx}),
-        (rmb3::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb3", id: Zpg}),
-        (sec::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "sec", id: Imp}),
-        (and::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "and", id: Aby}),
-        (dec::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "dec", id: Acc}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (bit::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "bit", id: Abx}),
-        (and::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "and", id: Abx}),
-        (rol::<AbsoluteXAddressing>, 6, true, OpcodeMarker{ name: "rol", id: Abx}),
-        (bbr3::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr3", id: Zpr}),
+        OpcodeEntry::new(bmi::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bmi", id: Rel}),
+        OpcodeEntry::new(and::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "and", id: Iny}),
+        OpcodeEntry::new(and::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "and", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(bit::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "bit", id: Zpx}),
+        OpcodeEntry::new(and::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "and", id: Zpx}),
+        OpcodeEntry::new(rol::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "rol", id: Zpx}),
+        OpcodeEntry::new(rmb3::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb3", id: Zpg}),
+        OpcodeEntry::new(sec::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "sec", id: Imp}),
+        OpcodeEntry::new(and::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "and", id: Aby}),
+        OpcodeEntry::new(dec::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "dec", id: Acc}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(bit::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "bit", id: Abx}),
+        OpcodeEntry::new(and::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "and", id: Abx}),
+        OpcodeEntry::new(rol::<AbsoluteXAddressing, B>, 6, true, OpcodeMarker{ name: "rol", id: Abx}),
+        OpcodeEntry::new(bbr3::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr3", id: Zpr}),
 
         // 0x40 - 0x4f
-        (rti::<ImpliedAddressing>, 6, false, OpcodeMarker{ name: "rti", id: Imp}),
-        (eor::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "eor", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (nop::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
-        (eor::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "eor", id: Zpg}),
-        (lsr::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "lsr", id: Zpg}),
-        (rmb4::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb4", id: Zpg}),
-        (pha::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "pha", id: Imp}),
-        (eor::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "eor", id: Imm}),
-        (lsr::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "lsr", id: Acc}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (jmp::<AbsoluteAddressing>, 3, false, OpcodeMarker{ name: "jmp", id: Abs}),
-        (eor::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "eor", id: Abs}),
-        (lsr::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "lsr", id: Abs}),
-        (bbr4::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr4", id: Zpr}),
+        OpcodeEntry::new(rti::<ImpliedAddressing, B>, 6, false, OpcodeMarker{ name: "rti", id: Imp}),
+        OpcodeEntry::new(eor::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "eor", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(nop::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "nop", id: Zpg}),
+        OpcodeEntry::new(eor::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "eor", id: Zpg}),
+        OpcodeEntry::new(lsr::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "lsr", id: Zpg}),
+        OpcodeEntry::new(rmb4::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb4", id: Zpg}),
+        OpcodeEntry::new(pha::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "pha", id: Imp}),
+        OpcodeEntry::new(eor::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "eor", id: Imm}),
+        OpcodeEntry::new(lsr::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "lsr", id: Acc}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(jmp::<AbsoluteAddressing, B>, 3, false, OpcodeMarker{ name: "jmp", id: Abs}),
+        OpcodeEntry::new(eor::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "eor", id: Abs}),
+        OpcodeEntry::new(lsr::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "lsr", id: Abs}),
+        OpcodeEntry::new(bbr4::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr4", id: Zpr}),
 
         // 0x50 - 0x5f
-        (bvc::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bvc", id: Rel}),
-        (eor::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "eor", id: Iny}),
-        (eor::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "eor", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (eor::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "eor", id: Zpx}),
-        (lsr::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "lsr", id: Zpx}),
-        (rmb5::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb5", id: Zpg}),
-        (cli::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "cli", id: Imp}),
-        (eor::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "eor", id: Aby}),
-        (phy::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "phy", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (nop::<AbsoluteAddressing>, 8, false, OpcodeMarker{ name: "nop", id: Abs}),
-        (eor::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "eor", id: Abx}),
-        (lsr::<AbsoluteXAddressing>, 6, true, OpcodeMarker{ name: "lsr", id: Abx}),
-        (bbr5::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr5", id: Zpr}),
+        OpcodeEntry::new(bvc::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bvc", id: Rel}),
+        OpcodeEntry::new(eor::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "eor", id: Iny}),
+        OpcodeEntry::new(eor::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "eor", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(eor::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "eor", id: Zpx}),
+        OpcodeEntry::new(lsr::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "lsr", id: Zpx}),
+        OpcodeEntry::new(rmb5::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb5", id: Zpg}),
+        OpcodeEntry::new(cli::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "cli", id: Imp}),
+        OpcodeEntry::new(eor::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "eor", id: Aby}),
+        OpcodeEntry::new(phy::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "phy", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(nop::<AbsoluteAddressing, B>, 8, false, OpcodeMarker{ name: "nop", id: Abs}),
+        OpcodeEntry::new(eor::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "eor", id: Abx}),
+        OpcodeEntry::new(lsr::<AbsoluteXAddressing, B>, 6, true, OpcodeMarker{ name: "lsr", id: Abx}),
+        OpcodeEntry::new(bbr5::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr5", id: Zpr}),
 
         // 0x60 - 0x6f
-        (rts::<ImpliedAddressing>, 6, false, OpcodeMarker{ name: "rts", id: Imp}),
-        (adc::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "adc", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (stz::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "stz", id: Zpg}),
-        (adc::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "adc", id: Zpg}),
-        (ror::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "ror", id: Zpg}),
-        (rmb6::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb6", id: Zpg}),
-        (pla::<ImpliedAddressing>, 4, false, OpcodeMarker{ name: "pla", id: Imp}),
-        (adc::<ImmediateAddressing>, 2, true, OpcodeMarker{ name: "adc", id: Imm}),
-        (ror::<AccumulatorAddressing>, 2, false, OpcodeMarker{ name: "ror", id: Acc}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (jmp::<IndirectAddressing>, 6, false, OpcodeMarker{ name: "jmp", id: Ind}),
-        (adc::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "adc", id: Abs}),
-        (ror::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "ror", id: Abs}),
-        (bbr6::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr6", id: Zpr}),
+        OpcodeEntry::new(rts::<ImpliedAddressing, B>, 6, false, OpcodeMarker{ name: "rts", id: Imp}),
+        OpcodeEntry::new(adc::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "adc", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(stz::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "stz", id: Zpg}),
+        OpcodeEntry::new(adc::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "adc", id: Zpg}),
+        OpcodeEntry::new(ror::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "ror", id: Zpg}),
+        OpcodeEntry::new(rmb6::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb6", id: Zpg}),
+        OpcodeEntry::new(pla::<ImpliedAddressing, B>, 4, false, OpcodeMarker{ name: "pla", id: Imp}),
+        OpcodeEntry::new(adc::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "adc", id: Imm}),
+        OpcodeEntry::new(ror::<AccumulatorAddressing, B>, 2, false, OpcodeMarker{ name: "ror", id: Acc}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(jmp::<IndirectAddressing, B>, 6, false, OpcodeMarker{ name: "jmp", id: Ind}),
+        OpcodeEntry::new(adc::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "adc", id: Abs}),
+        OpcodeEntry::new(ror::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "ror", id: Abs}),
+        OpcodeEntry::new(bbr6::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr6", id: Zpr}),
 
         // 0x70 - 0x7f
-        (bvs::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bvs", id: Rel}),
-        (adc::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "adc", id: Iny}),
-        (adc::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "adc", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (stz::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "stz", id: Zpx}),
-        (adc::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "adc", id: Zpx}),
-        (ror::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "ror", id: Zpx}),
-        (rmb7::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "rmb7", id: Zpg}),
-        (sei::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "sei", id: Imp}),
-        (adc::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "adc", id: Aby}),
-        (ply::<ImpliedAddressing>, 4, false, OpcodeMarker{ name: "ply", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (jmp::<AbsoluteIndirectXAddressing>, 6, false, OpcodeMarker{ name: "jmp", id: Aix}),
-        (adc::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "adc", id: Abx}),
-        (ror::<AbsoluteXAddressing>, 7, true, OpcodeMarker{ name: "ror", id: Abx}),
-        (bbr7::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbr7", id: Zpr}),
+        OpcodeEntry::new(bvs::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bvs", id: Rel}),
+        OpcodeEntry::new(adc::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "adc", id: Iny}),
+        OpcodeEntry::new(adc::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "adc", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(stz::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "stz", id: Zpx}),
+        OpcodeEntry::new(adc::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "adc", id: Zpx}),
+        OpcodeEntry::new(ror::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "ror", id: Zpx}),
+        OpcodeEntry::new(rmb7::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "rmb7", id: Zpg}),
+        OpcodeEntry::new(sei::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "sei", id: Imp}),
+        OpcodeEntry::new(adc::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "adc", id: Aby}),
+        OpcodeEntry::new(ply::<ImpliedAddressing, B>, 4, false, OpcodeMarker{ name: "ply", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(jmp::<AbsoluteIndirectXAddressing, B>, 6, false, OpcodeMarker{ name: "jmp", id: Aix}),
+        OpcodeEntry::new(adc::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "adc", id: Abx}),
+        OpcodeEntry::new(ror::<AbsoluteXAddressing, B>, 6, true, OpcodeMarker{ name: "ror", id: Abx}),
+        OpcodeEntry::new(bbr7::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbr7", id: Zpr}),
 
         // 0x80 - 0x8f
-        (bra::<RelativeAddressing>, 3, true, OpcodeMarker{ name: "bra", id: Rel}),
-        (sta::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "sta", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (sty::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "sty", id: Zpg}),
-        (sta::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "sta", id: Zpg}),
-        (stx::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "stx", id: Zpg}),
-        (smb0::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb0", id: Zpg}),
-        (dey::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "dey", id: Imp}),
-        (bit::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "bit", id: Imm}),
-        (txa::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "txa", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (sty::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "sty", id: Abs}),
-        (sta::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "sta", id: Abs}),
-        (stx::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "stx", id: Abs}),
-        (bbs0::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs0", id: Zpr}),
+        OpcodeEntry::new(bra::<RelativeAddressing, B>, 3, true, OpcodeMarker{ name: "bra", id: Rel}),
+        OpcodeEntry::new(sta::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "sta", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(sty::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "sty", id: Zpg}),
+        OpcodeEntry::new(sta::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "sta", id: Zpg}),
+        OpcodeEntry::new(stx::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "stx", id: Zpg}),
+        OpcodeEntry::new(smb0::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb0", id: Zpg}),
+        OpcodeEntry::new(dey::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "dey", id: Imp}),
+        OpcodeEntry::new(bit::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "bit", id: Imm}),
+        OpcodeEntry::new(txa::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "txa", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(sty::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "sty", id: Abs}),
+        OpcodeEntry::new(sta::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "sta", id: Abs}),
+        OpcodeEntry::new(stx::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "stx", id: Abs}),
+        OpcodeEntry::new(bbs0::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs0", id: Zpr}),
 
         // 0x90 - 0x9f
-        (bcc::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bcc", id: Rel}),
-        (sta::<IndirectYAddressing>, 6, false, OpcodeMarker{ name: "sta", id: Iny}),
-        (sta::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "kil", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (sty::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "sty", id: Zpx}),
-        (sta::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "sta", id: Zpx}),
-        (stx::<ZeroPageYAddressing>, 4, false, OpcodeMarker{ name: "stx", id: Zpy}),
-        (smb1::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb1", id: Zpg}),
-        (tya::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tya", id: Imp}),
-        (sta::<AbsoluteYAddressing>, 5, false, OpcodeMarker{ name: "sta", id: Aby}),
-        (txs::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "txs", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (stz::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "stz", id: Abs}),
-        (sta::<AbsoluteXAddressing>, 5, false, OpcodeMarker{ name: "sta", id: Abx}),
-        (stz::<AbsoluteXAddressing>, 5, false, OpcodeMarker{ name: "stz", id: Abx}),
-        (bbs1::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs1", id: Zpr}),
+        OpcodeEntry::new(bcc::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bcc", id: Rel}),
+        OpcodeEntry::new(sta::<IndirectYAddressing, B>, 6, false, OpcodeMarker{ name: "sta", id: Iny}),
+        OpcodeEntry::new(sta::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "sta", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(sty::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "sty", id: Zpx}),
+        OpcodeEntry::new(sta::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "sta", id: Zpx}),
+        OpcodeEntry::new(stx::<ZeroPageYAddressing, B>, 4, false, OpcodeMarker{ name: "stx", id: Zpy}),
+        OpcodeEntry::new(smb1::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb1", id: Zpg}),
+        OpcodeEntry::new(tya::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tya", id: Imp}),
+        OpcodeEntry::new(sta::<AbsoluteYAddressing, B>, 5, false, OpcodeMarker{ name: "sta", id: Aby}),
+        OpcodeEntry::new(txs::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "txs", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(stz::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "stz", id: Abs}),
+        OpcodeEntry::new(sta::<AbsoluteXAddressing, B>, 5, false, OpcodeMarker{ name: "sta", id: Abx}),
+        OpcodeEntry::new(stz::<AbsoluteXAddressing, B>, 5, false, OpcodeMarker{ name: "stz", id: Abx}),
+        OpcodeEntry::new(bbs1::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs1", id: Zpr}),
 
         // 0xa0 - 0xaf
-        (ldy::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "ldy", id: Imm}),
-        (lda::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "lda", id: Xin}),
-        (ldx::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "ldx", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (ldy::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "ldy", id: Zpg}),
-        (lda::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "lda", id: Zpg}),
-        (ldx::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "ldx", id: Zpg}),
-        (smb2::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb2", id: Zpg}),
-        (tay::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tay", id: Imp}),
-        (lda::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "lda", id: Imm}),
-        (tax::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tax", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (ldy::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "ldy", id: Abs}),
-        (lda::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "lda", id: Abs}),
-        (ldx::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "ldx", id: Abs}),
-        (bbs2::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs2", id: Zpr}),
+        OpcodeEntry::new(ldy::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "ldy", id: Imm}),
+        OpcodeEntry::new(lda::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "lda", id: Xin}),
+        OpcodeEntry::new(ldx::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "ldx", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(ldy::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "ldy", id: Zpg}),
+        OpcodeEntry::new(lda::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "lda", id: Zpg}),
+        OpcodeEntry::new(ldx::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "ldx", id: Zpg}),
+        OpcodeEntry::new(smb2::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb2", id: Zpg}),
+        OpcodeEntry::new(tay::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tay", id: Imp}),
+        OpcodeEntry::new(lda::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "lda", id: Imm}),
+        OpcodeEntry::new(tax::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tax", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(ldy::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "ldy", id: Abs}),
+        OpcodeEntry::new(lda::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "lda", id: Abs}),
+        OpcodeEntry::new(ldx::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "ldx", id: Abs}),
+        OpcodeEntry::new(bbs2::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs2", id: Zpr}),
 
         // 0xb0 - 0xbf
-        (bcs::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bcs", id: Rel}),
-        (lda::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "lda", id: Iny}),
-        (lda::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "lda", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (ldy::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "ldy", id: Zpx}),
-        (lda::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "lda", id: Zpx}),
-        (ldx::<ZeroPageYAddressing>, 4, false, OpcodeMarker{ name: "ldx", id: Zpy}),
-        (smb3::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb3", id: Zpg}),
-        (clv::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "clv", id: Imp}),
-        (lda::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "lda", id: Aby}),
-        (tsx::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "tsx", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (ldy::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "ldy", id: Abx}),
-        (lda::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "lda", id: Abx}),
-        (ldx::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "ldx", id: Aby}),
-        (bbs3::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs3", id: Zpr}),
+        OpcodeEntry::new(bcs::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bcs", id: Rel}),
+        OpcodeEntry::new(lda::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "lda", id: Iny}),
+        OpcodeEntry::new(lda::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "lda", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(ldy::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "ldy", id: Zpx}),
+        OpcodeEntry::new(lda::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "lda", id: Zpx}),
+        OpcodeEntry::new(ldx::<ZeroPageYAddressing, B>, 4, false, OpcodeMarker{ name: "ldx", id: Zpy}),
+        OpcodeEntry::new(smb3::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb3", id: Zpg}),
+        OpcodeEntry::new(clv::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "clv", id: Imp}),
+        OpcodeEntry::new(lda::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "lda", id: Aby}),
+        OpcodeEntry::new(tsx::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "tsx", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(ldy::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "ldy", id: Abx}),
+        OpcodeEntry::new(lda::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "lda", id: Abx}),
+        OpcodeEntry::new(ldx::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "ldx", id: Aby}),
+        OpcodeEntry::new(bbs3::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs3", id: Zpr}),
 
         // 0xc0 - 0xcf
-        (cpy::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "cpy", id: Imm}),
-        (cmp::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "cmp", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (cpy::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "cpy", id: Zpg}),
-        (cmp::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "cmp", id: Zpg}),
-        (dec::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "dec", id: Zpg}),
-        (smb4::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb4", id: Zpg}),
-        (iny::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "iny", id: Imp}),
-        (cmp::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "cmp", id: Imm}),
-        (dex::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "dex", id: Imp}),
-        (wai::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "wai", id: Imp}),
-        (cpy::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "cpy", id: Abs}),
-        (cmp::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "cmp", id: Abs}),
-        (dec::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "dec", id: Abs}),
-        (bbs4::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs4", id: Zpr}),
+        OpcodeEntry::new(cpy::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "cpy", id: Imm}),
+        OpcodeEntry::new(cmp::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "cmp", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(cpy::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "cpy", id: Zpg}),
+        OpcodeEntry::new(cmp::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "cmp", id: Zpg}),
+        OpcodeEntry::new(dec::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "dec", id: Zpg}),
+        OpcodeEntry::new(smb4::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb4", id: Zpg}),
+        OpcodeEntry::new(iny::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "iny", id: Imp}),
+        OpcodeEntry::new(cmp::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "cmp", id: Imm}),
+        OpcodeEntry::new(dex::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "dex", id: Imp}),
+        OpcodeEntry::new(wai::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "wai", id: Imp}),
+        OpcodeEntry::new(cpy::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "cpy", id: Abs}),
+        OpcodeEntry::new(cmp::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "cmp", id: Abs}),
+        OpcodeEntry::new(dec::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "dec", id: Abs}),
+        OpcodeEntry::new(bbs4::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs4", id: Zpr}),
 
         // 0xd0 - 0xdf
-        (bne::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "bne", id: Rel}),
-        (cmp::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "cmp", id: Iny}),
-        (cmp::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "cmp", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (cmp::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "cmp", id: Zpx}),
-        (dec::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "dec", id: Zpx}),
-        (smb5::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb5", id: Zpg}),
-        (cld::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "cld", id: Imp}),
-        (cmp::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "cmp", id: Aby}),
-        (phx::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "phx", id: Imp}),
-        (stp::<ImpliedAddressing>, 3, false, OpcodeMarker{ name: "stp", id: Imp}),
-        (nop::<AbsoluteAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abs}),
-        (cmp::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "cmp", id: Abx}),
-        (dec::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "dec", id: Abx}),
-        (bbs5::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs5", id: Zpr}),
+        OpcodeEntry::new(bne::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "bne", id: Rel}),
+        OpcodeEntry::new(cmp::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "cmp", id: Iny}),
+        OpcodeEntry::new(cmp::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "cmp", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(cmp::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "cmp", id: Zpx}),
+        OpcodeEntry::new(dec::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "dec", id: Zpx}),
+        OpcodeEntry::new(smb5::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb5", id: Zpg}),
+        OpcodeEntry::new(cld::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "cld", id: Imp}),
+        OpcodeEntry::new(cmp::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "cmp", id: Aby}),
+        OpcodeEntry::new(phx::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "phx", id: Imp}),
+        OpcodeEntry::new(stp::<ImpliedAddressing, B>, 3, false, OpcodeMarker{ name: "stp", id: Imp}),
+        OpcodeEntry::new(nop::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Abs}),
+        OpcodeEntry::new(cmp::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "cmp", id: Abx}),
+        OpcodeEntry::new(dec::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "dec", id: Abx}),
+        OpcodeEntry::new(bbs5::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs5", id: Zpr}),
 
         // 0xe0 - 0xef
-        (cpx::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "cpx", id: Imm}),
-        (sbc::<XIndirectAddressing>, 6, false, OpcodeMarker{ name: "sbc", id: Xin}),
-        (nop::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (cpx::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "cpx", id: Zpg}),
-        (sbc::<ZeroPageAddressing>, 3, false, OpcodeMarker{ name: "sbc", id: Zpg}),
-        (inc::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "inc", id: Zpg}),
-        (smb6::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb6", id: Zpg}),
-        (inx::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "inx", id: Imp}),
-        (sbc::<ImmediateAddressing>, 2, false, OpcodeMarker{ name: "sbc", id: Imm}),
-        (nop::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (cpx::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "cpx", id: Abs}),
-        (sbc::<AbsoluteAddressing>, 4, false, OpcodeMarker{ name: "sbc", id: Abs}),
-        (inc::<AbsoluteAddressing>, 6, false, OpcodeMarker{ name: "inc", id: Abs}),
-        (bbs6::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs6", id: Zpr}),
+        OpcodeEntry::new(cpx::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "cpx", id: Imm}),
+        OpcodeEntry::new(sbc::<XIndirectAddressing, B>, 6, false, OpcodeMarker{ name: "sbc", id: Xin}),
+        OpcodeEntry::new(nop::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(cpx::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "cpx", id: Zpg}),
+        OpcodeEntry::new(sbc::<ZeroPageAddressing, B>, 3, false, OpcodeMarker{ name: "sbc", id: Zpg}),
+        OpcodeEntry::new(inc::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "inc", id: Zpg}),
+        OpcodeEntry::new(smb6::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb6", id: Zpg}),
+        OpcodeEntry::new(inx::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "inx", id: Imp}),
+        OpcodeEntry::new(sbc::<ImmediateAddressing, B>, 2, false, OpcodeMarker{ name: "sbc", id: Imm}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(cpx::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "cpx", id: Abs}),
+        OpcodeEntry::new(sbc::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "sbc", id: Abs}),
+        OpcodeEntry::new(inc::<AbsoluteAddressing, B>, 6, false, OpcodeMarker{ name: "inc", id: Abs}),
+        OpcodeEntry::new(bbs6::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs6", id: Zpr}),
 
         // 0xf0 - 0xff
-        (beq::<RelativeAddressing>, 2, true, OpcodeMarker{ name: "beq", id: Rel}),
-        (sbc::<IndirectYAddressing>, 5, true, OpcodeMarker{ name: "sbc", id: Iny}),
-        (sbc::<IndirectZeroPageAddressing>, 5, false, OpcodeMarker{ name: "sbc", id: Izp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (nop::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
-        (sbc::<ZeroPageXAddressing>, 4, false, OpcodeMarker{ name: "sbc", id: Zpx}),
-        (inc::<ZeroPageXAddressing>, 6, false, OpcodeMarker{ name: "inc", id: Zpx}),
-        (smb7::<ZeroPageAddressing>, 5, false, OpcodeMarker{ name: "smb7", id: Zpg}),
-        (sed::<ImpliedAddressing>, 2, false, OpcodeMarker{ name: "sed", id: Imp}),
-        (sbc::<AbsoluteYAddressing>, 4, true, OpcodeMarker{ name: "sbc", id: Aby}),
-        (plx::<ImpliedAddressing>, 4, false, OpcodeMarker{ name: "plx", id: Imp}),
-        (nop::<ImpliedAddressing>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
-        (nop::<AbsoluteAddressing>, 4, true, OpcodeMarker{ name: "nop", id: Abs}),
-        (sbc::<AbsoluteXAddressing>, 4, true, OpcodeMarker{ name: "sbc", id: Abx}),
-        (inc::<AbsoluteXAddressing>, 7, false, OpcodeMarker{ name: "inc", id: Abx}),
-        (bbs7::<ZeroPageRelativeAddressing>, 5, false, OpcodeMarker{ name: "bbs7", id: Zpr}),
-    ];
- }
+        OpcodeEntry::new(beq::<RelativeAddressing, B>, 2, true, OpcodeMarker{ name: "beq", id: Rel}),
+        OpcodeEntry::new(sbc::<IndirectYAddressing, B>, 5, true, OpcodeMarker{ name: "sbc", id: Iny}),
+        OpcodeEntry::new(sbc::<IndirectZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "sbc", id: Izp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(nop::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Zpx}),
+        OpcodeEntry::new(sbc::<ZeroPageXAddressing, B>, 4, false, OpcodeMarker{ name: "sbc", id: Zpx}),
+        OpcodeEntry::new(inc::<ZeroPageXAddressing, B>, 6, false, OpcodeMarker{ name: "inc", id: Zpx}),
+        OpcodeEntry::new(smb7::<ZeroPageAddressing, B>, 5, false, OpcodeMarker{ name: "smb7", id: Zpg}),
+        OpcodeEntry::new(sed::<ImpliedAddressing, B>, 2, false, OpcodeMarker{ name: "sed", id: Imp}),
+        OpcodeEntry::new(sbc::<AbsoluteYAddressing, B>, 4, true, OpcodeMarker{ name: "sbc", id: Aby}),
+        OpcodeEntry::new(plx::<ImpliedAddressing, B>, 4, false, OpcodeMarker{ name: "plx", id: Imp}),
+        OpcodeEntry::new(nop::<ImpliedAddressing, B>, 1, false, OpcodeMarker{ name: "nop", id: Imp}),
+        OpcodeEntry::new(nop::<AbsoluteAddressing, B>, 4, false, OpcodeMarker{ name: "nop", id: Abs}),
+        OpcodeEntry::new(sbc::<AbsoluteXAddressing, B>, 4, true, OpcodeMarker{ name: "sbc", id: Abx}),
+        OpcodeEntry::new(inc::<AbsoluteXAddressing, B>, 7, false, OpcodeMarker{ name: "inc", id: Abx}),
+        OpcodeEntry::new(bbs7::<ZeroPageRelativeAddressing, B>, 5, false, OpcodeMarker{ name: "bbs7", id: Zpr}),
+        ];
+
+    /// the 1 byte, 1 cycle NOP in place of the bit instructions and WAI/STP, on the 65C02 variants without them.
+    pub(crate) const NOP_65C02: OpcodeEntry<B> = OpcodeEntry::new(
+        nop::<ImpliedAddressing, B>,
+        1,
+        false,
+        OpcodeMarker {
+            name: "nop",
+            id: Imp,
+        },
+    );
+}
+
+/**
+ * returns the opcode table entry for the given opcode byte and cpu type.
+ */
+#[inline]
+pub(crate) fn opcode_entry<'a, B: Bus>(opcode: u8, cpu_type: CpuType) -> &'a OpcodeEntry<B> {
+    if !cpu_type.is_65c02() {
+        return &Opcodes::<B>::OPCODE_MATRIX[opcode as usize];
+    }
+
+    // the 65C02 variants without the bit instructions ($x7/$xf) or WAI/STP ($cb/$db) have NOPs there
+    let is_bit = opcode & 0x7 == 0x7;
+    let is_wai_stp = opcode == 0xcb || opcode == 0xdb;
+    if (is_bit && !cpu_type.has_bit_instructions()) || (is_wai_stp && !cpu_type.has_wai_stp()) {
+        &Opcodes::<B>::NOP_65C02
+    } else {
+        &Opcodes::<B>::OPCODE_MATRIX_65C02[opcode as usize]
+    }
+}
 
 /**
  * helper to set Z and N flags in one shot, depending on val
  */
-fn set_zn_flags(c: &mut Cpu, val: u8) {
+fn set_zn_flags<B: Bus>(c: &mut Cpu<B>, val: u8) {
     c.set_cpu_flags(CpuFlags::Z, val == 0);
     c.set_cpu_flags(CpuFlags::N, utils::is_signed(val));
 }
 
+/**
+ * A+M+C -> A, with decimal mode support, shared by ADC and RRA: sets N, V, Z and C.
+ *
+ * decimal mode follows Bruce Clark's appendix (http://www.6502.org/tutorials/decimal_mode.html#A), valid for any
+ * operand, including the non-BCD ones: the accumulator and C by sequence 1, V by sequence 2 on both NMOS and 65C02, N
+ * by sequence 2 and Z from the binary sum on NMOS, N and Z from the accumulator on 65C02.
+ */
+fn add_with_carry<B: Bus>(c: &mut Cpu<B>, b: u8) {
+    let carry = c.is_cpu_flag_set(CpuFlags::C) as u16;
+    let (a, b) = (c.regs.a as u16, b as u16);
+    let bin_sum = a + b + carry;
+    if !c.is_decimal_mode() {
+        let o = (a ^ bin_sum) & (b ^ bin_sum) & 0x80;
+        c.set_cpu_flags(CpuFlags::C, bin_sum > 0xff);
+        c.set_cpu_flags(CpuFlags::V, o != 0);
+        c.regs.a = (bin_sum & 0xff) as u8;
+        set_zn_flags(c, c.regs.a);
+        return;
+    }
+
+    // seq. 1: add the low digits, on a decimal carry adjust them and propagate the half carry into the high digits
+    let mut al = (a & 0x0f) + (b & 0x0f) + carry;
+    if al >= 0x0a {
+        al = ((al + 0x06) & 0x0f) + 0x10;
+    }
+    let mut sum = (a & 0xf0) + (b & 0xf0) + al;
+    if sum >= 0xa0 {
+        sum += 0x60;
+    }
+    c.regs.a = (sum & 0xff) as u8;
+    c.set_cpu_flags(CpuFlags::C, sum >= 0x100);
+
+    // seq. 2: the same sum, signed and before adjusting the high digits
+    let signed = (a & 0xf0) as u8 as i8 as i16 + (b & 0xf0) as u8 as i8 as i16 + al as i16;
+    c.set_cpu_flags(CpuFlags::V, !(-128..=127).contains(&signed));
+    if c.is_65c02() {
+        set_zn_flags(c, c.regs.a);
+    } else {
+        c.set_cpu_flags(CpuFlags::N, signed & 0x80 != 0);
+        c.set_cpu_flags(CpuFlags::Z, bin_sum & 0xff == 0);
+    }
+}
+
+/**
+ * A-M-(1-C) -> A, with decimal mode support, shared by SBC and ISC: sets N, V, Z and C.
+ */
+fn subtract_with_borrow<B: Bus>(c: &mut Cpu<B>, b: u8) {
+    // perform non-bcd subtraction (regs.a-b-1+C)
+    let sub: u16 = (c.regs.a as u16)
+        .wrapping_sub(b as u16)
+        .wrapping_sub(1)
+        .wrapping_add(c.is_cpu_flag_set(CpuFlags::C) as u16);
+    let o = ((c.regs.a as u16) ^ sub) & ((c.regs.a as u16) ^ (b as u16)) & 0x80;
+    c.set_cpu_flags(CpuFlags::V, o != 0);
+
+    if c.is_decimal_mode() {
+        if c.is_65c02() {
+            // bcd, 65C02 adjusts the whole binary result
+            // http://www.6502.org/tutorials/decimal_mode.html#A (seq. 4)
+            let carry = c.is_cpu_flag_set(CpuFlags::C) as i16;
+            let lo: i16 = ((c.regs.a & 0x0f) as i16) - ((b & 0x0f) as i16) + carry - 1;
+            let mut res: i16 = (c.regs.a as i16) - (b as i16) + carry - 1;
+            if res < 0 {
+                res -= 0x60;
+            }
+            if lo < 0 {
+                res -= 0x06;
+            }
+            c.regs.a = (res & 0xff) as u8;
+        } else {
+            // bcd
+            let mut lo: u8 = (c.regs.a & 0x0f)
+                .wrapping_sub(b & 0x0f)
+                .wrapping_sub(1)
+                .wrapping_add(c.is_cpu_flag_set(CpuFlags::C) as u8);
+            let mut hi: u8 = (c.regs.a >> 4).wrapping_sub(b >> 4);
+            if lo & 0x10 != 0 {
+                lo = lo.wrapping_sub(6);
+                hi = hi.wrapping_sub(1);
+            }
+            if hi & 0x10 != 0 {
+                hi = hi.wrapping_sub(6);
+            }
+            c.regs.a = (hi << 4) | (lo & 0xf);
+        }
+    } else {
+        // normal
+        c.regs.a = (sub & 0xff) as u8;
+    }
+    c.set_cpu_flags(CpuFlags::C, sub < 0x100);
+    if c.is_decimal_mode() && !c.is_65c02() {
+        // on NMOS in decimal mode, N and Z come from the binary result
+        set_zn_flags(c, (sub & 0xff) as u8);
+    } else {
+        set_zn_flags(c, c.regs.a);
+    }
+}
+
+/**
+ * take the branch at pc (len bytes long), to the next instruction + the signed offset.
+ *
+ * returns true if the target is on a different page than the next instruction (branch penalty cycle).
+ */
+fn take_branch<B: Bus>(c: &mut Cpu<B>, len: i8, offset: u8) -> Result<bool, CpuError> {
+    let next_pc = c.regs.pc.wrapping_add(len as u16);
+    let (new_pc, cross) = addressing_modes::get_relative_branch_target(next_pc, offset);
+
+    // check for deadlock
+    if c.deadlock_detection && new_pc == c.regs.pc {
+        return Err(CpuError::new_default(
+            CpuErrorType::Deadlock,
+            c.regs.pc,
+            None,
+        ));
+    }
+    c.set_pc(new_pc);
+    Ok(cross)
+}
+
+/**
+ * the extra access of read-modify-write instructions, between the read and the write of the modified value: the NMOS 6502
+ * writes back the unmodified value, the 65C02 reads it again (unless disabled with Cpu::set_rmw_dummy_access()).
+ */
+fn rmw_dummy_access<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    tgt: u16,
+    b: u8,
+) -> Result<(), CpuError> {
+    if A::id() == AddressingModeId::Acc || !c.rmw_dummy_access {
+        return Ok(());
+    }
+    if c.is_65c02() {
+        A::load(c, d, tgt)?;
+    } else {
+        A::store(c, d, tgt, b)?;
+    }
+    Ok(())
+}
+
+/**
+ * read-modify-write the byte at tgt (load, dummy access, store), returns the (old, new) values.
+ */
+fn rmw<A: AddressingMode, B: Bus, F: FnOnce(u8) -> u8>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    tgt: u16,
+    f: F,
+) -> Result<(u8, u8), CpuError> {
+    let b = A::load(c, d, tgt)?;
+    rmw_dummy_access::<A, B>(c, d, tgt, b)?;
+    let res = f(b);
+    A::store(c, d, tgt, res)?;
+    Ok((b, res))
+}
+
 /**
  * push byte on the stack
  */
-pub(super) fn push_byte(c: &mut Cpu, d: Option<&Debugger>, b: u8) -> Result<(), CpuError> {
-    let mem = c.bus.get_memory();
+pub(super) fn push_byte<B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    b: u8,
+) -> Result<(), CpuError> {
     let addr = 0x100 + c.regs.s as usize;
-    mem.write_byte(addr, b)?;
+    c.journal_write(addr as u16);
+    c.bus.get_memory().write_byte(addr, b)?;
+    let old_s = c.regs.s;
     c.regs.s = c.regs.s.wrapping_sub(1);
     // handle breakpoint
     if d.is_some() {
         d.unwrap()
-            .handle_rw_breakpoint(c, addr as u16, BreakpointType::WRITE)?
+            .handle_rw_breakpoint(c, addr as u16, BreakpointType::WRITE)?;
+        d.unwrap().handle_stack_breakpoint(c, old_s, 1, true)?
     }
 
     // call callback if any
+    c.cover_access(addr as u16, true);
+    c.count_access(addr as u16, true);
     c.call_callback(addr as u16, b, 1, CpuOperation::Write);
     Ok(())
 }
@@ -688,8 +1019,9 @@ pub(super) fn push_byte(c: &mut Cpu, d: Option<&Debugger>, b: u8) -> Result<(),
 /**
  * pop byte off the stack
  */
-fn pop_byte(c: &mut Cpu, d: Option<&Debugger>) -> Result<u8, CpuError> {
+fn pop_byte<B: Bus>(c: &mut Cpu<B>, d: Option<&Debugger>) -> Result<u8, CpuError> {
     let mem = c.bus.get_memory();
+    let old_s = c.regs.s;
     c.regs.s = c.regs.s.wrapping_add(1);
     let addr = 0x100 + c.regs.s as usize;
     let b = mem.read_byte(addr)?;
@@ -697,31 +1029,42 @@ fn pop_byte(c: &mut Cpu, d: Option<&Debugger>) -> Result<u8, CpuError> {
     // handle breakpoint
     if d.is_some() {
         d.unwrap()
-            .handle_rw_breakpoint(c, addr as u16, BreakpointType::READ)?
+            .handle_rw_breakpoint(c, addr as u16, BreakpointType::READ)?;
+        d.unwrap().handle_stack_breakpoint(c, old_s, 1, false)?
     }
 
-    // call callback if any
-    c.call_callback(addr as u16, b, 1, CpuOperation::Read);
-    Ok(b)
+    // call callback if any, it may override the byte read
+    c.cover_access(addr as u16, false);
+    c.count_access(addr as u16, false);
+    Ok(c.call_read_callback(addr as u16, b, CpuOperation::Read))
 }
 
 /**
  * pop word off the stack
  */
-fn pop_word_le(c: &mut Cpu, d: Option<&Debugger>) -> Result<u16, CpuError> {
+pub(crate) fn pop_word_le<B: Bus>(c: &mut Cpu<B>, d: Option<&Debugger>) -> Result<u16, CpuError> {
     let mem = c.bus.get_memory();
-    c.regs.s = c.regs.s.wrapping_add(2);
-    let addr = 0x100 + (c.regs.s - 1) as usize;
 
-    let w = mem.read_word_le(addr)?;
+    // read byte by byte, each wrapping within page $01
+    let addr = 0x100 + c.regs.s.wrapping_add(1) as usize;
+    let lsb = mem.read_byte(addr)?;
+    let msb = mem.read_byte(0x100 + c.regs.s.wrapping_add(2) as usize)?;
+    let old_s = c.regs.s;
+    c.regs.s = c.regs.s.wrapping_add(2);
+    let w = ((msb as u16) << 8) | (lsb as u16);
 
     // handle breakpoint
     if d.is_some() {
         d.unwrap()
-            .handle_rw_breakpoint(c, addr as u16, BreakpointType::READ)?
+            .handle_rw_breakpoint(c, addr as u16, BreakpointType::READ)?;
+        d.unwrap().handle_stack_breakpoint(c, old_s, 2, false)?
     }
 
     // call callback if any
+    c.cover_access(addr as u16, false);
+    c.count_access(addr as u16, false);
+    c.cover_access(0x100 | ((addr as u16 + 1) & 0xff), false);
+    c.count_access(0x100 | ((addr as u16 + 1) & 0xff), false);
     c.call_callback(addr as u16, (w & 0xff) as u8, 2, CpuOperation::Read);
 
     Ok(w)
@@ -730,19 +1073,34 @@ fn pop_word_le(c: &mut Cpu, d: Option<&Debugger>) -> Result<u16, CpuError> {
 /**
  * push word on the stack
  */
-pub(super) fn push_word_le(c: &mut Cpu, d: Option<&Debugger>, w: u16) -> Result<(), CpuError> {
+pub(super) fn push_word_le<B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    w: u16,
+) -> Result<(), CpuError> {
+    // write msb first, byte by byte, each wrapping within page $01
+    let addr_msb = 0x100 + c.regs.s as usize;
+    let addr = 0x100 + c.regs.s.wrapping_sub(1) as usize;
+    c.journal_write(addr_msb as u16);
+    c.journal_write(addr as u16);
     let mem = c.bus.get_memory();
-    let addr = 0x100 + (c.regs.s - 1) as usize;
-    mem.write_word_le(addr, w)?;
+    mem.write_byte(addr_msb, (w >> 8) as u8)?;
+    mem.write_byte(addr, (w & 0xff) as u8)?;
+    let old_s = c.regs.s;
     c.regs.s = c.regs.s.wrapping_sub(2);
 
     // handle breakpoint
     if d.is_some() {
         d.unwrap()
-            .handle_rw_breakpoint(c, addr as u16, BreakpointType::WRITE)?
+            .handle_rw_breakpoint(c, addr as u16, BreakpointType::WRITE)?;
+        d.unwrap().handle_stack_breakpoint(c, old_s, 2, true)?
     }
 
     // call callback if any
+    c.cover_access(addr as u16, true);
+    c.count_access(addr as u16, true);
+    c.cover_access(0x100 | ((addr as u16 + 1) & 0xff), true);
+    c.count_access(0x100 | ((addr as u16 + 1) & 0xff), true);
     c.call_callback(addr as u16, (w & 0xff) as u8, 2, CpuOperation::Write);
     Ok(())
 }
@@ -776,11 +1134,11 @@ pub(super) fn push_word_le(c: &mut Cpu, d: Option<&Debugger>, w: u16) -> Result<
  * (indirect,X)	ADC (oper,X)	61	2	6  
  * (indirect),Y	ADC (oper),Y	71	2	5*
  *
- * ADC implementation (including decimal mode support) converted from c code, taken from https://github.com/DavidBuchanan314/6502-emu/blob/master/6502.c
+ * decimal mode implemented following Bruce Clark's appendix, see add_with_carry().
  */
 #[named]
-fn adc<A: AddressingMode>(
-    c: &mut Cpu,
+fn adc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -791,7 +1149,7 @@ fn adc<A: AddressingMode>(
     // get target_address
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     let mut cycles = in_cycles;
     if !decode_only {
@@ -799,48 +1157,50 @@ fn adc<A: AddressingMode>(
         let b = A::load(c, d, tgt)?;
 
         // perform the addition (regs.a+b+C)
-        let mut sum: u16;
-        if c.is_cpu_flag_set(CpuFlags::D) {
-            if c.cpu_type == CpuType::WDC65C02 {
-                // one extra cycle in decimal mode
-                cycles += 1;
-            }
-
-            // bcd
-            sum = ((c.regs.a as u16) & 0x0f)
-                .wrapping_add((b as u16) & 0x0f)
-                .wrapping_add(c.is_cpu_flag_set(CpuFlags::C) as u16);
-            if sum >= 10 {
-                sum = (sum.wrapping_sub(10)) | 0x10;
-            }
-            sum = sum
-                .wrapping_add((c.regs.a as u16) & 0xf0)
-                .wrapping_add((b as u16) & 0xf0);
-            if sum > 0x9f {
-                sum = sum.wrapping_add(0x60);
-            }
-        } else {
-            // normal
-            sum = (c.regs.a as u16)
-                .wrapping_add(b as u16)
-                .wrapping_add(c.is_cpu_flag_set(CpuFlags::C) as u16);
+        add_with_carry(c, b);
+        if c.is_decimal_mode() && c.is_65c02() {
+            // one extra cycle in decimal mode
+            cycles += 1;
         }
-        // set flags
-        c.set_cpu_flags(CpuFlags::C, sum > 0xff);
-        let o = ((c.regs.a as u16) ^ sum) & ((b as u16) ^ sum) & 0x80;
-        c.set_cpu_flags(CpuFlags::V, o != 0);
-        c.regs.a = (sum & 0xff) as u8;
-        set_zn_flags(c, c.regs.a);
     }
     Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
+/**
+ * shared by AHX/SHX/SHY/TAS: stores v AND (H+1) at tgt, H being the high byte of the base address (before indexing).
+ *
+ * when indexing crosses a page, the high byte of the address is replaced by the stored value.
+ *
+ * [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
+ */
+fn store_sh<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    tgt: u16,
+    index: u8,
+    v: u8,
+) -> Result<(), CpuError> {
+    let base = tgt.wrapping_sub(index as u16);
+    let h = (base >> 8) as u8;
+    let res = if c.sh_drop_and {
+        v
+    } else {
+        v & h.wrapping_add(1)
+    };
+    let mut address = tgt;
+    if base & 0xff00 != tgt & 0xff00 {
+        // page cross, the address high byte gets corrupted
+        address = ((res as u16) << 8) | (tgt & 0xff);
+    }
+    A::store(c, d, address, res)
+}
+
 /**
  * AHX (undoc) (aka SHA, aka AXA)
  *
  * Stores A AND X AND (high-byte of addr. + 1) at addr.
  *
- * unstable: sometimes 'AND (H+1)' is dropped, page boundary crossings may not work (with the high-byte of the value used as the high-byte of the address)
+ * unstable: sometimes 'AND (H+1)' is dropped. on page boundary crossings the high-byte of the value is used as the high-byte of the address.
  *
  * A AND X AND (H+1) -> M
  *
@@ -852,8 +1212,8 @@ fn adc<A: AddressingMode>(
  * (indirect),Y	SHA (oper),Y	93	2	    6  	†
  */
 #[named]
-fn ahx<A: AddressingMode>(
-    c: &mut Cpu,
+fn ahx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -865,24 +1225,13 @@ fn ahx<A: AddressingMode>(
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
 
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
-        // get msb from target address
-        let mut h = (tgt >> 8) as u8;
-
-        // add 1 on msb when page crossing
-        // [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
-        if extra_cycle_on_page_crossing {
-            h = h.wrapping_add(1);
-        }
-
         // A & X & (H + 1)
-        let res = c.regs.a & c.regs.x & h.wrapping_add(1);
-
-        // store
-        A::store(c, d, tgt, res)?;
+        let (x, y) = (c.regs.x, c.regs.y);
+        store_sh::<A, B>(c, d, tgt, y, c.regs.a & x)?;
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -901,28 +1250,27 @@ fn ahx<A: AddressingMode>(
  * immediate	ALR #oper	4B	2	    2
  */
 #[named]
-fn alr<A: AddressingMode>(
-    c: &mut Cpu,
+fn alr<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
-    opcode_byte: u8,
+    _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
-        // and (preserve flags, n and z are set in lfr)
-        let prev_p = c.regs.p;
-        and::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
-        c.regs.p = prev_p;
-
-        // lsr A
-        lsr::<AccumulatorAddressing>(c, d, opcode_byte, 0, false, decode_only, true)?;
+        // A AND oper, then LSR A: C is bit 0 of the AND result, N is always clear
+        let b = A::load(c, d, tgt)?;
+        let res = c.regs.a & b;
+        c.set_cpu_flags(CpuFlags::C, res & 1 != 0);
+        c.regs.a = res >> 1;
+        set_zn_flags(c, c.regs.a);
     }
 
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
@@ -942,8 +1290,8 @@ fn alr<A: AddressingMode>(
  * immediate	ANC #oper	0B	2	    2
  */
 #[named]
-fn anc<A: AddressingMode>(
-    c: &mut Cpu,
+fn anc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     opcode_byte: u8,
     in_cycles: usize,
@@ -953,12 +1301,12 @@ fn anc<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
         // and
-        and::<A>(c, d, opcode_byte, in_cycles, extra_cycle, decode_only, true)?;
+        and::<A, B>(c, d, opcode_byte, in_cycles, extra_cycle, decode_only, true)?;
         c.set_cpu_flags(CpuFlags::C, utils::is_signed(c.regs.a));
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
@@ -993,8 +1341,8 @@ fn anc<A: AddressingMode>(
 
 */
 #[named]
-fn and<A: AddressingMode>(
-    c: &mut Cpu,
+fn and<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1004,7 +1352,7 @@ fn and<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -1029,6 +1377,8 @@ fn and<A: AddressingMode>(
  * V-flag is set according to (A AND oper) + oper
  * The carry is not set, but bit 7 (sign) is exchanged with the carry
  *
+ * in binary mode C = bit 6 of the result, V = bit 6 XOR bit 5 of the result.
+ *
  * A AND oper, C -> [76543210] -> C
  *
  * N	Z	C	I	D	V
@@ -1040,8 +1390,8 @@ fn and<A: AddressingMode>(
  * implemented according to [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
  */
 #[named]
-fn arr<A: AddressingMode>(
-    c: &mut Cpu,
+fn arr<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     opcode_byte: u8,
     in_cycles: usize,
@@ -1051,32 +1401,28 @@ fn arr<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
-        if !c.is_cpu_flag_set(CpuFlags::D) {
+        if !c.is_decimal_mode() {
             // and
-            and::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+            and::<A, B>(c, d, opcode_byte, 0, false, decode_only, true)?;
 
             // ror A
-            let prev_a = c.regs.a;
-            ror::<AccumulatorAddressing>(c, d, opcode_byte, 0, false, decode_only, true)?;
-
-            // set carry and overflow
-            c.set_cpu_flags(CpuFlags::C, utils::is_signed(prev_a));
-            let is_bit_6_set = prev_a & 0b01000000;
-            c.set_cpu_flags(
-                CpuFlags::V,
-                (is_bit_6_set as i8 ^ utils::is_signed(prev_a) as i8) != 0,
-            );
-            set_zn_flags(c, c.regs.a);
+            ror::<AccumulatorAddressing, B>(c, d, opcode_byte, 0, false, decode_only, true)?;
+
+            // C = bit 6 of the result, V = bit 6 xor bit 5 of the result
+            let res = c.regs.a;
+            c.set_cpu_flags(CpuFlags::C, res & 0x40 != 0);
+            c.set_cpu_flags(CpuFlags::V, ((res >> 6) ^ (res >> 5)) & 1 != 0);
+            set_zn_flags(c, res);
         } else {
             // decimal
             // and
-            and::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+            and::<A, B>(c, d, opcode_byte, 0, false, decode_only, true)?;
             let and_res = c.regs.a;
-            ror::<AccumulatorAddressing>(c, d, opcode_byte, 0, false, decode_only, true)?;
+            ror::<AccumulatorAddressing, B>(c, d, opcode_byte, 0, false, decode_only, true)?;
 
             // fix for decimal
 
@@ -1132,8 +1478,8 @@ fn arr<A: AddressingMode>(
  * absolute,X	ASL oper,X	1E	3	    7
  */
 #[named]
-fn asl<A: AddressingMode>(
-    c: &mut Cpu,
+fn asl<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1143,12 +1489,13 @@ fn asl<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
         c.set_cpu_flags(CpuFlags::C, utils::is_signed(b));
 
         // shl
@@ -1180,8 +1527,8 @@ fn asl<A: AddressingMode>(
  * relative	    BCC oper	90	2	    2**
  */
 #[named]
-fn bcc<A: AddressingMode>(
-    c: &mut Cpu,
+fn bcc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1191,36 +1538,24 @@ fn bcc<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
-    // read operand
-    let b = A::load(c, d, tgt)?;
-
-    // branch
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
     if !decode_only {
+        // read operand
+        let b = A::load(c, d, tgt)?;
+
+        // branch
         if !c.is_cpu_flag_set(CpuFlags::C) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1243,8 +1578,8 @@ fn bcc<A: AddressingMode>(
  */
 
 #[named]
-fn bcs<A: AddressingMode>(
-    c: &mut Cpu,
+fn bcs<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1254,36 +1589,24 @@ fn bcs<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
-    // read operand
-    let b = A::load(c, d, tgt)?;
-
-    // branch
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
     if !decode_only {
+        // read operand
+        let b = A::load(c, d, tgt)?;
+
+        // branch
         if c.is_cpu_flag_set(CpuFlags::C) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1305,8 +1628,8 @@ fn bcs<A: AddressingMode>(
  * relative	    BEQ oper	F0	2	    2**
  */
 #[named]
-fn beq<A: AddressingMode>(
-    c: &mut Cpu,
+fn beq<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1316,37 +1639,24 @@ fn beq<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
-    // read operand
-    let b = A::load(c, d, tgt)?;
-
-    // branch
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
-
     if !decode_only {
+        // read operand
+        let b = A::load(c, d, tgt)?;
+
+        // branch
         if c.is_cpu_flag_set(CpuFlags::Z) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1375,8 +1685,8 @@ fn beq<A: AddressingMode>(
 
 */
 #[named]
-fn bit<A: AddressingMode>(
-    c: &mut Cpu,
+fn bit<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1386,7 +1696,7 @@ fn bit<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -1397,9 +1707,7 @@ fn bit<A: AddressingMode>(
         c.set_cpu_flags(CpuFlags::Z, and_res == 0);
 
         // on 65c02 and immediate mode, N and V are not affected
-        if c.cpu_type == CpuType::MOS6502
-            || (c.cpu_type == CpuType::WDC65C02 && A::id() != AddressingModeId::Imm)
-        {
+        if !c.is_65c02() || A::id() != AddressingModeId::Imm {
             c.set_cpu_flags(CpuFlags::N, utils::is_signed(b));
             c.set_cpu_flags(CpuFlags::V, b & 0b01000000 != 0);
         }
@@ -1426,8 +1734,8 @@ fn bit<A: AddressingMode>(
  * relative	    BMI oper	30	2	    2**
  */
 #[named]
-fn bmi<A: AddressingMode>(
-    c: &mut Cpu,
+fn bmi<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1437,38 +1745,24 @@ fn bmi<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
     if !decode_only {
         // read operand
         let b = A::load(c, d, tgt)?;
 
         // branch
         if c.is_cpu_flag_set(CpuFlags::N) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1490,8 +1784,8 @@ fn bmi<A: AddressingMode>(
  * relative	    BNE oper	D0	2	    2**
  */
 #[named]
-fn bne<A: AddressingMode>(
-    c: &mut Cpu,
+fn bne<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1501,37 +1795,24 @@ fn bne<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
     if !decode_only {
         // read operand
         let b = A::load(c, d, tgt)?;
 
         // branch
         if !c.is_cpu_flag_set(CpuFlags::Z) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1553,8 +1834,8 @@ fn bne<A: AddressingMode>(
  * relative	    BPL oper	10	2	    2**
  */
 #[named]
-fn bpl<A: AddressingMode>(
-    c: &mut Cpu,
+fn bpl<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1564,35 +1845,24 @@ fn bpl<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
-    // read operand
-    let b = A::load(c, d, tgt)?;
 
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
     if !decode_only {
+        // read operand
+        let b = A::load(c, d, tgt)?;
+
         // branch
         if !c.is_cpu_flag_set(CpuFlags::N) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1612,9 +1882,11 @@ fn bpl<A: AddressingMode>(
  * addressing	assembler	opc	bytes	cycles
  * implied	    BRK	        00	1	    7  
  */
+// the legacy must_trigger_* fields are still honored
+#[allow(deprecated)]
 #[named]
-fn brk<A: AddressingMode>(
-    c: &mut Cpu,
+fn brk<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1624,20 +1896,20 @@ fn brk<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
+        // a breakpoint triggered by the pushes or the vector fetch is reported once the sequence is complete
+        let mut bp: Option<CpuError> = None;
+
         // push pc and p on stack
-        push_word_le(c, d, c.regs.pc + 2)?;
+        defer_breakpoint(push_word_le(c, d, c.regs.pc.wrapping_add(2)), &mut bp)?;
 
         // push P with U and B set
-        // https://wiki.nesdev.com/w/index.php/Status_flags#The_B_flag
-        let mut flags = c.regs.p.clone();
-        flags.set(CpuFlags::B, true);
-        flags.set(CpuFlags::U, true);
-        push_byte(c, d, flags.bits())?;
+        let flags = c.regs.status_byte_for_push(true);
+        defer_breakpoint(push_byte(c, d, flags), &mut bp)?;
 
-        if c.cpu_type == CpuType::WDC65C02 {
+        if c.is_65c02() {
             // clear the D flag
             // http://6502.org/tutorials/65c02opcodes.html
             c.regs.p.set(CpuFlags::D, false);
@@ -1646,23 +1918,38 @@ fn brk<A: AddressingMode>(
         // set I
         c.set_cpu_flags(CpuFlags::I, true);
 
-        // set pc to address contained at irq vector
-        let addr = c.bus.get_memory().read_word_le(Vectors::IRQ as usize)?;
+        // on NMOS, an nmi asserted while BRK is executing hijacks it: the pushed flags still have B set, but the nmi vector is fetched.
+        // https://wiki.nesdev.com/w/index.php/CPU_interrupts#Interrupt_hijacking
+        let mut v = Vectors::IRQ;
+        let mut kind = InterruptKind::Brk;
+        if !c.is_65c02() && (c.must_trigger_nmi || c.nmi_latched) {
+            // the nmi is consumed
+            c.must_trigger_nmi = false;
+            c.nmi_latched = false;
+            v = Vectors::NMI;
+            kind = InterruptKind::Nmi;
+        }
+
+        // set pc to address contained at irq (or nmi, if hijacked) vector
+        let addr = c.fetch_vector(d, v, &mut bp)?;
 
         // check for deadlock
-        if addr == c.regs.pc {
+        if c.deadlock_detection && addr == c.regs.pc {
             return Err(CpuError::new_default(
                 CpuErrorType::Deadlock,
                 c.regs.pc,
                 None,
             ));
         }
-        c.regs.pc = addr;
+        c.set_pc(addr);
+
+        // the handler starts once BRK completes
+        c.interrupt_entered(kind, c.cycles + in_cycles);
+        if let Some(e) = bp {
+            return Err(e);
+        }
     }
-    Ok((
-        if decode_only { A::len() } else { 0 },
-        in_cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1684,8 +1971,8 @@ fn brk<A: AddressingMode>(
  * relative	    BVC oper	50	2	    2**
  */
 #[named]
-fn bvc<A: AddressingMode>(
-    c: &mut Cpu,
+fn bvc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1695,37 +1982,24 @@ fn bvc<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
-    // read operand
-    let b = A::load(c, d, tgt)?;
-
-    // branch
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
-
     if !decode_only {
+        // read operand
+        let b = A::load(c, d, tgt)?;
+
+        // branch
         if !c.is_cpu_flag_set(CpuFlags::V) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1747,8 +2021,8 @@ fn bvc<A: AddressingMode>(
  * relative	    BVS oper	70	2	    2**
  */
 #[named]
-fn bvs<A: AddressingMode>(
-    c: &mut Cpu,
+fn bvs<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1758,37 +2032,24 @@ fn bvs<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
-    // read operand
-    let b = A::load(c, d, tgt)?;
-
-    // branch
     let mut cycles = in_cycles;
-    let mut taken: bool = false;
-
     if !decode_only {
+        // read operand
+        let b = A::load(c, d, tgt)?;
+
+        // branch
         if c.is_cpu_flag_set(CpuFlags::V) {
-            // branch is taken, add another cycle
+            // branch is taken, add another cycle (and another one if the target is on a different page)
             cycles += 1;
-            taken = true;
-            let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -1810,9 +2071,9 @@ fn bvs<A: AddressingMode>(
  * implied	    CLC	        18	1	    2  
  */
 #[named]
-fn clc<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn clc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -1821,7 +2082,7 @@ fn clc<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // clear carry
@@ -1849,9 +2110,9 @@ fn clc<A: AddressingMode>(
  * implied	    CLD	        D8	1	    2  
  */
 #[named]
-fn cld<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn cld<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -1860,7 +2121,7 @@ fn cld<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -1888,10 +2149,12 @@ fn cld<A: AddressingMode>(
  * addressing	assembler	opc	bytes	cycles
  * implied	    CLI	        58	1	    2  
  */
+// the legacy must_trigger_* fields are still honored
+#[allow(deprecated)]
 #[named]
-fn cli<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn cli<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -1900,10 +2163,11 @@ fn cli<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // enable interrupts, clear the flag
+        // enable interrupts, clear the flag (irq polling sees the change after the next instruction)
+        c.delay_i_flag_change(c.is_cpu_flag_set(CpuFlags::I));
         c.set_cpu_flags(CpuFlags::I, false);
 
         if c.irq_pending {
@@ -1933,9 +2197,9 @@ fn cli<A: AddressingMode>(
  * implied	    CLV	        B8	1	    2  
  */
 #[named]
-fn clv<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn clv<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -1944,7 +2208,7 @@ fn clv<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // clear the overflow flag
@@ -1981,8 +2245,8 @@ fn clv<A: AddressingMode>(
  * (indirect),Y	CMP (oper),Y	D1	2	    5*
  */
 #[named]
-fn cmp<A: AddressingMode>(
-    c: &mut Cpu,
+fn cmp<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -1992,7 +2256,7 @@ fn cmp<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2029,8 +2293,8 @@ fn cmp<A: AddressingMode>(
  * absolute	    CPX oper	EC	3	    4  
  */
 #[named]
-fn cpx<A: AddressingMode>(
-    c: &mut Cpu,
+fn cpx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2040,7 +2304,7 @@ fn cpx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2077,8 +2341,8 @@ fn cpx<A: AddressingMode>(
  * absolute	    CPY oper	CC	3	    4  
  */
 #[named]
-fn cpy<A: AddressingMode>(
-    c: &mut Cpu,
+fn cpy<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2088,7 +2352,7 @@ fn cpy<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2122,25 +2386,24 @@ fn cpy<A: AddressingMode>(
  * (indirect),Y	DCP (oper),Y	D3	2	    8
  */
 #[named]
-fn dcp<A: AddressingMode>(
-    c: &mut Cpu,
+fn dcp<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
-    opcode_byte: u8,
+    _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // perform dec + cmp internally (flags are set according to cmp, so save before)
-        let prev_p = c.regs.p;
-        dec::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
-        c.regs.p = prev_p;
-        cmp::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+        // M - 1 -> M, then compare A with the new M: C = A >= M, N and Z from A - M
+        let (_, m) = rmw::<A, B, _>(c, d, tgt, |b| b.wrapping_sub(1))?;
+        c.set_cpu_flags(CpuFlags::C, c.regs.a >= m);
+        set_zn_flags(c, c.regs.a.wrapping_sub(m));
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -2169,8 +2432,8 @@ fn dcp<A: AddressingMode>(
 * absolute,X	DEC oper,X	DE	3	    7  
 */
 #[named]
-fn dec<A: AddressingMode>(
-    c: &mut Cpu,
+fn dec<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2180,11 +2443,12 @@ fn dec<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
         b = b.wrapping_sub(1);
         set_zn_flags(c, b);
 
@@ -2215,9 +2479,9 @@ fn dec<A: AddressingMode>(
  * implied	    DEX	        CA	1	    2  
 */
 #[named]
-fn dex<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn dex<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -2226,7 +2490,7 @@ fn dex<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.x = c.regs.x.wrapping_sub(1);
@@ -2256,9 +2520,9 @@ fn dex<A: AddressingMode>(
  * implied	    DEY	        88	1	    2  
  */
 #[named]
-fn dey<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn dey<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -2267,7 +2531,7 @@ fn dey<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -2306,8 +2570,8 @@ fn dey<A: AddressingMode>(
  */
 
 #[named]
-fn eor<A: AddressingMode>(
-    c: &mut Cpu,
+fn eor<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2317,7 +2581,7 @@ fn eor<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2353,8 +2617,8 @@ fn eor<A: AddressingMode>(
  * absolute,X	INC oper,X	FE	3	    7
 */
 #[named]
-fn inc<A: AddressingMode>(
-    c: &mut Cpu,
+fn inc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2364,11 +2628,12 @@ fn inc<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
 
         b = b.wrapping_add(1);
         set_zn_flags(c, b);
@@ -2400,9 +2665,9 @@ fn inc<A: AddressingMode>(
  * implied	    INX	        E8	1	    2  
  */
 #[named]
-fn inx<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn inx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -2411,7 +2676,7 @@ fn inx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.x = c.regs.x.wrapping_add(1);
@@ -2441,9 +2706,9 @@ fn inx<A: AddressingMode>(
  * implied	    INY	        C8	1	    2  
  */
 #[named]
-fn iny<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn iny<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -2452,7 +2717,7 @@ fn iny<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.y = c.regs.y.wrapping_add(1);
@@ -2482,30 +2747,23 @@ fn iny<A: AddressingMode>(
  */
 
 #[named]
-fn isc<A: AddressingMode>(
-    c: &mut Cpu,
+fn isc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
-    opcode_byte: u8,
+    _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // perform inc + sbc internally (sbc sets p, preserve carry flag after inc)
-        let prev_p = c.regs.p;
-        inc::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
-
-        // preserve carry
-        let is_c_set = c.is_cpu_flag_set(CpuFlags::C);
-        c.regs.p = prev_p;
-        c.set_cpu_flags(CpuFlags::C, is_c_set);
-
-        sbc::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+        // M + 1 -> M, then A - M - (1 - C) -> A: N, V, Z and C as SBC (including decimal mode)
+        let (_, m) = rmw::<A, B, _>(c, d, tgt, |b| b.wrapping_add(1))?;
+        subtract_with_borrow(c, m);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -2530,22 +2788,29 @@ fn isc<A: AddressingMode>(
  * indirect	    JMP (oper)	6C	3	    5  
  */
 #[named]
-fn jmp<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn jmp<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (mut tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
+        if let Some((lo, hi)) = A::pointer(c)? {
+            // indirect, the pointer reads are visible to the hooks and the read breakpoints
+            let lsb = A::load(c, d, lo)?;
+            let msb = A::load(c, d, hi)?;
+            tgt = ((msb as u16) << 8) | (lsb as u16);
+        }
+
         // check for deadlock
-        if tgt == c.regs.pc {
+        if c.deadlock_detection && tgt == c.regs.pc {
             return Err(CpuError::new_default(
                 CpuErrorType::Deadlock,
                 c.regs.pc,
@@ -2553,13 +2818,10 @@ fn jmp<A: AddressingMode>(
             ));
         }
         // set pc
-        c.regs.pc = tgt;
+        c.set_pc(tgt);
     }
 
-    Ok((
-        if decode_only { A::len() } else { 0 },
-        in_cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -2581,8 +2843,8 @@ fn jmp<A: AddressingMode>(
  * absolute	    JSR oper	20	3	    6  
  */
 #[named]
-fn jsr<A: AddressingMode>(
-    c: &mut Cpu,
+fn jsr<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2592,7 +2854,7 @@ fn jsr<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // push return address
@@ -2603,7 +2865,7 @@ fn jsr<A: AddressingMode>(
         )?;
 
         // check for deadlock
-        if tgt == c.regs.pc {
+        if c.deadlock_detection && tgt == c.regs.pc {
             return Err(CpuError::new_default(
                 CpuErrorType::Deadlock,
                 c.regs.pc,
@@ -2611,39 +2873,45 @@ fn jsr<A: AddressingMode>(
             ));
         }
         // set pc
-        c.regs.pc = tgt;
+        c.set_pc(tgt);
     }
-    Ok((
-        if decode_only { A::len() } else { 0 },
-        in_cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
  * CPU JAM!
+ *
+ * the cpu is stuck (pc does not advance, interrupts are ignored) until reset, or an InvalidOpcode error is
+ * returned if Cpu::set_strict_jam() is set.
  */
 #[named]
-fn kil<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
-    _opcode_byte: u8,
-    _in_cycles: usize,
+fn kil<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
+    opcode_byte: u8,
+    in_cycles: usize,
     _extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    // this is an invalid opcode and emulation should be halted!
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if decode_only {
         // perform decode only, no execution
         return Ok((A::len(), 0));
     }
-    // invalid !
-    let mut e = CpuError::new_default(CpuErrorType::InvalidOpcode, c.regs.pc, None);
-    e.address = c.regs.pc as usize;
-    Err(e)
+    if c.strict_jam {
+        // invalid !
+        let mut e = CpuError::new_default(CpuErrorType::InvalidOpcode, c.regs.pc, None);
+        e.address = c.regs.pc as usize;
+        return Err(e);
+    }
+
+    // the cpu is stuck on the opcode until reset
+    c.jam(opcode_byte);
+    c.set_pc(c.regs.pc);
+    Ok((A::len(), in_cycles))
 }
 
 /**
@@ -2661,8 +2929,8 @@ fn kil<A: AddressingMode>(
  */
 
 #[named]
-fn las<A: AddressingMode>(
-    c: &mut Cpu,
+fn las<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2672,7 +2940,7 @@ fn las<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // get operand
@@ -2706,8 +2974,8 @@ fn las<A: AddressingMode>(
  * (indirect),Y	    LAX (oper),Y	B3	2	    5*
 */
 #[named]
-fn lax<A: AddressingMode>(
-    c: &mut Cpu,
+fn lax<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2717,7 +2985,7 @@ fn lax<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2756,8 +3024,8 @@ fn lax<A: AddressingMode>(
  * (indirect),Y	LDA (oper),Y	B1	2	    5*
  */
 #[named]
-fn lda<A: AddressingMode>(
-    c: &mut Cpu,
+fn lda<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2767,7 +3035,7 @@ fn lda<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2802,8 +3070,8 @@ fn lda<A: AddressingMode>(
  * absolute,Y	LDX oper,Y	BE	3	    4*
  */
 #[named]
-fn ldx<A: AddressingMode>(
-    c: &mut Cpu,
+fn ldx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2813,7 +3081,7 @@ fn ldx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2848,8 +3116,8 @@ fn ldx<A: AddressingMode>(
  * absolute,X	LDY oper,X	BC	3	4*
  */
 #[named]
-fn ldy<A: AddressingMode>(
-    c: &mut Cpu,
+fn ldy<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2859,7 +3127,7 @@ fn ldy<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -2896,8 +3164,8 @@ fn ldy<A: AddressingMode>(
  * absolute,X	LSR oper,X	5E	3	    7  
  */
 #[named]
-fn lsr<A: AddressingMode>(
-    c: &mut Cpu,
+fn lsr<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2907,11 +3175,12 @@ fn lsr<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
 
         // save bit 0 in the carry
         c.set_cpu_flags(CpuFlags::C, b & 1 != 0);
@@ -2943,8 +3212,8 @@ fn lsr<A: AddressingMode>(
  * immediate	LXA #oper	AB	2	2  	††
 */
 #[named]
-fn lxa<A: AddressingMode>(
-    c: &mut Cpu,
+fn lxa<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -2954,20 +3223,18 @@ fn lxa<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let b = A::load(c, d, tgt)?;
 
-        // N and Z are set according to the value of the accumulator before the instruction executed
-        set_zn_flags(c, c.regs.a);
-
-        // we choose $ee as constant as specified in [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
-        let k = 0xee;
+        // constant defaults to $ee as specified in [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
+        let k = c.lxa_magic;
         let res: u8 = (c.regs.a | k) & b;
         c.regs.x = res;
         c.regs.a = res;
+        set_zn_flags(c, res);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -2989,12 +3256,22 @@ fn lxa<A: AddressingMode>(
  *
  * addressing	assembler	opc	bytes	cycles
  * implied	    NOP	        EA	1	    2     
+ *
+ * on the 65C02 the reserved opcodes are NOPs too, with the following sizes and cycles:
+ *
+ * opc	                        bytes	cycles
+ * x3, xB (except CB, DB)	    1	    1
+ * 02 22 42 62 82 C2 E2	        2	    2
+ * 44	                        2	    3
+ * 54 D4 F4	                    2	    4
+ * 5C	                        3	    8
+ * DC FC	                    3	    4
 */
 
 #[named]
-fn nop<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn nop<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -3003,7 +3280,7 @@ fn nop<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     // noop, do nothing ...
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
@@ -3036,8 +3313,8 @@ fn nop<A: AddressingMode>(
  * (indirect),Y	ORA (oper),Y	11	2	    5*
  */
 #[named]
-fn ora<A: AddressingMode>(
-    c: &mut Cpu,
+fn ora<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3047,7 +3324,7 @@ fn ora<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -3078,8 +3355,8 @@ fn ora<A: AddressingMode>(
  * implied	    PHA	        48	1	    3  
  */
 #[named]
-fn pha<A: AddressingMode>(
-    c: &mut Cpu,
+fn pha<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3089,7 +3366,7 @@ fn pha<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -3119,8 +3396,8 @@ fn pha<A: AddressingMode>(
  * implied	    PHP	        08	1	    3  
  */
 #[named]
-fn php<A: AddressingMode>(
-    c: &mut Cpu,
+fn php<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3130,14 +3407,12 @@ fn php<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // ensure B and U(ndefined) are set to 1
-        let mut flags = c.regs.p.clone();
-        flags.set(CpuFlags::U, true);
-        flags.set(CpuFlags::B, true);
-        push_byte(c, d, flags.bits())?;
+        // push P with U and B set
+        let flags = c.regs.status_byte_for_push(true);
+        push_byte(c, d, flags)?;
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -3159,8 +3434,8 @@ fn php<A: AddressingMode>(
  * implied	    PLA	        68	1	    4  
  */
 #[named]
-fn pla<A: AddressingMode>(
-    c: &mut Cpu,
+fn pla<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3170,7 +3445,7 @@ fn pla<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -3200,9 +3475,11 @@ fn pla<A: AddressingMode>(
  * addressing	assembler	opc	bytes	cycles
  * implied	    PLP	        28	1	    4  
  */
+// the legacy must_trigger_* fields are still honored
+#[allow(deprecated)]
 #[named]
-fn plp<A: AddressingMode>(
-    c: &mut Cpu,
+fn plp<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3212,16 +3489,13 @@ fn plp<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
         let popped_flags = pop_byte(c, d)?;
-        c.regs.p = CpuFlags::from_bits(popped_flags).unwrap();
-
-        // ensure flag Unused is set and B is unset
-        c.set_cpu_flags(CpuFlags::B, false);
-        c.set_cpu_flags(CpuFlags::U, true);
+        c.delay_i_flag_change(c.is_cpu_flag_set(CpuFlags::I));
+        c.regs.set_status_from_pull(popped_flags);
         if c.irq_pending {
             if !c.is_cpu_flag_set(CpuFlags::I) {
                 // we'll trigger an irq right after
@@ -3253,30 +3527,26 @@ fn plp<A: AddressingMode>(
  */
 
 #[named]
-fn rla<A: AddressingMode>(
-    c: &mut Cpu,
+fn rla<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
-    opcode_byte: u8,
+    _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // perform rol + and internally
-        let prev_p = c.regs.p;
-        rol::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
-
-        // preserve carry
-        let is_c_set = c.is_cpu_flag_set(CpuFlags::C);
-        c.regs.p = prev_p;
-        c.set_cpu_flags(CpuFlags::C, is_c_set);
-        // n and z are set according to AND
-        and::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+        // rotate M left through C, then A AND M -> A: C is the old bit 7 of M, N and Z from A
+        let carry = c.is_cpu_flag_set(CpuFlags::C) as u8;
+        let (old, m) = rmw::<A, B, _>(c, d, tgt, |b| (b << 1) | carry)?;
+        c.set_cpu_flags(CpuFlags::C, old & 0x80 != 0);
+        c.regs.a &= m;
+        set_zn_flags(c, c.regs.a);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -3306,8 +3576,8 @@ fn rla<A: AddressingMode>(
  * absolute,X	ROL oper,X	3E	3	    7
  */
 #[named]
-fn rol<A: AddressingMode>(
-    c: &mut Cpu,
+fn rol<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3317,11 +3587,12 @@ fn rol<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
 
         // save current carry
         let carry = c.is_cpu_flag_set(CpuFlags::C);
@@ -3363,8 +3634,8 @@ fn rol<A: AddressingMode>(
  * N	Negative Flag	    Set if bit 7 of the result is set
  */
 #[named]
-fn ror<A: AddressingMode>(
-    c: &mut Cpu,
+fn ror<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3374,11 +3645,12 @@ fn ror<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
 
         // save current carry
         let carry = c.is_cpu_flag_set(CpuFlags::C);
@@ -3424,31 +3696,26 @@ fn ror<A: AddressingMode>(
  * (indirect),Y	RRA (oper),Y	73	2	8
  */
 #[named]
-fn rra<A: AddressingMode>(
-    c: &mut Cpu,
+fn rra<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
-    opcode_byte: u8,
+    _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // perform ror + adc internally
-        let prev_p = c.regs.p;
-        ror::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
-
-        // preserve carry
-        let is_c_set = c.is_cpu_flag_set(CpuFlags::C);
-        c.regs.p = prev_p;
-        c.set_cpu_flags(CpuFlags::C, is_c_set);
-
-        // all other flags are set by adc
-        adc::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+        // rotate M right through C, then A + M + C -> A: C from the old bit 0 of M is the carry in for the addition,
+        // which sets N, V, Z and C as ADC (including decimal mode)
+        let carry = c.is_cpu_flag_set(CpuFlags::C) as u8;
+        let (old, m) = rmw::<A, B, _>(c, d, tgt, |b| (b >> 1) | (carry << 7))?;
+        c.set_cpu_flags(CpuFlags::C, old & 1 != 0);
+        add_with_carry(c, m);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -3476,8 +3743,8 @@ fn rra<A: AddressingMode>(
 * implied	    RTI	        40	1	    6
 */
 #[named]
-fn rti<A: AddressingMode>(
-    c: &mut Cpu,
+fn rti<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3487,27 +3754,20 @@ fn rti<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
+        // the handler ends once RTI completes
+        c.interrupt_returned(c.cycles + in_cycles);
         let popped_flags = pop_byte(c, d)?;
-        c.regs.p = CpuFlags::from_bits(popped_flags).unwrap();
-
-        // ensure flag Unused is set and B is unset
-        c.set_cpu_flags(CpuFlags::B, false);
-        c.set_cpu_flags(CpuFlags::U, true);
+        c.regs.set_status_from_pull(popped_flags);
 
-        // pull pc
-        c.regs.pc = pop_word_le(c, d)?;
-
-        // apply fix if needed, and anyway reset the flag.
-        c.regs.pc = c.regs.pc.wrapping_add(c.fix_pc_rti as u16);
+        // pull pc, apply fix if needed, and anyway reset the flag.
+        let pc = pop_word_le(c, d)?;
+        c.set_pc(pc.wrapping_add(c.fix_pc_rti as u16));
         c.fix_pc_rti = 0;
     }
-    Ok((
-        if decode_only { A::len() } else { 0 },
-        in_cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -3531,8 +3791,8 @@ fn rti<A: AddressingMode>(
  * implied	    RTS 	    60	1	    6  
  */
 #[named]
-fn rts<A: AddressingMode>(
-    c: &mut Cpu,
+fn rts<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3542,16 +3802,14 @@ fn rts<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
-        c.regs.pc = pop_word_le(c, d)?.wrapping_add(1);
+        let pc = pop_word_le(c, d)?;
+        c.set_pc(pc.wrapping_add(1));
     }
-    Ok((
-        if decode_only { A::len() } else { 0 },
-        in_cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -3571,8 +3829,8 @@ fn rts<A: AddressingMode>(
  * (indirect,X)	SAX (oper,X)	83	2	    6
  */
 #[named]
-fn sax<A: AddressingMode>(
-    c: &mut Cpu,
+fn sax<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3582,7 +3840,7 @@ fn sax<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         let b = c.regs.a & c.regs.x;
@@ -3612,8 +3870,8 @@ fn sax<A: AddressingMode>(
  * SBC implementation converted from c code, taken from https://github.com/DavidBuchanan314/6502-emu/blob/master/6502.c
  */
 #[named]
-fn sbc<A: AddressingMode>(
-    c: &mut Cpu,
+fn sbc<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3624,7 +3882,7 @@ fn sbc<A: AddressingMode>(
     // get target_address
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     let mut cycles = in_cycles;
@@ -3633,40 +3891,12 @@ fn sbc<A: AddressingMode>(
         // read operand
         let b = A::load(c, d, tgt)?;
 
-        // perform non-bcd subtraction (regs.a-b-1+C)
-        let sub: u16 = (c.regs.a as u16)
-            .wrapping_sub(b as u16)
-            .wrapping_sub(1)
-            .wrapping_add(c.is_cpu_flag_set(CpuFlags::C) as u16);
-        let o = ((c.regs.a as u16) ^ sub) & ((c.regs.a as u16) ^ (b as u16)) & 0x80;
-        c.set_cpu_flags(CpuFlags::V, o != 0);
-
-        if c.is_cpu_flag_set(CpuFlags::D) {
-            if c.cpu_type == CpuType::WDC65C02 {
-                // one extra cycle in decimal mode
-                cycles += 1;
-            }
-
-            // bcd
-            let mut lo: u8 = (c.regs.a & 0x0f)
-                .wrapping_sub(b & 0x0f)
-                .wrapping_sub(1)
-                .wrapping_add(c.is_cpu_flag_set(CpuFlags::C) as u8);
-            let mut hi: u8 = (c.regs.a >> 4).wrapping_sub(b >> 4);
-            if lo & 0x10 != 0 {
-                lo = lo.wrapping_sub(6);
-                hi = hi.wrapping_sub(1);
-            }
-            if hi & 0x10 != 0 {
-                hi = hi.wrapping_sub(6);
-            }
-            c.regs.a = (hi << 4) | (lo & 0xf);
-        } else {
-            // normal
-            c.regs.a = (sub & 0xff) as u8;
+        // perform the subtraction (regs.a-b-1+C)
+        subtract_with_borrow(c, b);
+        if c.is_decimal_mode() && c.is_65c02() {
+            // one extra cycle in decimal mode
+            cycles += 1;
         }
-        c.set_cpu_flags(CpuFlags::C, sub < 0x100);
-        set_zn_flags(c, c.regs.a);
     }
     Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -3685,8 +3915,8 @@ fn sbc<A: AddressingMode>(
 * immediate	    SBX #oper	CB	2	    2
 */
 #[named]
-fn sbx<A: AddressingMode>(
-    c: &mut Cpu,
+fn sbx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3696,7 +3926,7 @@ fn sbx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -3705,9 +3935,9 @@ fn sbx<A: AddressingMode>(
         // and
         let and_res = c.regs.a & c.regs.x;
 
-        // cmp
+        // cmp (carry is computed from A AND X, not from A)
         c.regs.x = and_res.wrapping_sub(b);
-        c.set_cpu_flags(CpuFlags::C, c.regs.a >= b);
+        c.set_cpu_flags(CpuFlags::C, and_res >= b);
         set_zn_flags(c, c.regs.x);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
@@ -3732,9 +3962,9 @@ fn sbx<A: AddressingMode>(
  * implied	    SEC	        38	1	    2  
  */
 #[named]
-fn sec<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn sec<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -3743,7 +3973,7 @@ fn sec<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -3772,9 +4002,9 @@ fn sec<A: AddressingMode>(
  * implied	    SED	        F8	1	    2  
  */
 #[named]
-fn sed<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn sed<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -3783,7 +4013,7 @@ fn sed<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -3812,9 +4042,9 @@ fn sed<A: AddressingMode>(
  * implied	    SED	        78	1	    2  
  */
 #[named]
-fn sei<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn sei<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -3823,11 +4053,12 @@ fn sei<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
-        // disable interrupts
+        // disable interrupts (a pending irq is still taken right after)
+        c.delay_i_flag_change(c.is_cpu_flag_set(CpuFlags::I));
         c.set_cpu_flags(CpuFlags::I, true);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
@@ -3838,8 +4069,7 @@ fn sei<A: AddressingMode>(
  *
  * Stores X AND (high-byte of addr. + 1) at addr.
  *
- * unstable: sometimes 'AND (H+1)' is dropped, page boundary crossings may not work
- * (with the high-byte of the value used as the high-byte of the address)
+ * unstable: sometimes 'AND (H+1)' is dropped. on page boundary crossings the high-byte of the value is used as the high-byte of the address.
  *
  * X AND (H+1) -> M
  *
@@ -3850,8 +4080,8 @@ fn sei<A: AddressingMode>(
  * absolut,Y	SHX oper,Y	9E	3	5  	†
 */
 #[named]
-fn shx<A: AddressingMode>(
-    c: &mut Cpu,
+fn shx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3861,23 +4091,12 @@ fn shx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // get msb from target address
-        let mut h = (tgt >> 8) as u8;
-
-        // add 1 on msb when page crossing
-        // [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
-        if extra_cycle_on_page_crossing {
-            h = h.wrapping_add(1);
-        }
-
         // X & (H + 1)
-        let res = c.regs.x & h.wrapping_add(1);
-
-        // store
-        A::store(c, d, tgt, res)?;
+        let (x, y) = (c.regs.x, c.regs.y);
+        store_sh::<A, B>(c, d, tgt, y, x)?;
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -3887,7 +4106,7 @@ fn shx<A: AddressingMode>(
  *
  * Stores Y AND (high-byte of addr. + 1) at addr.
  *
- * unstable: sometimes 'AND (H+1)' is dropped, page boundary crossings may not work (with the high-byte of the value used as the high-byte of the address)
+ * unstable: sometimes 'AND (H+1)' is dropped. on page boundary crossings the high-byte of the value is used as the high-byte of the address.
  *
  * Y AND (H+1) -> M
  *
@@ -3898,8 +4117,8 @@ fn shx<A: AddressingMode>(
  * absolut,X	SHY oper,X	9C	3	    5  	†
 */
 #[named]
-fn shy<A: AddressingMode>(
-    c: &mut Cpu,
+fn shy<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -3909,23 +4128,12 @@ fn shy<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // get msb from target address
-        let mut h = (tgt >> 8) as u8;
-
-        // add 1 on msb when page crossing
-        // [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
-        if extra_cycle_on_page_crossing {
-            h = h.wrapping_add(1);
-        }
-
         // Y & (H + 1)
-        let res = c.regs.y & h.wrapping_add(1);
-
-        // store
-        A::store(c, d, tgt, res)?;
+        let (x, y) = (c.regs.x, c.regs.y);
+        store_sh::<A, B>(c, d, tgt, x, y)?;
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -3950,31 +4158,25 @@ fn shy<A: AddressingMode>(
  * (indirect),Y	SLO (oper),Y	13	2	    8
 */
 #[named]
-fn slo<A: AddressingMode>(
-    c: &mut Cpu,
+fn slo<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
-    opcode_byte: u8,
+    _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // perform asl + ora internally
-        let prev_p = c.regs.p;
-        asl::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
-
-        // preserve carry
-        let is_c_set = c.is_cpu_flag_set(CpuFlags::C);
-        c.regs.p = prev_p;
-        c.set_cpu_flags(CpuFlags::C, is_c_set);
-
-        // other flags are set by ora
-        ora::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+        // shift M left, then A OR M -> A: C is the old bit 7 of M, N and Z from A
+        let (old, m) = rmw::<A, B, _>(c, d, tgt, |b| b << 1)?;
+        c.set_cpu_flags(CpuFlags::C, old & 0x80 != 0);
+        c.regs.a |= m;
+        set_zn_flags(c, c.regs.a);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -3999,31 +4201,25 @@ fn slo<A: AddressingMode>(
  * (indirect),Y	SRE (oper),Y	53	2	    8  
  */
 #[named]
-fn sre<A: AddressingMode>(
-    c: &mut Cpu,
+fn sre<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
-    opcode_byte: u8,
+    _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
+    let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // perform lsr + eor internally
-        let prev_p = c.regs.p;
-        lsr::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
-
-        // preserve carry
-        let is_c_set = c.is_cpu_flag_set(CpuFlags::C);
-        c.regs.p = prev_p;
-        c.set_cpu_flags(CpuFlags::C, is_c_set);
-
-        // other flags are set by eor
-        eor::<A>(c, d, opcode_byte, 0, false, decode_only, true)?;
+        // shift M right, then A EOR M -> A: C is the old bit 0 of M, N and Z from A
+        let (old, m) = rmw::<A, B, _>(c, d, tgt, |b| b >> 1)?;
+        c.set_cpu_flags(CpuFlags::C, old & 1 != 0);
+        c.regs.a ^= m;
+        set_zn_flags(c, c.regs.a);
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -4046,8 +4242,8 @@ fn sre<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn sta<A: AddressingMode>(
-    c: &mut Cpu,
+fn sta<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4057,7 +4253,7 @@ fn sta<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -4085,8 +4281,8 @@ fn sta<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn stx<A: AddressingMode>(
-    c: &mut Cpu,
+fn stx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4096,7 +4292,7 @@ fn stx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -4124,8 +4320,8 @@ fn stx<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn sty<A: AddressingMode>(
-    c: &mut Cpu,
+fn sty<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4135,7 +4331,7 @@ fn sty<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -4150,7 +4346,7 @@ fn sty<A: AddressingMode>(
  *
  * Puts A AND X in SP and stores A AND X AND (high-byte of addr. + 1) at addr.
  *
- * unstable: sometimes 'AND (H+1)' is dropped, page boundary crossings may not work (with the high-byte of the value used as the high-byte of the address)
+ * unstable: sometimes 'AND (H+1)' is dropped. on page boundary crossings the high-byte of the value is used as the high-byte of the address.
  *
  * A AND X -> SP, A AND X AND (H+1) -> M
  *
@@ -4161,8 +4357,8 @@ fn sty<A: AddressingMode>(
  * absolut,Y	TAS oper,Y	9B	3	5  	†
 */
 #[named]
-fn tas<A: AddressingMode>(
-    c: &mut Cpu,
+fn tas<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4172,24 +4368,13 @@ fn tas<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
-        // get msb from target address
-        let mut h = (tgt >> 8) as u8;
-
-        // add 1 on msb when page crossing
-        // [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
-        if extra_cycle_on_page_crossing {
-            h = h.wrapping_add(1);
-        }
-
-        // set sp
+        // set sp, then S & (H + 1)
         c.regs.s = c.regs.a & c.regs.x;
-        let res = c.regs.s & h.wrapping_add(1);
-
-        // store
-        A::store(c, d, tgt, res)?;
+        let (s, y) = (c.regs.s, c.regs.y);
+        store_sh::<A, B>(c, d, tgt, y, s)?;
     }
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -4215,9 +4400,9 @@ fn tas<A: AddressingMode>(
  * implied	    TAX	        AA	1	    2  
  */
 #[named]
-fn tax<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn tax<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -4226,7 +4411,7 @@ fn tax<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.x = c.regs.a;
@@ -4255,9 +4440,9 @@ fn tax<A: AddressingMode>(
  * implied	    TAY	        A8	1	    2  
 */
 #[named]
-fn tay<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn tay<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -4266,7 +4451,7 @@ fn tay<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.y = c.regs.a;
@@ -4296,9 +4481,9 @@ fn tay<A: AddressingMode>(
  * implied	    TSX	        BA	1	    2
  */
 #[named]
-fn tsx<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn tsx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -4307,7 +4492,7 @@ fn tsx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -4338,9 +4523,9 @@ fn tsx<A: AddressingMode>(
  * implied	    TXA	        8A	1	    2  
 */
 #[named]
-fn txa<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn txa<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -4349,7 +4534,7 @@ fn txa<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.a = c.regs.x;
@@ -4379,9 +4564,9 @@ fn txa<A: AddressingMode>(
  * implied	    TXS	        9A	1	    2  
  */
 #[named]
-fn txs<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn txs<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -4390,7 +4575,7 @@ fn txs<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.s = c.regs.x;
@@ -4419,9 +4604,9 @@ fn txs<A: AddressingMode>(
  * implied	    TYA	        98	    1	    2  
  */
 #[named]
-fn tya<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn tya<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -4430,7 +4615,7 @@ fn tya<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.a = c.regs.y;
@@ -4461,8 +4646,8 @@ fn tya<A: AddressingMode>(
  */
 
 #[named]
-fn xaa<A: AddressingMode>(
-    c: &mut Cpu,
+fn xaa<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4472,7 +4657,7 @@ fn xaa<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
@@ -4481,8 +4666,8 @@ fn xaa<A: AddressingMode>(
         // N and Z are set according to the value of the accumulator before the instruction executed
         set_zn_flags(c, c.regs.a);
 
-        // we choose $ef as constant as specified in [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
-        let k = 0xef;
+        // constant defaults to $ef as specified in [https://csdb.dk/release/?id=198357](NMOS 6510 Unintended Opcodes)
+        let k = c.ane_magic;
         let res: u8 = (c.regs.a | k) & c.regs.x & b;
         c.regs.a = res;
     }
@@ -4493,8 +4678,8 @@ fn xaa<A: AddressingMode>(
  * 65c02 only opcodes following
  */
 
-fn bbr_bbs_internal<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr_bbs_internal<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4508,10 +4693,10 @@ fn bbr_bbs_internal<A: AddressingMode>(
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
 
     if !quiet {
-        debug_out_opcode::<A>(c, name)?;
+        debug_out_opcode::<A, B>(c, d, name)?;
     }
 
-    let mut taken = false;
+    let mut cycles = in_cycles;
     if !decode_only {
         // read operand
         let b = A::load(c, d, tgt)?;
@@ -4520,30 +4705,20 @@ fn bbr_bbs_internal<A: AddressingMode>(
         let to_test_addr = A::load(c, d, c.regs.pc.wrapping_add(1))?;
         let to_test = A::load(c, d, to_test_addr as u16)?;
 
-        if is_bbr {
-            taken = (to_test & (1 << bit)) == 0;
+        let taken = if is_bbr {
+            (to_test & (1 << bit)) == 0
         } else {
-            taken = (to_test & (1 << bit)) != 0;
-        }
+            (to_test & (1 << bit)) != 0
+        };
         if taken {
-            // branch is taken
-            let (mut new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-            new_pc = new_pc.wrapping_add(1);
-            // check for deadlock
-            if new_pc == c.regs.pc {
-                return Err(CpuError::new_default(
-                    CpuErrorType::Deadlock,
-                    c.regs.pc,
-                    None,
-                ));
+            // branch is taken, add another cycle (and another one if the target is on a different page)
+            cycles += 1;
+            if take_branch(c, A::len(), b)? {
+                cycles += 1;
             }
-            c.regs.pc = new_pc;
         }
     }
-    Ok((
-        if taken { 0 } else { A::len() },
-        in_cycles + if extra_cycle { 1 } else { 0 },
-    ))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -4564,8 +4739,8 @@ fn bbr_bbs_internal<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn bbr0<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr0<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4573,7 +4748,7 @@ fn bbr0<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4588,8 +4763,8 @@ fn bbr0<A: AddressingMode>(
 }
 
 #[named]
-fn bbr1<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr1<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4597,7 +4772,7 @@ fn bbr1<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4612,8 +4787,8 @@ fn bbr1<A: AddressingMode>(
 }
 
 #[named]
-fn bbr2<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr2<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4621,7 +4796,7 @@ fn bbr2<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4636,8 +4811,8 @@ fn bbr2<A: AddressingMode>(
 }
 
 #[named]
-fn bbr3<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr3<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4645,7 +4820,7 @@ fn bbr3<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4660,8 +4835,8 @@ fn bbr3<A: AddressingMode>(
 }
 
 #[named]
-fn bbr4<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr4<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4669,7 +4844,7 @@ fn bbr4<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4684,8 +4859,8 @@ fn bbr4<A: AddressingMode>(
 }
 
 #[named]
-fn bbr5<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr5<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4693,7 +4868,7 @@ fn bbr5<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4708,8 +4883,8 @@ fn bbr5<A: AddressingMode>(
 }
 
 #[named]
-fn bbr6<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr6<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4717,7 +4892,7 @@ fn bbr6<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4732,8 +4907,8 @@ fn bbr6<A: AddressingMode>(
 }
 
 #[named]
-fn bbr7<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbr7<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4741,7 +4916,7 @@ fn bbr7<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4756,8 +4931,8 @@ fn bbr7<A: AddressingMode>(
 }
 
 #[named]
-fn bbs0<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs0<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4765,7 +4940,7 @@ fn bbs0<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4780,8 +4955,8 @@ fn bbs0<A: AddressingMode>(
 }
 
 #[named]
-fn bbs1<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs1<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4789,7 +4964,7 @@ fn bbs1<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4804,8 +4979,8 @@ fn bbs1<A: AddressingMode>(
 }
 
 #[named]
-fn bbs2<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs2<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4813,7 +4988,7 @@ fn bbs2<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4828,8 +5003,8 @@ fn bbs2<A: AddressingMode>(
 }
 
 #[named]
-fn bbs3<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs3<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4837,7 +5012,7 @@ fn bbs3<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4852,8 +5027,8 @@ fn bbs3<A: AddressingMode>(
 }
 
 #[named]
-fn bbs4<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs4<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4861,7 +5036,7 @@ fn bbs4<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4876,8 +5051,8 @@ fn bbs4<A: AddressingMode>(
 }
 
 #[named]
-fn bbs5<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs5<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4885,7 +5060,7 @@ fn bbs5<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4900,8 +5075,8 @@ fn bbs5<A: AddressingMode>(
 }
 
 #[named]
-fn bbs6<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs6<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4909,7 +5084,7 @@ fn bbs6<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4924,8 +5099,8 @@ fn bbs6<A: AddressingMode>(
 }
 
 #[named]
-fn bbs7<A: AddressingMode>(
-    c: &mut Cpu,
+fn bbs7<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4933,7 +5108,7 @@ fn bbs7<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    bbr_bbs_internal::<A>(
+    bbr_bbs_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -4947,8 +5122,8 @@ fn bbs7<A: AddressingMode>(
     )
 }
 
-fn rmb_smb_internal<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb_smb_internal<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -4962,12 +5137,13 @@ fn rmb_smb_internal<A: AddressingMode>(
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
 
     if !quiet {
-        debug_out_opcode::<A>(c, name)?;
+        debug_out_opcode::<A, B>(c, d, name)?;
     }
 
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
 
         if is_rmb {
             // reset bit
@@ -5001,8 +5177,8 @@ fn rmb_smb_internal<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn rmb0<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb0<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5010,7 +5186,7 @@ fn rmb0<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5025,8 +5201,8 @@ fn rmb0<A: AddressingMode>(
 }
 
 #[named]
-fn rmb1<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb1<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5034,7 +5210,7 @@ fn rmb1<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5049,8 +5225,8 @@ fn rmb1<A: AddressingMode>(
 }
 
 #[named]
-fn rmb2<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb2<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5058,7 +5234,7 @@ fn rmb2<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5073,8 +5249,8 @@ fn rmb2<A: AddressingMode>(
 }
 
 #[named]
-fn rmb3<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb3<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5082,7 +5258,7 @@ fn rmb3<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5097,8 +5273,8 @@ fn rmb3<A: AddressingMode>(
 }
 
 #[named]
-fn rmb4<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb4<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5106,7 +5282,7 @@ fn rmb4<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5121,8 +5297,8 @@ fn rmb4<A: AddressingMode>(
 }
 
 #[named]
-fn rmb5<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb5<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5130,7 +5306,7 @@ fn rmb5<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5145,8 +5321,8 @@ fn rmb5<A: AddressingMode>(
 }
 
 #[named]
-fn rmb6<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb6<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5154,7 +5330,7 @@ fn rmb6<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5169,8 +5345,8 @@ fn rmb6<A: AddressingMode>(
 }
 
 #[named]
-fn rmb7<A: AddressingMode>(
-    c: &mut Cpu,
+fn rmb7<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5178,7 +5354,7 @@ fn rmb7<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5193,8 +5369,8 @@ fn rmb7<A: AddressingMode>(
 }
 
 #[named]
-fn smb0<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb0<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5202,7 +5378,7 @@ fn smb0<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5217,8 +5393,8 @@ fn smb0<A: AddressingMode>(
 }
 
 #[named]
-fn smb1<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb1<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5226,7 +5402,7 @@ fn smb1<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5241,8 +5417,8 @@ fn smb1<A: AddressingMode>(
 }
 
 #[named]
-fn smb2<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb2<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5250,7 +5426,7 @@ fn smb2<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5265,8 +5441,8 @@ fn smb2<A: AddressingMode>(
 }
 
 #[named]
-fn smb3<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb3<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5274,7 +5450,7 @@ fn smb3<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5289,8 +5465,8 @@ fn smb3<A: AddressingMode>(
 }
 
 #[named]
-fn smb4<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb4<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5298,7 +5474,7 @@ fn smb4<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5313,8 +5489,8 @@ fn smb4<A: AddressingMode>(
 }
 
 #[named]
-fn smb5<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb5<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5322,7 +5498,7 @@ fn smb5<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5337,8 +5513,8 @@ fn smb5<A: AddressingMode>(
 }
 
 #[named]
-fn smb6<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb6<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5346,7 +5522,7 @@ fn smb6<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5361,8 +5537,8 @@ fn smb6<A: AddressingMode>(
 }
 
 #[named]
-fn smb7<A: AddressingMode>(
-    c: &mut Cpu,
+fn smb7<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5370,7 +5546,7 @@ fn smb7<A: AddressingMode>(
     decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
-    rmb_smb_internal::<A>(
+    rmb_smb_internal::<A, B>(
         c,
         d,
         _opcode_byte,
@@ -5400,8 +5576,8 @@ fn smb7<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn bra<A: AddressingMode>(
-    c: &mut Cpu,
+fn bra<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5411,27 +5587,21 @@ fn bra<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
-    // read operand
-    let b = A::load(c, d, tgt)?;
-
-    // branch
+    let mut cycles = in_cycles;
     if !decode_only {
-        // branch is always taken
-        let (new_pc, _) = addressing_modes::get_relative_branch_target(c.regs.pc, b);
-        // check for deadlock
-        if new_pc == c.regs.pc {
-            return Err(CpuError::new_default(
-                CpuErrorType::Deadlock,
-                c.regs.pc,
-                None,
-            ));
+        // read operand
+        let b = A::load(c, d, tgt)?;
+
+        // branch is always taken, so there's no taken cycle as for the other branches: 3 cycles, 4 if the target is
+        // on a different page
+        if take_branch(c, A::len(), b)? {
+            cycles += 1;
         }
-        c.regs.pc = new_pc;
     }
-    Ok((0, in_cycles + if extra_cycle { 1 } else { 0 }))
+    Ok((A::len(), cycles + if extra_cycle { 1 } else { 0 }))
 }
 
 /**
@@ -5450,8 +5620,8 @@ fn bra<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn phx<A: AddressingMode>(
-    c: &mut Cpu,
+fn phx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5461,7 +5631,7 @@ fn phx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -5486,8 +5656,8 @@ fn phx<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn phy<A: AddressingMode>(
-    c: &mut Cpu,
+fn phy<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5497,7 +5667,7 @@ fn phy<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
 
     if !decode_only {
@@ -5520,8 +5690,8 @@ fn phy<A: AddressingMode>(
  * N	Negative Flag	Set if bit 7 of X is set
  */
 #[named]
-fn plx<A: AddressingMode>(
-    c: &mut Cpu,
+fn plx<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5531,7 +5701,7 @@ fn plx<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.x = pop_byte(c, d)?;
@@ -5546,16 +5716,16 @@ fn plx<A: AddressingMode>(
  * Pulls an 8 bit value from the stack and into the Y register. The zero and negative flags are set as appropriate.
  *
  * C	Carry Flag	Not affected
- * Z	Zero Flag	Set if A = 0
+ * Z	Zero Flag	Set if Y = 0
  * I	Interrupt Disable	Not affected
  * D	Decimal Mode Flag	Not affected
  * B	Break Command	Not affected
  * V	Overflow Flag	Not affected
- * N	Negative Flag	Set if bit 7 of A is set
+ * N	Negative Flag	Set if bit 7 of Y is set
  */
 #[named]
-fn ply<A: AddressingMode>(
-    c: &mut Cpu,
+fn ply<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5565,7 +5735,7 @@ fn ply<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         c.regs.y = pop_byte(c, d)?;
@@ -5588,21 +5758,23 @@ fn ply<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn stp<A: AddressingMode>(
-    c: &mut Cpu,
-    _: Option<&Debugger>,
+fn stp<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     _extra_cycle_on_page_crossing: bool,
-    _decode_only: bool,
+    decode_only: bool,
     quiet: bool,
 ) -> Result<(i8, usize), CpuError> {
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
-
-    // will deadlock !
-    Ok((0, in_cycles))
+    if !decode_only {
+        // halt, run() will stop until reset
+        c.halted = true;
+    }
+    Ok((A::len(), in_cycles))
 }
 
 /**
@@ -5623,8 +5795,8 @@ fn stp<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn stz<A: AddressingMode>(
-    c: &mut Cpu,
+fn stz<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5634,7 +5806,7 @@ fn stz<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // store
@@ -5666,8 +5838,8 @@ fn stz<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn trb<A: AddressingMode>(
-    c: &mut Cpu,
+fn trb<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5677,11 +5849,12 @@ fn trb<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
 
         let res = (b & c.regs.a) == 0;
         c.set_cpu_flags(CpuFlags::Z, res);
@@ -5714,8 +5887,8 @@ fn trb<A: AddressingMode>(
  * N	Negative Flag	Not affected
  */
 #[named]
-fn tsb<A: AddressingMode>(
-    c: &mut Cpu,
+fn tsb<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
     d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
@@ -5725,11 +5898,12 @@ fn tsb<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
     if !decode_only {
         // read operand
         let mut b = A::load(c, d, tgt)?;
+        rmw_dummy_access::<A, B>(c, d, tgt, b)?;
         let res = (b & c.regs.a) == 0;
         c.set_cpu_flags(CpuFlags::Z, res);
         b |= c.regs.a;
@@ -5738,10 +5912,12 @@ fn tsb<A: AddressingMode>(
     Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
 
+// the legacy must_trigger_* fields are still honored
+#[allow(deprecated)]
 #[named]
-fn wai<A: AddressingMode>(
-    c: &mut Cpu,
-    _d: Option<&Debugger>,
+fn wai<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     _opcode_byte: u8,
     in_cycles: usize,
     extra_cycle_on_page_crossing: bool,
@@ -5750,16 +5926,11 @@ fn wai<A: AddressingMode>(
 ) -> Result<(i8, usize), CpuError> {
     let (_tgt, extra_cycle) = A::target_address(c, extra_cycle_on_page_crossing)?;
     if !quiet {
-        debug_out_opcode::<A>(c, function_name!())?;
+        debug_out_opcode::<A, B>(c, d, function_name!())?;
     }
-    let mut len = A::len();
-
-    if !decode_only {
-        // read operand
-        if !c.must_trigger_irq && !c.must_trigger_nmi {
-            // will wait for interrupt
-            len = 0;
-        }
+    if !decode_only && !c.must_trigger_irq && !c.must_trigger_nmi && !c.irq_line && !c.nmi_latched {
+        // will wait for interrupt, stay on WAI
+        c.set_pc(c.regs.pc);
     }
-    Ok((len, in_cycles + if extra_cycle { 1 } else { 0 }))
+    Ok((A::len(), in_cycles + if extra_cycle { 1 } else { 0 }))
 }
@@ -0,0 +1,85 @@
+/*
+ * Filename: /src/cpu/profiler.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 17:20:51
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus::Bus;
+use crate::cpu::Cpu;
+use alloc::{vec, vec::Vec};
+
+impl<B: Bus> Cpu<B> {
+    /**
+     * enable/disable the per-address execution profiler, enabling clears the counters.
+     */
+    pub fn enable_profiling(&mut self, enable: bool) {
+        self.profile = if enable {
+            Some(vec![(0, 0); 0x10000].into_boxed_slice())
+        } else {
+            None
+        };
+    }
+
+    /**
+     * returns true if the profiler is enabled.
+     */
+    pub fn is_profiling(&self) -> bool {
+        self.profile.is_some()
+    }
+
+    /**
+     * returns the top_n (all if 0) executed addresses as (address, executions, cycles), by cycles (then executions) descending.
+     */
+    pub fn profile_report(&self, top_n: usize) -> Vec<(u16, usize, usize)> {
+        let p = match self.profile.as_ref() {
+            Some(p) => p,
+            None => return Vec::new(),
+        };
+        let mut v: Vec<(u16, usize, usize)> = p
+            .iter()
+            .enumerate()
+            .filter(|(_, (n, _))| *n != 0)
+            .map(|(a, (n, cycles))| (a as u16, *n as usize, *cycles as usize))
+            .collect();
+        v.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then(a.0.cmp(&b.0)));
+        if top_n != 0 {
+            v.truncate(top_n);
+        }
+        v
+    }
+
+    /**
+     * account an executed instruction at address, if profiling.
+     */
+    pub(crate) fn profile_instruction(&mut self, address: u16, cycles: usize) {
+        if let Some(p) = self.profile.as_mut() {
+            let e = &mut p[address as usize];
+            e.0 = e.0.wrapping_add(1);
+            e.1 = e.1.wrapping_add(cycles as u64);
+        }
+    }
+}
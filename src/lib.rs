@@ -29,6 +29,12 @@
  */
 
 //! implements a MOS6502 CPU emulator.
+//!
+//! the debugger, logging to stdout and file loading need the std feature (default), without it the core (Cpu, opcodes,
+//! addressing modes, Memory, Bus and errors) builds as no_std with alloc.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 /// implements the cpu.
 pub mod cpu;
@@ -39,5 +45,13 @@ pub mod memory;
 /// implements the emulated bus.
 pub mod bus;
 
+/// wasm_bindgen facade for javascript.
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// C interface, see include/rv6502emu.h.
+#[cfg(feature = "capi")]
+pub mod capi;
+
 /// utilities
 pub(crate) mod utils;
@@ -30,16 +30,29 @@
 
 use crate::cpu::cpu_error;
 use crate::cpu::cpu_error::{CpuError, CpuErrorType};
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::Cursor;
+use crate::cpu::Vectors;
+#[cfg(feature = "std")]
+use log::info;
+
+mod banked;
+#[cfg(feature = "std")]
+mod image;
+mod init_policy;
+mod mirrored;
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use banked::BankedMemory;
+#[cfg(feature = "std")]
+use image::ImageFormat;
+use mirrored::MirroredMemory;
+
+pub use init_policy::{MemoryInitPolicy, PATTERN_STRIPE_SIZE};
 
 /**
  * trait for the emulated memory exposed by the cpu.
  *
+ * must be Send, as the Bus holding it.
  */
-pub trait Memory {
+pub trait Memory: Send {
     /**
      * reads a byte at address.
      */
@@ -66,19 +79,193 @@ pub trait Memory {
     fn get_size(&self) -> usize;
 
     /**
-     * load file in memory at address. files bigger than 0xffff will be truncated.
+     * load file in memory at address (as load_from_slice()). files bigger than 0xffff will be truncated.
      */
-    fn load(&mut self, path: &str, address: usize) -> Result<(), CpuError>;
+    #[cfg(feature = "std")]
+    fn load(&mut self, path: &str, address: usize) -> Result<(), CpuError> {
+        let tmp = read_file(path, address, self.get_size())?;
+        self.load_from_slice(&tmp, address)?;
+        info!("{} correctly loaded at ${:04x} !", path, address);
+        Ok(())
+    }
 
     /**
-     * fill memory with zeroes and reset cursor to 0.
+     * fill memory according to the init policy (zeroes by default, see set_init_policy()).
      */
     fn clear(&mut self);
 
     /**
-     * gets a reference to the underlying buffer.
+     * sets how clear() fills the memory, doesn't touch the current content.
+     *
+     * the default implementation ignores the policy.
+     */
+    fn set_init_policy(&mut self, _policy: MemoryInitPolicy) {}
+
+    /**
+     * returns the policy used by clear().
+     */
+    fn init_policy(&self) -> MemoryInitPolicy {
+        MemoryInitPolicy::Zero
+    }
+
+    /**
+     * copies buf.len() bytes starting at start into buf, without side effects (for the debugger).
      */
-    fn as_vec(&self) -> &Vec<u8>;
+    fn read_range(&self, start: usize, buf: &mut [u8]) -> Result<(), CpuError>;
+
+    /**
+     * copies data to memory at address, bypassing the rom protection. nothing is written if data doesn't fit.
+     */
+    fn load_from_slice(&mut self, data: &[u8], address: usize) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            self.get_size(),
+            address,
+            data.len(),
+            CpuErrorType::MemoryLoad,
+            Some(format!(
+                "{} bytes at ${:x} exceed memory size ${:x}",
+                data.len(),
+                address,
+                self.get_size()
+            )),
+        )?;
+        for (i, b) in data.iter().enumerate() {
+            self.write_byte_privileged(address + i, *b)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * copies bytes to memory at origin (as load_from_slice()), pointing the RESET vector at origin if set_reset_vector is
+     * set, so reset(None, ...) starts it.
+     *
+     * ```
+     * use rv6502emu::cpu::{Cpu, ResetKind, Vectors};
+     *
+     * let mut c = Cpu::new_default(None);
+     * c.bus.get_memory().install_program(0x0400, &[0xea, 0xea], true).unwrap();
+     * assert_eq!(c.get_vector(Vectors::RESET).unwrap(), 0x0400);
+     * c.reset(None, ResetKind::PowerOn).unwrap();
+     * assert_eq!(c.regs.pc, 0x0400);
+     * ```
+     */
+    fn install_program(
+        &mut self,
+        origin: u16,
+        bytes: &[u8],
+        set_reset_vector: bool,
+    ) -> Result<(), CpuError> {
+        self.load_from_slice(bytes, origin as usize)?;
+        if set_reset_vector {
+            self.load_from_slice(&origin.to_le_bytes(), Vectors::RESET.address() as usize)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * returns len bytes starting at address, without side effects (as read_range()).
+     */
+    fn dump_range(&self, address: usize, len: usize) -> Result<Vec<u8>, CpuError> {
+        // check before allocating, len may be anything
+        cpu_error::check_address_boundaries(
+            self.get_size(),
+            address,
+            len,
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+        let mut v = vec![0; len];
+        self.read_range(address, &mut v)?;
+        Ok(v)
+    }
+
+    /**
+     * saves len bytes starting at address to the file at path (an empty file if len is 0).
+     */
+    #[cfg(feature = "std")]
+    fn save_to_file(&self, path: &str, address: usize, len: usize) -> Result<(), CpuError> {
+        let v = self.dump_range(address, len)?;
+        std::fs::write(path, v)?;
+        Ok(())
+    }
+
+    /**
+     * maps bank in the given slot, for banked memories (see new_banked()).
+     *
+     * the default implementation returns an error.
+     */
+    fn select_bank(&mut self, _slot: usize, _bank: usize) -> Result<(), CpuError> {
+        Err(CpuError::new_default(
+            CpuErrorType::Generic,
+            0,
+            Some(String::from("not a banked memory")),
+        ))
+    }
+
+    /**
+     * marks len bytes starting at address as read-only (rom).
+     *
+     * writes to the region are silently ignored, or reported as ReadOnlyMemory errors if report_error is set.
+     *
+     * > load() and write_byte_privileged() can still write the region.
+     */
+    fn set_rom_region(&mut self, _address: usize, _len: usize, _report_error: bool) {}
+
+    /**
+     * writes a byte at address bypassing the rom protection (for loaders and the debugger).
+     */
+    fn write_byte_privileged(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        self.write_byte(address, b)
+    }
+
+    /**
+     * load an Intel HEX file in memory at the addresses specified by its records.
+     *
+     * returns the number of bytes loaded and the entry point, if present (start address record).
+     */
+    #[cfg(feature = "std")]
+    fn load_ihex(&mut self, path: &str) -> Result<(usize, Option<u16>), CpuError> {
+        let text = std::fs::read_to_string(path)?;
+        image::parse_ihex(path, &text)?.write_to(self, path)
+    }
+
+    /**
+     * load a Motorola S-record file in memory at the addresses specified by its records.
+     *
+     * returns the number of bytes loaded and the entry point, if present (S7/S8/S9 record).
+     */
+    #[cfg(feature = "std")]
+    fn load_srec(&mut self, path: &str) -> Result<(usize, Option<u16>), CpuError> {
+        let text = std::fs::read_to_string(path)?;
+        image::parse_srec(path, &text)?.write_to(self, path)
+    }
+
+    /**
+     * load an Intel HEX, Motorola S-record or raw binary file, detecting the format from the content.
+     *
+     * address is used only for raw binary files, returns the number of bytes loaded and the entry point, if present.
+     */
+    #[cfg(feature = "std")]
+    fn load_image(&mut self, path: &str, address: usize) -> Result<(usize, Option<u16>), CpuError> {
+        let data = std::fs::read(path)?;
+        match ImageFormat::detect(&data) {
+            ImageFormat::IHex => self.load_ihex(path),
+            ImageFormat::SRec => self.load_srec(path),
+            ImageFormat::Bin => {
+                self.load(path, address)?;
+                Ok((core::cmp::min(data.len(), 0x10000), None))
+            }
+        }
+    }
+}
+
+/**
+ * a read-only region.
+ */
+struct RomRegion {
+    address: usize,
+    len: usize,
+    report_error: bool,
 }
 
 /**
@@ -86,27 +273,61 @@ pub trait Memory {
  */
 struct DefaultMemory {
     size: usize,
-    cur: Cursor<Vec<u8>>,
+    mem: Vec<u8>,
+    roms: Vec<RomRegion>,
+    policy: MemoryInitPolicy,
+}
+
+impl DefaultMemory {
+    /**
+     * check if address is writable, returns false if the write must be ignored, or a ReadOnlyMemory error.
+     */
+    fn check_rom(&self, address: usize, access_size: usize) -> Result<bool, CpuError> {
+        for r in self.roms.iter() {
+            if address >= r.address && address < r.address + r.len {
+                if r.report_error {
+                    return Err(CpuError {
+                        t: CpuErrorType::ReadOnlyMemory,
+                        address,
+                        mem_size: self.size,
+                        access_size,
+                        bp_idx: 0,
+                        msg: None,
+                        opcode: None,
+                        mode: None,
+                    });
+                }
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
 }
 
 impl Memory for DefaultMemory {
-    fn as_vec(&self) -> &Vec<u8> {
-        let v = self.cur.get_ref();
-        v
+    fn read_range(&self, start: usize, buf: &mut [u8]) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            self.size,
+            start,
+            buf.len(),
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+        buf.copy_from_slice(&self.mem[start..start + buf.len()]);
+        Ok(())
     }
+
     fn read_byte(&mut self, address: usize) -> Result<u8, CpuError> {
         cpu_error::check_address_boundaries(self.size, address, 1, CpuErrorType::MemoryRead, None)?;
-        self.cur.set_position(address as u64);
-        let res = self.cur.read_u8()?;
-        Ok(res)
+        Ok(self.mem[address])
     }
 
     fn read_word_le(&mut self, address: usize) -> Result<u16, CpuError> {
         cpu_error::check_address_boundaries(self.size, address, 2, CpuErrorType::MemoryRead, None)?;
-
-        self.cur.set_position(address as u64);
-        let res = self.cur.read_u16::<LittleEndian>()?;
-        Ok(res)
+        Ok(u16::from_le_bytes([
+            self.mem[address],
+            self.mem[address + 1],
+        ]))
     }
 
     fn write_word_le(&mut self, address: usize, w: u16) -> Result<(), CpuError> {
@@ -117,12 +338,28 @@ impl Memory for DefaultMemory {
             CpuErrorType::MemoryWrite,
             None,
         )?;
-        self.cur.set_position(address as u64);
-        let res = self.cur.write_u16::<LittleEndian>(w)?;
-        Ok(res)
+
+        // check both bytes first, the word may straddle a rom boundary
+        let write_lsb = self.check_rom(address, 2)?;
+        let write_msb = self.check_rom(address + 1, 2)?;
+        if write_lsb {
+            self.write_byte_privileged(address, (w & 0xff) as u8)?;
+        }
+        if write_msb {
+            self.write_byte_privileged(address + 1, (w >> 8) as u8)?;
+        }
+        Ok(())
     }
 
     fn write_byte(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        if !self.check_rom(address, 1)? {
+            // ignored
+            return Ok(());
+        }
+        self.write_byte_privileged(address, b)
+    }
+
+    fn write_byte_privileged(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
         cpu_error::check_address_boundaries(
             self.size,
             address,
@@ -131,68 +368,133 @@ impl Memory for DefaultMemory {
             None,
         )?;
 
-        self.cur.set_position(address as u64);
-        self.cur.write_u8(b)?;
+        self.mem[address] = b;
         Ok(())
     }
 
+    fn load_from_slice(&mut self, data: &[u8], address: usize) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            self.size,
+            address,
+            data.len(),
+            CpuErrorType::MemoryLoad,
+            Some(format!(
+                "{} bytes at ${:x} exceed memory size ${:x}",
+                data.len(),
+                address,
+                self.size
+            )),
+        )?;
+        self.mem[address..address + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn set_rom_region(&mut self, address: usize, len: usize, report_error: bool) {
+        self.roms.push(RomRegion {
+            address,
+            len,
+            report_error,
+        });
+    }
+
     fn get_size(&self) -> usize {
         self.size
     }
 
     fn clear(&mut self) {
-        let l = self.size;
-        self.cur.get_mut().clear();
-        self.cur.get_mut().resize(l, 0x0);
-        self.cur.set_position(0);
+        self.policy.fill(&mut self.mem);
     }
 
-    fn load(&mut self, path: &str, address: usize) -> Result<(), CpuError> {
-        // read file to a tmp vec
-        let mut f = File::open(path)?;
-        let mut tmp: Vec<u8> = Vec::new();
-        f.read_to_end(&mut tmp)?;
-        let mut l = tmp.len();
-
-        // truncate bigger files to 64k (max addressable size)
-        if tmp.len() > 0x10000 {
-            tmp.truncate(0x10000);
-            l = 0x10000
-        }
-
-        // check size
-        cpu_error::check_address_boundaries(
-            self.size,
-            address,
-            l as usize,
-            CpuErrorType::MemoryLoad,
-            Some(String::from(path)),
-        )?;
+    fn set_init_policy(&mut self, policy: MemoryInitPolicy) {
+        self.policy = policy;
+    }
 
-        // read in memory at the given offset
-        let m = self.cur.get_mut();
-        m.splice(address..address + l as usize, tmp);
-        println!("{} correctly loaded at ${:04x} !", path, address);
-        Ok(())
+    fn init_policy(&self) -> MemoryInitPolicy {
+        self.policy
     }
 }
 
+/**
+ * read a file to be loaded at address in a memory of the given size. files bigger than 0xffff are truncated.
+ */
+#[cfg(feature = "std")]
+fn read_file(path: &str, address: usize, size: usize) -> Result<Vec<u8>, CpuError> {
+    // read file to a tmp vec
+    let mut tmp = std::fs::read(path)?;
+
+    // truncate bigger files to 64k (max addressable size)
+    tmp.truncate(0x10000);
+
+    // check size
+    cpu_error::check_address_boundaries(
+        size,
+        address,
+        tmp.len(),
+        CpuErrorType::MemoryLoad,
+        Some(String::from(path)),
+    )?;
+    Ok(tmp)
+}
+
 /**
  * returns an istance of DefaultMemory
  *
  */
 pub fn new_default() -> Box<dyn Memory> {
     // create addressable 64k memory
-    let size = 0x10000;
-    let mut m = DefaultMemory {
-        size: size as usize,
-        cur: Cursor::new(Vec::with_capacity(size)),
-    };
-    // and fill with zeroes
-    let v = m.cur.get_mut();
-    for _ in 0..size {
-        v.push(0)
-    }
-
-    Box::new(m)
+    new_sized(0x10000)
+}
+
+/**
+ * returns an istance of DefaultMemory of the given size, zero filled.
+ *
+ * > with less than 64k the vectors are not addressable, reset() must be given the start address.
+ */
+pub fn new_sized(size: usize) -> Box<dyn Memory> {
+    new_with_policy(size, MemoryInitPolicy::Zero)
+}
+
+/**
+ * returns an istance of DefaultMemory of the given size, filled according to policy (which is kept for clear()).
+ *
+ * ```
+ * use rv6502emu::memory::{self, MemoryInitPolicy};
+ *
+ * let m = memory::new_with_policy(0x10000, MemoryInitPolicy::Pattern(0x00, 0xff));
+ * assert_eq!(m.dump_range(0x3e, 4).unwrap(), [0x00, 0x00, 0xff, 0xff]);
+ * ```
+ */
+pub fn new_with_policy(size: usize, policy: MemoryInitPolicy) -> Box<dyn Memory> {
+    let mut mem = vec![0; size];
+    policy.fill(&mut mem);
+    Box::new(DefaultMemory {
+        size,
+        mem,
+        roms: Vec::new(),
+        policy,
+    })
+}
+
+/**
+ * returns a 64k memory where the given slots are windows of bank_size bytes on a set of switchable banks, see Memory::select_bank().
+ *
+ * bank_size is usually $2000 (8K) or $4000 (16K) and must be a power of two, slots are the start addresses of the windows
+ * (aligned to bank_size) and any of the banks may be mapped in any slot. initially slot n maps bank n.
+ */
+pub fn new_banked(
+    bank_size: usize,
+    slots: &[usize],
+    banks: usize,
+) -> Result<Box<dyn Memory>, CpuError> {
+    Ok(Box::new(BankedMemory::new(bank_size, slots, banks)?))
+}
+
+/**
+ * returns a 64k memory where the first size bytes are mirrored up to end (excluded), i.e. new_mirrored(0x800, 0x2000) for the
+ * NES 2K ram mirrored across $0000-$1fff.
+ *
+ * size must divide end.
+ */
+pub fn new_mirrored(size: usize, end: usize) -> Result<Box<dyn Memory>, CpuError> {
+    Ok(Box::new(MirroredMemory::new(size, end)?))
 }
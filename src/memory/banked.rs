@@ -0,0 +1,201 @@
+/*
+ * Filename: /src/memory/banked.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 19:41:05
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! 64k memory with switchable bank windows.
+
+use crate::cpu::cpu_error;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::memory::{Memory, MemoryInitPolicy};
+use alloc::{format, string::String, vec, vec::Vec};
+
+/**
+ * a window on the banks.
+ */
+struct Slot {
+    /// start address.
+    address: usize,
+    /// the mapped bank.
+    bank: usize,
+}
+
+/**
+ * 64k of ram, where each slot maps one of the banks.
+ */
+pub(super) struct BankedMemory {
+    ram: Vec<u8>,
+    banks: Vec<u8>,
+    bank_size: usize,
+    slots: Vec<Slot>,
+    policy: MemoryInitPolicy,
+}
+
+/**
+ * returns a Generic error with the given message.
+ */
+fn invalid(msg: String) -> CpuError {
+    CpuError::new_default(CpuErrorType::Generic, 0, Some(msg))
+}
+
+impl BankedMemory {
+    /**
+     * creates the memory, see memory::new_banked().
+     */
+    pub(super) fn new(
+        bank_size: usize,
+        slots: &[usize],
+        banks: usize,
+    ) -> Result<BankedMemory, CpuError> {
+        if !bank_size.is_power_of_two() || bank_size > 0x8000 {
+            return Err(invalid(format!("invalid bank size ${:x}", bank_size)));
+        }
+        if slots.len() > banks {
+            return Err(invalid(format!(
+                "{} banks are not enough for {} slots",
+                banks,
+                slots.len()
+            )));
+        }
+        for (i, s) in slots.iter().enumerate() {
+            // aligned slots can only overlap if they're the same
+            if s % bank_size != 0 || s + bank_size > 0x10000 || slots[..i].contains(s) {
+                return Err(invalid(format!("invalid slot ${:04x}", s)));
+            }
+        }
+        Ok(BankedMemory {
+            ram: vec![0; 0x10000],
+            banks: vec![0; banks * bank_size],
+            bank_size,
+            slots: slots
+                .iter()
+                .enumerate()
+                .map(|(i, s)| Slot {
+                    address: *s,
+                    bank: i,
+                })
+                .collect(),
+            policy: MemoryInitPolicy::Zero,
+        })
+    }
+
+    /**
+     * returns the offset in banks if address is in a slot.
+     */
+    fn bank_offset(&self, address: usize) -> Option<usize> {
+        self.slots
+            .iter()
+            .find(|s| address >= s.address && address < s.address + self.bank_size)
+            .map(|s| s.bank * self.bank_size + address - s.address)
+    }
+
+    fn byte(&self, address: usize) -> u8 {
+        match self.bank_offset(address) {
+            Some(o) => self.banks[o],
+            None => self.ram[address],
+        }
+    }
+
+    fn byte_mut(&mut self, address: usize) -> &mut u8 {
+        match self.bank_offset(address) {
+            Some(o) => &mut self.banks[o],
+            None => &mut self.ram[address],
+        }
+    }
+}
+
+impl Memory for BankedMemory {
+    fn read_byte(&mut self, address: usize) -> Result<u8, CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 1, CpuErrorType::MemoryRead, None)?;
+        Ok(self.byte(address))
+    }
+
+    fn read_word_le(&mut self, address: usize) -> Result<u16, CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 2, CpuErrorType::MemoryRead, None)?;
+
+        // the word may straddle a slot boundary
+        Ok(((self.byte(address + 1) as u16) << 8) | (self.byte(address) as u16))
+    }
+
+    fn write_word_le(&mut self, address: usize, w: u16) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 2, CpuErrorType::MemoryWrite, None)?;
+        *self.byte_mut(address) = (w & 0xff) as u8;
+        *self.byte_mut(address + 1) = (w >> 8) as u8;
+        Ok(())
+    }
+
+    fn write_byte(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 1, CpuErrorType::MemoryWrite, None)?;
+        *self.byte_mut(address) = b;
+        Ok(())
+    }
+
+    fn get_size(&self) -> usize {
+        0x10000
+    }
+
+    fn clear(&mut self) {
+        self.policy.fill(&mut self.ram);
+        self.policy.fill(&mut self.banks);
+    }
+
+    fn set_init_policy(&mut self, policy: MemoryInitPolicy) {
+        self.policy = policy;
+    }
+
+    fn init_policy(&self) -> MemoryInitPolicy {
+        self.policy
+    }
+
+    fn read_range(&self, start: usize, buf: &mut [u8]) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            0x10000,
+            start,
+            buf.len(),
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = self.byte(start + i);
+        }
+        Ok(())
+    }
+
+    fn select_bank(&mut self, slot: usize, bank: usize) -> Result<(), CpuError> {
+        if bank * self.bank_size >= self.banks.len() {
+            return Err(invalid(format!("invalid bank {}", bank)));
+        }
+        match self.slots.get_mut(slot) {
+            Some(s) => {
+                s.bank = bank;
+                Ok(())
+            }
+            None => Err(invalid(format!("invalid slot {}", slot))),
+        }
+    }
+}
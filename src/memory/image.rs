@@ -0,0 +1,368 @@
+/*
+ * Filename: /src/memory/image.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 11:02:47
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Intel HEX and Motorola S-record parsers.
+
+use crate::cpu::cpu_error;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::memory::Memory;
+use alloc::{format, vec::Vec};
+
+/**
+ * supported image formats.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ImageFormat {
+    /// raw binary, loaded at a given address.
+    Bin,
+    /// Intel HEX.
+    IHex,
+    /// Motorola S-record.
+    SRec,
+}
+
+impl ImageFormat {
+    /**
+     * guess the format from the file content: ':' starts an Intel HEX record, 'S<digit>' an S-record, anything else is raw binary.
+     */
+    pub(crate) fn detect(data: &[u8]) -> ImageFormat {
+        let mut it = data.iter().skip_while(|b| b.is_ascii_whitespace());
+        match (it.next(), it.next()) {
+            (Some(b':'), _) => ImageFormat::IHex,
+            (Some(b'S'), Some(d)) if d.is_ascii_digit() => ImageFormat::SRec,
+            _ => ImageFormat::Bin,
+        }
+    }
+}
+
+/**
+ * a parsed image, data segments are written to memory only once the whole file has been validated.
+ */
+pub(crate) struct Image {
+    /// (address, data).
+    segments: Vec<(usize, Vec<u8>)>,
+    entry: Option<u16>,
+}
+
+impl Image {
+    /**
+     * writes the image to memory (bypassing rom protection), returns the number of bytes written and the entry point.
+     */
+    pub(crate) fn write_to<M: Memory + ?Sized>(
+        &self,
+        m: &mut M,
+        path: &str,
+    ) -> Result<(usize, Option<u16>), CpuError> {
+        // check all the segments first, so a bad image doesn't leave memory half written
+        for (address, data) in self.segments.iter() {
+            cpu_error::check_address_boundaries(
+                m.get_size(),
+                *address,
+                data.len(),
+                CpuErrorType::MemoryLoad,
+                Some(format!(
+                    "{}: {} bytes at ${:x} exceed memory size ${:x}",
+                    path,
+                    data.len(),
+                    address,
+                    m.get_size()
+                )),
+            )?;
+        }
+        let mut n = 0;
+        for (address, data) in self.segments.iter() {
+            for (i, b) in data.iter().enumerate() {
+                m.write_byte_privileged(address + i, *b)?;
+            }
+            n += data.len();
+        }
+        Ok((n, self.entry))
+    }
+}
+
+/**
+ * build a MemoryLoad error for a malformed record.
+ */
+fn load_error(path: &str, line: usize, msg: &str) -> CpuError {
+    CpuError::new_default(
+        CpuErrorType::MemoryLoad,
+        0,
+        Some(format!("{}:{}: {}", path, line, msg)),
+    )
+}
+
+/**
+ * decodes a string of hex digit pairs.
+ */
+fn decode_hex(s: &str, path: &str, line: usize) -> Result<Vec<u8>, CpuError> {
+    if s.len() % 2 != 0 || !s.is_ascii() {
+        return Err(load_error(path, line, "odd number of hex digits"));
+    }
+    let mut v = Vec::with_capacity(s.len() / 2);
+    for i in (0..s.len()).step_by(2) {
+        match u8::from_str_radix(&s[i..i + 2], 16) {
+            Ok(b) => v.push(b),
+            Err(_) => {
+                return Err(load_error(
+                    path,
+                    line,
+                    &format!("invalid hex digits '{}'", &s[i..i + 2]),
+                ))
+            }
+        }
+    }
+    Ok(v)
+}
+
+/**
+ * big-endian value of up to 4 bytes.
+ */
+fn be_value(b: &[u8]) -> usize {
+    b.iter().fold(0, |acc, x| (acc << 8) | *x as usize)
+}
+
+/**
+ * checks that a record doesn't cross the 64k boundary.
+ */
+fn check_segment(address: usize, len: usize, path: &str, line: usize) -> Result<(), CpuError> {
+    if len > 0 && address + len - 1 > 0xffff {
+        return Err(load_error(
+            path,
+            line,
+            &format!(
+                "record at ${:x} ({} bytes) exceeds the 64k address space",
+                address, len
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/**
+ * checks that an entry point fits 16 bit.
+ */
+fn check_entry(entry: usize, path: &str, line: usize) -> Result<u16, CpuError> {
+    if entry > 0xffff {
+        return Err(load_error(
+            path,
+            line,
+            &format!("entry point ${:x} exceeds the 64k address space", entry),
+        ));
+    }
+    Ok(entry as u16)
+}
+
+/**
+ * parses an Intel HEX file (record types 00-05).
+ */
+pub(crate) fn parse_ihex(path: &str, text: &str) -> Result<Image, CpuError> {
+    let mut img = Image {
+        segments: Vec::new(),
+        entry: None,
+    };
+    let mut base: usize = 0;
+    for (i, l) in text.lines().enumerate() {
+        let line = i + 1;
+        let l = l.trim();
+        if l.is_empty() {
+            continue;
+        }
+        if !l.starts_with(':') {
+            return Err(load_error(path, line, "record doesn't start with ':'"));
+        }
+        let rec = decode_hex(&l[1..], path, line)?;
+        if rec.len() < 5 || rec.len() != rec[0] as usize + 5 {
+            return Err(load_error(path, line, "invalid record length"));
+        }
+
+        // all bytes, checksum included, must sum to 0
+        let sum = rec.iter().fold(0u8, |acc, x| acc.wrapping_add(*x));
+        if sum != 0 {
+            let expected = rec[..rec.len() - 1]
+                .iter()
+                .fold(0u8, |acc, x| acc.wrapping_add(*x))
+                .wrapping_neg();
+            return Err(load_error(
+                path,
+                line,
+                &format!(
+                    "checksum mismatch, expected ${:02x}, found ${:02x}",
+                    expected,
+                    rec[rec.len() - 1]
+                ),
+            ));
+        }
+        let offset = be_value(&rec[1..3]);
+        let data = &rec[4..rec.len() - 1];
+        match rec[3] {
+            0x00 => {
+                // data
+                let address = base + offset;
+                check_segment(address, data.len(), path, line)?;
+                if !data.is_empty() {
+                    img.segments.push((address, data.to_vec()));
+                }
+            }
+            0x01 => {
+                // eof, ignore anything after
+                return Ok(img);
+            }
+            0x02 | 0x04 => {
+                // extended segment/linear address
+                if data.len() != 2 {
+                    return Err(load_error(path, line, "invalid extended address record"));
+                }
+                base = if rec[3] == 0x02 {
+                    be_value(data) << 4
+                } else {
+                    be_value(data) << 16
+                };
+            }
+            0x03 | 0x05 => {
+                // start segment (CS:IP) / start linear address
+                if data.len() != 4 {
+                    return Err(load_error(path, line, "invalid start address record"));
+                }
+                let entry = if rec[3] == 0x03 {
+                    (be_value(&data[..2]) << 4) + be_value(&data[2..])
+                } else {
+                    be_value(data)
+                };
+                img.entry = Some(check_entry(entry, path, line)?);
+            }
+            t => {
+                return Err(load_error(
+                    path,
+                    line,
+                    &format!("unsupported record type ${:02x}", t),
+                ))
+            }
+        }
+    }
+    Err(load_error(path, text.lines().count(), "missing eof record"))
+}
+
+/**
+ * parses a Motorola S-record file (S0-S9, S4 excluded).
+ */
+pub(crate) fn parse_srec(path: &str, text: &str) -> Result<Image, CpuError> {
+    let mut img = Image {
+        segments: Vec::new(),
+        entry: None,
+    };
+    let mut data_records: usize = 0;
+    for (i, l) in text.lines().enumerate() {
+        let line = i + 1;
+        let l = l.trim();
+        if l.is_empty() {
+            continue;
+        }
+        let t = l.as_bytes();
+        if t.len() < 2 || t[0] != b'S' || !t[1].is_ascii_digit() {
+            return Err(load_error(
+                path,
+                line,
+                "record doesn't start with 'S<type>'",
+            ));
+        }
+        let rec = decode_hex(&l[2..], path, line)?;
+        if rec.len() < 2 || rec.len() != rec[0] as usize + 1 {
+            return Err(load_error(path, line, "invalid record length"));
+        }
+
+        // one's complement of the sum of count, address and data
+        let expected = !rec[..rec.len() - 1]
+            .iter()
+            .fold(0u8, |acc, x| acc.wrapping_add(*x));
+        if expected != rec[rec.len() - 1] {
+            return Err(load_error(
+                path,
+                line,
+                &format!(
+                    "checksum mismatch, expected ${:02x}, found ${:02x}",
+                    expected,
+                    rec[rec.len() - 1]
+                ),
+            ));
+        }
+
+        // address size depends on the record type
+        let rec_type = t[1] - b'0';
+        let addr_size = match rec_type {
+            0 | 1 | 5 | 9 => 2,
+            2 | 6 | 8 => 3,
+            3 | 7 => 4,
+            _ => {
+                return Err(load_error(
+                    path,
+                    line,
+                    &format!("unsupported record type S{}", rec_type),
+                ))
+            }
+        };
+        if rec.len() < addr_size + 2 {
+            return Err(load_error(path, line, "record too short"));
+        }
+        let address = be_value(&rec[1..1 + addr_size]);
+        let data = &rec[1 + addr_size..rec.len() - 1];
+        match rec_type {
+            0 => {
+                // header, ignored
+            }
+            1 | 2 | 3 => {
+                check_segment(address, data.len(), path, line)?;
+                if !data.is_empty() {
+                    img.segments.push((address, data.to_vec()));
+                }
+                data_records += 1;
+            }
+            5 | 6 => {
+                // record count
+                if address != data_records {
+                    return Err(load_error(
+                        path,
+                        line,
+                        &format!(
+                            "record count mismatch, expected {}, found {}",
+                            address, data_records
+                        ),
+                    ));
+                }
+            }
+            _ => {
+                // termination with entry point
+                img.entry = Some(check_entry(address, path, line)?);
+                return Ok(img);
+            }
+        }
+    }
+    // termination record is optional
+    Ok(img)
+}
@@ -0,0 +1,102 @@
+/*
+ * Filename: /src/memory/init_policy.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 21:12:37
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! the contents of the memory at construction and after clear().
+
+use core::fmt::{Display, Formatter};
+
+/**
+ * size of the stripes written by MemoryInitPolicy::Pattern.
+ */
+pub const PATTERN_STRIPE_SIZE: usize = 64;
+
+/**
+ * how the memory is filled at construction and by Memory::clear().
+ *
+ * real hardware powers on with garbage in ram, a policy other than Zero helps finding code relying on uninitialized memory.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryInitPolicy {
+    /// all $00 (the default).
+    #[default]
+    Zero,
+    /// all $ff.
+    Ones,
+    /// alternating stripes of PATTERN_STRIPE_SIZE bytes, first a then b, i.e. Pattern(0x00, 0xff) for the C64 power-on pattern.
+    Pattern(u8, u8),
+    /// pseudo-random bytes, always the same for the same seed.
+    Random { seed: u64 },
+}
+
+impl MemoryInitPolicy {
+    /**
+     * fills buf according to the policy, buf[0] is the first byte of the memory.
+     */
+    pub fn fill(&self, buf: &mut [u8]) {
+        match *self {
+            MemoryInitPolicy::Zero => buf.fill(0),
+            MemoryInitPolicy::Ones => buf.fill(0xff),
+            MemoryInitPolicy::Pattern(a, b) => {
+                for (i, stripe) in buf.chunks_mut(PATTERN_STRIPE_SIZE).enumerate() {
+                    stripe.fill(if i % 2 == 0 { a } else { b });
+                }
+            }
+            MemoryInitPolicy::Random { seed } => {
+                let mut state = seed;
+                for chunk in buf.chunks_mut(8) {
+                    let r = splitmix64(&mut state).to_le_bytes();
+                    chunk.copy_from_slice(&r[..chunk.len()]);
+                }
+            }
+        }
+    }
+}
+
+impl Display for MemoryInitPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MemoryInitPolicy::Zero => write!(f, "zero"),
+            MemoryInitPolicy::Ones => write!(f, "ones"),
+            MemoryInitPolicy::Pattern(a, b) => write!(f, "pattern ${:02x} ${:02x}", a, b),
+            MemoryInitPolicy::Random { seed } => write!(f, "random seed=${:x}", seed),
+        }
+    }
+}
+
+/**
+ * splitmix64, a small and fast generator which is good enough for garbage.
+ */
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
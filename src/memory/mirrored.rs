@@ -0,0 +1,142 @@
+/*
+ * Filename: /src/memory/mirrored.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 19:44:52
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! 64k memory with the low addresses mirrored.
+
+use crate::cpu::cpu_error;
+use crate::cpu::cpu_error::{CpuError, CpuErrorType};
+use crate::memory::{Memory, MemoryInitPolicy};
+use alloc::{format, vec, vec::Vec};
+
+/**
+ * 64k of ram, where addresses below end map to address % size.
+ */
+pub(super) struct MirroredMemory {
+    ram: Vec<u8>,
+    size: usize,
+    end: usize,
+    policy: MemoryInitPolicy,
+}
+
+impl MirroredMemory {
+    /**
+     * creates the memory, see memory::new_mirrored().
+     */
+    pub(super) fn new(size: usize, end: usize) -> Result<MirroredMemory, CpuError> {
+        if size == 0 || end > 0x10000 || !end.is_multiple_of(size) {
+            return Err(CpuError::new_default(
+                CpuErrorType::Generic,
+                0,
+                Some(format!(
+                    "invalid mirror, ${:x} bytes up to ${:04x}",
+                    size, end
+                )),
+            ));
+        }
+        Ok(MirroredMemory {
+            ram: vec![0; 0x10000],
+            size,
+            end,
+            policy: MemoryInitPolicy::Zero,
+        })
+    }
+
+    /**
+     * returns the address in ram backing address.
+     */
+    fn translate(&self, address: usize) -> usize {
+        if address < self.end {
+            return address % self.size;
+        }
+        address
+    }
+}
+
+impl Memory for MirroredMemory {
+    fn read_byte(&mut self, address: usize) -> Result<u8, CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 1, CpuErrorType::MemoryRead, None)?;
+        Ok(self.ram[self.translate(address)])
+    }
+
+    fn read_word_le(&mut self, address: usize) -> Result<u16, CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 2, CpuErrorType::MemoryRead, None)?;
+
+        // the msb may be on the next mirror
+        let lsb = self.ram[self.translate(address)];
+        let msb = self.ram[self.translate(address + 1)];
+        Ok(((msb as u16) << 8) | (lsb as u16))
+    }
+
+    fn write_word_le(&mut self, address: usize, w: u16) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 2, CpuErrorType::MemoryWrite, None)?;
+        let lsb = self.translate(address);
+        let msb = self.translate(address + 1);
+        self.ram[lsb] = (w & 0xff) as u8;
+        self.ram[msb] = (w >> 8) as u8;
+        Ok(())
+    }
+
+    fn write_byte(&mut self, address: usize, b: u8) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(0x10000, address, 1, CpuErrorType::MemoryWrite, None)?;
+        let a = self.translate(address);
+        self.ram[a] = b;
+        Ok(())
+    }
+
+    fn get_size(&self) -> usize {
+        0x10000
+    }
+
+    fn clear(&mut self) {
+        self.policy.fill(&mut self.ram);
+    }
+
+    fn set_init_policy(&mut self, policy: MemoryInitPolicy) {
+        self.policy = policy;
+    }
+
+    fn init_policy(&self) -> MemoryInitPolicy {
+        self.policy
+    }
+
+    fn read_range(&self, start: usize, buf: &mut [u8]) -> Result<(), CpuError> {
+        cpu_error::check_address_boundaries(
+            0x10000,
+            start,
+            buf.len(),
+            CpuErrorType::MemoryRead,
+            None,
+        )?;
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = self.ram[self.translate(start + i)];
+        }
+        Ok(())
+    }
+}
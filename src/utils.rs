@@ -28,11 +28,16 @@
  * SOFTWARE.
  */
 
+use crate::bus::Bus;
 use crate::cpu::addressing_modes::AddressingMode;
 use crate::cpu::cpu_error::CpuError;
-use crate::cpu::opcodes::OpcodeMarker;
-use crate::cpu::opcodes::OPCODE_MATRIX;
+use crate::cpu::debugger::{dbg_println, Debugger};
+use crate::cpu::opcodes::opcode_info;
 use crate::cpu::Cpu;
+#[cfg(feature = "std")]
+use crate::cpu::CpuType;
+use alloc::format;
+use core::fmt::Display;
 use log::*;
 
 /**
@@ -48,6 +53,7 @@ pub(crate) fn is_signed(n: u8) -> bool {
 /**
  * returns 1 if string is prepended with $, 0 otherwise.
  */
+#[cfg(feature = "std")]
 pub(crate) fn is_dollar_hex(v: &str) -> usize {
     if v.chars().next().unwrap_or_default() != '$' {
         return 0;
@@ -58,6 +64,7 @@ pub(crate) fn is_dollar_hex(v: &str) -> usize {
 /**
  * activate logging on stdout through env_logger (max level).
  */
+#[cfg(feature = "std")]
 pub(crate) fn enable_logging_internal(enable: bool) {
     if enable == true {
         let _ = env_logger::builder()
@@ -80,36 +87,54 @@ pub(crate) fn log_enabled() -> bool {
 }
 
 /**
- * display opcode string, currently implemented to stdout
+ * display text on the debugger output, or log it (info level) without a debugger.
  */
-pub(crate) fn debug_out_opcode<A: AddressingMode>(
-    c: &mut Cpu,
+pub(crate) fn debug_out_text(d: Option<&Debugger>, s: &dyn Display) {
+    match d {
+        Some(dbg) => dbg_println!(dbg, "{}", s),
+        None => info!("{}", s),
+    }
+}
+
+/**
+ * display opcode string (undocumented opcodes marked with '*'), on the debugger output or the log.
+ */
+pub(crate) fn debug_out_opcode<A: AddressingMode, B: Bus>(
+    c: &mut Cpu<B>,
+    d: Option<&Debugger>,
     opcode_name: &str,
 ) -> Result<(), CpuError> {
     if log_enabled() {
-        let opc_string = A::repr(c, opcode_name)?;
-        println!("\t{}", opc_string);
+        // undocumented opcodes are marked with '*', as in the disassembler
+        let b = c.fetch()?;
+        let opc_string = if opcode_info(b, c.cpu_type()).undocumented {
+            A::repr(c, &format!("*{}", opcode_name))?
+        } else {
+            A::repr(c, opcode_name)?
+        };
+        debug_out_text(d, &format_args!("\t{}", opc_string));
     }
     Ok(())
 }
 
 /**
- * display registers and cycles, currently implemented to stdout
+ * display registers and cycles, on the debugger output.
  */
-pub(crate) fn debug_out_registers(c: &Cpu) {
-    println!("\t{}, cycles={}", c.regs, c.cycles);
+pub(crate) fn debug_out_registers<B: Bus>(d: &Debugger, c: &Cpu<B>) {
+    dbg_println!(d, "\t{}, cycles={}", c.regs, c.cycles);
 }
 
 /**
- * prints the opcode table, for debugging ....
+ * logs the opcode table for the given cpu type (16 opcodes per line, info level), for debugging ....
  */
+#[cfg(feature = "std")]
 #[allow(dead_code)]
-pub fn debug_out_opcode_table() {
-    let mut c = 0;
-    for (i, item) in OPCODE_MATRIX.iter().enumerate() {
-        let mrk: OpcodeMarker = item.3;
+pub fn debug_out_opcode_table(t: CpuType) {
+    let mut line = String::new();
+    for i in 0..=0xff_usize {
+        let info = opcode_info(i as u8, t);
 
-        print!(
+        line.push_str(&format!(
             "{}0x{:02x}={}({})",
             if i & 0xf == 2
                 || i & 0xf == 3
@@ -126,16 +151,14 @@ pub fn debug_out_opcode_table() {
                 ""
             },
             i,
-            mrk.name,
-            mrk.id
-        );
-        if c == 15 {
-            print!("\n");
-            c = 0;
+            info.mnemonic,
+            info.addressing_mode
+        ));
+        if i & 0xf == 0xf {
+            info!("{}", line);
+            line.clear();
         } else {
-            print!(",");
-            c += 1;
+            line.push(',');
         }
     }
-    print!("\n");
 }
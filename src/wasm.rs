@@ -0,0 +1,175 @@
+/*
+ * Filename: /src/wasm.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 10:12:40
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bus;
+use crate::cpu::cpu_error::CpuError;
+use crate::cpu::{Cpu, ResetKind};
+use crate::memory;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use wasm_bindgen::prelude::*;
+
+/**
+ * converts a CpuError to a javascript exception.
+ */
+fn to_js(e: CpuError) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+/**
+ * registers snapshot returned by WasmCpu::step() and WasmCpu::registers().
+ */
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct WasmRegisters {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub s: u8,
+    pub p: u8,
+    pub pc: u16,
+    /// total elapsed cycles.
+    pub cycles: usize,
+}
+
+/**
+ * a Cpu with a DefaultMemory of the requested size on a DefaultBus, exposed to javascript through wasm_bindgen.
+ *
+ * the debugger and file loading are not available here, programs are passed in as byte arrays.
+ */
+#[wasm_bindgen]
+pub struct WasmCpu {
+    c: Cpu,
+}
+
+#[wasm_bindgen]
+impl WasmCpu {
+    /**
+     * creates a 6502 with memory_size bytes of zero filled memory.
+     */
+    #[wasm_bindgen(constructor)]
+    pub fn new(memory_size: usize) -> WasmCpu {
+        let m = memory::new_sized(memory_size);
+        WasmCpu {
+            c: Cpu::new(bus::new_default(m), None, None),
+        }
+    }
+
+    /**
+     * copies bytes to memory at address.
+     */
+    pub fn load(&mut self, bytes: &[u8], address: u16) -> Result<(), JsValue> {
+        self.c
+            .bus
+            .get_memory()
+            .load_from_slice(bytes, address as usize)
+            .map_err(to_js)
+    }
+
+    /**
+     * power-on reset, starting at address or at the reset vector if undefined.
+     */
+    pub fn reset(&mut self, address: Option<u16>) -> Result<(), JsValue> {
+        self.c.reset(address, ResetKind::PowerOn).map_err(to_js)
+    }
+
+    /**
+     * executes one instruction (or services a pending interrupt) and returns the registers snapshot.
+     */
+    pub fn step(&mut self) -> Result<JsValue, JsValue> {
+        self.c.run_until(None, 0, 1).map_err(to_js)?;
+        Ok(self.registers().into())
+    }
+
+    /**
+     * returns the current registers.
+     */
+    pub fn registers(&self) -> WasmRegisters {
+        WasmRegisters {
+            a: self.c.regs.a,
+            x: self.c.regs.x,
+            y: self.c.regs.y,
+            s: self.c.regs.s,
+            p: self.c.regs.p.bits(),
+            pc: self.c.regs.pc,
+            cycles: self.c.cycles(),
+        }
+    }
+
+    /**
+     * returns len bytes of memory starting at address.
+     */
+    pub fn read_mem(&mut self, address: u16, len: usize) -> Result<Vec<u8>, JsValue> {
+        let mut buf = vec![0; len];
+        self.c
+            .bus
+            .get_memory()
+            .read_range(address as usize, &mut buf)
+            .map_err(to_js)?;
+        Ok(buf)
+    }
+
+    /**
+     * writes bytes to memory at address, ignoring rom regions.
+     */
+    pub fn write_mem(&mut self, address: u16, bytes: &[u8]) -> Result<(), JsValue> {
+        let m = self.c.bus.get_memory();
+        for (i, b) in bytes.iter().enumerate() {
+            m.write_byte_privileged(address as usize + i, *b)
+                .map_err(to_js)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * asserts or releases the (level triggered) irq line.
+     */
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.c.set_irq_line(asserted);
+    }
+
+    /**
+     * disassembles count instructions starting at address, one "$addr: text" line each.
+     */
+    pub fn disassemble(&mut self, address: u16, count: usize) -> Result<Vec<String>, JsValue> {
+        let mut lines = Vec::new();
+        let mut addr = address;
+        for _ in 0..count {
+            let (s, size) = self.c.disassemble_at(addr).map_err(to_js)?;
+            lines.push(format!("${:04x}: {}", addr, s));
+            addr = addr.wrapping_add(size as u16);
+        }
+        Ok(lines)
+    }
+}
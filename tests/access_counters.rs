@@ -0,0 +1,117 @@
+/*
+ * Filename: /tests/access_counters.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:52:30
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the per-address read/write counters.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, ResetKind, TrapReason};
+
+/**
+ * copies 16 bytes from $1000 to $2000 twice (with a pha/pla after each round), then jmp *.
+ */
+fn memcpy(counters: bool) -> (Cpu, Debugger) {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    c.enable_access_counters(counters);
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[
+            0xa0, 0x02, // ldy #$02
+            0xa2, 0x00, // ldx #$00
+            0xbd, 0x00, 0x10, // lda $1000,x
+            0x9d, 0x00, 0x20, // sta $2000,x
+            0xe8, // inx
+            0xe0, 0x10, // cpx #$10
+            0xd0, 0xf5, // bne $0404
+            0x48, // pha
+            0x68, // pla
+            0x88, // dey
+            0xd0, 0xee, // bne $0402
+            0x4c, 0x14, 0x04, // jmp *
+        ],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let r = c.run_until_trap(0).unwrap();
+    assert_eq!((r.reason, r.pc), (TrapReason::SelfJump, 0x414));
+    (c, d)
+}
+
+#[test]
+fn memcpy_counts() {
+    // disabled by default
+    let (c, _) = memcpy(false);
+    assert!(!c.is_access_counters_enabled());
+    assert_eq!(c.access_counts(0x1000), (0, 0));
+    assert!(c.top_accessed(0).is_empty());
+
+    let (c, _) = memcpy(true);
+    assert!(c.is_access_counters_enabled());
+    for i in 0..0x10 {
+        assert_eq!(c.access_counts(0x1000 + i), (2, 0));
+        assert_eq!(c.access_counts(0x2000 + i), (0, 2));
+    }
+    assert_eq!(c.access_counts(0x1010), (0, 0));
+    assert_eq!(c.access_counts(0x2010), (0, 0));
+
+    // the stack byte is pushed and pulled twice, the program itself (opcodes, immediates and branch offsets) is not
+    // counted
+    assert_eq!(c.access_counts(0x1ff), (2, 2));
+    assert_eq!(c.access_counts(0x404), (0, 0));
+    assert_eq!(c.access_counts(0x40e), (0, 0));
+
+    // 16 + 16 + 1 addresses, the stack first (4 accesses) then by address
+    let all = c.top_accessed(0);
+    assert_eq!(all.len(), 33);
+    assert_eq!(all[0], (0x1ff, 2, 2));
+    assert_eq!(all[1], (0x1000, 2, 0));
+    assert_eq!(all[32], (0x200f, 0, 2));
+    assert_eq!(c.top_accessed(2).len(), 2);
+}
+
+#[test]
+fn heat_command() {
+    let (mut c, mut d) = memcpy(true);
+    assert!(d.parse_cmd(&mut c, "heat $1000 $20ff").1);
+    assert!(d.parse_cmd(&mut c, "heat 1008 1009").1);
+
+    // enabling clears the counters
+    assert!(d.parse_cmd(&mut c, "heat on").1);
+    assert_eq!(c.access_counts(0x1000), (0, 0));
+
+    // missing or reversed range
+    assert!(!d.parse_cmd(&mut c, "heat").1);
+    assert!(!d.parse_cmd(&mut c, "heat $1000").1);
+    assert!(!d.parse_cmd(&mut c, "heat $2000 $1000").1);
+    assert!(d.parse_cmd(&mut c, "heat off").1);
+    assert!(!c.is_access_counters_enabled());
+    assert!(!d.parse_cmd(&mut c, "heat $1000 $20ff").1);
+}
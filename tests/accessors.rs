@@ -0,0 +1,148 @@
+/*
+ * Filename: /tests/accessors.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 23:12:05
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the Cpu accessors and its Display.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, CpuFlags, CpuOperation, CpuType, InterruptState, ResetKind, Vectors};
+
+/**
+ * compiles only if T is Copy and Eq.
+ */
+fn copy_eq<T: Copy + Eq>(v: T) -> (T, T) {
+    (v, v)
+}
+
+#[test]
+fn cpu_type() {
+    let mut c = Cpu::new_default(None);
+    assert_eq!(c.cpu_type(), CpuType::MOS6502);
+    c.set_cpu_type(CpuType::WDC65C02);
+    assert_eq!(c.cpu_type(), CpuType::WDC65C02);
+
+    // Copy and Eq
+    let (a, b) = copy_eq(c.cpu_type());
+    assert_eq!(a, b);
+    assert_ne!(a, CpuType::MOS6510);
+    let (a, b) = copy_eq(CpuOperation::Exec);
+    assert_eq!(a, b);
+    assert_ne!(a, CpuOperation::Read);
+}
+
+#[test]
+fn cycles() {
+    // lda #$01, nop, nop
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0xa9, 0x01, 0xea, 0xea])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    assert_eq!(c.cycles(), 7);
+    let r = c.run_until(None, 0, 2).unwrap();
+    assert_eq!((r.cycles, c.cycles()), (4, 11));
+
+    // restarts from zero, the registers are untouched
+    c.reset_cycle_counter();
+    assert_eq!((c.cycles(), c.regs.pc, c.regs.a), (0, 0x403, 0x01));
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.cycles(), 2);
+}
+
+#[test]
+fn interrupt_state() {
+    // cli, nop, nop, stp
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(CpuType::WDC65C02);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0x58, 0xea, 0xea, 0xdb])
+        .unwrap();
+    d.write_memory(&mut c, 0x2000, &[0x40]).unwrap();
+    c.set_vector(Vectors::NMI, 0x2000).unwrap();
+    c.set_vector(Vectors::IRQ, 0x2000).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let s = c.interrupt_state();
+    assert_eq!(
+        s,
+        InterruptState {
+            irq_masked: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(s.to_string(), "no interrupts pending");
+
+    // a masked irq
+    c.set_irq_line(true);
+    let s = c.interrupt_state();
+    assert!(s.irq_line && s.irq_pending && s.irq_masked);
+    assert_eq!(s.to_string(), "IRQ pending (masked)");
+    c.set_irq_line(false);
+
+    // an nmi edge stays pending until serviced, the line does not
+    c.set_nmi_line(true);
+    let s = c.interrupt_state();
+    assert!(s.nmi_line && s.nmi_pending);
+    assert_eq!(s.to_string(), "NMI pending");
+    c.run_until(None, 0, 1).unwrap();
+    let s = c.interrupt_state();
+    assert!(s.nmi_line && !s.nmi_pending);
+    c.set_nmi_line(false);
+    assert!(!c.interrupt_state().nmi_line);
+
+    // rti already ran in the same step, then cli, nop, nop, stp
+    c.run_until(None, 0, 4).unwrap();
+    let s = c.interrupt_state();
+    assert!(s.halted && !s.irq_masked);
+    assert_eq!(s.to_string(), "halted");
+
+    // nmos jam
+    let mut c = Cpu::new_default(None);
+    d.write_memory(&mut c, 0x400, &[0x02]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 1).unwrap();
+    assert!(c.interrupt_state().jammed);
+    assert_eq!(c.interrupt_state().to_string(), "jammed");
+}
+
+#[test]
+fn display() {
+    let mut c = Cpu::new_default(None);
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    assert_eq!(
+        c.to_string(),
+        format!("{}, cpu=MOS6502, cycles=7, no interrupts pending", c.regs)
+    );
+    c.regs.p.remove(CpuFlags::I);
+    c.set_irq_line(true);
+    c.set_cpu_type(CpuType::Ricoh2A03);
+    assert_eq!(
+        c.to_string(),
+        format!("{}, cpu=Ricoh2A03, cycles=7, IRQ pending", c.regs)
+    );
+}
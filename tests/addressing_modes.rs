@@ -0,0 +1,185 @@
+/*
+ * Filename: /tests/addressing_modes.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 20:06:37
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! addressing modes edge cases.
+
+use rv6502emu::bus;
+use rv6502emu::cpu::cpu_error::CpuErrorType;
+use rv6502emu::cpu::debugger::{BreakpointType, Debugger};
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuOperation, CpuType, CpuView, ResetKind, StopReason,
+};
+use rv6502emu::memory;
+use std::sync::{Arc, Mutex};
+
+/**
+ * runs the instruction in prg at $0400 with the pointer $1234 at $ff/$00, returns a.
+ */
+fn run_zp_pointer(t: CpuType, prg: &[u8], x: u8, y: u8) -> u8 {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0xff, &[0x34]).unwrap();
+    d.write_memory(&mut c, 0x00, &[0x12]).unwrap();
+
+    // what would be read with the msb taken from $0100
+    d.write_memory(&mut c, 0x100, &[0x56]).unwrap();
+    d.write_memory(&mut c, 0x5634, &[0xee, 0xee]).unwrap();
+
+    d.write_memory(&mut c, 0x1234, &[0xaa, 0xbb]).unwrap();
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.x = x;
+    c.regs.y = y;
+    c.run_until(None, 0, 1).unwrap();
+    c.regs.a
+}
+
+#[test]
+fn zp_pointer_wraps() {
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        // lda ($ff),y
+        assert_eq!(run_zp_pointer(t, &[0xb1, 0xff], 0, 1), 0xbb);
+
+        // lda ($f0,x)
+        assert_eq!(run_zp_pointer(t, &[0xa1, 0xf0], 0x0f, 0), 0xaa);
+    }
+
+    // lda ($ff)
+    assert_eq!(run_zp_pointer(CpuType::WDC65C02, &[0xb2, 0xff], 0, 0), 0xaa);
+}
+
+#[test]
+fn address_wrapping() {
+    // lda $1234 at $fffe, the operand msb is at $0000
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0xfffe, &[0xad, 0x34]).unwrap();
+    d.write_memory(&mut c, 0x0000, &[0x12]).unwrap();
+    d.write_memory(&mut c, 0x1234, &[0x99]).unwrap();
+
+    // strict (default), the operand is past the end of memory
+    c.reset(Some(0xfffe), ResetKind::PowerOn).unwrap();
+    let e = c.run_until(None, 0, 1).unwrap_err();
+    assert_eq!((e.t, c.regs.a), (CpuErrorType::MemoryRead, 0));
+
+    // fast loop, then the checked one (with a hook installed)
+    c.set_address_wrapping(true);
+    for hooked in [false, true] {
+        if hooked {
+            c.set_hook(|_: &CpuCallbackContext, _: &mut CpuView| {});
+        }
+        c.reset(Some(0xfffe), ResetKind::PowerOn).unwrap();
+        let r = c.run_until(None, 0, 1).unwrap();
+        assert_eq!(r.reason, StopReason::InstructionsReached);
+        assert_eq!((c.regs.a, c.regs.pc), (0x99, 0x0001));
+    }
+
+    // 32k, lda $9234,x at $7ffe reads $1235, then sta $c000 (fetched at $8001, mirroring $0001) writes $4000
+    let mut c = Cpu::new(bus::new_default(memory::new_sized(0x8000)), None, None);
+    d.write_memory(&mut c, 0x7ffe, &[0xbd, 0x34]).unwrap();
+    d.write_memory(&mut c, 0x0000, &[0x92, 0x8d, 0x00, 0xc0])
+        .unwrap();
+    d.write_memory(&mut c, 0x1235, &[0x77]).unwrap();
+    c.set_address_wrapping(true);
+    c.reset(Some(0x7ffe), ResetKind::PowerOn).unwrap();
+    c.regs.x = 1;
+    c.run_until(None, 0, 2).unwrap();
+    assert_eq!((c.regs.a, c.regs.pc), (0x77, 0x8004));
+    assert_eq!(d.read_memory(&mut c, 0x4000, 1).unwrap(), [0x77]);
+}
+
+/**
+ * runs the jmp in prg at $0400 with x, returns (pc, cycles, pointer reads seen by the hook).
+ */
+fn run_jmp(t: CpuType, prg: &[u8], x: u8) -> (u16, usize, Vec<u16>) {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    let d = Debugger::new(false);
+
+    // pointers at $12fe, $12ff (msb from $1200 on NMOS) and $1310
+    d.write_memory(&mut c, 0x1200, &[0x56]).unwrap();
+    d.write_memory(&mut c, 0x12fe, &[0x22, 0x34, 0x78]).unwrap();
+    d.write_memory(&mut c, 0x1310, &[0xcd, 0xab]).unwrap();
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.x = x;
+    let reads: Arc<Mutex<Vec<u16>>> = Arc::new(Mutex::new(Vec::new()));
+    let r = reads.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+        if ctx.operation == CpuOperation::Read {
+            r.lock().unwrap().push(ctx.address);
+        }
+    });
+    let res = c.run_until(None, 0, 1).unwrap();
+    let v = reads.lock().unwrap().clone();
+    (c.regs.pc, res.cycles, v)
+}
+
+#[test]
+fn jmp_indirect() {
+    // jmp ($12ff), the NMOS bug takes the msb from $1200
+    let prg = [0x6c, 0xff, 0x12];
+    assert_eq!(
+        run_jmp(CpuType::MOS6502, &prg, 0),
+        (0x5634, 5, vec![0x12ff, 0x1200])
+    );
+    assert_eq!(
+        run_jmp(CpuType::WDC65C02, &prg, 0),
+        (0x7834, 6, vec![0x12ff, 0x1300])
+    );
+
+    // jmp ($12fe), no page crossing
+    let prg = [0x6c, 0xfe, 0x12];
+    for (t, cycles) in [(CpuType::MOS6502, 5), (CpuType::WDC65C02, 6)] {
+        assert_eq!(run_jmp(t, &prg, 0), (0x3422, cycles, vec![0x12fe, 0x12ff]));
+    }
+
+    // jmp ($12f0,x), x is added with carry before reading the pointer
+    let prg = [0x7c, 0xf0, 0x12];
+    assert_eq!(
+        run_jmp(CpuType::WDC65C02, &prg, 0x20),
+        (0xabcd, 6, vec![0x1310, 0x1311])
+    );
+    assert_eq!(
+        run_jmp(CpuType::WDC65C02, &prg, 0x0f),
+        (0x7834, 6, vec![0x12ff, 0x1300])
+    );
+
+    // the buggy msb read triggers a read breakpoint, the jump is not taken
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0x6c, 0xff, 0x12]).unwrap();
+    d.add_breakpoint(&mut c, 0x1200, BreakpointType::READ, None)
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!((r.reason, c.regs.pc), (StopReason::Breakpoint(0), 0x400));
+}
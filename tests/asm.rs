@@ -0,0 +1,109 @@
+/*
+ * Filename: /tests/asm.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 23:52:10
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the one-shot assembler.
+
+use rv6502emu::cpu::asm::{self, AsmError};
+use rv6502emu::cpu::disasm;
+use rv6502emu::cpu::CpuType;
+
+#[test]
+fn round_trip() {
+    // every opcode, with operand bytes covering the zeropage/absolute and forward/backward branches
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        for op in 0..=0xff_u8 {
+            for operand in [[0x12, 0x34], [0xf0, 0x00], [0x80, 0xff]] {
+                let i = disasm::decode(&[op, operand[0], operand[1]], 0x1000, t).unwrap();
+                let src = format!("{} {}", i.mnemonic, i.operand_text());
+                let v = asm::assemble(&src, 0x1000, t)
+                    .unwrap_or_else(|e| panic!("{} ({}): {}", src, t, e));
+
+                // undocumented duplicates may assemble to another opcode, they must decode the same
+                let j = disasm::decode(&v, 0x1000, t).unwrap();
+                assert_eq!(
+                    (j.mnemonic, j.mode, j.operand_text()),
+                    (i.mnemonic, i.mode, i.operand_text()),
+                    "{} ({})",
+                    src,
+                    t
+                );
+                if !i.is_undocumented {
+                    assert_eq!(v, i.bytes, "{} ({})", src, t);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn source() {
+    let src = "
+        ; copy loop
+        ldx #$00
+        lda $2000,x   ; source
+        sta ($10),y
+        inx
+        bne $0402
+        rts";
+    assert_eq!(
+        asm::assemble(src, 0x400, CpuType::MOS6502).unwrap(),
+        [0xa2, 0x00, 0xbd, 0x00, 0x20, 0x91, 0x10, 0xe8, 0xd0, 0xf8, 0x60]
+    );
+    assert!(asm::assemble("", 0x400, CpuType::MOS6502)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn errors() {
+    // with the source line number
+    let src = "nop\n\nlda #$1234\nnop";
+    let e = asm::assemble(src, 0x400, CpuType::MOS6502).unwrap_err();
+    assert_eq!(
+        e,
+        AsmError {
+            line: 3,
+            msg: String::from("invalid operand '#$1234'!")
+        }
+    );
+    assert_eq!(e.to_string(), "line 3: invalid operand '#$1234'!");
+
+    // 65c02 only
+    let e = asm::assemble("bra $0400", 0x400, CpuType::MOS6502).unwrap_err();
+    assert_eq!((e.line, e.msg.as_str()), (1, "invalid opcode!"));
+    assert!(asm::assemble("bra $0400", 0x400, CpuType::WDC65C02).is_ok());
+
+    // out of range, no symbols
+    let e = asm::assemble("nop\nbne $0500", 0x400, CpuType::MOS6502).unwrap_err();
+    assert_eq!(e.line, 2);
+    assert!(e.msg.contains("out of range"));
+    let e = asm::assemble("jmp start", 0x400, CpuType::MOS6502).unwrap_err();
+    assert_eq!(e.msg, "unknown symbol 'start'!");
+}
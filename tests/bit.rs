@@ -0,0 +1,141 @@
+/*
+ * Filename: /tests/bit.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:48:30
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! BIT, including the 65C02 zp,X / abs,X / #imm forms.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{opcode_info, AddressingModeId, Cpu, CpuType, ResetKind};
+
+/**
+ * runs prg at $400 with the given A and X, one instruction at a time, returns the cpu and the cycles of the last one.
+ */
+fn run(t: CpuType, prg: &[u8], a: u8, x: u8, instructions: usize) -> (Cpu, usize) {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    d.write_memory(&mut c, 0x15, &[0xc0]).unwrap();
+    d.write_memory(&mut c, 0x1310, &[0x40]).unwrap();
+    d.write_memory(&mut c, 0x1220, &[0x80]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.a = a;
+    c.regs.x = x;
+    let mut cycles = 0;
+    for _ in 0..instructions {
+        cycles = c.run_until(None, 0, 1).unwrap().cycles;
+    }
+    (c, cycles)
+}
+
+#[test]
+fn zpx() {
+    // bit $10,x
+    assert_eq!(
+        c02_decode(0x34),
+        ("bit", AddressingModeId::Zpx, String::from("BIT $10, X"))
+    );
+    let (c, cycles) = run(CpuType::WDC65C02, &[0x34, 0x10], 0x00, 0x05, 1);
+    assert!(c.regs.zero() && c.regs.negative() && c.regs.overflow());
+    assert_eq!((cycles, c.regs.pc), (4, 0x402));
+
+    let (c, _) = run(CpuType::WDC65C02, &[0x34, 0x10], 0x80, 0x05, 1);
+    assert!(!c.regs.zero() && c.regs.negative() && c.regs.overflow());
+}
+
+#[test]
+fn absx() {
+    assert_eq!(
+        c02_decode(0x3c),
+        ("bit", AddressingModeId::Abx, String::from("BIT $12f0, X"))
+    );
+
+    // bit $12f0,x crossing into $1310: V only
+    let (c, cycles) = run(CpuType::WDC65C02, &[0x3c, 0xf0, 0x12], 0x40, 0x20, 1);
+    assert!(!c.regs.zero() && !c.regs.negative() && c.regs.overflow());
+    assert_eq!((cycles, c.regs.pc), (5, 0x403));
+
+    // bit $1200,x at $1220, same page: N only, A & M = 0
+    let (c, cycles) = run(CpuType::WDC65C02, &[0x3c, 0x00, 0x12], 0x40, 0x20, 1);
+    assert!(c.regs.zero() && c.regs.negative() && !c.regs.overflow());
+    assert_eq!(cycles, 4);
+}
+
+#[test]
+fn imm() {
+    assert_eq!(
+        c02_decode(0x89),
+        ("bit", AddressingModeId::Imm, String::from("BIT #$c0"))
+    );
+
+    // bit $15 (sets N and V), bit #$00: only Z changes
+    let (c, cycles) = run(CpuType::WDC65C02, &[0x24, 0x15, 0x89, 0x00], 0xff, 0, 2);
+    assert!(c.regs.zero() && c.regs.negative() && c.regs.overflow());
+    assert_eq!(cycles, 2);
+
+    // clv, lda #$01 (N and V clear), bit #$c0: N and V stay clear even with bit 7 and 6 set
+    let (c, _) = run(CpuType::WDC65C02, &[0xb8, 0xa9, 0x01, 0x89, 0xc0], 0, 0, 3);
+    assert!(c.regs.zero() && !c.regs.negative() && !c.regs.overflow());
+    let (c, _) = run(CpuType::WDC65C02, &[0xb8, 0xa9, 0xc1, 0x89, 0xc0], 0, 0, 3);
+    assert!(!c.regs.zero() && c.regs.negative() && !c.regs.overflow());
+}
+
+#[test]
+fn nmos() {
+    // $89 is an undocumented nop #imm, $34 and $3c nop zp,x / abs,x
+    for op in [0x89, 0x34, 0x3c] {
+        let info = opcode_info(op, CpuType::MOS6502);
+        assert_ne!(info.mnemonic, "bit");
+        assert!(info.undocumented);
+    }
+
+    // flags unchanged
+    let (c, _) = run(CpuType::MOS6502, &[0xb8, 0xa9, 0x01, 0x89, 0xc0], 0, 0, 3);
+    assert!(!c.regs.zero() && !c.regs.negative() && !c.regs.overflow());
+    assert_eq!((c.regs.pc, c.regs.a), (0x405, 0x01));
+}
+
+/**
+ * decodes opcode on 65C02, with $10, $12f0 or #$c0 as operand.
+ */
+fn c02_decode(op: u8) -> (&'static str, AddressingModeId, String) {
+    let info = opcode_info(op, CpuType::WDC65C02);
+    let (mut c, _) = run(CpuType::WDC65C02, &[], 0, 0, 0);
+    let operand: &[u8] = match info.addressing_mode {
+        AddressingModeId::Abx => &[0xf0, 0x12],
+        AddressingModeId::Imm => &[0xc0],
+        _ => &[0x10],
+    };
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x2000, &[op]).unwrap();
+    d.write_memory(&mut c, 0x2001, operand).unwrap();
+    let (text, size) = c.disassemble_at(0x2000).unwrap();
+    assert_eq!(size, info.bytes);
+    (info.mnemonic, info.addressing_mode, text)
+}
@@ -0,0 +1,267 @@
+/*
+ * Filename: /tests/branches.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 19:08:27
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! relative branch targets at page edges, forward and backward.
+
+use rv6502emu::cpu::cpu_error::CpuErrorType;
+use rv6502emu::cpu::debugger::{BreakpointType, Debugger};
+use rv6502emu::cpu::disasm;
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuOperation, CpuType, CpuView, ResetKind, StopReason, Vectors,
+};
+use std::sync::{Arc, Mutex};
+
+/// branch locations around page boundaries.
+const EDGES: [u16; 6] = [0x04fd, 0x04fe, 0x04ff, 0x0500, 0x0501, 0x8080];
+
+/**
+ * executes the branch in prg at address, returns pc after it.
+ */
+fn run_branch(c: &mut Cpu, d: &Debugger, address: u16, prg: &[u8], carry: bool) -> u16 {
+    d.write_memory(c, address, prg).unwrap();
+    c.reset(Some(address), ResetKind::PowerOn).unwrap();
+    c.regs.set_carry(carry);
+    c.run_until(None, 0, 1).unwrap();
+    c.regs.pc
+}
+
+/**
+ * expected target, offset relative to the next instruction.
+ */
+fn target(next: u16, offset: u8) -> u16 {
+    next.wrapping_add(offset as i8 as u16)
+}
+
+#[test]
+fn rel() {
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        let mut c = Cpu::new_default(None);
+        c.set_cpu_type(t);
+        c.set_deadlock_detection(false);
+        let d = Debugger::new(false);
+        for address in EDGES {
+            for offset in 0..=255u8 {
+                // bcc, taken
+                let prg = [0x90, offset];
+                let pc = run_branch(&mut c, &d, address, &prg, false);
+                let next = address.wrapping_add(2);
+                assert_eq!(pc, target(next, offset), "{} bcc at ${:04x}", t, address);
+
+                // the disassembler agrees
+                let i = disasm::decode(&prg, address, t).unwrap();
+                assert_eq!(i.target, Some(pc));
+
+                // not taken
+                assert_eq!(run_branch(&mut c, &d, address, &prg, true), next);
+            }
+        }
+    }
+}
+
+#[test]
+fn bra() {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(CpuType::WDC65C02);
+    c.set_deadlock_detection(false);
+    let d = Debugger::new(false);
+    for address in EDGES {
+        for offset in 0..=255u8 {
+            let pc = run_branch(&mut c, &d, address, &[0x80, offset], false);
+            assert_eq!(pc, target(address.wrapping_add(2), offset));
+        }
+    }
+}
+
+#[test]
+fn zpr() {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(CpuType::WDC65C02);
+    c.set_deadlock_detection(false);
+    let d = Debugger::new(false);
+
+    // bit 0 of $10 is reset
+    d.write_memory(&mut c, 0x10, &[0xfe]).unwrap();
+    for address in EDGES {
+        for offset in 0..=255u8 {
+            let next = address.wrapping_add(3);
+
+            // bbr0 $10, taken
+            let prg = [0x0f, 0x10, offset];
+            let pc = run_branch(&mut c, &d, address, &prg, false);
+            assert_eq!(pc, target(next, offset), "bbr0 at ${:04x}", address);
+            let i = disasm::decode(&prg, address, CpuType::WDC65C02).unwrap();
+            assert_eq!(i.target, Some(pc));
+
+            // bbs0 $10, not taken
+            assert_eq!(
+                run_branch(&mut c, &d, address, &[0x8f, 0x10, offset], false),
+                next
+            );
+        }
+    }
+}
+
+#[test]
+fn deadlock() {
+    // bne *, with deadlock detection the branch to itself is an error returned to the caller
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x4fe, &[0xd0, 0xfe]).unwrap();
+    c.reset(Some(0x4fe), ResetKind::PowerOn).unwrap();
+    c.regs.set_zero(false);
+    let e = c.run_until(None, 0, 1).unwrap_err();
+    assert_eq!((e.t, e.address), (CpuErrorType::Deadlock, 0x4fe));
+    assert_eq!(c.regs.pc, 0x4fe);
+}
+
+#[test]
+fn idle_loop_nmi() {
+    // jmp * waiting for the nmi, whose handler (inc $10, rti) returns into the loop
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0x4c, 0x00, 0x04]).unwrap();
+    d.write_memory(&mut c, 0x600, &[0xe6, 0x10, 0x40]).unwrap();
+    c.set_vector(Vectors::NMI, 0x600).unwrap();
+    c.set_deadlock_detection(false);
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let res = c.run_until(None, 0, 10).unwrap();
+    assert_eq!(res.reason, StopReason::InstructionsReached);
+    assert_eq!(c.regs.pc, 0x400);
+
+    // the nmi breaks out of the loop, the handler runs (inc $10)
+    c.trigger_nmi();
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.regs.pc, 0x602);
+    assert_eq!(c.bus.dump_range(0x10, 1).unwrap(), [1]);
+    assert_eq!(c.stack_slice()[1..], [0x00, 0x04]);
+
+    // rti
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.regs.pc, 0x400);
+
+    // and it keeps looping
+    let res = c.run_until(None, 0, 10).unwrap();
+    assert_eq!(res.reason, StopReason::InstructionsReached);
+    assert_eq!(c.regs.pc, 0x400);
+}
+
+#[test]
+fn cycles() {
+    // (cpu, opcode, address, offset, taken, expected cycles)
+    let matrix = [
+        // bne, next instruction at $0502
+        (CpuType::MOS6502, 0xd0, 0x0500, 0x10, false, 2),
+        (CpuType::MOS6502, 0xd0, 0x0500, 0x10, true, 3),
+        (CpuType::MOS6502, 0xd0, 0x0500, 0xf0, false, 2),
+        (CpuType::MOS6502, 0xd0, 0x0500, 0xf0, true, 4),
+        // bne at the end of the page, next instruction at $0500 so backward is the crossing one
+        (CpuType::MOS6502, 0xd0, 0x04fe, 0x10, true, 3),
+        (CpuType::MOS6502, 0xd0, 0x04fe, 0xfc, true, 4),
+        (CpuType::WDC65C02, 0xd0, 0x0500, 0xf0, false, 2),
+        (CpuType::WDC65C02, 0xd0, 0x0500, 0xf0, true, 4),
+        // bra
+        (CpuType::WDC65C02, 0x80, 0x0500, 0x10, true, 3),
+        (CpuType::WDC65C02, 0x80, 0x0500, 0xf0, true, 4),
+        (CpuType::WDC65C02, 0x80, 0x04fe, 0x10, true, 3),
+        (CpuType::WDC65C02, 0x80, 0x04fe, 0xfc, true, 4),
+        // bbr0 $10 and bbs7 $10, next instruction at $0503 (or $0500)
+        (CpuType::WDC65C02, 0x0f, 0x0500, 0x10, false, 5),
+        (CpuType::WDC65C02, 0x0f, 0x0500, 0x10, true, 6),
+        (CpuType::WDC65C02, 0x0f, 0x0500, 0xf0, true, 7),
+        (CpuType::WDC65C02, 0xff, 0x04fd, 0x10, false, 5),
+        (CpuType::WDC65C02, 0xff, 0x04fd, 0x10, true, 6),
+        (CpuType::WDC65C02, 0xff, 0x04fd, 0xfc, true, 7),
+    ];
+    for (t, opcode, address, offset, taken, expected) in matrix {
+        let mut c = Cpu::new_default(None);
+        c.set_cpu_type(t);
+        let d = Debugger::new(false);
+        if opcode & 0x0f == 0x0f {
+            // bbr/bbs on $10, the tested bit is set if bbs is taken or bbr is not
+            let bit = 1 << ((opcode >> 4) & 7);
+            let bbs = opcode & 0x80 != 0;
+            d.write_memory(&mut c, address, &[opcode, 0x10, offset])
+                .unwrap();
+            d.write_memory(&mut c, 0x10, &[if taken == bbs { bit } else { 0 }])
+                .unwrap();
+        } else {
+            d.write_memory(&mut c, address, &[opcode, offset]).unwrap();
+        }
+        c.reset(Some(address), ResetKind::PowerOn).unwrap();
+        c.regs.set_zero(!taken);
+        let start = c.cycles();
+        c.run_until(None, 0, 1).unwrap();
+        assert_eq!(
+            c.cycles() - start,
+            expected,
+            "{} ${:02x} ${:02x} at ${:04x}, taken={}",
+            t,
+            opcode,
+            offset,
+            address,
+            taken
+        );
+    }
+}
+
+#[test]
+fn offset_read_once() {
+    // bpl, bmi, bvc, bvs, bcc, bcs, bne, beq, bra: the offset is read only when executing
+    for opcode in [0x10, 0x30, 0x50, 0x70, 0x90, 0xb0, 0xd0, 0xf0, 0x80] {
+        let mut c = Cpu::new_default(None);
+        c.set_cpu_type(CpuType::WDC65C02);
+        let mut d = Debugger::new(false);
+        d.write_memory(&mut c, 0x400, &[opcode, 0x10]).unwrap();
+
+        // a read breakpoint on the offset stops the (disabled) debugger, the branch is not executed
+        d.add_breakpoint(&mut c, 0x401, BreakpointType::READ, None)
+            .unwrap();
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+        assert_eq!(
+            (r.reason, c.regs.pc),
+            (StopReason::Breakpoint(0), 0x400),
+            "${:02x}",
+            opcode
+        );
+
+        // hooks see a single read
+        let reads = Arc::new(Mutex::new(0));
+        let r = reads.clone();
+        c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+            if ctx.operation == CpuOperation::Read && ctx.address == 0x401 {
+                *r.lock().unwrap() += 1;
+            }
+        });
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        c.run_until(None, 0, 1).unwrap();
+        assert_eq!(*reads.lock().unwrap(), 1, "${:02x}", opcode);
+    }
+}
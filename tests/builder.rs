@@ -0,0 +1,106 @@
+/*
+ * Filename: /tests/builder.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 21:14:05
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! constructing a Cpu through CpuBuilder.
+
+use rv6502emu::bus::BusBuilder;
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{CpuBuilder, CpuCallbackContext, CpuOperation, CpuType, CpuView, ResetKind};
+use rv6502emu::memory::{self, MemoryInitPolicy};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn defaults() {
+    let mut c = CpuBuilder::new().build().unwrap();
+    assert_eq!(c.cpu_type(), CpuType::MOS6502);
+    assert_eq!(c.bus.get_memory().get_size(), 0x10000);
+    assert!(c.is_bcd_enabled());
+}
+
+#[test]
+fn invalid() {
+    // memory not covering the vectors
+    assert!(CpuBuilder::new().memory_size(0x800).build().is_err());
+
+    // both bus and memory size
+    let bus = BusBuilder::new(memory::new_default()).build();
+    assert!(CpuBuilder::new()
+        .bus(bus)
+        .memory_size(0x10000)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn options() {
+    let writes = Arc::new(AtomicUsize::new(0));
+    let w = writes.clone();
+    let mut c = CpuBuilder::new()
+        .cpu_type(CpuType::WDC65C02)
+        .bus(BusBuilder::new(memory::new_default()).build())
+        .deadlock_detection(false)
+        .decimal_mode_enabled(false)
+        .hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+            if ctx.operation == CpuOperation::Write {
+                w.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .build()
+        .unwrap();
+    assert_eq!(c.cpu_type(), CpuType::WDC65C02);
+
+    // sed, lda #$01, adc #$09, sta $10 (binary, with D set)
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0xf8, 0xa9, 0x01, 0x69, 0x09, 0x85, 0x10])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 4).unwrap();
+    assert_eq!(c.regs.a, 0x0a);
+    assert_eq!(writes.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn memory_init() {
+    let mut c = CpuBuilder::new()
+        .memory_init(MemoryInitPolicy::Ones)
+        .build()
+        .unwrap();
+    assert_eq!(c.bus.dump_range(0x1234, 2).unwrap(), [0xff, 0xff]);
+    assert_eq!(c.bus.get_memory().init_policy(), MemoryInitPolicy::Ones);
+
+    // a custom bus is cleared too
+    let mut c = CpuBuilder::new()
+        .bus(BusBuilder::new(memory::new_default()).build())
+        .memory_init(MemoryInitPolicy::Pattern(0x00, 0xff))
+        .build()
+        .unwrap();
+    assert_eq!(c.bus.dump_range(0x003f, 2).unwrap(), [0x00, 0xff]);
+}
@@ -0,0 +1,99 @@
+/*
+ * Filename: /tests/capi.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 11:32:50
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the C interface: builds the crate as a staticlib and runs tests/capi/smoke.c against it (needs the capi feature).
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Command;
+
+/**
+ * returns the host triple, from rustc -vV.
+ */
+fn host_triple() -> String {
+    let out = Command::new("rustc").arg("-vV").output().unwrap();
+    String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .find_map(|l| l.strip_prefix("host: "))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn c_program_through_ffi() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("capi");
+
+    // the crate is not a staticlib by default (it would break the no_std build)
+    let status = Command::new(env!("CARGO"))
+        .current_dir(manifest_dir)
+        .args(&[
+            "rustc",
+            "--lib",
+            "--features",
+            "capi",
+            "--crate-type",
+            "staticlib",
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let host = host_triple();
+    let exe = target_dir.join("smoke");
+    let status = cc::Build::new()
+        .cargo_metadata(false)
+        .opt_level(0)
+        .host(&host)
+        .target(&host)
+        .get_compiler()
+        .to_command()
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/capi/smoke.c"))
+        .arg(target_dir.join("debug/librv6502emu.a"))
+        .args(&["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&exe)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let out = Command::new(&exe).output().unwrap();
+    assert!(out.status.success());
+    // 4 nops, 2 cycles each
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(
+        stdout.lines().last(),
+        Some("a=42 pc=040d port=42 executed=8")
+    );
+}
@@ -0,0 +1,65 @@
+/*
+ * runs a tiny program through the C interface, with a memory-mapped port at $f000 served by the rw callback.
+ *
+ * prints the final registers and the port and exits with 0 if the accumulator is $42 and a fatal memory error is
+ * reported by step/run_cycles.
+ */
+#include <stdio.h>
+#include "rv6502emu.h"
+
+static bool port_io(void* user_data, uint16_t address, uint8_t* value, bool write) {
+    uint8_t* port = (uint8_t*)user_data;
+    if (address != 0xf000) {
+        return false;
+    }
+    if (write) {
+        *port = *value;
+    } else {
+        *value = 5;
+    }
+    return true;
+}
+
+int main(void) {
+    /* lda $f000, clc, adc #$3d, sta $f000, nop x 4 */
+    static const uint8_t prg[] = {0xad, 0x00, 0xf0, 0x18, 0x69, 0x3d, 0x8d, 0x00, 0xf0, 0xea, 0xea, 0xea, 0xea};
+    uint8_t port = 0;
+    size_t executed = 0;
+    rv6502_regs regs;
+    rv6502* c = rv6502_new(0x10000);
+    for (size_t i = 0; i < sizeof(prg); i++) {
+        if (rv6502_write(c, 0x400 + i, prg[i]) != RV6502_OK) {
+            return 1;
+        }
+    }
+    rv6502_set_rw_callback(c, port_io, &port);
+    if (rv6502_reset(c, 0x400) != RV6502_OK) {
+        return 1;
+    }
+    for (int i = 0; i < 4; i++) {
+        if (rv6502_step(c) != RV6502_OK) {
+            return 1;
+        }
+    }
+    if (rv6502_run_cycles(c, 8, &executed) != RV6502_OK) {
+        return 1;
+    }
+    rv6502_get_regs(c, &regs);
+    printf("a=%02x pc=%04x port=%02x executed=%zu\n", regs.a, regs.pc, port, executed);
+
+    /* lda abs at the end of 4k of memory, reading the operand past the end is an error */
+    rv6502* small = rv6502_new(0x1000);
+    if (rv6502_write(small, 0xfff, 0xad) != RV6502_OK || rv6502_reset(small, 0xfff) != RV6502_OK) {
+        return 1;
+    }
+    if (rv6502_step(small) != RV6502_ERR_MEMORY_READ || rv6502_run_cycles(small, 8, &executed) != RV6502_ERR_MEMORY_READ) {
+        return 1;
+    }
+    rv6502_destroy(small);
+
+    regs.x = 7;
+    rv6502_set_regs(c, &regs);
+    rv6502_get_regs(c, &regs);
+    rv6502_destroy(c);
+    return (regs.a == 0x42 && regs.x == 7) ? 0 : 1;
+}
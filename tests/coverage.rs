@@ -0,0 +1,105 @@
+/*
+ * Filename: /tests/coverage.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 17:58:44
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! coverage tracking tests.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, ResetKind};
+use std::env;
+use std::fs;
+
+#[test]
+fn executed_read_written() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // lda #$11, sta $0200, lda $0300, sta $0201, pha, nop
+    let prg = [
+        0xa9, 0x11, 0x8d, 0x00, 0x02, 0xad, 0x00, 0x03, 0x8d, 0x01, 0x02, 0x48, 0xea,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // disabled by default
+    c.run(Some(&mut d), 1).unwrap();
+    assert!(!c.is_coverage_enabled());
+    assert_eq!(c.coverage().counts(), (0, 0, 0));
+
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.enable_coverage(true);
+    c.run_until(Some(&mut d), 0, 6).unwrap();
+
+    let cov = c.coverage();
+    assert_eq!(cov.executed_ranges(), vec![(0x400, 0x40c)]);
+    assert_eq!(cov.read_ranges(), vec![(0x300, 0x300)]);
+    assert_eq!(cov.written_ranges(), vec![(0x1ff, 0x201)]);
+    assert_eq!(cov.counts(), (13, 1, 3));
+    assert!(cov.is_executed(0x40b) && !cov.is_executed(0x40d));
+    assert!(cov.is_written(0x200) && !cov.is_read(0x200));
+
+    // dump, one byte per address
+    let path = env::temp_dir().join(format!("rv6502emu_{}_cov.bin", std::process::id()));
+    assert!(
+        d.parse_cmd(&mut c, &format!("cov save {}", path.to_str().unwrap()))
+            .1
+    );
+    let map = fs::read(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(map.len(), 0x10000);
+    assert_eq!(map[0x400], 1);
+    assert_eq!(map[0x300], 2);
+    assert_eq!(map[0x201], 4);
+    assert_eq!(map[0x202], 0);
+
+    // enabling again starts from scratch
+    assert!(d.parse_cmd(&mut c, "cov on").1);
+    assert_eq!(c.coverage().counts(), (0, 0, 0));
+}
+
+#[test]
+fn branch_offsets_not_read() {
+    // ldx #$02, dex, bne $0402 (taken once), lda $0300: only the lda is a data read
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[0xa2, 0x02, 0xca, 0xd0, 0xfd, 0xad, 0x00, 0x03],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.enable_coverage(true);
+    c.run_until(None, 0, 6).unwrap();
+
+    let cov = c.coverage();
+    assert_eq!(cov.executed_ranges(), vec![(0x400, 0x407)]);
+    assert_eq!(cov.read_ranges(), vec![(0x300, 0x300)]);
+    assert!(!cov.is_read(0x404));
+}
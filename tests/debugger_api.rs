@@ -0,0 +1,1351 @@
+/*
+ * Filename: /tests/debugger_api.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 12:21:05
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the programmatic debugger api, no stdin/stdout involved.
+
+use rv6502emu::cpu::cpu_error::CpuErrorType;
+use rv6502emu::cpu::debugger::{
+    BpCondition, BreakpointType, Debugger, FlagTrigger, HexdumpOptions, HexdumpText, TraceFormat,
+    TRACE_CONTEXT,
+};
+use rv6502emu::cpu::{AddressingModeId, Cpu, CpuFlags, CpuType, ResetKind, StopReason};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::rc::Rc;
+
+/**
+ * captures the debugger output.
+ */
+#[derive(Clone, Default)]
+struct Sink(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Sink {
+    /**
+     * returns the text captured so far and clears it.
+     */
+    fn take(&self) -> String {
+        String::from_utf8(self.0.borrow_mut().split_off(0)).unwrap()
+    }
+}
+
+#[test]
+fn breakpoints() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(true);
+
+    let cond = BpCondition {
+        a: Some(0x10),
+        cycles: Some(100),
+        ..Default::default()
+    };
+    assert_eq!(
+        d.add_breakpoint(&mut c, 0x400, BreakpointType::EXEC, None)
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        d.add_breakpoint(
+            &mut c,
+            0x1234,
+            BreakpointType::READ | BreakpointType::WRITE,
+            Some(cond)
+        )
+        .unwrap(),
+        1
+    );
+
+    // duplicated
+    let e = d
+        .add_breakpoint(&mut c, 0x400, BreakpointType::EXEC, None)
+        .unwrap_err();
+    assert_eq!(e.t, CpuErrorType::Generic);
+
+    // irq takes the address from the vector
+    d.write_memory(&mut c, 0xfffe, &[0x00, 0x20]).unwrap();
+    let id = d
+        .add_breakpoint(&mut c, 0, BreakpointType::IRQ, None)
+        .unwrap();
+    assert_eq!(d.list_breakpoints()[id].address(), 0x2000);
+
+    let l = d.list_breakpoints();
+    assert_eq!(l.len(), 3);
+    assert_eq!(l[0].address(), 0x400);
+    assert_eq!(l[0].bp_type(), BreakpointType::EXEC);
+    assert_eq!(l[0].condition(), None);
+    assert_eq!(l[1].condition(), Some(cond));
+
+    d.enable_breakpoint(1, false).unwrap();
+    assert!(!d.list_breakpoints()[1].is_enabled());
+    d.remove_breakpoint(0).unwrap();
+    assert_eq!(d.list_breakpoints()[0].address(), 0x1234);
+    assert!(d.remove_breakpoint(5).is_err());
+    d.clear_breakpoints();
+    assert!(d.list_breakpoints().is_empty());
+}
+
+#[test]
+fn vector_fetch_breakpoint() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // brk, handler at $2000: lda #$42
+    d.write_memory(&mut c, 0x400, &[0x00]).unwrap();
+    d.write_memory(&mut c, 0xfffe, &[0x00, 0x20]).unwrap();
+    d.write_memory(&mut c, 0x2000, &[0xa9, 0x42]).unwrap();
+    let id = d
+        .add_breakpoint(&mut c, 0xfffe, BreakpointType::READ, None)
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // reported once brk completes, before the handler runs
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(id));
+    assert_eq!((c.regs.pc, c.regs.s, c.regs.a), (0x2000, 0xfc, 0));
+
+    // resuming runs the handler
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a), (0x2002, 0x42));
+}
+
+#[test]
+fn memory_and_disassemble() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(true);
+
+    // lda #$01, sta $0200,x, bne $fb
+    let prg = [0xa9, 0x01, 0x9d, 0x00, 0x02, 0xd0, 0xfb];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    assert_eq!(d.read_memory(&mut c, 0x400, prg.len()).unwrap(), prg);
+    assert!(d.read_memory(&mut c, 0xffff, 2).is_err());
+
+    let pc = c.regs.pc;
+    let v = d.disassemble(&mut c, 0x400, 3).unwrap();
+    assert_eq!(c.regs.pc, pc);
+    assert_eq!(v.len(), 3);
+    assert_eq!(v[0].address, 0x400);
+    assert_eq!(v[0].mnemonic, "lda");
+    assert_eq!(v[0].mode, AddressingModeId::Imm);
+    assert_eq!(v[1].address, 0x402);
+    assert_eq!(v[1].bytes, vec![0x9d, 0x00, 0x02]);
+    assert_eq!(v[1].mode, AddressingModeId::Abx);
+    assert_eq!(v[2].mnemonic, "bne");
+    assert!(v[2].to_string().contains("BNE"));
+}
+
+/**
+ * runs the program at $400 and returns the triggered breakpoint, if any.
+ */
+fn run_watched(prg: &[u8], start: u16, end: u16) -> Option<usize> {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+
+    // trailing nop, a triggered r/w breakpoint skips the instruction without accounting cycles
+    d.write_memory(&mut c, 0x400 + prg.len() as u16, &[0xea])
+        .unwrap();
+    d.add_breakpoint_range(&mut c, start, end, BreakpointType::WRITE, None)
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    for _ in 0..prg.len() {
+        if c.regs.pc >= 0x400 + prg.len() as u16 {
+            break;
+        }
+        c.run(Some(&mut d), 1).unwrap();
+    }
+    d.triggered_breakpoint()
+}
+
+#[test]
+fn range_watchpoints() {
+    // ldx #$80, sta $0200,x
+    assert_eq!(
+        run_watched(&[0xa2, 0x80, 0x9d, 0x00, 0x02], 0x200, 0x2ff),
+        Some(0)
+    );
+
+    // ldx #$ff, sta $0201,x (writes $0300)
+    assert_eq!(
+        run_watched(&[0xa2, 0xff, 0x9d, 0x01, 0x02], 0x200, 0x2ff),
+        None
+    );
+
+    // ldx #$ff, sta $0100,x (writes $01ff)
+    assert_eq!(
+        run_watched(&[0xa2, 0xff, 0x9d, 0x00, 0x01], 0x200, 0x2ff),
+        None
+    );
+
+    // bounds are inclusive
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    let id = d
+        .add_breakpoint_range(&mut c, 0x200, 0x2ff, BreakpointType::WRITE, None)
+        .unwrap();
+    let bp = &d.list_breakpoints()[id];
+    assert_eq!((bp.address(), bp.end_address()), (0x200, 0x2ff));
+    assert!(bp.contains(0x200) && bp.contains(0x2ff));
+    assert!(!bp.contains(0x1ff) && !bp.contains(0x300));
+    assert_eq!(format!("{}", bp), "$0200-$02ff [-W-,enabled]");
+    assert!(d
+        .add_breakpoint_range(&mut c, 0x300, 0x2ff, BreakpointType::WRITE, None)
+        .is_err());
+}
+
+/**
+ * runs a recursive JSR at $400 up to 130 levels deep, returns the triggered breakpoint and S at that point.
+ */
+fn run_recursive_jsr(floor: u8) -> (Option<usize>, u8) {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // jsr $0400, nop (executed after the aborted jsr when the breakpoint triggers)
+    d.write_memory(&mut c, 0x400, &[0x20, 0x00, 0x04, 0xea])
+        .unwrap();
+    c.set_deadlock_detection(false);
+    d.add_breakpoint(&mut c, floor as u16, BreakpointType::STACK, None)
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    for _ in 0..130 {
+        c.run(Some(&mut d), 1).unwrap();
+        if d.triggered_breakpoint().is_some() {
+            break;
+        }
+    }
+    (d.triggered_breakpoint(), c.regs.s)
+}
+
+#[test]
+fn stack_breakpoints() {
+    // wrap on push with S=$00
+    assert_eq!(run_recursive_jsr(0), (Some(0), 0xff));
+
+    // first push below $20
+    assert_eq!(run_recursive_jsr(0x20), (Some(0), 0x1f));
+
+    // floor must fit a byte, and can't be mixed with other types
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    assert!(d
+        .add_breakpoint(&mut c, 0x100, BreakpointType::STACK, None)
+        .is_err());
+    assert!(d
+        .add_breakpoint(
+            &mut c,
+            0x10,
+            BreakpointType::STACK | BreakpointType::READ,
+            None
+        )
+        .is_err());
+    let id = d
+        .add_breakpoint(&mut c, 0x10, BreakpointType::STACK, None)
+        .unwrap();
+    assert_eq!(
+        format!("{}", d.list_breakpoints()[id]),
+        "[STACK,enabled], floor=$10"
+    );
+}
+
+#[test]
+fn find_memory() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // jsr $xxxx; lda #$xx; sta $xxxx at $800 and $900, overlapping "aaa" at $a00, "HELLO" in petscii at $b00
+    let prg = [0x20, 0x00, 0x10, 0xa9, 0x01, 0x8d, 0x00, 0xd0];
+    d.write_memory(&mut c, 0x800, &prg).unwrap();
+    let mut prg2 = prg;
+    prg2[1] = 0x34;
+    prg2[4] = 0xff;
+    d.write_memory(&mut c, 0x900, &prg2).unwrap();
+    d.write_memory(&mut c, 0xa00, &[0xaa; 4]).unwrap();
+    d.write_memory(&mut c, 0xb00, &[0xc8, 0xc5, 0xcc, 0xcc, 0xcf])
+        .unwrap();
+
+    // wildcards
+    let p = [Some(0x20), None, None, Some(0xa9), None, Some(0x8d)];
+    assert_eq!(
+        d.find_memory(&mut c, 0x800, 0xbfff, &p, 0).unwrap(),
+        vec![0x800, 0x900]
+    );
+    assert_eq!(
+        d.find_memory(&mut c, 0x800, 0xbfff, &p, 1).unwrap(),
+        vec![0x800]
+    );
+
+    // the range is inclusive, the whole pattern must fit
+    assert_eq!(
+        d.find_memory(&mut c, 0x801, 0x905, &p, 0).unwrap(),
+        vec![0x900]
+    );
+    assert!(d
+        .find_memory(&mut c, 0x801, 0x904, &p, 0)
+        .unwrap()
+        .is_empty());
+
+    // overlapping
+    let p = [Some(0xaa); 3];
+    assert_eq!(
+        d.find_memory(&mut c, 0, 0xffff, &p, 0).unwrap(),
+        vec![0xa00, 0xa01]
+    );
+    assert!(d.find_memory(&mut c, 0, 0xffff, &[], 0).is_err());
+
+    // from the debugger
+    assert!(d.parse_cmd(&mut c, "f $800 $bfff 20 ?? 10 a9").1);
+    assert!(d.parse_cmd(&mut c, "f 800 bfff \"HELLO\" petscii max=1").1);
+    assert!(!d.parse_cmd(&mut c, "f 800 bfff zz").1);
+}
+
+#[test]
+fn compare_checksum() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // same data at $1000 and at the very end of memory
+    let data = b"123456789";
+    d.write_memory(&mut c, 0x1000, data).unwrap();
+    d.write_memory(&mut c, 0xfff7, data).unwrap();
+
+    // identical regions
+    assert!(d
+        .compare_memory(&mut c, 0x1000, 0xfff7, data.len())
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        d.checksum(&mut c, 0x1000, data.len()).unwrap(),
+        d.checksum(&mut c, 0xfff7, data.len()).unwrap()
+    );
+
+    // standard CRC32 check value
+    assert_eq!(d.checksum(&mut c, 0x1000, data.len()).unwrap(), 0xcbf43926);
+
+    // single byte difference
+    d.write_memory(&mut c, 0xffff, &[0xff]).unwrap();
+    let v = d
+        .compare_memory(&mut c, 0x1000, 0xfff7, data.len())
+        .unwrap();
+    assert_eq!(v.len(), 1);
+    assert_eq!(v[0].offset, 8);
+    assert_eq!(v[0].a, b'9');
+    assert_eq!(v[0].b, 0xff);
+    assert_ne!(
+        d.checksum(&mut c, 0x1000, data.len()).unwrap(),
+        d.checksum(&mut c, 0xfff7, data.len()).unwrap()
+    );
+
+    // the full 64k, but not past the end
+    assert!(d.checksum(&mut c, 0, 0x10000).is_ok());
+    assert!(d.compare_memory(&mut c, 0, 0, 0x10000).unwrap().is_empty());
+    let e = d.checksum(&mut c, 1, 0x10000).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::MemoryRead);
+    assert!(d
+        .compare_memory(&mut c, 0x1000, 0xfff8, data.len())
+        .is_err());
+
+    // the text commands
+    assert!(d.parse_cmd(&mut c, "cmp $1000 $fff7 9").1);
+    assert!(d.parse_cmd(&mut c, "crc $1000 9").1);
+    assert!(d.parse_cmd(&mut c, "crc $fff0").1);
+    assert!(!d.parse_cmd(&mut c, "cmp $1000 $fff8 9").1);
+    assert!(!d.parse_cmd(&mut c, "cmp $1000 $fff7").1);
+}
+
+#[test]
+fn fill_copy() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // the pattern wraps
+    d.fill_memory(&mut c, 0x1000, 7, &[1, 2, 3]).unwrap();
+    assert_eq!(
+        d.read_memory(&mut c, 0x1000, 8).unwrap(),
+        [1, 2, 3, 1, 2, 3, 1, 0]
+    );
+
+    // up to the end of memory, but not past it
+    d.fill_memory(&mut c, 0xfff0, 0x10, &[0xaa]).unwrap();
+    assert_eq!(d.read_memory(&mut c, 0xffff, 1).unwrap(), [0xaa]);
+    let e = d.fill_memory(&mut c, 0xfff0, 0x11, &[0x55]).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::MemoryWrite);
+    assert_eq!(d.read_memory(&mut c, 0xfff0, 1).unwrap(), [0xaa]);
+    assert!(d.fill_memory(&mut c, 0x1000, 1, &[]).is_err());
+
+    // overlapping forward (dst > src)
+    let data = [1, 2, 3, 4, 5, 6, 7, 8];
+    d.write_memory(&mut c, 0x2000, &data).unwrap();
+    d.copy_memory(&mut c, 0x2000, 0x2003, 8).unwrap();
+    assert_eq!(
+        d.read_memory(&mut c, 0x2000, 11).unwrap(),
+        [1, 2, 3, 1, 2, 3, 4, 5, 6, 7, 8]
+    );
+
+    // overlapping backward (dst < src)
+    d.write_memory(&mut c, 0x3003, &data).unwrap();
+    d.copy_memory(&mut c, 0x3003, 0x3000, 8).unwrap();
+    assert_eq!(
+        d.read_memory(&mut c, 0x3000, 11).unwrap(),
+        [1, 2, 3, 4, 5, 6, 7, 8, 6, 7, 8]
+    );
+
+    // zero length is a no-op
+    let before = d.read_memory(&mut c, 0x2000, 16).unwrap();
+    d.copy_memory(&mut c, 0x3000, 0x2000, 0).unwrap();
+    d.fill_memory(&mut c, 0x2000, 0, &[0xff]).unwrap();
+    assert_eq!(d.read_memory(&mut c, 0x2000, 16).unwrap(), before);
+
+    // destination past the end, nothing is written
+    assert!(d.copy_memory(&mut c, 0x2000, 0xfffc, 8).is_err());
+    assert_eq!(d.read_memory(&mut c, 0xfffc, 4).unwrap(), [0xaa; 4]);
+
+    // write breakpoints are bypassed
+    d.add_breakpoint_range(&mut c, 0x4000, 0x40ff, BreakpointType::WRITE, None)
+        .unwrap();
+    d.fill_memory(&mut c, 0x4000, 0x100, &[0xea]).unwrap();
+    d.copy_memory(&mut c, 0x2000, 0x4010, 8).unwrap();
+    assert_eq!(d.triggered_breakpoint(), None);
+
+    // the text commands
+    assert!(d.parse_cmd(&mut c, "fill $5000 5 $de,ad").1);
+    assert_eq!(
+        d.read_memory(&mut c, 0x5000, 5).unwrap(),
+        [0xde, 0xad, 0xde, 0xad, 0xde]
+    );
+    assert!(d.parse_cmd(&mut c, "copy $5000 $5001 5").1);
+    assert_eq!(
+        d.read_memory(&mut c, 0x5000, 6).unwrap(),
+        [0xde, 0xde, 0xad, 0xde, 0xad, 0xde]
+    );
+    assert!(!d.parse_cmd(&mut c, "fill $5000 5 zz").1);
+    assert!(!d.parse_cmd(&mut c, "copy $5000 $fffe 5").1);
+}
+
+#[test]
+fn history() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // lda #$11, sta $10, inc $10, ldx #$22, stx $0200, pha, jsr $0420
+    let prg = [
+        0xa9, 0x11, 0x85, 0x10, 0xe6, 0x10, 0xa2, 0x22, 0x8e, 0x00, 0x02, 0x48, 0x20, 0x20, 0x04,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+
+    // lda #$33, sta $10, sta $0201
+    d.write_memory(&mut c, 0x420, &[0xa9, 0x33, 0x85, 0x10, 0x8d, 0x01, 0x02])
+        .unwrap();
+
+    // nmi handler: nop
+    d.write_memory(&mut c, 0xfffa, &[0x40, 0x04]).unwrap();
+    d.write_memory(&mut c, 0x440, &[0xea]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    d.enable_history(16);
+
+    // run 5 instructions and take a snapshot
+    let res = c.run_until(Some(&mut d), 0, 5).unwrap();
+    assert_eq!(res.instructions, 5);
+    let regs = c.regs;
+    let cycles = c.cycles();
+    let mem = d.read_memory(&mut c, 0, 0x300).unwrap();
+    assert_eq!(mem[0x10], 0x12);
+    assert_eq!(mem[0x200], 0x22);
+
+    // 5 more, writing the stack too
+    c.run_until(Some(&mut d), 0, 5).unwrap();
+    assert_eq!(c.regs.pc, 0x427);
+    assert_eq!(d.read_memory(&mut c, 0x10, 1).unwrap(), [0x33]);
+    assert_ne!(d.read_memory(&mut c, 0, 0x300).unwrap(), mem);
+    assert_eq!(d.history_len(), 10);
+
+    // back 5
+    assert_eq!(d.step_back(&mut c, 5).unwrap(), 5);
+    assert_eq!(c.regs, regs);
+    assert_eq!(c.cycles(), cycles);
+    assert_eq!(d.read_memory(&mut c, 0, 0x300).unwrap(), mem);
+    assert_eq!(d.history_len(), 5);
+
+    // and replay
+    c.run_until(Some(&mut d), 0, 5).unwrap();
+    assert_eq!(c.regs.pc, 0x427);
+    assert_eq!(d.read_memory(&mut c, 0x201, 1).unwrap(), [0x33]);
+
+    // the nmi entry is a step too
+    let regs = c.regs;
+    let stack = d.read_memory(&mut c, 0x100, 0x100).unwrap();
+    d.write_memory(&mut c, 0x427, &[0xea]).unwrap();
+    c.set_nmi_line(true);
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!(c.regs.pc, 0x441);
+    assert_eq!(d.step_back(&mut c, 2).unwrap(), 2);
+    assert_eq!(c.regs, regs);
+    assert_eq!(d.read_memory(&mut c, 0x100, 0x100).unwrap(), stack);
+
+    // bounded
+    d.enable_history(3);
+    c.set_nmi_line(false);
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(Some(&mut d), 0, 5).unwrap();
+    assert_eq!(d.history_len(), 3);
+    assert_eq!(d.step_back(&mut c, 5).unwrap(), 3);
+    assert_eq!(c.regs.pc, 0x404);
+    assert_eq!(d.step_back(&mut c, 1).unwrap(), 0);
+
+    // the text commands
+    assert!(d.parse_cmd(&mut c, "hist 0").1);
+    assert!(!d.parse_cmd(&mut c, "back").1);
+    assert!(d.parse_cmd(&mut c, "hist 8").1);
+    assert!(d.parse_cmd(&mut c, "back 2").1);
+}
+
+#[test]
+fn opcode_breakpoints() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // lda #$01, lax $1234, sta $10, sta $0200, brk
+    let prg = [
+        0xa9, 0x01, 0xaf, 0x34, 0x12, 0x85, 0x10, 0x8d, 0x00, 0x02, 0x00,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+
+    // brk, pc is not advanced
+    let brk = d.add_opcode_breakpoint(&c, "brk", None).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(brk));
+    assert_eq!((r.instructions, c.regs.pc), (4, 0x40a));
+    assert_eq!(d.list_breakpoints()[brk].bp_type(), BreakpointType::OPCODE);
+    d.enable_breakpoint(brk, false).unwrap();
+
+    // lax absolute
+    let lax = d.add_opcode_breakpoint(&c, "$af", None).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(lax));
+    assert_eq!(c.regs.pc, 0x402);
+    d.remove_breakpoint(lax).unwrap();
+
+    // the mnemonic matches all the addressing modes
+    let sta = d.add_opcode_breakpoint(&c, "STA", None).unwrap();
+    assert!(d.list_breakpoints()[sta].opcodes().contains(&0x85));
+    assert!(d.list_breakpoints()[sta].opcodes().contains(&0x91));
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(sta));
+    assert_eq!(c.regs.pc, 0x405);
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(sta));
+    assert_eq!(c.regs.pc, 0x407);
+
+    // duplicated, invalid, and 65C02 only
+    assert!(d.add_opcode_breakpoint(&c, "sta", None).is_err());
+    assert!(d.add_opcode_breakpoint(&c, "foo", None).is_err());
+    assert!(d.add_opcode_breakpoint(&c, "stz", None).is_err());
+    c.set_cpu_type(CpuType::WDC65C02);
+    assert!(d.add_opcode_breakpoint(&c, "stz", None).is_ok());
+}
+
+#[test]
+fn flag_breakpoints() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // lda #$08, pha, cld, sed, cld, plp, nop, jmp *
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[
+            0xa9, 0x08, 0x48, 0xd8, 0xf8, 0xd8, 0x28, 0xea, 0x4c, 0x08, 0x04,
+        ],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // sed triggers after executing, cld does not
+    let bp = d
+        .add_flag_breakpoint(CpuFlags::D, FlagTrigger::Set, None)
+        .unwrap();
+    assert_eq!(d.list_breakpoints()[bp].bp_type(), BreakpointType::FLAG);
+    assert_eq!(
+        d.list_breakpoints()[bp].flag(),
+        Some((CpuFlags::D, FlagTrigger::Set))
+    );
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(bp));
+    assert_eq!((r.instructions, c.regs.pc), (4, 0x405));
+    assert_eq!(
+        d.flag_change().unwrap(),
+        "D set by the instruction at $0404, P=--U--I-- -> --U-DI--"
+    );
+
+    // plp restoring D
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(bp));
+    assert_eq!((r.instructions, c.regs.pc), (2, 0x407));
+    assert_eq!(
+        d.flag_change().unwrap(),
+        "D set by the instruction at $0406, P=--U--I-- -> --U-D---"
+    );
+    // then the jmp * deadlock ends the run
+    let e = c.run_until(Some(&mut d), 0, 100).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::Deadlock);
+
+    // duplicated and invalid
+    assert!(d
+        .add_flag_breakpoint(CpuFlags::D, FlagTrigger::Set, None)
+        .is_err());
+    assert!(d
+        .add_flag_breakpoint(CpuFlags::D | CpuFlags::C, FlagTrigger::Changed, None)
+        .is_err());
+    assert!(d
+        .add_breakpoint(&mut c, 0x400, BreakpointType::FLAG, None)
+        .is_err());
+
+    // from the command line, plp clears I
+    d.clear_breakpoints();
+    let sink = Sink::default();
+    d.set_output(sink.clone());
+    assert!(d.parse_cmd(&mut c, "bf i=0").1);
+    assert!(d.parse_cmd(&mut c, "bf v changed").1);
+    assert!(!d.parse_cmd(&mut c, "bf d=2").1);
+    assert!(!d.parse_cmd(&mut c, "bf q=1").1);
+    assert!(!d.parse_cmd(&mut c, "bf d").1);
+    assert_eq!(
+        sink.take(),
+        "breakpoint set! (i=0 [FLAG,enabled])\n\
+         breakpoint set! (v changed [FLAG,enabled])\n\
+         invalid command, try 'h' for help !\n\
+         invalid command, try 'h' for help !\n\
+         invalid command, try 'h' for help !\n"
+    );
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(0));
+    assert_eq!(c.regs.pc, 0x407);
+}
+
+#[derive(Deserialize)]
+struct JsonRegisters {
+    pc: u16,
+    a: u8,
+    x: u8,
+    y: u8,
+    s: u8,
+    p: u8,
+    flags: String,
+    cycles: usize,
+}
+
+#[derive(Deserialize)]
+struct JsonBreakpoint {
+    id: usize,
+    address: u16,
+    end: u16,
+    #[serde(rename = "type")]
+    t: String,
+    enabled: bool,
+    hits: usize,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct JsonInstruction {
+    addr: u16,
+    bytes: Vec<u8>,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct JsonMemory {
+    address: u16,
+    bytes: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct JsonError {
+    error: String,
+}
+
+#[test]
+fn json_output() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(true);
+    let sink = Sink::default();
+    d.set_output(sink.clone());
+
+    // lda #$42, ldx #$10, sta $0200,x, jmp *
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[0xa9, 0x42, 0xa2, 0x10, 0x9d, 0x00, 0x02, 0x4c, 0x07, 0x04],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    assert!(d.parse_cmd(&mut c, "bx $0407").1);
+    assert!(d.parse_cmd(&mut c, "bw $0300-$03ff").1);
+    d.queue_cmd("g");
+    d.queue_cmd("q");
+    c.run_until(Some(&mut d), 0, 0).unwrap();
+    sink.take();
+    assert!(d.parse_cmd(&mut c, "set output json").1);
+    assert!(d.is_json_output());
+    assert_eq!(sink.take(), "{\"output\":\"json\"}\n");
+
+    // one line each
+    let mut cmd = |s: &str| -> String {
+        d.parse_cmd(&mut c, s);
+        let out = sink.take();
+        assert_eq!(out.matches('\n').count(), 1, "{}", out);
+        out
+    };
+
+    // registers
+    let r: JsonRegisters = serde_json::from_str(&cmd("r")).unwrap();
+    assert_eq!(
+        (r.pc, r.a, r.x, r.y, r.s, r.p),
+        (0x407, 0x42, 0x10, 0, 0xff, 0x24)
+    );
+    assert_eq!(r.flags, "--U--I--");
+    assert_eq!(r.cycles, 16);
+
+    // breakpoints, the exec one has hit
+    let bl: Vec<JsonBreakpoint> = serde_json::from_str(&cmd("bl")).unwrap();
+    assert_eq!(bl.len(), 2);
+    assert_eq!((bl[0].id, bl[0].address, bl[0].end), (0, 0x407, 0x407));
+    assert_eq!(
+        (bl[0].t.as_str(), bl[0].enabled, bl[0].hits),
+        ("--X", true, 1)
+    );
+    assert_eq!((bl[1].id, bl[1].address, bl[1].end), (1, 0x300, 0x3ff));
+    assert_eq!((bl[1].t.as_str(), bl[1].hits), ("-W-", 0));
+    assert_eq!(bl[1].text, "$0300-$03ff [-W-,enabled]");
+
+    // disassembly
+    let dis: Vec<JsonInstruction> = serde_json::from_str(&cmd("d 2 $0402")).unwrap();
+    assert_eq!(dis.len(), 2);
+    assert_eq!((dis[0].addr, &dis[0].bytes[..]), (0x402, &[0xa2, 0x10][..]));
+    assert_eq!(dis[1].addr, 0x404);
+    assert_eq!(dis[1].bytes, [0x9d, 0x00, 0x02]);
+    assert!(dis[1].text.contains("STA $0200, X"), "{}", dis[1].text);
+
+    // memory
+    let m: JsonMemory = serde_json::from_str(&cmd("x 2 $0210")).unwrap();
+    assert_eq!((m.address, &m.bytes[..]), (0x210, &[0x42, 0x00][..]));
+
+    // errors, and bc does not ask
+    let e: JsonError = serde_json::from_str(&cmd("zz")).unwrap();
+    assert_eq!(e.error, "invalid command");
+    let e: JsonError = serde_json::from_str(&cmd("d 2 $nolabel")).unwrap();
+    assert!(!e.error.is_empty());
+    cmd("bc");
+    let bl: Vec<JsonBreakpoint> = serde_json::from_str(&cmd("bl")).unwrap();
+    assert!(bl.is_empty());
+
+    // back to text
+    assert_eq!(cmd("set output text"), "output is text.\n");
+    assert!(!d.is_json_output());
+}
+
+/**
+ * three nested subroutines called from $400, returns a cpu at $400 and an enabled debugger.
+ *
+ * $0400: jsr $0410, nop
+ * $0410: jsr $0420, rts
+ * $0420: jsr $0430, rts
+ * $0430: inx, rts
+ */
+fn nested_jsr() -> (Cpu, Debugger) {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(true);
+    d.write_memory(&mut c, 0x400, &[0x20, 0x10, 0x04, 0xea])
+        .unwrap();
+    d.write_memory(&mut c, 0x410, &[0x20, 0x20, 0x04, 0x60])
+        .unwrap();
+    d.write_memory(&mut c, 0x420, &[0x20, 0x30, 0x04, 0x60])
+        .unwrap();
+    d.write_memory(&mut c, 0x430, &[0xe8, 0x60]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    (c, d)
+}
+
+#[test]
+fn step_over_and_run_to() {
+    // step over the whole call chain
+    let (mut c, mut d) = nested_jsr();
+    d.queue_cmd("n");
+    d.queue_cmd("q");
+    c.run(Some(&mut d), 0).unwrap();
+    assert_eq!(c.regs.pc, 0x403);
+    assert_eq!(c.regs.x, 1);
+    assert_eq!(c.regs.s, 0xff);
+    assert!(d.list_breakpoints().is_empty());
+    assert_eq!(d.triggered_breakpoint(), None);
+
+    // run to, the temporary breakpoint is deleted once triggered
+    let (mut c, mut d) = nested_jsr();
+    d.queue_cmd("u $0420");
+    d.queue_cmd("q");
+    c.run(Some(&mut d), 0).unwrap();
+    assert_eq!(c.regs.pc, 0x420);
+    assert!(d.list_breakpoints().is_empty());
+}
+
+#[test]
+fn step_out() {
+    // stop at level 2, step out skipping the nested call to level 3
+    let (mut c, mut d) = nested_jsr();
+    d.add_breakpoint(&mut c, 0x420, BreakpointType::EXEC, None)
+        .unwrap();
+    d.queue_cmd("g");
+    d.queue_cmd("fin");
+    d.queue_cmd("q");
+    c.run(Some(&mut d), 0).unwrap();
+    assert_eq!(c.regs.pc, 0x413);
+    assert_eq!(c.regs.x, 1);
+    assert_eq!(d.triggered_breakpoint(), Some(0));
+
+    // step out twice from level 3
+    let (mut c, mut d) = nested_jsr();
+    d.queue_cmd("u $0430");
+    d.queue_cmd("fin");
+    d.queue_cmd("fin");
+    d.queue_cmd("q");
+    c.run(Some(&mut d), 0).unwrap();
+    assert_eq!(c.regs.pc, 0x413);
+    assert_eq!(c.regs.s, 0xfd);
+}
+
+#[test]
+fn trace_nestest() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // ldx #$03, stx $10, ldy #$01, lda ($10),y, dex, bne $0408, jmp ($0500), lax $10 (undocumented), nop
+    let prg = [
+        0xa2, 0x03, 0x86, 0x10, 0xa0, 0x01, 0xb1, 0x10, 0xca, 0xd0, 0xfd, 0x6c, 0x00, 0x05,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    d.write_memory(&mut c, 0x410, &[0xa7, 0x10, 0xea]).unwrap();
+    d.write_memory(&mut c, 0x500, &[0x10, 0x04]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    let path = env::temp_dir().join(format!("rv6502emu_{}_trace.log", std::process::id()));
+    d.start_trace(path.to_str().unwrap(), TraceFormat::Nestest)
+        .unwrap();
+    while c.regs.pc != 0x413 {
+        c.run(Some(&mut d), 1).unwrap();
+    }
+    d.stop_trace().unwrap();
+
+    let golden = fs::read_to_string("./tests/trace/nestest_format.log").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), golden);
+}
+
+/**
+ * the trace_nestest program at $400, reset.
+ */
+fn trace_prg() -> (Cpu, Debugger) {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    let prg = [
+        0xa2, 0x03, 0x86, 0x10, 0xa0, 0x01, 0xb1, 0x10, 0xca, 0xd0, 0xfd, 0x6c, 0x00, 0x05,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    d.write_memory(&mut c, 0x410, &[0xa7, 0x10, 0xea]).unwrap();
+    d.write_memory(&mut c, 0x500, &[0x10, 0x04]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    (c, d)
+}
+
+#[test]
+fn compare_trace() {
+    // the golden trace matches
+    let (mut c, mut d) = trace_prg();
+    let r = d
+        .compare_trace(
+            &mut c,
+            "./tests/trace/nestest_format.log",
+            TraceFormat::Nestest,
+        )
+        .unwrap();
+    assert!(r.is_none());
+    assert_eq!(c.regs.pc, 0x413);
+
+    // a wrong X on the 10th line (the last bne of the loop)
+    let golden = fs::read_to_string("./tests/trace/nestest_format.log").unwrap();
+    let mut lines: Vec<String> = golden.lines().map(String::from).collect();
+    lines[9] = lines[9].replace("X:00", "X:01");
+    let path = env::temp_dir().join(format!("rv6502emu_{}_wrong.log", std::process::id()));
+    fs::write(&path, lines.join("\n")).unwrap();
+    let (mut c, mut d) = trace_prg();
+    let r = d
+        .compare_trace(&mut c, path.to_str().unwrap(), TraceFormat::Nestest)
+        .unwrap()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(r.line, 10);
+    assert_eq!(r.fields, vec!["X"]);
+    assert_eq!(r.expected, lines[9]);
+    assert_eq!(r.actual, golden.lines().nth(9).unwrap());
+    assert_eq!(r.context.len(), TRACE_CONTEXT);
+    assert_eq!(r.context[TRACE_CONTEXT - 1].0, lines[8]);
+    assert!(r.to_string().contains(&format!("> {} | ", lines[9])));
+
+    // stopped before the diverging instruction
+    assert_eq!((c.regs.pc, c.regs.x), (0x409, 0x00));
+
+    // csv, written by the trace itself
+    let path = env::temp_dir().join(format!("rv6502emu_{}_trace.csv", std::process::id()));
+    let (mut c, mut d) = trace_prg();
+    d.start_trace(path.to_str().unwrap(), TraceFormat::Csv)
+        .unwrap();
+    while c.regs.pc != 0x413 {
+        c.run(Some(&mut d), 1).unwrap();
+    }
+    d.stop_trace().unwrap();
+    let csv = fs::read_to_string(&path).unwrap();
+    assert!(csv.starts_with("pc,a,x,y,p,s,cycles\n0400,00,00,00,24,ff,7\n"));
+    let (mut c, mut d) = trace_prg();
+    assert!(d
+        .compare_trace(&mut c, path.to_str().unwrap(), TraceFormat::Csv)
+        .unwrap()
+        .is_none());
+
+    // one cycle off on the jmp ($0500)
+    fs::write(
+        &path,
+        csv.replace("040b,00,00,01,26,ff,33", "040b,00,00,01,26,ff,34"),
+    )
+    .unwrap();
+    let (mut c, mut d) = trace_prg();
+    let r = d
+        .compare_trace(&mut c, path.to_str().unwrap(), TraceFormat::Csv)
+        .unwrap()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!((r.line, r.fields.clone()), (12, vec!["CYC"]));
+    assert_eq!(r.actual, "040b,00,00,01,26,ff,33");
+}
+
+#[test]
+fn labels() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(true);
+    assert_eq!(d.load_labels("./tests/labels/sample.lbl").unwrap(), 5);
+    assert_eq!(d.label_at(0x410), Some("init_screen"));
+    assert_eq!(d.label_at(0x10), Some("ptr"));
+    assert_eq!(d.resolve_label("main").unwrap(), 0x400);
+
+    // ambiguous and unknown symbols
+    let e = d.resolve_label("loop").unwrap_err();
+    assert_eq!(e.t, CpuErrorType::Generic);
+    assert!(e.msg.unwrap().contains("ambiguous"));
+    let e = d.resolve_label("nothere").unwrap_err();
+    assert!(e.msg.unwrap().contains("unknown"));
+
+    // jsr $0410, lda $10, nop
+    d.write_memory(&mut c, 0x400, &[0x20, 0x10, 0x04, 0xa5, 0x10, 0xea])
+        .unwrap();
+    let v = d.disassemble(&mut c, 0x400, 3).unwrap();
+    assert!(d.format_instruction(&v[0]).contains("JSR init_screen\t"));
+    assert!(d.format_instruction(&v[1]).contains("LDA ptr\t"));
+    assert!(d.parse_cmd(&mut c, "d 3 main").1);
+
+    // breakpoints by name
+    assert!(d.parse_cmd(&mut c, "bx init_screen").1);
+    assert!(!d.parse_cmd(&mut c, "bx loop").1);
+    assert!(!d.parse_cmd(&mut c, "bx nothere").1);
+    let l = d.list_breakpoints();
+    assert_eq!(l.len(), 1);
+    assert_eq!(l[0].address(), 0x410);
+}
+
+#[test]
+fn assembler_65c02() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(true);
+    let prg = [
+        "stz $10",
+        "lda ($20)",
+        "jmp ($2000,x)",
+        "bbr3 $12,$34",
+        "smb 5,$40",
+        "bra $0400",
+        "lda ($10, x)",
+        "nop",
+        "bbs7 $12,$0400",
+    ];
+
+    // not available on 6502
+    assert!(d.assemble(&mut c, 0x400, &prg).is_err());
+    assert_eq!(d.assemble(&mut c, 0x400, &["nop"]).unwrap(), 0x401);
+    assert_eq!(d.read_memory(&mut c, 0x400, 1).unwrap(), [0xea]);
+
+    c.set_cpu_type(CpuType::WDC65C02);
+    assert_eq!(d.assemble(&mut c, 0x400, &prg).unwrap(), 0x414);
+    assert_eq!(
+        d.read_memory(&mut c, 0x400, 0x14).unwrap(),
+        [
+            0x64, 0x10, 0xb2, 0x20, 0x7c, 0x00, 0x20, 0x3f, 0x12, 0x34, 0xd7, 0x40, 0x80, 0xf2,
+            0xa1, 0x10, 0xea, 0xff, 0x12, 0xec
+        ]
+    );
+
+    // branch out of range
+    let e = d.assemble(&mut c, 0x400, &["bra $0500"]).unwrap_err();
+    assert!(e.msg.unwrap().contains("out of range"));
+}
+
+#[test]
+fn hexdump() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    let data: Vec<u8> = (0x3c..0x5c).chain([0x00, 0xc1, 0xff, 0x7e]).collect();
+    d.write_memory(&mut c, 0x1000, &data).unwrap();
+
+    assert_eq!(
+        d.hexdump(&mut c, 0x1000, 20, 8).unwrap(),
+        "$1000  3c 3d 3e 3f 40 41 42 43  |<=>?@ABC|\n\
+         $1008  44 45 46 47 48 49 4a 4b  |DEFGHIJK|\n\
+         $1010  4c 4d 4e 4f              |LMNO|\n"
+    );
+    assert_eq!(
+        d.hexdump(&mut c, 0x1010, 20, 16).unwrap(),
+        "$1010  4c 4d 4e 4f 50 51 52 53 54 55 56 57 58 59 5a 5b  |LMNOPQRSTUVWXYZ[|\n\
+         $1020  00 c1 ff 7e                                      |...~|\n"
+    );
+    assert_eq!(
+        d.hexdump(&mut c, 0x1018, 12, 32).unwrap(),
+        format!(
+            "$1018  54 55 56 57 58 59 5a 5b 00 c1 ff 7e{}|TUVWXYZ[...~|\n",
+            " ".repeat(20 * 3 + 2)
+        )
+    );
+
+    // uppercase, petscii
+    let opts = HexdumpOptions {
+        width: 8,
+        uppercase: true,
+        text: HexdumpText::Petscii,
+    };
+    assert_eq!(
+        d.hexdump_with_options(&mut c, 0x101c, 8, &opts).unwrap(),
+        "$101C  58 59 5A 5B 00 C1 FF 7E  |XYZ[.A..|\n"
+    );
+    let opts = HexdumpOptions {
+        text: HexdumpText::None,
+        ..Default::default()
+    };
+    assert_eq!(
+        d.hexdump_with_options(&mut c, 0x1020, 4, &opts).unwrap(),
+        "$1020  00 c1 ff 7e\n"
+    );
+
+    // errors
+    assert!(d.hexdump(&mut c, 0x1000, 4, 0).is_err());
+    assert!(d.hexdump(&mut c, 0xfffe, 4, 16).is_err());
+
+    // to a file from the command line
+    let path = env::temp_dir().join(format!("rv6502emu_{}_hexdump.txt", std::process::id()));
+    let cmd = format!("x 4 $1020 8 u >{}", path.to_str().unwrap());
+    assert!(d.parse_cmd(&mut c, &cmd).1);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "$1020  00 C1 FF 7E              |...~|\n"
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn script_and_breakpoint_commands() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(true);
+
+    // lda #$aa, sta $0200, ldx #$55, stx $0201, jmp *
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[
+            0xa9, 0xaa, 0x8d, 0x00, 0x02, 0xa2, 0x55, 0x8e, 0x01, 0x02, 0x4c, 0x0a, 0x04,
+        ],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // the script sets the breakpoint and runs, the breakpoint dumps memory and quits
+    let dump = env::temp_dir().join(format!("rv6502emu_{}_script.txt", std::process::id()));
+    let script = env::temp_dir().join(format!("rv6502emu_{}_script.cmd", std::process::id()));
+    fs::write(
+        &script,
+        format!(
+            "# dump the stored bytes at the jmp\n\nbx $040a do \"r; x 4 $0200 >{}; q\"\ng\nr\n",
+            dump.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    assert_eq!(d.run_script(&mut c, script.to_str().unwrap()).unwrap(), "p");
+    assert_eq!(
+        d.list_breakpoints()[0].commands(),
+        [
+            "r",
+            &format!("x 4 $0200 >{}", dump.to_str().unwrap())[..],
+            "q"
+        ]
+    );
+    let r = c.run_until(Some(&mut d), 0, 0).unwrap();
+    assert_eq!(r.reason, StopReason::Stopped);
+    assert_eq!(c.regs.pc, 0x40a);
+    assert_eq!(
+        fs::read_to_string(&dump).unwrap(),
+        "$0200  aa 55 00 00                                      |.U..|\n"
+    );
+    fs::remove_file(&dump).unwrap();
+
+    // errors report the line
+    fs::write(&script, "r\n# comment\nbx $zzzz\n").unwrap();
+    let e = d.run_script(&mut c, script.to_str().unwrap()).unwrap_err();
+    assert_eq!(
+        e.msg.unwrap(),
+        format!("{}:3: command 'bx $zzzz' failed!", script.to_str().unwrap())
+    );
+    fs::remove_file(&script).unwrap();
+
+    // a command list ending with g continues, malformed lists are rejected
+    d.clear_breakpoints();
+    assert!(d.parse_cmd(&mut c, "bx $0405 do \"r; g\"").1);
+    assert_eq!(
+        d.list_breakpoints()[0].to_string(),
+        "$0405 [--X,enabled] do \"r; g\""
+    );
+    assert!(!d.parse_cmd(&mut c, "bx $0407 do r").1);
+    assert!(!d.parse_cmd(&mut c, "bx $0407 do \"\"").1);
+    d.set_breakpoint_commands(0, &[]).unwrap();
+    assert!(d.set_breakpoint_commands(1, &["g"]).is_err());
+}
+
+#[test]
+fn output_sink() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(true);
+    let sink = Sink::default();
+    d.set_output(sink.clone());
+
+    // lda #$42, nop, jmp *
+    d.write_memory(&mut c, 0x400, &[0xa9, 0x42, 0xea, 0x4c, 0x03, 0x04])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // commands
+    assert!(d.parse_cmd(&mut c, "bx $0402").1);
+    assert!(d.parse_cmd(&mut c, "bl").1);
+    assert!(d.parse_cmd(&mut c, "x 2 $0400").1);
+    assert!(!d.parse_cmd(&mut c, "zz").1);
+    assert_eq!(
+        sink.take(),
+        "breakpoint set! ($0402 [--X,enabled])\n\
+         listing 1 breakpoints\n\n\
+         0... $0402 [--X,enabled]\n\
+         dumping 2 bytes at $0400\n\n\
+         $0400  a9 42                                            |.B|\n\
+         invalid command, try 'h' for help !\n"
+    );
+
+    // prompt, breakpoint messages and registers from the run loop
+    d.queue_cmd("g");
+    d.queue_cmd("r");
+    d.queue_cmd("q");
+    c.run_until(Some(&mut d), 0, 0).unwrap();
+    assert_eq!(
+        sink.take(),
+        "?:> g\n\
+         breakpoint 0 triggered!\n\
+         ?:> r\n\
+         \tPC: $0402, A: $42, X: $00, Y: $00, S: $ff, P: $24(--U--I--), cycles=9\n\
+         ?:> q\n\
+         quit!\n"
+    );
+}
+
+#[test]
+fn backtrace() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // $400: jsr $0500, $500: jsr $0600, $600: jsr $0700
+    d.write_memory(&mut c, 0x400, &[0x20, 0x00, 0x05]).unwrap();
+    d.write_memory(&mut c, 0x500, &[0x20, 0x00, 0x06, 0x60])
+        .unwrap();
+    d.write_memory(&mut c, 0x600, &[0x20, 0x00, 0x07, 0x60])
+        .unwrap();
+
+    // $700: pushes $04, $02 (looks like the return address of the jsr at $400) and a junk $ff
+    d.write_memory(
+        &mut c,
+        0x700,
+        &[0xa9, 0x04, 0x48, 0xa9, 0x02, 0x48, 0xa9, 0xff, 0x48, 0xea],
+    )
+    .unwrap();
+    let labels = env::temp_dir().join(format!("rv6502emu_{}_bt.lbl", std::process::id()));
+    fs::write(&labels, "al C:0600 .sub2\n").unwrap();
+    d.load_labels(labels.to_str().unwrap()).unwrap();
+    fs::remove_file(&labels).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    assert!(d.backtrace(&mut c).is_empty());
+
+    // three frames, newest first
+    d.add_breakpoint(&mut c, 0x700, BreakpointType::EXEC, None)
+        .unwrap();
+    c.run_until(Some(&mut d), 0, 0).unwrap();
+    assert_eq!(c.stack_slice(), vec![0x02, 0x06, 0x02, 0x05, 0x02, 0x04]);
+    let bt = d.backtrace(&mut c);
+    let sites: Vec<(u16, u16, bool)> = bt
+        .iter()
+        .map(|f| (f.call_site, f.target, f.certain))
+        .collect();
+    assert_eq!(
+        sites,
+        vec![
+            (0x600, 0x700, true),
+            (0x500, 0x600, true),
+            (0x400, 0x500, true)
+        ]
+    );
+    assert_eq!(bt[1].label.as_deref(), Some("sub2"));
+    assert_eq!(
+        bt[1].to_string(),
+        "$0500: jsr $0600 (sub2), returns to $0503 (stack $01fc)"
+    );
+
+    // the pushed data is a false positive, marked as uncertain
+    d.add_breakpoint(&mut c, 0x709, BreakpointType::EXEC, None)
+        .unwrap();
+    c.run_until(Some(&mut d), 0, 0).unwrap();
+    let bt = d.backtrace(&mut c);
+    let sites: Vec<(u16, bool)> = bt.iter().map(|f| (f.call_site, f.certain)).collect();
+    assert_eq!(
+        sites,
+        vec![(0x400, false), (0x600, true), (0x500, true), (0x400, true)]
+    );
+    assert_eq!(
+        bt[0].to_string(),
+        "$0400: jsr $0500, returns to $0403 (stack $01f8) ?"
+    );
+
+    // bt command
+    let sink = Sink::default();
+    d.set_output(sink.clone());
+    assert!(d.parse_cmd(&mut c, "bt").1);
+    assert_eq!(
+        sink.take().lines().nth(2).unwrap(),
+        "#2 $0500: jsr $0600 (sub2), returns to $0503 (stack $01fc)"
+    );
+}
+
+#[test]
+fn edit_registers() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(true);
+    let sink = Sink::default();
+    d.set_output(sink.clone());
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // registers, the updated registers are shown
+    assert!(d.parse_cmd(&mut c, "v a $12").1);
+    assert_eq!(
+        sink.take(),
+        "\tPC: $0400, A: $12, X: $00, Y: $00, S: $ff, P: $24(--U--I--), cycles=7\n"
+    );
+    assert!(d.parse_cmd(&mut c, "v x 34").1);
+    assert!(d.parse_cmd(&mut c, "v y $56").1);
+    assert!(d.parse_cmd(&mut c, "v s $f0").1);
+    assert!(d.parse_cmd(&mut c, "v pc $c000").1);
+    assert_eq!(
+        (c.regs.a, c.regs.x, c.regs.y, c.regs.s, c.regs.pc),
+        (0x12, 0x34, 0x56, 0xf0, 0xc000)
+    );
+    assert!(d.parse_cmd(&mut c, "v sp $e0").1);
+    assert_eq!(c.regs.s, 0xe0);
+
+    // p keeps U set
+    assert!(d.parse_cmd(&mut c, "v p $c3").1);
+    assert_eq!(c.regs.p.bits(), 0xe3);
+    assert!(d.parse_cmd(&mut c, "v p 0").1);
+    assert_eq!(c.regs.p.bits(), 0x20);
+
+    // single flags
+    for (f, bits) in [
+        ("n", 0x80),
+        ("v", 0x40),
+        ("b", 0x10),
+        ("d", 0x08),
+        ("i", 0x04),
+        ("z", 0x02),
+        ("c", 0x01),
+    ] {
+        assert!(d.parse_cmd(&mut c, &format!("v {} 1", f)).1);
+        assert_eq!(c.regs.p.bits(), 0x20 | bits);
+        assert!(d.parse_cmd(&mut c, &format!("v {} 0", f)).1);
+        assert_eq!(c.regs.p.bits(), 0x20);
+    }
+    sink.take();
+
+    // out of range values, unknown registers and missing values are rejected, nothing changes
+    for cmd in [
+        "v a $100",
+        "v s 1ff",
+        "v p $100",
+        "v pc $10000",
+        "v c 2",
+        "v n $ff",
+        "v a zz",
+        "v q 1",
+        "v u 1",
+        "v a",
+    ] {
+        assert!(!d.parse_cmd(&mut c, cmd).1, "{}", cmd);
+    }
+    assert_eq!(
+        (c.regs.a, c.regs.s, c.regs.pc, c.regs.p.bits()),
+        (0x12, 0xe0, 0xc000, 0x20)
+    );
+    assert!(!sink.take().contains("PC:"));
+}
@@ -0,0 +1,463 @@
+/*
+ * Filename: /tests/decimal.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 21:31:40
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! decimal mode and the cpus without it.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, CpuFlags, CpuType, ResetKind};
+
+/**
+ * runs sed, then lda #a, clc, the given opcode with an immediate operand, then lax $10 (undocumented), returns A and X.
+ */
+fn run_one(t: CpuType, bcd: bool, opcode: u8, a: u8, oper: u8) -> (u8, u8) {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    c.set_bcd_enabled(bcd);
+    let d = Debugger::new(false);
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[0xf8, 0xa9, a, 0x18, opcode, oper, 0xa7, 0x10],
+    )
+    .unwrap();
+    d.write_memory(&mut c, 0x10, &[0x77]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 4).unwrap();
+    let res = c.regs.a;
+    c.run_until(None, 0, 1).unwrap();
+    assert!(c.regs.decimal());
+    (res, c.regs.x)
+}
+
+#[test]
+fn ricoh_2a03() {
+    // adc #$09 on $01
+    assert_eq!(run_one(CpuType::MOS6502, true, 0x69, 0x01, 0x09).0, 0x10);
+    assert_eq!(
+        run_one(CpuType::Ricoh2A03, true, 0x69, 0x01, 0x09),
+        (0x0a, 0x77)
+    );
+
+    // sbc #$01 on $10 (borrow, carry clear)
+    assert_eq!(run_one(CpuType::MOS6502, true, 0xe9, 0x10, 0x01).0, 0x08);
+    assert_eq!(run_one(CpuType::Ricoh2A03, true, 0xe9, 0x10, 0x01).0, 0x0e);
+}
+
+#[test]
+fn bcd_disabled() {
+    assert_eq!(run_one(CpuType::MOS6502, false, 0x69, 0x01, 0x09).0, 0x0a);
+    assert_eq!(run_one(CpuType::WDC65C02, false, 0x69, 0x01, 0x09).0, 0x0a);
+    assert_eq!(run_one(CpuType::WDC65C02, true, 0x69, 0x01, 0x09).0, 0x10);
+}
+
+/**
+ * the expected A, C, Z, N, V of a decimal mode ADC (or SBC) of b to a with carry c, as documented in
+ * http://www.6502.org/tutorials/decimal_mode.html#A (sequences 1 to 4).
+ */
+fn bcd_model(cmos: bool, sbc: bool, a: u8, b: u8, c: bool) -> (u8, bool, bool, bool, bool) {
+    let (ai, bi, ci) = (a as i32, b as i32, c as i32);
+    if sbc {
+        // binary flags on NMOS, and C/V on 65C02
+        let bin = ai - bi + ci - 1;
+        let v = ((ai ^ bin) & (ai ^ bi) & 0x80) != 0;
+        let carry = bin >= 0;
+        let res = if cmos {
+            // seq. 4
+            let al = (ai & 0x0f) - (bi & 0x0f) + ci - 1;
+            let mut r = bin;
+            if r < 0 {
+                r -= 0x60;
+            }
+            if al < 0 {
+                r -= 0x06;
+            }
+            (r & 0xff) as u8
+        } else {
+            // seq. 3
+            let mut al = (ai & 0x0f) - (bi & 0x0f) + ci - 1;
+            if al < 0 {
+                al = ((al - 0x06) & 0x0f) - 0x10;
+            }
+            let mut r = (ai & 0xf0) - (bi & 0xf0) + al;
+            if r < 0 {
+                r -= 0x60;
+            }
+            (r & 0xff) as u8
+        };
+        let nz = if cmos { res } else { (bin & 0xff) as u8 };
+        return (res, carry, nz == 0, nz & 0x80 != 0, v);
+    }
+
+    // seq. 1, accumulator and carry
+    let mut al = (ai & 0x0f) + (bi & 0x0f) + ci;
+    if al >= 0x0a {
+        al = ((al + 0x06) & 0x0f) + 0x10;
+    }
+    let mut r = (ai & 0xf0) + (bi & 0xf0) + al;
+    if r >= 0xa0 {
+        r += 0x60;
+    }
+    let res = (r & 0xff) as u8;
+
+    // seq. 2, N and V from the signed intermediate result
+    let signed = (a & 0xf0) as i8 as i32 + (b & 0xf0) as i8 as i32 + al;
+    let v = !(-128..=127).contains(&signed);
+    if cmos {
+        return (res, r >= 0x100, res == 0, res & 0x80 != 0, v);
+    }
+    let bin = ((ai + bi + ci) & 0xff) as u8;
+    (res, r >= 0x100, bin == 0, signed & 0x80 != 0, v)
+}
+
+#[test]
+fn adc_half_carry() {
+    // (A, operand, C in, NMOS (A, C, Z, N, V), 65C02 (A, C, Z, N, V)): the low digit carry propagates into the high one
+    let cases = [
+        (
+            0x99,
+            0x01,
+            false,
+            (0x00, true, false, true, false),
+            (0x00, true, true, false, false),
+        ),
+        (
+            0x09,
+            0x01,
+            false,
+            (0x10, false, false, false, false),
+            (0x10, false, false, false, false),
+        ),
+        (
+            0x79,
+            0x00,
+            true,
+            (0x80, false, false, true, true),
+            (0x80, false, false, true, true),
+        ),
+        (
+            0x58,
+            0x46,
+            true,
+            (0x05, true, false, true, true),
+            (0x05, true, false, false, true),
+        ),
+        (
+            0x0f,
+            0x0f,
+            true,
+            (0x15, false, false, false, false),
+            (0x15, false, false, false, false),
+        ),
+    ];
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        let mut c = Cpu::new_default(None);
+        c.set_cpu_type(t);
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        for (a, b, carry, nmos, cmos) in cases {
+            // adc #b
+            c.bus
+                .get_memory()
+                .load_from_slice(&[0x69, b], 0x400)
+                .unwrap();
+            c.regs.pc = 0x400;
+            c.regs.a = a;
+            c.regs.p = CpuFlags::U | CpuFlags::D;
+            c.regs.p.set(CpuFlags::C, carry);
+            c.run_until(None, 0, 1).unwrap();
+            let p = c.regs.p;
+            let expected = if t == CpuType::WDC65C02 { cmos } else { nmos };
+            assert_eq!(
+                (
+                    c.regs.a,
+                    p.contains(CpuFlags::C),
+                    p.contains(CpuFlags::Z),
+                    p.contains(CpuFlags::N),
+                    p.contains(CpuFlags::V)
+                ),
+                expected,
+                "{} ${:02x}+${:02x}+{}",
+                t,
+                a,
+                b,
+                carry as u8
+            );
+            assert_eq!(
+                expected,
+                bcd_model(t == CpuType::WDC65C02, false, a, b, carry)
+            );
+        }
+    }
+}
+
+#[test]
+fn decimal_sweep() {
+    // adc #$xx and sbc #$xx with every A, operand and carry, compared with the documented behaviour
+    let mut mismatches: Vec<String> = Vec::new();
+    let mut count = 0;
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        let mut c = Cpu::new_default(None);
+        c.set_cpu_type(t);
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        for opcode in [0x69, 0xe9] {
+            c.bus.get_memory().write_byte(0x400, opcode).unwrap();
+            for a in 0..=0xff_u8 {
+                for b in 0..=0xff_u8 {
+                    c.bus.get_memory().write_byte(0x401, b).unwrap();
+                    for carry in [false, true] {
+                        c.regs.pc = 0x400;
+                        c.regs.a = a;
+                        c.regs.p = CpuFlags::U | CpuFlags::D;
+                        c.regs.p.set(CpuFlags::C, carry);
+                        c.run_until(None, 0, 1).unwrap();
+                        count += 1;
+
+                        let expected =
+                            bcd_model(t == CpuType::WDC65C02, opcode == 0xe9, a, b, carry);
+                        let p = c.regs.p;
+                        let got = (
+                            c.regs.a,
+                            p.contains(CpuFlags::C),
+                            p.contains(CpuFlags::Z),
+                            p.contains(CpuFlags::N),
+                            p.contains(CpuFlags::V),
+                        );
+                        if got != expected && mismatches.len() < 10 {
+                            mismatches.push(format!(
+                                "{} {} a=${:02x} oper=${:02x} c={}: expected (a, c, z, n, v)={:?}, got {:?}",
+                                t,
+                                if opcode == 0x69 { "adc" } else { "sbc" },
+                                a,
+                                b,
+                                carry as u8,
+                                expected,
+                                got
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(count, 2 * 2 * 256 * 256 * 2);
+    assert!(mismatches.is_empty(), "\n{}", mismatches.join("\n"));
+}
+
+/**
+ * runs the given opcode with an immediate operand in decimal mode, returns A, C, Z, N, V.
+ */
+fn step_bcd(c: &mut Cpu, opcode: u8, a: u8, oper: u8, carry: bool) -> (u8, bool, bool, bool, bool) {
+    c.bus
+        .get_memory()
+        .load_from_slice(&[opcode, oper], 0x400)
+        .unwrap();
+    c.regs.pc = 0x400;
+    c.regs.a = a;
+    c.regs.p = CpuFlags::U | CpuFlags::D;
+    c.regs.p.set(CpuFlags::C, carry);
+    c.run_until(None, 0, 1).unwrap();
+    let p = c.regs.p;
+    (
+        c.regs.a,
+        p.contains(CpuFlags::C),
+        p.contains(CpuFlags::Z),
+        p.contains(CpuFlags::N),
+        p.contains(CpuFlags::V),
+    )
+}
+
+#[test]
+fn decimal_flags_nmos_vs_65c02() {
+    // (opcode, A, operand, C in, NMOS (A, C, Z, N, V), 65C02 (A, C, Z, N, V)): same accumulator, different N/Z
+    let cases = [
+        // sed, clc, lda #$99, adc #$01: NMOS Z/N come from the binary sum $9a
+        (
+            0x69,
+            0x99,
+            0x01,
+            false,
+            (0x00, true, false, true, false),
+            (0x00, true, true, false, false),
+        ),
+        (
+            0x69,
+            0x50,
+            0x50,
+            false,
+            (0x00, true, false, true, true),
+            (0x00, true, true, false, true),
+        ),
+        (
+            0x69,
+            0x80,
+            0xf0,
+            false,
+            (0xd0, true, false, false, true),
+            (0xd0, true, false, true, true),
+        ),
+        (
+            0x69,
+            0x89,
+            0x76,
+            true,
+            (0x66, true, true, false, false),
+            (0x66, true, false, false, false),
+        ),
+        (
+            0x69,
+            0x99,
+            0x99,
+            true,
+            (0x99, true, false, false, true),
+            (0x99, true, false, true, true),
+        ),
+        // sbc: NMOS N/Z come from the binary difference
+        (
+            0xe9,
+            0x00,
+            0x21,
+            true,
+            (0x79, false, false, true, false),
+            (0x79, false, false, false, false),
+        ),
+        (
+            0xe9,
+            0x50,
+            0x99,
+            true,
+            (0x51, false, false, true, true),
+            (0x51, false, false, false, true),
+        ),
+        (
+            0xe9,
+            0x00,
+            0x99,
+            false,
+            (0x00, false, false, false, false),
+            (0x00, false, true, false, false),
+        ),
+        // no difference
+        (
+            0xe9,
+            0x00,
+            0x00,
+            false,
+            (0x99, false, false, true, false),
+            (0x99, false, false, true, false),
+        ),
+        (
+            0xe9,
+            0x01,
+            0x01,
+            true,
+            (0x00, true, true, false, false),
+            (0x00, true, true, false, false),
+        ),
+        (
+            0xe9,
+            0x80,
+            0x01,
+            true,
+            (0x79, true, false, false, true),
+            (0x79, true, false, false, true),
+        ),
+    ];
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        let mut c = Cpu::new_default(None);
+        c.set_cpu_type(t);
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        for (opcode, a, b, carry, nmos, cmos) in cases {
+            let expected = if t == CpuType::WDC65C02 { cmos } else { nmos };
+            assert_eq!(
+                step_bcd(&mut c, opcode, a, b, carry),
+                expected,
+                "{} opcode=${:02x} a=${:02x} oper=${:02x} c={}",
+                t,
+                opcode,
+                a,
+                b,
+                carry as u8
+            );
+        }
+    }
+}
+
+#[test]
+fn decimal_table_nmos() {
+    // (opcode, A, operand, C in, (A, C, Z, N, V)) on the NMOS 6502, including invalid bcd operands
+    let cases = [
+        (0x69, 0x99, 0x01, false, (0x00, true, false, true, false)),
+        (0x69, 0x00, 0x00, false, (0x00, false, true, false, false)),
+        (0x69, 0x79, 0x00, true, (0x80, false, false, true, true)),
+        (0x69, 0x24, 0x56, false, (0x80, false, false, true, true)),
+        (0x69, 0x93, 0x82, false, (0x75, true, false, false, true)),
+        (0x69, 0x89, 0x76, false, (0x65, true, false, false, false)),
+        (0x69, 0x89, 0x76, true, (0x66, true, true, false, false)),
+        (0x69, 0x80, 0xf0, false, (0xd0, true, false, false, true)),
+        (0x69, 0x80, 0xfa, false, (0xe0, true, false, true, false)),
+        (0x69, 0x2f, 0x4f, false, (0x74, false, false, false, false)),
+        (0x69, 0x6f, 0x00, true, (0x76, false, false, false, false)),
+        (0x69, 0x58, 0x46, true, (0x05, true, false, true, true)),
+        (0x69, 0x12, 0x34, false, (0x46, false, false, false, false)),
+        (0x69, 0x15, 0x26, false, (0x41, false, false, false, false)),
+        (0x69, 0x81, 0x92, false, (0x73, true, false, false, true)),
+        (0x69, 0x50, 0x50, false, (0x00, true, false, true, true)),
+        (0x69, 0x01, 0x98, true, (0x00, true, false, true, false)),
+        (0x69, 0x99, 0x99, true, (0x99, true, false, false, true)),
+        (0xe9, 0x00, 0x00, false, (0x99, false, false, true, false)),
+        (0xe9, 0x00, 0x00, true, (0x00, true, true, false, false)),
+        (0xe9, 0x00, 0x01, true, (0x99, false, false, true, false)),
+        (0xe9, 0x0a, 0x00, true, (0x0a, true, false, false, false)),
+        (0xe9, 0x0b, 0x00, false, (0x0a, true, false, false, false)),
+        (0xe9, 0x9a, 0x00, true, (0x9a, true, false, true, false)),
+        (0xe9, 0x9b, 0x00, false, (0x9a, true, false, true, false)),
+        (0xe9, 0x46, 0x12, true, (0x34, true, false, false, false)),
+        (0xe9, 0x40, 0x13, true, (0x27, true, false, false, false)),
+        (0xe9, 0x32, 0x02, false, (0x29, true, false, false, false)),
+        (0xe9, 0x12, 0x21, true, (0x91, false, false, true, false)),
+        (0xe9, 0x21, 0x34, true, (0x87, false, false, true, false)),
+        (0xe9, 0x80, 0x01, true, (0x79, true, false, false, true)),
+        (0xe9, 0x01, 0x01, true, (0x00, true, true, false, false)),
+        (0xe9, 0x50, 0x99, true, (0x51, false, false, true, true)),
+        (0xe9, 0x99, 0x98, false, (0x00, true, true, false, false)),
+    ];
+    let mut c = Cpu::new_default(None);
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    for (opcode, a, b, carry, expected) in cases {
+        assert_eq!(
+            step_bcd(&mut c, opcode, a, b, carry),
+            expected,
+            "opcode=${:02x} a=${:02x} oper=${:02x} c={}",
+            opcode,
+            a,
+            b,
+            carry as u8
+        );
+    }
+}
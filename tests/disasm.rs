@@ -0,0 +1,415 @@
+/*
+ * Filename: /tests/disasm.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 15:58:40
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the structured disassembler.
+
+use rv6502emu::bus::{BusBuilder, Device};
+use rv6502emu::cpu::cpu_error::CpuError;
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::disasm::{self, DisasmOptions};
+use rv6502emu::cpu::{AddressingModeId, Cpu, CpuCallbackContext, CpuType, CpuView, ResetKind};
+use rv6502emu::memory::{self, MemoryInitPolicy};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn all_modes_6502() {
+    let block = [
+        0xea, // nop
+        0x0a, // asl a
+        0xa9, 0x01, // lda #$01
+        0xa5, 0x10, // lda $10
+        0xb5, 0x10, // lda $10,x
+        0xb6, 0x10, // ldx $10,y
+        0xad, 0x00, 0x02, // lda $0200
+        0xbd, 0x00, 0x02, // lda $0200,x
+        0xb9, 0x00, 0x02, // lda $0200,y
+        0x6c, 0x00, 0x03, // jmp ($0300)
+        0xa1, 0x10, // lda ($10,x)
+        0xb1, 0x10, // lda ($10),y
+        0xd0, 0xfe, // bne *
+        0xa7, 0x10, // lax $10 (undocumented)
+        0xad, 0x00, // truncated lda $xx00
+    ];
+    let v = disasm::disassemble(&block, 0x400, CpuType::MOS6502);
+
+    // round trip, the truncated instruction is left out
+    let bytes: Vec<u8> = v.iter().flat_map(|i| i.bytes.clone()).collect();
+    assert_eq!(bytes, block[..block.len() - 2]);
+
+    let modes: Vec<AddressingModeId> = v.iter().map(|i| i.mode).collect();
+    assert_eq!(
+        modes,
+        vec![
+            AddressingModeId::Imp,
+            AddressingModeId::Acc,
+            AddressingModeId::Imm,
+            AddressingModeId::Zpg,
+            AddressingModeId::Zpx,
+            AddressingModeId::Zpy,
+            AddressingModeId::Abs,
+            AddressingModeId::Abx,
+            AddressingModeId::Aby,
+            AddressingModeId::Ind,
+            AddressingModeId::Xin,
+            AddressingModeId::Iny,
+            AddressingModeId::Rel,
+            AddressingModeId::Zpg,
+        ]
+    );
+    let text: Vec<String> = v.iter().map(|i| i.operand_text()).collect();
+    assert_eq!(
+        text,
+        vec![
+            "", "A", "#$01", "$10", "$10, X", "$10, Y", "$0200", "$0200, X", "$0200, Y", "($0300)",
+            "($10, X)", "($10), Y", "$fe", "$10",
+        ]
+    );
+
+    assert_eq!(v[6].address, 0x40a);
+    assert_eq!(v[6].operand, Some(0x200));
+    assert_eq!(v[0].operand, None);
+    assert_eq!(v[12].target, Some(0x41a));
+    assert_eq!(v[6].target, None);
+    assert!(v[13].is_undocumented);
+    assert!(!v[12].is_undocumented);
+
+    assert_eq!(
+        v[2].to_string(),
+        "$0402:\ta9 01\t\t-->\tLDA #$01\t[Imm, tgt=$0403]"
+    );
+    assert_eq!(
+        v[7].to_string(),
+        "$040d:\tbd 00 02\t-->\tLDA $0200, X\t[AbX]"
+    );
+    assert_eq!(
+        v[12].to_string(),
+        "$041a:\td0 fe\t\t-->\tBNE $fe\t\t[Rel, tgt=$041a]"
+    );
+}
+
+#[test]
+fn all_modes_65c02() {
+    let block = [
+        0xb2, 0x10, // lda ($10)
+        0x7c, 0x00, 0x03, // jmp ($0300,x)
+        0x0f, 0x10, 0x02, // bbr0 $10, +2
+        0x80, 0x80, // bra -128
+    ];
+    let v = disasm::disassemble(&block, 0xfff8, CpuType::WDC65C02);
+    let bytes: Vec<u8> = v.iter().flat_map(|i| i.bytes.clone()).collect();
+    assert_eq!(bytes, block);
+
+    assert_eq!(v[0].mode, AddressingModeId::Izp);
+    assert_eq!(v[0].operand_text(), "($10)");
+    assert_eq!(v[1].mode, AddressingModeId::Aix);
+    assert_eq!(v[1].operand_text(), "($0300, X)");
+    assert_eq!(v[2].mode, AddressingModeId::Zpr);
+    assert_eq!(v[2].mnemonic, "bbr0");
+    assert_eq!(v[2].operand, Some(0x10));
+    assert_eq!(v[2].target, Some(0x0002));
+    assert_eq!(v[2].operand_text(), "$10, $02");
+
+    // addresses wrap at 64k
+    assert_eq!(v[3].address, 0x0000);
+    assert_eq!(v[3].target, Some(0xff82));
+    assert!(v.iter().all(|i| !i.is_undocumented));
+}
+
+#[test]
+fn disassemble_at() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    let cases: [(&[u8], &str); 16] = [
+        (&[0xea], "NOP"),
+        (&[0x0a], "ASL A"),
+        (&[0xa9, 0x01], "LDA #$01"),
+        (&[0xa5, 0x10], "LDA $10"),
+        (&[0xb5, 0x10], "LDA $10, X"),
+        (&[0xb6, 0x10], "LDX $10, Y"),
+        (&[0xad, 0x00, 0x02], "LDA $0200"),
+        (&[0xbd, 0x00, 0x02], "LDA $0200, X"),
+        (&[0xb9, 0x00, 0x02], "LDA $0200, Y"),
+        (&[0x6c, 0x00, 0x03], "JMP ($0300)"),
+        (&[0xa1, 0x20], "LDA ($20, X)"),
+        (&[0xb1, 0x20], "LDA ($20), Y"),
+        (&[0xd0, 0xfe], "BNE $fe"),
+        // 65c02 only
+        (&[0xb2, 0x10], "LDA ($10)"),
+        (&[0x7c, 0x00, 0x03], "JMP ($0300, X)"),
+        (&[0x0f, 0x10, 0x02], "BBR0 $10, $02"),
+    ];
+
+    // no callbacks must be triggered
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cc = calls.clone();
+    c.set_hook(move |_ctx: &CpuCallbackContext, _cpu: &mut CpuView| {
+        cc.fetch_add(1, Ordering::Relaxed);
+    });
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    calls.store(0, Ordering::Relaxed);
+    for (i, (bytes, text)) in cases.iter().enumerate() {
+        if i == 13 {
+            c.set_cpu_type(CpuType::WDC65C02);
+        }
+        d.write_memory(&mut c, 0x1000, bytes).unwrap();
+        assert_eq!(
+            c.disassemble_at(0x1000).unwrap(),
+            (String::from(*text), bytes.len() as u8)
+        );
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 0);
+    assert_eq!(c.regs.pc, 0x400);
+
+    // operand bytes past the end of memory
+    d.write_memory(&mut c, 0xfffe, &[0xad, 0x00]).unwrap();
+    assert!(c.disassemble_at(0xfffe).is_err());
+    d.write_memory(&mut c, 0xfffe, &[0xa9, 0x00]).unwrap();
+    assert_eq!(
+        c.disassemble_at(0xfffe).unwrap(),
+        (String::from("LDA #$00"), 2)
+    );
+}
+
+#[test]
+fn undocumented() {
+    let block = [
+        0xa9, 0x01, // lda #$01
+        0x03, 0x12, // slo ($12,x) (undocumented)
+        0xea, // nop
+    ];
+
+    // marked with '*'
+    let v = disasm::disassemble(&block, 0x400, CpuType::MOS6502);
+    assert_eq!(v.len(), 3);
+    assert!(v[1].is_undocumented && !v[1].is_data());
+    assert_eq!(v[1].mnemonic_text(), "*SLO");
+    assert_eq!(
+        v[1].to_string(),
+        "$0402:\t03 12\t\t-->\t*SLO ($12, X)\t[Xin]"
+    );
+    assert_eq!(v[0].mnemonic_text(), "LDA");
+
+    // stop before
+    let opts = DisasmOptions {
+        stop_on_undocumented: true,
+        ..Default::default()
+    };
+    let v = disasm::disassemble_with_options(&block, 0x400, CpuType::MOS6502, &opts);
+    assert_eq!(v.len(), 1);
+
+    // as data bytes, $12 is an undocumented jam too
+    let opts = DisasmOptions {
+        bytes_for_undocumented: true,
+        ..Default::default()
+    };
+    let v = disasm::disassemble_with_options(&block, 0x400, CpuType::MOS6502, &opts);
+    let text: Vec<String> = v.iter().map(|i| i.to_string()).collect();
+    assert_eq!(
+        text,
+        vec![
+            "$0400:\ta9 01\t\t-->\tLDA #$01\t[Imm, tgt=$0401]",
+            "$0402:\t03\t\t-->\t.byte $03",
+            "$0403:\t12\t\t-->\t.byte $12",
+            "$0404:\tea\t\t-->\tNOP\t\t[Imp]",
+        ]
+    );
+    assert!(v[1].is_data() && v[2].is_data());
+    let bytes: Vec<u8> = v.iter().flat_map(|i| i.bytes.clone()).collect();
+    assert_eq!(bytes, block);
+
+    // through the debugger
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &block).unwrap();
+    let opts = DisasmOptions {
+        stop_on_undocumented: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        d.disassemble_with_options(&mut c, 0x400, 3, &opts)
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(d.disassemble(&mut c, 0x400, 3).unwrap()[1].is_undocumented);
+    assert!(d.parse_cmd(&mut c, "d 3 $400 stop").1);
+    assert!(d.parse_cmd(&mut c, "d 3 bytes $400").1);
+    assert!(!d.parse_cmd(&mut c, "d 3 $400 zz").1);
+}
+
+#[test]
+fn decode_at() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    c.regs.x = 0x04;
+    c.regs.y = 0x10;
+    d.write_memory(&mut c, 0x24, &[0x00, 0x20]).unwrap();
+    d.write_memory(&mut c, 0x30, &[0x00, 0x30]).unwrap();
+    d.write_memory(&mut c, 0x3ff, &[0x34, 0x12]).unwrap();
+    d.write_memory(&mut c, 0x300, &[0x78, 0x56]).unwrap();
+    d.write_memory(&mut c, 0x304, &[0xcd, 0xab]).unwrap();
+    let cases: [(&[u8], u8, u8, Option<u16>); 14] = [
+        (&[0xea], 1, 2, None),
+        (&[0x0a], 1, 2, None),
+        (&[0xa9, 0x01], 2, 2, None),
+        (&[0xa5, 0x10], 2, 3, Some(0x10)),
+        (&[0xb5, 0xfe], 2, 4, Some(0x02)),
+        (&[0xb6, 0x10], 2, 4, Some(0x20)),
+        (&[0xad, 0x00, 0x02], 3, 4, Some(0x0200)),
+        (&[0xbd, 0xff, 0x02], 3, 4, Some(0x0303)),
+        (&[0x99, 0x00, 0x02], 3, 5, Some(0x0210)),
+        (&[0xa1, 0x20], 2, 6, Some(0x2000)),
+        (&[0xb1, 0x30], 2, 5, Some(0x3010)),
+        (&[0xd0, 0xfe], 2, 2, Some(0x1000)),
+        (&[0x20, 0x00, 0xc0], 3, 6, Some(0xc000)),
+        // the 6502 bug takes the msb from $0300
+        (&[0x6c, 0xff, 0x03], 3, 5, Some(0x7834)),
+    ];
+    for (bytes, size, cycles, ea) in cases.iter() {
+        d.write_memory(&mut c, 0x1000, bytes).unwrap();
+        let i = c.decode_at(0x1000).unwrap();
+        assert_eq!(i.opcode, bytes[0]);
+        assert_eq!((i.size, i.base_cycles), (*size, *cycles), "{:x?}", bytes);
+        assert_eq!(i.operand_bytes, bytes[1..]);
+        assert_eq!(i.effective_address, *ea, "{:x?}", bytes);
+    }
+
+    // 65c02
+    c.set_cpu_type(CpuType::WDC65C02);
+    let cases: [(&[u8], &str, AddressingModeId, Option<u16>); 4] = [
+        (
+            &[0x6c, 0xff, 0x03],
+            "jmp",
+            AddressingModeId::Ind,
+            Some(0x1234),
+        ),
+        (
+            &[0x7c, 0x00, 0x03],
+            "jmp",
+            AddressingModeId::Aix,
+            Some(0xabcd),
+        ),
+        (&[0xb2, 0x24], "lda", AddressingModeId::Izp, Some(0x2000)),
+        (
+            &[0x0f, 0x10, 0x02],
+            "bbr0",
+            AddressingModeId::Zpr,
+            Some(0x10),
+        ),
+    ];
+    for (bytes, mnemonic, mode, ea) in cases.iter() {
+        d.write_memory(&mut c, 0x1000, bytes).unwrap();
+        let i = c.decode_at(0x1000).unwrap();
+        assert_eq!((i.mnemonic, i.mode), (*mnemonic, *mode));
+        assert_eq!(i.effective_address, *ea, "{:x?}", bytes);
+    }
+
+    // the instruction must fit, the effective address may not
+    d.write_memory(&mut c, 0xfffe, &[0xad, 0x00]).unwrap();
+    assert!(c.decode_at(0xfffe).is_err());
+    let mut c = Cpu::new(
+        rv6502emu::bus::new_default(memory::new_sized(0x1000)),
+        None,
+        None,
+    );
+    d.write_memory(&mut c, 0x400, &[0xad, 0x00, 0x20]).unwrap();
+    assert_eq!(c.decode_at(0x400).unwrap().effective_address, Some(0x2000));
+    d.write_memory(&mut c, 0x400, &[0x6c, 0x00, 0x20]).unwrap();
+    assert_eq!(c.decode_at(0x400).unwrap().effective_address, None);
+}
+
+/**
+ * a device counting the reads, with side effects.
+ */
+struct Counter(Arc<Mutex<usize>>);
+
+impl Device for Counter {
+    fn read(&mut self, _address: u16) -> Result<u8, CpuError> {
+        *self.0.lock().unwrap() += 1;
+        Ok(0xff)
+    }
+
+    fn write(&mut self, _address: u16, _v: u8) {}
+}
+
+#[test]
+fn decode_at_no_side_effects() {
+    let reads = Arc::new(Mutex::new(0));
+    let bus = BusBuilder::new(memory::new_with_policy(
+        0x10000,
+        MemoryInitPolicy::Random { seed: 0x6502 },
+    ))
+    .map(0x8000..=0x80ff, Box::new(Counter(reads.clone())))
+    .build();
+    let mut c = Cpu::new(bus, None, None);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cc = calls.clone();
+    c.set_hook(move |_ctx: &CpuCallbackContext, _cpu: &mut CpuView| {
+        cc.fetch_add(1, Ordering::Relaxed);
+    });
+
+    // registers, cycles and memory
+    let checksum = |c: &mut Cpu| {
+        let mut v = c.bus.dump_range(0, 0x10000).unwrap();
+        v.extend_from_slice(&[c.regs.a, c.regs.x, c.regs.y, c.regs.s, c.regs.p.bits()]);
+        v.extend_from_slice(&c.regs.pc.to_le_bytes());
+        v.extend_from_slice(&c.cycles.to_le_bytes());
+        v
+    };
+    let mut seed: u32 = 0x1234_5678;
+    let mut random = move || {
+        // xorshift
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed
+    };
+    for t in [CpuType::MOS6502, CpuType::WDC65C02].iter() {
+        c.set_cpu_type(*t);
+        for _ in 0..500 {
+            let r = random();
+            c.regs.x = r as u8;
+            c.regs.y = (r >> 8) as u8;
+            let before = checksum(&mut c);
+            let address = (random() % 0xfffe) as u16;
+            let i = c.decode_at(address).unwrap();
+            assert_eq!(i.size as usize, 1 + i.operand_bytes.len());
+            assert_eq!(checksum(&mut c), before);
+        }
+
+        // the whole device range too
+        for address in 0x7ffe..=0x8100 {
+            let _ = c.decode_at(address).unwrap();
+        }
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 0);
+    assert_eq!(*reads.lock().unwrap(), 0);
+}
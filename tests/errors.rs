@@ -0,0 +1,78 @@
+/*
+ * Filename: /tests/errors.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:05:13
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! CpuError contents and conversions.
+
+use rv6502emu::cpu::cpu_error::{CpuError, CpuErrorType};
+use rv6502emu::cpu::{AddressingModeId, Cpu};
+use rv6502emu::memory;
+use std::error::Error;
+
+#[test]
+fn absolute_read_out_of_bounds() {
+    // lda $xxxx at $ffff, the operand is past the end of memory
+    let mut c = Cpu::new_default(None);
+    c.bus.get_memory().write_byte(0xffff, 0xad).unwrap();
+    let e = c.disassemble_at(0xffff).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::MemoryRead);
+    assert_eq!((e.address, e.access_size, e.mem_size), (0xffff, 3, 0x10000));
+    assert_eq!(
+        (e.opcode, e.mode),
+        (Some(0xad), Some(AddressingModeId::Abs))
+    );
+    assert_eq!(
+        e.to_string(),
+        "Error (MemRead) at address=$ffff, access size=3, max memory size=$10000 (65536), opcode=$ad, mode=Abs"
+    );
+
+    // a plain memory access knows nothing about opcodes
+    let mut m = memory::new_default();
+    let e = m.read_word_le(0xffff).unwrap_err();
+    assert_eq!((e.address, e.access_size), (0xffff, 2));
+    assert_eq!((e.opcode, e.mode), (None, None));
+    assert!(!e.to_string().contains("opcode"));
+}
+
+/**
+ * loads a missing file, to check the conversions.
+ */
+fn load_missing() -> Result<(), Box<dyn Error>> {
+    let mut m = memory::new_default();
+    m.load("/nonexistent/rv6502emu.bin", 0x400)?;
+    Ok(())
+}
+
+#[test]
+fn std_error() {
+    let e = load_missing().unwrap_err();
+    let e = e.downcast_ref::<CpuError>().unwrap();
+    assert_eq!(e.t, CpuErrorType::MemoryLoad);
+    assert!(e.msg.is_some());
+}
@@ -0,0 +1,159 @@
+/*
+ * Filename: /tests/gdbstub.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:31:45
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the gdb remote serial protocol stub, speaking raw RSP over a localhost socket (needs the gdbstub feature).
+
+use rv6502emu::cpu::debugger::gdbstub::GdbStub;
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, ResetKind};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+
+/**
+ * a minimal gdb client.
+ */
+struct Client(TcpStream);
+
+impl Client {
+    /**
+     * read a byte.
+     */
+    fn byte(&mut self) -> u8 {
+        let mut b = [0u8; 1];
+        self.0.read_exact(&mut b).unwrap();
+        b[0]
+    }
+
+    /**
+     * send a packet, returns the reply.
+     */
+    fn cmd(&mut self, data: &str) -> String {
+        let sum = data.bytes().fold(0u8, |acc, b| acc.wrapping_add(b));
+        write!(self.0, "${}#{:02x}", data, sum).unwrap();
+        assert_eq!(self.byte(), b'+');
+        self.reply()
+    }
+
+    /**
+     * read a reply packet, checking and acknowledging it.
+     */
+    fn reply(&mut self) -> String {
+        assert_eq!(self.byte(), b'$');
+        let mut data = Vec::new();
+        loop {
+            match self.byte() {
+                b'#' => break,
+                b => data.push(b),
+            }
+        }
+        let cs = [self.byte(), self.byte()];
+        let sum = data.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+        assert_eq!(
+            u8::from_str_radix(std::str::from_utf8(&cs).unwrap(), 16).unwrap(),
+            sum
+        );
+        self.0.write_all(b"+").unwrap();
+        String::from_utf8(data).unwrap()
+    }
+}
+
+#[test]
+fn rsp_session() {
+    let mut stub = GdbStub::listen("127.0.0.1:0").unwrap();
+    let addr = stub.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        // lda #$01, inx, sta $0200, jmp $0402
+        let mut c = Cpu::new_default(None);
+        let mut d = Debugger::new(false);
+        d.write_memory(
+            &mut c,
+            0x400,
+            &[0xa9, 0x01, 0xe8, 0x8d, 0x00, 0x02, 0x4c, 0x02, 0x04],
+        )
+        .unwrap();
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        stub.serve(&mut c, &mut d).unwrap();
+        d.list_breakpoints().len()
+    });
+    let mut g = Client(TcpStream::connect(addr).unwrap());
+    g.0.set_nodelay(true).unwrap();
+
+    // registers: a, x, y, s, p, pc (little endian)
+    assert!(g.cmd("qSupported:multiprocess+").starts_with("PacketSize="));
+    assert_eq!(g.cmd("?"), "S05");
+    assert_eq!(g.cmd("g"), "000000ff240004");
+    assert_eq!(g.cmd("s"), "S05");
+    assert_eq!(g.cmd("p5"), "0204");
+    assert_eq!(g.cmd("p0"), "01");
+    assert_eq!(g.cmd("P1=41"), "OK");
+    assert_eq!(g.cmd("g"), "014100ff240204");
+    assert_eq!(g.cmd("G424100ff240204"), "OK");
+    assert_eq!(g.cmd("p0"), "42");
+    assert_eq!(g.cmd("p9"), "E01");
+
+    // memory
+    assert_eq!(g.cmd("m400,3"), "a901e8");
+    assert_eq!(g.cmd("M200,2:aabb"), "OK");
+    assert_eq!(g.cmd("m200,2"), "aabb");
+    assert_eq!(g.cmd("M200,2:aa"), "E01");
+    assert_eq!(g.cmd("mffff,2"), "E01");
+    assert_eq!(g.cmd("m10000,1"), "E01");
+    assert_eq!(g.cmd("m0,ffffffffffffffff"), "E01");
+
+    // exec breakpoint
+    assert_eq!(g.cmd("Z0,406,1"), "OK");
+    assert_eq!(g.cmd("c"), "S05");
+    assert_eq!(g.cmd("p5"), "0604");
+    assert_eq!(g.cmd("g"), "424200ff240604");
+    assert_eq!(g.cmd("z0,406,1"), "OK");
+    assert_eq!(g.cmd("z0,406,1"), "E01");
+
+    // write watchpoint, sta has not executed
+    assert_eq!(g.cmd("Z2,200,1"), "OK");
+    assert_eq!(g.cmd("c"), "T05watch:0200;");
+    assert_eq!(g.cmd("p5"), "0304");
+    assert_eq!(g.cmd("z2,200,1"), "OK");
+
+    // unsupported
+    assert_eq!(g.cmd("Z5,400,1"), "");
+    assert_eq!(g.cmd("vMustReplyEmpty"), "");
+
+    // running forever, interrupted by ctrl-c
+    assert_eq!(g.cmd("Z4,300,10"), "OK");
+    write!(g.0, "$c#63").unwrap();
+    assert_eq!(g.byte(), b'+');
+    g.0.write_all(&[0x03]).unwrap();
+    assert_eq!(g.reply(), "S02");
+
+    // detach
+    assert_eq!(g.cmd("D"), "OK");
+    assert_eq!(server.join().unwrap(), 1);
+}
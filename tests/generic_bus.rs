@@ -0,0 +1,326 @@
+/*
+ * Filename: /tests/generic_bus.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 19:12:40
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for cpus monomorphized on the bus type.
+
+use rv6502emu::bus::{Bus, BusBuilder, DefaultBus, Device};
+use rv6502emu::cpu::cpu_error::CpuError;
+use rv6502emu::cpu::debugger::{BreakpointType, Debugger};
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuOperation, CpuView, DynCpu, ResetKind, StopReason,
+};
+use rv6502emu::memory::{self, Memory};
+use std::sync::{Arc, Mutex};
+
+/**
+ * a user bus, counting how many times the memory is requested.
+ */
+struct CountingBus {
+    m: Box<dyn Memory>,
+    accesses: usize,
+}
+
+impl Bus for CountingBus {
+    fn get_memory(&mut self) -> &mut Box<dyn Memory> {
+        self.accesses += 1;
+        &mut self.m
+    }
+}
+
+/**
+ * lda #$11, sta $0200, pha, nop at $400.
+ */
+fn load_prg<B: Bus>(c: &mut Cpu<B>, d: &Debugger) {
+    d.write_memory(c, 0x400, &[0xa9, 0x11, 0x8d, 0x00, 0x02, 0x48, 0xea])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+}
+
+fn stop_at_nop(c: &mut Cpu<CountingBus>, cb: CpuCallbackContext) {
+    if cb.operation == CpuOperation::Exec && c.bus.m.read_byte(c.regs.pc as usize).unwrap() == 0xea
+    {
+        c.done = true;
+    }
+}
+
+#[test]
+fn same_as_dyn() {
+    let d = Debugger::new(false);
+    let mut dyn_c: DynCpu = Cpu::new_default(None);
+    load_prg(&mut dyn_c, &d);
+    let mut c = Cpu::new(DefaultBus::new(memory::new_default()), None, None);
+    load_prg(&mut c, &d);
+
+    let r = c.run_until(None, 0, 4).unwrap();
+    assert_eq!(r, dyn_c.run_until(None, 0, 4).unwrap());
+    assert_eq!(c.regs, dyn_c.regs);
+    assert_eq!(c.cycles, dyn_c.cycles);
+    assert_eq!(d.read_memory(&mut c, 0x200, 1).unwrap(), [0x11]);
+    assert_eq!(d.read_memory(&mut c, 0x1ff, 1).unwrap(), [0x11]);
+}
+
+#[test]
+fn user_bus_with_hooks_and_debugger() {
+    let bus = CountingBus {
+        m: memory::new_default(),
+        accesses: 0,
+    };
+    let mut c = Cpu::new(bus, Some(stop_at_nop), None);
+    let mut d = Debugger::new(false);
+    load_prg(&mut c, &d);
+    c.run(None, 0).unwrap();
+    assert_eq!(c.regs.pc, 0x406);
+    assert!(c.bus.accesses > 0);
+
+    // closure hooks get a view on the same bus type
+    let mut writes = 0;
+    c.set_hook(
+        move |ctx: &CpuCallbackContext, cpu: &mut CpuView<CountingBus>| {
+            if ctx.operation == CpuOperation::Write {
+                writes += 1;
+                if writes == 2 {
+                    cpu.stop();
+                }
+            }
+        },
+    );
+    c.done = false;
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run(None, 0).unwrap();
+    assert_eq!(c.regs.pc, 0x406);
+
+    // debugger breakpoints
+    c.clear_hook();
+    c.done = false;
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    d.add_breakpoint(&mut c, 0x405, BreakpointType::EXEC, None)
+        .unwrap();
+    let r = c.run_until(Some(&mut d), 0, 0).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(0));
+    assert_eq!(c.regs.pc, 0x405);
+}
+
+/**
+ * a device recording the accesses, as ('r'|'w', address).
+ */
+struct Recorder(Arc<Mutex<Vec<(char, u16)>>>);
+
+impl Device for Recorder {
+    fn read(&mut self, address: u16) -> Result<u8, CpuError> {
+        self.0.lock().unwrap().push(('r', address));
+        Ok(0)
+    }
+
+    fn write(&mut self, address: u16, _v: u8) {
+        self.0.lock().unwrap().push(('w', address));
+    }
+}
+
+/**
+ * runs prg at $0400 with the recorder mapped at $1200-$13ff, returns the recorded accesses.
+ */
+fn bus_accesses(prg: &[u8], x: u8, y: u8, accurate: bool) -> Vec<(char, u16)> {
+    let accesses = Arc::new(Mutex::new(Vec::new()));
+    let bus = BusBuilder::new(memory::new_default())
+        .map(0x1200..=0x13ff, Box::new(Recorder(accesses.clone())))
+        .build();
+    let mut c = Cpu::new(bus, None, None);
+    c.set_accurate_bus(accurate);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+
+    // pointer for (zp),y
+    d.write_memory(&mut c, 0x10, &[0xf0, 0x12]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.x = x;
+    c.regs.y = y;
+    c.run_until(None, 0, 1).unwrap();
+    let v = accesses.lock().unwrap().clone();
+    v
+}
+
+#[test]
+fn accurate_bus_dummy_reads() {
+    // lda $12f0,x crossing the page, reads $1210 first
+    let lda_abx = [0xbd, 0xf0, 0x12];
+    assert_eq!(
+        bus_accesses(&lda_abx, 0x20, 0, true),
+        vec![('r', 0x1210), ('r', 0x1310)]
+    );
+    assert_eq!(bus_accesses(&lda_abx, 0x20, 0, false), vec![('r', 0x1310)]);
+
+    // not crossing
+    assert_eq!(bus_accesses(&lda_abx, 0x05, 0, true), vec![('r', 0x12f5)]);
+
+    // lda ($10),y and lda $12f0,y
+    assert_eq!(
+        bus_accesses(&[0xb1, 0x10], 0, 0x20, true),
+        vec![('r', 0x1210), ('r', 0x1310)]
+    );
+    assert_eq!(
+        bus_accesses(&[0xb9, 0xf0, 0x12], 0, 0x20, true),
+        vec![('r', 0x1210), ('r', 0x1310)]
+    );
+
+    // sta $1200,y always reads first
+    assert_eq!(
+        bus_accesses(&[0x99, 0x00, 0x12], 0, 0x10, true),
+        vec![('r', 0x1210), ('w', 0x1210)]
+    );
+    assert_eq!(
+        bus_accesses(&[0x99, 0x00, 0x12], 0, 0x10, false),
+        vec![('w', 0x1210)]
+    );
+
+    // inc $12f0,x: one dummy read, then the read-modify-write accesses
+    assert_eq!(
+        bus_accesses(&[0xfe, 0xf0, 0x12], 0x20, 0, true),
+        vec![('r', 0x1210), ('r', 0x1310), ('w', 0x1310), ('w', 0x1310)]
+    );
+}
+
+/**
+ * a register cleared when read (i.e. an interrupt status), which can be peeked.
+ */
+struct Latch(u8);
+
+impl Device for Latch {
+    fn read(&mut self, _address: u16) -> Result<u8, CpuError> {
+        let v = self.0;
+        self.0 = 0;
+        Ok(v)
+    }
+
+    fn write(&mut self, _address: u16, v: u8) {
+        self.0 = v;
+    }
+
+    fn peek(&self, _address: u16) -> Option<u8> {
+        Some(self.0)
+    }
+}
+
+#[test]
+fn debugger_peeks() {
+    let accesses = Arc::new(Mutex::new(Vec::new()));
+    let bus = BusBuilder::new(memory::new_default())
+        .map(0xc000..=0xc00e, Box::new(Recorder(accesses.clone())))
+        .map(0xc00f..=0xc00f, Box::new(Latch(0x81)))
+        .build();
+    let mut c = Cpu::new(bus, None, None);
+    let reads = Arc::new(Mutex::new(0));
+    let r = reads.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+        if ctx.operation == CpuOperation::Read {
+            *r.lock().unwrap() += 1;
+        }
+    });
+    let mut d = Debugger::new(false);
+    c.bus.poke(0x10, 0x42).unwrap();
+    assert_eq!(c.bus.peek(0x10).unwrap(), 0x42);
+
+    // dumping and disassembling the i/o page has no side effects, the latch is peeked and the recorder shows the ram
+    assert!(d.parse_cmd(&mut c, "x 16 $c000").1);
+    assert!(d.parse_cmd(&mut c, "d 2 $c000").1);
+    assert_eq!(d.read_memory(&mut c, 0xc00e, 2).unwrap(), vec![0x00, 0x81]);
+    assert_eq!(c.bus.peek(0xc00f).unwrap(), 0x81);
+    assert_eq!(*reads.lock().unwrap(), 0);
+    assert!(accesses.lock().unwrap().is_empty());
+
+    // the cpu reads it for real
+    d.write_memory(&mut c, 0x400, &[0xad, 0x0f, 0xc0]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.regs.a, 0x81);
+    assert_eq!(c.bus.peek(0xc00f).unwrap(), 0);
+    assert!(*reads.lock().unwrap() > 0);
+}
+
+/**
+ * four registers returning the complement of what was written, logging ('r'|'w', address, value).
+ */
+struct ToyDevice {
+    regs: [u8; 4],
+    log: Arc<Mutex<Vec<(char, u16, u8)>>>,
+}
+
+impl Device for ToyDevice {
+    fn read(&mut self, address: u16) -> Result<u8, CpuError> {
+        let v = !self.regs[(address & 3) as usize];
+        self.log.lock().unwrap().push(('r', address, v));
+        Ok(v)
+    }
+
+    fn write(&mut self, address: u16, v: u8) {
+        self.regs[(address & 3) as usize] = v;
+        self.log.lock().unwrap().push(('w', address, v));
+    }
+}
+
+#[test]
+fn toy_device() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let bus = BusBuilder::new(memory::new_default())
+        .map(
+            0xd000..=0xd003,
+            Box::new(ToyDevice {
+                regs: [0; 4],
+                log: log.clone(),
+            }),
+        )
+        .build();
+    let mut c = Cpu::new(bus, None, None);
+    let d = Debugger::new(false);
+
+    // lda #$5a, sta $d001, ldx $d001, sta $d004, ldy $d004, stx $0200
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[
+            0xa9, 0x5a, 0x8d, 0x01, 0xd0, 0xae, 0x01, 0xd0, 0x8d, 0x04, 0xd0, 0xac, 0x04, 0xd0,
+            0x8e, 0x00, 0x02,
+        ],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 6).unwrap();
+
+    // the device saw the store and the load, and nothing else
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![('w', 0xd001, 0x5a), ('r', 0xd001, 0xa5)]
+    );
+    assert_eq!((c.regs.x, c.regs.y), (0xa5, 0x5a));
+
+    // the ram below the device is untouched, past it and elsewhere the accesses went to ram
+    assert_eq!(c.bus.peek(0xd001).unwrap(), 0);
+    assert_eq!(c.bus.peek(0xd004).unwrap(), 0x5a);
+    assert_eq!(c.bus.peek(0x200).unwrap(), 0xa5);
+}
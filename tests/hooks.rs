@@ -0,0 +1,296 @@
+/*
+ * Filename: /tests/hooks.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 17:02:44
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the cpu hooks.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuOperation, CpuType, CpuView, HookFilter, ResetKind,
+};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn closure_records_writes() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+
+    // lda #$11, sta $0200, ldx #$22, stx $0201, pha, nop
+    let prg = [
+        0xa9, 0x11, 0x8d, 0x00, 0x02, 0xa2, 0x22, 0x8e, 0x01, 0x02, 0x48, 0xea,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    let writes: Arc<Mutex<Vec<(u16, u8)>>> = Arc::new(Mutex::new(Vec::new()));
+    let w = writes.clone();
+    let mut execs = 0;
+    c.set_hook(move |ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+        match ctx.operation {
+            CpuOperation::Write => w.lock().unwrap().push((ctx.address, ctx.value)),
+            CpuOperation::Exec => {
+                execs += 1;
+                // the view gives access to registers and memory
+                assert_eq!(cpu.regs().pc, ctx.address);
+                if execs == 6 {
+                    assert_eq!(cpu.read_byte(0x201).unwrap(), 0x22);
+                    cpu.stop();
+                }
+            }
+            _ => (),
+        }
+    });
+    c.run(None, 0).unwrap();
+    assert_eq!(c.regs.pc, 0x40b);
+    assert_eq!(
+        *writes.lock().unwrap(),
+        vec![(0x200, 0x11), (0x201, 0x22), (0x1ff, 0x11)]
+    );
+
+    // no more notifications once cleared
+    c.clear_hook();
+    c.done = false;
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run(None, 16).unwrap();
+    assert_eq!(writes.lock().unwrap().len(), 3);
+}
+
+fn stop_at_nop(c: &mut Cpu, cb: CpuCallbackContext) {
+    if cb.operation == CpuOperation::Exec
+        && c.bus.get_memory().read_byte(c.regs.pc as usize).unwrap() == 0xea
+    {
+        c.done = true;
+    }
+}
+
+#[test]
+fn fn_callback_compat() {
+    let mut c = Cpu::new_default(Some(stop_at_nop));
+    let d = Debugger::new(false);
+
+    // lda #$11, nop
+    d.write_memory(&mut c, 0x400, &[0xa9, 0x11, 0xea]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run(None, 0).unwrap();
+    assert_eq!(c.regs.pc, 0x402);
+}
+
+#[test]
+fn vector_fetch_override() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+
+    // brk, the vector points to $2000
+    d.write_memory(&mut c, 0x400, &[0x00]).unwrap();
+    d.write_memory(&mut c, 0xfffe, &[0x00, 0x20]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // the hook maps the vector to $3000
+    let fetches: Arc<Mutex<Vec<u16>>> = Arc::new(Mutex::new(Vec::new()));
+    let f = fetches.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+        if ctx.operation == CpuOperation::VectorFetch {
+            f.lock().unwrap().push(ctx.address);
+            if ctx.address == 0xffff {
+                cpu.override_read(0x30);
+            }
+        }
+    });
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.regs.pc, 0x3000);
+    assert_eq!(*fetches.lock().unwrap(), vec![0xfffe, 0xffff]);
+
+    // reset fetches through the hook too
+    d.write_memory(&mut c, 0xfffc, &[0x34, 0x12]).unwrap();
+    c.reset(None, ResetKind::PowerOn).unwrap();
+    assert_eq!(c.regs.pc, 0x1234);
+    assert_eq!(fetches.lock().unwrap()[2..], [0xfffc, 0xfffd]);
+}
+
+/**
+ * runs asl $2000 with $81 at $2000, returns the (operation, value) accesses to $2000.
+ */
+fn rmw_accesses(t: CpuType, dummy: bool) -> Vec<(CpuOperation, u8)> {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    c.set_rmw_dummy_access(dummy);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0x0e, 0x00, 0x20]).unwrap();
+    d.write_memory(&mut c, 0x2000, &[0x81]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    let accesses: Arc<Mutex<Vec<(CpuOperation, u8)>>> = Arc::new(Mutex::new(Vec::new()));
+    let a = accesses.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+        if ctx.address == 0x2000 {
+            a.lock().unwrap().push((ctx.operation, ctx.value));
+        }
+    });
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(d.read_memory(&mut c, 0x2000, 1).unwrap(), vec![0x02]);
+    let v = accesses.lock().unwrap().clone();
+    v
+}
+
+#[test]
+fn rmw_dummy_access() {
+    use CpuOperation::{Read, Write};
+
+    // nmos writes the unmodified value back first
+    assert_eq!(
+        rmw_accesses(CpuType::MOS6502, true),
+        vec![(Read, 0x81), (Write, 0x81), (Write, 0x02)]
+    );
+
+    // 65c02 reads twice
+    assert_eq!(
+        rmw_accesses(CpuType::WDC65C02, true),
+        vec![(Read, 0x81), (Read, 0x81), (Write, 0x02)]
+    );
+
+    // disabled
+    assert_eq!(
+        rmw_accesses(CpuType::MOS6502, false),
+        vec![(Read, 0x81), (Write, 0x02)]
+    );
+}
+
+#[test]
+fn filtered_hooks() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+
+    // lda #$11, sta $10, sta $d020, lda $d021, nop
+    let prg = [
+        0xa9, 0x11, 0x85, 0x10, 0x8d, 0x20, 0xd0, 0xad, 0x21, 0xd0, 0xea,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    d.write_memory(&mut c, 0xd021, &[0x33]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // an i/o write hook, which never sees the zeropage store
+    let events: Arc<Mutex<Vec<(&str, CpuOperation, u16)>>> = Arc::new(Mutex::new(Vec::new()));
+    let e = events.clone();
+    let io = c.add_hook(
+        HookFilter {
+            operations: vec![CpuOperation::Write],
+            range: Some((0xd000, 0xdfff)),
+        },
+        move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+            e.lock().unwrap().push(("io", ctx.operation, ctx.address));
+        },
+    );
+
+    // two read hooks on the same range, the first override wins
+    let e = events.clone();
+    let read_filter = HookFilter {
+        operations: vec![CpuOperation::Read],
+        range: Some((0xd000, 0xdfff)),
+    };
+    c.add_hook(
+        read_filter.clone(),
+        move |ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+            e.lock()
+                .unwrap()
+                .push(("first", ctx.operation, ctx.address));
+            cpu.override_read(0x44);
+        },
+    );
+    let e = events.clone();
+    let second = c.add_hook(
+        read_filter,
+        move |ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+            e.lock()
+                .unwrap()
+                .push(("second", ctx.operation, ctx.address));
+            cpu.override_read(0x55);
+        },
+    );
+    assert_ne!(io, second);
+
+    c.run_until(None, 0, 4).unwrap();
+    assert_eq!(c.regs.a, 0x44);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            ("io", CpuOperation::Write, 0xd020),
+            ("first", CpuOperation::Read, 0xd021),
+            ("second", CpuOperation::Read, 0xd021),
+        ]
+    );
+
+    // removed hooks are not notified anymore, and can't be removed twice
+    c.remove_hook(io).unwrap();
+    c.remove_hook(second).unwrap();
+    assert!(c.remove_hook(io).is_err());
+    events.lock().unwrap().clear();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 4).unwrap();
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![("first", CpuOperation::Read, 0xd021)]
+    );
+}
+
+#[test]
+fn exec_context() {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+
+    // ldx #$05, sta $d020,x, bne $0400 (taken), nop
+    d.write_memory(&mut c, 0x400, &[0xa2, 0x05, 0x9d, 0x20, 0xd0, 0xd0, 0xf9])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    let events: Arc<Mutex<Vec<(u16, Option<u8>, Option<&str>, Option<u16>)>>> =
+        Arc::new(Mutex::new(Vec::new()));
+    let e = events.clone();
+    c.add_hook(
+        HookFilter {
+            operations: vec![CpuOperation::Exec],
+            range: None,
+        },
+        move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+            e.lock()
+                .unwrap()
+                .push((ctx.address, ctx.opcode, ctx.mnemonic, ctx.target));
+        },
+    );
+    c.run_until(None, 0, 3).unwrap();
+
+    // the target is reported before the store happens, with X applied
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            (0x400, Some(0xa2), Some("ldx"), None),
+            (0x402, Some(0x9d), Some("sta"), Some(0xd025)),
+            (0x405, Some(0xd0), Some("bne"), Some(0x400)),
+        ]
+    );
+}
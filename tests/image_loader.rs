@@ -0,0 +1,141 @@
+/*
+ * Filename: /tests/image_loader.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 11:40:12
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Intel HEX and Motorola S-record loader tests.
+
+use rv6502emu::cpu::cpu_error::CpuErrorType;
+use rv6502emu::memory;
+use rv6502emu::memory::Memory;
+use std::env;
+use std::fs;
+
+/**
+ * writes content to a temporary file and returns its path.
+ */
+fn tmp_file(name: &str, content: &str) -> String {
+    let p = env::temp_dir().join(format!("rv6502emu_{}_{}", std::process::id(), name));
+    fs::write(&p, content).unwrap();
+    String::from(p.to_str().unwrap())
+}
+
+/**
+ * reads len bytes starting at address.
+ */
+fn range(m: &dyn Memory, address: usize, len: usize) -> Vec<u8> {
+    let mut v = vec![0; len];
+    m.read_range(address, &mut v).unwrap();
+    v
+}
+
+#[test]
+fn ihex_segments_with_gap() {
+    // 3 bytes at $0400, 2 bytes at $0800, start linear address $0400
+    let p = tmp_file(
+        "gap.hex",
+        ":03040000A9018DC2\n:02080000EAEA22\n:0400000500000400F3\n:00000001FF\n",
+    );
+    let mut m = memory::new_default();
+    let (n, entry) = m.load_ihex(&p).unwrap();
+    assert_eq!(n, 5);
+    assert_eq!(entry, Some(0x400));
+    assert_eq!(range(m.as_ref(), 0x400, 3), &[0xa9, 0x01, 0x8d]);
+    assert_eq!(range(m.as_ref(), 0x403, 0x3fd), &[0; 0x3fd][..]);
+    assert_eq!(range(m.as_ref(), 0x800, 2), &[0xea, 0xea]);
+}
+
+#[test]
+fn ihex_checksum_failure() {
+    let p = tmp_file(
+        "bad.hex",
+        ":03040000A9018DC2\n:02080000EAEA23\n:00000001FF\n",
+    );
+    let mut m = memory::new_default();
+    let e = m.load_ihex(&p).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::MemoryLoad);
+    assert!(e.msg.unwrap().contains(":2: checksum mismatch"));
+
+    // nothing is written on error
+    assert_eq!(range(m.as_ref(), 0x400, 1)[0], 0);
+}
+
+#[test]
+fn ihex_64k_boundary() {
+    // ends exactly at $ffff
+    let p = tmp_file("end.hex", ":02FFFE00FCFF06\n:00000001FF\n");
+    let mut m = memory::new_default();
+    assert_eq!(m.load_ihex(&p).unwrap(), (2, None));
+    assert_eq!(m.read_word_le(0xfffe).unwrap(), 0xfffc);
+
+    // crosses $ffff
+    let p = tmp_file("cross.hex", ":03FFFE00FCFF0005\n:00000001FF\n");
+    let e = m.load_ihex(&p).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::MemoryLoad);
+
+    // above 64k via extended linear address
+    let p = tmp_file("ext.hex", ":020000040001F9\n:01000000EA15\n:00000001FF\n");
+    assert!(m.load_ihex(&p).is_err());
+}
+
+#[test]
+fn srec_segments_and_entry() {
+    let p = tmp_file(
+        "prog.s19",
+        "S00600004844521B\nS1060400A9018DBE\nS1050800EAEA1E\nS5030002FA\nS9030400F8\n",
+    );
+    let mut m = memory::new_default();
+    let (n, entry) = m.load_srec(&p).unwrap();
+    assert_eq!(n, 5);
+    assert_eq!(entry, Some(0x400));
+    assert_eq!(range(m.as_ref(), 0x400, 3), &[0xa9, 0x01, 0x8d]);
+    assert_eq!(range(m.as_ref(), 0x800, 2), &[0xea, 0xea]);
+}
+
+#[test]
+fn srec_checksum_and_boundary() {
+    let p = tmp_file("bad.s19", "S1060400A9018DBF\n");
+    let mut m = memory::new_default();
+    let e = m.load_srec(&p).unwrap_err();
+    assert!(e.msg.unwrap().contains(":1: checksum mismatch"));
+
+    let p = tmp_file("cross.s19", "S106FFFEFCFF0001\n");
+    assert!(m.load_srec(&p).is_err());
+}
+
+#[test]
+fn image_autodetect() {
+    let mut m = memory::new_default();
+    let p = tmp_file("auto.hex", ":02080000EAEA22\n:00000001FF\n");
+    assert_eq!(m.load_image(&p, 0).unwrap(), (2, None));
+    let p = tmp_file("auto.s19", "S1050800EAEA1E\nS9030400F8\n");
+    assert_eq!(m.load_image(&p, 0).unwrap(), (2, Some(0x400)));
+    let p = tmp_file("auto.bin", "\u{1}\u{2}");
+    assert_eq!(m.load_image(&p, 0x1000).unwrap(), (2, None));
+    assert_eq!(m.read_word_le(0x1000).unwrap(), 0x0201);
+}
@@ -0,0 +1,573 @@
+/*
+ * Filename: /tests/interrupts.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 20:19:48
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! interrupts injected through the lines, with and without a debugger.
+
+use rv6502emu::cpu::debugger::{BpCondition, BreakpointType, Debugger, InterruptContext};
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuFlags, CpuOperation, CpuView, InterruptStats, ResetKind,
+    StopReason, Vectors,
+};
+use std::sync::{Arc, Mutex};
+
+/**
+ * cli, then inx forever at $0401. nmi handler at $2000 (lda #$42, rti), irq handler at $3000 (lda #$11, rti).
+ */
+fn setup() -> (Cpu, Debugger) {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0x58, 0xe8, 0x4c, 0x01, 0x04])
+        .unwrap();
+    d.write_memory(&mut c, 0x2000, &[0xa9, 0x42, 0x40]).unwrap();
+    d.write_memory(&mut c, 0x3000, &[0xa9, 0x11, 0x40]).unwrap();
+    d.write_memory(&mut c, 0xfffa, &[0x00, 0x20]).unwrap();
+    d.write_memory(&mut c, 0xfffe, &[0x00, 0x30]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    (c, d)
+}
+
+#[test]
+fn no_debugger() {
+    // nmi, serviced once
+    let (mut c, _) = setup();
+    c.run_until(None, 0, 3).unwrap();
+    c.trigger_nmi();
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a, c.regs.s), (0x2002, 0x42, 0xfc));
+    c.run_until(None, 0, 3).unwrap();
+    assert_eq!(c.regs.s, 0xff);
+    assert!(c.regs.pc >= 0x401 && c.regs.pc < 0x405);
+
+    // irq, level triggered
+    let (mut c, _) = setup();
+    c.run_until(None, 0, 3).unwrap();
+    c.set_irq_line(true);
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a, c.regs.s), (0x3002, 0x11, 0xfc));
+
+    // from a hook
+    let (mut c, _) = setup();
+    c.set_hook(|ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+        if ctx.operation == CpuOperation::Exec && ctx.address == 0x402 {
+            cpu.trigger_nmi();
+        }
+    });
+    c.run_until(None, 0, 4).unwrap();
+    assert_eq!(c.regs.a, 0x42);
+}
+
+/**
+ * counts the irq and nmi serviced.
+ */
+fn count_interrupts(c: &mut Cpu) -> Arc<Mutex<(usize, usize)>> {
+    let count = Arc::new(Mutex::new((0, 0)));
+    let cc = count.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+        let mut n = cc.lock().unwrap();
+        match ctx.operation {
+            CpuOperation::Irq => n.0 += 1,
+            CpuOperation::Nmi => n.1 += 1,
+            _ => (),
+        }
+    });
+    count
+}
+
+#[test]
+fn irq_held_reenters() {
+    let (mut c, _) = setup();
+    let count = count_interrupts(&mut c);
+    c.run_until(None, 0, 2).unwrap();
+
+    // irq and lda #$11, then rti: with the line still asserted the handler is entered again, for as long as it's held
+    c.set_irq_line(true);
+    for n in 1..=3 {
+        c.run_until(None, 0, 1).unwrap();
+        assert_eq!(c.regs.pc, 0x3002);
+        assert_eq!(*count.lock().unwrap(), (n, 0));
+        c.run_until(None, 0, 1).unwrap();
+        assert!(c.regs.pc >= 0x401 && c.regs.pc < 0x405);
+        assert!(!c.regs.interrupt_disable());
+    }
+
+    // released, the main loop goes on
+    c.set_irq_line(false);
+    c.run_until(None, 0, 10).unwrap();
+    assert_eq!(*count.lock().unwrap(), (3, 0));
+    assert!(c.regs.pc >= 0x401 && c.regs.pc < 0x405);
+}
+
+#[test]
+fn nmi_held_no_retrigger() {
+    let (mut c, _) = setup();
+    let count = count_interrupts(&mut c);
+    c.run_until(None, 0, 2).unwrap();
+
+    // held low, serviced once (lda #$42, rti)
+    c.set_nmi_line(true);
+    c.run_until(None, 0, 2).unwrap();
+    assert_eq!(*count.lock().unwrap(), (0, 1));
+    c.run_until(None, 0, 20).unwrap();
+    assert_eq!(*count.lock().unwrap(), (0, 1));
+
+    // pulsing it while the handler is running doesn't re-enter, the new edge is serviced after rti
+    c.set_nmi_line(false);
+    c.set_nmi_line(true);
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.regs.pc, 0x2002);
+    c.set_nmi_line(true);
+    c.run_until(None, 0, 1).unwrap();
+    assert!(c.regs.pc >= 0x401 && c.regs.pc < 0x405);
+    assert_eq!(*count.lock().unwrap(), (0, 2));
+
+    // a new edge
+    c.set_nmi_line(false);
+    c.set_nmi_line(true);
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.regs.pc, 0x2002);
+    assert_eq!(*count.lock().unwrap(), (0, 3));
+}
+
+#[test]
+fn warm_reset_keeps_lines() {
+    let (mut c, _) = setup();
+    let count = count_interrupts(&mut c);
+
+    // both lines asserted while I is set, the nmi edge is latched
+    c.set_irq_line(true);
+    c.set_nmi_line(true);
+    c.reset(Some(0x400), ResetKind::Warm).unwrap();
+
+    // the lines survive the reset, the latched nmi does not
+    let s = c.interrupt_state();
+    assert!(s.irq_line && s.nmi_line && s.irq_pending && s.irq_masked);
+    assert!(!s.nmi_pending);
+
+    // the held irq is serviced after cli and the inx it lets through, the nmi needs a new edge
+    c.run_until(None, 0, 3).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a, c.regs.x), (0x3002, 0x11, 1));
+    assert_eq!(*count.lock().unwrap(), (1, 0));
+
+    // a power-on reset drops them instead
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let s = c.interrupt_state();
+    assert!(!s.irq_line && !s.nmi_line && !s.irq_pending);
+}
+
+#[test]
+fn nmi_wins_over_irq() {
+    let (mut c, _) = setup();
+    let count = count_interrupts(&mut c);
+    c.run_until(None, 0, 2).unwrap();
+
+    // both asserted, the nmi is serviced first
+    c.set_irq_line(true);
+    c.set_nmi_line(true);
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a), (0x2002, 0x42));
+    assert_eq!(*count.lock().unwrap(), (0, 1));
+
+    // then the irq, once the nmi handler returns
+    c.run_until(None, 0, 2).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a), (0x3002, 0x11));
+    assert_eq!(*count.lock().unwrap(), (1, 1));
+}
+
+#[test]
+fn brk_hijacked_by_nmi() {
+    // brk (with its padding byte) at $0500
+    let (mut c, d) = setup();
+    d.write_memory(&mut c, 0x500, &[0x00, 0xea]).unwrap();
+    c.reset(Some(0x500), ResetKind::PowerOn).unwrap();
+    let s = c.regs.s;
+
+    // the nmi is asserted while BRK is pushing (on the P push)
+    let mut fired = false;
+    c.set_hook(move |ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+        if ctx.operation == CpuOperation::Write
+            && ctx.address == 0x100 + s.wrapping_sub(2) as u16
+            && !fired
+        {
+            fired = true;
+            cpu.trigger_nmi();
+        }
+    });
+    c.run_until(None, 0, 1).unwrap();
+
+    // the nmi vector is taken instead of the irq/brk one, the stacked P has B set and the return address is pc + 2
+    assert_eq!(c.regs.pc, 0x2000);
+    assert_eq!(c.regs.s, s.wrapping_sub(3));
+    let stack = c.stack_slice();
+    assert_eq!(stack[0] & CpuFlags::B.bits(), CpuFlags::B.bits());
+    assert_eq!(stack[1..3], [0x02, 0x05]);
+
+    // the nmi has been consumed: the handler runs (lda #$42, rti) and returns after the brk
+    c.run_until(None, 0, 2).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a, c.regs.s), (0x502, 0x42, s));
+}
+
+/**
+ * runs prg at $0400 (I set, as after reset) with the irq line held, one instruction at a time until the irq is taken.
+ *
+ * returns the instructions executed before the irq and the stacked P and return address.
+ */
+fn irq_taken_after(prg: &[u8]) -> (usize, u8, u16) {
+    let (mut c, d) = setup();
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    assert!(c.regs.interrupt_disable());
+    c.set_irq_line(true);
+    for n in 0..prg.len() {
+        c.run_until(None, 0, 1).unwrap();
+        if c.regs.pc == 0x3002 {
+            let stack = c.stack_slice();
+            return (n, stack[0], u16::from_le_bytes([stack[1], stack[2]]));
+        }
+    }
+    panic!("irq not taken");
+}
+
+#[test]
+fn irq_delay_cli() {
+    // cli, nop, nop: the irq is taken after the nop following cli, not right after cli
+    let (n, _, pc) = irq_taken_after(&[0x58, 0xea, 0xea]);
+    assert_eq!((n, pc), (2, 0x402));
+}
+
+#[test]
+fn irq_delay_sei() {
+    // cli, sei, nop: with I clear for one instruction, the irq still gets in after sei
+    let (n, p, pc) = irq_taken_after(&[0x58, 0x78, 0xea, 0xea]);
+    assert_eq!((n, pc), (2, 0x402));
+
+    // the stacked P has I set by sei, so rti doesn't re-enter
+    assert_eq!(p & CpuFlags::I.bits(), CpuFlags::I.bits());
+}
+
+#[test]
+fn irq_delay_plp() {
+    // lda #$00, pha, plp, nop, nop: as cli, the irq is taken after the nop following plp
+    let (n, _, pc) = irq_taken_after(&[0xa9, 0x00, 0x48, 0x28, 0xea, 0xea]);
+    assert_eq!((n, pc), (4, 0x405));
+}
+
+#[test]
+fn nmi_breakpoint() {
+    let (mut c, mut d) = setup();
+    let id = d
+        .add_breakpoint(&mut c, 0, BreakpointType::NMI, None)
+        .unwrap();
+    let r = c.run_until(Some(&mut d), 0, 3).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+
+    // the run loop stops on the handler, before executing it
+    c.trigger_nmi();
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(id));
+    assert_eq!((c.regs.pc, c.regs.a, c.regs.s), (0x2000, 0, 0xfc));
+
+    // resume
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!(c.regs.a, 0x42);
+}
+
+#[test]
+fn so_pin() {
+    // clv, bvc *, lda #$01
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    c.set_deadlock_detection(false);
+    d.write_memory(&mut c, 0x400, &[0xb8, 0x50, 0xfe, 0xa9, 0x01])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let mut so = 0;
+    c.set_hook(move |ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+        if ctx.operation == CpuOperation::So {
+            so += 1;
+            assert_eq!(so, 1);
+            assert!(cpu.regs().overflow());
+        }
+    });
+
+    // waits
+    c.run_until(None, 0, 10).unwrap();
+    assert_eq!(c.regs.pc, 0x401);
+
+    // falls through
+    c.assert_so();
+    c.run_until(None, 0, 2).unwrap();
+    assert_eq!((c.regs.pc, c.regs.a), (0x405, 0x01));
+    assert!(c.regs.overflow());
+}
+
+#[test]
+fn rdy_stall() {
+    // inx, stx $10, jmp $0400
+    let program = [0xe8, 0x86, 0x10, 0x4c, 0x00, 0x04];
+    let mut reference = Cpu::new_default(None);
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    for cpu in [&mut reference, &mut c] {
+        d.write_memory(cpu, 0x400, &program).unwrap();
+        cpu.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    }
+    reference.run_until(None, 0, 10).unwrap();
+
+    // stall after the stx
+    c.run_until(None, 0, 5).unwrap();
+    c.set_rdy(false);
+    let regs = c.regs;
+    let cycles = c.cycles;
+    let mem = d.read_memory(&mut c, 0, 0x800).unwrap();
+    let r = c.run_until(None, 100, 0).unwrap();
+    assert_eq!(r.reason, StopReason::Stalled { cycles: 100 });
+    assert_eq!((r.cycles, r.instructions), (0, 0));
+    c.run(None, 0).unwrap();
+    assert_eq!(c.regs, regs);
+    assert_eq!(c.cycles, cycles);
+    assert_eq!(d.read_memory(&mut c, 0, 0x800).unwrap(), mem);
+
+    // resumes where it left off
+    c.set_rdy(true);
+    c.run_until(None, 0, 5).unwrap();
+    assert_eq!(c.regs, reference.regs);
+    assert_eq!(c.cycles, reference.cycles);
+    assert_eq!(
+        d.read_memory(&mut c, 0, 0x800).unwrap(),
+        d.read_memory(&mut reference, 0, 0x800).unwrap()
+    );
+
+    // lowered from the hook, stalls at the next instruction boundary
+    c.set_hook(|ctx: &CpuCallbackContext, cpu: &mut CpuView| {
+        if ctx.operation == CpuOperation::Write {
+            cpu.set_rdy(false);
+        }
+    });
+    let r = c.run_until(None, 50, 0).unwrap();
+    assert_eq!(r.instructions, 1);
+    assert_eq!(
+        r.reason,
+        StopReason::Stalled {
+            cycles: 50 - r.cycles
+        }
+    );
+    assert_eq!(c.regs.pc, 0x403);
+    assert!(!c.is_rdy());
+}
+
+#[test]
+fn interrupt_context() {
+    let (mut c, mut d) = setup();
+    c.set_deadlock_detection(false);
+
+    // only for a handler at $c000, never triggers
+    let cond = BpCondition {
+        handler: Some(0xc000),
+        ..Default::default()
+    };
+    d.add_breakpoint(&mut c, 0, BreakpointType::NMI, Some(cond))
+        .unwrap();
+    c.run_until(Some(&mut d), 0, 4).unwrap();
+    c.trigger_nmi();
+    let r = c.run_until(Some(&mut d), 0, 3).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+    assert!(d.interrupt_context().is_none());
+
+    // the handler at $2000, from the command line
+    assert!(d.parse_cmd(&mut c, "bn handler=$2000").1);
+    c.run_until(Some(&mut d), 0, 3).unwrap();
+    let pc_before = c.regs.pc;
+    let cycles = c.cycles();
+    c.trigger_nmi();
+    let r = c.run_until(Some(&mut d), 0, 100).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(1));
+    assert_eq!(c.regs.pc, 0x2000);
+    let ctx = *d.interrupt_context().unwrap();
+    assert_eq!(
+        ctx,
+        InterruptContext {
+            kind: BreakpointType::NMI,
+            pc: pc_before,
+            vector: 0xfffa,
+            handler: 0x2000,
+            p: 0x20,
+            cycles,
+        }
+    );
+    assert!(ctx.pc == 0x401 || ctx.pc == 0x402);
+    assert_eq!(
+        ctx.to_string(),
+        format!(
+            "NMI taken at PC=${:04x}, vector $fffa -> $2000, stacked P=$20, cycles={}",
+            pc_before, cycles
+        )
+    );
+
+    // the irq breakpoint does not trigger on nmi
+    d.clear_breakpoints();
+    d.add_breakpoint(&mut c, 0, BreakpointType::IRQ, None)
+        .unwrap();
+    c.run_until(Some(&mut d), 0, 3).unwrap();
+    c.trigger_nmi();
+    let r = c.run_until(Some(&mut d), 0, 6).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+}
+
+#[test]
+fn interrupt_stats() {
+    // 5 irqs, the handler takes 8 cycles (lda #$11, rti)
+    let (mut c, mut d) = setup();
+    c.run_until(None, 0, 2).unwrap();
+    for _ in 0..5 {
+        c.set_irq_line(true);
+        c.run_until(None, 0, 1).unwrap();
+        assert_eq!(c.regs.pc, 0x3002);
+        c.set_irq_line(false);
+        c.run_until(None, 0, 3).unwrap();
+    }
+    let s = c.interrupt_stats();
+    assert_eq!((s.irqs, s.irq_cycles, s.max_irq_latency), (5, 5 * 8, 7));
+    assert_eq!((s.nmis, s.brks), (0, 0));
+
+    // masked for inx and jmp, then serviced: 5 cycles latency plus the sequence
+    c.regs.p.insert(CpuFlags::I);
+    c.set_irq_line(true);
+    c.run_until(None, 0, 2).unwrap();
+    c.regs.p.remove(CpuFlags::I);
+    c.run_until(None, 0, 1).unwrap();
+    c.set_irq_line(false);
+    c.run_until(None, 0, 1).unwrap();
+    let s = c.interrupt_stats();
+    assert_eq!((s.irqs, s.irq_cycles, s.max_irq_latency), (6, 6 * 8, 5 + 7));
+
+    // nmi, with the handler still running it's not accounted yet
+    c.trigger_nmi();
+    c.run_until(None, 0, 1).unwrap();
+    let s = c.interrupt_stats();
+    assert_eq!((s.nmis, s.nmi_cycles), (1, 0));
+    c.run_until(None, 0, 1).unwrap();
+    let s = c.interrupt_stats();
+    assert_eq!((s.nmis, s.nmi_cycles, s.max_nmi_latency), (1, 8, 7));
+
+    // istat clear
+    assert!(d.parse_cmd(&mut c, "istat clear").1);
+    assert_eq!(c.interrupt_stats(), InterruptStats::default());
+
+    // brk, the handler starts after its 7 cycles and returns to $0402
+    d.write_memory(&mut c, 0x400, &[0x00, 0xea]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 3).unwrap();
+    assert_eq!(c.regs.pc, 0x402);
+    let s = c.interrupt_stats();
+    assert_eq!((s.brks, s.brk_cycles, s.irqs), (1, 8, 0));
+    assert_eq!(
+        s.to_string(),
+        "IRQ: 0 serviced, 0 handler cycles, max latency 0\n\
+         NMI: 0 serviced, 0 handler cycles, max latency 0\n\
+         BRK: 1 executed, 8 handler cycles"
+    );
+}
+
+#[test]
+fn interrupt_cycles() {
+    // fast loop, then the checked one (with a hook installed)
+    for hooked in [false, true] {
+        let (mut c, _) = setup();
+        if hooked {
+            c.set_hook(|_: &CpuCallbackContext, _: &mut CpuView| {});
+        }
+        // cli, inx
+        c.run_until(None, 0, 2).unwrap();
+        let start = c.cycles;
+
+        // 3 irqs: 7 (sequence) + 2 (lda #$11) + 6 (rti), then jmp and inx (3 + 2)
+        for _ in 0..3 {
+            c.set_irq_line(true);
+            let r = c.run_until(None, 0, 1).unwrap();
+            assert_eq!((c.regs.pc, r.cycles), (0x3002, Cpu::INTERRUPT_CYCLES + 2));
+            c.set_irq_line(false);
+            c.run_until(None, 0, 3).unwrap();
+        }
+        assert_eq!(c.cycles, start + 3 * (7 + 2 + 6 + 3 + 2));
+
+        // the sequence consumes the cycles budget too
+        c.set_irq_line(true);
+        let r = c.run_cycles(9).unwrap();
+        assert_eq!((r.cycles, r.overshoot), (9, 0));
+        assert_eq!(c.regs.pc, 0x3002);
+        c.set_irq_line(false);
+
+        // nmi, same length
+        let cycles = c.cycles;
+        c.trigger_nmi();
+        let r = c.run_until(None, 0, 1).unwrap();
+        assert_eq!((c.regs.pc, r.cycles), (0x2002, 9));
+        assert_eq!(c.cycles, cycles + 9);
+    }
+}
+
+#[test]
+fn vectors() {
+    let mut c = Cpu::new_default(None);
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    let w = writes.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+        if ctx.operation == CpuOperation::Write {
+            w.lock().unwrap().push((ctx.address, ctx.value));
+        }
+    });
+
+    // round trip, through the hooks
+    for (v, a) in [
+        (Vectors::NMI, 0x2000),
+        (Vectors::RESET, 0x0400),
+        (Vectors::IRQ, 0x3000),
+    ] {
+        c.set_vector(v, a).unwrap();
+        assert_eq!(c.get_vector(v).unwrap(), a);
+    }
+    assert_eq!(Vectors::RESET.address(), 0xfffc);
+    assert_eq!(
+        writes.lock().unwrap()[..2],
+        [(0xfffa, 0x00), (0xfffb, 0x20)]
+    );
+
+    // the cpu uses them
+    c.reset(None, ResetKind::PowerOn).unwrap();
+    assert_eq!(c.regs.pc, 0x0400);
+    c.regs.p.remove(CpuFlags::I);
+    c.irq(None).unwrap();
+    assert_eq!(c.regs.pc, 0x3000);
+
+    // rom regions are respected
+    c.bus.get_memory().set_rom_region(0xfffa, 6, false);
+    c.set_vector(Vectors::IRQ, 0x4000).unwrap();
+    assert_eq!(c.get_vector(Vectors::IRQ).unwrap(), 0x3000);
+}
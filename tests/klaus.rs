@@ -0,0 +1,165 @@
+/*
+ * Filename: /tests/klaus.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 19:40:03
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Klaus Dormann's functional tests (https://github.com/Klaus2m5/6502_65C02_functional_tests).
+//!
+//! the binaries are taken from the tests/6502_65C02_functional_tests submodule, or from the directory in
+//! KLAUS_TESTS_DIR. tests whose binary is missing are skipped.
+//!
+//! the tests signal failure by trapping (branching/jumping to self) at the failed check, so they're run until the
+//! pc stops changing and the trap address is checked against the success one.
+
+use rv6502emu::cpu::debugger::{BreakpointType, Debugger};
+use rv6502emu::cpu::{Cpu, CpuType, ResetKind, StopReason};
+use std::env;
+use std::path::PathBuf;
+
+/// instructions run between checks for a trap.
+const SLICE: usize = 100_000;
+
+/// cycles budget, the longest test (functional) takes ~96M cycles.
+const MAX_CYCLES: usize = 200_000_000;
+
+/**
+ * returns the path of the given test binary, or None if it's not available.
+ */
+fn bin_path(name: &str) -> Option<PathBuf> {
+    let dir = env::var("KLAUS_TESTS_DIR")
+        .unwrap_or_else(|_| String::from("./tests/6502_65C02_functional_tests/bin_files"));
+    let p = PathBuf::from(dir).join(name);
+    if p.exists() {
+        Some(p)
+    } else {
+        println!("{} not found, skipping.", p.display());
+        None
+    }
+}
+
+/**
+ * creates a cpu with the given binary loaded at address, ready to run at start.
+ */
+fn setup(name: &str, address: usize, start: u16, t: CpuType) -> Option<Cpu> {
+    let path = bin_path(name)?;
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    c.bus
+        .get_memory()
+        .load(path.to_str().unwrap(), address)
+        .unwrap();
+
+    // traps are how the tests report
+    c.set_deadlock_detection(false);
+    c.reset(Some(start), ResetKind::PowerOn).unwrap();
+    Some(c)
+}
+
+/**
+ * runs until the pc stops changing (a branch/jump to self) or a breakpoint triggers, and returns the pc there.
+ *
+ * fails with the cpu state if an error occurs or the cycles budget is exhausted.
+ */
+fn run_to_trap(c: &mut Cpu, mut d: Option<&mut Debugger>) -> u16 {
+    while c.cycles < MAX_CYCLES {
+        let r = c.run_until(d.as_deref_mut(), 0, SLICE).unwrap();
+        match r.reason {
+            StopReason::InstructionsReached => (),
+            StopReason::Breakpoint(_) => return c.regs.pc,
+            _ => panic!("stopped ({:?}), {}, cycles={}", r.reason, c.regs, c.cycles),
+        }
+
+        // once trapped the cpu stays there, so one more instruction tells
+        let pc = c.regs.pc;
+        c.run_until(d.as_deref_mut(), 0, 1).unwrap();
+        if c.regs.pc == pc {
+            return pc;
+        }
+    }
+    panic!(
+        "no trap within {} cycles, {}, cycles={}",
+        MAX_CYCLES, c.regs, c.cycles
+    );
+}
+
+/**
+ * asserts the cpu trapped at the success address.
+ */
+fn assert_success(c: &Cpu, trap: u16, success: u16) {
+    assert_eq!(
+        trap, success,
+        "trapped at ${:04x} (success is ${:04x}), {}, cycles={}",
+        trap, success, c.regs, c.cycles
+    );
+}
+
+#[test]
+fn functional() {
+    let mut c = match setup("6502_functional_test.bin", 0, 0x400, CpuType::MOS6502) {
+        Some(c) => c,
+        None => return,
+    };
+    let trap = run_to_trap(&mut c, None);
+    assert_success(&c, trap, 0x3469);
+}
+
+#[test]
+fn decimal() {
+    let mut c = match setup("6502_decimal_test.bin", 0x200, 0x200, CpuType::MOS6502) {
+        Some(c) => c,
+        None => return,
+    };
+
+    // the test ends at DONE, with the result in ERROR ($0b, 0 = success)
+    let mut d = Debugger::new(false);
+    d.add_breakpoint(&mut c, 0x24b, BreakpointType::EXEC, None)
+        .unwrap();
+    let trap = run_to_trap(&mut c, Some(&mut d));
+    assert_success(&c, trap, 0x24b);
+    assert_eq!(
+        d.read_memory(&mut c, 0x0b, 1).unwrap(),
+        [0],
+        "decimal test failed, {}",
+        c.regs
+    );
+}
+
+#[test]
+fn extended_65c02() {
+    let mut c = match setup(
+        "65C02_extended_opcodes_test.bin",
+        0,
+        0x400,
+        CpuType::WDC65C02,
+    ) {
+        Some(c) => c,
+        None => return,
+    };
+    let trap = run_to_trap(&mut c, None);
+    assert_success(&c, trap, 0x24f1);
+}
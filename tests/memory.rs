@@ -0,0 +1,401 @@
+/*
+ * Filename: /tests/memory.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 19:58:14
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the Memory implementations.
+
+use rv6502emu::bus::{self, Bus};
+use rv6502emu::cpu::cpu_error::{CpuError, CpuErrorType};
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, ResetKind, Vectors};
+use rv6502emu::memory::{self, MemoryInitPolicy};
+use std::env;
+
+#[test]
+fn flat() {
+    let mut m = memory::new_default();
+    m.write_word_le(0x1234, 0xbeef).unwrap();
+    let mut buf = [0u8; 3];
+    m.read_range(0x1233, &mut buf).unwrap();
+    assert_eq!(buf, [0x00, 0xef, 0xbe]);
+
+    // out of bounds
+    let mut buf = [0u8; 2];
+    let e = m.read_range(0xffff, &mut buf).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::MemoryRead);
+
+    // not banked
+    assert_eq!(m.select_bank(0, 0).unwrap_err().t, CpuErrorType::Generic);
+}
+
+#[test]
+fn mirrored() {
+    // nes style, 2k mirrored up to $1fff
+    let mut m = memory::new_mirrored(0x800, 0x2000).unwrap();
+    m.write_byte(0x0001, 0x42).unwrap();
+    for a in [0x0801, 0x1001, 0x1801] {
+        assert_eq!(m.read_byte(a).unwrap(), 0x42);
+    }
+    assert_eq!(m.read_byte(0x2001).unwrap(), 0);
+
+    // words straddling the mirrors
+    m.write_word_le(0x1fff, 0xbeef).unwrap();
+    assert_eq!(m.read_byte(0x07ff).unwrap(), 0xef);
+    assert_eq!(m.read_byte(0x2000).unwrap(), 0xbe);
+    m.write_byte(0x0000, 0x12).unwrap();
+    assert_eq!(m.read_word_le(0x0fff).unwrap(), 0x12ef);
+
+    // read_range sees the mirrors too
+    let mut buf = [0u8; 2];
+    m.read_range(0x1000, &mut buf).unwrap();
+    assert_eq!(buf, [0x12, 0x42]);
+
+    // the cpu stores through a mirror: lda #$99, sta $0803, lda $1803
+    let mut c = Cpu::new(bus::new_default(m), None, None);
+    let d = Debugger::new(false);
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[0xa9, 0x99, 0x8d, 0x03, 0x08, 0xad, 0x03, 0x18],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 2).unwrap();
+    c.regs.a = 0;
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(c.regs.a, 0x99);
+    assert_eq!(c.bus.get_memory().read_byte(0x0003).unwrap(), 0x99);
+
+    // size must divide the mirrored range
+    assert!(memory::new_mirrored(0x300, 0x2000).is_err());
+    assert!(memory::new_mirrored(0, 0x2000).is_err());
+    assert!(memory::new_mirrored(0x800, 0x10800).is_err());
+}
+
+#[test]
+fn banked() {
+    // two 8k slots at $8000 and $a000 on 4 banks
+    let mut m = memory::new_banked(0x2000, &[0x8000, 0xa000], 4).unwrap();
+    for bank in 0..4 {
+        m.select_bank(0, bank).unwrap();
+        m.write_byte(0x8000, 0x10 + bank as u8).unwrap();
+    }
+    m.select_bank(0, 0).unwrap();
+    m.select_bank(1, 1).unwrap();
+
+    // any bank in any slot
+    assert_eq!(m.read_byte(0x8000).unwrap(), 0x10);
+    assert_eq!(m.read_byte(0xa000).unwrap(), 0x11);
+    m.select_bank(1, 3).unwrap();
+    assert_eq!(m.read_byte(0xa000).unwrap(), 0x13);
+
+    // a word straddling the slots, and unbanked ram
+    m.write_byte(0x9fff, 0x34).unwrap();
+    assert_eq!(m.read_word_le(0x9fff).unwrap(), 0x1334);
+    m.write_byte(0xc000, 0x55).unwrap();
+    m.select_bank(0, 2).unwrap();
+    assert_eq!(m.read_byte(0xc000).unwrap(), 0x55);
+    let mut buf = [0u8; 2];
+    m.read_range(0x7fff, &mut buf).unwrap();
+    assert_eq!(buf, [0x00, 0x12]);
+
+    // invalid slot, bank and geometry
+    assert_eq!(m.select_bank(2, 0).unwrap_err().t, CpuErrorType::Generic);
+    assert!(m.select_bank(0, 4).is_err());
+    assert!(memory::new_banked(0x3000, &[0x8000], 2).is_err());
+    assert!(memory::new_banked(0x2000, &[0x8100], 2).is_err());
+    assert!(memory::new_banked(0x2000, &[0x8000, 0x8000], 2).is_err());
+    assert!(memory::new_banked(0x4000, &[0x8000, 0xc000], 1).is_err());
+}
+
+#[test]
+fn bank_switch_mid_run() {
+    let m = memory::new_banked(0x4000, &[0x8000], 2).unwrap();
+    let mut c = Cpu::new(bus::new_default(m), None, None);
+    let d = Debugger::new(false);
+    c.bus.get_memory().write_byte(0x8000, 0x11).unwrap();
+    c.bus.get_memory().select_bank(0, 1).unwrap();
+    c.bus.get_memory().write_byte(0x8000, 0x22).unwrap();
+    c.bus.get_memory().select_bank(0, 0).unwrap();
+
+    // lda $8000, sta $0200, lda $8000, sta $0201
+    let prg = [
+        0xad, 0x00, 0x80, 0x8d, 0x00, 0x02, 0xad, 0x00, 0x80, 0x8d, 0x01, 0x02,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 2).unwrap();
+    c.bus.get_memory().select_bank(0, 1).unwrap();
+    c.run_until(None, 0, 2).unwrap();
+    assert_eq!(d.read_memory(&mut c, 0x200, 2).unwrap(), vec![0x11, 0x22]);
+
+    // the debugger sees the mapped bank
+    assert_eq!(d.read_memory(&mut c, 0x8000, 1).unwrap(), vec![0x22]);
+}
+
+#[test]
+fn load_from_slice() {
+    // fits exactly at the top of memory
+    let mut m = memory::new_default();
+    m.load_from_slice(&[1, 2, 3, 4], 0xfffc).unwrap();
+    assert_eq!(m.dump_range(0xfffc, 4).unwrap(), [1, 2, 3, 4]);
+
+    // does not fit, nothing written
+    let e = m.load_from_slice(&[5, 6, 7, 8], 0xfffe).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "Error (MemLoad), msg=4 bytes at $fffe exceed memory size $10000"
+    );
+    assert_eq!((e.t, e.address), (CpuErrorType::MemoryLoad, 0xfffe));
+    assert_eq!(m.dump_range(0xfffc, 4).unwrap(), [1, 2, 3, 4]);
+
+    // without a message, the address is shown
+    let e = CpuError::new_default(CpuErrorType::MemoryLoad, 0x1234, None);
+    assert_eq!(
+        e.to_string(),
+        "Error (MemLoad) at address=$1234, access size=0, max memory size=$0000 (0)"
+    );
+
+    // bypasses rom protection, on other memories too
+    m.set_rom_region(0x1000, 0x100, true);
+    m.load_from_slice(&[0xaa], 0x1000).unwrap();
+    assert_eq!(m.dump_range(0x1000, 1).unwrap(), [0xaa]);
+    let mut m = memory::new_mirrored(0x800, 0x2000).unwrap();
+    m.load_from_slice(&[0x42, 0x43], 0x7ff).unwrap();
+    assert_eq!(m.dump_range(0xfff, 2).unwrap(), [0x42, 0x43]);
+    assert_eq!(
+        m.dump_range(0xffff, 2).unwrap_err().t,
+        CpuErrorType::MemoryRead
+    );
+
+    // huge lengths are rejected before allocating
+    let m = memory::new_default();
+    let e = m.dump_range(0x10, usize::MAX).unwrap_err();
+    assert_eq!((e.t, e.address), (CpuErrorType::MemoryRead, 0x10));
+}
+
+#[test]
+fn save_to_file() {
+    let mut b = bus::new_default(memory::new_default());
+    b.load_from_slice(&[0xde, 0xad, 0xbe, 0xef], 0x2000)
+        .unwrap();
+    let path = env::temp_dir().join(format!("rv6502emu_{}_save.bin", std::process::id()));
+    let p = path.to_str().unwrap();
+    b.save_to_file(p, 0x2001, 3).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), [0xad, 0xbe, 0xef]);
+
+    // zero length, at the end of memory too
+    b.save_to_file(p, 0x2000, 0).unwrap();
+    assert!(std::fs::read(&path).unwrap().is_empty());
+    b.save_to_file(p, 0x10000, 0).unwrap();
+    assert!(std::fs::read(&path).unwrap().is_empty());
+
+    // past the end, the file is untouched
+    b.save_to_file(p, 0x2000, 1).unwrap();
+    let e = b.save_to_file(p, 0xffff, 2).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::MemoryRead);
+    assert_eq!(std::fs::read(&path).unwrap(), [0xde]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn install_program() {
+    let mut c = Cpu::new_default(None);
+    let prg = [0xa9, 0x42, 0xea];
+    c.bus
+        .get_memory()
+        .install_program(0xc000, &prg, true)
+        .unwrap();
+    assert_eq!(c.bus.dump_range(0xc000, 3).unwrap(), prg);
+    assert_eq!(c.get_vector(Vectors::RESET).unwrap(), 0xc000);
+    c.reset(None, ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!((c.regs.a, c.regs.pc), (0x42, 0xc002));
+
+    // without touching the vector
+    c.bus
+        .get_memory()
+        .install_program(0x0400, &prg, false)
+        .unwrap();
+    assert_eq!(c.get_vector(Vectors::RESET).unwrap(), 0xc000);
+
+    // the vectors are not addressable with less than 64k
+    let mut m = memory::new_sized(0x1000);
+    let e = m.install_program(0x0400, &prg, true).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "Error (MemLoad), msg=2 bytes at $fffc exceed memory size $1000"
+    );
+    assert!(m.install_program(0x0400, &prg, false).is_ok());
+}
+
+#[test]
+fn init_policy() {
+    // the default stays zero
+    let mut m = memory::new_default();
+    assert_eq!(m.init_policy(), MemoryInitPolicy::Zero);
+    assert!(m.dump_range(0, 0x10000).unwrap().iter().all(|b| *b == 0));
+
+    // clear() applies the policy
+    m.set_init_policy(MemoryInitPolicy::Ones);
+    m.clear();
+    assert!(m.dump_range(0, 0x10000).unwrap().iter().all(|b| *b == 0xff));
+
+    // c64 style stripes
+    let m = memory::new_with_policy(0x10000, MemoryInitPolicy::Pattern(0x00, 0xff));
+    let v = m.dump_range(0, 0x10000).unwrap();
+    for (i, b) in v.iter().enumerate() {
+        let expected = if (i / memory::PATTERN_STRIPE_SIZE) % 2 == 0 {
+            0x00
+        } else {
+            0xff
+        };
+        assert_eq!(*b, expected, "at ${:04x}", i);
+    }
+
+    // the other implementations too
+    let mut m = memory::new_mirrored(0x800, 0x2000).unwrap();
+    m.set_init_policy(MemoryInitPolicy::Pattern(0x12, 0x34));
+    m.clear();
+    assert_eq!(m.dump_range(0x0800, 2).unwrap(), [0x12, 0x12]);
+    assert_eq!(m.dump_range(0x0840, 2).unwrap(), [0x34, 0x34]);
+    let mut m = memory::new_banked(0x2000, &[0x8000], 2).unwrap();
+    m.set_init_policy(MemoryInitPolicy::Ones);
+    m.clear();
+    m.select_bank(0, 1).unwrap();
+    assert_eq!(m.dump_range(0x8000, 2).unwrap(), [0xff, 0xff]);
+    assert_eq!(m.init_policy(), MemoryInitPolicy::Ones);
+}
+
+#[test]
+fn init_policy_random() {
+    let policy = MemoryInitPolicy::Random { seed: 0x6502 };
+    let a = memory::new_with_policy(0x10000, policy)
+        .dump_range(0, 0x10000)
+        .unwrap();
+
+    // same seed, same garbage, also after clear()
+    let mut m = memory::new_default();
+    m.set_init_policy(policy);
+    m.clear();
+    assert_eq!(m.dump_range(0, 0x10000).unwrap(), a);
+
+    // looks like garbage, and another seed gives another one
+    assert!(a.iter().filter(|b| **b == 0).count() < 0x400);
+    let b = memory::new_with_policy(0x10000, MemoryInitPolicy::Random { seed: 0x6510 })
+        .dump_range(0, 0x10000)
+        .unwrap();
+    assert_ne!(a, b);
+
+    // sizes not multiple of 8 are filled too
+    let mut buf = [0u8; 13];
+    policy.fill(&mut buf);
+    assert_eq!(buf[..], a[..13]);
+}
+
+#[test]
+fn init_policy_debugger() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    assert!(d.parse_cmd(&mut c, "init pattern $aa $55").1);
+    assert_eq!(
+        c.bus.get_memory().init_policy(),
+        MemoryInitPolicy::Pattern(0xaa, 0x55)
+    );
+    assert_eq!(c.bus.dump_range(0x003f, 2).unwrap(), [0xaa, 0x55]);
+    assert!(d.parse_cmd(&mut c, "init random $1234").1);
+    assert_eq!(
+        c.bus.get_memory().init_policy(),
+        MemoryInitPolicy::Random { seed: 0x1234 }
+    );
+    assert!(d.parse_cmd(&mut c, "init").1);
+    assert!(!d.parse_cmd(&mut c, "init garbage").1);
+    assert!(!d.parse_cmd(&mut c, "init pattern $zz").1);
+}
+
+#[test]
+fn rom_regions() {
+    // ignored writes leave the contents unchanged
+    let mut m = memory::new_default();
+    m.load_from_slice(&[0x11, 0x22], 0x10ff).unwrap();
+    m.set_rom_region(0x1000, 0x100, false);
+    m.write_byte(0x1000, 0xaa).unwrap();
+    assert_eq!(m.read_byte(0x1000).unwrap(), 0x00);
+
+    // a word straddling the boundary writes only the ram half
+    m.write_word_le(0x10ff, 0xbbaa).unwrap();
+    assert_eq!(m.dump_range(0x10ff, 2).unwrap(), [0x11, 0xbb]);
+
+    // reported writes fail with ReadOnlyMemory, and leave the contents unchanged too
+    m.set_rom_region(0x2000, 0x10, true);
+    m.load_from_slice(&[0x33], 0x200f).unwrap();
+    let e = m.write_byte(0x200f, 0xaa).unwrap_err();
+    assert_eq!((e.t, e.address), (CpuErrorType::ReadOnlyMemory, 0x200f));
+    let e = m.write_word_le(0x200f, 0xbbaa).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::ReadOnlyMemory);
+    assert_eq!(m.dump_range(0x200f, 2).unwrap(), [0x33, 0x00]);
+    m.write_byte(0x2010, 0xcc).unwrap();
+    assert_eq!(m.read_byte(0x2010).unwrap(), 0xcc);
+}
+
+#[test]
+fn rom_from_cpu() {
+    // lda #$ea, sta $c005 (would turn the inx into a nop), inx, sta $fffc, sta $fffd, in rom at $c000-$ffff
+    let prg = [
+        0xa9, 0xea, 0x8d, 0x05, 0xc0, 0xe8, 0x8d, 0xfc, 0xff, 0x8d, 0xfd, 0xff,
+    ];
+    let mut c = Cpu::new_default(None);
+    c.bus
+        .get_memory()
+        .install_program(0xc000, &prg, true)
+        .unwrap();
+    c.bus.get_memory().set_rom_region(0xc000, 0x4000, false);
+    c.reset(None, ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 5).unwrap();
+
+    // the self-modifying store was ignored, and the reset vector survived the stray stores
+    assert_eq!((c.regs.x, c.regs.pc), (1, 0xc00c));
+    assert_eq!(c.bus.dump_range(0xc000, prg.len()).unwrap(), prg);
+    assert_eq!(c.get_vector(Vectors::RESET).unwrap(), 0xc000);
+    c.reset(None, ResetKind::PowerOn).unwrap();
+    assert_eq!(c.regs.pc, 0xc000);
+
+    // reporting the error instead returns it from the store
+    let mut c = Cpu::new_default(None);
+    c.bus
+        .get_memory()
+        .install_program(0xc000, &prg, true)
+        .unwrap();
+    c.bus.get_memory().set_rom_region(0xc000, 0x4000, true);
+    c.reset(None, ResetKind::PowerOn).unwrap();
+    let e = c.run_until(None, 0, 5).unwrap_err();
+    assert_eq!((e.t, e.address), (CpuErrorType::ReadOnlyMemory, 0xc005));
+    assert_eq!(c.regs.pc, 0xc002);
+    assert_eq!(c.bus.dump_range(0xc005, 1).unwrap(), [0xe8]);
+}
@@ -0,0 +1,287 @@
+/*
+ * Filename: /tests/opcode_tables.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 17:48:05
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! consistency checks for the opcode tables metadata (as exposed by opcode_info()) against the implementation.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{
+    opcode_info, AddressingModeId, Cpu, CpuCallbackContext, CpuOperation, CpuType, CpuView,
+    OpcodeInfo, ResetKind,
+};
+use std::sync::{Arc, Mutex};
+
+/**
+ * base cycles, from http://www.oxyron.de/html/opcodes02.html (JAM opcodes are 0).
+ */
+#[rustfmt::skip]
+const CYCLES_6502: [u8; 256] = [
+    7, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 3, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 5, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4,
+    2, 6, 0, 6, 4, 4, 4, 4, 2, 5, 2, 5, 5, 5, 5, 5,
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4,
+    2, 5, 0, 5, 4, 4, 4, 4, 2, 4, 2, 4, 4, 4, 4, 4,
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+];
+
+/**
+ * base cycles, from the WDC W65C02S datasheet and http://6502.org/tutorials/65c02opcodes.html.
+ */
+#[rustfmt::skip]
+const CYCLES_65C02: [u8; 256] = [
+    7, 6, 2, 1, 5, 3, 5, 5, 3, 2, 2, 1, 6, 4, 6, 5,
+    2, 5, 5, 1, 5, 4, 6, 5, 2, 4, 2, 1, 6, 4, 6, 5,
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 4, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 2, 1, 4, 4, 6, 5,
+    6, 6, 2, 1, 3, 3, 5, 5, 3, 2, 2, 1, 3, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 1, 8, 4, 6, 5,
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 6, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 6, 4, 6, 5,
+    3, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5,
+    2, 6, 5, 1, 4, 4, 4, 5, 2, 5, 2, 1, 4, 5, 5, 5,
+    2, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5,
+    2, 5, 5, 1, 4, 4, 4, 5, 2, 4, 2, 1, 4, 4, 4, 5,
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 3, 4, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 3, 4, 4, 7, 5,
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 1, 4, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 4, 4, 7, 5,
+];
+
+#[test]
+fn spot_check() {
+    // documented values, from http://www.6502.org/tutorials/6502opcodes.html
+    let expected = [
+        (0xbd, "lda", AddressingModeId::Abx, 3, 4, true, false),
+        (0xa9, "lda", AddressingModeId::Imm, 2, 2, false, false),
+        (0x6c, "jmp", AddressingModeId::Ind, 3, 5, false, false),
+        (0x91, "sta", AddressingModeId::Iny, 2, 6, false, false),
+        (0x00, "brk", AddressingModeId::Imp, 1, 7, false, false),
+        (0xa7, "lax", AddressingModeId::Zpg, 2, 3, false, true),
+    ];
+    for (opcode, mnemonic, addressing_mode, bytes, base_cycles, page_cross_penalty, undocumented) in
+        expected
+    {
+        assert_eq!(
+            opcode_info(opcode, CpuType::MOS6502),
+            OpcodeInfo {
+                opcode,
+                mnemonic,
+                addressing_mode,
+                bytes,
+                base_cycles,
+                page_cross_penalty,
+                undocumented,
+            }
+        );
+    }
+}
+
+#[test]
+fn cycles() {
+    for (t, table) in [
+        (CpuType::MOS6502, &CYCLES_6502),
+        (CpuType::WDC65C02, &CYCLES_65C02),
+    ] {
+        for op in 0..=0xffu8 {
+            let info = opcode_info(op, t);
+            assert_eq!(
+                info.base_cycles, table[op as usize],
+                "{} ${:02x} {}",
+                t, op, info.mnemonic
+            );
+
+            // the 65C02 has no JAM opcodes
+            if t == CpuType::WDC65C02 {
+                assert_ne!(info.mnemonic, "kil", "${:02x}", op);
+            }
+
+            // only indexed reads and branches pay the page crossing
+            if info.page_cross_penalty {
+                assert!(
+                    matches!(
+                        info.addressing_mode,
+                        AddressingModeId::Abx
+                            | AddressingModeId::Aby
+                            | AddressingModeId::Iny
+                            | AddressingModeId::Rel
+                    ),
+                    "{} ${:02x} {}",
+                    t,
+                    op,
+                    info.mnemonic
+                );
+            }
+        }
+    }
+}
+
+/**
+ * the effective address each addressing mode resolves to, with operand bytes $10 $02, X=$01, Y=$02,
+ * ($10)=$0320 and ($11)=$0503. None for modes without a data access.
+ */
+fn expected_address(id: AddressingModeId) -> Option<u16> {
+    match id {
+        AddressingModeId::Zpg => Some(0x10),
+        AddressingModeId::Zpx => Some(0x11),
+        AddressingModeId::Zpy => Some(0x12),
+        AddressingModeId::Abs => Some(0x210),
+        AddressingModeId::Abx => Some(0x211),
+        AddressingModeId::Aby => Some(0x212),
+        AddressingModeId::Xin => Some(0x503),
+        AddressingModeId::Iny => Some(0x322),
+        AddressingModeId::Izp => Some(0x320),
+        _ => None,
+    }
+}
+
+#[test]
+fn addressing_modes() {
+    // runs each opcode with a data access and checks the accessed address matches the table tag,
+    // a mismatch between the opcode function generic parameter and the tag (or the size) shows up here.
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        let mut c = Cpu::new_default(None);
+        let mut d = Debugger::new(false);
+        c.set_cpu_type(t);
+        c.set_deadlock_detection(false);
+        let accesses: Arc<Mutex<Vec<u16>>> = Arc::new(Mutex::new(Vec::new()));
+        let a = accesses.clone();
+        c.set_hook(move |ctx: &CpuCallbackContext, _cpu: &mut CpuView| {
+            // data accesses, skip the stack
+            let data = matches!(ctx.operation, CpuOperation::Read | CpuOperation::Write);
+            if data && ctx.address & 0xff00 != 0x100 {
+                a.lock().unwrap().push(ctx.address)
+            }
+        });
+
+        for op in 0..=0xffu8 {
+            let info = opcode_info(op, t);
+            let expected = match expected_address(info.addressing_mode) {
+                // jmp/jsr do not access data, nops just decode
+                Some(_) if matches!(info.mnemonic, "jmp" | "jsr" | "nop") => continue,
+                Some(a) => a,
+                None => continue,
+            };
+            d.write_memory(&mut c, 0x10, &[0x20, 0x03, 0x05]).unwrap();
+            d.write_memory(&mut c, 0x400, &[op, 0x10, 0x02]).unwrap();
+            c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+            c.regs.x = 0x01;
+            c.regs.y = 0x02;
+            accesses.lock().unwrap().clear();
+            c.run_until(Some(&mut d), 0, 1).unwrap();
+
+            assert_eq!(
+                c.regs.pc,
+                0x400 + info.bytes as u16,
+                "{} ${:02x} {}",
+                t,
+                op,
+                info.mnemonic
+            );
+            let v = accesses.lock().unwrap();
+            assert!(
+                !v.is_empty() && v.iter().all(|a| *a == expected),
+                "{} ${:02x} {} {}: accessed {:04x?}, expected ${:04x}",
+                t,
+                op,
+                info.mnemonic,
+                info.addressing_mode,
+                *v,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn cmos_variants() {
+    // the bit instructions and WAI/STP per variant
+    assert_eq!(opcode_info(0x0f, CpuType::WDC65C02).mnemonic, "bbr0");
+    assert_eq!(opcode_info(0x0f, CpuType::R65C02).mnemonic, "bbr0");
+    assert_eq!(opcode_info(0xcb, CpuType::WDC65C02).mnemonic, "wai");
+    assert_eq!(opcode_info(0xdb, CpuType::WDC65C02).mnemonic, "stp");
+    for (t, op) in [
+        (CpuType::G65SC02, 0x0f),
+        (CpuType::G65SC02, 0x87),
+        (CpuType::G65SC02, 0xcb),
+        (CpuType::R65C02, 0xcb),
+        (CpuType::R65C02, 0xdb),
+    ] {
+        let info = opcode_info(op, t);
+        assert_eq!(
+            (
+                info.mnemonic,
+                info.addressing_mode,
+                info.bytes,
+                info.base_cycles
+            ),
+            ("nop", AddressingModeId::Imp, 1, 1),
+            "{} ${:02x}",
+            t,
+            op
+        );
+    }
+
+    // the rest of the table is the 65C02 one
+    for op in 0..=0xffu8 {
+        if op & 0x7 == 0x7 || op == 0xcb || op == 0xdb {
+            continue;
+        }
+        assert_eq!(
+            opcode_info(op, CpuType::G65SC02),
+            opcode_info(op, CpuType::WDC65C02),
+            "${:02x}",
+            op
+        );
+    }
+
+    // $0f is a 1 cycle NOP without the bit instructions, BBR0 with them: bbr0 $10,+2
+    let run = |t: CpuType| -> (u16, usize) {
+        let mut c = Cpu::new_default(None);
+        let mut d = Debugger::new(false);
+        c.set_cpu_type(t);
+        d.write_memory(&mut c, 0x10, &[0xfe]).unwrap();
+        d.write_memory(&mut c, 0x400, &[0x0f, 0x10, 0x02]).unwrap();
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+        (c.regs.pc, r.cycles)
+    };
+    assert_eq!(run(CpuType::G65SC02), (0x401, 1));
+    assert_eq!(run(CpuType::R65C02).0, 0x405);
+    assert_eq!(run(CpuType::WDC65C02).0, 0x405);
+}
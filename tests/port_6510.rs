@@ -0,0 +1,103 @@
+/*
+ * Filename: /tests/port_6510.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 22:05:14
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the MOS6510 i/o port at $0000/$0001.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, CpuType, PortHandler, ResetKind};
+use std::sync::{Arc, Mutex};
+
+/**
+ * records the (ddr, data) writes, returns input on reads.
+ */
+struct Port {
+    writes: Arc<Mutex<Vec<(u8, u8)>>>,
+    input: u8,
+}
+
+impl PortHandler for Port {
+    fn write(&mut self, ddr: u8, data: u8) {
+        self.writes.lock().unwrap().push((ddr, data));
+    }
+
+    fn read(&mut self, _ddr: u8) -> u8 {
+        self.input
+    }
+}
+
+/**
+ * lda #$2f, sta $00, lda #$37, sta $01, lda $01 at $400, with $99 in ram at $0000/$0001.
+ */
+fn run(t: CpuType, input: u8) -> (Cpu, Vec<(u8, u8)>) {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    c.set_port_handler(Box::new(Port {
+        writes: writes.clone(),
+        input,
+    }));
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x0000, &[0x99, 0x99]).unwrap();
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[0xa9, 0x2f, 0x85, 0x00, 0xa9, 0x37, 0x85, 0x01, 0xa5, 0x01],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 5).unwrap();
+    let w = writes.lock().unwrap().clone();
+    (c, w)
+}
+
+#[test]
+fn sta_calls_handler() {
+    let (mut c, writes) = run(CpuType::MOS6510, 0x00);
+
+    // the handler sees both writes, with the current ddr and data
+    assert_eq!(writes, [(0x2f, 0x00), (0x2f, 0x37)]);
+
+    // the ram below the port is untouched
+    assert_eq!(c.bus.dump_range(0, 2).unwrap(), [0x99, 0x99]);
+}
+
+#[test]
+fn lda_reads_handler() {
+    // output bits (ddr=$2f) from the data register, input bits from the handler
+    let (c, _) = run(CpuType::MOS6510, 0xd0);
+    assert_eq!(c.regs.a, (0x37 & 0x2f) | (0xd0 & !0x2f));
+    assert_ne!(c.regs.a, 0x99);
+
+    // on the plain 6502 it's just ram
+    let (mut c, writes) = run(CpuType::MOS6502, 0xd0);
+    assert!(writes.is_empty());
+    assert_eq!(c.regs.a, 0x37);
+    assert_eq!(c.bus.dump_range(0, 2).unwrap(), [0x2f, 0x37]);
+}
@@ -0,0 +1,225 @@
+/*
+ * Filename: /tests/processor_tests.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 10:12:31
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! runner for Tom Harte's ProcessorTests (https://github.com/SingleStepTests/65x02).
+//!
+//! each json file (i.e. "a9.json") holds 10000 test cases for a single opcode, each with the initial and final
+//! cpu/ram state and the bus activity for every cycle.
+//!
+//! this test is ignored by default, run it with:
+//!
+//! PROCESSOR_TESTS_DIR=/path/to/65x02/6502/v1 [PROCESSOR_TESTS_CPU=6502|65c02] cargo test --test processor_tests -- --ignored --nocapture
+
+use rv6502emu::cpu::{Cpu, CpuFlags, CpuType, Registers, ResetKind};
+use serde::Deserialize;
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+/**
+ * cpu and ram state, "initial" and "final" in the json.
+ */
+#[derive(Deserialize)]
+struct State {
+    pc: u16,
+    s: u8,
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+    ram: Vec<(u16, u8)>,
+}
+
+/**
+ * a single test case.
+ */
+#[derive(Deserialize)]
+struct TestCase {
+    name: String,
+    initial: State,
+    #[serde(rename = "final")]
+    fin: State,
+    /// (address, value, "read"|"write") for each cycle, we just check the count.
+    cycles: Vec<(u16, u8, String)>,
+}
+
+/**
+ * setup cpu and memory from the initial state.
+ */
+fn setup(c: &mut Cpu, s: &State) {
+    // clear any state left by the previous test (i.e. halted by STP)
+    c.reset(Some(s.pc), ResetKind::PowerOn).unwrap();
+    for (addr, b) in s.ram.iter() {
+        c.bus.get_memory().write_byte(*addr as usize, *b).unwrap();
+    }
+    c.regs = Registers {
+        a: s.a,
+        x: s.x,
+        y: s.y,
+        p: CpuFlags::from_bits(s.p).unwrap(),
+        s: s.s,
+        pc: s.pc,
+    };
+    c.cycles = 0;
+}
+
+/**
+ * compare cpu and memory against the final state, returns a description of the differences if any.
+ */
+fn check(c: &mut Cpu, t: &TestCase) -> Option<String> {
+    let mut diffs: Vec<String> = Vec::new();
+    let f = &t.fin;
+
+    // B and U are not real flags, they only exists on the stack
+    let mask = !(CpuFlags::B | CpuFlags::U).bits();
+    if c.regs.pc != f.pc {
+        diffs.push(format!("PC=${:04x} (expected ${:04x})", c.regs.pc, f.pc));
+    }
+    if c.regs.a != f.a {
+        diffs.push(format!("A=${:02x} (expected ${:02x})", c.regs.a, f.a));
+    }
+    if c.regs.x != f.x {
+        diffs.push(format!("X=${:02x} (expected ${:02x})", c.regs.x, f.x));
+    }
+    if c.regs.y != f.y {
+        diffs.push(format!("Y=${:02x} (expected ${:02x})", c.regs.y, f.y));
+    }
+    if c.regs.s != f.s {
+        diffs.push(format!("S=${:02x} (expected ${:02x})", c.regs.s, f.s));
+    }
+    if c.regs.p.bits() & mask != f.p & mask {
+        diffs.push(format!(
+            "P=${:02x} (expected ${:02x})",
+            c.regs.p.bits(),
+            f.p
+        ));
+    }
+    for (addr, b) in f.ram.iter() {
+        let v = c.bus.get_memory().read_byte(*addr as usize).unwrap();
+        if v != *b {
+            diffs.push(format!("${:04x}=${:02x} (expected ${:02x})", addr, v, b));
+        }
+    }
+    if c.cycles != t.cycles.len() {
+        diffs.push(format!("cycles={} (expected {})", c.cycles, t.cycles.len()));
+    }
+
+    if diffs.is_empty() {
+        return None;
+    }
+    Some(diffs.join(", "))
+}
+
+/**
+ * runs all the test cases in a json file, returns (passed, failed, first failure).
+ */
+fn run_file(c: &mut Cpu, path: &PathBuf) -> (usize, usize, Option<String>) {
+    let f = File::open(path).unwrap();
+    let tests: Vec<TestCase> = serde_json::from_reader(BufReader::new(f)).unwrap();
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut first_failure: Option<String> = None;
+    for t in tests.iter() {
+        setup(c, &t.initial);
+
+        // execute exactly one instruction
+        let _ = c.run(None, 1);
+        match check(c, t) {
+            None => passed += 1,
+            Some(d) => {
+                failed += 1;
+                if first_failure.is_none() {
+                    first_failure = Some(format!("'{}': {}", t.name, d));
+                }
+            }
+        }
+    }
+    (passed, failed, first_failure)
+}
+
+#[test]
+#[ignore]
+fn processor_tests() {
+    let dir = match env::var("PROCESSOR_TESTS_DIR") {
+        Ok(d) => d,
+        Err(_) => {
+            println!("PROCESSOR_TESTS_DIR not set, skipping.");
+            return;
+        }
+    };
+    let t = match env::var("PROCESSOR_TESTS_CPU")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "65c02" => CpuType::WDC65C02,
+        _ => CpuType::MOS6502,
+    };
+
+    // collect the json files, sorted by opcode
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().map_or(false, |e| e == "json"))
+        .collect();
+    files.sort();
+
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+
+    // self branches are legit here
+    c.set_deadlock_detection(false);
+
+    let mut failed_opcodes: Vec<String> = Vec::new();
+    for path in files.iter() {
+        let opcode = path.file_stem().unwrap().to_string_lossy().to_string();
+        let (passed, failed, first_failure) = run_file(&mut c, path);
+        if failed == 0 {
+            println!("${}: PASSED ({} tests)", opcode, passed);
+        } else {
+            println!(
+                "${}: FAILED ({} passed, {} failed), first failure {}",
+                opcode,
+                passed,
+                failed,
+                first_failure.unwrap()
+            );
+            failed_opcodes.push(opcode);
+        }
+    }
+
+    assert!(
+        failed_opcodes.is_empty(),
+        "{} opcodes failed: {}",
+        failed_opcodes.len(),
+        failed_opcodes.join(" ")
+    );
+}
@@ -0,0 +1,72 @@
+/*
+ * Filename: /tests/profiler.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 17:36:02
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the execution profiler.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, ResetKind};
+
+#[test]
+fn hot_loop() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // ldx #$05, dex, bne $0402, nop
+    d.write_memory(&mut c, 0x400, &[0xa2, 0x05, 0xca, 0xd0, 0xfd, 0xea])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+
+    // disabled by default
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert!(!c.is_profiling());
+    assert!(c.profile_report(0).is_empty());
+
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.enable_profiling(true);
+    let r = c.run_until(Some(&mut d), 0, 12).unwrap();
+    assert_eq!(c.regs.pc, 0x406);
+
+    // the loop body dominates
+    let v = c.profile_report(2);
+    assert_eq!(v.len(), 2);
+    assert_eq!((v[0].0, v[0].1), (0x403, 5));
+    assert_eq!(v[1], (0x402, 5, 10));
+
+    let all = c.profile_report(0);
+    assert_eq!(all.len(), 4);
+    assert!(all.contains(&(0x400, 1, 2)));
+    assert!(all.contains(&(0x405, 1, 2)));
+    assert_eq!(all.iter().map(|e| e.2).sum::<usize>(), r.cycles);
+
+    // the debugger command
+    assert!(d.parse_cmd(&mut c, "prof 3").1);
+    c.enable_profiling(false);
+    assert!(!d.parse_cmd(&mut c, "prof").1);
+}
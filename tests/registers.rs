@@ -0,0 +1,263 @@
+/*
+ * Filename: /tests/registers.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 18:10:22
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the Registers flag api and the status byte pushed on the stack.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{Cpu, CpuFlags, CpuType, RegChange, Registers, RegistersSnapshot, ResetKind};
+
+#[test]
+fn flags() {
+    let mut r = Registers::new();
+    r.set_carry(true);
+    r.set_negative(true);
+    r.set_decimal(true);
+    assert!(r.carry() && r.negative() && r.decimal());
+    assert!(!r.zero() && !r.overflow() && !r.interrupt_disable());
+    assert_eq!(r.p, CpuFlags::C | CpuFlags::N | CpuFlags::D);
+
+    r.set_carry(false);
+    r.set_zero(true);
+    r.set_overflow(true);
+    r.set_interrupt_disable(true);
+    assert!(!r.carry() && r.zero() && r.overflow() && r.interrupt_disable());
+
+    // U always set, B only for php/brk
+    r.p = CpuFlags::C;
+    assert_eq!(r.status_byte_for_push(true), 0x31);
+    assert_eq!(r.status_byte_for_push(false), 0x21);
+    assert_eq!(r.p, CpuFlags::C);
+}
+
+/**
+ * runs the program at $400 for n instructions, returns the cpu.
+ */
+fn run_prg(prg: &[u8], n: usize) -> Cpu {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(Some(&mut d), 0, n).unwrap();
+    c
+}
+
+#[test]
+fn pulled_status() {
+    // B is cleared and U set, whatever is pulled
+    let mut r = Registers::new();
+    r.set_status_from_pull(0xff);
+    assert_eq!(r.p.bits(), 0xef);
+    r.set_status_from_pull(0x00);
+    assert_eq!(r.p.bits(), 0x20);
+    r.set_status_from_pull(0x10);
+    assert_eq!(r.p, CpuFlags::U);
+
+    // lda #$ff, pha, plp
+    let c = run_prg(&[0xa9, 0xff, 0x48, 0x28], 3);
+    assert_eq!(c.regs.p.bits(), 0xef);
+    assert!(!c.regs.p.contains(CpuFlags::B));
+
+    // lda #$00, pha, plp
+    let c = run_prg(&[0xa9, 0x00, 0x48, 0x28], 3);
+    assert_eq!(c.regs.p, CpuFlags::U);
+
+    // push $0420 and the flags, then rti
+    for (pulled, p) in [(0xff, 0xef), (0x00, 0x20)] {
+        let c = run_prg(
+            &[0xa9, 0x04, 0x48, 0xa9, 0x20, 0x48, 0xa9, pulled, 0x48, 0x40],
+            7,
+        );
+        assert_eq!(c.regs.pc, 0x420);
+        assert_eq!(c.regs.p.bits(), p);
+        assert!(c.regs.p.contains(CpuFlags::U));
+        assert!(!c.regs.p.contains(CpuFlags::B));
+    }
+}
+
+/**
+ * runs the program at $400 with the irq handler at $600 and the nmi handler at $700 (nops), returns P as pushed at $01fd
+ * (or $01ff for php).
+ */
+fn pushed_status(prg: &[u8], irq: bool, nmi: bool) -> u8 {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    d.write_memory(&mut c, 0xfffa, &[0x00, 0x07, 0x00, 0x04, 0x00, 0x06])
+        .unwrap();
+    d.write_memory(&mut c, 0x600, &[0xea, 0xea]).unwrap();
+    d.write_memory(&mut c, 0x700, &[0xea, 0xea]).unwrap();
+    c.reset(None, ResetKind::PowerOn).unwrap();
+    c.regs.p.insert(CpuFlags::C);
+    c.set_irq_line(irq);
+    c.set_nmi_line(nmi);
+    c.run_until(Some(&mut d), 0, 3).unwrap();
+    let addr = if prg[0] == 0x08 { 0x1ff } else { 0x1fd };
+    d.read_memory(&mut c, addr, 1).unwrap()[0]
+}
+
+#[test]
+fn pushed_b_flag() {
+    // php
+    assert_eq!(pushed_status(&[0x08, 0xea], false, false) & 0x30, 0x30);
+
+    // brk
+    assert_eq!(pushed_status(&[0x00, 0xea], false, false) & 0x30, 0x30);
+
+    // cli, irq (polled after the next instruction)
+    assert_eq!(pushed_status(&[0x58, 0xea], true, false) & 0x30, 0x20);
+
+    // nmi
+    let p = pushed_status(&[0xea, 0xea], false, true);
+    assert_eq!(p & 0x30, 0x20);
+    assert_eq!(p & 0x01, 0x01);
+}
+
+/**
+ * runs lda/ldx/ldy/sed then resets with the given kind, returns the cpu.
+ */
+fn reset_after_run(t: CpuType, kind: ResetKind) -> Cpu {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0xa9, 0x11, 0xa2, 0x22, 0xa0, 0x33, 0xf8])
+        .unwrap();
+    d.write_memory(&mut c, 0xfffc, &[0x00, 0x06]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(Some(&mut d), 0, 4).unwrap();
+    assert_eq!(c.cycles(), 7 + 8);
+    c.reset(None, kind).unwrap();
+    c
+}
+
+#[test]
+fn reset_power_on() {
+    for t in [CpuType::MOS6502, CpuType::WDC65C02] {
+        let c = reset_after_run(t, ResetKind::PowerOn);
+        assert_eq!((c.regs.a, c.regs.x, c.regs.y), (0, 0, 0));
+        assert_eq!((c.regs.pc, c.regs.s), (0x600, 0xff));
+        assert_eq!(c.regs.p, CpuFlags::U | CpuFlags::I);
+        assert_eq!(c.cycles(), 7);
+    }
+}
+
+#[test]
+fn reset_warm() {
+    // NMOS leaves D untouched
+    let c = reset_after_run(CpuType::MOS6502, ResetKind::Warm);
+    assert_eq!((c.regs.a, c.regs.x, c.regs.y), (0x11, 0x22, 0x33));
+    assert_eq!((c.regs.pc, c.regs.s), (0x600, 0xfc));
+    assert!(c.regs.p.contains(CpuFlags::I | CpuFlags::D));
+    assert_eq!(c.cycles(), 7 + 8 + 7);
+
+    // 65C02 clears D
+    let c = reset_after_run(CpuType::WDC65C02, ResetKind::Warm);
+    assert_eq!((c.regs.a, c.regs.x, c.regs.y), (0x11, 0x22, 0x33));
+    assert_eq!(c.regs.s, 0xfc);
+    assert!(c.regs.p.contains(CpuFlags::I));
+    assert!(!c.regs.p.contains(CpuFlags::D));
+    assert_eq!(c.cycles(), 7 + 8 + 7);
+
+    // from the S=$00 of a real power-on, the three fake pushes leave S at $FD
+    let mut c = reset_after_run(CpuType::MOS6502, ResetKind::PowerOn);
+    c.regs.s = 0;
+    c.reset(None, ResetKind::Warm).unwrap();
+    assert_eq!(c.regs.s, 0xfd);
+}
+
+#[test]
+fn flags_string_round_trip() {
+    // every P value survives flags_to_string() -> parse()
+    let mut r = Registers::new();
+    for p in 0..=0xffu8 {
+        r.p = CpuFlags::from_bits(p).unwrap();
+        let s = r.flags_to_string();
+        assert_eq!(s.parse::<CpuFlags>().unwrap(), r.p, "{}", s);
+    }
+
+    // lowercase letters are clear flags too
+    let r = Registers::with(0x01, 0x02, 0x03, 0xfd, 0x400, "Nv-bdizC").unwrap();
+    assert_eq!(r.p, CpuFlags::N | CpuFlags::C);
+    assert_eq!(r.flags_to_string(), "N------C");
+    assert_eq!("NVUBDIZC".parse::<CpuFlags>().unwrap(), CpuFlags::all());
+
+    // wrong length or letter
+    assert!("NV-BDIZ".parse::<CpuFlags>().is_err());
+    assert!("NV-BDIZCX".parse::<CpuFlags>().is_err());
+    assert!("VN-BDIZC".parse::<CpuFlags>().is_err());
+    assert!(Registers::with(0, 0, 0, 0, 0, "nv-bdiz1").is_err());
+}
+
+#[test]
+fn snapshot_and_diff() {
+    let r = Registers::with(0x01, 0x02, 0x03, 0xfd, 0x400, "nv-bdIzc").unwrap();
+    let s = r.snapshot();
+    assert_eq!(
+        s,
+        RegistersSnapshot {
+            a: 0x01,
+            x: 0x02,
+            y: 0x03,
+            s: 0xfd,
+            pc: 0x400,
+            i: true,
+            ..Default::default()
+        }
+    );
+    assert!(r.diff(&r).is_empty());
+
+    // lda #$80, sec
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0xa9, 0x80, 0x38]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let before = c.regs;
+    c.run_until(Some(&mut d), 0, 2).unwrap();
+    let changes = before.diff(&c.regs);
+    assert_eq!(
+        changes,
+        vec![
+            RegChange::A { from: 0, to: 0x80 },
+            RegChange::PC {
+                from: 0x400,
+                to: 0x403
+            },
+            RegChange::Flag {
+                flag: CpuFlags::N,
+                to: true
+            },
+            RegChange::Flag {
+                flag: CpuFlags::C,
+                to: true
+            },
+        ]
+    );
+    assert_eq!(changes[0].to_string(), "A: $00 -> $80");
+    assert_eq!(changes[3].to_string(), "C: set");
+}
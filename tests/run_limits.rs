@@ -0,0 +1,393 @@
+/*
+ * Filename: /tests/run_limits.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 16:31:18
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for run_until() budgets and stop reasons.
+
+use rv6502emu::cpu::cpu_error::CpuErrorType;
+use rv6502emu::cpu::debugger::{BreakpointType, Debugger};
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuOperation, CpuType, CpuView, HookFilter, ResetKind, RunResult,
+    StopReason, TrapReason, TrapResult,
+};
+use std::time::{Duration, Instant};
+
+/**
+ * lda #$01 (2 cycles), sta $0200 (4 cycles), nop (2 cycles) x 8 at $400.
+ */
+fn setup() -> (Cpu, Debugger) {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0xa9, 0x01, 0x8d, 0x00, 0x02])
+        .unwrap();
+    d.write_memory(&mut c, 0x405, &[0xea; 8]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    (c, d)
+}
+
+#[test]
+fn cycles_overshoot() {
+    let (mut c, mut d) = setup();
+
+    // sta straddles the budget
+    let r = c.run_until(Some(&mut d), 5, 0).unwrap();
+    assert_eq!(
+        r,
+        RunResult {
+            reason: StopReason::CyclesReached { overshoot: 1 },
+            cycles: 6,
+            instructions: 2,
+        }
+    );
+    assert_eq!(c.regs.pc, 0x405);
+
+    // carry the overshoot into the next 5 cycles slice: 4 cycles, two nops
+    let r = c.run_until(Some(&mut d), 5 - 1, 0).unwrap();
+    assert_eq!(r.reason, StopReason::CyclesReached { overshoot: 0 });
+    assert_eq!((r.cycles, r.instructions), (4, 2));
+    assert_eq!(c.regs.pc, 0x407);
+}
+
+#[test]
+fn instructions_limit() {
+    let (mut c, mut d) = setup();
+    let r = c.run_until(Some(&mut d), 0, 3).unwrap();
+    assert_eq!(
+        r,
+        RunResult {
+            reason: StopReason::InstructionsReached,
+            cycles: 8,
+            instructions: 3,
+        }
+    );
+
+    // whichever comes first
+    let r = c.run_until(Some(&mut d), 3, 3).unwrap();
+    assert_eq!(r.reason, StopReason::CyclesReached { overshoot: 1 });
+    assert_eq!(r.instructions, 2);
+}
+
+#[test]
+fn breakpoint_and_resume() {
+    let (mut c, mut d) = setup();
+    d.add_breakpoint(&mut c, 0x405, BreakpointType::EXEC, None)
+        .unwrap();
+    let r = c.run_until(Some(&mut d), 100, 0).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(0));
+    assert_eq!((r.cycles, r.instructions), (6, 2));
+    assert_eq!(c.regs.pc, 0x405);
+
+    // resuming does not trigger the same breakpoint again
+    let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+    assert_eq!(c.regs.pc, 0x406);
+
+    // write watchpoint, sta is not executed
+    let (mut c, mut d) = setup();
+    d.add_breakpoint(&mut c, 0x200, BreakpointType::WRITE, None)
+        .unwrap();
+    let r = c.run_until(Some(&mut d), 100, 0).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(0));
+    assert_eq!(c.regs.pc, 0x402);
+    let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+    assert_eq!(d.read_memory(&mut c, 0x200, 1).unwrap(), [0x01]);
+}
+
+#[test]
+fn halted() {
+    let (mut c, mut d) = setup();
+    c.set_cpu_type(CpuType::WDC65C02);
+
+    // stp
+    d.write_memory(&mut c, 0x405, &[0xdb]).unwrap();
+    let r = c.run_until(Some(&mut d), 100, 0).unwrap();
+    assert_eq!(r.reason, StopReason::Halted);
+    assert_eq!(r.instructions, 3);
+    assert_eq!(c.run(None, 100).unwrap_err().t, CpuErrorType::Halted);
+}
+
+#[test]
+fn halted_step() {
+    let (mut c, mut d) = setup();
+    c.set_cpu_type(CpuType::WDC65C02);
+
+    // stp, single stepping
+    d.write_memory(&mut c, 0x405, &[0xdb]).unwrap();
+    for _ in 0..3 {
+        let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+        assert_eq!(r.reason, StopReason::InstructionsReached);
+    }
+    assert!(c.is_halted());
+    let (pc, cycles) = (c.regs.pc, c.cycles);
+
+    // further steps are no-ops, interrupts don't wake it
+    c.set_irq_line(true);
+    c.set_nmi_line(true);
+    for _ in 0..3 {
+        let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+        assert_eq!(r.reason, StopReason::Halted);
+        assert_eq!((r.cycles, r.instructions), (0, 0));
+        assert_eq!((c.regs.pc, c.cycles), (pc, cycles));
+    }
+    let r = c.run_until(None, 0, 1).unwrap();
+    assert_eq!((r.reason, r.instructions), (StopReason::Halted, 0));
+    assert_eq!((c.regs.pc, c.cycles), (pc, cycles));
+
+    // reset recovers
+    c.set_irq_line(false);
+    c.set_nmi_line(false);
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    assert!(!c.is_halted());
+    let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+    assert_eq!((c.regs.a, c.regs.pc), (0x01, 0x402));
+}
+
+#[test]
+fn jammed() {
+    let (mut c, mut d) = setup();
+
+    // kil after lda/sta
+    d.write_memory(&mut c, 0x405, &[0x02]).unwrap();
+    let r = c.run_until(Some(&mut d), 100, 0).unwrap();
+    assert_eq!(
+        r.reason,
+        StopReason::Jammed {
+            opcode: 0x02,
+            pc: 0x405
+        }
+    );
+    assert_eq!(r.instructions, 3);
+    assert_eq!(c.regs.pc, 0x405);
+    assert!(c.is_jammed());
+
+    // further runs are no-ops, interrupts are ignored too
+    let cycles = c.cycles;
+    c.set_nmi_line(true);
+    let r = c.run_until(Some(&mut d), 0, 10).unwrap();
+    assert_eq!((r.cycles, r.instructions), (0, 0));
+    assert_eq!((c.regs.pc, c.cycles), (0x405, cycles));
+    assert!(c.run(None, 100).is_ok());
+    assert_eq!(c.regs.pc, 0x405);
+
+    // reset recovers
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    assert!(!c.is_jammed());
+    let r = c.run_until(Some(&mut d), 0, 2).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+    assert_eq!(c.regs.pc, 0x405);
+
+    // strict, the InvalidOpcode error is returned as unrecoverable
+    c.set_strict_jam(true);
+    let e = c.run_until(Some(&mut d), 0, 1).unwrap_err();
+    assert_eq!(e.t, CpuErrorType::InvalidOpcode);
+    assert_eq!(c.regs.pc, 0x405);
+    assert!(!c.is_jammed());
+}
+
+/**
+ * runs a decimal mode loop with stack usage, with or without an (unreachable) exec breakpoint forcing the checked loop.
+ */
+fn run_bcd(watched: bool) -> (Cpu, RunResult) {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+
+    // sed, clc, ldx #$00, txa, pha, adc #$19, sbc #$07, sta $10, pla, dex, bne $0404, brk
+    let prg = [
+        0xf8, 0x18, 0xa2, 0x00, 0x8a, 0x48, 0x69, 0x19, 0xe9, 0x07, 0x85, 0x10, 0x68, 0xca, 0xd0,
+        0xf4, 0x00,
+    ];
+    d.write_memory(&mut c, 0x400, &prg).unwrap();
+    if watched {
+        d.add_breakpoint(&mut c, 0xfff0, BreakpointType::EXEC, None)
+            .unwrap();
+    }
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let r = c.run_until(Some(&mut d), 0, 3 + 256 * 8).unwrap();
+    (c, r)
+}
+
+#[test]
+fn fast_path_matches() {
+    let (mut fast, r_fast) = run_bcd(false);
+    let (mut checked, r_checked) = run_bcd(true);
+    assert_eq!(r_fast, r_checked);
+    assert_eq!(r_fast.reason, StopReason::InstructionsReached);
+    assert_eq!(fast.regs, checked.regs);
+    assert_eq!(fast.regs.pc, 0x410);
+    assert_eq!(fast.cycles, checked.cycles);
+    let d = Debugger::new(false);
+    assert_eq!(
+        d.read_memory(&mut fast, 0, 0x200).unwrap(),
+        d.read_memory(&mut checked, 0, 0x200).unwrap()
+    );
+}
+
+#[test]
+fn run_cycles_frames() {
+    // lda $12f0,x (page crossing for x >= $10), sta $0200,x, inx, bne $f7, jmp $0400
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(
+        &mut c,
+        0x400,
+        &[
+            0xbd, 0xf0, 0x12, 0x9d, 0x00, 0x02, 0xe8, 0xd0, 0xf7, 0x4c, 0x00, 0x04,
+        ],
+    )
+    .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let start = c.cycles();
+
+    // 7 cycles frames, the overshoot carried into the next one
+    let mut total = 0;
+    let mut carry = 0;
+    for _ in 0..1000 {
+        let r = c.run_cycles(7 - carry).unwrap();
+        assert_eq!(
+            r.reason,
+            StopReason::CyclesReached {
+                overshoot: r.overshoot as usize
+            }
+        );
+        assert_eq!(r.cycles, 7 - carry + r.overshoot);
+        assert!(r.overshoot < 5);
+        total += r.cycles;
+        carry = r.overshoot;
+    }
+    assert_eq!(total, (c.cycles() - start) as u64);
+    assert!(total >= 7000 && total - 7000 == carry);
+
+    // nothing to run
+    let pc = c.regs.pc;
+    let r = c.run_cycles(0).unwrap();
+    assert_eq!((r.cycles, r.overshoot, c.regs.pc), (0, 0, pc));
+}
+
+/**
+ * loads prg at $400 and runs it until trapping.
+ */
+fn run_trap(t: CpuType, prg: &[u8], max_cycles: usize) -> TrapResult {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(t);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until_trap(max_cycles).unwrap()
+}
+
+#[test]
+fn trap_self_branch() {
+    // lda #$01, bne *
+    let r = run_trap(CpuType::MOS6502, &[0xa9, 0x01, 0xd0, 0xfe], 0);
+    assert_eq!(r.reason, TrapReason::SelfJump);
+    assert_eq!(r.pc, 0x402);
+    assert_eq!(r.regs.a, 0x01);
+    assert_eq!(r.cycles, 5);
+}
+
+#[test]
+fn trap_functional_success() {
+    // lda #$10, clc, adc #oper, cmp #$30, bne fail, jmp success ... fail: jmp *, success: jmp *
+    let mut prg = vec![0xea; 0x23];
+    prg[..0x0c].copy_from_slice(&[
+        0xa9, 0x10, 0x18, 0x69, 0x20, 0xc9, 0x30, 0xd0, 0x07, 0x4c, 0x20, 0x04,
+    ]);
+    prg[0x10..0x13].copy_from_slice(&[0x4c, 0x10, 0x04]);
+    prg[0x20..0x23].copy_from_slice(&[0x4c, 0x20, 0x04]);
+    let r = run_trap(CpuType::MOS6502, &prg, 0);
+    assert_eq!((r.reason, r.pc), (TrapReason::SelfJump, 0x420));
+
+    // a failing check traps at the fail label
+    prg[4] = 0x21;
+    let r = run_trap(CpuType::MOS6502, &prg, 0);
+    assert_eq!((r.reason, r.pc), (TrapReason::SelfJump, 0x410));
+}
+
+#[test]
+fn trap_wai_stp_and_limit() {
+    // wai, stp
+    let r = run_trap(CpuType::WDC65C02, &[0xcb], 0);
+    assert_eq!((r.reason, r.pc), (TrapReason::Waiting, 0x400));
+    let r = run_trap(CpuType::WDC65C02, &[0xdb], 0);
+    assert_eq!(r.reason, TrapReason::Halted);
+
+    // nop, jmp $0400 never traps
+    let r = run_trap(CpuType::MOS6502, &[0xea, 0x4c, 0x00, 0x04], 100);
+    assert_eq!(r.reason, TrapReason::CyclesReached);
+    assert!(r.cycles >= 100);
+}
+
+/**
+ * runs sta $10, jmp $0400 in realtime at 1 MHz for 100 ms, switching to the turbo clock (if any) after 50 ms.
+ */
+fn run_paced(turbo: Option<u32>) -> RunResult {
+    let mut c = Cpu::new_default(None);
+    c.bus
+        .get_memory()
+        .load_from_slice(&[0x85, 0x10, 0x4c, 0x00, 0x04], 0x400)
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let start = Instant::now();
+    c.add_hook(
+        HookFilter {
+            operations: vec![CpuOperation::Write],
+            range: Some((0x10, 0x10)),
+        },
+        move |_: &CpuCallbackContext, cpu: &mut CpuView| {
+            let elapsed = start.elapsed();
+            if elapsed >= Duration::from_millis(100) {
+                cpu.stop();
+            } else if elapsed >= Duration::from_millis(50) {
+                if let Some(hz) = turbo {
+                    cpu.set_realtime_frequency(hz);
+                }
+            }
+        },
+    );
+    c.run_realtime(1_000_000, Duration::from_millis(5), None)
+        .unwrap()
+}
+
+#[test]
+fn realtime_pacing() {
+    // 100 ms at 1 MHz, generous bounds for a loaded host
+    let r = run_paced(None);
+    assert_eq!(r.reason, StopReason::Stopped);
+    assert!(
+        r.cycles >= 50_000 && r.cycles <= 120_000,
+        "{} cycles",
+        r.cycles
+    );
+
+    // 50 ms at 1 MHz, then 50 ms unthrottled
+    let r = run_paced(Some(0));
+    assert!(r.cycles > 120_000, "{} cycles", r.cycles);
+}
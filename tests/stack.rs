@@ -0,0 +1,228 @@
+/*
+ * Filename: /tests/stack.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 14:20:31
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the stack wrapping within page one, the 65C02 stack instructions on X and Y (PHX, PHY, PLX, PLY) and
+//! the BRA operand fetch.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuFlags, CpuOperation, CpuType, CpuView, ResetKind, Vectors,
+};
+use std::sync::{Arc, Mutex};
+
+/**
+ * runs the one byte opcode at $0400 on a 65C02 with the given X, Y and P, after pushing pulled (if any).
+ *
+ * returns the cpu and the elapsed cycles.
+ */
+fn run_stack_op(opcode: u8, x: u8, y: u8, p: CpuFlags, pulled: Option<u8>) -> (Cpu, usize) {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(CpuType::WDC65C02);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[opcode]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    if let Some(b) = pulled {
+        d.write_memory(&mut c, 0x1ff, &[b]).unwrap();
+        c.regs.s = 0xfe;
+    }
+    c.regs.x = x;
+    c.regs.y = y;
+    c.regs.p = p;
+    let start = c.cycles();
+    c.run_until(None, 0, 1).unwrap();
+    let cycles = c.cycles() - start;
+    (c, cycles)
+}
+
+#[test]
+fn push_x_y() {
+    // (opcode, pushed register), flags are untouched
+    for (opcode, x, y, pushed) in [(0xda, 0x80, 0x11, 0x80), (0x5a, 0x11, 0x00, 0x00)] {
+        let p = CpuFlags::U | CpuFlags::I | CpuFlags::C;
+        let (mut c, cycles) = run_stack_op(opcode, x, y, p, None);
+        assert_eq!(c.bus.peek(0x1ff).unwrap(), pushed, "${:02x}", opcode);
+        assert_eq!(
+            (c.regs.s, c.regs.p, cycles),
+            (0xfe, p, 3),
+            "${:02x}",
+            opcode
+        );
+        assert_eq!((c.regs.x, c.regs.y), (x, y));
+    }
+}
+
+#[test]
+fn pull_x_y() {
+    // (opcode, pulled, expected N, expected Z), the other flags are untouched
+    let matrix = [
+        (0xfa, 0x00, false, true),
+        (0xfa, 0x80, true, false),
+        (0xfa, 0x42, false, false),
+        (0x7a, 0x00, false, true),
+        (0x7a, 0xff, true, false),
+        (0x7a, 0x01, false, false),
+    ];
+    for (opcode, pulled, n, z) in matrix {
+        let p = CpuFlags::U | CpuFlags::C | CpuFlags::V | CpuFlags::N | CpuFlags::Z;
+        let (c, cycles) = run_stack_op(opcode, 0x55, 0x55, p, Some(pulled));
+        let reg = if opcode == 0xfa { c.regs.x } else { c.regs.y };
+        let other = if opcode == 0xfa { c.regs.y } else { c.regs.x };
+        assert_eq!(
+            (reg, other),
+            (pulled, 0x55),
+            "${:02x} ${:02x}",
+            opcode,
+            pulled
+        );
+        assert_eq!((c.regs.s, cycles), (0xff, 4));
+        assert_eq!(
+            c.regs.p.contains(CpuFlags::N),
+            n,
+            "${:02x} ${:02x}",
+            opcode,
+            pulled
+        );
+        assert_eq!(
+            c.regs.p.contains(CpuFlags::Z),
+            z,
+            "${:02x} ${:02x}",
+            opcode,
+            pulled
+        );
+        assert!(c.regs.p.contains(CpuFlags::U | CpuFlags::C | CpuFlags::V));
+    }
+}
+
+#[test]
+fn push_pull_round_trip() {
+    // ldx #$c3, phx, ply, phy, plx
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(CpuType::WDC65C02);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0xa2, 0xc3, 0xda, 0x7a, 0x5a, 0xfa])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let start = c.cycles();
+    c.run_until(None, 0, 5).unwrap();
+    assert_eq!((c.regs.x, c.regs.y, c.regs.s), (0xc3, 0xc3, 0xff));
+    assert_eq!(c.cycles() - start, 2 + 3 + 4 + 3 + 4);
+}
+
+#[test]
+fn bra_operand_read_once() {
+    // the offset is read when executing only, not when decoding
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(CpuType::WDC65C02);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, &[0x80, 0x10]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    let reads = Arc::new(Mutex::new(Vec::new()));
+    let r = reads.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+        if ctx.operation == CpuOperation::Read {
+            r.lock().unwrap().push(ctx.address);
+        }
+    });
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!(*reads.lock().unwrap(), vec![0x401]);
+    assert_eq!(c.regs.pc, 0x412);
+}
+
+/**
+ * runs the call at $0400 (jsr $0500 or brk, returning to $0403/$0402) and its return (rts/rti) with the given S.
+ *
+ * returns S and pc after the call, the stack page, then S and pc after the return.
+ */
+fn call_and_return(call: &[u8], s: u8) -> (u8, u16, Vec<u8>, u8, u16) {
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, call).unwrap();
+    d.write_memory(&mut c, 0x500, &[0x60]).unwrap();
+    d.write_memory(&mut c, 0x600, &[0x40]).unwrap();
+    c.set_vector(Vectors::IRQ, 0x600).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.s = s;
+    c.run_until(None, 0, 1).unwrap();
+    let (s_call, pc_call) = (c.regs.s, c.regs.pc);
+    let stack = c.bus.dump_range(0x100, 0x100).unwrap();
+    c.run_until(None, 0, 1).unwrap();
+    (s_call, pc_call, stack, c.regs.s, c.regs.pc)
+}
+
+#[test]
+fn jsr_rts_wrap() {
+    // msb at $0100, lsb wraps to $01ff
+    let (s, pc, stack, s_ret, pc_ret) = call_and_return(&[0x20, 0x00, 0x05], 0x00);
+    assert_eq!((s, pc), (0xfe, 0x500));
+    assert_eq!((stack[0x00], stack[0xff]), (0x04, 0x02));
+    assert_eq!((s_ret, pc_ret), (0x00, 0x403));
+
+    // msb at $0101, lsb at $0100
+    let (s, pc, stack, s_ret, pc_ret) = call_and_return(&[0x20, 0x00, 0x05], 0x01);
+    assert_eq!((s, pc), (0xff, 0x500));
+    assert_eq!((stack[0x01], stack[0x00]), (0x04, 0x02));
+    assert_eq!((s_ret, pc_ret), (0x01, 0x403));
+}
+
+#[test]
+fn brk_rti_wrap() {
+    // msb at $0100, lsb at $01ff, P at $01fe
+    let (s, pc, stack, s_ret, pc_ret) = call_and_return(&[0x00, 0xea], 0x00);
+    assert_eq!((s, pc), (0xfd, 0x600));
+    assert_eq!((stack[0x00], stack[0xff]), (0x04, 0x02));
+    assert_eq!(stack[0xfe] & CpuFlags::B.bits(), CpuFlags::B.bits());
+    assert_eq!((s_ret, pc_ret), (0x00, 0x402));
+
+    // msb at $0101, lsb at $0100, P at $01ff
+    let (s, pc, stack, s_ret, pc_ret) = call_and_return(&[0x00, 0xea], 0x01);
+    assert_eq!((s, pc), (0xfe, 0x600));
+    assert_eq!((stack[0x01], stack[0x00]), (0x04, 0x02));
+    assert_eq!(stack[0xff] & CpuFlags::B.bits(), CpuFlags::B.bits());
+    assert_eq!((s_ret, pc_ret), (0x01, 0x402));
+}
+
+#[test]
+fn brk_at_top_of_memory() {
+    // the irq vector at $fffe is $0600, whose lsb is a brk: the return address wraps to $0000
+    let mut c = Cpu::new_default(None);
+    let d = Debugger::new(false);
+    d.write_memory(&mut c, 0x600, &[0x40]).unwrap();
+    c.set_vector(Vectors::IRQ, 0x600).unwrap();
+    c.set_address_wrapping(true);
+    c.reset(Some(0xfffe), ResetKind::PowerOn).unwrap();
+    c.regs.s = 0xff;
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!((c.regs.s, c.regs.pc), (0xfc, 0x600));
+    assert_eq!(c.bus.dump_range(0x1fe, 2).unwrap(), [0x00, 0x00]);
+
+    // rti
+    c.run_until(None, 0, 1).unwrap();
+    assert_eq!((c.regs.s, c.regs.pc), (0xff, 0x0000));
+}
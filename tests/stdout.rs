@@ -0,0 +1,126 @@
+/*
+ * Filename: /tests/stdout.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 23:48:17
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! without a debugger attached, the library messages go to the log and nothing is printed on stdout.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use rv6502emu::cpu::{Cpu, CpuType, ResetKind};
+use std::env;
+use std::process::Command;
+use std::sync::Mutex;
+
+/**
+ * a logger keeping the messages.
+ */
+struct Capture(Mutex<Vec<String>>);
+
+impl Log for Capture {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+/**
+ * creates a cpu, changes its type, loads a file, triggers an irq and runs into an error, all without a debugger.
+ */
+fn chatty() {
+    let mut c = Cpu::new_default(None);
+    c.set_cpu_type(CpuType::WDC65C02);
+    let path = env::temp_dir().join(format!("rv6502emu_{}_stdout.bin", std::process::id()));
+    std::fs::write(&path, [0x8d, 0x00, 0xc0]).unwrap();
+    c.bus
+        .get_memory()
+        .load(path.to_str().unwrap(), 0x400)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.irq(None).unwrap();
+
+    // sta $c000 in rom
+    c.bus.get_memory().set_rom_region(0xc000, 0x100, true);
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run_until(None, 0, 1).unwrap_err();
+}
+
+#[test]
+fn logged() {
+    log::set_logger(&CAPTURE).unwrap();
+    log::set_max_level(LevelFilter::Info);
+    chatty();
+    let lines = CAPTURE.0.lock().unwrap().clone();
+    for msg in [
+        "created new cpu, type=MOS6502",
+        "setting cpu type to WDC65C02.",
+        "correctly loaded at $0400 !",
+        "triggering irq !",
+    ] {
+        assert!(
+            lines.iter().any(|l| l.contains(msg)),
+            "{} not logged in {:?}",
+            msg,
+            lines
+        );
+    }
+}
+
+#[test]
+fn nothing_on_stdout() {
+    if env::var("RV6502EMU_STDOUT_CHILD").is_ok() {
+        println!("<begin>");
+        chatty();
+        println!("<end>");
+        return;
+    }
+
+    // run this test again in a child process, without capturing its output
+    let out = Command::new(env::current_exe().unwrap())
+        .args([
+            "nothing_on_stdout",
+            "--exact",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env("RV6502EMU_STDOUT_CHILD", "1")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let begin = stdout.find("<begin>\n").unwrap() + "<begin>\n".len();
+    let end = stdout.find("<end>").unwrap();
+    assert_eq!(&stdout[begin..end], "");
+}
@@ -0,0 +1,154 @@
+/*
+ * Filename: /tests/traps.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 23:40:12
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the cpu traps.
+
+use rv6502emu::cpu::debugger::{BreakpointType, Debugger};
+use rv6502emu::cpu::{
+    Cpu, CpuCallbackContext, CpuOperation, CpuView, ResetKind, StopReason, TrapAction,
+};
+use std::sync::{Arc, Mutex};
+
+/**
+ * prints the zero terminated string at $0500 through jsr $ffd2, stores the length at $0600 and jams at $0410.
+ *
+ * the routine at $ffd2 is a jam too, unless given.
+ */
+fn setup(routine: u8) -> Cpu {
+    let mut c = Cpu::new_default(None);
+    let prg = [
+        0xa2, 0x00, // ldx #0
+        0xbd, 0x00, 0x05, // loop: lda $0500,x
+        0xf0, 0x06, // beq done
+        0x20, 0xd2, 0xff, // jsr $ffd2
+        0xe8, // inx
+        0xd0, 0xf5, // bne loop
+        0x8e, 0x00, 0x06, // done: stx $0600
+        0x02, // jam
+    ];
+    let m = c.bus.get_memory();
+    m.load_from_slice(&prg, 0x400).unwrap();
+    m.load_from_slice(b"HELLO\0", 0x500).unwrap();
+    m.write_byte(0xffd2, routine).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c
+}
+
+#[test]
+fn trap_services_call() {
+    let mut c = setup(0x02);
+    let out: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+    let o = out.clone();
+    c.install_trap(0xffd2, move |cpu: &mut CpuView| {
+        o.lock().unwrap().push(cpu.regs().a);
+        TrapAction::Return
+    });
+    c.set_trap_cycles(10);
+
+    // ldx + 6 * (lda, beq) + 5 * (jsr, trap, inx, bne) + stx, stops on the jam
+    let r = c.run_until(None, 0, 1 + 6 * 2 + 5 * 4 + 1).unwrap();
+    assert_eq!(r.reason, StopReason::InstructionsReached);
+    assert_eq!(c.regs.pc, 0x410);
+    assert_eq!(r.cycles, 2 + 6 * 4 + 5 * 2 + 3 + 5 * (6 + 10 + 2 + 3) + 4);
+
+    // the routine has never been executed, the program went on after each jsr
+    assert_eq!(*out.lock().unwrap(), b"HELLO".to_vec());
+    assert_eq!(c.bus.get_memory().read_byte(0x600).unwrap(), 5);
+    assert_eq!(c.regs.s, 0xff);
+}
+
+#[test]
+fn trap_continue_executes_routine() {
+    // the routine is a plain rts, run through the checked loop (a hook is installed)
+    let mut c = setup(0x60);
+    c.set_hook(|_: &CpuCallbackContext, _: &mut CpuView| ());
+    let calls = Arc::new(Mutex::new(0));
+    let n = calls.clone();
+    c.install_trap(0xffd2, move |_: &mut CpuView| {
+        *n.lock().unwrap() += 1;
+        TrapAction::Continue
+    });
+    c.run(None, 0).unwrap();
+    assert_eq!(*calls.lock().unwrap(), 5);
+    assert_eq!(c.bus.get_memory().read_byte(0x600).unwrap(), 5);
+    assert!(c.is_jammed());
+
+    // once removed, the trap is not called anymore
+    c.remove_trap(0xffd2).unwrap();
+    assert!(c.remove_trap(0xffd2).is_err());
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.run(None, 0).unwrap();
+    assert_eq!(*calls.lock().unwrap(), 5);
+}
+
+/**
+ * runs up to the first jsr $ffd2 and then the next instruction (the trap or the routine), with coverage on, returns the
+ * stack reads seen by the hook as (address, access size) and the pc.
+ */
+fn stack_reads_after_jsr(c: &mut Cpu) -> (Vec<(u16, i8)>, u16) {
+    let reads = Arc::new(Mutex::new(Vec::new()));
+    let r = reads.clone();
+    c.set_hook(move |ctx: &CpuCallbackContext, _: &mut CpuView| {
+        if ctx.operation == CpuOperation::Read && (0x100..0x200).contains(&ctx.address) {
+            r.lock().unwrap().push((ctx.address, ctx.access_size));
+        }
+    });
+    c.enable_coverage(true);
+
+    // ldx, lda, beq, jsr
+    c.run_until(None, 0, 4).unwrap();
+    assert_eq!(c.regs.pc, 0xffd2);
+    c.run_until(None, 0, 1).unwrap();
+    let v = reads.lock().unwrap().clone();
+    (v, c.regs.pc)
+}
+
+#[test]
+fn trap_return_as_rts() {
+    // a plain rts
+    let mut c = setup(0x60);
+    let rts = stack_reads_after_jsr(&mut c);
+    assert_eq!(rts, (vec![(0x1fe, 2)], 0x40a));
+
+    // the trap returning pops the same way, hooks and coverage included
+    let mut c = setup(0x02);
+    c.install_trap(0xffd2, |_: &mut CpuView| TrapAction::Return);
+    assert_eq!(stack_reads_after_jsr(&mut c), rts);
+    assert!(c.coverage().is_read(0x1fe) && c.coverage().is_read(0x1ff));
+
+    // and triggers the read breakpoints on the stack
+    let mut c = setup(0x02);
+    c.install_trap(0xffd2, |_: &mut CpuView| TrapAction::Return);
+    let mut d = Debugger::new(false);
+    d.add_breakpoint(&mut c, 0x1fe, BreakpointType::READ, None)
+        .unwrap();
+    let r = c.run_until(Some(&mut d), 0, 5).unwrap();
+    assert_eq!(r.reason, StopReason::Breakpoint(0));
+}
@@ -0,0 +1,388 @@
+/*
+ * Filename: /tests/undocumented.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 17:02:41
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the undocumented opcodes.
+
+use rv6502emu::cpu::debugger::Debugger;
+use rv6502emu::cpu::{opcode_info, Cpu, CpuFlags, CpuType, ResetKind};
+
+/**
+ * runs a single instruction at $400 with the given registers, returns the cpu.
+ */
+fn run_one(prg: &[u8], a: u8, x: u8, y: u8) -> (Cpu, Debugger) {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    d.write_memory(&mut c, 0x400, prg).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.a = a;
+    c.regs.x = x;
+    c.regs.y = y;
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    (c, d)
+}
+
+fn peek(c: &mut Cpu, d: &Debugger, address: u16) -> u8 {
+    d.read_memory(c, address, 1).unwrap()[0]
+}
+
+#[test]
+fn shx() {
+    // shx $1200,y: X & ($12 + 1) -> $1201
+    let (mut c, d) = run_one(&[0x9e, 0x00, 0x12], 0, 0xff, 0x01);
+    assert_eq!(peek(&mut c, &d, 0x1201), 0x13);
+
+    // shx $12ff,y: page cross, X & $13 = $03 -> ($03 << 8) | $00
+    let (mut c, d) = run_one(&[0x9e, 0xff, 0x12], 0, 0x0f, 0x01);
+    assert_eq!(peek(&mut c, &d, 0x0300), 0x03);
+    assert_eq!(peek(&mut c, &d, 0x1300), 0x00);
+}
+
+#[test]
+fn shy() {
+    // shy $1200,x: Y & ($12 + 1) -> $1205
+    let (mut c, d) = run_one(&[0x9c, 0x00, 0x12], 0, 0x05, 0xff);
+    assert_eq!(peek(&mut c, &d, 0x1205), 0x13);
+
+    // shy $12f0,x: page cross, Y & $13 = $12 -> $1210
+    let (mut c, d) = run_one(&[0x9c, 0xf0, 0x12], 0, 0x20, 0xf6);
+    assert_eq!(peek(&mut c, &d, 0x1210), 0x12);
+    assert_eq!(peek(&mut c, &d, 0x1310), 0x00);
+}
+
+#[test]
+fn ahx() {
+    // ahx $3400,y: A & X & ($34 + 1) -> $3402
+    let (mut c, d) = run_one(&[0x9f, 0x00, 0x34], 0xf7, 0x3f, 0x02);
+    assert_eq!(peek(&mut c, &d, 0x3402), 0x35);
+
+    // ahx ($10),y with ($10) = $34ff: page cross, A & X & $35 = $15 -> $1501
+    let (mut c, mut d) = run_one(&[0xea], 0, 0, 0);
+    d.write_memory(&mut c, 0x10, &[0xff, 0x34]).unwrap();
+    d.write_memory(&mut c, 0x400, &[0x93, 0x10]).unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.a = 0x1f;
+    c.regs.x = 0xf5;
+    c.regs.y = 0x02;
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!(peek(&mut c, &d, 0x1501), 0x15);
+    assert_eq!(peek(&mut c, &d, 0x3501), 0x00);
+}
+
+#[test]
+fn tas() {
+    // tas $5600,y: S = A & X, S & ($56 + 1) -> $5610
+    let (mut c, d) = run_one(&[0x9b, 0x00, 0x56], 0xf3, 0x7e, 0x10);
+    assert_eq!(c.regs.s, 0x72);
+    assert_eq!(peek(&mut c, &d, 0x5610), 0x52);
+
+    // tas $56ff,y: page cross, S & $57 = $52 -> $5200
+    let (mut c, d) = run_one(&[0x9b, 0xff, 0x56], 0xf3, 0x7e, 0x01);
+    assert_eq!(c.regs.s, 0x72);
+    assert_eq!(peek(&mut c, &d, 0x5200), 0x52);
+}
+
+#[test]
+fn drop_and() {
+    // the 'AND (H+1)' is dropped, the page cross still corrupts the address
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    c.set_unstable_sh_drop_and(true);
+    d.write_memory(&mut c, 0x400, &[0x9e, 0x00, 0x12, 0x9e, 0xff, 0x12])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.x = 0x21;
+    c.regs.y = 0x01;
+    c.run_until(Some(&mut d), 0, 2).unwrap();
+    assert_eq!(peek(&mut c, &d, 0x1201), 0x21);
+    assert_eq!(peek(&mut c, &d, 0x2100), 0x21);
+}
+
+/**
+ * reference ARR in binary mode, returns (A, N, Z, C, V).
+ */
+fn arr_reference(a: u8, oper: u8, carry: bool) -> (u8, bool, bool, bool, bool) {
+    let t = a & oper;
+    let res = (t >> 1) | if carry { 0x80 } else { 0 };
+    let bit6 = res & 0x40 != 0;
+    let bit5 = res & 0x20 != 0;
+    (res, res & 0x80 != 0, res == 0, bit6, bit6 != bit5)
+}
+
+#[test]
+fn arr_binary() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    for carry in [false, true] {
+        for oper in [0xff, 0xaa, 0x55, 0x60, 0x00] {
+            for a in 0..=0xffu8 {
+                // arr #oper
+                d.write_memory(&mut c, 0x400, &[0x6b, oper]).unwrap();
+                c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+                c.regs.a = a;
+                c.regs.p.set(CpuFlags::C, carry);
+                c.run_until(Some(&mut d), 0, 1).unwrap();
+
+                let (res, n, z, cf, v) = arr_reference(a, oper, carry);
+                let p = c.regs.p;
+                assert_eq!(
+                    (
+                        c.regs.a,
+                        p.contains(CpuFlags::N),
+                        p.contains(CpuFlags::Z),
+                        p.contains(CpuFlags::C),
+                        p.contains(CpuFlags::V)
+                    ),
+                    (res, n, z, cf, v),
+                    "A=${:02x}, oper=${:02x}, C={}",
+                    a,
+                    oper,
+                    carry
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn combined_flags() {
+    // (opcode, A, M, P in, A out, M out, P out): M is the immediate for ALR, else the byte at $10 (zeropage)
+    let cases: [(u8, u8, u8, u8, u8, u8, u8); 27] = [
+        // alr #oper
+        (0x4b, 0xff, 0x03, 0x20, 0x01, 0x03, 0x21),
+        (0x4b, 0x81, 0x80, 0xa1, 0x40, 0x80, 0x20),
+        (0x4b, 0x01, 0x01, 0x60, 0x00, 0x01, 0x63),
+        // dcp oper
+        (0xc7, 0x10, 0x11, 0x20, 0x10, 0x10, 0x23),
+        (0xc7, 0x10, 0x00, 0x21, 0x10, 0xff, 0x20),
+        (0xc7, 0x80, 0x02, 0xe2, 0x80, 0x01, 0x61),
+        // isc oper
+        (0xe7, 0x10, 0x0f, 0x21, 0x00, 0x10, 0x23),
+        (0xe7, 0x50, 0xaf, 0x21, 0xa0, 0xb0, 0xe0),
+        (0xe7, 0x00, 0xff, 0x22, 0xff, 0x00, 0xa0),
+        (0xe7, 0x20, 0x08, 0x29, 0x11, 0x09, 0x29),
+        // rla oper
+        (0x27, 0xff, 0x80, 0x20, 0x00, 0x00, 0x23),
+        (0x27, 0xff, 0x40, 0x21, 0x81, 0x81, 0xa0),
+        (0x27, 0x0f, 0xc3, 0x62, 0x06, 0x86, 0x61),
+        // rra oper
+        (0x67, 0x00, 0x02, 0x22, 0x01, 0x01, 0x20),
+        (0x67, 0xff, 0x01, 0x20, 0x00, 0x00, 0x23),
+        (0x67, 0x7f, 0x03, 0x20, 0x81, 0x01, 0xe0),
+        (0x67, 0x15, 0x52, 0x28, 0x44, 0x29, 0x28),
+        // slo oper
+        (0x07, 0x00, 0x80, 0x20, 0x00, 0x00, 0x23),
+        (0x07, 0x01, 0x40, 0x23, 0x81, 0x80, 0xa0),
+        (0x07, 0x10, 0xc1, 0x60, 0x92, 0x82, 0xe1),
+        // sre oper
+        (0x47, 0x00, 0x01, 0x20, 0x00, 0x00, 0x23),
+        (0x47, 0x80, 0x02, 0x21, 0x81, 0x01, 0xa0),
+        (0x47, 0x40, 0x81, 0x60, 0x00, 0x40, 0x63),
+        // the flags not involved are left alone
+        (0xc7, 0x10, 0x11, 0x2c, 0x10, 0x10, 0x2f),
+        (0x27, 0x03, 0x01, 0x6c, 0x02, 0x02, 0x6c),
+        (0x07, 0x01, 0x01, 0x6c, 0x03, 0x02, 0x6c),
+        (0x47, 0x01, 0x01, 0x64, 0x01, 0x00, 0x65),
+    ];
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    for (opcode, a, m, p_in, a_out, m_out, p_out) in cases {
+        let operand = if opcode == 0x4b { m } else { 0x10 };
+        d.write_memory(&mut c, 0x10, &[m]).unwrap();
+        d.write_memory(&mut c, 0x400, &[opcode, operand]).unwrap();
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        c.regs.a = a;
+        c.regs.p = CpuFlags::from_bits(p_in).unwrap();
+        c.run_until(Some(&mut d), 0, 1).unwrap();
+        assert_eq!(
+            (c.regs.a, peek(&mut c, &d, 0x10), c.regs.p.bits()),
+            (a_out, m_out, p_out),
+            "opcode=${:02x}, A=${:02x}, M=${:02x}, P=${:02x}",
+            opcode,
+            a,
+            m,
+            p_in
+        );
+    }
+}
+
+#[test]
+fn nop_65c02() {
+    // (opcodes, bytes, cycles) for the 65C02 reserved opcodes
+    let mut reserved: Vec<(Vec<u8>, u8, usize)> = vec![
+        (vec![0x02, 0x22, 0x42, 0x62, 0x82, 0xc2, 0xe2], 2, 2),
+        (vec![0x44], 2, 3),
+        (vec![0x54, 0xd4, 0xf4], 2, 4),
+        (vec![0x5c], 3, 8),
+        (vec![0xdc, 0xfc], 3, 4),
+    ];
+    let one_byte: Vec<u8> = (0..16u8)
+        .flat_map(|h| [(h << 4) | 0x3, (h << 4) | 0xb])
+        .filter(|op| *op != 0xcb && *op != 0xdb)
+        .collect();
+    reserved.push((one_byte, 1, 1));
+
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    c.set_cpu_type(CpuType::WDC65C02);
+    for (ops, bytes, cycles) in reserved.iter() {
+        for op in ops.iter() {
+            let info = opcode_info(*op, CpuType::WDC65C02);
+            assert_eq!(info.mnemonic, "nop", "${:02x}", op);
+            assert_eq!(
+                (
+                    info.bytes,
+                    info.base_cycles as usize,
+                    info.page_cross_penalty
+                ),
+                (*bytes, *cycles, false),
+                "${:02x}",
+                op
+            );
+
+            // operands crossing a page do not add cycles
+            d.write_memory(&mut c, 0x400, &[*op, 0xff, 0x12]).unwrap();
+            c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+            c.regs.x = 0x10;
+            let r = c.run_until(Some(&mut d), 0, 1).unwrap();
+            assert_eq!(
+                (c.regs.pc, r.cycles),
+                (0x400 + *bytes as u16, *cycles),
+                "${:02x}",
+                op
+            );
+        }
+    }
+}
+
+#[test]
+fn sbx() {
+    // (A, X, imm, P in, X out, P out): X = (A & X) - imm, flags as CMP, V and D ignored
+    let cases: [(u8, u8, u8, u8, u8, u8); 6] = [
+        // (A & X) < imm, C clear even if A >= imm
+        (0xf0, 0x0f, 0x05, 0x21, 0xfb, 0xa0),
+        (0xff, 0x3c, 0x10, 0x20, 0x2c, 0x21),
+        (0xff, 0x10, 0x10, 0x20, 0x00, 0x23),
+        (0x7f, 0xff, 0xff, 0x21, 0x80, 0xa0),
+        // binary even with D set, V left alone
+        (0x10, 0xff, 0x01, 0x68, 0x0f, 0x69),
+        (0x80, 0xff, 0x01, 0x20, 0x7f, 0x21),
+    ];
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    for (a, x, imm, p_in, x_out, p_out) in cases {
+        d.write_memory(&mut c, 0x400, &[0xcb, imm]).unwrap();
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        c.regs.a = a;
+        c.regs.x = x;
+        c.regs.p = CpuFlags::from_bits(p_in).unwrap();
+        c.run_until(Some(&mut d), 0, 1).unwrap();
+        assert_eq!(
+            (c.regs.a, c.regs.x, c.regs.p.bits()),
+            (a, x_out, p_out),
+            "A=${:02x}, X=${:02x}, imm=${:02x}, P=${:02x}",
+            a,
+            x,
+            imm,
+            p_in
+        );
+    }
+}
+
+#[test]
+fn lxa() {
+    // lxa #$55 with A=$00: (A | $ee) & $55 = $44 in both A and X, plain LAX would load $55
+    let (c, _) = run_one(&[0xab, 0x55], 0x00, 0x12, 0);
+    assert_eq!((c.regs.a, c.regs.x), (0x44, 0x44));
+    assert!(!c.regs.zero() && !c.regs.negative());
+
+    // with a custom constant, (A | $00) & $55
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    c.set_unstable_magic(0xef, 0x00);
+    d.write_memory(&mut c, 0x400, &[0xab, 0x55, 0xab, 0xf0])
+        .unwrap();
+    c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+    c.regs.a = 0x0f;
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!((c.regs.a, c.regs.x), (0x05, 0x05));
+
+    // (A=$05 | $00) & $f0, zero
+    c.run_until(Some(&mut d), 0, 1).unwrap();
+    assert_eq!((c.regs.a, c.regs.x), (0x00, 0x00));
+    assert!(c.regs.zero());
+}
+
+#[test]
+fn unstable_magic() {
+    let mut c = Cpu::new_default(None);
+    let mut d = Debugger::new(false);
+    assert_eq!(c.get_unstable_magic(), (0xef, 0xee));
+    assert!(!c.get_unstable_sh_drop_and());
+
+    // xaa #$ff and lxa #$ff with A=$00, X=$ff: the result is the constant
+    for (ane, lxa) in [(0xef, 0xee), (0x00, 0xff), (0xff, 0x00), (0x5a, 0xa5)] {
+        c.set_unstable_magic(ane, lxa);
+        assert_eq!(c.get_unstable_magic(), (ane, lxa));
+        d.write_memory(&mut c, 0x400, &[0x8b, 0xff, 0xab, 0xff])
+            .unwrap();
+        c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+        c.regs.a = 0x00;
+        c.regs.x = 0xff;
+        c.run_until(Some(&mut d), 0, 1).unwrap();
+        assert_eq!(c.regs.a, ane);
+        c.regs.a = 0x00;
+        c.run_until(Some(&mut d), 0, 1).unwrap();
+        assert_eq!((c.regs.a, c.regs.x), (lxa, lxa));
+    }
+
+    // the sh* 'AND (H+1)', stable and dropped: shy $1200,x (Y=$ff), ahx $1200,y and tas $1200,y (A=X=$ff) -> $1201
+    for drop in [false, true] {
+        c.set_unstable_sh_drop_and(drop);
+        assert_eq!(c.get_unstable_sh_drop_and(), drop);
+        let expected = if drop { 0xff } else { 0x13 };
+        for (opcode, x, y) in [(0x9c, 0x01, 0xff), (0x9f, 0xff, 0x01), (0x9b, 0xff, 0x01)] {
+            d.write_memory(&mut c, 0x1201, &[0]).unwrap();
+            d.write_memory(&mut c, 0x400, &[opcode, 0x00, 0x12])
+                .unwrap();
+            c.reset(Some(0x400), ResetKind::PowerOn).unwrap();
+            c.regs.a = 0xff;
+            c.regs.x = x;
+            c.regs.y = y;
+            c.run_until(Some(&mut d), 0, 1).unwrap();
+            assert_eq!(
+                peek(&mut c, &d, 0x1201),
+                expected,
+                "${:02x}, drop={}",
+                opcode,
+                drop
+            );
+        }
+    }
+}
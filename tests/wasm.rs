@@ -0,0 +1,54 @@
+/*
+ * Filename: /tests/wasm.rs
+ * Project: rv6502emu
+ * Created Date: 2026-10-16, 10:41:07
+ * Author: valerino <xoanino@gmail.com>
+ * Copyright (c) 2021 valerino
+ *
+ * MIT License
+ *
+ * Copyright (c) 2021 valerino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+ * of the Software, and to permit persons to whom the Software is furnished to do
+ * so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! tests for the wasm façade, natively (needs the wasm feature). step() builds a JsValue, so it can only run on wasm32.
+
+use rv6502emu::wasm::WasmCpu;
+
+#[test]
+fn wasm_cpu_memory_and_disassembly() {
+    let mut c = WasmCpu::new(0x1000);
+    // lda #$42, sta $10
+    c.load(&[0xa9, 0x42, 0x85, 0x10], 0x200).unwrap();
+    c.reset(Some(0x200)).unwrap();
+    let r = c.registers();
+    assert_eq!((r.pc, r.s), (0x200, 0xff));
+
+    assert_eq!(
+        c.disassemble(0x200, 2).unwrap(),
+        vec![
+            String::from("$0200: LDA #$42"),
+            String::from("$0202: STA $10")
+        ]
+    );
+
+    c.write_mem(0xffe, &[1, 2]).unwrap();
+    assert_eq!(c.read_mem(0xffd, 3).unwrap(), vec![0, 1, 2]);
+}